@@ -0,0 +1,50 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+//! Parses a DA file and, optionally, a GPT image without any device.
+//! Only needs the `parsing` feature:
+//!
+//! ```sh
+//! cargo run -p penumbra --example parse_offline --no-default-features --features parsing -- \
+//!     MTK_AllInOne_DA.bin [pgpt.bin] [emmc|ufs]
+//! ```
+use std::env;
+use std::fs;
+
+use penumbra::api::{DAFile, Gpt, StorageType};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(da_path) = args.first() else {
+        eprintln!("Usage: parse_offline <DA_FILE> [GPT_IMAGE] [emmc|ufs]");
+        std::process::exit(1);
+    };
+
+    let da_file = DAFile::parse_da(&fs::read(da_path)?)?;
+    println!("DA file: {} ({:?}, {} entries)", da_path, da_file.da_type, da_file.das.len());
+    for da in &da_file.das {
+        println!("  hw_code 0x{:04X}, {} regions", da.hw_code, da.regions.len());
+        for region in &da.regions {
+            println!(
+                "    0x{:08X}: 0x{:X} bytes at 0x{:X} (signature 0x{:X})",
+                region.addr, region.length, region.offset, region.sig_len
+            );
+        }
+    }
+
+    if let Some(gpt_path) = args.get(1) {
+        let storage_type = match args.get(2).map(String::as_str) {
+            Some("ufs") => StorageType::Ufs,
+            _ => StorageType::Emmc,
+        };
+
+        let gpt = Gpt::parse(&fs::read(gpt_path)?, storage_type)?;
+        println!("GPT: {}", gpt_path);
+        for part in gpt.partitions() {
+            println!("  {:<24} 0x{:010X} 0x{:X} bytes", part.name, part.address, part.size);
+        }
+    }
+
+    Ok(())
+}
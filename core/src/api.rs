@@ -0,0 +1,134 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! The public API of Penumbra.
+//!
+//! Frontends should import from here rather than from the modules the items are defined in,
+//! which get reorganized as the crate grows. Everything re-exported here only changes with
+//! a new major version. The most used items are re-exported at the crate root too.
+//!
+//! ```rust,ignore
+//! use penumbra::api::{Device, DeviceBuilder, Progress, find_mtk_port};
+//! ```
+#[cfg(feature = "usb")]
+pub use crate::connection::{DEFAULT_RESPONSE_TIMEOUT, REENUMERATION_TIMEOUT};
+#[cfg(feature = "tcp")]
+pub use crate::connection::TcpMTKPort;
+#[cfg(feature = "tcp")]
+pub use crate::connection::bridge::{BRIDGE_ENV, DEFAULT_BRIDGE_PORT, serve_bridge};
+#[cfg(feature = "usb")]
+pub use crate::connection::cancel::CancelToken;
+#[cfg(feature = "usb")]
+pub use crate::connection::phases::{FlashPhase, PhaseStats, PhaseTiming, PhaseTracker};
+#[cfg(feature = "usb")]
+pub use crate::connection::port::{
+    ConnectionType,
+    MTKPort,
+    PortInfo,
+    UsbLink,
+    UsbSpeed,
+    find_mtk_port,
+    list_mtk_ports,
+    open_mtk_port,
+    probe_mtk_port,
+    register_port,
+};
+#[cfg(feature = "usb")]
+pub use crate::connection::retry::RetryPolicy;
+#[cfg(feature = "usb")]
+pub use crate::connection::stats::{ConnStats, StatsSnapshot};
+#[cfg(feature = "usb")]
+pub use crate::connection::transfer::TransferConfig;
+#[cfg(feature = "usb")]
+pub use crate::connection::watch::{PortEvent, PortWatch, watch_mtk_ports};
+#[cfg(not(feature = "no_localslakeyring"))]
+pub use crate::core::auth::local_keyring::LocalKeyring;
+#[cfg(feature = "usb")]
+pub use crate::core::auth::ExecSigner;
+pub use crate::core::auth::{
+    AuthManager,
+    BUILTIN_SIGNER_PRIORITY,
+    SignData,
+    SignPurpose,
+    SignRequest,
+    Signer,
+};
+#[cfg(feature = "da-protocols")]
+pub use crate::core::devinfo::{DevInfoData, TargetConfig};
+pub use crate::core::emi::{extract_emi, validate_emi};
+pub use crate::core::events::{CoreEvent, EventReceiver, EventSender, event_channel};
+#[cfg(feature = "da-protocols")]
+pub use crate::core::journal::{Journal, JournalEntry, JournalState};
+#[cfg(feature = "da-protocols")]
+pub use crate::core::progress::{Progress, ProgressFn, ProgressTracker, from_bytes_fn};
+pub use crate::core::scatter::{ScatterEntry, ScatterFile};
+pub use crate::core::seccfg::{LockFlag, SecCfg, SecCfgV3, SecCfgV4};
+#[cfg(feature = "da-protocols")]
+pub use crate::core::storage::sparse::SparseHeader;
+pub use crate::core::storage::{
+    EmmcPartition,
+    EraseMethod,
+    EraseReport,
+    Gpt,
+    GptDiff,
+    GptEntryDiff,
+    GptReport,
+    GptSource,
+    Partition,
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageType,
+    UfsPartition,
+    is_protected_part,
+};
+pub use crate::da::{DAFile, DaChipEntry};
+#[cfg(feature = "da-protocols")]
+pub use crate::da::ExtState;
+#[cfg(feature = "da-protocols")]
+pub use crate::da::protocol::BootMode;
+#[cfg(feature = "da-protocols")]
+pub use crate::device::{Device, DeviceBuilder, PortSelector};
+pub use crate::error::{
+    Domain,
+    Error,
+    Result,
+    Severity,
+    StatusDescription,
+    XFlashError,
+    XFlashErrorKind,
+    XmlError,
+    XmlErrorKind,
+    describe_status,
+};
+#[cfg(feature = "usb")]
+pub use crate::utilities::hash::HashingWriter;
+pub use crate::utilities::hash::sha256;
+
+/// Raw access to the DA protocols, for tools sending their own commands.
+///
+/// These follow the protocols, so they are less stable than the rest of the API.
+#[cfg(feature = "da-protocols")]
+pub mod protocol {
+    pub use crate::da::xflash::Cmd as XFlashCmd;
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub use crate::da::xflash::exts::read_rpmb;
+    pub use crate::da::xflash::flash::set_rsc_info;
+    pub use crate::da::xml::XmlCmdLifetime;
+    pub use crate::da::{DAProtocol, XFlash, Xml};
+}
+
+/// Capture of the traffic with the device, enabled with [`DeviceBuilder::with_trace`].
+#[cfg(feature = "usb")]
+pub mod trace {
+    pub use crate::connection::trace::{
+        DEFAULT_TRACE_LIMIT,
+        TRACE_MAGIC,
+        TraceKind,
+        TraceRecord,
+        dump,
+        read_trace,
+    };
+}
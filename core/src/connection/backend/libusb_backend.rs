@@ -6,12 +6,19 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use log::{debug, error, info};
-use rusb::{Context, Device, DeviceHandle, Direction, Recipient, RequestType, UsbContext};
+use rusb::{Context, Device, DeviceHandle, Direction, Recipient, RequestType, Speed, UsbContext};
 use tokio::sync::Mutex;
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 
-use crate::connection::port::{ConnectionType, KNOWN_PORTS, MTKPort};
+use crate::connection::port::{
+    ConnectionType,
+    MTKPort,
+    UsbLink,
+    UsbSpeed,
+    port_connection_type,
+};
+use crate::connection::retry::cut_short;
 use crate::error::{Error, Result};
 
 #[derive(Debug, Clone)]
@@ -23,6 +30,7 @@ pub struct UsbMTKPort {
     port_name: String,
     in_endpoint: u8,
     out_endpoint: u8,
+    usb_link: UsbLink,
 }
 
 impl UsbMTKPort {
@@ -33,6 +41,7 @@ impl UsbMTKPort {
         baudrate: u32,
         in_endpoint: u8,
         out_endpoint: u8,
+        usb_link: UsbLink,
     ) -> Self {
         Self {
             handle: Arc::new(Mutex::new(handle)),
@@ -42,6 +51,7 @@ impl UsbMTKPort {
             port_name,
             in_endpoint,
             out_endpoint,
+            usb_link,
         }
     }
 
@@ -122,10 +132,7 @@ impl UsbMTKPort {
         let descriptor = device.device_descriptor().ok()?;
         let (vid, pid) = (descriptor.vendor_id(), descriptor.product_id());
 
-        let connection_type = KNOWN_PORTS
-            .iter()
-            .find(|&&(kvid, kpid, _)| kvid == vid && kpid == pid)
-            .map(|&(_, _, ct)| ct)?;
+        let connection_type = port_connection_type(vid, pid)?;
 
         let baudrate = match connection_type {
             ConnectionType::Brom => 115_200,
@@ -146,6 +153,7 @@ impl UsbMTKPort {
             baudrate,
             in_endpoint,
             out_endpoint,
+            usb_link(&device),
         ))
     }
 }
@@ -260,14 +268,18 @@ impl MTKPort for UsbMTKPort {
                     match locked.read_bulk(endpoint, &mut temp_buf, timeout) {
                         Ok(n) => Ok((temp_buf, n)),
                         Err(rusb::Error::Timeout) => Err(Error::io("USB timeout")),
-                        Err(e) => Err(Error::io(e.to_string())),
+                        Err(e) => Err(e.into()),
                     }
                 }
             })
             .await
-            .unwrap()?;
+            .unwrap();
 
-            let (temp_buf, n) = result;
+            let (temp_buf, n) = match result {
+                Ok(result) => result,
+                Err(e) if total_read > 0 => return Err(cut_short(e)),
+                Err(e) => return Err(e),
+            };
             if n == 0 {
                 continue;
             }
@@ -325,15 +337,26 @@ impl MTKPort for UsbMTKPort {
         let handle = self.handle.clone();
         let endpoint = self.out_endpoint;
         let timeout = Duration::from_millis(5000);
-        let data = buf.to_vec();
 
-        spawn_blocking(move || {
-            let locked = handle.blocking_lock();
-            let res = locked.write_bulk(endpoint, &data, timeout);
-            res.map_err(|_| Error::io("Bulk write failed"))
-        })
-        .await
-        .unwrap()?;
+        let mut total_written = 0;
+        while total_written < buf.len() {
+            let data = buf[total_written..].to_vec();
+            let result = spawn_blocking({
+                let handle = handle.clone();
+                move || {
+                    let locked = handle.blocking_lock();
+                    locked.write_bulk(endpoint, &data, timeout).map_err(Error::from)
+                }
+            })
+            .await
+            .unwrap();
+
+            match result {
+                Ok(n) => total_written += n,
+                Err(e) if total_written > 0 => return Err(cut_short(e)),
+                Err(e) => return Err(e),
+            }
+        }
 
         Ok(())
     }
@@ -354,6 +377,10 @@ impl MTKPort for UsbMTKPort {
         self.port_name.clone()
     }
 
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        Some(self.usb_link)
+    }
+
     async fn find_device() -> Result<Option<Self>> {
         let devices = spawn_blocking(|| -> Result<Vec<Device<Context>>> {
             let context = Context::new()
@@ -375,7 +402,7 @@ impl MTKPort for UsbMTKPort {
             let vid = descriptor.vendor_id();
             let pid = descriptor.product_id();
 
-            if KNOWN_PORTS.iter().any(|(kvid, kpid, _)| *kvid == vid && *kpid == pid)
+            if port_connection_type(vid, pid).is_some()
                 && let Some(port) = UsbMTKPort::from_device(device) {
                     return Ok(Some(port));
                 }
@@ -439,3 +466,17 @@ impl MTKPort for UsbMTKPort {
         .map_err(|_| Error::io("Failed to run blocking control IN"))?
     }
 }
+
+/// Speed and hubs of the link to `device`, as seen by libusb.
+fn usb_link(device: &Device<Context>) -> UsbLink {
+    let speed = match device.speed() {
+        Speed::Low => Some(UsbSpeed::Low),
+        Speed::Full => Some(UsbSpeed::Full),
+        Speed::High => Some(UsbSpeed::High),
+        Speed::Super => Some(UsbSpeed::Super),
+        Speed::SuperPlus => Some(UsbSpeed::SuperPlus),
+        _ => None,
+    };
+
+    UsbLink::new(speed, &device.port_numbers().unwrap_or_default())
+}
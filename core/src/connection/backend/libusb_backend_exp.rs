@@ -14,13 +14,21 @@ use rusb::{
     Direction,
     Recipient,
     RequestType,
+    Speed,
     UsbContext,
 };
 use tokio::sync::Mutex;
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 
-use crate::connection::port::{ConnectionType, KNOWN_PORTS, MTKPort};
+use crate::connection::port::{
+    ConnectionType,
+    MTKPort,
+    UsbLink,
+    UsbSpeed,
+    port_connection_type,
+};
+use crate::connection::retry::cut_short;
 use crate::error::{Error, Result};
 
 /// Default timeout for USB operations
@@ -75,6 +83,7 @@ pub struct UsbMTKPort {
     is_open: bool,
     port_name: String,
     endpoints: BulkEndpoints,
+    usb_link: UsbLink,
 }
 
 impl std::fmt::Debug for UsbMTKPort {
@@ -155,10 +164,7 @@ impl UsbMTKPort {
         let vid = descriptor.vendor_id();
         let pid = descriptor.product_id();
 
-        let connection_type = KNOWN_PORTS
-            .iter()
-            .find(|&&(kvid, kpid, _)| kvid == vid && kpid == pid)
-            .map(|&(_, _, ct)| ct)?;
+        let connection_type = port_connection_type(vid, pid)?;
 
         debug!("Found known MTK device {:04X}:{:04X} ({:?})", vid, pid, connection_type);
 
@@ -182,6 +188,7 @@ impl UsbMTKPort {
             is_open: false,
             port_name,
             endpoints,
+            usb_link: usb_link(&device),
         })
     }
 
@@ -274,7 +281,7 @@ impl UsbMTKPort {
                 Ok(n) => Ok((temp, n)),
                 Err(rusb::Error::Timeout) => Err(Error::io("USB bulk read timeout")),
                 Err(rusb::Error::Pipe) => Err(Error::io("USB endpoint halted")),
-                Err(rusb::Error::NoDevice) => Err(Error::io("USB device disconnected")),
+                Err(e @ rusb::Error::NoDevice) => Err(e.into()),
                 Err(e) => Err(Error::io(format!("USB bulk read error: {:?}", e))),
             }
         })
@@ -301,7 +308,7 @@ impl UsbMTKPort {
                 Ok(n) => Ok(n),
                 Err(rusb::Error::Timeout) => Err(Error::io("USB bulk write timeout")),
                 Err(rusb::Error::Pipe) => Err(Error::io("USB endpoint halted")),
-                Err(rusb::Error::NoDevice) => Err(Error::io("USB device disconnected")),
+                Err(e @ rusb::Error::NoDevice) => Err(e.into()),
                 Err(e) => Err(Error::io(format!("USB bulk write error: {:?}", e))),
             }
         })
@@ -414,7 +421,7 @@ impl MTKPort for UsbMTKPort {
                             buf.len(),
                             e
                         );
-                        return Ok(total_read);
+                        return Err(cut_short(e));
                     }
                     return Err(e);
                 }
@@ -441,7 +448,7 @@ impl MTKPort for UsbMTKPort {
                 }
                 Err(e) => {
                     error!("Write failed after {}/{} bytes: {:?}", total_written, buf.len(), e);
-                    return Err(e);
+                    return Err(if total_written > 0 { cut_short(e) } else { e });
                 }
             }
         }
@@ -544,6 +551,10 @@ impl MTKPort for UsbMTKPort {
         self.port_name.clone()
     }
 
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        Some(self.usb_link)
+    }
+
     async fn find_device() -> Result<Option<Self>> {
         let devices = spawn_blocking(|| -> Result<Vec<(Device<Context>, u8, u8)>> {
             let context = Context::new()
@@ -574,7 +585,7 @@ impl MTKPort for UsbMTKPort {
             let vid = descriptor.vendor_id();
             let pid = descriptor.product_id();
 
-            let is_known = KNOWN_PORTS.iter().any(|(kvid, kpid, _)| *kvid == vid && *kpid == pid);
+            let is_known = port_connection_type(vid, pid).is_some();
 
             if is_known {
                 debug!("Found potential MTK device: {:04X}:{:04X}", vid, pid);
@@ -645,3 +656,17 @@ impl MTKPort for UsbMTKPort {
         .map_err(|e| Error::io(format!("Control IN task panicked: {:?}", e)))?
     }
 }
+
+/// Speed and hubs of the link to `device`, as seen by libusb.
+fn usb_link(device: &Device<Context>) -> UsbLink {
+    let speed = match device.speed() {
+        Speed::Low => Some(UsbSpeed::Low),
+        Speed::Full => Some(UsbSpeed::Full),
+        Speed::High => Some(UsbSpeed::High),
+        Speed::Super => Some(UsbSpeed::Super),
+        Speed::SuperPlus => Some(UsbSpeed::SuperPlus),
+        _ => None,
+    };
+
+    UsbLink::new(speed, &device.port_numbers().unwrap_or_default())
+}
@@ -8,7 +8,9 @@ pub mod libusb_backend_exp;
 pub mod libusb_backend;
 #[cfg(feature = "serial")]
 pub mod serial_backend;
-#[cfg(not(any(feature = "libusb", feature = "serial")))]
+#[cfg(feature = "tcp")]
+pub mod tcp_backend;
+#[cfg(all(feature = "nusb", not(any(feature = "libusb", feature = "serial"))))]
 pub mod usb_backend;
 #[cfg(all(feature = "libusb", feature = "libusb-exp"))]
 pub use libusb_backend_exp::UsbMTKPort;
@@ -16,5 +18,10 @@ pub use libusb_backend_exp::UsbMTKPort;
 pub use libusb_backend::UsbMTKPort;
 #[cfg(feature = "serial")]
 pub use serial_backend::SerialMTKPort;
-#[cfg(not(any(feature = "libusb", feature = "serial")))]
+#[cfg(feature = "tcp")]
+pub use tcp_backend::TcpMTKPort;
+#[cfg(all(feature = "nusb", not(any(feature = "libusb", feature = "serial"))))]
 pub use usb_backend::UsbMTKPort;
+
+#[cfg(not(any(feature = "nusb", feature = "libusb", feature = "serial")))]
+compile_error!("The usb feature needs a backend: enable one of nusb, libusb or serial");
@@ -2,11 +2,22 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
-// use std::io::{Error, ErrorKind};
+
+//! Serial backend, talking to the device through the serial port the OS makes of it.
+//!
+//! The OS owns the USB device, so some operations aren't available and fail with
+//! [`Error::Unsupported`]:
+//! * control transfers, which the Kamakiri exploit needs
+//! * the USB link speed and topology, so the DA can't be switched to a higher USB speed
+//!   based on it
+//!
+//! On the other hand, the baudrate and the DTR and RTS lines can be set, see
+//! [`MTKPort::set_baudrate`] and [`MTKPort::set_control_lines`].
 use std::time::Duration;
 
-use log::{error, info};
+use log::{debug, error, info};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
 use tokio_serial::{
     SerialPort,
     SerialPortBuilderExt,
@@ -15,9 +26,15 @@ use tokio_serial::{
     SerialStream,
 };
 
-use crate::connection::port::{ConnectionType, KNOWN_PORTS, MTKPort};
+use crate::connection::port::{ConnectionType, MTKPort, port_connection_type};
+use crate::connection::retry::cut_short;
 use crate::error::{Error, Result};
 
+/// How long each handshake byte gets to come back
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(1000);
+/// Start bytes sent before giving up on the handshake
+const HANDSHAKE_ATTEMPTS: usize = 10;
+
 #[derive(Debug)]
 pub struct SerialMTKPort {
     port: Option<SerialStream>,
@@ -38,12 +55,7 @@ impl SerialMTKPort {
             return None;
         };
 
-        let connection_type = KNOWN_PORTS
-            .iter()
-            .find(|&&(vid, pid, _)| vid == usb_info.vid && pid == usb_info.pid)
-            .map(|&(_, _, ct)| ct);
-
-        let connection_type = match connection_type {
+        let connection_type = match port_connection_type(usb_info.vid, usb_info.pid) {
             Some(ct) => ct,
             None => {
                 error!("Unknown MTK port type: {:04x}:{:04x}", usb_info.vid, usb_info.pid);
@@ -58,6 +70,28 @@ impl SerialMTKPort {
 
         Some(SerialMTKPort::new(port_info, baudrate, connection_type))
     }
+
+    /// Reads one handshake byte, `None` if none came in time
+    async fn read_handshake_byte(port: &mut SerialStream) -> Result<Option<u8>> {
+        let mut byte = [0u8; 1];
+        match timeout(HANDSHAKE_TIMEOUT, port.read_exact(&mut byte)).await {
+            Ok(result) => result.map(|_| Some(byte[0])).map_err(Error::from),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Sends `byte` and checks the device answers with `expected`
+    async fn handshake_step(port: &mut SerialStream, byte: u8, expected: u8) -> Result<()> {
+        port.write_all(&[byte]).await?;
+        match Self::read_handshake_byte(port).await? {
+            Some(answer) if answer == expected => Ok(()),
+            Some(answer) => Err(Error::io(format!(
+                "Handshake failed: Expected 0x{:02X}, got 0x{:02X}",
+                expected, answer
+            ))),
+            None => Err(Error::io(format!("Handshake failed: no answer to 0x{:02X}", byte))),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -88,24 +122,42 @@ impl MTKPort for SerialMTKPort {
     }
 
     async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
-        if let Some(port) = &mut self.port {
-            port.read_exact(buf).await.map_err(|e| Error::Io(e.to_string()))
-        } else {
-            Err(Error::io("Port is not open"))
+        let Some(port) = &mut self.port else {
+            return Err(Error::io("Port is not open"));
+        };
+
+        let mut pos = 0;
+        while pos < buf.len() {
+            match port.read(&mut buf[pos..]).await {
+                Ok(0) => return Err(Error::conn("Serial read ended early")),
+                Ok(n) => pos += n,
+                Err(e) if pos > 0 => return Err(cut_short(e.into())),
+                Err(e) => return Err(e.into()),
+            }
         }
+        Ok(buf.len())
     }
 
     async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
-        if let Some(port) = &mut self.port {
-            port.write_all(buf).await.map_err(|e| Error::Io(e.to_string()))
-        } else {
-            Err(Error::io("Port is not open"))
+        let Some(port) = &mut self.port else {
+            return Err(Error::io("Port is not open"));
+        };
+
+        let mut pos = 0;
+        while pos < buf.len() {
+            match port.write(&buf[pos..]).await {
+                Ok(0) => return Err(Error::conn("Serial write ended early")),
+                Ok(n) => pos += n,
+                Err(e) if pos > 0 => return Err(cut_short(e.into())),
+                Err(e) => return Err(e.into()),
+            }
         }
+        Ok(())
     }
 
     async fn flush(&mut self) -> Result<()> {
         if let Some(port) = &mut self.port {
-            port.clear(tokio_serial::ClearBuffer::Input).map_err(|e| Error::Io(e.to_string()))?;
+            port.clear(tokio_serial::ClearBuffer::Input).map_err(|e| Error::io(e.to_string()))?;
             Ok(())
         } else {
             Err(Error::io("Port is not open"))
@@ -113,72 +165,109 @@ impl MTKPort for SerialMTKPort {
     }
 
     async fn handshake(&mut self) -> Result<()> {
-        if let Some(port) = &mut self.port {
-            loop {
-                port.write_all(&[0xA0]).await?;
-
-                let mut response = [0u8; 1];
-                match port.read_exact(&mut response).await {
-                    Ok(_) if response[0] == 0x5F => break,
-                    Ok(_) if response[0] == 0xA0 => {
-                        // We already handshaked, just return
-                        return Ok(());
-                    }
-                    Ok(_) | Err(_) => {
-                        info!("Received byte: 0x{:02X}", response[0]);
-                    }
-                }
-            }
-
-            port.write_all(&[0x0A]).await?;
-            let mut r1 = [0u8; 1];
-            port.read_exact(&mut r1).await?;
-            if r1 != [0xF5] {
-                return Err(Error::io("Handshake failed: Expected 0xF5"));
-            }
+        let Some(port) = &mut self.port else {
+            return Err(Error::io("Port is not open"));
+        };
 
-            port.write_all(&[0x50]).await?;
-            let mut r2 = [0u8; 1];
-            port.read_exact(&mut r2).await?;
-            if r2 != [0xAF] {
-                return Err(Error::io("Handshake failed: Expected 0xAF"));
-            }
+        // A device that never answers would keep the handshake going forever
+        let mut synced = false;
+        for _ in 0..HANDSHAKE_ATTEMPTS {
+            port.write_all(&[0xA0]).await?;
 
-            port.write_all(&[0x05]).await?;
-            let mut r3 = [0u8; 1];
-            port.read_exact(&mut r3).await?;
-            if r3 != [0xFA] {
-                return Err(Error::io("Handshake failed: Expected 0xFA"));
+            match Self::read_handshake_byte(port).await? {
+                Some(0x5F) => {
+                    synced = true;
+                    break;
+                }
+                // We already handshaked, just return
+                Some(0xA0) => return Ok(()),
+                Some(byte) => info!("Received byte: 0x{:02X}", byte),
+                None => debug!("No answer to the handshake start byte, retrying"),
             }
+        }
 
-            Ok(())
-        } else {
-            Err(Error::io("Port is not open"))
+        if !synced {
+            return Err(Error::io(format!(
+                "Handshake failed: no answer after {} attempts",
+                HANDSHAKE_ATTEMPTS
+            )));
         }
+
+        Self::handshake_step(port, 0x0A, 0xF5).await?;
+        Self::handshake_step(port, 0x50, 0xAF).await?;
+        Self::handshake_step(port, 0x05, 0xFA).await
     }
 
     fn get_connection_type(&self) -> ConnectionType {
         self.connection_type
     }
 
+    /// The rate the port actually runs at, which the driver may have rounded
     fn get_baudrate(&self) -> u32 {
-        self.baudrate
+        match &self.port {
+            Some(port) => port.baud_rate().unwrap_or(self.baudrate),
+            None => self.baudrate,
+        }
+    }
+
+    fn is_uart(&self) -> bool {
+        true
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> Result<()> {
+        if let Some(port) = &mut self.port {
+            port.set_baud_rate(baudrate).map_err(|e| Error::io(e.to_string()))?;
+        }
+        self.baudrate = baudrate;
+        Ok(())
+    }
+
+    fn set_control_lines(&mut self, dtr: bool, rts: bool) -> Result<()> {
+        let Some(port) = &mut self.port else {
+            return Err(Error::io("Port is not open"));
+        };
+
+        port.write_data_terminal_ready(dtr).map_err(|e| Error::io(e.to_string()))?;
+        port.write_request_to_send(rts).map_err(|e| Error::io(e.to_string()))?;
+        Ok(())
     }
 
     fn get_port_name(&self) -> String {
         self.port_info.port_name.clone()
     }
 
+    fn get_location(&self) -> Option<String> {
+        Some(self.port_info.port_name.clone())
+    }
+
+    fn get_usb_ids(&self) -> Option<(u16, u16)> {
+        match &self.port_info.port_type {
+            SerialPortType::UsbPort(usb_info) => Some((usb_info.vid, usb_info.pid)),
+            _ => None,
+        }
+    }
+
+    fn get_serial(&self) -> Option<String> {
+        match &self.port_info.port_type {
+            SerialPortType::UsbPort(usb_info) => usb_info.serial_number.clone(),
+            _ => None,
+        }
+    }
+
     async fn find_device() -> Result<Option<Self>> {
+        Ok(Self::find_devices().await?.into_iter().next())
+    }
+
+    async fn find_devices() -> Result<Vec<Self>> {
         use serialport::{SerialPortType, available_ports};
 
         let serial_ports = match available_ports() {
             Ok(ports) => ports
                 .into_iter()
                 .filter(|p| match &p.port_type {
-                    SerialPortType::UsbPort(usb_info) => KNOWN_PORTS
-                        .iter()
-                        .any(|(vid, pid, _)| usb_info.vid == *vid && usb_info.pid == *pid),
+                    SerialPortType::UsbPort(usb_info) => {
+                        port_connection_type(usb_info.vid, usb_info.pid).is_some()
+                    }
                     _ => false,
                 })
                 .collect::<Vec<_>>(),
@@ -188,13 +277,7 @@ impl MTKPort for SerialMTKPort {
             }
         };
 
-        for port_info in serial_ports {
-            if let Some(port) = SerialMTKPort::from_port_info(port_info) {
-                return Ok(Some(port));
-            }
-        }
-
-        Ok(None)
+        Ok(serial_ports.into_iter().filter_map(SerialMTKPort::from_port_info).collect())
     }
 
     async fn ctrl_out(
@@ -205,7 +288,7 @@ impl MTKPort for SerialMTKPort {
         index: u16,
         data: &[u8],
     ) -> Result<()> {
-        Err(Error::io("Control transfer OUT not supported on serial connections"))
+        Err(Error::Unsupported("Control transfers are not available on serial connections".into()))
     }
 
     async fn ctrl_in(
@@ -216,6 +299,6 @@ impl MTKPort for SerialMTKPort {
         index: u16,
         len: usize,
     ) -> Result<Vec<u8>> {
-        Err(Error::io("Control transfer IN not supported on serial connections"))
+        Err(Error::Unsupported("Control transfers are not available on serial connections".into()))
     }
 }
@@ -0,0 +1,238 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fmt;
+
+use tokio::net::TcpStream;
+
+use crate::connection::bridge::{
+    DEFAULT_BRIDGE_PORT,
+    Op,
+    bridge_addr,
+    decode_error,
+    decode_info,
+    read_frame,
+    write_frame,
+};
+use crate::connection::port::{ConnectionType, MTKPort, PortInfo};
+use crate::error::{Error, Result};
+
+/// A port attached to another machine, reached through [`crate::connection::bridge`].
+pub struct TcpMTKPort {
+    addr: String,
+    stream: Option<TcpStream>,
+    info: Option<PortInfo>,
+    is_open: bool,
+    /// A request was sent and its answer not read yet
+    in_flight: bool,
+}
+
+impl fmt::Debug for TcpMTKPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TcpMTKPort {{ addr: {}, info: {:?}, is_open: {} }}",
+            self.addr, self.info, self.is_open
+        )
+    }
+}
+
+impl TcpMTKPort {
+    /// A port behind the bridge at `addr` (`host:port`, or just `host` for the default
+    /// port). Nothing is connected until [`MTKPort::open`].
+    pub fn new<S: Into<String>>(addr: S) -> Self {
+        let mut addr = addr.into();
+        if !addr.contains(':') {
+            addr = format!("{}:{}", addr, DEFAULT_BRIDGE_PORT);
+        }
+
+        Self { addr, stream: None, info: None, is_open: false, in_flight: false }
+    }
+
+    /// Asks the bridge whether a device is attached, without opening it
+    pub async fn probe(&mut self) -> Result<Option<PortInfo>> {
+        let payload = self.request(Op::Probe, &[]).await?;
+        if payload.is_empty() {
+            return Ok(None);
+        }
+
+        let info = decode_info(&payload)?;
+        self.info = Some(info.clone());
+        Ok(Some(info))
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        if self.stream.is_none() {
+            let stream = TcpStream::connect(&self.addr)
+                .await
+                .map_err(|e| Error::conn(format!("Can't reach bridge {}: {}", self.addr, e)))?;
+            stream.set_nodelay(true)?;
+            self.stream = Some(stream);
+        }
+
+        Ok(())
+    }
+
+    async fn request(&mut self, op: Op, payload: &[u8]) -> Result<Vec<u8>> {
+        // A request dropped halfway, e.g. by a response timeout, leaves its answer on the
+        // way. Start over on a new connection, which the bridge sees as a new client.
+        if self.in_flight {
+            self.stream = None;
+            self.in_flight = false;
+            if self.is_open && op != Op::Open {
+                self.exchange(Op::Open, &[]).await?;
+            }
+        }
+
+        self.exchange(op, payload).await
+    }
+
+    async fn exchange(&mut self, op: Op, payload: &[u8]) -> Result<Vec<u8>> {
+        self.connect().await?;
+        self.in_flight = true;
+        let stream = self.stream.as_mut().unwrap();
+        let result = async {
+            write_frame(stream, op as u8, payload).await?;
+            read_frame(stream).await
+        }
+        .await;
+
+        match result {
+            Ok((status, data)) => {
+                self.in_flight = false;
+                match status {
+                    0 => Ok(data),
+                    _ => Err(decode_error(&data)),
+                }
+            }
+            Err(e) => {
+                // The bridge went away, the port behind it can't be used anymore
+                self.stream = None;
+                self.in_flight = false;
+                self.is_open = false;
+                Err(Error::Io { kind: std::io::ErrorKind::NotConnected, msg: e.to_string() })
+            }
+        }
+    }
+
+    fn ctrl_header(request_type: u8, request: u8, value: u16, index: u16) -> Vec<u8> {
+        let mut payload = vec![request_type, request];
+        payload.extend_from_slice(&value.to_le_bytes());
+        payload.extend_from_slice(&index.to_le_bytes());
+        payload
+    }
+}
+
+#[async_trait::async_trait]
+impl MTKPort for TcpMTKPort {
+    async fn open(&mut self) -> Result<()> {
+        if self.is_open {
+            return Ok(());
+        }
+
+        let payload = self.request(Op::Open, &[]).await?;
+        self.info = Some(decode_info(&payload)?);
+        self.is_open = true;
+
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if !self.is_open {
+            return Ok(());
+        }
+
+        self.is_open = false;
+        self.request(Op::Close, &[]).await?;
+        Ok(())
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let data = self.request(Op::Read, &(buf.len() as u32).to_le_bytes()).await?;
+        if data.len() != buf.len() {
+            return Err(Error::conn("Short read from the bridge"));
+        }
+
+        buf.copy_from_slice(&data);
+        Ok(buf.len())
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.request(Op::Write, buf).await?;
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.request(Op::Flush, &[]).await?;
+        Ok(())
+    }
+
+    async fn handshake(&mut self) -> Result<()> {
+        self.request(Op::Handshake, &[]).await?;
+        Ok(())
+    }
+
+    fn get_connection_type(&self) -> ConnectionType {
+        self.info.as_ref().map_or(ConnectionType::Brom, |i| i.connection_type)
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        0
+    }
+
+    fn get_port_name(&self) -> String {
+        match &self.info {
+            Some(info) => format!("{} via {}", info.name, self.addr),
+            None => format!("Bridge {}", self.addr),
+        }
+    }
+
+    fn get_location(&self) -> Option<String> {
+        let location = self.info.as_ref()?.location.as_ref()?;
+        Some(format!("{} on {}", location, self.addr))
+    }
+
+    /// Uses the bridge from [`crate::connection::bridge::BRIDGE_ENV`], if set
+    async fn find_device() -> Result<Option<Self>> {
+        let Some(addr) = bridge_addr() else {
+            return Ok(None);
+        };
+
+        let mut port = TcpMTKPort::new(addr);
+        Ok(port.probe().await?.map(|_| port))
+    }
+
+    /// The bridge looks the port up again on open
+    async fn reopen(&mut self) -> Result<bool> {
+        self.open().await?;
+        Ok(true)
+    }
+
+    async fn ctrl_out(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut payload = Self::ctrl_header(request_type, request, value, index);
+        payload.extend_from_slice(data);
+        self.request(Op::CtrlOut, &payload).await?;
+        Ok(())
+    }
+
+    async fn ctrl_in(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        len: usize,
+    ) -> Result<Vec<u8>> {
+        let mut payload = Self::ctrl_header(request_type, request, value, index);
+        payload.extend_from_slice(&(len as u32).to_le_bytes());
+        self.request(Op::CtrlIn, &payload).await
+    }
+}
@@ -4,6 +4,7 @@
 */
 
 use std::fmt;
+use std::io::ErrorKind;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -11,18 +12,32 @@ use log::debug;
 use nusb::descriptors::TransferType;
 use nusb::io::{EndpointRead, EndpointWrite};
 use nusb::transfer::{Bulk, ControlIn, ControlOut, ControlType, Direction, In, Out, Recipient};
-use nusb::{DeviceInfo, Interface};
+use nusb::{DeviceInfo, Interface, Speed};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::MTKPort;
 use crate::connection::ConnectionType;
-use crate::connection::port::KNOWN_PORTS;
+use crate::connection::port::{UsbLink, UsbSpeed, port_connection_type};
+use crate::connection::retry::cut_short;
 use crate::error::{Error, Result};
 
 const MAX_TIMEOUT: Duration = Duration::from_secs(2);
 const BULK_IN_SZ: usize = 0x80000;
 const BULK_OUT_SZ: usize = 0x80000;
 
+/// Reports the bulk transfer errors a retry can recover from (timeouts and stalls), and a
+/// disconnected device, as [`Error::Io`], and the others as [`Error::Connection`].
+fn transfer_error(err: std::io::Error) -> Error {
+    match err.kind() {
+        ErrorKind::TimedOut
+        | ErrorKind::Interrupted
+        | ErrorKind::BrokenPipe
+        | ErrorKind::NotConnected
+        | ErrorKind::ConnectionAborted => err.into(),
+        _ => Error::conn(err.to_string()),
+    }
+}
+
 pub struct UsbMTKPort {
     info: DeviceInfo,
     interface: Option<Interface>,
@@ -197,17 +212,28 @@ impl MTKPort for UsbMTKPort {
     }
 
     async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
-        let reader = self.reader.as_mut().ok_or_else(|| Error::io("USB port is not open"))?;
-
-        reader.read_exact(buf).await?;
+        let reader = self.reader.as_mut().ok_or_else(|| Error::conn("USB port is not open"))?;
+
+        let mut pos = 0;
+        while pos < buf.len() {
+            let n = match reader.read(&mut buf[pos..]).await {
+                Ok(0) => return Err(Error::conn("USB read ended early")),
+                Ok(n) => n,
+                Err(e) if pos > 0 => return Err(cut_short(transfer_error(e))),
+                Err(e) => return Err(transfer_error(e)),
+            };
+            pos += n;
+        }
         Ok(buf.len())
     }
 
+    /// The writer doesn't tell how much of a failed write reached the device, so write
+    /// errors are never reported as transient.
     async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
-        let writer = self.writer.as_mut().ok_or_else(|| Error::io("USB port is not open"))?;
+        let writer = self.writer.as_mut().ok_or_else(|| Error::conn("USB port is not open"))?;
 
-        writer.write_all(buf).await?;
-        writer.flush().await?;
+        writer.write_all(buf).await.map_err(|e| cut_short(transfer_error(e)))?;
+        writer.flush().await.map_err(|e| cut_short(transfer_error(e)))?;
         Ok(())
     }
 
@@ -265,19 +291,50 @@ impl MTKPort for UsbMTKPort {
         format!("USB {:04X}:{:04X}", self.info.vendor_id(), self.info.product_id())
     }
 
+    fn get_location(&self) -> Option<String> {
+        let chain = self.info.port_chain();
+        if chain.is_empty() {
+            return None;
+        }
+
+        let ports: Vec<String> = chain.iter().map(|p| p.to_string()).collect();
+        Some(format!("bus {}-{}", self.info.bus_id(), ports.join(".")))
+    }
+
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        let speed = self.info.speed().and_then(|speed| match speed {
+            Speed::Low => Some(UsbSpeed::Low),
+            Speed::Full => Some(UsbSpeed::Full),
+            Speed::High => Some(UsbSpeed::High),
+            Speed::Super => Some(UsbSpeed::Super),
+            Speed::SuperPlus => Some(UsbSpeed::SuperPlus),
+            _ => None,
+        });
+
+        Some(UsbLink::new(speed, self.info.port_chain()))
+    }
+
+    fn get_usb_ids(&self) -> Option<(u16, u16)> {
+        Some((self.info.vendor_id(), self.info.product_id()))
+    }
+
+    fn get_serial(&self) -> Option<String> {
+        self.info.serial_number().map(str::to_string)
+    }
+
     async fn find_device() -> Result<Option<Self>> {
-        let devices = nusb::list_devices().await?;
+        Ok(Self::find_devices().await?.into_iter().next())
+    }
 
-        for device in devices {
-            if let Some((_, _, conn_type)) = KNOWN_PORTS
-                .iter()
-                .find(|(vid, pid, _)| device.vendor_id() == *vid && device.product_id() == *pid)
-            {
-                return Ok(Some(UsbMTKPort::new(device, *conn_type)));
-            }
-        }
+    async fn find_devices() -> Result<Vec<Self>> {
+        let devices = nusb::list_devices().await?;
 
-        Ok(None)
+        Ok(devices
+            .filter_map(|device| {
+                let conn_type = port_connection_type(device.vendor_id(), device.product_id())?;
+                Some(UsbMTKPort::new(device, conn_type))
+            })
+            .collect())
     }
 
     async fn ctrl_out(
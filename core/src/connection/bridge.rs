@@ -0,0 +1,301 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Bridge exposing a port attached to one machine to penumbra running on another.
+//!
+//! [`serve_bridge`] runs next to the device, and [`TcpMTKPort`] forwards every
+//! [`MTKPort`] call to it. Each call is a frame made of an [`Op`], the payload length
+//! (u32 LE) and the payload. The answer is a status byte (0 on success), the payload
+//! length and the payload, which holds an error kind and message on failure.
+//!
+//! The bridge has no authentication: only listen on trusted networks.
+//!
+//! [`TcpMTKPort`]: crate::connection::TcpMTKPort
+use std::io::ErrorKind;
+
+use log::{debug, info, warn};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::connection::port::{ConnectionType, MTKPort, PortInfo, find_local_port, probe_local_port};
+use crate::error::{Error, Result};
+
+/// Environment variable holding the `host:port` of a bridge. When set,
+/// [`crate::find_mtk_port`] and [`crate::probe_mtk_port`] go through it.
+pub const BRIDGE_ENV: &str = "PENUMBRA_BRIDGE";
+/// Port the bridge listens on when none is given
+pub const DEFAULT_BRIDGE_PORT: u16 = 7474;
+/// Largest payload accepted, well above the biggest transfer chunk
+const MAX_PAYLOAD: usize = 0x400_0000;
+
+/// The bridge address from [`BRIDGE_ENV`], if set
+pub fn bridge_addr() -> Option<String> {
+    std::env::var(BRIDGE_ENV).ok().filter(|addr| !addr.trim().is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum Op {
+    /// Looks for a port without opening it, answers with its info or nothing
+    Probe = 0,
+    /// Finds and opens a port, answers with its info
+    Open = 1,
+    Close = 2,
+    /// Payload: the length to read (u32 LE), answers with the data
+    Read = 3,
+    Write = 4,
+    Flush = 5,
+    Handshake = 6,
+    /// Payload: request type, request, value (u16 LE), index (u16 LE) and the data
+    CtrlOut = 7,
+    /// Payload: request type, request, value (u16 LE), index (u16 LE) and the length (u32 LE)
+    CtrlIn = 8,
+}
+
+impl Op {
+    fn from_u8(op: u8) -> Option<Self> {
+        Some(match op {
+            0 => Op::Probe,
+            1 => Op::Open,
+            2 => Op::Close,
+            3 => Op::Read,
+            4 => Op::Write,
+            5 => Op::Flush,
+            6 => Op::Handshake,
+            7 => Op::CtrlOut,
+            8 => Op::CtrlIn,
+            _ => return None,
+        })
+    }
+}
+
+/// Error kinds sent over the wire, so the retry layer on the other side still tells
+/// a timeout from an unplugged device
+const ERR_OTHER: u8 = 0;
+const ERR_TIMED_OUT: u8 = 1;
+const ERR_NOT_CONNECTED: u8 = 2;
+const ERR_BROKEN_PIPE: u8 = 3;
+const ERR_INTERRUPTED: u8 = 4;
+/// Not an I/O error, reported as [`Error::Connection`]
+const ERR_CONNECTION: u8 = 0xFF;
+
+pub(crate) async fn write_frame<W>(w: &mut W, head: u8, payload: &[u8]) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut frame = Vec::with_capacity(payload.len() + 5);
+    frame.push(head);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    w.write_all(&frame).await?;
+    w.flush().await?;
+    Ok(())
+}
+
+pub(crate) async fn read_frame<R>(r: &mut R) -> Result<(u8, Vec<u8>)>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut hdr = [0u8; 5];
+    r.read_exact(&mut hdr).await?;
+
+    let len = u32::from_le_bytes(hdr[1..5].try_into().unwrap()) as usize;
+    if len > MAX_PAYLOAD {
+        return Err(Error::conn(format!("Bridge frame too large (0x{:X} bytes)", len)));
+    }
+
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload).await?;
+    Ok((hdr[0], payload))
+}
+
+pub(crate) fn encode_error(err: &Error) -> Vec<u8> {
+    let kind = match err.io_kind() {
+        Some(ErrorKind::TimedOut) => ERR_TIMED_OUT,
+        Some(ErrorKind::NotConnected | ErrorKind::ConnectionAborted) => ERR_NOT_CONNECTED,
+        Some(ErrorKind::BrokenPipe) => ERR_BROKEN_PIPE,
+        Some(ErrorKind::Interrupted) => ERR_INTERRUPTED,
+        Some(_) => ERR_OTHER,
+        None => ERR_CONNECTION,
+    };
+
+    // The other side wraps the message again
+    let msg = match err {
+        Error::Io { msg, .. } | Error::Connection(msg) => msg.clone(),
+        err => err.to_string(),
+    };
+
+    let mut payload = vec![kind];
+    payload.extend_from_slice(msg.as_bytes());
+    payload
+}
+
+pub(crate) fn decode_error(payload: &[u8]) -> Error {
+    let msg = String::from_utf8_lossy(payload.get(1..).unwrap_or_default()).into_owned();
+    let kind = match payload.first().copied().unwrap_or(ERR_CONNECTION) {
+        ERR_TIMED_OUT => ErrorKind::TimedOut,
+        ERR_NOT_CONNECTED => ErrorKind::NotConnected,
+        ERR_BROKEN_PIPE => ErrorKind::BrokenPipe,
+        ERR_INTERRUPTED => ErrorKind::Interrupted,
+        ERR_OTHER => ErrorKind::Other,
+        _ => return Error::conn(msg),
+    };
+
+    Error::Io { kind, msg }
+}
+
+/// Port info as sent over the wire: the connection type, then the name and the
+/// location separated by a NUL. An empty location means unknown.
+pub(crate) fn encode_info(info: &PortInfo) -> Vec<u8> {
+    let kind = match info.connection_type {
+        ConnectionType::Brom => 0,
+        ConnectionType::Preloader => 1,
+        ConnectionType::Da => 2,
+    };
+
+    let mut payload = vec![kind];
+    payload.extend_from_slice(info.name.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(info.location.as_deref().unwrap_or_default().as_bytes());
+    payload
+}
+
+pub(crate) fn decode_info(payload: &[u8]) -> Result<PortInfo> {
+    let connection_type = match payload.first() {
+        Some(0) => ConnectionType::Brom,
+        Some(1) => ConnectionType::Preloader,
+        Some(2) => ConnectionType::Da,
+        _ => return Err(Error::conn("Invalid port info from the bridge")),
+    };
+
+    let text = String::from_utf8_lossy(&payload[1..]);
+    let (name, location) = text.split_once('\0').unwrap_or((&text, ""));
+    Ok(PortInfo {
+        connection_type,
+        name: name.to_string(),
+        location: Some(location.to_string()).filter(|l| !l.is_empty()),
+        usb_link: None,
+        vid: 0,
+        pid: 0,
+        serial: None,
+    })
+}
+
+/// Serves the port attached to this machine on `addr` (e.g. `0.0.0.0:7474`), until an
+/// error occurs while listening.
+///
+/// Clients are served one at a time, since a port can only be claimed once. The port is
+/// looked up again on each open, so it follows the device through BROM, preloader and
+/// DA mode.
+pub async fn serve_bridge(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Bridge listening on {}", listener.local_addr()?);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        info!("Bridge client connected from {}", peer);
+        stream.set_nodelay(true)?;
+
+        match serve_client(stream).await {
+            Err(e) if e.io_kind() != Some(ErrorKind::UnexpectedEof) => {
+                warn!("Bridge client {} dropped: {}", peer, e)
+            }
+            _ => info!("Bridge client {} disconnected", peer),
+        }
+    }
+}
+
+async fn serve_client(mut stream: TcpStream) -> Result<()> {
+    let mut port: Option<Box<dyn MTKPort>> = None;
+
+    let result = loop {
+        let (op, payload) = match read_frame(&mut stream).await {
+            Ok(frame) => frame,
+            Err(e) => break Err(e),
+        };
+
+        let Some(op) = Op::from_u8(op) else {
+            let err = Error::conn(format!("Unknown bridge op {}", op));
+            write_frame(&mut stream, 1, &encode_error(&err)).await?;
+            continue;
+        };
+        debug!("Bridge op {:?} with 0x{:X} bytes", op, payload.len());
+
+        match handle_op(&mut port, op, &payload).await {
+            Ok(data) => write_frame(&mut stream, 0, &data).await?,
+            Err(e) => write_frame(&mut stream, 1, &encode_error(&e)).await?,
+        }
+    };
+
+    if let Some(mut port) = port {
+        port.close().await.ok();
+    }
+    result
+}
+
+async fn handle_op(
+    port: &mut Option<Box<dyn MTKPort>>,
+    op: Op,
+    payload: &[u8],
+) -> Result<Vec<u8>> {
+    match op {
+        Op::Probe => {
+            let info = probe_local_port().await;
+            return Ok(info.map(|i| encode_info(&i)).unwrap_or_default());
+        }
+        Op::Open => {
+            if let Some(mut old) = port.take() {
+                old.close().await.ok();
+            }
+            let found = find_local_port().await.ok_or_else(|| Error::conn("No MTK port found"))?;
+            let info = encode_info(&PortInfo::from_port(found.as_ref()));
+            *port = Some(found);
+            return Ok(info);
+        }
+        Op::Close => {
+            if let Some(mut old) = port.take() {
+                old.close().await?;
+            }
+            return Ok(Vec::new());
+        }
+        _ => {}
+    }
+
+    let port = port.as_mut().ok_or_else(|| Error::conn("Bridge port is not open"))?;
+    let short = || Error::conn("Truncated bridge request");
+
+    match op {
+        Op::Read => {
+            let len = u32::from_le_bytes(payload.try_into().map_err(|_| short())?) as usize;
+            if len > MAX_PAYLOAD {
+                return Err(Error::conn(format!("Bridge read too large (0x{:X} bytes)", len)));
+            }
+            let mut buf = vec![0u8; len];
+            port.read_exact(&mut buf).await?;
+            Ok(buf)
+        }
+        Op::Write => port.write_all(payload).await.map(|_| Vec::new()),
+        Op::Flush => port.flush().await.map(|_| Vec::new()),
+        Op::Handshake => port.handshake().await.map(|_| Vec::new()),
+        Op::CtrlOut | Op::CtrlIn => {
+            if payload.len() < 6 {
+                return Err(short());
+            }
+            let (request_type, request) = (payload[0], payload[1]);
+            let value = u16::from_le_bytes([payload[2], payload[3]]);
+            let index = u16::from_le_bytes([payload[4], payload[5]]);
+
+            if op == Op::CtrlOut {
+                port.ctrl_out(request_type, request, value, index, &payload[6..]).await?;
+                return Ok(Vec::new());
+            }
+
+            let len = payload.get(6..10).ok_or_else(short)?;
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+            port.ctrl_in(request_type, request, value, index, len.min(MAX_PAYLOAD)).await
+        }
+        Op::Probe | Op::Open | Op::Close => unreachable!(),
+    }
+}
@@ -0,0 +1,31 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Asks the running flash operation to stop.
+///
+/// The protocols check it between chunks, so the DA is told to abort the transfer and
+/// stays usable, unlike when the task running the operation is dropped mid-transfer.
+/// It's shared with the connection, so a clone can be kept to cancel from elsewhere.
+///
+/// Once cancelled, the token stays so until [`CancelToken::reset`], so that operations
+/// made of several transfers stop as a whole. Reset it before starting a new operation.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
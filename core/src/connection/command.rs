@@ -39,6 +39,7 @@ pub enum Command {
     SendDa = 0xD7,
     GetTargetConfig = 0xD8,
     Uart1LogEn = 0xDB,
+    Uart1SetBaudrate = 0xDC,
 
     SendCert = 0xE0,
     GetMeId = 0xE1,
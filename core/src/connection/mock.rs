@@ -0,0 +1,413 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Port playing a scripted device, to test protocol code without hardware.
+//!
+//! A [`Transcript`] lists what the host must write and what the device answers, in order.
+//! It's either written by hand or loaded from a trace recorded through [`record`] on a
+//! real device. [`MockMTKPort`] then plays it: every write must match the transcript, and
+//! reads get the answers, failing as soon as the host strays from the script.
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::connection::port::{ConnectionType, MTKPort, UsbLink};
+use crate::connection::retry::cut_short;
+use crate::connection::trace::{TraceKind, TracePort, TraceRecord, read_trace};
+use crate::error::{Error, Result};
+
+/// Magic starting the packet headers of both DA protocols
+const DA_MAGIC: u32 = 0xFEEEEEEF;
+/// Data type of the packets sent by both DA protocols
+const DA_PROTOCOL_FLOW: u32 = 1;
+
+/// A step of a [`Transcript`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Bytes the host must write. Consecutive writes can be split differently by the host.
+    Write(Vec<u8>),
+    /// Bytes the device sends. Consecutive reads can be split differently by the host.
+    Read(Vec<u8>),
+    /// Control transfer the host must make
+    CtrlOut { request_type: u8, request: u8, value: u16, index: u16, data: Vec<u8> },
+    /// Control transfer the device answers with `data`
+    CtrlIn { request_type: u8, request: u8, value: u16, index: u16, data: Vec<u8> },
+    /// The host must handshake
+    Handshake,
+    /// The next port call fails with this message
+    Error(String),
+}
+
+impl Step {
+    fn describe(&self) -> String {
+        match self {
+            Step::Write(data) => format!("a write of {:02X?}", data),
+            Step::Read(data) => format!("a read of 0x{:X} bytes", data.len()),
+            Step::CtrlOut { request, .. } => format!("a control out (request 0x{:02X})", request),
+            Step::CtrlIn { request, .. } => format!("a control in (request 0x{:02X})", request),
+            Step::Handshake => "a handshake".to_string(),
+            Step::Error(msg) => format!("an error ({})", msg),
+        }
+    }
+}
+
+/// What the host and the device exchange, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    steps: Vec<Step>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a trace file recorded through [`record`], or
+    /// [`crate::DeviceBuilder::with_trace`] without a trace limit.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_records(&read_trace(path)?)
+    }
+
+    /// Builds a transcript from trace records. Records cut to the trace limit can't be
+    /// played back, and fail the whole transcript.
+    pub fn from_records(records: &[TraceRecord]) -> Result<Self> {
+        let mut transcript = Self::new();
+
+        for (i, record) in records.iter().enumerate() {
+            if record.is_truncated() {
+                return Err(Error::penumbra(format!(
+                    "Trace record {} was cut to the trace limit, record without one",
+                    i
+                )));
+            }
+
+            let data = record.data.clone();
+            let step = match record.kind {
+                TraceKind::Write => Step::Write(data),
+                TraceKind::Read => Step::Read(data),
+                TraceKind::CtrlOut | TraceKind::CtrlIn => {
+                    let (request_type, request, value, index) = ctrl_setup(&data)?;
+                    let data = data[6..].to_vec();
+                    match record.kind {
+                        TraceKind::CtrlOut => {
+                            Step::CtrlOut { request_type, request, value, index, data }
+                        }
+                        _ => Step::CtrlIn { request_type, request, value, index, data },
+                    }
+                }
+                TraceKind::Handshake => Step::Handshake,
+                TraceKind::Error => Step::Error(String::from_utf8_lossy(&data).into_owned()),
+            };
+            transcript.steps.push(step);
+        }
+
+        Ok(transcript)
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn push(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// The host must write `data`
+    pub fn write(self, data: impl Into<Vec<u8>>) -> Self {
+        self.push(Step::Write(data.into()))
+    }
+
+    /// The device sends `data`
+    pub fn read(self, data: impl Into<Vec<u8>>) -> Self {
+        self.push(Step::Read(data.into()))
+    }
+
+    /// The host must send `data` as a DA packet, header first
+    pub fn write_packet(self, data: impl Into<Vec<u8>>) -> Self {
+        let data = data.into();
+        self.write(packet_header(data.len())).write(data)
+    }
+
+    /// The device sends `data` as a DA packet, header first
+    pub fn read_packet(self, data: impl Into<Vec<u8>>) -> Self {
+        let data = data.into();
+        self.read(packet_header(data.len())).read(data)
+    }
+
+    pub fn handshake(self) -> Self {
+        self.push(Step::Handshake)
+    }
+
+    /// The next port call fails with `msg`
+    pub fn error(self, msg: impl Into<String>) -> Self {
+        self.push(Step::Error(msg.into()))
+    }
+}
+
+/// Header of a DA packet holding `len` bytes
+fn packet_header(len: usize) -> Vec<u8> {
+    let mut hdr = Vec::with_capacity(12);
+    hdr.extend_from_slice(&DA_MAGIC.to_le_bytes());
+    hdr.extend_from_slice(&DA_PROTOCOL_FLOW.to_le_bytes());
+    hdr.extend_from_slice(&(len as u32).to_le_bytes());
+    hdr
+}
+
+fn ctrl_setup(data: &[u8]) -> Result<(u8, u8, u16, u16)> {
+    if data.len() < 6 {
+        return Err(Error::penumbra("Control transfer record without its setup"));
+    }
+    let value = u16::from_le_bytes([data[2], data[3]]);
+    let index = u16::from_le_bytes([data[4], data[5]]);
+    Ok((data[0], data[1], value, index))
+}
+
+/// Wraps `port` to record every call to `path`, whole, so it can be loaded back with
+/// [`Transcript::load`]. The file is complete once the returned port is closed or dropped.
+pub fn record(port: Box<dyn MTKPort>, path: impl AsRef<Path>) -> Result<Box<dyn MTKPort>> {
+    Ok(Box::new(TracePort::create(port, path.as_ref(), None)?))
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    steps: VecDeque<Step>,
+    /// Bytes of the front step already read or written
+    offset: usize,
+}
+
+impl MockState {
+    /// Fails with the error step in front, if any. Like a real port, a transfer failing
+    /// after `done` bytes went through isn't reported as transient.
+    fn take_error(&mut self, done: usize) -> Result<()> {
+        if let Some(Step::Error(msg)) = self.steps.front() {
+            let err = Error::io(msg.clone());
+            self.steps.pop_front();
+            return Err(if done > 0 { cut_short(err) } else { err });
+        }
+        Ok(())
+    }
+
+    /// Takes the front step, failing if it's not what `call` expects
+    fn next(&mut self, call: &str, matches: impl Fn(&Step) -> bool) -> Result<Step> {
+        self.take_error(0)?;
+        match self.steps.front() {
+            Some(step) if matches(step) => Ok(self.steps.pop_front().unwrap()),
+            Some(step) => Err(Error::proto(format!(
+                "Mock port: host made {}, transcript expects {}",
+                call,
+                step.describe()
+            ))),
+            None => Err(ended(call)),
+        }
+    }
+
+    /// Consumes `len` bytes of the front step, which is a write or a read
+    fn advance(&mut self, len: usize) {
+        self.offset += len;
+        let step_len = match self.steps.front() {
+            Some(Step::Write(data) | Step::Read(data)) => data.len(),
+            _ => 0,
+        };
+        if self.offset >= step_len {
+            self.steps.pop_front();
+            self.offset = 0;
+        }
+    }
+}
+
+/// Transcript ran out, the device is gone as far as the host can tell
+fn ended(call: &str) -> Error {
+    Error::Io { kind: ErrorKind::NotConnected, msg: format!("Mock port: {} past the end", call) }
+}
+
+/// Port playing a [`Transcript`]. Clones share it, so one can be kept to check the whole
+/// transcript was played once the port is handed to a connection.
+#[derive(Debug, Clone)]
+pub struct MockMTKPort {
+    state: Arc<Mutex<MockState>>,
+    connection_type: ConnectionType,
+    usb_link: Option<UsbLink>,
+}
+
+impl MockMTKPort {
+    /// Plays `transcript` as a DA port, see [`Self::with_connection_type`]
+    pub fn new(transcript: Transcript) -> Self {
+        let state = MockState { steps: transcript.steps.into(), offset: 0 };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            connection_type: ConnectionType::Da,
+            usb_link: None,
+        }
+    }
+
+    pub fn with_connection_type(mut self, connection_type: ConnectionType) -> Self {
+        self.connection_type = connection_type;
+        self
+    }
+
+    /// Makes the port report `link`, like a USB backend would
+    pub fn with_usb_link(mut self, link: UsbLink) -> Self {
+        self.usb_link = Some(link);
+        self
+    }
+
+    /// Steps of the transcript not played yet, including one partly played
+    pub fn remaining(&self) -> Vec<Step> {
+        self.state.lock().unwrap().steps.iter().cloned().collect()
+    }
+
+    /// Whether the whole transcript was played
+    pub fn is_done(&self) -> bool {
+        self.state.lock().unwrap().steps.is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl MTKPort for MockMTKPort {
+    async fn open(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        let mut pos = 0;
+
+        while pos < buf.len() {
+            state.take_error(pos)?;
+            let offset = state.offset;
+            let data = match state.steps.front() {
+                Some(Step::Read(data)) => &data[offset..],
+                Some(step) => {
+                    return Err(Error::proto(format!(
+                        "Mock port: host read 0x{:X} bytes, transcript expects {}",
+                        buf.len(),
+                        step.describe()
+                    )));
+                }
+                None => return Err(ended("read")),
+            };
+
+            let len = data.len().min(buf.len() - pos);
+            buf[pos..pos + len].copy_from_slice(&data[..len]);
+            pos += len;
+            state.advance(len);
+        }
+
+        Ok(buf.len())
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut pos = 0;
+
+        while pos < buf.len() {
+            state.take_error(pos)?;
+            let offset = state.offset;
+            let expected = match state.steps.front() {
+                Some(Step::Write(data)) => &data[offset..],
+                Some(step) => {
+                    return Err(Error::proto(format!(
+                        "Mock port: host wrote {:02X?}, transcript expects {}",
+                        &buf[pos..],
+                        step.describe()
+                    )));
+                }
+                None => return Err(ended("write")),
+            };
+
+            let len = expected.len().min(buf.len() - pos);
+            if buf[pos..pos + len] != expected[..len] {
+                return Err(Error::proto(format!(
+                    "Mock port: host wrote {:02X?}, transcript expects {:02X?}",
+                    &buf[pos..pos + len],
+                    &expected[..len]
+                )));
+            }
+            pos += len;
+            state.advance(len);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn handshake(&mut self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.next("a handshake", |s| matches!(s, Step::Handshake))?;
+        Ok(())
+    }
+
+    fn get_connection_type(&self) -> ConnectionType {
+        self.connection_type
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        0
+    }
+
+    fn get_port_name(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        self.usb_link
+    }
+
+    /// The transcript goes on after a re-enumeration, as if the device came right back
+    async fn reopen(&mut self) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Only made by hand from a transcript
+    async fn find_device() -> Result<Option<Self>> {
+        Ok(None)
+    }
+
+    async fn ctrl_out(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        data: &[u8],
+    ) -> Result<()> {
+        let call = format!("a control out (request 0x{:02X})", request);
+        let expected = Step::CtrlOut { request_type, request, value, index, data: data.to_vec() };
+
+        let mut state = self.state.lock().unwrap();
+        state.next(&call, |s| *s == expected)?;
+        Ok(())
+    }
+
+    async fn ctrl_in(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        len: usize,
+    ) -> Result<Vec<u8>> {
+        let call = format!("a control in (request 0x{:02X})", request);
+        let matches = |s: &Step| {
+            matches!(s, Step::CtrlIn { request_type: t, request: r, value: v, index: i, data }
+                if (*t, *r, *v, *i) == (request_type, request, value, index) && data.len() <= len)
+        };
+
+        let mut state = self.state.lock().unwrap();
+        match state.next(&call, matches)? {
+            Step::CtrlIn { data, .. } => Ok(data),
+            _ => unreachable!(),
+        }
+    }
+}
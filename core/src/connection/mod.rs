@@ -3,22 +3,98 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 mod backend;
+#[cfg(feature = "tcp")]
+pub mod bridge;
+pub mod cancel;
 mod command;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod phases;
 pub mod port;
-use std::time::Duration;
-
-use log::{debug, error, info};
-use tokio::time::timeout;
-
+pub mod rate_limit;
+pub mod retry;
+pub mod stats;
+pub mod trace;
+pub mod transfer;
+pub mod watch;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, warn};
+use tokio::time::{sleep, timeout};
+
+use crate::connection::cancel::CancelToken;
 use crate::connection::command::Command;
-use crate::connection::port::{ConnectionType, MTKPort};
+use crate::connection::phases::PhaseTracker;
+use crate::connection::port::{ConnectionType, MTKPort, list_mtk_ports, open_mtk_port};
+use crate::connection::rate_limit::RateLimiter;
+use crate::connection::retry::{RetryPolicy, is_transient};
+use crate::connection::stats::{ConnStats, StatsSnapshot};
+use crate::connection::transfer::TransferConfig;
 use crate::error::{Error, Result};
 
+#[cfg(feature = "tcp")]
+pub use backend::TcpMTKPort;
+pub use port::{known_ports, register_port};
+
+/// How long the DA gets to start answering, unless set otherwise on the connection.
+pub const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a device dropping off the bus after booting the DA gets to come back
+pub const REENUMERATION_TIMEOUT: Duration = Duration::from_secs(5);
+/// Wait between two looks for the re-enumerated port
+const REENUMERATION_POLL: Duration = Duration::from_millis(250);
+
+/// Environment variable holding the baudrate of UART connections, see
+/// [`Connection::switch_baudrate`]
+pub const BAUDRATE_ENV: &str = "PENUMBRA_BAUDRATE";
+
+/// How long a DA switching USB speed gets to drop off the bus, before looking for it again
+const SPEED_SWITCH_SETTLE: Duration = Duration::from_secs(1);
+
+/// Status of the SLA challenge command when the BootROM doesn't ask for one
+const SLA_NOT_REQUIRED: u16 = 0x7017;
+
 #[derive(Debug)]
 pub struct Connection {
     pub port: Box<dyn MTKPort>,
     pub connection_type: ConnectionType,
     pub baudrate: u32,
+    /// Optional limit on the flash transfer rate
+    pub rate_limiter: Option<RateLimiter>,
+    /// Timing of the erase, write and verify phases of flash operations
+    pub phases: PhaseTracker,
+    /// Set to stop the running flash operation at the next chunk
+    pub cancel: CancelToken,
+    /// Chunk size overrides, bounded by the packet lengths the DA reported
+    pub transfer: TransferConfig,
+    /// How data chunks are retried after transient I/O errors
+    pub retry: RetryPolicy,
+    /// How long to wait for the DA to start a response, `None` waits forever.
+    /// Bulk data following the start of a response isn't bound by it.
+    pub response_timeout: Option<Duration>,
+    /// Bytes, chunks and retries of the data transfers
+    stats: ConnStats,
+}
+
+/// The baudrate from [`BAUDRATE_ENV`], if set to one
+pub(crate) fn env_baudrate() -> Option<u32> {
+    let value = std::env::var(BAUDRATE_ENV).ok()?;
+    match value.trim().parse() {
+        Ok(baudrate) if baudrate > 0 => Some(baudrate),
+        _ => {
+            warn!("Ignoring {}={}, not a baudrate", BAUDRATE_ENV, value);
+            None
+        }
+    }
+}
+
+/// Opens the DA port plugged at `location`, or any DA port when it's unknown
+async fn open_da_port(location: Option<&str>) -> Option<Box<dyn MTKPort>> {
+    let info = list_mtk_ports().await.into_iter().find(|p| {
+        p.connection_type == ConnectionType::Da
+            && (location.is_none() || p.location.as_deref() == location)
+    })?;
+    open_mtk_port(&info).await
 }
 
 impl Connection {
@@ -26,7 +102,183 @@ impl Connection {
         let connection_type = port.get_connection_type();
         let baudrate = port.get_baudrate();
 
-        Connection { port, connection_type, baudrate }
+        Connection {
+            port,
+            connection_type,
+            baudrate,
+            rate_limiter: None,
+            phases: PhaseTracker::default(),
+            cancel: CancelToken::default(),
+            transfer: TransferConfig::default(),
+            retry: RetryPolicy::default(),
+            response_timeout: Some(DEFAULT_RESPONSE_TIMEOUT),
+            stats: ConnStats::default(),
+        }
+    }
+
+    /// Counters of the data transfers since the last reset. The handle is shared with the
+    /// connection, so it can be cloned to follow operations in progress.
+    pub fn stats(&self) -> &ConnStats {
+        &self.stats
+    }
+
+    /// Resets the transfer counters, returning their values until now
+    pub fn reset_stats(&self) -> StatsSnapshot {
+        self.stats.take()
+    }
+
+    /// Limits flash transfers to `rate` bytes per second, or removes the limit if `None`.
+    pub fn set_rate_limit(&mut self, rate: Option<u64>) {
+        self.rate_limiter = rate.filter(|&r| r > 0).map(RateLimiter::new);
+    }
+
+    /// Waits as needed to keep flash transfers under the rate limit, if any.
+    /// Must only be called between chunks, never while the DA waits for data.
+    /// The bytes also count towards the running flash phase.
+    pub async fn throttle(&mut self, bytes: usize) {
+        self.phases.add_bytes(bytes as u64);
+        if let Some(limiter) = &mut self.rate_limiter {
+            limiter.throttle(bytes).await;
+        }
+    }
+
+    /// Reads the start of a DA response, like a packet header, failing if the DA doesn't
+    /// send it within [`Connection::response_timeout`], e.g. because it crashed.
+    pub async fn read_response(&mut self, buf: &mut [u8]) -> Result<()> {
+        let Some(limit) = self.response_timeout else {
+            self.port.read_exact(buf).await?;
+            return Ok(());
+        };
+
+        match timeout(limit, self.port.read_exact(buf)).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err(Error::conn("timeout waiting for DA response")),
+        }
+    }
+
+    /// Reads a packet of `len` bytes, in port reads of at most `chunk` bytes if given.
+    pub async fn read_packet(&mut self, len: usize, chunk: Option<usize>) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; len];
+        match chunk {
+            Some(chunk) => {
+                for part in data.chunks_mut(chunk) {
+                    self.read_chunk(part).await?;
+                }
+            }
+            None => self.read_chunk(&mut data).await?,
+        }
+        Ok(data)
+    }
+
+    /// Reads a data chunk, retrying transient errors as set by [`Connection::retry`]. Ports
+    /// only report those when none of the chunk went through, so it's re-issued whole.
+    /// If all attempts fail, the first error is returned.
+    pub async fn read_chunk(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut attempt = 0;
+        let mut first_err = None;
+        loop {
+            let started = Instant::now();
+            match self.port.read_exact(buf).await {
+                Ok(_) => {
+                    self.stats.add_rx(buf.len(), started.elapsed());
+                    return Ok(());
+                }
+                Err(e) if is_transient(&e) && attempt < self.retry.attempts => {
+                    let delay = self.retry.delay(attempt);
+                    warn!("Read of 0x{:X} bytes failed: {}, retrying in {:?}", buf.len(), e, delay);
+                    self.stats.add_retry(&e);
+                    first_err.get_or_insert(e);
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    self.stats.set_error(&e);
+                    return Err(first_err.unwrap_or(e));
+                }
+            }
+        }
+    }
+
+    /// Writes a data chunk, retrying transient errors as set by [`Connection::retry`]. Ports
+    /// only report those when none of the chunk went through, so it's re-issued whole.
+    /// If all attempts fail, the first error is returned.
+    pub async fn write_chunk(&mut self, data: &[u8]) -> Result<()> {
+        let mut attempt = 0;
+        let mut first_err = None;
+        loop {
+            let started = Instant::now();
+            match self.port.write_all(data).await {
+                Ok(()) => {
+                    self.stats.add_tx(data.len(), started.elapsed());
+                    return Ok(());
+                }
+                Err(e) if is_transient(&e) && attempt < self.retry.attempts => {
+                    let delay = self.retry.delay(attempt);
+                    let len = data.len();
+                    warn!("Write of 0x{:X} bytes failed: {}, retrying in {:?}", len, e, delay);
+                    self.stats.add_retry(&e);
+                    first_err.get_or_insert(e);
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    self.stats.set_error(&e);
+                    return Err(first_err.unwrap_or(e));
+                }
+            }
+        }
+    }
+
+    /// Reopens the device after it dropped off the bus and came back as the DA port
+    /// (e.g. `0E8D:2001`), which many devices do once DA2 runs. Only a DA port plugged
+    /// where the old one was is picked, when the backend knows where that is.
+    ///
+    /// The port keeps its wrappers, like the trace, and the protocol goes on where it was.
+    pub async fn reconnect_da(&mut self) -> Result<()> {
+        let location = self.port.get_location();
+        self.port.close().await.ok();
+
+        info!("Device dropped off the bus, waiting for the DA port...");
+        let deadline = Instant::now() + REENUMERATION_TIMEOUT;
+        loop {
+            let reconnected = match self.port.reopen().await {
+                Ok(true) => true,
+                Ok(false) => match open_da_port(location.as_deref()).await {
+                    Some(port) => {
+                        if let Some(port) = self.port.replace_inner(port) {
+                            self.port = port;
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                // Not back yet
+                Err(_) => false,
+            };
+
+            if reconnected {
+                info!("Reconnected to {}", self.port.get_port_name());
+                self.connection_type = ConnectionType::Da;
+                self.baudrate = self.port.get_baudrate();
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::conn(format!(
+                    "Device didn't come back as a DA port within {}s",
+                    REENUMERATION_TIMEOUT.as_secs()
+                )));
+            }
+            sleep(REENUMERATION_POLL).await;
+        }
+    }
+
+    /// Reopens the device once the DA re-enumerated to switch USB speed. Unlike
+    /// [`Connection::reconnect_da`], the old port is given time to go away first, since
+    /// the DA only drops off the bus after answering the switch.
+    pub async fn reconnect_after_speed_switch(&mut self) -> Result<()> {
+        sleep(SPEED_SWITCH_SETTLE).await;
+        self.reconnect_da().await
     }
 
     pub async fn write(&mut self, data: &[u8], size: usize) -> Result<Vec<u8>> {
@@ -59,6 +311,34 @@ impl Connection {
         Ok(())
     }
 
+    /// Asks the BootROM or preloader to move its UART to `baudrate`, then follows with the
+    /// port. Ports that aren't UARTs fail with [`Error::Unsupported`] before anything is sent.
+    pub async fn switch_baudrate(&mut self, baudrate: u32) -> Result<()> {
+        if !self.port.is_uart() {
+            return Err(Error::Unsupported(format!(
+                "{} is not a UART, its baudrate can't be changed",
+                self.port.get_port_name()
+            )));
+        }
+
+        debug!("Switching UART baudrate to {}", baudrate);
+        self.echo(&[Command::Uart1SetBaudrate as u8], 1).await?;
+        self.echo(&baudrate.to_be_bytes(), 4).await?;
+
+        let mut status = [0u8; 2];
+        self.port.read_exact(&mut status).await?;
+        let status = u16::from_be_bytes(status);
+        if status != 0 {
+            return Err(Error::Status { ctx: "Baudrate switch".into(), status: status as u32 });
+        }
+
+        self.port.set_baudrate(baudrate)?;
+        self.port.flush().await?;
+        self.baudrate = self.port.get_baudrate();
+        info!("UART running at {} baud", self.baudrate);
+        Ok(())
+    }
+
     pub async fn jump_da(&mut self, address: u32) -> Result<()> {
         debug!("Jump to DA at 0x{:08X}", address);
 
@@ -269,6 +549,79 @@ impl Connection {
         Ok(u32::from_be_bytes(config_bytes))
     }
 
+    /// Sends the Download Agent Auth file, which lets the BootROM verify a signed DA.
+    pub async fn send_auth(&mut self, auth: &[u8]) -> Result<()> {
+        self.echo(&[Command::SendAuth as u8], 1).await?;
+        self.echo(&(auth.len() as u32).to_be_bytes(), 4).await?;
+
+        let status = self.read_sla_status().await?;
+        if status > 0xFF {
+            return Err(Error::conn(format!("SendAuth failed with status: 0x{:04X}", status)));
+        }
+
+        self.port.write_all(auth).await?;
+
+        let mut checksum = [0u8; 2];
+        self.port.read_exact(&mut checksum).await?;
+        debug!("Received auth checksum: {:02X}{:02X}", checksum[0], checksum[1]);
+
+        let status = self.read_sla_status().await?;
+        if status != 0 {
+            error!("Auth file rejected with status: 0x{:04X}", status);
+            return Err(Error::conn("Auth file rejected"));
+        }
+
+        Ok(())
+    }
+
+    /// Starts the BROM SLA handshake, returning the challenge to sign,
+    /// or `None` if the BootROM doesn't need one.
+    pub async fn get_sla_challenge(&mut self) -> Result<Option<Vec<u8>>> {
+        self.echo(&[Command::SlaChallenge as u8], 1).await?;
+
+        let status = self.read_sla_status().await?;
+        if status == SLA_NOT_REQUIRED {
+            return Ok(None);
+        }
+        if status > 0xFF {
+            return Err(Error::conn(format!("SLA challenge failed with status: 0x{:04X}", status)));
+        }
+
+        let mut length_bytes = [0u8; 4];
+        self.port.read_exact(&mut length_bytes).await?;
+
+        let mut challenge = vec![0u8; u32::from_be_bytes(length_bytes) as usize];
+        self.port.read_exact(&mut challenge).await?;
+
+        Ok(Some(challenge))
+    }
+
+    /// Answers the challenge from [`Connection::get_sla_challenge`] with its signature
+    pub async fn send_sla_signature(&mut self, signature: &[u8]) -> Result<()> {
+        self.echo(&(signature.len() as u32).to_be_bytes(), 4).await?;
+
+        let status = self.read_sla_status().await?;
+        if status > 0xFF {
+            return Err(Error::conn(format!("SLA signature refused with status: 0x{:04X}", status)));
+        }
+
+        self.port.write_all(signature).await?;
+
+        let status = self.read_sla_status().await?;
+        if status != 0 {
+            error!("SLA signature rejected with status: 0x{:04X}", status);
+            return Err(Error::conn("BROM SLA signature rejected"));
+        }
+
+        Ok(())
+    }
+
+    async fn read_sla_status(&mut self) -> Result<u16> {
+        let mut status_bytes = [0u8; 2];
+        self.port.read_exact(&mut status_bytes).await?;
+        Ok(u16::from_be_bytes(status_bytes))
+    }
+
     pub async fn get_pl_capabilities(&mut self) -> Result<u32> {
         self.echo(&[Command::GetPlCap as u8], 1).await?;
 
@@ -307,4 +660,30 @@ impl Connection {
 
         Ok(data)
     }
+
+    /// Writes `values` to consecutive registers starting at `address`.
+    pub async fn write32(&mut self, address: u32, values: &[u32]) -> Result<()> {
+        self.echo(&[Command::Write32 as u8], 1).await?;
+        self.echo(&address.to_be_bytes(), 4).await?;
+        self.echo(&(values.len() as u32).to_be_bytes(), 4).await?;
+
+        let mut status_bytes = [0u8; 2];
+        self.port.read_exact(&mut status_bytes).await?;
+        let status = u16::from_be_bytes(status_bytes);
+        if status > 0xFF {
+            return Err(Error::Status { ctx: "Write32".into(), status: status as u32 });
+        }
+
+        for value in values {
+            self.echo(&value.to_be_bytes(), 4).await?;
+        }
+
+        self.port.read_exact(&mut status_bytes).await?;
+        let status = u16::from_be_bytes(status_bytes);
+        if status > 0xFF {
+            return Err(Error::Status { ctx: "Write32".into(), status: status as u32 });
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,170 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The stages of a flash operation, timed separately so that the time the DA spends
+/// erasing, which transfers no data, doesn't drag the transfer speed down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashPhase {
+    Erase,
+    Write,
+    Verify,
+}
+
+impl FlashPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlashPhase::Erase => "erase",
+            FlashPhase::Write => "write",
+            FlashPhase::Verify => "verify",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseTiming {
+    pub phase: FlashPhase,
+    pub elapsed: Duration,
+    /// Bytes transferred during the phase, 0 for erase
+    pub bytes: u64,
+}
+
+impl PhaseTiming {
+    /// Average transfer rate in bytes per second
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.bytes as f64 / secs } else { 0.0 }
+    }
+}
+
+/// Time spent in each phase of the operations since the last [`PhaseTracker::take`].
+/// Repeated phases, e.g. when flashing several partitions, are added up.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseStats {
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl PhaseStats {
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+
+    pub fn get(&self, phase: FlashPhase) -> Option<&PhaseTiming> {
+        self.phases.iter().find(|t| t.phase == phase)
+    }
+
+    fn add(&mut self, timing: PhaseTiming) {
+        match self.phases.iter_mut().find(|t| t.phase == timing.phase) {
+            Some(t) => {
+                t.elapsed += timing.elapsed;
+                t.bytes += timing.bytes;
+            }
+            None => self.phases.push(timing),
+        }
+    }
+}
+
+/// Formats the stats as "erase 1m02s, write 3m40s @ 21.0 MB/s, verify 1m10s"
+impl fmt::Display for PhaseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, timing) in self.phases.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", timing.phase.as_str(), format_duration(timing.elapsed))?;
+            if timing.phase == FlashPhase::Write && timing.bytes > 0 {
+                write!(f, " @ {:.1} MB/s", timing.rate() / 1_000_000.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0 => format!("{}ms", elapsed.as_millis()),
+        1..60 => format!("{}s", secs),
+        _ => format!("{}m{:02}s", secs / 60, secs % 60),
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    stats: PhaseStats,
+    /// The running phase, when it started and the bytes transferred so far
+    current: Option<(FlashPhase, Instant, u64)>,
+}
+
+impl TrackerState {
+    fn close(&mut self) {
+        if let Some((phase, started, bytes)) = self.current.take() {
+            self.stats.add(PhaseTiming { phase, elapsed: started.elapsed(), bytes });
+        }
+    }
+}
+
+/// Records the phase boundaries of flash operations.
+///
+/// Owned by the connection, which counts the bytes of each transfer against the running
+/// phase. Clones share the same state, so a UI can keep one to read the current phase and
+/// speed while the device is busy.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl PhaseTracker {
+    /// Ends the running phase, if any, and starts `phase`
+    pub fn enter(&self, phase: FlashPhase) {
+        let mut state = self.lock();
+        state.close();
+        state.current = Some((phase, Instant::now(), 0));
+    }
+
+    /// Ends the running phase, if any
+    pub fn finish(&self) {
+        self.lock().close();
+    }
+
+    /// Accounts for `bytes` transferred. Ignored outside of a phase, e.g. while reading.
+    pub fn add_bytes(&self, bytes: u64) {
+        if let Some((_, _, total)) = &mut self.lock().current {
+            *total += bytes;
+        }
+    }
+
+    pub fn current(&self) -> Option<FlashPhase> {
+        self.lock().current.map(|(phase, _, _)| phase)
+    }
+
+    /// Transfer rate of the running phase in bytes per second, `None` while erasing
+    /// or outside of a phase.
+    pub fn rate(&self) -> Option<f64> {
+        match self.lock().current {
+            Some((FlashPhase::Erase, _, _)) | None => None,
+            Some((phase, started, bytes)) => Some(PhaseTiming {
+                phase,
+                elapsed: started.elapsed(),
+                bytes,
+            }
+            .rate()),
+        }
+    }
+
+    /// Ends the running phase and returns the stats collected so far, resetting them.
+    pub fn take(&self) -> PhaseStats {
+        let mut state = self.lock();
+        state.close();
+        std::mem::take(&mut state.stats)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackerState> {
+        // The state is always left consistent, a poisoned lock is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
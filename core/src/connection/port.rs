@@ -3,13 +3,17 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use log::{debug, warn};
 
 use crate::connection::backend::*;
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// List of all ports available for connecting and what mode they refer to.
-/// Add more entries here for vendor specific ports
+/// Add more entries here for vendor specific ports, or at runtime with [`register_port`]
 #[rustfmt::skip]
 pub const KNOWN_PORTS: &[(u16, u16, ConnectionType)] = &[
     (0x0E8D, 0x0003, ConnectionType::Brom),      // Mediatek USB Port (BROM)
@@ -27,6 +31,59 @@ pub const KNOWN_PORTS: &[(u16, u16, ConnectionType)] = &[
     (0x0FCE, 0xD1DD, ConnectionType::Brom),      // Sony USB Port (BROM F3111)
 ];
 
+/// Ports added with [`register_port`], taking precedence over [`KNOWN_PORTS`]
+static USER_PORTS: RwLock<Vec<(u16, u16, ConnectionType)>> = RwLock::new(Vec::new());
+
+/// Adds a port to the ones scanned for, e.g. for a vendor VID/PID missing from
+/// [`KNOWN_PORTS`]. A pair already known, built-in or not, gets `connection_type` instead.
+pub fn register_port(vid: u16, pid: u16, connection_type: ConnectionType) -> Result<()> {
+    if vid == 0 || pid == 0 {
+        return Err(Error::penumbra(format!("Invalid port {:04x}:{:04x}", vid, pid)));
+    }
+
+    let mut ports = USER_PORTS.write()?;
+    match ports.iter_mut().find(|(pvid, ppid, _)| *pvid == vid && *ppid == pid) {
+        Some((_, _, ct)) if *ct == connection_type => {}
+        Some((_, _, ct)) => {
+            warn!(
+                "Port {:04x}:{:04x} registered again, as {} instead of {}",
+                vid,
+                pid,
+                connection_type.as_str(),
+                ct.as_str()
+            );
+            *ct = connection_type;
+        }
+        None => {
+            debug!("Registered port {:04x}:{:04x} as {}", vid, pid, connection_type.as_str());
+            ports.push((vid, pid, connection_type));
+        }
+    }
+    Ok(())
+}
+
+/// The built-in ports merged with the registered ones, which override the built-in type
+/// of a pair they share
+pub fn known_ports() -> Vec<(u16, u16, ConnectionType)> {
+    let user = USER_PORTS.read().map(|ports| ports.clone()).unwrap_or_default();
+    let builtin = KNOWN_PORTS
+        .iter()
+        .filter(|(vid, pid, _)| !user.iter().any(|(uvid, upid, _)| uvid == vid && upid == pid));
+
+    builtin.copied().chain(user.iter().copied()).collect()
+}
+
+/// The mode a port is in, `None` if it's not a known MTK port
+pub fn port_connection_type(vid: u16, pid: u16) -> Option<ConnectionType> {
+    let user = USER_PORTS.read().ok().and_then(|ports| {
+        ports.iter().find(|(pvid, ppid, _)| *pvid == vid && *ppid == pid).map(|p| p.2)
+    });
+
+    user.or_else(|| {
+        KNOWN_PORTS.iter().find(|(kvid, kpid, _)| *kvid == vid && *kpid == pid).map(|p| p.2)
+    })
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum ConnectionType {
     Brom,
@@ -34,11 +91,183 @@ pub enum ConnectionType {
     Da,
 }
 
+impl ConnectionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionType::Brom => "BROM",
+            ConnectionType::Preloader => "Preloader",
+            ConnectionType::Da => "DA",
+        }
+    }
+}
+
+impl FromStr for ConnectionType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "brom" => Ok(ConnectionType::Brom),
+            "preloader" => Ok(ConnectionType::Preloader),
+            "da" => Ok(ConnectionType::Da),
+            _ => Err(Error::penumbra(format!(
+                "Unknown connection type '{}', expected brom, preloader or da",
+                s
+            ))),
+        }
+    }
+}
+
+/// Speed negotiated by a USB link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsbSpeed {
+    Low,
+    Full,
+    High,
+    Super,
+    SuperPlus,
+}
+
+impl UsbSpeed {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsbSpeed::Low => "USB 1.1 Low-Speed",
+            UsbSpeed::Full => "USB 1.1 Full-Speed",
+            UsbSpeed::High => "USB 2.0 High-Speed",
+            UsbSpeed::Super => "USB 3.0 SuperSpeed",
+            UsbSpeed::SuperPlus => "USB 3.1 SuperSpeed+",
+        }
+    }
+
+    /// Rough bulk transfer rate a DA reaches on such a link, in bytes per second.
+    pub fn throughput(&self) -> u64 {
+        match self {
+            UsbSpeed::Low => 100 * 1024,
+            UsbSpeed::Full => 1024 * 1024,
+            UsbSpeed::High => 30 * 1024 * 1024,
+            UsbSpeed::Super => 150 * 1024 * 1024,
+            UsbSpeed::SuperPlus => 300 * 1024 * 1024,
+        }
+    }
+
+    /// Whether this is a USB 1.1 speed, which makes flashing crawl.
+    pub fn is_slow(&self) -> bool {
+        matches!(self, UsbSpeed::Low | UsbSpeed::Full)
+    }
+
+    /// Parses the speed reported by a DA, like `high-speed`.
+    pub fn from_da_name(name: &str) -> Option<Self> {
+        match name.trim_end_matches('\0').trim().to_ascii_lowercase().as_str() {
+            "low-speed" => Some(UsbSpeed::Low),
+            "full-speed" => Some(UsbSpeed::Full),
+            "high-speed" => Some(UsbSpeed::High),
+            "super-speed" => Some(UsbSpeed::Super),
+            "super-speed-plus" => Some(UsbSpeed::SuperPlus),
+            _ => None,
+        }
+    }
+}
+
+/// How a USB port is linked to the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsbLink {
+    /// `None` when the backend or the OS doesn't report it.
+    pub speed: Option<UsbSpeed>,
+    /// Hubs between the root port and the device, 0 when plugged into a root port.
+    pub hub_depth: usize,
+}
+
+impl UsbLink {
+    /// Builds a link from the port numbers leading to the device, one per hop.
+    pub fn new(speed: Option<UsbSpeed>, port_chain: &[u8]) -> Self {
+        Self { speed, hub_depth: port_chain.len().saturating_sub(1) }
+    }
+
+    /// Whether the link fell back to USB 1.1 speeds, which makes flashing crawl.
+    pub fn is_slow(&self) -> bool {
+        self.speed.is_some_and(|s| s.is_slow())
+    }
+}
+
+impl Display for UsbLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.speed.map_or("USB (unknown speed)", |s| s.as_str()))?;
+        match self.hub_depth {
+            0 => write!(f, " on a root port"),
+            1 => write!(f, " via hub (1 level)"),
+            depth => write!(f, " via hub ({} levels)", depth),
+        }
+    }
+}
+
+/// A MTK port found by [`probe_mtk_port`] or [`list_mtk_ports`], without opening or
+/// claiming it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortInfo {
+    pub connection_type: ConnectionType,
+    pub name: String,
+    /// Where the port is plugged in (e.g. `bus 3-2`), when the backend knows it.
+    pub location: Option<String>,
+    /// Speed and topology of the USB link, when the backend knows them.
+    pub usb_link: Option<UsbLink>,
+    /// USB vendor ID, 0 when unknown.
+    pub vid: u16,
+    /// USB product ID, 0 when unknown.
+    pub pid: u16,
+    /// USB serial number, when the device reports one.
+    pub serial: Option<String>,
+}
+
+impl PortInfo {
+    pub fn from_port(port: &dyn MTKPort) -> Self {
+        let (vid, pid) = port.get_usb_ids().unwrap_or_default();
+        Self {
+            connection_type: port.get_connection_type(),
+            name: port.get_port_name(),
+            location: port.get_location(),
+            usb_link: port.get_usb_link(),
+            vid,
+            pid,
+            serial: port.get_serial(),
+        }
+    }
+
+    /// Whether `query` names this port, by serial number, location or name.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        self.serial.as_deref() == Some(query)
+            || self.location.as_deref() == Some(query)
+            || self.name == query
+    }
+
+    /// Whether both describe the same physical port. The connection type is left out,
+    /// since it changes along with the device mode.
+    fn same_port(&self, other: &PortInfo) -> bool {
+        self.name == other.name && self.location == other.location && self.serial == other.serial
+    }
+}
+
+impl Display for PortInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.connection_type.as_str())?;
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        if let Some(serial) = &self.serial {
+            write!(f, ", serial {}", serial)?;
+        }
+        Ok(())
+    }
+}
+
 #[async_trait::async_trait]
 pub trait MTKPort: Send + Debug {
     async fn open(&mut self) -> Result<()>;
     async fn close(&mut self) -> Result<()>;
+    /// Fills `buf` whole. A transient [`Error::Io`] is only returned when none of `buf` was
+    /// read, see [`crate::connection::retry::cut_short`].
     async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize>;
+    /// Writes `buf` whole. A transient [`Error::Io`] is only returned when none of `buf` was
+    /// written, see [`crate::connection::retry::cut_short`].
     async fn write_all(&mut self, buf: &[u8]) -> Result<()>;
     async fn flush(&mut self) -> Result<()>;
 
@@ -46,11 +275,64 @@ pub trait MTKPort: Send + Debug {
     fn get_connection_type(&self) -> ConnectionType;
     fn get_baudrate(&self) -> u32;
     fn get_port_name(&self) -> String;
+    /// Physical location of the port, like `bus 3-2`. Only known by some backends.
+    fn get_location(&self) -> Option<String> {
+        None
+    }
+    /// Negotiated speed and hubs on the way to the port. Only known by USB backends.
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        None
+    }
+    /// USB vendor and product IDs. Only known by USB backends.
+    fn get_usb_ids(&self) -> Option<(u16, u16)> {
+        None
+    }
+    /// USB serial number, when the device reports one.
+    fn get_serial(&self) -> Option<String> {
+        None
+    }
+    /// Whether the port is a UART, whose baudrate and control lines can be set.
+    fn is_uart(&self) -> bool {
+        false
+    }
+    /// Changes the baudrate of a UART port. Others fail with [`Error::Unsupported`].
+    ///
+    /// [`Error::Unsupported`]: crate::error::Error::Unsupported
+    fn set_baudrate(&mut self, _baudrate: u32) -> Result<()> {
+        Err(Error::Unsupported(format!("{} has no baudrate", self.get_port_name())))
+    }
+    /// Drives the DTR and RTS lines of a UART port, which some boards wire to their reset
+    /// or download mode pins. Others fail with [`Error::Unsupported`].
+    ///
+    /// [`Error::Unsupported`]: crate::error::Error::Unsupported
+    fn set_control_lines(&mut self, _dtr: bool, _rts: bool) -> Result<()> {
+        Err(Error::Unsupported(format!("{} has no control lines", self.get_port_name())))
+    }
+    /// Hands `port` to a port wrapping another one, like the trace, to use in place of the
+    /// wrapped one once the device re-enumerated. Ports wrapping nothing give it back.
+    fn replace_inner(&mut self, port: Box<dyn MTKPort>) -> Option<Box<dyn MTKPort>> {
+        Some(port)
+    }
+    /// Opens the closed port again once the device re-enumerated, for ports finding the
+    /// device by themselves, like the bridge. The others return `false`, and get replaced
+    /// by a newly found port instead.
+    async fn reopen(&mut self) -> Result<bool> {
+        Ok(false)
+    }
 
     async fn find_device() -> Result<Option<Self>>
     where
         Self: Sized;
 
+    /// Every matching port, for when more than one device is plugged in. Backends that
+    /// can't enumerate only return what [`MTKPort::find_device`] finds.
+    async fn find_devices() -> Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        Ok(Self::find_device().await?.into_iter().collect())
+    }
+
     // Only for USB ports
     async fn ctrl_out(
         &mut self,
@@ -70,7 +352,22 @@ pub trait MTKPort: Send + Debug {
     ) -> Result<Vec<u8>>;
 }
 
+/// Finds a MTK port and opens it.
+///
+/// With the `tcp` feature, the port behind the bridge named by
+/// [`crate::connection::bridge::BRIDGE_ENV`] is used instead of the local ones.
 pub async fn find_mtk_port() -> Option<Box<dyn MTKPort>> {
+    #[cfg(feature = "tcp")]
+    if let Some(addr) = crate::connection::bridge::bridge_addr() {
+        let mut port = TcpMTKPort::new(addr);
+        return port.open().await.ok().map(|_| Box::new(port) as Box<dyn MTKPort>);
+    }
+
+    find_local_port().await
+}
+
+/// Finds and opens a port attached to this machine
+pub(crate) async fn find_local_port() -> Option<Box<dyn MTKPort>> {
     // Default NUSB backend
     #[cfg(not(any(feature = "libusb", feature = "serial")))]
     let port = UsbMTKPort::find_device().await;
@@ -95,3 +392,79 @@ pub async fn find_mtk_port() -> Option<Box<dyn MTKPort>> {
         Err(_) => None,
     }
 }
+
+/// Looks for a MTK port like [`find_mtk_port`], but without opening it.
+/// Meant for polling whether a device is plugged in, the port is left free for
+/// a later real connection.
+pub async fn probe_mtk_port() -> Option<PortInfo> {
+    #[cfg(feature = "tcp")]
+    if let Some(addr) = crate::connection::bridge::bridge_addr() {
+        let mut port = TcpMTKPort::new(addr);
+        port.probe().await.ok().flatten()?;
+        return Some(PortInfo::from_port(&port));
+    }
+
+    probe_local_port().await
+}
+
+/// Looks for a port attached to this machine, without opening it
+pub(crate) async fn probe_local_port() -> Option<PortInfo> {
+    #[cfg(not(any(feature = "libusb", feature = "serial")))]
+    let port = UsbMTKPort::find_device().await;
+
+    #[cfg(feature = "libusb")]
+    let port = UsbMTKPort::find_device().await;
+
+    #[cfg(feature = "serial")]
+    let port = SerialMTKPort::find_device().await;
+
+    let port = port.ok().flatten()?;
+    Some(PortInfo::from_port(&port))
+}
+
+async fn find_local_ports() -> Vec<Box<dyn MTKPort>> {
+    #[cfg(not(any(feature = "libusb", feature = "serial")))]
+    let ports = UsbMTKPort::find_devices().await;
+
+    #[cfg(feature = "libusb")]
+    let ports = UsbMTKPort::find_devices().await;
+
+    #[cfg(feature = "serial")]
+    let ports = SerialMTKPort::find_devices().await;
+
+    ports
+        .unwrap_or_default()
+        .into_iter()
+        .map(|port| Box::new(port) as Box<dyn MTKPort>)
+        .collect()
+}
+
+/// Lists every MTK port without opening them, so one can be picked when several
+/// devices are plugged in. Open the chosen one with [`open_mtk_port`].
+///
+/// With the `tcp` feature and a bridge set, only the port behind the bridge is listed.
+pub async fn list_mtk_ports() -> Vec<PortInfo> {
+    #[cfg(feature = "tcp")]
+    if crate::connection::bridge::bridge_addr().is_some() {
+        return probe_mtk_port().await.into_iter().collect();
+    }
+
+    find_local_ports().await.iter().map(|port| PortInfo::from_port(port.as_ref())).collect()
+}
+
+/// Opens the port described by `info`, as listed by [`list_mtk_ports`]. Returns `None`
+/// when it's gone or can't be opened.
+pub async fn open_mtk_port(info: &PortInfo) -> Option<Box<dyn MTKPort>> {
+    #[cfg(feature = "tcp")]
+    if crate::connection::bridge::bridge_addr().is_some() {
+        return find_mtk_port().await;
+    }
+
+    let mut port = find_local_ports()
+        .await
+        .into_iter()
+        .find(|port| PortInfo::from_port(port.as_ref()).same_port(info))?;
+
+    port.open().await.ok()?;
+    Some(port)
+}
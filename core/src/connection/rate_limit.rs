@@ -0,0 +1,47 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+
+/// A token bucket limiting the transfer rate of flash operations.
+///
+/// The bucket is only consumed between chunks, after the DA acknowledged them,
+/// so throttling never delays a chunk the DA is waiting for and can't trigger
+/// protocol timeouts.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// Maximum rate in bytes per second
+    rate: u64,
+    /// Available budget in bytes, can go negative when a chunk exceeds it
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u64) -> Self {
+        Self { rate, tokens: rate as f64, last_refill: Instant::now() }
+    }
+
+    /// The maximum rate in bytes per second
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Accounts for `bytes` transferred, and waits if the budget was exceeded.
+    pub async fn throttle(&mut self, bytes: usize) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        // Allow at most one second worth of burst
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.tokens -= bytes as f64;
+
+        if self.tokens < 0.0 {
+            sleep(Duration::from_secs_f64(-self.tokens / self.rate as f64)).await;
+        }
+    }
+}
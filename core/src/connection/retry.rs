@@ -0,0 +1,58 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::time::Duration;
+
+use crate::error::Error;
+
+/// Longest wait between two attempts, however many were made.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// How the data chunks of a transfer are retried after a transient I/O error, like a USB
+/// timeout or stall.
+///
+/// Only the raw port read or write of the failed chunk is re-issued, never a protocol
+/// exchange. Ports only report a transient error when none of the chunk went through (see
+/// [`cut_short`]), so a retry can't make the host and the DA disagree on what was sent.
+/// Backends that can't tell how much of a failed transfer went through, like nusb for
+/// writes, never report it as transient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first failure, 0 disables retrying
+    pub attempts: u32,
+    /// Wait before the first retry, doubled on each of the next ones
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { attempts: 3, backoff: Duration::from_millis(100) }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        RetryPolicy { attempts: 0, ..Default::default() }
+    }
+
+    /// Wait before the retry number `attempt`, starting from 0
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(1 << attempt.min(16)).min(MAX_BACKOFF)
+    }
+}
+
+/// Whether a port error is worth retrying. The backends report the recoverable ones, like
+/// timeouts, as [`Error::Io`], and the others as anything else. A port that went away is
+/// never retried.
+pub fn is_transient(err: &Error) -> bool {
+    matches!(err, Error::Io { .. }) && !err.is_disconnect()
+}
+
+/// Error of a port read or write that failed after some of its buffer may have gone
+/// through. Re-issuing the whole transfer would then lose or resend those bytes, so a
+/// transient error becomes an [`Error::Connection`], which isn't retried. A port that went
+/// away is kept as is, so it's still seen as a disconnect.
+pub fn cut_short(err: Error) -> Error {
+    if is_transient(&err) { Error::conn(format!("{}, transfer cut short", err)) } else { err }
+}
@@ -0,0 +1,146 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct Counters {
+    bytes_tx: AtomicU64,
+    bytes_rx: AtomicU64,
+    chunks: AtomicU64,
+    /// Time spent transferring the chunks, in microseconds
+    chunk_time: AtomicU64,
+    retries: AtomicU64,
+    /// Only written when a transfer fails, never in the hot path
+    last_error: Mutex<Option<String>>,
+}
+
+/// Traffic counters of a connection, to tell where a slow operation spends its time.
+///
+/// Counts the data chunks sent and received by [`Connection`](crate::connection::Connection),
+/// which carry nearly all of the traffic. Command packets aren't counted. Clones share the
+/// same counters, so a UI can keep one to follow operations in progress.
+#[derive(Debug, Clone, Default)]
+pub struct ConnStats {
+    counters: Arc<Counters>,
+}
+
+impl ConnStats {
+    /// Accounts for a chunk of `bytes` sent in `elapsed`
+    pub fn add_tx(&self, bytes: usize, elapsed: Duration) {
+        self.counters.bytes_tx.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.add_chunk(elapsed);
+    }
+
+    /// Accounts for a chunk of `bytes` received in `elapsed`
+    pub fn add_rx(&self, bytes: usize, elapsed: Duration) {
+        self.counters.bytes_rx.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.add_chunk(elapsed);
+    }
+
+    /// Accounts for a transfer retried after failing with `error`
+    pub fn add_retry(&self, error: &dyn fmt::Display) {
+        self.counters.retries.fetch_add(1, Ordering::Relaxed);
+        self.set_error(error);
+    }
+
+    /// Records the error a transfer failed with
+    pub fn set_error(&self, error: &dyn fmt::Display) {
+        *self.lock_error() = Some(error.to_string());
+    }
+
+    /// The counters as they are now
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let counters = &self.counters;
+        StatsSnapshot {
+            bytes_tx: counters.bytes_tx.load(Ordering::Relaxed),
+            bytes_rx: counters.bytes_rx.load(Ordering::Relaxed),
+            chunks: counters.chunks.load(Ordering::Relaxed),
+            chunk_time: Duration::from_micros(counters.chunk_time.load(Ordering::Relaxed)),
+            retries: counters.retries.load(Ordering::Relaxed),
+            last_error: self.lock_error().clone(),
+        }
+    }
+
+    /// Resets the counters, e.g. before starting the next operation
+    pub fn reset(&self) {
+        let counters = &self.counters;
+        counters.bytes_tx.store(0, Ordering::Relaxed);
+        counters.bytes_rx.store(0, Ordering::Relaxed);
+        counters.chunks.store(0, Ordering::Relaxed);
+        counters.chunk_time.store(0, Ordering::Relaxed);
+        counters.retries.store(0, Ordering::Relaxed);
+        *self.lock_error() = None;
+    }
+
+    /// Returns the counters and resets them
+    pub fn take(&self) -> StatsSnapshot {
+        let snapshot = self.snapshot();
+        self.reset();
+        snapshot
+    }
+
+    fn add_chunk(&self, elapsed: Duration) {
+        self.counters.chunks.fetch_add(1, Ordering::Relaxed);
+        self.counters.chunk_time.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn lock_error(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        // A poisoned lock still holds a valid message
+        self.counters.last_error.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Values of the [`ConnStats`] counters at some point
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    pub bytes_tx: u64,
+    pub bytes_rx: u64,
+    pub chunks: u64,
+    /// Total time spent transferring chunks
+    pub chunk_time: Duration,
+    pub retries: u64,
+    pub last_error: Option<String>,
+}
+
+impl StatsSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.chunks == 0 && self.retries == 0
+    }
+
+    /// Average time a chunk took to go through, `None` if there were none
+    pub fn avg_chunk_latency(&self) -> Option<Duration> {
+        let micros = self.chunk_time.as_micros() as u64;
+        (self.chunks > 0).then(|| Duration::from_micros(micros / self.chunks))
+    }
+}
+
+/// Formats the stats as "sent 1.2 MB, received 3.4 GB in 1234 chunks (avg 2.1ms), 3 retries"
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sent {}, received {} in {} chunks",
+            format_bytes(self.bytes_tx),
+            format_bytes(self.bytes_rx),
+            self.chunks
+        )?;
+        if let Some(latency) = self.avg_chunk_latency() {
+            write!(f, " (avg {:.1}ms)", latency.as_secs_f64() * 1000.0)?;
+        }
+        write!(f, ", {} retries", self.retries)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{} B", bytes),
+        1_000..1_000_000 => format!("{:.1} kB", bytes as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1} MB", bytes as f64 / 1e6),
+        _ => format!("{:.1} GB", bytes as f64 / 1e9),
+    }
+}
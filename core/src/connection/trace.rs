@@ -0,0 +1,434 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Capture of the raw traffic with the device, to debug DA issues from user reports.
+//!
+//! A trace file starts with [`TRACE_MAGIC`], followed by one record per port call: the
+//! [`TraceKind`] (u8), the time since the capture started in microseconds (u64 LE), the
+//! length of the transfer (u32 LE), the length of the data kept (u32 LE) and the data.
+//! Control transfers keep their setup (request type, request, value and index, both u16
+//! LE) in front of the data, and errors keep their message.
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Read;
+#[cfg(feature = "da-protocols")]
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+#[cfg(feature = "da-protocols")]
+use std::time::Instant;
+
+#[cfg(feature = "da-protocols")]
+use log::warn;
+
+#[cfg(feature = "da-protocols")]
+use crate::connection::port::{ConnectionType, MTKPort, UsbLink};
+#[cfg(feature = "da-protocols")]
+use crate::da::xflash::Cmd;
+use crate::error::{Error, Result};
+
+/// Start of every trace file
+pub const TRACE_MAGIC: &[u8; 8] = b"PNTRACE1";
+/// Bytes kept of each transfer unless set otherwise, enough for any command or XML
+/// message but not for the partition data around them
+pub const DEFAULT_TRACE_LIMIT: usize = 0x1000;
+/// Magic starting the packet headers of both DA protocols
+const DA_MAGIC: u32 = 0xFEEEEEEF;
+/// Bytes shown in hex by [`dump`] for each record
+const DUMP_HEX_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TraceKind {
+    /// Host to device
+    Write = 0,
+    /// Device to host
+    Read = 1,
+    CtrlOut = 2,
+    CtrlIn = 3,
+    Handshake = 4,
+    /// A port call failed, the data is the error message
+    Error = 5,
+}
+
+impl TraceKind {
+    fn from_u8(kind: u8) -> Option<Self> {
+        Some(match kind {
+            0 => TraceKind::Write,
+            1 => TraceKind::Read,
+            2 => TraceKind::CtrlOut,
+            3 => TraceKind::CtrlIn,
+            4 => TraceKind::Handshake,
+            5 => TraceKind::Error,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceKind::Write => ">> WRITE",
+            TraceKind::Read => "<< READ",
+            TraceKind::CtrlOut => ">> CTRL",
+            TraceKind::CtrlIn => "<< CTRL",
+            TraceKind::Handshake => "-- HANDSHAKE",
+            TraceKind::Error => "!! ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub kind: TraceKind,
+    /// Time since the capture started
+    pub time: Duration,
+    /// Length of the whole transfer, the data may only hold its start
+    pub len: usize,
+    pub data: Vec<u8>,
+}
+
+impl TraceRecord {
+    /// Whether the data was cut to the trace limit
+    pub fn is_truncated(&self) -> bool {
+        let setup = match self.kind {
+            TraceKind::CtrlOut | TraceKind::CtrlIn => 6,
+            _ => 0,
+        };
+        self.kind != TraceKind::Error && self.data.len() < self.len + setup
+    }
+}
+
+/// Records every call made on the port it wraps, see [`crate::DeviceBuilder::with_trace`]
+#[cfg(feature = "da-protocols")]
+#[derive(Debug)]
+pub(crate) struct TracePort {
+    inner: Box<dyn MTKPort>,
+    /// `None` once writing the trace failed, the port keeps working untraced
+    writer: Option<BufWriter<File>>,
+    start: Instant,
+    /// Bytes kept of each transfer, `None` keeps them whole
+    limit: Option<usize>,
+}
+
+#[cfg(feature = "da-protocols")]
+impl TracePort {
+    pub(crate) fn create(
+        inner: Box<dyn MTKPort>,
+        path: &Path,
+        limit: Option<usize>,
+    ) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(TRACE_MAGIC)?;
+
+        Ok(Self { inner, writer: Some(writer), start: Instant::now(), limit })
+    }
+
+    fn record(&mut self, kind: TraceKind, len: usize, head: &[u8], data: &[u8]) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+
+        let data = match self.limit {
+            Some(limit) => &data[..data.len().min(limit)],
+            None => data,
+        };
+        let time = self.start.elapsed().as_micros() as u64;
+
+        let mut entry = Vec::with_capacity(17 + head.len() + data.len());
+        entry.push(kind as u8);
+        entry.extend_from_slice(&time.to_le_bytes());
+        entry.extend_from_slice(&(len as u32).to_le_bytes());
+        entry.extend_from_slice(&((head.len() + data.len()) as u32).to_le_bytes());
+        entry.extend_from_slice(head);
+        entry.extend_from_slice(data);
+
+        // Errors are what the trace is read for, make sure they reach the file
+        let result = writer.write_all(&entry).and_then(|_| match kind {
+            TraceKind::Error => writer.flush(),
+            _ => Ok(()),
+        });
+        if let Err(e) = result {
+            warn!("Failed to write the connection trace, stopping it: {}", e);
+            self.writer = None;
+        }
+    }
+
+    fn record_error(&mut self, err: &Error) {
+        self.record(TraceKind::Error, 0, &[], err.to_string().as_bytes());
+    }
+
+    fn ctrl_setup(request_type: u8, request: u8, value: u16, index: u16) -> [u8; 6] {
+        let [v0, v1] = value.to_le_bytes();
+        let [i0, i1] = index.to_le_bytes();
+        [request_type, request, v0, v1, i0, i1]
+    }
+}
+
+#[cfg(feature = "da-protocols")]
+#[async_trait::async_trait]
+impl MTKPort for TracePort {
+    async fn open(&mut self) -> Result<()> {
+        self.inner.open().await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if let Some(writer) = &mut self.writer {
+            writer.flush().ok();
+        }
+        self.inner.close().await
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self.inner.read_exact(buf).await {
+            Ok(n) => {
+                self.record(TraceKind::Read, n, &[], &buf[..n]);
+                Ok(n)
+            }
+            Err(e) => {
+                self.record_error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        let result = self.inner.write_all(buf).await;
+        match &result {
+            Ok(()) => self.record(TraceKind::Write, buf.len(), &[], buf),
+            Err(e) => self.record_error(e),
+        }
+        result
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn handshake(&mut self) -> Result<()> {
+        let result = self.inner.handshake().await;
+        match &result {
+            Ok(()) => self.record(TraceKind::Handshake, 0, &[], &[]),
+            Err(e) => self.record_error(e),
+        }
+        result
+    }
+
+    fn get_connection_type(&self) -> ConnectionType {
+        self.inner.get_connection_type()
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        self.inner.get_baudrate()
+    }
+
+    fn get_port_name(&self) -> String {
+        self.inner.get_port_name()
+    }
+
+    fn get_location(&self) -> Option<String> {
+        self.inner.get_location()
+    }
+
+    fn get_usb_link(&self) -> Option<UsbLink> {
+        self.inner.get_usb_link()
+    }
+
+    fn get_usb_ids(&self) -> Option<(u16, u16)> {
+        self.inner.get_usb_ids()
+    }
+
+    fn get_serial(&self) -> Option<String> {
+        self.inner.get_serial()
+    }
+
+    fn is_uart(&self) -> bool {
+        self.inner.is_uart()
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> Result<()> {
+        self.inner.set_baudrate(baudrate)
+    }
+
+    fn set_control_lines(&mut self, dtr: bool, rts: bool) -> Result<()> {
+        self.inner.set_control_lines(dtr, rts)
+    }
+
+    fn replace_inner(&mut self, port: Box<dyn MTKPort>) -> Option<Box<dyn MTKPort>> {
+        if let Some(port) = self.inner.replace_inner(port) {
+            self.inner = port;
+        }
+        None
+    }
+
+    async fn reopen(&mut self) -> Result<bool> {
+        self.inner.reopen().await
+    }
+
+    /// Only wraps ports found through the other backends
+    async fn find_device() -> Result<Option<Self>> {
+        Ok(None)
+    }
+
+    async fn ctrl_out(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        data: &[u8],
+    ) -> Result<()> {
+        let result = self.inner.ctrl_out(request_type, request, value, index, data).await;
+        match &result {
+            Ok(()) => {
+                let setup = Self::ctrl_setup(request_type, request, value, index);
+                self.record(TraceKind::CtrlOut, data.len(), &setup, data);
+            }
+            Err(e) => self.record_error(e),
+        }
+        result
+    }
+
+    async fn ctrl_in(
+        &mut self,
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        len: usize,
+    ) -> Result<Vec<u8>> {
+        let result = self.inner.ctrl_in(request_type, request, value, index, len).await;
+        match &result {
+            Ok(data) => {
+                let setup = Self::ctrl_setup(request_type, request, value, index);
+                self.record(TraceKind::CtrlIn, data.len(), &setup, data);
+            }
+            Err(e) => self.record_error(e),
+        }
+        result
+    }
+}
+
+/// Reads back the records of a trace file written through
+/// [`crate::DeviceBuilder::with_trace`]. A record cut short at the end of the file, e.g.
+/// when the host crashed, is left out.
+pub fn read_trace(path: impl AsRef<Path>) -> Result<Vec<TraceRecord>> {
+    let mut raw = Vec::new();
+    File::open(path)?.read_to_end(&mut raw)?;
+
+    let Some(mut rest) = raw.strip_prefix(TRACE_MAGIC.as_slice()) else {
+        return Err(Error::penumbra("Not a penumbra trace file"));
+    };
+
+    let mut records = Vec::new();
+    while rest.len() >= 17 {
+        let kind = TraceKind::from_u8(rest[0])
+            .ok_or_else(|| Error::penumbra(format!("Unknown trace record kind {}", rest[0])))?;
+        let time = u64::from_le_bytes(rest[1..9].try_into().unwrap());
+        let len = u32::from_le_bytes(rest[9..13].try_into().unwrap()) as usize;
+        let kept = u32::from_le_bytes(rest[13..17].try_into().unwrap()) as usize;
+
+        let Some(data) = rest.get(17..17 + kept) else {
+            break;
+        };
+        records.push(TraceRecord {
+            kind,
+            time: Duration::from_micros(time),
+            len,
+            data: data.to_vec(),
+        });
+        rest = &rest[17 + kept..];
+    }
+
+    Ok(records)
+}
+
+/// Formats a trace file one record per line, with the start of the data in hex and
+/// what it means when recognized: DA packet headers, XFlash commands, XML commands and
+/// short text answers.
+pub fn dump(path: impl AsRef<Path>) -> Result<String> {
+    let mut out = String::new();
+
+    for record in read_trace(path)? {
+        let secs = record.time.as_secs_f64();
+        let _ = write!(out, "{:>12.6} {:<12}", secs, record.kind.as_str());
+
+        if record.kind == TraceKind::Error {
+            let _ = writeln!(out, " {}", String::from_utf8_lossy(&record.data));
+            continue;
+        }
+        if record.kind == TraceKind::Handshake {
+            out.push('\n');
+            continue;
+        }
+
+        let data = match record.kind {
+            TraceKind::CtrlOut | TraceKind::CtrlIn if record.data.len() >= 6 => {
+                let setup = &record.data[..6];
+                let value = u16::from_le_bytes([setup[2], setup[3]]);
+                let index = u16::from_le_bytes([setup[4], setup[5]]);
+                let _ = write!(
+                    out,
+                    " type 0x{:02X} req 0x{:02X} val 0x{:04X} idx 0x{:04X}",
+                    setup[0], setup[1], value, index
+                );
+                &record.data[6..]
+            }
+            _ => &record.data[..],
+        };
+
+        let _ = write!(out, " {:>8} bytes ", format!("0x{:X}", record.len));
+        for byte in data.iter().take(DUMP_HEX_LEN) {
+            let _ = write!(out, " {:02X}", byte);
+        }
+        if data.len() > DUMP_HEX_LEN || record.is_truncated() {
+            out.push_str(" ..");
+        }
+        if let Some(note) = annotate(record.kind, data) {
+            let _ = write!(out, "  [{}]", note);
+        }
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+/// What a transfer is, when it's recognizable
+fn annotate(kind: TraceKind, data: &[u8]) -> Option<String> {
+    if data.len() == 12 && data[..4] == DA_MAGIC.to_le_bytes() {
+        let data_type = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let len = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        return Some(format!("header, type {}, 0x{:X} bytes", data_type, len));
+    }
+
+    if let Some(cmd) = xml_command(data) {
+        return Some(cmd);
+    }
+
+    // Statuses read back could look like commands, only look at what the host sent
+    #[cfg(feature = "da-protocols")]
+    if kind == TraceKind::Write
+        && data.len() == 4
+        && let Ok(cmd) = Cmd::try_from(u32::from_le_bytes(data.try_into().unwrap()))
+    {
+        return Some(format!("{:?}", cmd));
+    }
+    #[cfg(not(feature = "da-protocols"))]
+    let _ = kind;
+
+    let text = std::str::from_utf8(data).ok()?;
+    let printable = text.chars().all(|c| c.is_ascii_graphic() || c == ' ');
+    (printable && !text.is_empty() && text.len() <= 64).then(|| format!("\"{}\"", text))
+}
+
+/// Name of the XML command in `data`, like `CMD:DOWNLOAD-FILE`
+fn xml_command(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let start = text.find("CMD:")?;
+    let name: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '_'))
+        .collect();
+
+    Some(name)
+}
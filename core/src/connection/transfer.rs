@@ -0,0 +1,44 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+/// Chunk size used when neither the user nor the DA picked one.
+pub const DEFAULT_CHUNK: usize = 0x8000;
+
+/// Overrides of the transfer chunk sizes, and whether written packets are checksummed.
+///
+/// The DAs report the largest packets they accept and send, which are used by default.
+/// An override can only lower them: anything above what the DA reported is clamped,
+/// so a too large value never makes the DA drop data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferConfig {
+    /// Largest single read from the port while receiving a packet
+    pub read_chunk: Option<usize>,
+    /// Largest packet sent to the DA
+    pub write_chunk: Option<usize>,
+    /// Have XML DAs check every packet of a download against its checksum, so a corrupt
+    /// transfer fails right away instead of at boot. Slows the flash slightly.
+    /// Only applies when set before the DA is uploaded, XFlash DAs always check them.
+    pub checksum: bool,
+}
+
+impl TransferConfig {
+    /// Size of the packets sent to the DA, given the write packet length it reported
+    pub fn write_chunk(&self, reported: Option<usize>) -> usize {
+        Self::negotiate(self.write_chunk, reported).unwrap_or(DEFAULT_CHUNK)
+    }
+
+    /// Size of the port reads, given the read packet length the DA reported.
+    /// `None` reads whole packets at once.
+    pub fn read_chunk(&self, reported: Option<usize>) -> Option<usize> {
+        Self::negotiate(self.read_chunk, reported)
+    }
+
+    fn negotiate(wanted: Option<usize>, reported: Option<usize>) -> Option<usize> {
+        match (wanted.filter(|&c| c > 0), reported.filter(|&r| r > 0)) {
+            (Some(wanted), Some(reported)) => Some(wanted.min(reported)),
+            (wanted, reported) => wanted.or(reported),
+        }
+    }
+}
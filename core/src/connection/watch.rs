@@ -0,0 +1,148 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Notifications of MTK ports being plugged in and out, see [`watch_mtk_ports`].
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout};
+
+use crate::connection::port::{PortInfo, list_mtk_ports};
+
+/// How often ports are listed when the OS doesn't report USB devices coming and going
+const POLL_INTERVAL: Duration = Duration::from_millis(700);
+/// How long to wait for a hotplug event before checking whether the watch was dropped
+const HOTPLUG_IDLE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortEvent {
+    /// A port showed up, or was already plugged in when the watch started.
+    Connected(PortInfo),
+    /// A port went away, with the info it was listed with.
+    Disconnected(PortInfo),
+}
+
+/// Stream of [`PortEvent`]s returned by [`watch_mtk_ports`]. Watching stops once dropped.
+pub struct PortWatch {
+    events: mpsc::UnboundedReceiver<PortEvent>,
+}
+
+impl PortWatch {
+    /// Waits for the next event, for callers not using stream combinators.
+    pub async fn next(&mut self) -> Option<PortEvent> {
+        self.events.recv().await
+    }
+}
+
+impl Stream for PortWatch {
+    type Item = PortEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<PortEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// Watches MTK ports being plugged in and out. Ports already plugged in are reported
+/// first, as [`PortEvent::Connected`].
+///
+/// Ports are listed again whenever the OS reports a USB device coming or going, when
+/// the nusb backend supports it. Otherwise, e.g. with the serial backend or through a
+/// bridge, they are polled every 700 ms. Must be called from a tokio runtime.
+pub fn watch_mtk_ports() -> PortWatch {
+    let (tx, events) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut hotplug = hotplug_notifications();
+        let mut known: Vec<PortInfo> = Vec::new();
+        let mut relist = true;
+
+        while !tx.is_closed() {
+            if relist {
+                let ports = list_mtk_ports().await;
+                let gone = known.iter().filter(|p| !ports.contains(p));
+                let new = ports.iter().filter(|p| !known.contains(p));
+                let events: Vec<PortEvent> = gone
+                    .cloned()
+                    .map(PortEvent::Disconnected)
+                    .chain(new.cloned().map(PortEvent::Connected))
+                    .collect();
+
+                for event in events {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+                known = ports;
+            }
+
+            let Some(notifications) = hotplug.as_mut() else {
+                sleep(POLL_INTERVAL).await;
+                relist = true;
+                continue;
+            };
+
+            relist = match timeout(HOTPLUG_IDLE, notifications.recv()).await {
+                Ok(Some(())) => {
+                    // A device often comes with a burst of events, list the ports once
+                    while notifications.try_recv().is_ok() {}
+                    true
+                }
+                Ok(None) => {
+                    hotplug = None;
+                    true
+                }
+                Err(_) => false,
+            };
+        }
+    });
+
+    PortWatch { events }
+}
+
+/// Notifies every USB device plugged in or out, or `None` when the backend or the OS
+/// can't report them and ports must be polled instead.
+#[cfg(all(
+    feature = "nusb",
+    not(any(feature = "libusb", feature = "serial")),
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+fn hotplug_notifications() -> Option<mpsc::UnboundedReceiver<()>> {
+    // Local hotplug events say nothing about the device behind a bridge
+    #[cfg(feature = "tcp")]
+    if crate::connection::bridge::bridge_addr().is_some() {
+        return None;
+    }
+
+    let mut watch = match nusb::watch_devices() {
+        Ok(watch) => watch,
+        Err(e) => {
+            log::debug!("USB hotplug unavailable, polling ports instead: {}", e);
+            return None;
+        }
+    };
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while std::future::poll_fn(|cx| Pin::new(&mut watch).poll_next(cx)).await.is_some() {
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Some(rx)
+}
+
+#[cfg(not(all(
+    feature = "nusb",
+    not(any(feature = "libusb", feature = "serial")),
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+)))]
+fn hotplug_notifications() -> Option<mpsc::UnboundedReceiver<()>> {
+    None
+}
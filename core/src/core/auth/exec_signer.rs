@@ -0,0 +1,138 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::core::auth::{SignPurpose, SignRequest, Signer};
+use crate::error::{Error, Result};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A signer running an external command, for keys kept in an HSM or behind a remote service.
+///
+/// The command gets the challenge and the key material (the DA2 image for DA SLA) as hex, one
+/// per line on stdin, and must print the signature as hex on stdout. These placeholders are
+/// replaced in its arguments: `{purpose}` (`brom` or `da`), `{challenge}`, `{soc_id}`
+/// and `{hrid}`.
+///
+/// It isn't registered by default, see [`AuthManager::register_signer`](super::AuthManager).
+/// The command is killed once the timeout is reached, or if the signing is dropped.
+pub struct ExecSigner {
+    name: String,
+    program: String,
+    args: Vec<String>,
+    purposes: Vec<SignPurpose>,
+    timeout: Duration,
+}
+
+impl ExecSigner {
+    /// Runs `program` for both BROM and DA SLA, killing it after 30 seconds.
+    /// It's called `exec:<program>` unless renamed with [`ExecSigner::with_name`].
+    pub fn new(program: impl Into<String>) -> Self {
+        let program = program.into();
+        ExecSigner {
+            name: format!("exec:{program}"),
+            program,
+            args: Vec::new(),
+            purposes: vec![SignPurpose::BromSla, SignPurpose::DaSla],
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Builds the signer from a command line, split on whitespace
+    pub fn from_command_line(command: &str) -> Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or_else(|| Error::penumbra("Empty signer command"))?;
+        Ok(Self::new(program).with_args(parts))
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Limits the signer to these challenges
+    pub fn with_purposes(mut self, purposes: &[SignPurpose]) -> Self {
+        self.purposes = purposes.to_vec();
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn expand_arg(arg: &str, req: &SignRequest) -> String {
+        let purpose = match req.purpose {
+            SignPurpose::BromSla => "brom",
+            SignPurpose::DaSla => "da",
+        };
+        arg.replace("{purpose}", purpose)
+            .replace("{challenge}", &hex::encode(&req.data.rnd))
+            .replace("{soc_id}", &hex::encode(&req.data.soc_id))
+            .replace("{hrid}", &hex::encode(&req.data.hrid))
+    }
+}
+
+#[async_trait]
+impl Signer for ExecSigner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn can_sign(&self, req: &SignRequest) -> bool {
+        self.purposes.contains(&req.purpose)
+    }
+
+    async fn sign(&self, req: &SignRequest) -> Result<Vec<u8>> {
+        let args: Vec<String> = self.args.iter().map(|a| Self::expand_arg(a, req)).collect();
+        debug!("Running signer: {} {}", self.program, args.join(" "));
+
+        let mut child = Command::new(&self.program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // The input is written while waiting for the output, so a command reading and writing
+        // at once can't block
+        let mut stdin = child.stdin.take().unwrap();
+        let input = format!("{}\n{}\n", hex::encode(&req.data.rnd), hex::encode(&req.pubk_mod));
+        let write_input = async move {
+            // The command may exit without reading its input, that's its call
+            stdin.write_all(input.as_bytes()).await.ok();
+        };
+        let run = async { tokio::join!(write_input, child.wait_with_output()).1 };
+
+        let output = timeout(self.timeout, run).await.map_err(|_| {
+            Error::penumbra(format!("Signer '{}' timed out after {:?}", self.program, self.timeout))
+        })??;
+
+        if !output.status.success() {
+            let status = output.status;
+            return Err(Error::penumbra(format!("Signer '{}' failed: {}", self.program, status)));
+        }
+
+        hex::decode(String::from_utf8_lossy(&output.stdout).trim())
+            .map_err(|_| Error::penumbra(format!("Signer '{}' printed invalid hex", self.program)))
+    }
+}
@@ -2,7 +2,12 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
 use async_trait::async_trait;
+use log::{info, warn};
 use num_bigint::BigUint;
 
 use crate::core::auth::keys::SLA_KEYS;
@@ -15,14 +20,18 @@ pub struct LocalKeyring {
     /// The private key used for signing.
     /// In MTK SLA flow, signing is performed by encrypting data with the private key.
     /// The corresponding public key is used for verification.
-    keys: Vec<RsaPrivateKey>,
+    keys: RwLock<Vec<RsaPrivateKey>>,
 }
 
 #[async_trait]
 impl Signer for LocalKeyring {
+    fn name(&self) -> &str {
+        "local-keyring"
+    }
+
     async fn sign(&self, req: &SignRequest) -> Result<Vec<u8>> {
-        let key = self
-            .keys
+        let keys = self.keys.read()?;
+        let key = keys
             .iter()
             .find(|k| contains_bytes(&req.pubk_mod, &k.n().to_bytes_be()) != HEX_NOT_FOUND)
             .ok_or_else(|| Error::penumbra("No matching key found"))?;
@@ -32,9 +41,10 @@ impl Signer for LocalKeyring {
     }
 
     fn can_sign(&self, req: &SignRequest) -> bool {
-        self.keys
-            .iter()
-            .any(|k| contains_bytes(&req.pubk_mod, &k.n().to_bytes_be()) != HEX_NOT_FOUND)
+        let Ok(keys) = self.keys.read() else {
+            return false;
+        };
+        keys.iter().any(|k| contains_bytes(&req.pubk_mod, &k.n().to_bytes_be()) != HEX_NOT_FOUND)
     }
 }
 
@@ -52,7 +62,53 @@ impl LocalKeyring {
             })
             .collect();
 
-        LocalKeyring { keys }
+        LocalKeyring { keys: RwLock::new(keys) }
+    }
+
+    /// Adds a private key from a PEM file (`RSA PRIVATE KEY` or `PRIVATE KEY`)
+    pub fn add_key_from_pem(&self, path: impl AsRef<Path>) -> Result<()> {
+        let pem = fs::read_to_string(path)?;
+        self.add_key(RsaPrivateKey::from_pem(&pem)?)
+    }
+
+    /// Adds a DER encoded private key, PKCS#1 or PKCS#8
+    pub fn add_key_from_der(&self, der: &[u8]) -> Result<()> {
+        self.add_key(RsaPrivateKey::from_der(der)?)
+    }
+
+    /// Adds every `.pem` and `.der` key of `dir`, skipping the ones that can't be parsed.
+    /// Returns how many were added.
+    pub fn add_keys_from_dir(&self, dir: impl AsRef<Path>) -> usize {
+        let dir = dir.as_ref();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+
+        let mut added = 0;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let result = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("pem") => self.add_key_from_pem(&path),
+                Some("der") => fs::read(&path)
+                    .map_err(Error::from)
+                    .and_then(|der| self.add_key_from_der(&der)),
+                _ => continue,
+            };
+
+            match result {
+                Ok(()) => added += 1,
+                Err(e) => warn!("Skipping SLA key {}: {}", path.display(), e),
+            }
+        }
+
+        if added > 0 {
+            info!("Loaded {} SLA key(s) from {}", added, dir.display());
+        }
+        added
+    }
+
+    fn add_key(&self, key: RsaPrivateKey) -> Result<()> {
+        self.keys.write()?.push(key);
+        Ok(())
     }
 }
 
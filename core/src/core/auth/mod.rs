@@ -2,10 +2,21 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+#[cfg(feature = "usb")]
+mod exec_signer;
 #[cfg(not(feature = "no_localslakeyring"))]
 mod keys;
 #[cfg(not(feature = "no_localslakeyring"))]
 pub mod local_keyring;
 mod sla;
 
-pub use sla::{AuthManager, SignData, SignPurpose, SignRequest, Signer};
+#[cfg(feature = "usb")]
+pub use exec_signer::ExecSigner;
+pub use sla::{
+    AuthManager,
+    BUILTIN_SIGNER_PRIORITY,
+    SignData,
+    SignPurpose,
+    SignRequest,
+    Signer,
+};
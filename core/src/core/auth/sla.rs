@@ -31,12 +31,25 @@ pub struct SignRequest {
 
 #[async_trait]
 pub trait Signer: Send + Sync {
+    /// Identifies the signer in [`AuthManager::list_signers`], unregistering and `sign_with`
+    fn name(&self) -> &str;
     fn can_sign(&self, req: &SignRequest) -> bool;
     async fn sign(&self, req: &SignRequest) -> Result<Vec<u8>>;
 }
 
+/// Priority of the built-in signers, register with a higher one to be tried first
+pub const BUILTIN_SIGNER_PRIORITY: i32 = 0;
+
+struct RegisteredSigner {
+    priority: i32,
+    signer: Arc<dyn Signer>,
+}
+
 pub struct AuthManager {
-    signers: RwLock<Vec<Arc<dyn Signer>>>,
+    /// Highest priority first, in registration order for the same priority
+    signers: RwLock<Vec<RegisteredSigner>>,
+    #[cfg(not(feature = "no_localslakeyring"))]
+    local_keyring: Arc<LocalKeyring>,
 }
 
 static INSTANCE: OnceLock<AuthManager> = OnceLock::new();
@@ -46,26 +59,66 @@ impl AuthManager {
     pub fn get() -> &'static AuthManager {
         INSTANCE.get_or_init(|| {
             #[allow(unused_mut)]
-            let mut default_signers: Vec<Arc<dyn Signer>> = Vec::new();
+            let mut default_signers: Vec<RegisteredSigner> = Vec::new();
 
             #[cfg(not(feature = "no_localslakeyring"))]
-            {
-                let local_keyring = Arc::new(LocalKeyring::new());
-                default_signers.push(local_keyring);
+            let local_keyring = Arc::new(LocalKeyring::new());
+            #[cfg(not(feature = "no_localslakeyring"))]
+            default_signers.push(RegisteredSigner {
+                priority: BUILTIN_SIGNER_PRIORITY,
+                signer: local_keyring.clone(),
+            });
+
+            AuthManager {
+                signers: RwLock::new(default_signers),
+                #[cfg(not(feature = "no_localslakeyring"))]
+                local_keyring,
             }
-
-            AuthManager { signers: RwLock::new(default_signers) }
         })
     }
 
-    /// Registers a new signer to be available for signing requests.
+    /// The built-in keyring, to add user provided SLA keys to.
+    #[cfg(not(feature = "no_localslakeyring"))]
+    pub fn local_keyring(&self) -> &LocalKeyring {
+        &self.local_keyring
+    }
+
+    /// Registers a new signer to be available for signing requests, after the built-in ones.
     pub fn register_signer(&self, signer: Arc<dyn Signer>) -> Result<()> {
+        self.register_signer_with_priority(signer, BUILTIN_SIGNER_PRIORITY)
+    }
+
+    /// Registers a signer, tried before the ones with a lower priority
+    /// and after the ones already registered with the same.
+    pub fn register_signer_with_priority(
+        &self,
+        signer: Arc<dyn Signer>,
+        priority: i32,
+    ) -> Result<()> {
         let mut signers = self.signers.write()?;
-        signers.push(signer);
+        let index = signers.iter().position(|s| s.priority < priority).unwrap_or(signers.len());
+        signers.insert(index, RegisteredSigner { priority, signer });
 
         Ok(())
     }
 
+    /// Removes the signers called `name`, returning whether there was any.
+    pub fn unregister_signer(&self, name: &str) -> Result<bool> {
+        let mut signers = self.signers.write()?;
+        let count = signers.len();
+        signers.retain(|s| s.signer.name() != name);
+
+        Ok(signers.len() != count)
+    }
+
+    /// The names of the registered signers, in the order they are tried.
+    pub fn list_signers(&self) -> Vec<String> {
+        match self.signers.read() {
+            Ok(signers) => signers.iter().map(|s| s.signer.name().to_string()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     /// Return whether any of the registered signers can sign the given request.
     pub fn can_sign(&self, req: &SignRequest) -> bool {
         let signers = match self.signers.read() {
@@ -73,19 +126,33 @@ impl AuthManager {
             Err(_) => return false,
         };
 
-        signers.iter().any(|signer| signer.can_sign(req))
+        signers.iter().any(|s| s.signer.can_sign(req))
     }
 
     /// Signs the given request using the first capable signer.
     pub async fn sign(&self, req: &SignRequest) -> Result<Vec<u8>> {
-        let signer = {
-            let list = self.signers.read()?;
-            list.iter().find(|s| s.can_sign(req)).cloned()
-        };
+        let signer = self.find_signer(|s| s.can_sign(req))?;
 
         match signer {
             Some(s) => s.sign(req).await,
             None => Err(Error::penumbra("Could not find any signer")),
         }
     }
+
+    /// Signs the given request with the signer called `name`, even if it doesn't
+    /// claim to handle it.
+    pub async fn sign_with(&self, name: &str, req: &SignRequest) -> Result<Vec<u8>> {
+        let signer = self.find_signer(|s| s.name() == name)?;
+
+        match signer {
+            Some(s) => s.sign(req).await,
+            None => Err(Error::penumbra(format!("No signer called '{}'", name))),
+        }
+    }
+
+    /// Clones the signer out, so the lock isn't held while it signs
+    fn find_signer(&self, pred: impl Fn(&dyn Signer) -> bool) -> Result<Option<Arc<dyn Signer>>> {
+        let signers = self.signers.read()?;
+        Ok(signers.iter().find(|s| pred(s.signer.as_ref())).map(|s| s.signer.clone()))
+    }
 }
@@ -0,0 +1,72 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Names of MediaTek chips, by the hardware code BROM and the preloader report.
+
+/// A known chip. New chips only need a new entry in [`CHIPS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChipInfo {
+    pub hw_code: u16,
+    /// Marketing name, like `MT6768`
+    pub name: &'static str,
+    /// Base of the watchdog (TOPRGU) registers
+    pub wdt_base: u32,
+}
+
+/// Known chips, sorted by hardware code.
+/// Codes and watchdog bases come from mtkclient's chip configurations.
+pub const CHIPS: &[ChipInfo] = &[
+    ChipInfo { hw_code: 0x0279, name: "MT6797", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0321, name: "MT6735", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0326, name: "MT6755", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0335, name: "MT6737", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0337, name: "MT6753", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0551, name: "MT6757", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0562, name: "MT6799", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0633, name: "MT6570", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0688, name: "MT6758", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0690, name: "MT6763", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0699, name: "MT6739", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0707, name: "MT6768", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0717, name: "MT6761", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0725, name: "MT6779", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0766, name: "MT6765", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0788, name: "MT6771", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0813, name: "MT6785", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0816, name: "MT6885", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0886, name: "MT6873", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0950, name: "MT6893", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0959, name: "MT6877", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0989, name: "MT6833", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x0996, name: "MT6853", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x6572, name: "MT6572", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x6580, name: "MT6580", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x6582, name: "MT6582", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x6589, name: "MT6589", wdt_base: 0x10000000 },
+    ChipInfo { hw_code: 0x6592, name: "MT6592", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x6595, name: "MT6595", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x8127, name: "MT8127", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x8163, name: "MT8163", wdt_base: 0x10007000 },
+    ChipInfo { hw_code: 0x8173, name: "MT8173", wdt_base: 0x10007000 },
+];
+
+/// Looks up a chip by hardware code
+pub fn chip_info(hw_code: u16) -> Option<&'static ChipInfo> {
+    CHIPS.iter().find(|chip| chip.hw_code == hw_code)
+}
+
+/// Returns the marketing name of a chip, if known
+pub fn chip_name(hw_code: u16) -> Option<&'static str> {
+    chip_info(hw_code).map(|chip| chip.name)
+}
+
+/// Returns the watchdog mode register of a chip, if known, and the value turning the
+/// watchdog off (the 0x22 key with the enable bit cleared).
+pub fn watchdog(hw_code: u16) -> Option<(u32, u32)> {
+    chip_info(hw_code).map(|chip| match chip.wdt_base {
+        0x10007000 => (chip.wdt_base, 0x22000064),
+        base => (base, 0x22000000),
+    })
+}
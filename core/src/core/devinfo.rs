@@ -2,11 +2,15 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::fmt;
 use std::sync::Arc;
 
+use log::warn;
 use tokio::sync::RwLock;
 
-use crate::core::storage::{Partition, Storage};
+use crate::connection::port::UsbSpeed;
+use crate::core::chipdb;
+use crate::core::storage::{GptDiff, GptReport, GptSource, Partition, Storage};
 
 /// Safe wrapper around device information with async read/write access.
 #[derive(Clone, Default)]
@@ -14,6 +18,74 @@ pub struct DeviceInfo {
     inner: Arc<RwLock<DevInfoData>>,
 }
 
+/// Security settings of the chip, decoded from the target config the BootROM or
+/// preloader reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TargetConfig {
+    /// Secure boot, images must be signed
+    pub sbc: bool,
+    /// Serial link authentication, the host must answer a challenge
+    pub sla: bool,
+    /// Download agent authentication, the DA must be signed
+    pub daa: bool,
+    /// Software JTAG
+    pub swjtag: bool,
+    pub epp: bool,
+    /// The root certificate must be provided
+    pub root_cert_required: bool,
+    pub mem_preserve: bool,
+}
+
+impl TargetConfig {
+    const SBC: u32 = 0x1;
+    const SLA: u32 = 0x2;
+    const DAA: u32 = 0x4;
+    const EPP: u32 = 0x8;
+    const ROOT_CERT_REQUIRED: u32 = 0x10;
+    const SWJTAG: u32 = 0x20;
+    const MEM_PRESERVE: u32 = 0x40;
+
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            sbc: raw & Self::SBC != 0,
+            sla: raw & Self::SLA != 0,
+            daa: raw & Self::DAA != 0,
+            swjtag: raw & Self::SWJTAG != 0,
+            epp: raw & Self::EPP != 0,
+            root_cert_required: raw & Self::ROOT_CERT_REQUIRED != 0,
+            mem_preserve: raw & Self::MEM_PRESERVE != 0,
+        }
+    }
+
+    fn flags(&self) -> [(&'static str, bool); 7] {
+        [
+            ("SBC", self.sbc),
+            ("SLA", self.sla),
+            ("DAA", self.daa),
+            ("SWJTAG", self.swjtag),
+            ("EPP", self.epp),
+            ("ROOT_CERT", self.root_cert_required),
+            ("MEM_PRESERVE", self.mem_preserve),
+        ]
+    }
+}
+
+impl From<u32> for TargetConfig {
+    fn from(raw: u32) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+/// Lists the enabled settings, like "SBC, SLA, DAA", or "none"
+impl fmt::Display for TargetConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let enabled = self.flags().into_iter().filter(|(_, set)| *set).map(|(name, _)| name);
+        let enabled = enabled.collect::<Vec<_>>();
+        if enabled.is_empty() { write!(f, "none") } else { write!(f, "{}", enabled.join(", ")) }
+    }
+}
+
 /// Struct holding device information data.
 /// This should not be accessed directly, instead use the `DeviceInfo` wrapper.
 #[derive(Clone, Default)]
@@ -23,8 +95,37 @@ pub struct DevInfoData {
     pub meid: Vec<u8>,
     pub hw_code: u16,
     pub partitions: Vec<Partition>,
+    /// Where `partitions` was read from
+    pub gpt_source: GptSource,
+    /// Whether the primary and secondary GPT are valid and agree,
+    /// `None` when the device has no GPT
+    pub gpt_report: Option<GptReport>,
     pub storage: Option<Arc<dyn Storage + Send + Sync>>,
     pub target_config: u32,
+    /// USB speed the DA runs at, once in DA mode
+    pub usb_speed: Option<UsbSpeed>,
+}
+
+impl DevInfoData {
+    /// Marketing name of the chip, like `MT6768`, if its hardware code is known
+    pub fn chip_name(&self) -> Option<&'static str> {
+        chipdb::chip_name(self.hw_code)
+    }
+
+    /// The security settings in [`Self::target_config`]
+    pub fn security(&self) -> TargetConfig {
+        TargetConfig::from_raw(self.target_config)
+    }
+
+    /// Partitions extending past the end of their storage region, see [`Partition::fits`].
+    /// Empty while the storage is unknown.
+    pub fn out_of_range_partitions(&self) -> Vec<Partition> {
+        let Some(storage) = &self.storage else {
+            return Vec::new();
+        };
+
+        self.partitions.iter().filter(|p| !p.fits(storage.as_ref())).cloned().collect()
+    }
 }
 
 impl DeviceInfo {
@@ -61,6 +162,10 @@ impl DeviceInfo {
         self.inner().read().await.hw_code
     }
 
+    pub async fn chip_name(&self) -> Option<&'static str> {
+        self.inner().read().await.chip_name()
+    }
+
     pub async fn partitions(&self) -> Vec<Partition> {
         self.inner().read().await.partitions.clone()
     }
@@ -74,14 +179,60 @@ impl DeviceInfo {
         write_guard.storage = Some(storage);
     }
 
+    /// Drops the cached storage, so it gets detected again on next use.
+    pub async fn clear_storage(&self) {
+        let mut write_guard = self.inner().write().await;
+        write_guard.storage = None;
+    }
+
     pub async fn get_partition(&self, name: &str) -> Option<Partition> {
         let partitions = self.inner().read().await.partitions.clone();
         partitions.into_iter().find(|p| p.name.eq_ignore_ascii_case(name))
     }
 
+    /// Caches the partition table, warning about the partitions that don't fit in
+    /// the storage.
     pub async fn set_partitions(&self, partitions: Vec<Partition>) {
         let mut write_guard = self.inner().write().await;
         write_guard.partitions = partitions;
+
+        for p in write_guard.out_of_range_partitions() {
+            warn!(
+                "[Penumbra] Partition '{}' (0x{:X}+0x{:X}) extends past the end of {}",
+                p.name,
+                p.address,
+                p.size,
+                p.kind.as_str()
+            );
+        }
+    }
+
+    pub async fn out_of_range_partitions(&self) -> Vec<Partition> {
+        self.inner().read().await.out_of_range_partitions()
+    }
+
+    pub async fn gpt_source(&self) -> GptSource {
+        self.inner().read().await.gpt_source
+    }
+
+    pub async fn set_gpt_source(&self, source: GptSource) {
+        let mut write_guard = self.inner().write().await;
+        write_guard.gpt_source = source;
+    }
+
+    pub async fn gpt_report(&self) -> Option<GptReport> {
+        self.inner().read().await.gpt_report.clone()
+    }
+
+    pub async fn set_gpt_report(&self, report: Option<GptReport>) {
+        let mut write_guard = self.inner().write().await;
+        write_guard.gpt_report = report;
+    }
+
+    /// How the primary and secondary GPT disagree, if both are valid but differ
+    pub async fn gpt_diff(&self) -> Option<GptDiff> {
+        let report = self.inner().read().await.gpt_report.clone()?;
+        Some(report.diff).filter(|d| !d.is_empty())
     }
 
     pub async fn target_config(&self) -> u32 {
@@ -93,18 +244,28 @@ impl DeviceInfo {
         write_guard.target_config = cfg;
     }
 
+    pub async fn usb_speed(&self) -> Option<UsbSpeed> {
+        self.inner().read().await.usb_speed
+    }
+
+    pub async fn set_usb_speed(&self, speed: Option<UsbSpeed>) {
+        let mut write_guard = self.inner().write().await;
+        write_guard.usb_speed = speed;
+    }
+
+    pub async fn security(&self) -> TargetConfig {
+        self.inner().read().await.security()
+    }
+
     pub async fn sbc_enabled(&self) -> bool {
-        let target_config = self.inner().read().await.target_config;
-        (target_config & 0x1) != 0
+        self.security().await.sbc
     }
 
     pub async fn sla_enabled(&self) -> bool {
-        let target_config = self.inner().read().await.target_config;
-        (target_config & 0x2) != 0
+        self.security().await.sla
     }
 
     pub async fn daa_enabled(&self) -> bool {
-        let target_config = self.inner().read().await.target_config;
-        (target_config & 0x4) != 0
+        self.security().await.daa
     }
 }
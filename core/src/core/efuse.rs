@@ -0,0 +1,37 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+/// Number of 32-bit eFuse words read, covering the security configuration (SBC, DAA, SLA)
+pub const EFUSE_BANK_COUNT: usize = 64;
+
+/// eFuse controller base address by hardware code.
+/// Addresses come from mtkclient's chip configurations, other chips aren't supported yet.
+const EFUSE_BASES: &[(u16, u32)] = &[
+    (0x0321, 0x10206000), // MT6735
+    (0x0326, 0x10206000), // MT6755
+    (0x0335, 0x10206000), // MT6737
+    (0x0717, 0x11C50000), // MT6761
+    (0x0766, 0x11C50000), // MT6765
+    (0x0788, 0x11F10000), // MT6771
+    (0x0996, 0x11C10000), // MT6853
+    (0x6580, 0x10206000), // MT6580
+];
+
+/// Returns the eFuse controller base address of a chip, if known
+pub fn efuse_base(hw_code: u16) -> Option<u32> {
+    EFUSE_BASES.iter().find(|(code, _)| *code == hw_code).map(|(_, base)| *base)
+}
+
+/// A 32-bit word of the eFuse controller, as read from the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EfuseBank {
+    /// Index of the word from the start of the eFuse controller
+    pub index: usize,
+    /// Physical address of the word
+    pub address: u32,
+    /// Raw value of the word
+    pub value: u32,
+}
@@ -4,29 +4,67 @@
 */
 use log::error;
 
+use crate::error::{Error, Result, XFlashError, XFlashErrorKind};
 use crate::utilities::patching::{HEX_NOT_FOUND, find_pattern};
 
 const FILE_INFO_EMI: &str = "4D4D4D0138000000";
+/// Every EMI settings block starts with this, followed by its version
+pub const EMI_MAGIC: &[u8] = b"MTK_BLOADER_INFO_v";
 
-pub fn extract_emi_settings(preloader: &[u8]) -> Option<Vec<u8>> {
+/// Extracts the EMI settings from a preloader, to initialize DRAM without sending the
+/// whole preloader. The result can be given to `DeviceBuilder::with_emi`.
+///
+/// The block sits at the end of the preloader code, right before its signature, and its
+/// length is stored in the last 4 bytes.
+pub fn extract_emi(preloader: &[u8]) -> Result<Vec<u8>> {
     let header_off = find_pattern(preloader, FILE_INFO_EMI, 0);
     if header_off == HEX_NOT_FOUND {
-        error!("Failed to extract EMI: EMI header not found.");
-        return None;
+        return Err(invalid(XFlashErrorKind::EmiHdrInvalid, "FILE_INFO header not found"));
     }
 
     let mut data = &preloader[header_off..];
+    if data.len() < 0x30 {
+        return Err(invalid(XFlashErrorKind::EmiHdrInvalid, "FILE_INFO header is truncated"));
+    }
 
-    let mlen = u32::from_le_bytes(data[0x20..0x24].try_into().ok()?) as usize;
-    let siglen = u32::from_le_bytes(data[0x2C..0x30].try_into().ok()?) as usize;
+    let mlen = u32::from_le_bytes(data[0x20..0x24].try_into().unwrap()) as usize;
+    let siglen = u32::from_le_bytes(data[0x2C..0x30].try_into().unwrap()) as usize;
+    if mlen > data.len() || siglen + 4 > mlen {
+        return Err(invalid(
+            XFlashErrorKind::EmiInfoLengthInvalid,
+            &format!("file length 0x{:X} and signature length 0x{:X}", mlen, siglen),
+        ));
+    }
     data = &data[..mlen - siglen];
 
-    let mut dramsize = u32::from_le_bytes(data[data.len() - 4..].try_into().ok()?) as usize;
+    let mut dramsize = u32::from_le_bytes(data[data.len() - 4..].try_into().unwrap()) as usize;
     if dramsize == 0 && data.len() >= 0x804 {
         data = &data[..data.len() - 0x800];
-        dramsize = u32::from_le_bytes(data[data.len() - 4..].try_into().ok()?) as usize;
+        dramsize = u32::from_le_bytes(data[data.len() - 4..].try_into().unwrap()) as usize;
+    }
+    if dramsize == 0 || dramsize + 4 > data.len() {
+        return Err(invalid(
+            XFlashErrorKind::EmiInfoLengthOffsetInvalid,
+            &format!("EMI length 0x{:X} out of the preloader", dramsize),
+        ));
+    }
+
+    let emi = data[data.len() - dramsize - 4..data.len() - 4].to_vec();
+    validate_emi(&emi)?;
+
+    Ok(emi)
+}
+
+/// Checks that `emi` looks like an EMI settings block
+pub fn validate_emi(emi: &[u8]) -> Result<()> {
+    if !emi.starts_with(EMI_MAGIC) {
+        return Err(invalid(XFlashErrorKind::EmiHdrInvalid, "missing MTK_BLOADER_INFO header"));
     }
-    data = &data[data.len() - dramsize - 4..data.len() - 4];
 
-    Some(data[..].to_vec())
+    Ok(())
+}
+
+fn invalid(kind: XFlashErrorKind, reason: &str) -> Error {
+    error!("Invalid EMI settings: {}", reason);
+    Error::XFlash(XFlashError::from_code(kind.into()))
 }
@@ -0,0 +1,22 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use tokio::sync::mpsc;
+
+/// Events emitted by the core outside of the request/response flow,
+/// for frontends that want to surface them.
+#[derive(Debug, Clone)]
+pub enum CoreEvent {
+    /// An unsolicited information frame sent by the DA (thermal warnings,
+    /// storage remap notices...)
+    DaNotice(String),
+}
+
+pub type EventSender = mpsc::UnboundedSender<CoreEvent>;
+pub type EventReceiver = mpsc::UnboundedReceiver<CoreEvent>;
+
+/// Creates a new event channel, to be passed to [`crate::DeviceBuilder::with_event_sink`].
+pub fn event_channel() -> (EventSender, EventReceiver) {
+    mpsc::unbounded_channel()
+}
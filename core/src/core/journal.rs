@@ -0,0 +1,223 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::error::Result;
+
+/// Outcome of a journaled operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalState {
+    /// The operation started, and never reported back.
+    Started,
+    Completed,
+    Failed,
+    /// The operation was found dangling on a later session, and acknowledged.
+    Interrupted,
+}
+
+impl JournalState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalState::Started => "started",
+            JournalState::Completed => "completed",
+            JournalState::Failed => "failed",
+            JournalState::Interrupted => "interrupted",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "started" => Some(JournalState::Started),
+            "completed" => Some(JournalState::Completed),
+            "failed" => Some(JournalState::Failed),
+            "interrupted" => Some(JournalState::Interrupted),
+            _ => None,
+        }
+    }
+}
+
+/// A write or erase operation recorded in the journal.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub id: u64,
+    /// Device identifier (MEID), hex encoded.
+    pub device: String,
+    pub partition: String,
+    pub size: u64,
+    /// Start time, in seconds since the Unix epoch.
+    pub started: u64,
+    pub state: JournalState,
+    /// SHA-256 of the data that was written, only known once a write completes.
+    pub hash: Option<String>,
+}
+
+/// Append-only log of write and erase operations, used to tell after a crash which
+/// partition was being written.
+///
+/// Each operation appends a `begin` line before starting, and an `end` line once done.
+/// Operations without an `end` line were interrupted.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records the start of an operation, returning its id.
+    pub fn begin(&self, device: &str, partition: &str, size: u64) -> Result<u64> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let id = now.as_nanos() as u64;
+
+        self.append(&format!(
+            "begin\t{}\t{}\t{}\t{}\t{}",
+            id,
+            now.as_secs(),
+            device,
+            partition.replace(['\t', '\n'], " "),
+            size
+        ))?;
+
+        Ok(id)
+    }
+
+    /// Records the outcome of an operation started with [`Journal::begin`].
+    pub fn finish(&self, id: u64, state: JournalState, hash: Option<&[u8]>) -> Result<()> {
+        let hash = hash.map(hex::encode).unwrap_or_default();
+        self.append(&format!("end\t{}\t{}\t{}", id, state.as_str(), hash))
+    }
+
+    /// Returns all the recorded operations, oldest first.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self::parse(BufReader::new(file)))
+    }
+
+    /// Returns the operations that started but never finished.
+    pub fn dangling(&self) -> Result<Vec<JournalEntry>> {
+        Ok(self.entries()?.into_iter().filter(|e| e.state == JournalState::Started).collect())
+    }
+
+    /// Marks dangling operations as interrupted, so they are only reported once,
+    /// and returns them.
+    pub fn acknowledge(&self) -> Result<Vec<JournalEntry>> {
+        let dangling = self.dangling()?;
+        for entry in &dangling {
+            self.finish(entry.id, JournalState::Interrupted, None)?;
+        }
+
+        Ok(dangling)
+    }
+
+    /// Parses the lines of a journal, e.g. [`Journal::path`] opened elsewhere.
+    /// Malformed lines are skipped, and `end` lines without a `begin` are ignored.
+    pub fn parse(reader: impl BufRead) -> Vec<JournalEntry> {
+        let mut entries: Vec<JournalEntry> = Vec::new();
+
+        // Malformed lines (e.g. half written during a crash) are skipped
+        for line in reader.lines().map_while(|l| l.ok()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["begin", id, started, device, partition, size] => {
+                    let (Ok(id), Ok(started), Ok(size)) =
+                        (id.parse(), started.parse(), size.parse())
+                    else {
+                        continue;
+                    };
+
+                    entries.push(JournalEntry {
+                        id,
+                        device: device.to_string(),
+                        partition: partition.to_string(),
+                        size,
+                        started,
+                        state: JournalState::Started,
+                        hash: None,
+                    });
+                }
+                ["end", id, state, hash] => {
+                    let (Ok(id), Some(state)) = (id.parse::<u64>(), JournalState::from_str(state))
+                    else {
+                        continue;
+                    };
+
+                    if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+                        entry.state = state;
+                        entry.hash = (!hash.is_empty()).then(|| hash.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        entries
+    }
+
+    fn append(&self, line: &str) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        // The whole point is to survive crashes, so make sure the line hits the disk
+        file.sync_data()?;
+
+        Ok(())
+    }
+}
+
+/// Reader wrapper computing the SHA-256 of everything read through it.
+pub(crate) struct HashingReader<'a> {
+    inner: &'a mut (dyn AsyncRead + Unpin + Send),
+    hasher: Sha256,
+}
+
+impl<'a> HashingReader<'a> {
+    pub fn new(inner: &'a mut (dyn AsyncRead + Unpin + Send)) -> Self {
+        Self { inner, hasher: Sha256::new() }
+    }
+
+    pub fn digest(self) -> [u8; 32] {
+        self.hasher.finalize().into()
+    }
+}
+
+impl AsyncRead for HashingReader<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let this = &mut *self;
+
+        let result = Pin::new(&mut *this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            this.hasher.update(&buf.filled()[before..]);
+        }
+
+        result
+    }
+}
@@ -3,8 +3,18 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 pub mod auth;
+pub mod chipdb;
 pub mod crypto;
+#[cfg(feature = "da-protocols")]
 pub mod devinfo;
+pub mod efuse;
 pub mod emi;
+#[cfg(feature = "da-protocols")]
+pub mod events;
+#[cfg(feature = "da-protocols")]
+pub mod journal;
+#[cfg(feature = "da-protocols")]
+pub mod progress;
+pub mod scatter;
 pub mod seccfg;
 pub mod storage;
@@ -0,0 +1,96 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Progress reporting of the transfers and other long running device operations.
+//!
+//! Callbacks receive a [`Progress`] snapshot, which carries enough to compute the rate
+//! and time left, so frontends don't need to keep track of it themselves.
+use std::time::{Duration, Instant};
+
+use crate::connection::phases::{FlashPhase, PhaseTracker};
+
+/// Snapshot of a running operation, passed to the progress callbacks.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    /// Bytes processed so far
+    pub written: u64,
+    /// Bytes to process in total
+    pub total: u64,
+    /// When the operation started
+    pub started: Instant,
+    /// When the snapshot was taken
+    pub instant: Instant,
+    /// The flash phase running, if known
+    pub phase: Option<FlashPhase>,
+}
+
+impl Progress {
+    pub fn new(written: u64, total: u64, started: Instant) -> Self {
+        Progress { written, total, started, instant: Instant::now(), phase: None }
+    }
+
+    /// Completed fraction, between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 { 0.0 } else { (self.written as f64 / self.total as f64).min(1.0) }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.written >= self.total
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.instant.saturating_duration_since(self.started)
+    }
+
+    /// Average rate since the start in bytes per second, `None` until something was processed
+    pub fn rate(&self) -> Option<f64> {
+        let secs = self.elapsed().as_secs_f64();
+        (secs > 0.0 && self.written > 0).then(|| self.written as f64 / secs)
+    }
+
+    /// Time left at the average rate, `None` until the rate is known
+    pub fn eta(&self) -> Option<Duration> {
+        let left = self.total.saturating_sub(self.written) as f64;
+        self.rate().map(|rate| Duration::from_secs_f64(left / rate))
+    }
+}
+
+/// Progress callback of the DA protocols.
+pub type ProgressFn<'a> = dyn FnMut(&Progress) + Send + 'a;
+
+/// Turns the `(written, total)` byte counts of an operation into [`Progress`] snapshots,
+/// timed from the creation of the tracker.
+pub struct ProgressTracker<'a> {
+    callback: &'a mut (dyn FnMut(&Progress) + Send),
+    started: Instant,
+    phases: Option<PhaseTracker>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub fn new(callback: &'a mut (dyn FnMut(&Progress) + Send)) -> Self {
+        ProgressTracker { callback, started: Instant::now(), phases: None }
+    }
+
+    /// Reports the flash phase running on `phases` along with the byte counts
+    pub fn with_phases(mut self, phases: Option<PhaseTracker>) -> Self {
+        self.phases = phases;
+        self
+    }
+
+    pub fn update(&mut self, written: u64, total: u64) {
+        let mut progress = Progress::new(written, total, self.started);
+        progress.phase = self.phases.as_ref().and_then(|p| p.current());
+        (self.callback)(&progress);
+    }
+}
+
+/// Adapts a `(written, total)` closure of the previous progress API, for code that
+/// hasn't moved to [`Progress`] yet.
+pub fn from_bytes_fn<F>(mut callback: F) -> impl FnMut(&Progress) + Send
+where
+    F: FnMut(u64, u64) + Send,
+{
+    move |progress| callback(progress.written, progress.total)
+}
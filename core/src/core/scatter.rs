@@ -0,0 +1,207 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Parser for the MT65xx scatter files shipped with MediaTek firmware packages.
+//!
+//! Only the V1.1.x layout is supported, which is YAML-like text:
+//!
+//! ```text
+//! - general: MTK_PLATFORM_CFG
+//!   info:
+//!     - config_version: V1.1.2
+//!       platform: MT6765
+//!       storage: EMMC
+//! - partition_index: SYS0
+//!   partition_name: preloader
+//!   file_name: preloader_k65v1_64_bsp.bin
+//!   is_download: true
+//!   ...
+//! ```
+use std::path::Path;
+
+use log::error;
+
+use crate::core::storage::StorageType;
+use crate::error::{Error, Result, XFlashError, XFlashErrorKind};
+
+/// A parsed scatter file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScatterFile {
+    /// Layout version, e.g. `V1.1.2`.
+    pub version: String,
+    /// Chip the firmware was built for, e.g. `MT6765`.
+    pub platform: String,
+    /// Storage the layout was made for, e.g. `EMMC` or `UFS`.
+    pub storage: String,
+    /// Partitions, in the order they appear in the file.
+    pub entries: Vec<ScatterEntry>,
+}
+
+/// A partition described by a scatter file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScatterEntry {
+    pub name: String,
+    /// Image to flash, relative to the scatter file. `None` when the file says `NONE`.
+    pub file_name: Option<String>,
+    /// Whether SP Flash Tool downloads the image by default.
+    pub is_download: bool,
+    /// Image type, e.g. `SV5_BL_BIN` or `NORMAL_ROM`.
+    pub kind: String,
+    /// Start of the partition inside its region.
+    pub address: u64,
+    pub size: u64,
+    /// Region of the storage, e.g. `EMMC_USER` or `UFS_LU2`.
+    pub region: String,
+}
+
+impl ScatterEntry {
+    /// Whether the entry has an image to be flashed.
+    pub fn is_flashable(&self) -> bool {
+        self.is_download && self.file_name.is_some()
+    }
+}
+
+impl ScatterFile {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            error!("Failed to read scatter file {}: {}", path.display(), e);
+            Error::XFlash(XFlashError::from_code(XFlashErrorKind::ScatterFileNotFound.into()))
+        })?;
+
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let blocks = split_blocks(text)?;
+
+        let general = blocks
+            .iter()
+            .find(|b| b.key(0) == "general")
+            .ok_or_else(|| invalid(1, "No general section"))?;
+        let version = general.get("config_version")?.to_string();
+        if !version.starts_with("V1.1") {
+            return Err(invalid(general.line, &format!("Unsupported version {}", version)));
+        }
+
+        let mut entries = Vec::new();
+        for block in blocks.iter().filter(|b| b.key(0) == "partition_index") {
+            let file_name = block.get("file_name")?;
+            entries.push(ScatterEntry {
+                name: block.get("partition_name")?.to_string(),
+                file_name: (file_name != "NONE").then(|| file_name.to_string()),
+                is_download: block.get_bool("is_download")?,
+                kind: block.get("type")?.to_string(),
+                address: block.get_u64("linear_start_addr")?,
+                size: block.get_u64("partition_size")?,
+                region: block.get("region")?.to_string(),
+            });
+        }
+
+        if entries.is_empty() {
+            return Err(invalid(general.line, "No partition entries"));
+        }
+
+        Ok(ScatterFile {
+            version,
+            platform: general.get("platform")?.to_string(),
+            storage: general.get("storage")?.to_string(),
+            entries,
+        })
+    }
+
+    /// The storage type the layout was made for, `Unknown` for unrecognized values.
+    pub fn storage_type(&self) -> StorageType {
+        match self.storage.to_ascii_uppercase().trim_start_matches("HW_STORAGE_") {
+            "EMMC" => StorageType::Emmc,
+            "UFS" => StorageType::Ufs,
+            "NAND" => StorageType::Nand,
+            _ => StorageType::Unknown,
+        }
+    }
+
+    /// Entries with an image to be flashed, in file order.
+    pub fn flashable(&self) -> impl Iterator<Item = &ScatterEntry> {
+        self.entries.iter().filter(|e| e.is_flashable())
+    }
+}
+
+/// A `- key: value` item and its indented fields, flattened.
+struct Block {
+    /// Line of the item start, 1-based.
+    line: usize,
+    fields: Vec<(String, String)>,
+}
+
+impl Block {
+    fn key(&self, index: usize) -> &str {
+        self.fields.get(index).map_or("", |(key, _)| key)
+    }
+
+    fn get(&self, key: &str) -> Result<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| invalid(self.line, &format!("Missing field '{}'", key)))
+    }
+
+    fn get_bool(&self, key: &str) -> Result<bool> {
+        match self.get(key)? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            value => Err(invalid(self.line, &format!("'{}' is not a boolean: {}", key, value))),
+        }
+    }
+
+    fn get_u64(&self, key: &str) -> Result<u64> {
+        let value = self.get(key)?;
+        let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+
+        parsed.map_err(|_| invalid(self.line, &format!("'{}' is not a number: {}", key, value)))
+    }
+}
+
+fn split_blocks(text: &str) -> Result<Vec<Block>> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // Top level items start at column 0, everything else belongs to the last one
+        let starts_block = raw.starts_with("- ");
+        let field = trimmed.trim_start_matches("- ");
+        let Some((key, value)) = field.split_once(':') else {
+            return Err(unknown_field(line, trimmed));
+        };
+
+        let field = (key.trim().to_string(), value.trim().to_string());
+        if starts_block {
+            blocks.push(Block { line, fields: vec![field] });
+        } else if let Some(block) = blocks.last_mut() {
+            block.fields.push(field);
+        } else {
+            return Err(unknown_field(line, trimmed));
+        }
+    }
+
+    Ok(blocks)
+}
+
+fn invalid(line: usize, reason: &str) -> Error {
+    error!("Invalid scatter file (line {}): {}", line, reason);
+    Error::XFlash(XFlashError::from_code(XFlashErrorKind::ScatterFileInvalid.into()))
+}
+
+fn unknown_field(line: usize, field: &str) -> Error {
+    error!("Unknown scatter field (line {}): {}", line, field);
+    Error::XFlash(XFlashError::from_code(XFlashErrorKind::UnknownScatterField.into()))
+}
@@ -16,22 +16,150 @@ use sha2::{Digest, Sha256};
 
 use crate::error::{Error, Result};
 
-const V4_MAGIC_BEGIN: u32 = 0x4D4D4D4D;
-const V4_MAGIC_END: u32 = 0x45454545;
+const MAGIC_BEGIN: u32 = 0x4D4D4D4D;
+const MAGIC_END: u32 = 0x45454545;
 
+const V4_LOCK_DEFAULT: u32 = 1;
+const V4_UNLOCKED: u32 = 3;
+const V4_LOCKED: u32 = 4;
+
+const V3_INFO_HEADER: &[u8] = b"AND_SECCFG_v";
+const V3_HEADER_SIZE: usize = 0x2C;
+const V3_IMGINFO_SIZE: usize = 0x68 * 20;
+const V3_STATUS_COMPLETE: u32 = 0x43434343;
+const V3_STATUS_INCOMPLETE: u32 = 0x49494949;
+const V3_ATTR_DEFAULT: u32 = 0x33333333;
+const V3_ATTR_UNLOCK: u32 = 0x44444444;
+const V3_ATTRS: [u32; 6] = [0x6000, 0x6001, 0x6002, 0x6003, V3_ATTR_DEFAULT, V3_ATTR_UNLOCK];
+
+/// The most seccfg data that needs to be read to parse any version
+pub const SECCFG_MAX_SIZE: usize = 0x2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LockFlag {
     Lock,
     Unlock,
 }
 
-#[derive(Clone)]
-pub enum SecCfgV4Algo {
+/// How the seccfg hash (V4) or data (V3) is encrypted through SEJ
+#[derive(Clone, Copy)]
+pub enum SecCfgAlgo {
     SW,
     HW,
     HWv3,
     HWv4,
 }
 
+impl SecCfgAlgo {
+    pub const ALL: [SecCfgAlgo; 4] =
+        [SecCfgAlgo::SW, SecCfgAlgo::HW, SecCfgAlgo::HWv3, SecCfgAlgo::HWv4];
+
+    /// The `(legacy, anti_clone, xor)` SEJ flags of this algorithm
+    pub fn sej_flags(&self) -> (bool, bool, bool) {
+        match self {
+            SecCfgAlgo::SW => (false, false, false),
+            SecCfgAlgo::HW => (false, true, true),
+            SecCfgAlgo::HWv3 => (true, true, false),
+            SecCfgAlgo::HWv4 => (false, true, false),
+        }
+    }
+}
+
+/// A seccfg partition of any supported version, picked from its magic
+pub enum SecCfg {
+    V3(SecCfgV3),
+    V4(SecCfgV4),
+}
+
+impl SecCfg {
+    pub fn parse_header(data: &[u8]) -> Result<SecCfg> {
+        if data.starts_with(V3_INFO_HEADER) {
+            SecCfgV3::parse_header(data).map(SecCfg::V3)
+        } else {
+            SecCfgV4::parse_header(data).map(SecCfg::V4)
+        }
+    }
+
+    /// The data to decrypt to find the algorithm: the hash for V4, the whole body for V3
+    pub fn get_encrypted(&self) -> Vec<u8> {
+        match self {
+            SecCfg::V3(v3) => v3.enc_data.clone(),
+            SecCfg::V4(v4) => v4.get_encrypted_hash(),
+        }
+    }
+
+    /// Checks `decrypted` against the header, keeping it and `algo` if it matches.
+    /// Returns whether `algo` is the one the seccfg was encrypted with.
+    pub fn try_decrypted(&mut self, decrypted: &[u8], algo: SecCfgAlgo) -> bool {
+        let matches = match self {
+            SecCfg::V3(v3) => v3.set_decrypted(decrypted),
+            SecCfg::V4(v4) => decrypted == v4.get_hash(),
+        };
+        if matches {
+            self.set_algo(algo);
+        }
+        matches
+    }
+
+    /// The data to encrypt when writing back: the hash for V4, the whole body for V3
+    pub fn get_plain(&self) -> Vec<u8> {
+        match self {
+            SecCfg::V3(v3) => v3.get_body(),
+            SecCfg::V4(v4) => v4.get_hash(),
+        }
+    }
+
+    pub fn set_encrypted(&mut self, encrypted: Vec<u8>) {
+        match self {
+            SecCfg::V3(v3) => v3.enc_data = encrypted,
+            SecCfg::V4(v4) => v4.set_encrypted_hash(encrypted),
+        }
+    }
+
+    pub fn get_algo(&self) -> Option<SecCfgAlgo> {
+        match self {
+            SecCfg::V3(v3) => v3.algo,
+            SecCfg::V4(v4) => v4.get_algo(),
+        }
+    }
+
+    pub fn set_algo(&mut self, algo: SecCfgAlgo) {
+        match self {
+            SecCfg::V3(v3) => v3.algo = Some(algo),
+            SecCfg::V4(v4) => v4.set_algo(algo),
+        }
+    }
+
+    pub fn get_lock_state(&self) -> LockFlag {
+        match self {
+            SecCfg::V3(v3) => v3.get_lock_state(),
+            SecCfg::V4(v4) => v4.get_lock_state(),
+        }
+    }
+
+    pub fn set_lock_state(&mut self, lock_flag: LockFlag) {
+        match self {
+            SecCfg::V3(v3) => v3.set_lock_state(lock_flag),
+            SecCfg::V4(v4) => v4.set_lock_state(lock_flag),
+        }
+    }
+
+    /// Refuses flag combinations the bootloader wouldn't expect
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SecCfg::V3(_) => Ok(()),
+            SecCfg::V4(v4) => v4.validate(),
+        }
+    }
+
+    pub fn create(&mut self) -> Vec<u8> {
+        match self {
+            SecCfg::V3(v3) => v3.create(),
+            SecCfg::V4(v4) => v4.create(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct SecCfgV4 {
     pub seccfg_ver: u32,
@@ -39,7 +167,7 @@ pub struct SecCfgV4 {
     pub lock_state: u32,
     pub critical_lock_state: u32,
     pub sboot_runtime: u32,
-    algo: Option<SecCfgV4Algo>,
+    algo: Option<SecCfgAlgo>,
     enc_hash: Option<Vec<u8>>,
 }
 
@@ -57,7 +185,8 @@ impl SecCfgV4 {
     }
 
     pub fn parse_header(data: &[u8]) -> Result<SecCfgV4> {
-        if data.len() < 0x20 {
+        // Header and encrypted hash
+        if data.len() < 0x3C {
             return Err(Error::penumbra("SecCfg v4 data too short"));
         }
 
@@ -70,7 +199,7 @@ impl SecCfgV4 {
         let endflag = u32::from_le_bytes(data[24..28].try_into().unwrap());
         let enc_hash = data[28..60].to_vec();
 
-        if magic != V4_MAGIC_BEGIN || endflag != V4_MAGIC_END {
+        if magic != MAGIC_BEGIN || endflag != MAGIC_END {
             return Err(Error::penumbra("Invalid SecCfg v4 magic values"));
         }
 
@@ -87,13 +216,13 @@ impl SecCfgV4 {
 
     pub fn get_hash(&self) -> Vec<u8> {
         let header_data = [
-            V4_MAGIC_BEGIN.to_le_bytes(),
+            MAGIC_BEGIN.to_le_bytes(),
             self.seccfg_ver.to_le_bytes(),
             self.seccfg_size.to_le_bytes(),
             self.lock_state.to_le_bytes(),
             self.critical_lock_state.to_le_bytes(),
             self.sboot_runtime.to_le_bytes(),
-            V4_MAGIC_END.to_le_bytes(),
+            MAGIC_END.to_le_bytes(),
         ]
         .concat();
 
@@ -101,11 +230,11 @@ impl SecCfgV4 {
         hash.to_vec()
     }
 
-    pub fn get_algo(&self) -> Option<SecCfgV4Algo> {
-        self.algo.clone()
+    pub fn get_algo(&self) -> Option<SecCfgAlgo> {
+        self.algo
     }
 
-    pub fn set_algo(&mut self, algo: SecCfgV4Algo) {
+    pub fn set_algo(&mut self, algo: SecCfgAlgo) {
         self.algo = Some(algo);
     }
 
@@ -117,28 +246,65 @@ impl SecCfgV4 {
         self.enc_hash.clone().unwrap_or_default()
     }
 
-    pub fn set_lock_state(&mut self, lock_flag: LockFlag) {
-        match lock_flag {
-            LockFlag::Lock => {
-                self.lock_state = 4;
-                self.critical_lock_state = 1;
-            }
-            LockFlag::Unlock => {
-                self.lock_state = 3;
-                self.critical_lock_state = 0;
-            }
+    /// Whether the bootloader is locked. Any lock state other than unlocked counts as locked.
+    pub fn is_locked(&self) -> bool {
+        self.lock_state != V4_UNLOCKED
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.lock_state = if locked { V4_LOCKED } else { V4_UNLOCKED };
+    }
+
+    /// Whether the critical partitions (e.g. the bootloader itself) are locked,
+    /// which some fastboot unlocks check on their own.
+    pub fn is_critical_locked(&self) -> bool {
+        self.critical_lock_state != 0
+    }
+
+    pub fn set_critical_locked(&mut self, locked: bool) {
+        self.critical_lock_state = locked as u32;
+    }
+
+    pub fn get_sboot_runtime(&self) -> u32 {
+        self.sboot_runtime
+    }
+
+    pub fn set_sboot_runtime(&mut self, sboot_runtime: u32) {
+        self.sboot_runtime = sboot_runtime;
+    }
+
+    /// Refuses unknown lock states and a locked bootloader with unlocked critical partitions
+    pub fn validate(&self) -> Result<()> {
+        if ![V4_LOCK_DEFAULT, V4_UNLOCKED, V4_LOCKED].contains(&self.lock_state) {
+            return Err(Error::penumbra(format!("Unknown seccfg lock state {}", self.lock_state)));
+        }
+        if self.is_locked() && !self.is_critical_locked() {
+            return Err(Error::penumbra(
+                "Inconsistent seccfg: locked, but with the critical partitions unlocked",
+            ));
         }
+        Ok(())
+    }
+
+    pub fn get_lock_state(&self) -> LockFlag {
+        if self.is_locked() { LockFlag::Lock } else { LockFlag::Unlock }
+    }
+
+    pub fn set_lock_state(&mut self, lock_flag: LockFlag) {
+        let locked = lock_flag == LockFlag::Lock;
+        self.set_locked(locked);
+        self.set_critical_locked(locked);
     }
 
     pub fn create(&mut self) -> Vec<u8> {
         let mut seccfg_data = Vec::new();
-        seccfg_data.extend(&V4_MAGIC_BEGIN.to_le_bytes());
+        seccfg_data.extend(&MAGIC_BEGIN.to_le_bytes());
         seccfg_data.extend(&self.seccfg_ver.to_le_bytes());
         seccfg_data.extend(&self.seccfg_size.to_le_bytes());
         seccfg_data.extend(&self.lock_state.to_le_bytes());
         seccfg_data.extend(&self.critical_lock_state.to_le_bytes());
         seccfg_data.extend(&self.sboot_runtime.to_le_bytes());
-        seccfg_data.extend(&V4_MAGIC_END.to_le_bytes());
+        seccfg_data.extend(&MAGIC_END.to_le_bytes());
 
         if let Some(enc_hash) = &self.enc_hash {
             seccfg_data.extend_from_slice(enc_hash);
@@ -154,3 +320,142 @@ impl SecCfgV4 {
         seccfg_data
     }
 }
+
+/// The seccfg layout of older MT67xx devices.
+///
+/// Instead of a hash, the whole body after the header (image info, lock status and
+/// attributes) is encrypted, so it can only be read once the right algorithm is found.
+#[derive(Default)]
+pub struct SecCfgV3 {
+    pub seccfg_ver: u32,
+    pub seccfg_size: u32,
+    pub enc_offset: u32,
+    pub sw_sec_lock_try: u8,
+    pub sw_sec_lock_done: u8,
+    pub page_size: u16,
+    pub page_count: u32,
+    pub siu_status: u32,
+    pub seccfg_status: u32,
+    pub seccfg_attr: u32,
+    info_header: Vec<u8>,
+    imginfo: Vec<u8>,
+    seccfg_ext: Vec<u8>,
+    algo: Option<SecCfgAlgo>,
+    enc_data: Vec<u8>,
+}
+
+impl SecCfgV3 {
+    pub fn parse_header(data: &[u8]) -> Result<SecCfgV3> {
+        if data.len() < V3_HEADER_SIZE {
+            return Err(Error::penumbra("SecCfg v3 data too short"));
+        }
+
+        let u32_at = |off: usize| u32::from_le_bytes(data[off..off + 4].try_into().unwrap());
+        let magic = u32_at(0x10);
+        let seccfg_ver = u32_at(0x14);
+        let seccfg_size = u32_at(0x18);
+        let enc_offset = u32_at(0x1C);
+        let enc_len = u32_at(0x20);
+
+        if !data.starts_with(V3_INFO_HEADER) || magic != MAGIC_BEGIN {
+            return Err(Error::penumbra("Invalid SecCfg v3 magic values"));
+        }
+
+        let enc_start = enc_offset as usize;
+        let enc_end = enc_start + enc_len as usize;
+        if enc_start < V3_HEADER_SIZE || enc_end + 4 > data.len() {
+            return Err(Error::penumbra("SecCfg v3 data too short"));
+        }
+        if u32_at(enc_end) != MAGIC_END {
+            return Err(Error::penumbra("Invalid SecCfg v3 magic values"));
+        }
+
+        Ok(SecCfgV3 {
+            seccfg_ver,
+            seccfg_size,
+            enc_offset,
+            sw_sec_lock_try: data[0x24],
+            sw_sec_lock_done: data[0x25],
+            page_size: u16::from_le_bytes(data[0x26..0x28].try_into().unwrap()),
+            page_count: u32_at(0x28),
+            info_header: data[..0x10].to_vec(),
+            enc_data: data[enc_start..enc_end].to_vec(),
+            ..Default::default()
+        })
+    }
+
+    /// Reads the decrypted body, returning false if it doesn't look like one
+    fn set_decrypted(&mut self, body: &[u8]) -> bool {
+        if body.len() < V3_IMGINFO_SIZE + 12 {
+            return false;
+        }
+
+        let u32_at = |off: usize| u32::from_le_bytes(body[off..off + 4].try_into().unwrap());
+        let siu_status = u32_at(V3_IMGINFO_SIZE);
+        let seccfg_status = u32_at(V3_IMGINFO_SIZE + 4);
+        let seccfg_attr = u32_at(V3_IMGINFO_SIZE + 8);
+
+        let known_status = matches!(seccfg_status, V3_STATUS_COMPLETE | V3_STATUS_INCOMPLETE);
+        if !known_status || !V3_ATTRS.contains(&seccfg_attr) {
+            return false;
+        }
+
+        self.imginfo = body[..V3_IMGINFO_SIZE].to_vec();
+        self.siu_status = siu_status;
+        self.seccfg_status = seccfg_status;
+        self.seccfg_attr = seccfg_attr;
+        self.seccfg_ext = body[V3_IMGINFO_SIZE + 12..].to_vec();
+        true
+    }
+
+    fn get_body(&self) -> Vec<u8> {
+        let mut body = self.imginfo.clone();
+        body.extend(&self.siu_status.to_le_bytes());
+        body.extend(&self.seccfg_status.to_le_bytes());
+        body.extend(&self.seccfg_attr.to_le_bytes());
+        body.extend(&self.seccfg_ext);
+        body
+    }
+
+    pub fn get_lock_state(&self) -> LockFlag {
+        match self.seccfg_attr {
+            V3_ATTR_UNLOCK => LockFlag::Unlock,
+            _ => LockFlag::Lock,
+        }
+    }
+
+    pub fn set_lock_state(&mut self, lock_flag: LockFlag) {
+        self.seccfg_status = V3_STATUS_COMPLETE;
+        self.seccfg_attr = match lock_flag {
+            LockFlag::Lock => V3_ATTR_DEFAULT,
+            LockFlag::Unlock => V3_ATTR_UNLOCK,
+        };
+    }
+
+    /// The full seccfg, with the body as last set with [`SecCfg::set_encrypted`]
+    pub fn create(&mut self) -> Vec<u8> {
+        let mut seccfg_data = self.info_header.clone();
+        seccfg_data.extend(&MAGIC_BEGIN.to_le_bytes());
+        seccfg_data.extend(&self.seccfg_ver.to_le_bytes());
+        seccfg_data.extend(&self.seccfg_size.to_le_bytes());
+        seccfg_data.extend(&self.enc_offset.to_le_bytes());
+        seccfg_data.extend(&(self.enc_data.len() as u32).to_le_bytes());
+        seccfg_data.push(self.sw_sec_lock_try);
+        seccfg_data.push(self.sw_sec_lock_done);
+        seccfg_data.extend(&self.page_size.to_le_bytes());
+        seccfg_data.extend(&self.page_count.to_le_bytes());
+
+        seccfg_data.resize(self.enc_offset as usize, 0);
+        seccfg_data.extend_from_slice(&self.enc_data);
+        seccfg_data.extend(&MAGIC_END.to_le_bytes());
+
+        if seccfg_data.len() < self.seccfg_size as usize {
+            seccfg_data.resize(self.seccfg_size as usize, 0);
+        }
+        while !seccfg_data.len().is_multiple_of(0x200) {
+            seccfg_data.push(0);
+        }
+
+        seccfg_data
+    }
+}
@@ -4,7 +4,15 @@
 */
 use async_trait::async_trait;
 
-use crate::core::storage::{PartitionKind, Storage, StorageType};
+use crate::core::storage::{
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageHwInfo,
+    StorageType,
+    ascii_field,
+    raw_id,
+};
 use crate::error::{Error, Result};
 use crate::utilities::xml::{get_tag, get_tag_usize};
 
@@ -35,6 +43,8 @@ pub struct EmmcInfo {
     pub cid: Vec<u8>,
     /// eMMC firmware version.
     pub fwver: u64,
+    /// Erase group size in bytes, 0 when the DA doesn't report it.
+    pub erase_group_size: u64,
 }
 
 /// Represents eMMC partitions types.
@@ -129,6 +139,86 @@ impl Storage for EmmcStorage {
     fn get_user_size(&self) -> u64 {
         self.info.user_size
     }
+
+    fn regions(&self) -> Vec<RegionInfo> {
+        [
+            (EmmcPartition::Boot1, self.info.boot1_size),
+            (EmmcPartition::Boot2, self.info.boot2_size),
+            (EmmcPartition::Rpmb, self.info.rpmb_size),
+            (EmmcPartition::Gp1, self.info.gp1_size),
+            (EmmcPartition::Gp2, self.info.gp2_size),
+            (EmmcPartition::Gp3, self.info.gp3_size),
+            (EmmcPartition::Gp4, self.info.gp4_size),
+            (EmmcPartition::User, self.info.user_size),
+        ]
+        .into_iter()
+        .filter(|(_, size)| *size != 0)
+        .map(|(part, size)| {
+            RegionInfo::new(PartitionKind::Emmc(part), size, part != EmmcPartition::Rpmb)
+        })
+        .collect()
+    }
+
+    fn erase_group_size(&self) -> u64 {
+        match self.info.erase_group_size {
+            0 => self.info.block_size as u64,
+            size => size,
+        }
+    }
+
+    fn hw_info(&self) -> StorageHwInfo {
+        let raw_cid = raw_id(&self.info.cid);
+        let Some(cid) = raw_cid.as_deref().and_then(cid_register) else {
+            return StorageHwInfo { raw_cid, ..Default::default() };
+        };
+
+        let vendor = emmc_vendor(cid[0]).map(str::to_string);
+        let product = ascii_field(&cid[3..9]);
+        let serial = u32::from_be_bytes(cid[10..14].try_into().unwrap());
+
+        // EXT_CSD FIRMWARE_VERSION is only reported by XFlash DAs, the CID product
+        // revision is the best we have otherwise
+        let fw_version = match self.info.fwver {
+            0 => Some(format!("{}.{}", cid[9] >> 4, cid[9] & 0xF)),
+            fwver => Some(
+                ascii_field(&fwver.to_le_bytes()).unwrap_or_else(|| format!("0x{:X}", fwver)),
+            ),
+        };
+
+        StorageHwInfo {
+            vendor,
+            product,
+            fw_version,
+            serial: Some(format!("0x{:08X}", serial)),
+            raw_cid,
+        }
+    }
+}
+
+/// The CID in register order (MID first). Some DAs report it as four little endian words,
+/// so the order whose product name decodes is picked.
+fn cid_register(cid: &[u8]) -> Option<[u8; 16]> {
+    let raw: [u8; 16] = cid.get(..16)?.try_into().ok()?;
+
+    let mut swapped = raw;
+    swapped.chunks_exact_mut(4).for_each(|word| word.reverse());
+
+    [swapped, raw].into_iter().find(|cid| ascii_field(&cid[3..9]).is_some())
+}
+
+/// Manufacturer name from the CID MID field.
+fn emmc_vendor(mid: u8) -> Option<&'static str> {
+    Some(match mid {
+        0x11 => "Toshiba/Kioxia",
+        0x13 | 0xFE => "Micron",
+        0x15 => "Samsung",
+        0x45 => "SanDisk",
+        0x70 => "Kingston",
+        0x88 => "Foresee",
+        0x90 => "SK Hynix",
+        0x9B => "YMTC",
+        _ => return None,
+    })
 }
 
 impl EmmcStorage {
@@ -172,6 +262,42 @@ impl EmmcStorage {
                 user_size,
                 cid,
                 fwver,
+                // Not part of the XFlash answer
+                erase_group_size: 0,
+            },
+        })
+    }
+
+    /// Parses the eMMC report of legacy DAs, which is big endian and has no kind or block size.
+    pub fn from_legacy_response(data: &[u8]) -> Result<Self> {
+        if data.len() < 92 {
+            return Err(Error::penumbra("Emmc response data too short"));
+        }
+
+        // Status u32, then the sizes
+        let size = |idx: usize| {
+            let pos = 4 + idx * 8;
+            u64::from_be_bytes(data[pos..pos + 8].try_into().unwrap())
+        };
+        let cid = data[68..84].to_vec();
+        // Firmware version bytes, kept in the order they come like with XFlash DAs
+        let fwver = u64::from_le_bytes(data[84..92].try_into().unwrap());
+
+        Ok(EmmcStorage {
+            info: EmmcInfo {
+                kind: 0x1,
+                block_size: 0x200,
+                boot1_size: size(0),
+                boot2_size: size(1),
+                rpmb_size: size(2),
+                gp1_size: size(3),
+                gp2_size: size(4),
+                gp3_size: size(5),
+                gp4_size: size(6),
+                user_size: size(7),
+                cid,
+                fwver,
+                erase_group_size: 0,
             },
         })
     }
@@ -190,6 +316,7 @@ impl EmmcStorage {
 
         let cid_str: String = get_tag(xml, "emmc/id")?;
         let cid = hex::decode(cid_str).map_err(|_| Error::penumbra("Failed to decode EmmcCid"))?;
+        let erase_group_size = get_tag_usize(xml, "emmc/erase_group_size").unwrap_or(0) as u64;
 
         Ok(EmmcStorage {
             info: EmmcInfo {
@@ -205,6 +332,7 @@ impl EmmcStorage {
                 user_size,
                 cid,
                 fwver: 0,
+                erase_group_size,
             },
         })
     }
@@ -9,12 +9,120 @@ use crate::error::{Error, Result};
 
 const EFI_PART_SIGNATURE: &[u8; 8] = b"EFI PART";
 
+/// Where the partition table in use was read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GptSource {
+    /// The primary GPT at the start of the user area.
+    Primary,
+    /// The backup GPT at the end of the user area, used when the primary one is unreadable.
+    /// Its addresses might be stale, so writes based on it are unsafe.
+    Secondary,
+    /// No GPT could be parsed, the partitions come from the MediaTek partition table (PMT)
+    /// of a legacy device. This is the only table these devices have, so it can be trusted.
+    Pmt,
+    /// No partition table could be parsed, only the fixed entries (preloader, PGPT, SGPT)
+    /// are available.
+    #[default]
+    Synthetic,
+}
+
+impl GptSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GptSource::Primary => "PGPT",
+            GptSource::Secondary => "SGPT",
+            GptSource::Pmt => "PMT",
+            GptSource::Synthetic => "None",
+        }
+    }
+
+    /// Whether partition addresses from this table can be relied on for writes.
+    pub fn is_trusted(&self) -> bool {
+        matches!(self, GptSource::Primary | GptSource::Pmt)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum GptType {
     Pgpt,
     Sgpt,
 }
 
+/// A difference between the primary and secondary GPT, for a single partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GptEntryDiff {
+    /// The partition only exists in the primary GPT.
+    OnlyPrimary(Partition),
+    /// The partition only exists in the secondary GPT.
+    OnlySecondary(Partition),
+    /// The partition exists in both, with a different address or size.
+    Changed { primary: Partition, secondary: Partition },
+}
+
+/// How the primary and secondary GPT disagree, see [`Gpt::compare`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GptDiff {
+    pub entries: Vec<GptEntryDiff>,
+    /// Whether the raw partition arrays differ. This also catches changes to partition
+    /// types, GUIDs and attributes, which don't show up in `entries`.
+    pub arrays_differ: bool,
+}
+
+impl GptDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && !self.arrays_differ
+    }
+}
+
+/// Whether the primary and secondary GPT are valid, and how they disagree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GptReport {
+    /// Whether the primary GPT parsed, with a valid header and partition array CRC.
+    pub primary_ok: bool,
+    /// Whether the secondary GPT parsed, with a valid header and partition array CRC.
+    pub secondary_ok: bool,
+    /// How the two tables disagree, always empty unless both are valid.
+    pub diff: GptDiff,
+}
+
+impl GptReport {
+    pub fn new(primary: Option<&Gpt>, secondary: Option<&Gpt>) -> Self {
+        let diff = match (primary, secondary) {
+            (Some(p), Some(s)) => Gpt::compare(p, s),
+            _ => GptDiff::default(),
+        };
+
+        Self { primary_ok: primary.is_some(), secondary_ok: secondary.is_some(), diff }
+    }
+
+    /// Both tables are valid and identical.
+    pub fn is_healthy(&self) -> bool {
+        self.primary_ok && self.secondary_ok && self.diff.is_empty()
+    }
+
+    /// The table to repair the other one from, when only one of them is valid.
+    /// When both are valid but differ there's no telling which one is right, so the
+    /// choice is left to the user.
+    pub fn repair_source(&self) -> Option<GptSource> {
+        match (self.primary_ok, self.secondary_ok) {
+            (true, false) => Some(GptSource::Primary),
+            (false, true) => Some(GptSource::Secondary),
+            _ => None,
+        }
+    }
+}
+
+/// A GPT ready to be written to the user area, see [`Gpt::serialize`].
+#[derive(Debug, Clone)]
+pub struct GptImages {
+    /// Protective MBR, header and partition array, written at the start of the user area.
+    pub primary: Vec<u8>,
+    /// Partition array followed by the header, written at `secondary_offset`.
+    pub secondary: Vec<u8>,
+    /// Byte offset of the secondary GPT inside the user area.
+    pub secondary_offset: u64,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 struct GptHeader {
@@ -29,12 +137,16 @@ struct GptHeader {
     header_crc32: u32,
     part_array_crc32: u32,
     sector_size: usize,
+    /// The header as stored, `header_size` bytes long
+    raw: Vec<u8>,
 }
 
 #[derive(Debug)]
 pub struct Gpt {
-    #[allow(dead_code)]
+    gpt_type: GptType,
     header: GptHeader,
+    /// The partition array as stored, covered by `part_array_crc32`
+    entries: Vec<u8>,
     partitions: Vec<Partition>,
 }
 
@@ -49,7 +161,12 @@ impl Gpt {
         let (gpt_type, header_offset) =
             Self::detect_type(data).ok_or_else(|| Error::penumbra("No valid GPT header found"))?;
 
-        let header = Self::parse_header(data, header_offset)?;
+        // The SGPT header sits in the last sector of the data, not at an offset of one sector
+        let sector_size = match gpt_type {
+            GptType::Pgpt => header_offset,
+            GptType::Sgpt => data.len() - header_offset,
+        };
+        let header = Self::parse_header(data, header_offset, sector_size)?;
 
         let entries_data = match gpt_type {
             GptType::Pgpt => {
@@ -62,23 +179,198 @@ impl Gpt {
             }
             GptType::Sgpt => {
                 let len = header.num_entries as usize * header.entry_size as usize;
-                if data.len() < header_offset || header_offset < len {
-                    return Err(Error::io("SGPT buffer too small for entries"));
-                }
-                &data[0..len]
+                // The data ends with the header, so its first sector is this many LBAs before
+                let sectors = (data.len() / sector_size) as u64;
+                let first_lba = (header.current_lba + 1).checked_sub(sectors);
+                let start = first_lba
+                    .and_then(|first| header.part_entry_lba.checked_sub(first))
+                    .map(|lba| lba as usize * sector_size)
+                    .filter(|&start| start + len <= header_offset)
+                    .ok_or_else(|| Error::io("SGPT buffer too small for entries"))?;
+                &data[start..start + len]
             }
         };
 
         let partitions = Self::parse_partition_entries(entries_data, &header, part_kind)?;
+        let entries = entries_data[..Self::array_len(&header)].to_vec();
 
-        Ok(Self { header, partitions })
+        Ok(Self { gpt_type, header, entries, partitions })
     }
 
     pub fn partitions(&self) -> Vec<Partition> {
         self.partitions.clone()
     }
 
-    fn parse_header(data: &[u8], offset: usize) -> Result<GptHeader> {
+    /// Whether this table was parsed from a primary GPT (header after the protective MBR),
+    /// as opposed to a secondary one (header at the end of the data).
+    pub fn is_primary(&self) -> bool {
+        self.gpt_type == GptType::Pgpt
+    }
+
+    pub fn sector_size(&self) -> usize {
+        self.header.sector_size
+    }
+
+    /// Compares a primary and a secondary GPT. Both having a valid CRC doesn't mean they
+    /// agree, e.g. after an OTA changing the layout got interrupted.
+    /// Partitions are matched by name.
+    pub fn compare(primary: &Gpt, secondary: &Gpt) -> GptDiff {
+        let mut entries = Vec::new();
+
+        for p in &primary.partitions {
+            match secondary.partitions.iter().find(|s| s.name == p.name) {
+                None => entries.push(GptEntryDiff::OnlyPrimary(p.clone())),
+                Some(s) if s.address != p.address || s.size != p.size => {
+                    entries.push(GptEntryDiff::Changed { primary: p.clone(), secondary: s.clone() })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for s in &secondary.partitions {
+            if !primary.partitions.iter().any(|p| p.name == s.name) {
+                entries.push(GptEntryDiff::OnlySecondary(s.clone()));
+            }
+        }
+
+        GptDiff { entries, arrays_differ: primary.entries != secondary.entries }
+    }
+
+    /// LBA of the secondary header, which is the last LBA of the user area.
+    pub fn last_lba(&self) -> u64 {
+        match self.gpt_type {
+            GptType::Pgpt => self.header.backup_lba,
+            GptType::Sgpt => self.header.current_lba,
+        }
+    }
+
+    /// Changes the size of a partition, keeping its start. The new size must be a multiple
+    /// of the sector size, and the partition can't overlap another one or go past the last
+    /// usable LBA. Nothing is written to the device, see [`Gpt::serialize`].
+    pub fn resize_partition(&mut self, name: &str, size: u64) -> Result<()> {
+        let sector = self.header.sector_size as u64;
+        if size == 0 || !size.is_multiple_of(sector) {
+            return Err(Error::penumbra(format!(
+                "Partition size must be a non zero multiple of the sector size ({} bytes)",
+                sector
+            )));
+        }
+
+        let entry_size = self.header.entry_size as usize;
+        let index = self
+            .entries
+            .chunks_exact(entry_size)
+            .position(|entry| !Self::is_unused(entry) && Self::entry_name(entry) == name)
+            .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found in GPT", name)))?;
+
+        let (first_lba, _) = Self::entry_lbas(&self.entries[index * entry_size..]);
+        let last_lba = first_lba + size / sector - 1;
+        if last_lba > self.header.last_usable_lba {
+            return Err(Error::penumbra(format!(
+                "Partition '{}' would end past the last usable LBA ({})",
+                name, self.header.last_usable_lba
+            )));
+        }
+
+        let overlap = self.entries.chunks_exact(entry_size).enumerate().find(|(i, entry)| {
+            let (first, last) = Self::entry_lbas(entry);
+            *i != index && !Self::is_unused(entry) && first <= last_lba && last >= first_lba
+        });
+        if let Some((_, entry)) = overlap {
+            return Err(Error::penumbra(format!(
+                "Partition '{}' would overlap '{}'",
+                name,
+                Self::entry_name(entry)
+            )));
+        }
+
+        let offset = index * entry_size;
+        self.entries[offset + 40..offset + 48].copy_from_slice(&last_lba.to_le_bytes());
+        if let Some(part) = self.partitions.iter_mut().find(|p| p.name == name) {
+            part.size = size;
+        }
+
+        Ok(())
+    }
+
+    /// Builds both the primary and secondary GPT from this table, for writing a modified
+    /// or restored table back to the device.
+    ///
+    /// The headers point to each other, and both the header and partition array CRCs are
+    /// recomputed. A protective MBR covering the user area is generated for the primary GPT.
+    pub fn serialize(&self) -> Result<GptImages> {
+        let sector = self.header.sector_size;
+        let (_, pgpt) = self.relocate(GptSource::Primary)?;
+        let (secondary_offset, secondary) = self.relocate(GptSource::Secondary)?;
+
+        let mut primary = vec![0u8; sector];
+        // Single partition of type 0xEE, covering the whole disk starting at LBA 1
+        let size_lba = self.last_lba().min(u32::MAX as u64) as u32;
+        primary[446..462].copy_from_slice(
+            &[
+                &[0x00, 0x00, 0x02, 0x00, 0xEE, 0xFF, 0xFF, 0xFF][..],
+                &1u32.to_le_bytes(),
+                &size_lba.to_le_bytes(),
+            ]
+            .concat(),
+        );
+        primary[510..512].copy_from_slice(&[0x55, 0xAA]);
+        primary.extend_from_slice(&pgpt);
+
+        Ok(GptImages { primary, secondary, secondary_offset })
+    }
+
+    /// Builds this table as the primary or secondary GPT, to repair one from the other.
+    ///
+    /// The header is adjusted for its new location (MyLBA, AlternateLBA and the partition
+    /// array LBA) and its CRCs recomputed, the partition array is kept as is.
+    /// Returns the byte offset inside the user area to write the data at.
+    /// For the primary GPT, the data starts at LBA 1, leaving the protective MBR untouched.
+    pub fn relocate(&self, target: GptSource) -> Result<(u64, Vec<u8>)> {
+        let sector = self.header.sector_size as u64;
+        let last_lba = self.last_lba();
+
+        let array_sectors = (self.entries.len() as u64).div_ceil(sector);
+        let (current_lba, backup_lba, array_lba) = match target {
+            GptSource::Primary => (1, last_lba, 2),
+            GptSource::Secondary => {
+                let array_lba = last_lba.checked_sub(array_sectors).filter(|&lba| lba > 1);
+                let array_lba = array_lba.ok_or_else(|| {
+                    Error::penumbra(format!("Invalid secondary GPT location (LBA {})", last_lba))
+                })?;
+                (last_lba, 1, array_lba)
+            }
+            GptSource::Pmt | GptSource::Synthetic => {
+                return Err(Error::penumbra("Can only relocate to the primary or secondary GPT"));
+            }
+        };
+
+        let mut header = self.header.raw.clone();
+        header[24..32].copy_from_slice(&current_lba.to_le_bytes());
+        header[32..40].copy_from_slice(&backup_lba.to_le_bytes());
+        header[72..80].copy_from_slice(&array_lba.to_le_bytes());
+        header[88..92].copy_from_slice(&crc32(&self.entries).to_le_bytes());
+        header[16..20].fill(0);
+        let crc = crc32(&header);
+        header[16..20].copy_from_slice(&crc.to_le_bytes());
+        header.resize(sector as usize, 0);
+
+        let mut entries = self.entries.clone();
+        entries.resize((array_sectors * sector) as usize, 0);
+
+        let (offset, data) = match target {
+            GptSource::Secondary => (array_lba * sector, [entries, header].concat()),
+            _ => (sector, [header, entries].concat()),
+        };
+
+        Ok((offset, data))
+    }
+
+    fn array_len(header: &GptHeader) -> usize {
+        header.num_entries as usize * header.entry_size as usize
+    }
+
+    fn parse_header(data: &[u8], offset: usize, sector_size: usize) -> Result<GptHeader> {
         if offset + 92 > data.len() {
             return Err(Error::io("GPT header out of bounds"));
         }
@@ -118,12 +410,13 @@ impl Gpt {
             num_entries: u32::from_le_bytes(hdr[80..84].try_into().unwrap()),
             entry_size: u32::from_le_bytes(hdr[84..88].try_into().unwrap()),
             part_array_crc32: u32::from_le_bytes(hdr[88..92].try_into().unwrap()),
-            sector_size: offset,
+            sector_size,
+            raw: data[offset..offset + header_size].to_vec(),
         })
     }
 
     fn validate_parts_crc(entries: &[u8], header: &GptHeader) -> Result<()> {
-        let array_len = header.num_entries as usize * header.entry_size as usize;
+        let array_len = Self::array_len(header);
         if entries.len() < array_len {
             return Err(Error::io("Partition array out of bounds"));
         }
@@ -156,30 +449,23 @@ impl Gpt {
             }
 
             let entry = &entries_data[off..off + header.entry_size as usize];
-            if entry[0..16].iter().all(|&b| b == 0) {
+            if Self::is_unused(entry) {
                 continue;
             }
 
-            let first_lba = u64::from_le_bytes(entry[32..40].try_into().unwrap());
-            let last_lba = u64::from_le_bytes(entry[40..48].try_into().unwrap());
+            let (first_lba, last_lba) = Self::entry_lbas(entry);
             if last_lba < first_lba {
                 return Err(Error::io("Partition last_lba < first_lba"));
             }
 
-            let name = String::from_utf16_lossy(
-                &entry[56..128]
-                    .chunks_exact(2)
-                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
-                    .take_while(|&c| c != 0)
-                    .collect::<Vec<_>>(),
-            );
+            let name = Self::entry_name(entry);
 
             let sector_size = header.sector_size;
             let size_bytes = (last_lba - first_lba + 1) * sector_size as u64;
 
             parts.push(Partition::new(
                 &name,
-                size_bytes as usize,
+                size_bytes,
                 first_lba * sector_size as u64,
                 part_kind,
             ));
@@ -188,6 +474,28 @@ impl Gpt {
         Ok(parts)
     }
 
+    /// An entry with a zero partition type GUID is unused
+    fn is_unused(entry: &[u8]) -> bool {
+        entry[0..16].iter().all(|&b| b == 0)
+    }
+
+    fn entry_lbas(entry: &[u8]) -> (u64, u64) {
+        (
+            u64::from_le_bytes(entry[32..40].try_into().unwrap()),
+            u64::from_le_bytes(entry[40..48].try_into().unwrap()),
+        )
+    }
+
+    fn entry_name(entry: &[u8]) -> String {
+        String::from_utf16_lossy(
+            &entry[56..128]
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .take_while(|&c| c != 0)
+                .collect::<Vec<_>>(),
+        )
+    }
+
     fn detect_type(data: &[u8]) -> Option<(GptType, usize)> {
         let end = data.len();
         let sector_sizes = [512, 1024, 2048, 4096, 8192];
@@ -0,0 +1,114 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use crate::core::storage::PartitionKind;
+
+/// Bytes needed by [`ImageKind::detect`] to classify an image.
+pub const IMAGE_HEADER_LEN: usize = 0x1000;
+
+/// Headers of images meant for the boot region (preloader), either wrapped with the
+/// storage boot header, or the raw preloader starting with its FILE_INFO block.
+const BOOT_REGION_MAGICS: &[&[u8]] = &[b"EMMC_BOOT", b"UFS_BOOT", b"COMBO_BOOT", b"MMM\x01"];
+
+/// Start of a GFH (MediaTek file header) FILE_INFO block, opening preloaders and auth files.
+const GFH_FILE_INFO_MAGIC: &[u8] = b"MMM\x01";
+const GFH_FILE_INFO_ID: &[u8] = b"FILE_INFO";
+
+/// Offset of the file length (u32, including the GFH and signature) in FILE_INFO.
+const GFH_FILE_LEN_OFFSET: usize = 0x20;
+/// Boot region headers are padded to sector boundaries, so the preloader always starts on one
+/// (0x800 on eMMC, 0x1000 on UFS).
+const BOOT_HEADER_ALIGN: usize = 0x200;
+
+/// Whether `data` starts with a GFH FILE_INFO block
+pub fn has_gfh_header(data: &[u8]) -> bool {
+    data.starts_with(GFH_FILE_INFO_MAGIC) && data.get(8..17) == Some(GFH_FILE_INFO_ID)
+}
+
+/// Extracts the preloader from a boot region dump, dropping the storage boot header
+/// (`EMMC_BOOT`, `UFS_BOOT`, ...) before it and the padding after it.
+/// The result starts with a GFH header and can be given to `DeviceBuilder::with_preloader`.
+/// `None` when no preloader is found.
+pub fn extract_preloader(region: &[u8]) -> Option<&[u8]> {
+    let start = (0..region.len())
+        .step_by(BOOT_HEADER_ALIGN)
+        .find(|&offset| has_gfh_header(&region[offset..]))?;
+
+    let len_bytes = region.get(start + GFH_FILE_LEN_OFFSET..start + GFH_FILE_LEN_OFFSET + 4)?;
+    let len = u32::from_le_bytes(len_bytes.try_into().ok()?) as usize;
+    if len <= GFH_FILE_LEN_OFFSET {
+        return None;
+    }
+
+    region.get(start..start + len)
+}
+
+/// Headers (at offset 0) of images only found in the user area.
+const USER_AREA_MAGICS: &[&[u8]] = &[
+    // Android sparse image
+    &[0x3A, 0xFF, 0x26, 0xED],
+    // Android boot, vendor_boot
+    b"ANDROID!",
+    b"VNDRBOOT",
+    // MTK partition header (lk, logo, ...)
+    &[0x88, 0x16, 0x88, 0x58],
+];
+
+/// Where an image is meant to be written, guessed from its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    /// A preloader image, for the boot region (eMMC Boot1/2, UFS LU0/1).
+    BootRegion,
+    /// An image for a partition of the user area.
+    UserArea,
+    /// Nothing recognizable, no assumption can be made.
+    Unknown,
+}
+
+impl ImageKind {
+    /// Classifies an image from its first bytes, ideally [`IMAGE_HEADER_LEN`] of them.
+    pub fn detect(header: &[u8]) -> Self {
+        if BOOT_REGION_MAGICS.iter().any(|magic| header.starts_with(magic)) {
+            return ImageKind::BootRegion;
+        }
+
+        if USER_AREA_MAGICS.iter().any(|magic| header.starts_with(magic)) {
+            return ImageKind::UserArea;
+        }
+
+        // GPT header right after the protective MBR
+        if header.get(0x200..0x208) == Some(b"EFI PART") {
+            return ImageKind::UserArea;
+        }
+
+        // ext4 superblock magic
+        if header.get(0x438..0x43A) == Some(&[0x53, 0xEF]) {
+            return ImageKind::UserArea;
+        }
+
+        ImageKind::Unknown
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageKind::BootRegion => "boot region",
+            ImageKind::UserArea => "user area",
+            ImageKind::Unknown => "unknown",
+        }
+    }
+
+    /// Whether an image of this kind can be written to a partition of the given kind.
+    /// Unknown images and partitions are always accepted.
+    pub fn fits(&self, target: PartitionKind) -> bool {
+        if matches!(target, PartitionKind::Unknown) {
+            return true;
+        }
+
+        match self {
+            ImageKind::BootRegion => target.is_boot_region(),
+            ImageKind::UserArea => !target.is_boot_region(),
+            ImageKind::Unknown => true,
+        }
+    }
+}
@@ -1,9 +1,19 @@
 pub mod emmc;
 pub mod gpt;
+pub mod image;
+pub mod nand;
+pub mod pmt;
+#[cfg(feature = "da-protocols")]
+pub mod sparse;
 pub mod ufs;
 
+use std::ops::Range;
+
 pub use emmc::EmmcPartition;
-pub use gpt::Gpt;
+pub use gpt::{Gpt, GptDiff, GptEntryDiff, GptImages, GptReport, GptSource};
+pub use image::ImageKind;
+pub use nand::NandStorage;
+pub use pmt::Pmt;
 pub use ufs::UfsPartition;
 
 #[repr(u32)]
@@ -11,28 +21,252 @@ pub use ufs::UfsPartition;
 pub enum StorageType {
     Unknown = 0,
     Emmc = 0x1,
+    Nand = 0x10,
     Ufs = 0x30,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Identifies the physical region of the storage an operation targets.
+///
+/// eMMC exposes fixed hardware partitions: Boot1 and Boot2 hold the preloader and its backup,
+/// RPMB is the authenticated replay protected area, GP1-4 are optional general purpose areas,
+/// and User holds the GPT and every named partition.
+///
+/// UFS instead exposes logical units: LU0 and LU1 are the boot LUs (preloader and backup),
+/// LU2 is the equivalent of the eMMC User area, and LU3 is usually the RPMB well known LU.
+/// Only LU0-LU2 sizes are reported by the DA.
+///
+/// NAND has no hardware partitions, everything lives in the same flash array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PartitionKind {
     Emmc(EmmcPartition),
     Ufs(UfsPartition),
+    Nand,
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Partition {
     pub name: String,
-    pub size: usize,
+    pub size: u64,
     pub address: u64,
     pub kind: PartitionKind,
 }
 
+/// A physical storage region and its capabilities, as reported by the DA.
+#[derive(Debug, Clone, Copy)]
+pub struct RegionInfo {
+    pub kind: PartitionKind,
+    /// Size of the region in bytes.
+    pub size: u64,
+    /// Whether the region can be written through the regular flash commands.
+    /// RPMB requires authenticated frames, so it is never reported as writable.
+    pub writable: bool,
+}
+
+impl RegionInfo {
+    pub fn new(kind: PartitionKind, size: u64, writable: bool) -> Self {
+        Self { kind, size, writable }
+    }
+
+    /// Whether `[address, address + size)` fits inside this region.
+    pub fn contains(&self, address: u64, size: u64) -> bool {
+        address.checked_add(size).is_some_and(|end| end <= self.size)
+    }
+}
+
+/// Identification data of the flash chip, as reported by the DA during storage detection.
+/// Fields the storage doesn't report, or that can't be decoded, are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageHwInfo {
+    /// Flash manufacturer, e.g. `Samsung`.
+    pub vendor: Option<String>,
+    /// Product name or part number.
+    pub product: Option<String>,
+    pub fw_version: Option<String>,
+    pub serial: Option<String>,
+    /// Raw identification bytes: eMMC CID, UFS ID or NAND READ ID.
+    pub raw_cid: Option<Vec<u8>>,
+}
+
+/// Decodes a fixed size text field, padded with NULs or spaces.
+/// `None` when the field is empty or holds anything other than printable ASCII.
+pub(crate) fn ascii_field(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?.trim_matches(|c: char| c == '\0' || c == ' ');
+    (!text.is_empty() && text.chars().all(|c| c.is_ascii_graphic() || c == ' '))
+        .then(|| text.to_string())
+}
+
+/// Raw identification bytes, `None` when the DA left them empty.
+pub(crate) fn raw_id(bytes: &[u8]) -> Option<Vec<u8>> {
+    bytes.iter().any(|&b| b != 0).then(|| bytes.to_vec())
+}
+
+/// How the storage gets rid of erased data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraseMethod {
+    /// The blocks are physically erased (eMMC erase, NAND block erase).
+    Erase,
+    /// The blocks are only unmapped (UFS UNMAP), the controller may keep the old data
+    /// around until it decides to reclaim them.
+    Discard,
+}
+
+impl EraseMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EraseMethod::Erase => "erase",
+            EraseMethod::Discard => "discard",
+        }
+    }
+}
+
+/// A range inside a partition widened to whole blocks, since the DA can't transfer less
+/// than a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockWindow {
+    /// Address of the partition the range is in.
+    pub base: u64,
+    /// The requested range, relative to the partition start.
+    pub range: Range<u64>,
+    /// The block aligned range covering it, clamped to the end of the partition.
+    pub window: Range<u64>,
+    pub block_size: u64,
+}
+
+impl BlockWindow {
+    /// `None` when `[offset, offset + length)` crosses the end of `part`.
+    pub fn new(part: &Partition, offset: u64, length: u64, block_size: u64) -> Option<Self> {
+        let block_size = block_size.max(1);
+        let end = offset.checked_add(length).filter(|&end| end <= part.size)?;
+        let start = offset / block_size * block_size;
+        let aligned_end = (end.div_ceil(block_size) * block_size).min(part.size);
+
+        Some(Self {
+            base: part.address,
+            range: offset..end,
+            window: start..aligned_end,
+            block_size,
+        })
+    }
+
+    /// Absolute address of the window.
+    pub fn address(&self) -> u64 {
+        self.base + self.window.start
+    }
+
+    pub fn size(&self) -> u64 {
+        self.window.end - self.window.start
+    }
+
+    /// Bytes of the window before the requested range.
+    pub fn skip(&self) -> u64 {
+        self.range.start - self.window.start
+    }
+
+    /// Absolute range of the block holding the bytes before the requested range, which
+    /// a partial write reads first to write them back. Its first [`Self::skip`] bytes are
+    /// kept. `None` when the range starts on a block boundary.
+    pub fn head(&self) -> Option<Range<u64>> {
+        let start = self.address();
+        (self.skip() > 0).then(|| start..start + self.block_size.min(self.size()))
+    }
+
+    /// Absolute range of the blocks holding the bytes after the requested range, up to the
+    /// end of the window. Everything past `base + range.end` is kept.
+    /// `None` when the range ends on a block boundary or at the end of the partition.
+    pub fn tail(&self) -> Option<Range<u64>> {
+        let start = self.range.end / self.block_size * self.block_size;
+        (self.range.end < self.window.end).then(|| self.base + start..self.base + self.window.end)
+    }
+}
+
+/// A range to erase split on erase group boundaries.
+///
+/// Only `[start, start + size)` can be handed to the DA: erasing the partial groups
+/// at the head and tail would also wipe the data around the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraseSpan {
+    /// Start of the group aligned part.
+    pub start: u64,
+    /// Size of the group aligned part, 0 when the range doesn't cover a whole group.
+    pub size: u64,
+    /// Bytes before `start` sitting in a partial group.
+    pub head: u64,
+    /// Bytes after `start + size` sitting in a partial group.
+    pub tail: u64,
+}
+
+impl EraseSpan {
+    pub fn new(address: u64, size: u64, group_size: u64) -> Self {
+        let group_size = group_size.max(1);
+        let end = address + size;
+        let start = address.next_multiple_of(group_size);
+        let aligned_end = end - end % group_size;
+
+        if start >= aligned_end {
+            return Self { start: address, size: 0, head: size, tail: 0 };
+        }
+
+        Self { start, size: aligned_end - start, head: start - address, tail: end - aligned_end }
+    }
+}
+
+/// Outcome of an erase, telling what was actually done to each byte of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraseReport {
+    pub method: EraseMethod,
+    /// Bytes erased or discarded by the storage.
+    pub erased: u64,
+    /// Bytes in partial erase groups overwritten with zeros instead.
+    pub zero_filled: u64,
+    /// Bytes in partial erase groups left untouched.
+    pub skipped: u64,
+}
+
+/// The boot regions holding the preloader (Boot1) and its backup (Boot2),
+/// which are eMMC Boot1/Boot2 or UFS LU0/LU1 depending on the storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootRegion {
+    Boot1,
+    Boot2,
+}
+
+impl BootRegion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BootRegion::Boot1 => "boot1",
+            BootRegion::Boot2 => "boot2",
+        }
+    }
+
+    /// The section and size of the region on `storage`.
+    /// `None` when the storage has no such region, like NAND, or reports it as empty.
+    pub fn locate(&self, storage: &dyn Storage) -> Option<(PartitionKind, u64)> {
+        let (kind, size) = match self {
+            BootRegion::Boot1 => (storage.get_pl_part1(), storage.get_pl1_size()),
+            BootRegion::Boot2 => (storage.get_pl_part2(), storage.get_pl2_size()),
+        };
+
+        (kind.is_boot_region() && size != 0).then_some((kind, size))
+    }
+}
+
 impl Partition {
-    pub fn new(name: &str, size: usize, address: u64, kind: PartitionKind) -> Self {
+    pub fn new(name: &str, size: u64, address: u64, kind: PartitionKind) -> Self {
         Self { name: name.to_string(), size, address, kind }
     }
+
+    /// Whether the partition ends inside its storage region. A partition table made for
+    /// a bigger storage (e.g. firmware of another variant) has entries past the end.
+    /// Partitions in regions the storage doesn't report can't be checked and are assumed
+    /// to fit.
+    pub fn fits(&self, storage: &dyn Storage) -> bool {
+        storage
+            .regions()
+            .iter()
+            .find(|r| r.kind == self.kind)
+            .is_none_or(|r| r.contains(self.address, self.size))
+    }
 }
 
 impl PartitionKind {
@@ -40,14 +274,28 @@ impl PartitionKind {
         match self {
             PartitionKind::Emmc(part) => *part as u32,
             PartitionKind::Ufs(part) => *part as u32,
+            // Whole flash array
+            PartitionKind::Nand => 0,
             PartitionKind::Unknown => 0,
         }
     }
 
+    /// Whether the partition lives in the boot region (eMMC Boot1/2, UFS LU0/1),
+    /// where the preloader is stored.
+    pub fn is_boot_region(&self) -> bool {
+        matches!(
+            self,
+            PartitionKind::Emmc(
+                EmmcPartition::Boot1 | EmmcPartition::Boot2 | EmmcPartition::Boot1Boot2
+            ) | PartitionKind::Ufs(UfsPartition::Lu0 | UfsPartition::Lu1 | UfsPartition::Lu0Lu1)
+        )
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             PartitionKind::Emmc(part) => part.as_str(),
             PartitionKind::Ufs(part) => part.as_str(),
+            PartitionKind::Nand => "NAND-WHOLE",
             PartitionKind::Unknown => "Unknown",
         }
     }
@@ -66,8 +314,88 @@ pub trait Storage: Send + Sync {
     fn get_pl1_size(&self) -> u64;
     fn get_pl2_size(&self) -> u64;
     fn get_user_size(&self) -> u64;
+
+    /// All the regions exposed by the storage, skipping the ones with a size of 0.
+    fn regions(&self) -> Vec<RegionInfo>;
+
+    /// Smallest unit the storage erases at once, in bytes, as reported by the DA.
+    /// The block size when the DA doesn't report it.
+    fn erase_group_size(&self) -> u64 {
+        self.block_size() as u64
+    }
+
+    fn erase_method(&self) -> EraseMethod {
+        EraseMethod::Erase
+    }
+
+    /// Vendor, product and firmware of the flash chip.
+    fn hw_info(&self) -> StorageHwInfo;
 }
 
 pub fn is_pl_part(name: &str) -> bool {
     matches!(name, "preloader" | "preloader_backup")
 }
+
+/// Whether `name` is the primary or secondary GPT, which are written whole, at the storage's
+/// fixed offsets, whatever the partition table was read from.
+pub fn is_gpt_part(name: &str) -> bool {
+    name.eq_ignore_ascii_case("pgpt") || name.eq_ignore_ascii_case("sgpt")
+}
+
+/// Partitions without which the device can't boot or be flashed again, or holding per-device
+/// data that can't be restored
+const PROTECTED_PARTS: &[&str] = &[
+    "preloader",
+    "preloader_backup",
+    "pgpt",
+    "sgpt",
+    "lk",
+    "lk2",
+    "boot",
+    "seccfg",
+    "nvram",
+    "nvdata",
+    "proinfo",
+    "protect1",
+    "protect2",
+];
+
+/// Whether `name` is one of the partitions bulk operations leave alone unless told otherwise,
+/// and the CLI asks to type back before erasing.
+pub fn is_protected_part(name: &str) -> bool {
+    PROTECTED_PARTS.iter().any(|p| p.eq_ignore_ascii_case(name))
+}
+
+/// Matches a partition name against a glob-like pattern, ignoring case.
+/// `*` matches any number of characters and `?` a single one, e.g. `md*` or `nvdata`.
+pub fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.to_ascii_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_ascii_lowercase().chars().collect();
+
+    let (mut n, mut p) = (0, 0);
+    // Position after the last `*`, and the name position it was tried against
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                n += 1;
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    backtrack = Some((bp, bn + 1));
+                    p = bp;
+                    n = bn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,163 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use async_trait::async_trait;
+
+use crate::core::storage::{
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageHwInfo,
+    StorageType,
+    raw_id,
+};
+use crate::error::{Error, Result};
+use crate::utilities::xml::{get_tag, get_tag_usize};
+
+#[derive(Debug)]
+pub struct NandInfo {
+    pub kind: u32,
+    /// Smallest unit that can be read or programmed
+    pub page_size: u32,
+    /// Smallest unit that can be erased
+    pub erase_size: u32,
+    pub spare_size: u32,
+    pub total_size: u64,
+    /// Size left once the blocks reserved for bad block management are removed
+    pub available_size: u64,
+    pub bmt_exist: bool,
+    pub id: Vec<u8>,
+}
+
+/// Represents raw NAND storage, found on legacy devices.
+///
+/// NAND has no hardware partitions: the preloader, the partition table (PMT)
+/// and every named partition share the same flash array.
+pub struct NandStorage {
+    pub info: NandInfo,
+}
+
+#[async_trait]
+impl Storage for NandStorage {
+    fn kind(&self) -> StorageType {
+        StorageType::Nand
+    }
+
+    // Reads and writes are done per page, erase blocks are only relevant when erasing
+    fn block_size(&self) -> u32 {
+        self.info.page_size
+    }
+
+    fn total_size(&self) -> u64 {
+        self.info.total_size
+    }
+
+    fn get_user_part(&self) -> PartitionKind {
+        PartitionKind::Nand
+    }
+
+    fn get_pl_part1(&self) -> PartitionKind {
+        PartitionKind::Nand
+    }
+
+    fn get_pl_part2(&self) -> PartitionKind {
+        PartitionKind::Nand
+    }
+
+    fn get_pl1_size(&self) -> u64 {
+        0
+    }
+
+    fn get_pl2_size(&self) -> u64 {
+        0
+    }
+
+    fn get_user_size(&self) -> u64 {
+        if self.info.available_size != 0 { self.info.available_size } else { self.info.total_size }
+    }
+
+    fn regions(&self) -> Vec<RegionInfo> {
+        vec![RegionInfo::new(PartitionKind::Nand, self.get_user_size(), true)]
+    }
+
+    fn erase_group_size(&self) -> u64 {
+        self.info.erase_size as u64
+    }
+
+    fn hw_info(&self) -> StorageHwInfo {
+        let raw_cid = raw_id(&self.info.id);
+        let vendor = raw_cid.as_ref().and_then(|id| nand_vendor(id[0])).map(str::to_string);
+
+        StorageHwInfo { vendor, raw_cid, ..Default::default() }
+    }
+}
+
+/// Manufacturer name from the first byte of the READ ID answer.
+fn nand_vendor(id: u8) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Spansion",
+        0x2C => "Micron",
+        0x98 => "Toshiba/Kioxia",
+        0xAD => "SK Hynix",
+        0xC2 => "Macronix",
+        0xC8 => "GigaDevice",
+        0xEC => "Samsung",
+        _ => return None,
+    })
+}
+
+impl NandStorage {
+    pub fn from_response(data: &[u8]) -> Result<Self> {
+        if data.len() < 45 {
+            return Err(Error::io("NAND response data too short"));
+        }
+
+        // 0x10 == NAND
+        let kind = u32::from_le_bytes(data[0..4].try_into().unwrap());
+        let page_size = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let erase_size = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        let spare_size = u32::from_le_bytes(data[12..16].try_into().unwrap());
+        let total_size = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let available_size = u64::from_le_bytes(data[24..32].try_into().unwrap());
+        let bmt_exist = data[32] != 0;
+        let id = data[33..45].to_vec();
+
+        Ok(NandStorage {
+            info: NandInfo {
+                kind,
+                page_size,
+                erase_size,
+                spare_size,
+                total_size,
+                available_size,
+                bmt_exist,
+                id,
+            },
+        })
+    }
+
+    pub fn from_xml_response(xml: &str) -> Result<Self> {
+        let page_size = get_tag_usize(xml, "nand/page_size")? as u32;
+        let erase_size = get_tag_usize(xml, "nand/block_size")? as u32;
+        let spare_size = get_tag_usize(xml, "nand/spare_size").unwrap_or(0) as u32;
+        let total_size = get_tag_usize(xml, "nand/total_size")? as u64;
+        let available_size = get_tag_usize(xml, "nand/available_size").unwrap_or(0) as u64;
+
+        let id_str: String = get_tag(xml, "nand/id").unwrap_or_default();
+        let id = hex::decode(id_str).map_err(|_| Error::penumbra("Failed to decode NAND id"))?;
+
+        Ok(NandStorage {
+            info: NandInfo {
+                kind: 0x10,
+                page_size,
+                erase_size,
+                spare_size,
+                total_size,
+                available_size,
+                bmt_exist: false,
+                id,
+            },
+        })
+    }
+}
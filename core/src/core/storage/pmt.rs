@@ -0,0 +1,75 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use crate::core::storage::{EmmcPartition, Partition, PartitionKind, Storage, StorageType};
+use crate::error::{Error, Result};
+
+/// Signatures of the PMT header, followed by a 4 bytes version field
+const PMT_MAGICS: [&[u8; 4]; 2] = [b"PTv3", b"MPT3"];
+const PMT_HEADER_SIZE: usize = 8;
+const PMT_ENTRY_SIZE: usize = 96;
+const PMT_MAX_ENTRIES: usize = 128;
+
+/// The MediaTek partition table (PMT), used instead of a GPT by legacy devices,
+/// NAND ones in particular.
+///
+/// Each entry is 96 bytes long:
+/// `name[64] | size u64 | part_id u64 | offset u64 | mask_flags u64`
+#[derive(Debug)]
+pub struct Pmt {
+    partitions: Vec<Partition>,
+}
+
+impl Pmt {
+    pub fn parse(data: &[u8], storage_type: StorageType) -> Result<Self> {
+        let part_kind = match storage_type {
+            StorageType::Emmc => PartitionKind::Emmc(EmmcPartition::User),
+            StorageType::Nand => PartitionKind::Nand,
+            _ => PartitionKind::Unknown,
+        };
+
+        if data.len() < PMT_HEADER_SIZE || !PMT_MAGICS.iter().any(|m| data[0..4] == m[..]) {
+            return Err(Error::penumbra("No valid PMT header found"));
+        }
+
+        let mut partitions = Vec::new();
+
+        for entry in data[PMT_HEADER_SIZE..].chunks_exact(PMT_ENTRY_SIZE).take(PMT_MAX_ENTRIES) {
+            // The table ends at the first entry without a name
+            if entry[0] == 0 {
+                break;
+            }
+
+            let name = String::from_utf8_lossy(&entry[0..64]);
+            let name = name.trim_end_matches('\0');
+            let size = u64::from_le_bytes(entry[64..72].try_into().unwrap());
+            let offset = u64::from_le_bytes(entry[80..88].try_into().unwrap());
+
+            partitions.push(Partition::new(name, size, offset, part_kind));
+        }
+
+        if partitions.is_empty() {
+            return Err(Error::penumbra("PMT has no partitions"));
+        }
+
+        Ok(Self { partitions })
+    }
+
+    pub fn partitions(&self) -> Vec<Partition> {
+        self.partitions.clone()
+    }
+
+    /// Where the PMT is stored: the two last blocks of the user area.
+    /// Returns the address and the size to read.
+    pub fn location(storage: &dyn Storage) -> (u64, u64) {
+        let size = storage.block_size() as u64 * 2;
+        (storage.get_user_size().saturating_sub(size), size)
+    }
+}
+
+impl From<Pmt> for Vec<Partition> {
+    fn from(pmt: Pmt) -> Self {
+        pmt.partitions
+    }
+}
@@ -0,0 +1,294 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::io::{Error as IoError, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+pub const SPARSE_MAGIC: u32 = 0xED26FF3A;
+
+const SPARSE_HEADER_LEN: usize = 28;
+const CHUNK_HEADER_LEN: usize = 12;
+
+const CHUNK_TYPE_RAW: u16 = 0xCAC1;
+const CHUNK_TYPE_FILL: u16 = 0xCAC2;
+const CHUNK_TYPE_DONT_CARE: u16 = 0xCAC3;
+const CHUNK_TYPE_CRC32: u16 = 0xCAC4;
+
+/// Bytes read from the inner reader at once when copying RAW chunks
+const SCRATCH_LEN: usize = 0x10000;
+
+/// Header of an Android sparse image.
+#[derive(Debug, Clone, Copy)]
+pub struct SparseHeader {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub file_hdr_sz: u16,
+    pub chunk_hdr_sz: u16,
+    pub blk_sz: u32,
+    pub total_blks: u32,
+    pub total_chunks: u32,
+}
+
+impl SparseHeader {
+    /// Parses the sparse header at the start of `data`.
+    /// Returns `None` if `data` isn't a (supported) sparse image.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < SPARSE_HEADER_LEN {
+            return None;
+        }
+
+        let u16_at = |off: usize| u16::from_le_bytes([data[off], data[off + 1]]);
+        let u32_at = |off: usize| u32::from_le_bytes(data[off..off + 4].try_into().unwrap());
+
+        if u32_at(0) != SPARSE_MAGIC {
+            return None;
+        }
+
+        let header = Self {
+            major_version: u16_at(4),
+            minor_version: u16_at(6),
+            file_hdr_sz: u16_at(8),
+            chunk_hdr_sz: u16_at(10),
+            blk_sz: u32_at(12),
+            total_blks: u32_at(16),
+            total_chunks: u32_at(20),
+        };
+
+        let valid = header.major_version == 1
+            && header.file_hdr_sz as usize >= SPARSE_HEADER_LEN
+            && header.chunk_hdr_sz as usize >= CHUNK_HEADER_LEN
+            && header.blk_sz != 0
+            && header.blk_sz.is_multiple_of(4);
+
+        valid.then_some(header)
+    }
+
+    /// Size of the image once expanded.
+    pub fn expanded_size(&self) -> u64 {
+        self.total_blks as u64 * self.blk_sz as u64
+    }
+}
+
+enum State {
+    FileHeader,
+    ChunkHeader,
+    FillValue { left: u64 },
+    Raw { left: u64 },
+    Fill { pattern: [u8; 4], left: u64 },
+    Zero { left: u64 },
+    Skip { left: u64 },
+    Done,
+}
+
+/// Reader expanding an Android sparse image on the fly, yielding the raw image.
+///
+/// DONT_CARE chunks are expanded to zeroes, since the data is streamed to the DA
+/// sequentially and holes can't be skipped.
+pub struct SparseReader<'a> {
+    inner: &'a mut (dyn AsyncRead + Unpin + Send),
+    header: SparseHeader,
+    state: State,
+    chunks_left: u32,
+    /// Expanded bytes produced so far, to validate against the header
+    produced: u64,
+    /// Partially read header bytes
+    pending: Vec<u8>,
+    scratch: Vec<u8>,
+}
+
+impl<'a> SparseReader<'a> {
+    /// Wraps a reader positioned at the start of a sparse image described by `header`.
+    pub fn new(inner: &'a mut (dyn AsyncRead + Unpin + Send), header: SparseHeader) -> Self {
+        Self {
+            inner,
+            header,
+            state: State::FileHeader,
+            chunks_left: header.total_chunks,
+            produced: 0,
+            pending: Vec::new(),
+            scratch: vec![0u8; SCRATCH_LEN],
+        }
+    }
+
+    /// Reads from the inner reader until `pending` holds `len` bytes.
+    fn poll_pending(&mut self, cx: &mut Context<'_>, len: usize) -> Poll<std::io::Result<()>> {
+        while self.pending.len() < len {
+            let want = (len - self.pending.len()).min(self.scratch.len());
+            let mut buf = ReadBuf::new(&mut self.scratch[..want]);
+            match Pin::new(&mut *self.inner).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    return Poll::Ready(Err(truncated()));
+                }
+                Poll::Ready(Ok(())) => self.pending.extend_from_slice(buf.filled()),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn next_chunk(&mut self) -> std::io::Result<()> {
+        if self.chunks_left > 0 {
+            self.state = State::ChunkHeader;
+            return Ok(());
+        }
+
+        if self.produced != self.header.expanded_size() {
+            return Err(invalid(format!(
+                "Sparse image expands to 0x{:X} bytes, header says 0x{:X}",
+                self.produced,
+                self.header.expanded_size()
+            )));
+        }
+
+        self.state = State::Done;
+        Ok(())
+    }
+
+    fn parse_chunk_header(&mut self) -> std::io::Result<()> {
+        let hdr = &self.pending;
+        let chunk_type = u16::from_le_bytes([hdr[0], hdr[1]]);
+        let chunk_sz = u32::from_le_bytes(hdr[4..8].try_into().unwrap());
+        let total_sz = u32::from_le_bytes(hdr[8..12].try_into().unwrap()) as u64;
+        let data_sz = total_sz
+            .checked_sub(self.header.chunk_hdr_sz as u64)
+            .ok_or_else(|| invalid(format!("Invalid sparse chunk size 0x{:X}", total_sz)))?;
+
+        self.pending.clear();
+        self.chunks_left -= 1;
+
+        let out_sz = chunk_sz as u64 * self.header.blk_sz as u64;
+        if chunk_type != CHUNK_TYPE_CRC32 {
+            self.produced += out_sz;
+            if self.produced > self.header.expanded_size() {
+                return Err(invalid("Sparse chunks exceed the image size".to_string()));
+            }
+        }
+
+        let expect = |expected: u64| {
+            if data_sz == expected {
+                Ok(())
+            } else {
+                Err(invalid(format!(
+                    "Sparse chunk 0x{:04X} has 0x{:X} bytes of data, expected 0x{:X}",
+                    chunk_type, data_sz, expected
+                )))
+            }
+        };
+
+        self.state = match chunk_type {
+            CHUNK_TYPE_RAW => {
+                expect(out_sz)?;
+                State::Raw { left: out_sz }
+            }
+            CHUNK_TYPE_FILL => {
+                expect(4)?;
+                State::FillValue { left: out_sz }
+            }
+            CHUNK_TYPE_DONT_CARE => {
+                expect(0)?;
+                State::Zero { left: out_sz }
+            }
+            CHUNK_TYPE_CRC32 => State::Skip { left: data_sz },
+            _ => return Err(invalid(format!("Unknown sparse chunk type 0x{:04X}", chunk_type))),
+        };
+
+        Ok(())
+    }
+}
+
+impl AsyncRead for SparseReader<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+
+        loop {
+            if buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            match this.state {
+                State::FileHeader => {
+                    std::task::ready!(this.poll_pending(cx, this.header.file_hdr_sz as usize))?;
+                    this.pending.clear();
+                    this.next_chunk()?;
+                }
+                State::ChunkHeader => {
+                    std::task::ready!(this.poll_pending(cx, this.header.chunk_hdr_sz as usize))?;
+                    this.parse_chunk_header()?;
+                }
+                State::FillValue { left } => {
+                    std::task::ready!(this.poll_pending(cx, 4))?;
+                    let pattern = this.pending[..4].try_into().unwrap();
+                    this.pending.clear();
+                    this.state = State::Fill { pattern, left };
+                }
+                State::Raw { left: 0 }
+                | State::Fill { left: 0, .. }
+                | State::Zero { left: 0 }
+                | State::Skip { left: 0 } => this.next_chunk()?,
+                State::Raw { ref mut left } => {
+                    let want = (*left).min(buf.remaining() as u64) as usize;
+                    let want = want.min(this.scratch.len());
+                    let mut tmp = ReadBuf::new(&mut this.scratch[..want]);
+                    std::task::ready!(Pin::new(&mut *this.inner).poll_read(cx, &mut tmp))?;
+
+                    let read = tmp.filled().len();
+                    if read == 0 {
+                        return Poll::Ready(Err(truncated()));
+                    }
+
+                    buf.put_slice(tmp.filled());
+                    *left -= read as u64;
+                    return Poll::Ready(Ok(()));
+                }
+                State::Fill { pattern, ref mut left } => {
+                    // blk_sz is a multiple of 4, so the pattern phase only depends on `left`
+                    let n = (*left).min(buf.remaining() as u64) as usize;
+                    let phase = ((4 - (*left % 4)) % 4) as usize;
+                    let bytes: Vec<u8> = (0..n).map(|i| pattern[(phase + i) % 4]).collect();
+
+                    buf.put_slice(&bytes);
+                    *left -= n as u64;
+                    return Poll::Ready(Ok(()));
+                }
+                State::Zero { ref mut left } => {
+                    let n = (*left).min(buf.remaining() as u64) as usize;
+                    let n = n.min(this.scratch.len());
+
+                    buf.put_slice(&vec![0u8; n]);
+                    *left -= n as u64;
+                    return Poll::Ready(Ok(()));
+                }
+                State::Skip { ref mut left } => {
+                    let want = (*left).min(this.scratch.len() as u64) as usize;
+                    let mut tmp = ReadBuf::new(&mut this.scratch[..want]);
+                    std::task::ready!(Pin::new(&mut *this.inner).poll_read(cx, &mut tmp))?;
+
+                    let read = tmp.filled().len();
+                    if read == 0 {
+                        return Poll::Ready(Err(truncated()));
+                    }
+                    *left -= read as u64;
+                }
+                State::Done => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+fn truncated() -> IoError {
+    IoError::new(ErrorKind::UnexpectedEof, "Sparse image is truncated")
+}
+
+fn invalid(msg: String) -> IoError {
+    IoError::new(ErrorKind::InvalidData, msg)
+}
@@ -4,7 +4,16 @@
 */
 use async_trait::async_trait;
 
-use crate::core::storage::{PartitionKind, Storage, StorageType};
+use crate::core::storage::{
+    EraseMethod,
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageHwInfo,
+    StorageType,
+    ascii_field,
+    raw_id,
+};
 use crate::error::{Error, Result};
 use crate::utilities::xml::{get_tag, get_tag_usize};
 
@@ -99,6 +108,53 @@ impl Storage for UfsStorage {
     fn get_user_size(&self) -> u64 {
         self.info.lu2_size
     }
+
+    fn regions(&self) -> Vec<RegionInfo> {
+        [
+            (UfsPartition::Lu0, self.info.lu0_size),
+            (UfsPartition::Lu1, self.info.lu1_size),
+            (UfsPartition::Lu2, self.info.lu2_size),
+        ]
+        .into_iter()
+        .filter(|(_, size)| *size != 0)
+        .map(|(part, size)| RegionInfo::new(PartitionKind::Ufs(part), size, true))
+        .collect()
+    }
+
+    // DAs erase UFS with UNMAP, which only drops the logical to physical mapping
+    fn erase_method(&self) -> EraseMethod {
+        EraseMethod::Discard
+    }
+
+    fn hw_info(&self) -> StorageHwInfo {
+        // The ID is the product name from the device descriptor strings
+        let product = ascii_field(&self.info.cid);
+        let vendor = product.as_deref().and_then(ufs_vendor).map(str::to_string);
+        let text_or_hex = |bytes: &[u8]| {
+            raw_id(bytes).map(|b| ascii_field(&b).unwrap_or_else(|| hex::encode_upper(&b)))
+        };
+
+        StorageHwInfo {
+            vendor,
+            product,
+            fw_version: text_or_hex(&self.info.fwver),
+            serial: text_or_hex(&self.info.serial),
+            raw_cid: raw_id(&self.info.cid),
+        }
+    }
+}
+
+/// Manufacturer name guessed from the part number prefix.
+fn ufs_vendor(product: &str) -> Option<&'static str> {
+    const PREFIXES: &[(&str, &str)] = &[
+        ("KLU", "Samsung"),
+        ("H9", "SK Hynix"),
+        ("THG", "Toshiba/Kioxia"),
+        ("MT", "Micron"),
+        ("SDIN", "SanDisk"),
+    ];
+
+    PREFIXES.iter().find(|(prefix, _)| product.starts_with(prefix)).map(|(_, vendor)| *vendor)
 }
 
 impl UfsStorage {
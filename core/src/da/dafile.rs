@@ -3,7 +3,9 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 use log::debug;
+use sha2::{Digest, Sha256};
 
+use crate::core::chipdb;
 use crate::error::{Error, Result};
 
 /// Protocol used by the DA
@@ -53,6 +55,32 @@ pub struct DA {
     pub hw_code: u16,
     /// Always seems to be 0xCA00
     pub hw_sub_code: u16,
+    /// Firmware version of the DA entry, 0 on Legacy DAs
+    pub sw_version: u16,
+}
+
+/// A chip supported by a DA file, see [`DAFile::supported_chips`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaChipEntry {
+    /// Hardware code of the entry, in the form used by [`DA::hw_code`]
+    pub hw_code: u16,
+    pub hw_sub_code: u16,
+    pub fw_version: u16,
+}
+
+impl DaChipEntry {
+    /// Chip name for display, like `MT6768`. XFlash DAs already use the marketing number,
+    /// other DAs the hardware code, named when known.
+    pub fn label(&self) -> String {
+        if self.hw_code >= 0x6000 {
+            return format!("MT{:04X}", self.hw_code);
+        }
+
+        match chipdb::chip_name(self.hw_code) {
+            Some(name) => name.to_string(),
+            None => format!("0x{:04X}", self.hw_code),
+        }
+    }
 }
 
 /// Represents a Download Agent (DA) file containing multiple DA entries
@@ -110,6 +138,7 @@ impl DAFile {
             let hw_code = u16::from_le_bytes(da_entry[0x02..0x04].try_into().unwrap());
             let hw_sub_code = u16::from_le_bytes(da_entry[0x04..0x06].try_into().unwrap());
             let _hw_version = u16::from_le_bytes(da_entry[0x06..0x08].try_into().unwrap());
+            let sw_version = u16::from_le_bytes(da_entry[0x08..0x0A].try_into().unwrap());
             let mut regions: Vec<DAEntryRegion> = Vec::new();
             let region_count = u16::from_le_bytes(da_entry[0x12..0x14].try_into().unwrap());
             // Structure of the DA header entry
@@ -163,7 +192,14 @@ impl DAFile {
                 current_region_offset += 20; // Move to the next region header
             }
 
-            das.push(DA { da_type: inner_da_type, regions, magic, hw_code, hw_sub_code });
+            das.push(DA {
+                da_type: inner_da_type,
+                regions,
+                magic,
+                hw_code,
+                hw_sub_code,
+                sw_version,
+            });
             debug!(
                 "Parsed DA entry: hw_code={:04X}, hw_sub_code={:04X}, regions={}",
                 hw_code, hw_sub_code, region_count
@@ -173,6 +209,24 @@ impl DAFile {
         Ok(DAFile { da_raw_data: raw_data.to_vec(), da_type, das })
     }
 
+    /// Chips the DA file has an entry for, in file order
+    pub fn supported_chips(&self) -> Vec<DaChipEntry> {
+        self.das
+            .iter()
+            .map(|da| DaChipEntry {
+                hw_code: da.hw_code,
+                hw_sub_code: da.hw_sub_code,
+                fw_version: da.sw_version,
+            })
+            .collect()
+    }
+
+    /// Picks the DA entry for a device `hw_code`, as reported by BROM or the preloader
+    pub fn select_for(&self, hw_code: u16) -> Result<DA> {
+        self.get_da_from_hw_code(hw_code)
+            .ok_or_else(|| Error::penumbra(format!("DA does not support 0x{:04X}", hw_code)))
+    }
+
     // TODO: Make an Hashmap, possibly also including other info about a chip
     pub fn get_da_from_hw_code(&self, hw_code: u16) -> Option<DA> {
         let da_code = match hw_code {
@@ -275,3 +329,31 @@ impl DA {
         false
     }
 }
+
+impl DAEntryRegion {
+    /// SHA-256 of the region without its signature, as DA1 checks it for DA2
+    pub fn digest(&self) -> [u8; 32] {
+        let end = self.data.len().saturating_sub(self.sig_len as usize);
+        Sha256::digest(&self.data[..end]).into()
+    }
+}
+
+/// Replaces the digest of `old_da2` embedded in `da1` with the one of `new_da2`, so DA1
+/// accepts a patched DA2 through its own verification instead of having it disabled.
+/// Works on both V5 and V6 DAs, as the digest is found by value rather than by position.
+///
+/// Returns the offset of the digest in DA1, or `None` when DA1 doesn't hold it
+/// (e.g. DAs hashing DA2 with SHA-1, which isn't supported).
+pub fn hash_fixup(
+    da1: &mut DAEntryRegion,
+    old_da2: &DAEntryRegion,
+    new_da2: &DAEntryRegion,
+) -> Option<usize> {
+    let old_digest = old_da2.digest();
+    let offset = da1.data.windows(old_digest.len()).position(|w| w == old_digest)?;
+
+    da1.data[offset..offset + old_digest.len()].copy_from_slice(&new_da2.digest());
+    debug!("Patched DA2 digest in DA1 at offset 0x{:X}", offset);
+
+    Some(offset)
+}
@@ -0,0 +1,63 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+
+    Derived from:
+    https://github.com/bkerler/mtkclient/blob/main/mtkclient/Library/DA/legacy/dalegacy_param.py
+    Original SPDX-License-Identifier: GPL-3.0-or-later
+    Original SPDX-FileCopyrightText: 2018–2024 bkerler
+
+    This file remains under the GPL-3.0-or-later license.
+    However, as part of a larger project licensed under the AGPL-3.0-or-later,
+    the combined work is subject to the networking terms of the AGPL-3.0-or-later,
+    as for term 13 of the GPL-3.0-or-later license.
+*/
+
+/// Chunk size used to send DA2
+pub const DA2_PACKET_LENGTH: usize = 0x1000;
+/// Chunk size requested when reading and writing flash
+pub const FLASH_PACKET_LENGTH: usize = 0x100000;
+/// Storage selector of the read and write commands
+pub const STORAGE_EMMC: u8 = 0x02;
+/// Host selector of the read command (Linux)
+pub const HOST_LINUX: u8 = 0x0C;
+/// Watchdog timeout of a reboot, in milliseconds
+pub const REBOOT_TIMEOUT_MS: u32 = 1000;
+
+/// Ext RAM statuses asking for DRAM settings before DA2 can be sent
+pub const EXT_RAM_NEEDS_SETTINGS: [u32; 2] = [0xBC3, 0xBC4];
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmd {
+    SdmmcSwitchPart = 0x60,
+    SdmmcWriteData = 0x62,
+    Read = 0xD6,
+    Finish = 0xD9,
+    EnableWatchdog = 0xDB,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Rsp {
+    SocOk = 0xC1,
+    SocFail = 0xCF,
+    Sync = 0xC0,
+    Cont = 0x69,
+    Stop = 0x96,
+    Ack = 0x5A,
+    Nack = 0xA5,
+    UnknownCmd = 0xBB,
+}
+
+/// Size of each storage report the DA sends once DA2 runs
+pub const NOR_INFO_LENGTH: usize = 0x1C;
+/// Fixed part of the NAND report, the ID count being its last two bytes
+pub const NAND_INFO_LENGTH: usize = 0x11;
+/// Trailer following the NAND IDs
+pub const NAND_INFO_TRAILER: usize = 9;
+pub const EMMC_INFO_LENGTH: usize = 0x5C;
+pub const SDC_INFO_LENGTH: usize = 0x1C;
+pub const CONFIG_INFO_LENGTH: usize = 0x26;
+pub const PASS_INFO_LENGTH: usize = 0xA;
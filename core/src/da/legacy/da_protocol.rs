@@ -0,0 +1,357 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+
+use log::{info, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::Connection;
+use crate::connection::port::{ConnectionType, UsbSpeed};
+use crate::core::devinfo::DeviceInfo;
+use crate::core::progress::ProgressFn;
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::seccfg::{LockFlag, SecCfg};
+use crate::core::storage::{
+    Gpt,
+    GptReport,
+    GptSource,
+    Partition,
+    PartitionKind,
+    Storage,
+    StorageType,
+};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::da::DAEntryRegion;
+use crate::da::legacy::cmds::*;
+use crate::da::legacy::{Legacy, flash};
+use crate::da::protocol::{BootMode, ExtState};
+use crate::da::{DA, DAProtocol};
+use crate::error::{Error, Result};
+
+#[async_trait::async_trait]
+impl DAProtocol for Legacy {
+    async fn upload_da(&mut self) -> Result<bool> {
+        let da1 = self.da.get_da1().ok_or_else(|| Error::penumbra("DA1 region not found"))?;
+        self.upload_stage1(da1.addr, da1.length, da1.data.clone(), da1.sig_len)
+            .await
+            .map_err(|e| Error::proto(format!("Failed to upload DA1: {}", e)))?;
+
+        self.send_stage2_config().await?;
+
+        let da2 = self.da.get_da2().ok_or_else(|| Error::penumbra("DA2 region not found"))?;
+        let sig_len = da2.sig_len as usize;
+        let da2data = da2.data[..da2.data.len().saturating_sub(sig_len)].to_vec();
+        let da2addr = da2.addr;
+        info!(
+            "[Penumbra] Uploading DA2 to address 0x{:08X} with size 0x{:X} bytes",
+            da2addr,
+            da2data.len()
+        );
+        self.upload_stage2(da2addr, &da2data)
+            .await
+            .map_err(|e| Error::proto(format!("Error uploading DA2: {}", e)))?;
+        info!("[Penumbra] Successfully uploaded and executed DA2");
+
+        let storage = self.read_flash_info().await?;
+        self.dev_info.set_storage(Arc::new(storage)).await;
+
+        Ok(true)
+    }
+
+    async fn boot_to(&mut self, _addr: u32, _data: &[u8]) -> Result<bool> {
+        Err(Error::Unsupported("Booting code is not supported on legacy DAs".into()))
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<bool> {
+        self.conn.write_chunk(data).await?;
+        Ok(true)
+    }
+
+    async fn send_data(&mut self, data: &[&[u8]]) -> Result<bool> {
+        for param in data {
+            self.send(param).await?;
+        }
+        Ok(true)
+    }
+
+    async fn get_status(&mut self) -> Result<u32> {
+        self.read_u8().await.map(u32::from)
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        info!("Shutting down device...");
+
+        self.send_cmd(Cmd::Finish).await?;
+        self.expect(Rsp::Ack, "Shutting down").await?;
+        self.conn.port.write_all(&0u32.to_be_bytes()).await?;
+        self.expect(Rsp::Ack, "Shutting down").await?;
+
+        self.conn.port.close().await.ok();
+        Ok(())
+    }
+
+    async fn reboot(&mut self, bootmode: BootMode) -> Result<()> {
+        if bootmode != BootMode::Normal {
+            return Err(Error::Unsupported(format!(
+                "{:?} boot mode is not supported on legacy DAs",
+                bootmode
+            )));
+        }
+
+        let mut param = vec![Cmd::EnableWatchdog as u8];
+        param.extend_from_slice(&REBOOT_TIMEOUT_MS.to_be_bytes());
+        param.extend_from_slice(&[
+            0, // async
+            1, // bootup
+            0, // dlbit
+            0, // bNotResetRTCTime
+            0, // bNotDisconnectUSB
+        ]);
+
+        info!("Rebooting device into {:?} mode...", bootmode);
+        self.conn.port.write_all(&param).await?;
+        self.expect(Rsp::Ack, "Rebooting").await?;
+
+        self.conn.port.close().await.ok();
+        Ok(())
+    }
+
+    async fn read_flash(
+        &mut self,
+        addr: u64,
+        size: u64,
+        section: PartitionKind,
+        progress: &mut ProgressFn<'_>,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
+        flash::read_flash(self, addr, size, section, progress, writer).await
+    }
+
+    async fn write_flash(
+        &mut self,
+        addr: u64,
+        size: u64,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        section: PartitionKind,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
+        flash::write_flash(self, addr, size, reader, section, progress).await
+    }
+
+    async fn erase_flash(
+        &mut self,
+        _addr: u64,
+        _size: u64,
+        _section: PartitionKind,
+        _progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        Err(Error::Unsupported("Erasing is not supported on legacy DAs yet".into()))
+    }
+
+    async fn download(
+        &mut self,
+        part_name: String,
+        size: u64,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
+        flash::download(self, part_name, size, reader, progress).await
+    }
+
+    async fn upload(
+        &mut self,
+        part_name: String,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
+        flash::upload(self, part_name, writer, progress).await
+    }
+
+    async fn format(&mut self, _part_name: String, _progress: &mut ProgressFn<'_>) -> Result<()> {
+        Err(Error::Unsupported("Formatting is not supported on legacy DAs yet".into()))
+    }
+
+    async fn read32(&mut self, _addr: u32) -> Result<u32> {
+        Err(Error::Unsupported("Register access is not supported on legacy DAs".into()))
+    }
+
+    async fn write32(&mut self, _addr: u32, _value: u32) -> Result<()> {
+        Err(Error::Unsupported("Register access is not supported on legacy DAs".into()))
+    }
+
+    async fn get_usb_speed(&mut self) -> Result<UsbSpeed> {
+        Err(Error::Unsupported("Legacy DAs don't report their USB speed".into()))
+    }
+
+    async fn switch_usb_speed(&mut self) -> Result<bool> {
+        // Legacy DAs keep the speed the preloader ran at
+        Ok(false)
+    }
+
+    fn write_packet_length(&self) -> Option<usize> {
+        Some(FLASH_PACKET_LENGTH)
+    }
+
+    fn get_connection(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+
+    fn set_connection_type(&mut self, conn_type: ConnectionType) -> Result<()> {
+        self.conn.connection_type = conn_type;
+        Ok(())
+    }
+
+    async fn get_storage(&mut self) -> Option<Arc<dyn Storage>> {
+        self.storage().await
+    }
+
+    async fn get_storage_type(&mut self) -> StorageType {
+        self.storage().await.map_or(StorageType::Unknown, |s| s.kind())
+    }
+
+    async fn get_partitions(&mut self) -> Result<Vec<Partition>> {
+        let storage = self
+            .storage()
+            .await
+            .ok_or_else(|| Error::penumbra("Failed to detect the storage to read partitions"))?;
+
+        let storage_type = storage.kind();
+        let user_part = storage.get_user_part();
+        let user_size = storage.get_user_size();
+        let gpt_size = 32 * 1024;
+
+        let mut partitions = vec![
+            Partition::new("preloader", storage.get_pl1_size(), 0, storage.get_pl_part1()),
+            Partition::new("preloader_backup", storage.get_pl2_size(), 0, storage.get_pl_part2()),
+            Partition::new("PGPT", gpt_size, 0, user_part),
+        ];
+        let sgpt = Partition::new("SGPT", gpt_size, user_size - gpt_size, user_part);
+
+        let mut tables = Vec::new();
+        for part in [&partitions[2], &sgpt] {
+            let mut data = Vec::new();
+            // A table that can't be read is looked for elsewhere, unless the device is gone
+            let read =
+                flash::read_flash(self, part.address, part.size, part.kind, |_, _| {}, &mut data)
+                    .await;
+            if let Err(e) = read
+                && e.is_disconnect()
+            {
+                return Err(e);
+            }
+            tables.push(Gpt::parse(&data, storage_type).ok());
+        }
+        let sgpt_gpt = tables.pop().flatten();
+        let pgpt = tables.pop().flatten();
+
+        let report = GptReport::new(pgpt.as_ref(), sgpt_gpt.as_ref());
+        if !report.diff.is_empty() {
+            warn!(
+                "[Penumbra] Primary and secondary GPT differ ({} partitions), using the primary",
+                report.diff.entries.len()
+            );
+        }
+        let has_gpt = report.primary_ok || report.secondary_ok;
+        self.dev_info.set_gpt_report(Some(report).filter(|_| has_gpt)).await;
+
+        let parsed_gpt_parts = pgpt.map(|g| g.partitions()).unwrap_or_default();
+        let (mut gpt_parts, source) = if !parsed_gpt_parts.is_empty() {
+            (parsed_gpt_parts, GptSource::Primary)
+        } else {
+            let mut parts = sgpt_gpt.map(|g| g.partitions()).unwrap_or_default();
+            let mut source = GptSource::Secondary;
+
+            // Most devices shipping legacy DAs have a PMT instead of a GPT
+            if parts.is_empty() {
+                parts = self.read_pmt(storage.as_ref()).await;
+                source = if parts.is_empty() { GptSource::Synthetic } else { GptSource::Pmt };
+            }
+            (parts, source)
+        };
+
+        self.dev_info.set_gpt_source(source).await;
+
+        partitions.append(&mut gpt_parts);
+        partitions.push(sgpt);
+
+        Ok(partitions)
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn set_seccfg_lock_state(&mut self, _locked: LockFlag) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn get_seccfg_lock_state(&mut self) -> Result<LockFlag> {
+        Err(Error::Unsupported("Seccfg is not supported on legacy DAs".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn read_seccfg(&mut self) -> Result<SecCfg> {
+        Err(Error::Unsupported("Seccfg is not supported on legacy DAs".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn write_seccfg(&mut self, _seccfg: &mut SecCfg) -> Result<Vec<u8>> {
+        Err(Error::Unsupported("Seccfg is not supported on legacy DAs".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn peek(
+        &mut self,
+        _addr: u32,
+        _length: usize,
+        _writer: &mut (dyn AsyncWrite + Unpin + Send),
+        _progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        Err(Error::Unsupported("Peek is not supported on legacy DAs".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn poke(
+        &mut self,
+        _addr: u32,
+        _data: &[u8],
+        _progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        Err(Error::Unsupported("Poke is not supported on legacy DAs".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    fn patch_da(&mut self) -> Option<DA> {
+        None
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    fn patch_da1(&mut self) -> Option<DAEntryRegion> {
+        None
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    fn patch_da2(&mut self) -> Option<DAEntryRegion> {
+        None
+    }
+
+    fn get_devinfo(&self) -> &DeviceInfo {
+        &self.dev_info
+    }
+
+    fn get_da(&self) -> &DA {
+        &self.da
+    }
+
+    fn extensions_state(&self) -> ExtState {
+        ExtState::Disabled
+    }
+}
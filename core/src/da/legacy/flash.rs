@@ -0,0 +1,178 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use log::{debug, info};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::connection::phases::FlashPhase;
+use crate::core::storage::{Partition, PartitionKind};
+use crate::da::legacy::Legacy;
+use crate::da::legacy::cmds::*;
+use crate::error::{Error, Result};
+
+// Legacy DAs have no way to abort a transfer, so a cancelled one leaves the DA waiting
+// for the rest of it, and it has to be restarted before it can be used again.
+
+/// Additive 16-bit checksum sent along each chunk
+fn checksum(data: &[u8]) -> u16 {
+    (data.iter().fold(0u32, |total, &byte| total + byte as u32) & 0xFFFF) as u16
+}
+
+fn transfer_param(addr: u64, size: u64) -> Vec<u8> {
+    let mut param = Vec::new();
+    param.extend_from_slice(&addr.to_be_bytes());
+    param.extend_from_slice(&size.to_be_bytes());
+    param.extend_from_slice(&(FLASH_PACKET_LENGTH as u32).to_be_bytes());
+    param
+}
+
+pub async fn read_flash<F, W>(
+    legacy: &mut Legacy,
+    addr: u64,
+    size: u64,
+    section: PartitionKind,
+    mut progress: F,
+    mut writer: W,
+) -> Result<()>
+where
+    F: FnMut(u64, u64),
+    W: AsyncWrite + Unpin,
+{
+    info!("Reading flash at address {:#X} with size {:#X}", addr, size);
+
+    legacy.switch_partition(section.as_u32() as u8).await?;
+
+    // Cmd | Host | Storage | Address (BE) u64 | Size (BE) u64 | Packet length (BE) u32
+    let mut param = vec![Cmd::Read as u8, HOST_LINUX, STORAGE_EMMC];
+    param.extend_from_slice(&transfer_param(addr, size));
+    legacy.conn.port.write_all(&param).await?;
+    legacy.expect(Rsp::Ack, "Reading flash").await?;
+
+    let mut bytes_read = 0u64;
+    progress(0, size);
+    while bytes_read < size {
+        if legacy.conn.cancel.is_cancelled() {
+            return Err(Error::cancelled());
+        }
+
+        let len = (size - bytes_read).min(FLASH_PACKET_LENGTH as u64) as usize;
+        let chunk = legacy.conn.read_packet(len, None).await?;
+        let expected = legacy.read_u16().await?;
+        if checksum(&chunk) != expected {
+            return Err(Error::proto(format!(
+                "Checksum mismatch at 0x{:X}: expected 0x{:04X}, got 0x{:04X}",
+                addr + bytes_read,
+                expected,
+                checksum(&chunk)
+            )));
+        }
+        legacy.conn.port.write_all(&[Rsp::Ack as u8]).await?;
+
+        writer.write_all(&chunk).await?;
+        bytes_read += len as u64;
+        progress(bytes_read, size);
+        legacy.conn.throttle(len).await;
+        debug!("Read {:X}/{:X} bytes...", bytes_read, size);
+    }
+
+    info!("Flash read completed, 0x{:X} bytes read.", bytes_read);
+    Ok(())
+}
+
+pub async fn write_flash<F, R>(
+    legacy: &mut Legacy,
+    addr: u64,
+    size: u64,
+    mut reader: R,
+    section: PartitionKind,
+    mut progress: F,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(u64, u64),
+{
+    info!("Writing flash at address {:#X} with size {:#X}", addr, size);
+
+    // Cmd | Storage | Partition | Address (BE) u64 | Size (BE) u64 | Packet length (BE) u32
+    let mut param = vec![Cmd::SdmmcWriteData as u8, STORAGE_EMMC, section.as_u32() as u8];
+    param.extend_from_slice(&transfer_param(addr, size));
+    legacy.conn.port.write_all(&param).await?;
+    legacy.expect(Rsp::Ack, "Writing flash").await?;
+
+    let mut buffer = vec![0u8; FLASH_PACKET_LENGTH];
+    let mut bytes_written = 0u64;
+
+    legacy.conn.phases.enter(FlashPhase::Write);
+    progress(0, size);
+    while bytes_written < size {
+        if legacy.conn.cancel.is_cancelled() {
+            legacy.conn.phases.finish();
+            return Err(Error::cancelled());
+        }
+
+        // Short data is padded with zeroes, so nothing is left of the old contents
+        let to_read = (size - bytes_written).min(FLASH_PACKET_LENGTH as u64) as usize;
+        let bytes_read = reader.read(&mut buffer[..to_read]).await?;
+        buffer[bytes_read..to_read].fill(0);
+        let chunk = &buffer[..to_read];
+
+        legacy.conn.write_chunk(chunk).await?;
+        legacy.conn.port.write_all(&checksum(chunk).to_be_bytes()).await?;
+        legacy.expect(Rsp::Cont, "Writing flash").await?;
+
+        bytes_written += to_read as u64;
+        progress(bytes_written, size);
+        legacy.conn.throttle(to_read).await;
+        debug!("Written {}/{} bytes...", bytes_written, size);
+    }
+
+    legacy.conn.phases.finish();
+    info!("Flash write completed, 0x{:X} bytes written.", bytes_written);
+    Ok(())
+}
+
+/// Legacy DAs don't know partitions by name, so they are looked up in the partition table.
+async fn find_partition(legacy: &mut Legacy, part_name: &str) -> Result<Partition> {
+    legacy
+        .dev_info
+        .get_partition(part_name)
+        .await
+        .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found", part_name)))
+}
+
+pub async fn download<F, R>(
+    legacy: &mut Legacy,
+    part_name: String,
+    size: u64,
+    reader: R,
+    progress: F,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(u64, u64),
+{
+    let part = find_partition(legacy, &part_name).await?;
+    if size > part.size {
+        return Err(Error::penumbra(format!(
+            "Data (0x{:X} bytes) doesn't fit in partition '{}' (0x{:X} bytes)",
+            size, part_name, part.size
+        )));
+    }
+
+    write_flash(legacy, part.address, size, reader, part.kind, progress).await
+}
+
+pub async fn upload<F, W>(
+    legacy: &mut Legacy,
+    part_name: String,
+    writer: W,
+    progress: F,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    F: FnMut(u64, u64),
+{
+    let part = find_partition(legacy, &part_name).await?;
+    read_flash(legacy, part.address, part.size, part.kind, progress, writer).await
+}
@@ -0,0 +1,268 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+
+use log::{debug, info, warn};
+
+use crate::connection::Connection;
+use crate::core::devinfo::DeviceInfo;
+use crate::core::progress::{ProgressFn, ProgressTracker};
+use crate::core::storage::emmc::EmmcStorage;
+use crate::core::storage::{Partition, Pmt, Storage};
+use crate::da::DA;
+use crate::da::legacy::cmds::*;
+use crate::da::legacy::flash;
+use crate::error::{Error, Result};
+
+/// Legacy DA generation, from the version DA1 reports once it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyFlavor {
+    V3,
+    V4,
+}
+
+impl LegacyFlavor {
+    pub fn from_major(major: u8) -> Option<Self> {
+        match major {
+            3 => Some(LegacyFlavor::V3),
+            4 => Some(LegacyFlavor::V4),
+            _ => None,
+        }
+    }
+}
+
+pub struct Legacy {
+    pub conn: Connection,
+    pub da: DA,
+    pub dev_info: DeviceInfo,
+    /// Known once DA1 runs, `None` when reattaching to a running DA
+    pub flavor: Option<LegacyFlavor>,
+}
+
+impl Legacy {
+    pub fn new(conn: Connection, da: DA, dev_info: DeviceInfo) -> Self {
+        Legacy { conn, da, dev_info, flavor: None }
+    }
+
+    pub(super) async fn read_u8(&mut self) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.conn.read_response(&mut buf).await?;
+        Ok(buf[0])
+    }
+
+    pub(super) async fn read_u16(&mut self) -> Result<u16> {
+        let mut buf = [0u8; 2];
+        self.conn.read_response(&mut buf).await?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    pub(super) async fn read_u32(&mut self) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        self.conn.read_response(&mut buf).await?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// Reads a single response byte, failing unless it is `expected`.
+    pub(super) async fn expect(&mut self, expected: Rsp, what: &str) -> Result<()> {
+        match self.read_u8().await? {
+            byte if byte == expected as u8 => Ok(()),
+            byte => Err(Error::proto(format!(
+                "{} failed, DA answered 0x{:02X} instead of {:?}",
+                what, byte, expected
+            ))),
+        }
+    }
+
+    pub(super) async fn send_cmd(&mut self, cmd: Cmd) -> Result<()> {
+        debug!("[TX] Sending Command: 0x{:02X}", cmd as u8);
+        self.conn.port.write_all(&[cmd as u8]).await
+    }
+
+    pub(super) async fn upload_stage1(
+        &mut self,
+        addr: u32,
+        length: u32,
+        data: Vec<u8>,
+        sig_len: u32,
+    ) -> Result<()> {
+        info!(
+            "[Penumbra] Uploading DA1 region to address 0x{:08X} with length 0x{:X}",
+            addr, length
+        );
+
+        self.conn.send_da(&data, length, addr, sig_len).await?;
+        info!("[Penumbra] Sent DA1, jumping to address 0x{:08X}...", addr);
+        self.conn.jump_da(addr).await?;
+
+        self.expect(Rsp::Sync, "DA1 sync").await?;
+        info!("[Penumbra] Received sync byte");
+
+        // Storage IDs seen by DA1, the full report only comes once DA2 runs
+        let nand_info = self.read_u32().await?;
+        let nand_count = self.read_u16().await?;
+        let mut nand_ids = Vec::with_capacity(nand_count as usize);
+        for _ in 0..nand_count {
+            nand_ids.push(self.read_u16().await?);
+        }
+        let emmc_info = self.read_u32().await?;
+        let mut emmc_ids = [0u32; 4];
+        for id in &mut emmc_ids {
+            *id = self.read_u32().await?;
+        }
+        debug!(
+            "NAND info 0x{:08X} ids {:04X?}, eMMC info 0x{:08X} ids {:08X?}",
+            nand_info, nand_ids, emmc_info, emmc_ids
+        );
+
+        self.conn.port.write_all(&[Rsp::Ack as u8]).await?;
+
+        let mut version = [0u8; 3];
+        self.conn.read_response(&mut version).await?;
+        let [major, minor, baseband] = version;
+        self.flavor = LegacyFlavor::from_major(major);
+        match self.flavor {
+            Some(flavor) => info!(
+                "[Penumbra] Legacy DA {:?} (v{}.{}), baseband 0x{:02X}",
+                flavor, major, minor, baseband
+            ),
+            None => warn!("[Penumbra] Unknown legacy DA version v{}.{}, going on", major, minor),
+        }
+
+        Ok(())
+    }
+
+    /// Sends the settings DA1 needs before DA2, then checks the external RAM came up.
+    pub(super) async fn send_stage2_config(&mut self) -> Result<()> {
+        let hw_code = self.dev_info.hw_code().await;
+        // MT6583 resets when asked to reset its keys
+        let reset_keys: u8 = if hw_code == 0x6583 { 0 } else { 1 };
+
+        let mut config = Vec::new();
+        config.extend_from_slice(&0x08u16.to_be_bytes()); // nor_chip
+        config.push(0); // nor_chip_select
+        config.extend_from_slice(&0x7007FFFFu32.to_be_bytes()); // nand_acccon
+        config.push(0); // bmt_present
+        config.extend_from_slice(&0u32.to_be_bytes()); // bmt_part_size
+        config.push(0x02); // force_charge (off)
+        config.push(reset_keys);
+        config.push(0x02); // ext_clock (26 MHz)
+        config.push(0); // msdc_boot_ch
+        self.conn.port.write_all(&config).await?;
+        self.expect(Rsp::Ack, "Sending the DA config").await?;
+
+        match self.read_u32().await? {
+            0 => {}
+            status if EXT_RAM_NEEDS_SETTINGS.contains(&status) => {
+                return Err(Error::Unsupported(
+                    "The DA needs DRAM settings, which legacy DAs can't be given yet. Try \
+                     connecting through the preloader instead"
+                        .into(),
+                ));
+            }
+            status => {
+                return Err(Error::proto(format!(
+                    "DA failed to set up the external RAM (0x{:08X})",
+                    status
+                )));
+            }
+        }
+
+        // RAM type | chip select | size
+        let mut ext_ram = [0u8; 10];
+        self.conn.read_response(&mut ext_ram).await?;
+        let size = u64::from_be_bytes(ext_ram[2..10].try_into().unwrap());
+        info!("[Penumbra] External RAM: 0x{:X} bytes", size);
+
+        Ok(())
+    }
+
+    pub(super) async fn upload_stage2(&mut self, addr: u32, data: &[u8]) -> Result<()> {
+        let mut param = Vec::new();
+        param.extend_from_slice(&addr.to_be_bytes());
+        param.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        param.extend_from_slice(&(DA2_PACKET_LENGTH as u32).to_be_bytes());
+        self.conn.port.write_all(&param).await?;
+        self.expect(Rsp::Ack, "Starting the DA2 upload").await?;
+
+        for chunk in data.chunks(DA2_PACKET_LENGTH) {
+            self.conn.write_chunk(chunk).await?;
+            self.expect(Rsp::Ack, "Sending DA2").await?;
+        }
+
+        self.conn.port.write_all(&[Rsp::Ack as u8]).await?;
+        self.expect(Rsp::Ack, "Booting DA2").await
+    }
+
+    /// Reads the storage reports DA2 sends once it runs. Only eMMC is supported for now.
+    pub(super) async fn read_flash_info(&mut self) -> Result<EmmcStorage> {
+        let mut nor = [0u8; NOR_INFO_LENGTH];
+        self.conn.read_response(&mut nor).await?;
+
+        let mut nand = vec![0u8; NAND_INFO_LENGTH];
+        self.conn.read_response(&mut nand).await?;
+        let nand_ids = u16::from_be_bytes(nand[NAND_INFO_LENGTH - 2..].try_into().unwrap());
+        let mut nand_rest = vec![0u8; nand_ids as usize * 2 + NAND_INFO_TRAILER];
+        self.conn.read_response(&mut nand_rest).await?;
+
+        let mut emmc = [0u8; EMMC_INFO_LENGTH];
+        self.conn.read_response(&mut emmc).await?;
+        let mut sdc = [0u8; SDC_INFO_LENGTH];
+        self.conn.read_response(&mut sdc).await?;
+        let mut config = [0u8; CONFIG_INFO_LENGTH];
+        self.conn.read_response(&mut config).await?;
+
+        let mut pass = [0u8; PASS_INFO_LENGTH];
+        self.conn.read_response(&mut pass).await?;
+        if pass[0] != Rsp::Ack as u8 {
+            return Err(Error::proto(format!("DA2 failed to start (0x{:02X})", pass[0])));
+        }
+
+        let emmc_ret = u32::from_be_bytes(emmc[0..4].try_into().unwrap());
+        if emmc_ret != 0 {
+            return Err(Error::Unsupported(format!(
+                "Only eMMC storage is supported with legacy DAs, but none was found (0x{:08X})",
+                emmc_ret
+            )));
+        }
+
+        EmmcStorage::from_legacy_response(&emmc)
+    }
+
+    pub(super) async fn switch_partition(&mut self, part: u8) -> Result<()> {
+        self.send_cmd(Cmd::SdmmcSwitchPart).await?;
+        self.expect(Rsp::Ack, "Switching partition").await?;
+        self.conn.port.write_all(&[part]).await?;
+        self.expect(Rsp::Ack, "Switching partition").await
+    }
+
+    pub(super) fn track<'a>(&self, progress: &'a mut ProgressFn<'_>) -> ProgressTracker<'a> {
+        ProgressTracker::new(progress).with_phases(Some(self.conn.phases.clone()))
+    }
+
+    pub(super) async fn storage(&mut self) -> Option<Arc<dyn Storage>> {
+        self.dev_info.storage().await.map(|s| s as Arc<dyn Storage>)
+    }
+
+    /// Reads the PMT, the partition table of legacy devices without a GPT.
+    /// Returns no partitions if it can't be read or parsed.
+    pub(super) async fn read_pmt(&mut self, storage: &dyn Storage) -> Vec<Partition> {
+        let (addr, size) = Pmt::location(storage);
+        let mut data = Vec::new();
+
+        let section = storage.get_user_part();
+        if let Err(e) = flash::read_flash(self, addr, size, section, |_, _| {}, &mut data).await {
+            debug!("Failed to read PMT: {}", e);
+            return Vec::new();
+        }
+
+        match Pmt::parse(&data, storage.kind()) {
+            Ok(pmt) => pmt.partitions(),
+            Err(e) => {
+                debug!("Failed to parse PMT: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+//! Legacy (V3/V4) DAs, shipped with older MT65xx/MT67xx devices.
+//!
+//! Only eMMC reading, writing and rebooting are supported. The framing follows mtkclient's
+//! legacy DA implementation, and hasn't been checked against a device yet.
+mod cmds;
+mod da_protocol;
+pub mod flash;
+mod legacy_lib;
+pub use cmds::*;
+pub use legacy_lib::*;
@@ -3,10 +3,20 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 pub mod dafile;
+#[cfg(feature = "da-protocols")]
+pub mod legacy;
+#[cfg(feature = "da-protocols")]
 pub mod protocol;
+#[cfg(feature = "da-protocols")]
 pub mod xflash;
+#[cfg(feature = "da-protocols")]
 pub mod xml;
-pub use dafile::{DA, DAEntryRegion, DAFile, DAType};
-pub use protocol::DAProtocol;
+pub use dafile::{DA, DAEntryRegion, DAFile, DAType, DaChipEntry, hash_fixup};
+#[cfg(feature = "da-protocols")]
+pub use legacy::Legacy;
+#[cfg(feature = "da-protocols")]
+pub use protocol::{DAProtocol, ExtState};
+#[cfg(feature = "da-protocols")]
 pub use xflash::XFlash;
+#[cfg(feature = "da-protocols")]
 pub use xml::Xml;
@@ -8,11 +8,15 @@ use downcast_rs::{DowncastSend, impl_downcast};
 use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::connection::Connection;
-use crate::connection::port::ConnectionType;
+use crate::connection::port::{ConnectionType, UsbSpeed};
 use crate::core::devinfo::DeviceInfo;
-use crate::core::seccfg::LockFlag;
+use crate::core::progress::ProgressFn;
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::seccfg::{LockFlag, SecCfg};
 use crate::core::storage::{Partition, PartitionKind, Storage, StorageType};
-use crate::da::{DA, DAEntryRegion};
+use crate::da::DA;
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::da::DAEntryRegion;
 use crate::error::Result;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,16 +25,53 @@ pub enum BootMode {
     HomeScreen,
     Fastboot,
     Test,
+    /// META mode, talking over USB
     Meta,
+    /// META mode, talking over the UART (COM) port
+    MetaUart,
+    /// Android recovery, requested through the `misc` partition by [`crate::Device::reboot`]
+    Recovery,
 }
 
 impl BootMode {
     pub fn to_text(&self) -> Option<&'static str> {
         match self {
             BootMode::Fastboot => Some("FASTBOOT"),
-            BootMode::Meta => Some("META"),
+            BootMode::Meta | BootMode::MetaUart => Some("META"),
             BootMode::Test => Some("ANDROID-TEST-MODE"),
-            BootMode::Normal | BootMode::HomeScreen => None,
+            BootMode::Normal | BootMode::HomeScreen | BootMode::Recovery => None,
+        }
+    }
+}
+
+/// State of the DA extensions, which memory access and seccfg operations rely on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ExtState {
+    /// Extensions are booted and replying.
+    Active,
+    /// Booting the extensions was attempted, but failed.
+    FailedToBoot(String),
+    /// Extensions were not booted, either not attempted yet or disabled at build time.
+    #[default]
+    Disabled,
+    /// The DA was already running when connecting (e.g. in a previous CLI run), so whether
+    /// extensions were booted can't be known.
+    Unknown,
+}
+
+impl ExtState {
+    pub fn is_active(&self) -> bool {
+        matches!(self, ExtState::Active)
+    }
+}
+
+impl std::fmt::Display for ExtState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtState::Active => write!(f, "Active"),
+            ExtState::FailedToBoot(reason) => write!(f, "Failed to boot ({})", reason),
+            ExtState::Disabled => write!(f, "Disabled"),
+            ExtState::Unknown => write!(f, "Unknown"),
         }
     }
 }
@@ -50,56 +91,58 @@ pub trait DAProtocol: DowncastSend {
     async fn read_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
     ) -> Result<()>;
 
     async fn write_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()>;
 
     async fn erase_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()>;
 
     async fn download(
         &mut self,
         part_name: String,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()>;
 
     async fn upload(
         &mut self,
         part_name: String,
         reader: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()>;
 
-    async fn format(
-        &mut self,
-        part_name: String,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-    ) -> Result<()>;
+    async fn format(&mut self, part_name: String, progress: &mut ProgressFn<'_>) -> Result<()>;
 
     // Memory
     async fn read32(&mut self, addr: u32) -> Result<u32>;
     async fn write32(&mut self, addr: u32, value: u32) -> Result<()>;
 
-    async fn get_usb_speed(&mut self) -> Result<u32>;
-    // fn set_usb_speed(&mut self, speed: u32) -> Result<(), Error>;
+    /// Speed of the USB link the DA runs on.
+    async fn get_usb_speed(&mut self) -> Result<UsbSpeed>;
+    /// Asks the DA to re-enumerate at a higher USB speed, and reconnects once it's back.
+    /// Returns `false` without touching the connection when the link is already fast
+    /// or the DA can't switch.
+    async fn switch_usb_speed(&mut self) -> Result<bool>;
+    /// Size of the chunks sent to the DA when writing, if already negotiated.
+    fn write_packet_length(&self) -> Option<usize>;
 
     // Connection
     fn get_connection(&mut self) -> &mut Connection;
@@ -107,36 +150,60 @@ pub trait DAProtocol: DowncastSend {
 
     async fn get_storage(&mut self) -> Option<Arc<dyn Storage>>;
     async fn get_storage_type(&mut self) -> StorageType;
-    async fn get_partitions(&mut self) -> Vec<Partition>;
+    /// Reads the partition table. Fails when the storage can't be detected or the device
+    /// stops answering, while a missing or corrupted GPT falls back to other sources.
+    async fn get_partitions(&mut self) -> Result<Vec<Partition>>;
 
     // DevInfo helpers
     fn get_devinfo(&self) -> &DeviceInfo;
     fn get_da(&self) -> &DA;
 
+    /// Whether the DA extensions are booted, and why not otherwise.
+    fn extensions_state(&self) -> ExtState;
+
     /* EXTENSIONS / EXPLOITS
-     * These functions won't be included if the "no_exploits" feature is enabled
+     * These functions are only included with the "payloads" feature, and without "no_exploits"
      */
 
     // Sec
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn set_seccfg_lock_state(&mut self, locked: LockFlag) -> Option<Vec<u8>>;
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn get_seccfg_lock_state(&mut self) -> Result<LockFlag>;
+
+    /// Parses seccfg, finding the SEJ algorithm it is encrypted with
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn read_seccfg(&mut self) -> Result<SecCfg>;
+
+    /// Writes back a seccfg from [`DAProtocol::read_seccfg`], returning the written data
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn write_seccfg(&mut self, seccfg: &mut SecCfg) -> Result<Vec<u8>>;
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn peek(
         &mut self,
         addr: u32,
         length: usize,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<()>;
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn poke(
+        &mut self,
+        addr: u32,
+        data: &[u8],
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()>;
 
     // DA Patching utils. These *must* be protocol specific, as different protocols
     // have different DA implementations
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da(&mut self) -> Option<DA>;
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da1(&mut self) -> Option<DAEntryRegion>;
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da2(&mut self) -> Option<DAEntryRegion>;
 }
 
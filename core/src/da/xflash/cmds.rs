@@ -12,8 +12,13 @@
     the combined work is subject to the networking terms of the AGPL-3.0-or-later,
     as for term 13 of the GPL-3.0-or-later license.
 */
+use num_enum::TryFromPrimitive;
+
+/// USB IDs (VID << 16 | PID) the DA re-enumerates with after switching USB speed
+pub const DA_USB_ID: u32 = 0x0E8D_2001;
+
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[allow(dead_code)]
 pub enum Cmd {
     Magic = 0xFEEEEEEF,
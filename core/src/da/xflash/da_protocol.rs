@@ -5,28 +5,39 @@
 use std::io::Cursor;
 use std::sync::Arc;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::time::{Duration, timeout};
 
 use crate::connection::Connection;
-use crate::connection::port::ConnectionType;
+use crate::connection::port::{ConnectionType, UsbSpeed};
 use crate::core::devinfo::DeviceInfo;
-use crate::core::seccfg::LockFlag;
-use crate::core::storage::{Gpt, Partition, PartitionKind, Storage, StorageType};
-use crate::da::protocol::BootMode;
+use crate::core::progress::{Progress, ProgressFn};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::seccfg::{LockFlag, SecCfg};
+use crate::core::storage::{
+    Gpt,
+    GptReport,
+    GptSource,
+    Partition,
+    PartitionKind,
+    Storage,
+    StorageType,
+};
+use crate::da::protocol::{BootMode, ExtState};
 use crate::da::xflash::cmds::*;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xflash::exts::{read32_ext, write32_ext};
 use crate::da::xflash::flash;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xflash::patch;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xflash::sec::{parse_seccfg, write_seccfg};
-use crate::da::{DA, DAEntryRegion, DAProtocol, XFlash};
-use crate::error::{Error, Result, XFlashError};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::da::DAEntryRegion;
+use crate::da::{DA, DAProtocol, XFlash};
+use crate::error::{Error, Result, XFlashError, XFlashErrorKind};
 use crate::exploit;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::exploit::{Carbonara, Exploit, Kamakiri};
 
 #[async_trait::async_trait]
@@ -53,13 +64,22 @@ impl DAProtocol for XFlash {
             da2data.len()
         );
 
-        match self.boot_to(da2.addr, &da2data).await {
+        let booted = match self.boot_to(da2.addr, &da2data).await {
+            // Some devices re-enumerate as a new USB device once DA2 runs
+            Err(e) if e.is_disconnect() => {
+                info!("[Penumbra] Device re-enumerated, reconnecting to the DA port");
+                self.conn.reconnect_da().await.map(|_| true)
+            }
+            result => result,
+        };
+
+        match booted {
             Ok(true) => {
                 info!("[Penumbra] Successfully uploaded and executed DA2");
                 self.handle_sla().await?;
                 flash::get_packet_length(self).await?;
 
-                #[cfg(not(feature = "no_exploits"))]
+                #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
                 self.boot_extensions().await?;
 
                 Ok(true)
@@ -97,13 +117,13 @@ impl DAProtocol for XFlash {
             self.conn.port.write_all(&hdr).await?;
 
             let mut pos = 0;
-            let max_chunk_size = self.write_packet_length.unwrap_or(0x8000);
+            let max_chunk_size = self.conn.transfer.write_chunk(self.write_packet_length);
 
             while pos < param.len() {
                 let end = param.len().min(pos + max_chunk_size);
                 let chunk = &param[pos..end];
                 debug!("[TX] Sending chunk (0x{:X} bytes)", chunk.len());
-                self.conn.port.write_all(chunk).await?;
+                self.conn.write_chunk(chunk).await?;
                 pos = end;
             }
 
@@ -117,13 +137,7 @@ impl DAProtocol for XFlash {
 
     async fn get_status(&mut self) -> Result<u32> {
         let mut hdr = [0u8; 12];
-        match timeout(Duration::from_millis(3000), self.conn.port.read_exact(&mut hdr)).await {
-            Ok(result) => result?,
-            Err(_) => {
-                debug!("Status timeout");
-                return Err(Error::io("Status read timed out"));
-            }
-        };
+        self.conn.read_response(&mut hdr).await?;
 
         debug!("[RX] Status Header: {:02X?}", hdr);
         let len = self.parse_header(&hdr)?;
@@ -180,15 +194,25 @@ impl DAProtocol for XFlash {
     }
 
     async fn reboot(&mut self, bootmode: BootMode) -> Result<()> {
-        self.send_cmd(Cmd::Shutdown).await?;
-
         let bootup = match bootmode {
             BootMode::Normal => 0,
             BootMode::HomeScreen => 1,
             BootMode::Fastboot => 2,
-            _ => 0,
+            // META is flagged to the preloader beforehand, the reboot itself is a normal one
+            BootMode::Meta | BootMode::MetaUart => {
+                self.set_meta_boot_mode(bootmode == BootMode::MetaUart).await?;
+                0
+            }
+            BootMode::Test | BootMode::Recovery => {
+                error!("{:?} boot mode is not supported by XFlash DAs", bootmode);
+                return Err(Error::XFlash(XFlashError::from_code(
+                    XFlashErrorKind::UnsupportedCtrlCode.into(),
+                )));
+            }
         };
 
+        self.send_cmd(Cmd::Shutdown).await?;
+
         let params: [u32; 7] = [
             1, // is_dev_reboot
             0, // timeout_ms (0 = default, WDT decides)
@@ -214,42 +238,50 @@ impl DAProtocol for XFlash {
     async fn read_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::read_flash(self, addr, size, section, progress, writer).await
     }
 
     async fn write_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::write_flash(self, addr, size, reader, section, progress).await
     }
 
     async fn erase_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::erase_flash(self, addr, size, section, progress).await
     }
 
     async fn download(
         &mut self,
         part_name: String,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::download(self, part_name, size, reader, progress).await
     }
 
@@ -257,23 +289,53 @@ impl DAProtocol for XFlash {
         &mut self,
         part_name: String,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::upload(self, part_name, writer, progress).await
     }
 
-    async fn format(
-        &mut self,
-        part_name: String,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-    ) -> Result<()> {
+    async fn format(&mut self, part_name: String, progress: &mut ProgressFn<'_>) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::format(self, part_name, progress).await
     }
 
-    async fn get_usb_speed(&mut self) -> Result<u32> {
+    async fn get_usb_speed(&mut self) -> Result<UsbSpeed> {
         let usb_speed = self.devctrl(Cmd::GetUsbSpeed, None).await?;
         debug!("USB Speed Data: {:?}", usb_speed);
-        Ok(u32::from_le_bytes(usb_speed[0..4].try_into().unwrap()))
+        let name = String::from_utf8_lossy(&usb_speed);
+        UsbSpeed::from_da_name(&name).ok_or_else(|| {
+            Error::proto(format!("Unknown USB speed: {}", name.trim_end_matches('\0')))
+        })
+    }
+
+    async fn switch_usb_speed(&mut self) -> Result<bool> {
+        let speed = self.get_usb_speed().await?;
+        if !speed.is_slow() {
+            return Ok(false);
+        }
+
+        info!("[Penumbra] DA is running at {}, switching to a higher speed", speed.as_str());
+        match self.send_cmd(Cmd::SwitchUsbSpeed).await {
+            Ok(_) => {}
+            // The DA refused, it keeps going at the current speed
+            Err(Error::XFlash(e)) => {
+                warn!("[Penumbra] DA can't switch USB speed: {}", e);
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        }
+
+        // The DA answers, then drops off the bus and comes back with these IDs
+        self.send(&DA_USB_ID.to_le_bytes()).await?;
+        self.conn.reconnect_after_speed_switch().await?;
+        Ok(true)
+    }
+
+    fn write_packet_length(&self) -> Option<usize> {
+        self.write_packet_length
     }
 
     fn get_connection(&mut self) -> &mut Connection {
@@ -286,8 +348,8 @@ impl DAProtocol for XFlash {
     }
 
     async fn read32(&mut self, addr: u32) -> Result<u32> {
-        #[cfg(not(feature = "no_exploits"))]
-        if self.using_exts {
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+        if self.ext_state.is_active() {
             return read32_ext(self, addr).await;
         }
         debug!("Reading 32-bit register at address 0x{:08X}", addr);
@@ -302,8 +364,8 @@ impl DAProtocol for XFlash {
     }
 
     async fn write32(&mut self, addr: u32, value: u32) -> Result<()> {
-        #[cfg(not(feature = "no_exploits"))]
-        if self.using_exts {
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+        if self.ext_state.is_active() {
             return write32_ext(self, addr, value).await;
         }
         let mut param = Vec::new();
@@ -322,23 +384,36 @@ impl DAProtocol for XFlash {
         self.get_or_detect_storage().await
     }
 
-    async fn get_partitions(&mut self) -> Vec<Partition> {
-        let storage = match self.get_storage().await {
-            Some(s) => s,
-            None => {
-                error!("[Penumbra] Failed to get storage for partition parsing");
-                return Vec::new();
-            }
-        };
+    async fn get_partitions(&mut self) -> Result<Vec<Partition>> {
+        let storage = self
+            .get_storage()
+            .await
+            .ok_or_else(|| Error::penumbra("Failed to detect the storage to read partitions"))?;
 
         let storage_type = storage.kind();
+
+        // NAND has no GPT at all, its only partition table is the PMT
+        if storage_type == StorageType::Nand {
+            let partitions = self.read_pmt(storage.as_ref()).await;
+            let source = if partitions.is_empty() {
+                warn!("[Penumbra] Failed to read the PMT, no partitions available");
+                GptSource::Synthetic
+            } else {
+                GptSource::Pmt
+            };
+
+            self.dev_info.set_gpt_report(None).await;
+            self.dev_info.set_gpt_source(source).await;
+            return Ok(partitions);
+        }
+
         let pl_part1 = storage.get_pl_part1();
         let pl_part2 = storage.get_pl_part2();
         let user_part = storage.get_user_part();
-        let pl1_size = storage.get_pl1_size() as usize;
-        let pl2_size = storage.get_pl2_size() as usize;
-        let user_size = storage.get_user_size() as usize;
-        let gpt_size = 32 * 1024; // TODO: Change this when adding NAND support and PMT
+        let pl1_size = storage.get_pl1_size();
+        let pl2_size = storage.get_pl2_size();
+        let user_size = storage.get_user_size();
+        let gpt_size = 32 * 1024;
 
         let mut partitions = vec![
             Partition::new("preloader", pl1_size, 0, pl_part1),
@@ -346,34 +421,76 @@ impl DAProtocol for XFlash {
             Partition::new("PGPT", gpt_size, 0, user_part),
         ];
 
-        let sgpt = Partition::new("SGPT", gpt_size, user_size as u64 - gpt_size as u64, user_part);
+        let sgpt = Partition::new("SGPT", gpt_size, user_size - gpt_size, user_part);
 
-        let mut progress = |_, _| {};
+        let mut progress = |_: &Progress| {};
 
         let mut pgpt_data = Vec::new();
         let mut pgpt_cursor = Cursor::new(&mut pgpt_data);
-        self.upload("PGPT".into(), &mut pgpt_cursor, &mut progress).await.ok();
+        // A table that can't be read is looked for elsewhere, unless the device is gone
+        if let Err(e) = self.upload("PGPT".into(), &mut pgpt_cursor, &mut progress).await
+            && e.is_disconnect()
+        {
+            return Err(e);
+        }
+        self.send(&[0u8; 4]).await.ok();
+        let pgpt = Gpt::parse(&pgpt_data, storage_type).ok();
+
+        // The SGPT is always read, so a disagreement with the PGPT can be reported
+        let mut sgpt_data = Vec::new();
+        let mut sgpt_cursor = Cursor::new(&mut sgpt_data);
+        if let Err(e) = self.upload("SGPT".into(), &mut sgpt_cursor, &mut progress).await
+            && e.is_disconnect()
+        {
+            return Err(e);
+        }
         self.send(&[0u8; 4]).await.ok();
-        let parsed_gpt_parts =
-            Gpt::parse(&pgpt_data, storage_type).map(|g| g.partitions()).unwrap_or_default();
+        let sgpt_gpt = Gpt::parse(&sgpt_data, storage_type).ok();
+
+        let report = GptReport::new(pgpt.as_ref(), sgpt_gpt.as_ref());
+        if !report.diff.is_empty() {
+            warn!(
+                "[Penumbra] Primary and secondary GPT differ ({} partitions), using the primary",
+                report.diff.entries.len()
+            );
+        } else if report.primary_ok && !report.secondary_ok {
+            warn!("[Penumbra] Secondary GPT is corrupted");
+        }
+        // Without any valid GPT, the device might simply not have one
+        let has_gpt = report.primary_ok || report.secondary_ok;
+        self.dev_info.set_gpt_report(Some(report).filter(|_| has_gpt)).await;
 
-        let mut gpt_parts = if !parsed_gpt_parts.is_empty() {
-            parsed_gpt_parts
+        let parsed_gpt_parts = pgpt.map(|g| g.partitions()).unwrap_or_default();
+        let (mut gpt_parts, source) = if !parsed_gpt_parts.is_empty() {
+            (parsed_gpt_parts, GptSource::Primary)
         } else {
-            let mut sgpt_data = Vec::new();
-            let mut sgpt_cursor = Cursor::new(&mut sgpt_data);
-            self.upload("SGPT".into(), &mut sgpt_cursor, &mut progress).await.ok();
-            self.send(&[0u8; 4]).await.ok();
-            Gpt::parse(&sgpt_data, storage_type).map(|g| g.partitions()).unwrap_or_default()
+            let mut parts = sgpt_gpt.map(|g| g.partitions()).unwrap_or_default();
+            let mut source = GptSource::Secondary;
+
+            // Legacy eMMC devices might have a PMT instead of a GPT
+            if parts.is_empty() {
+                parts = self.read_pmt(storage.as_ref()).await;
+                source = if parts.is_empty() { GptSource::Synthetic } else { GptSource::Pmt };
+            }
+
+            if source != GptSource::Pmt {
+                warn!(
+                    "[Penumbra] Primary GPT is unreadable, partition table source: {}",
+                    source.as_str()
+                );
+            }
+            (parts, source)
         };
 
+        self.dev_info.set_gpt_source(source).await;
+
         partitions.append(&mut gpt_parts);
         partitions.push(sgpt);
 
-        partitions
+        Ok(partitions)
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn set_seccfg_lock_state(&mut self, locked: LockFlag) -> Option<Vec<u8>> {
         let seccfg = parse_seccfg(self).await;
         if seccfg.is_none() {
@@ -386,29 +503,55 @@ impl DAProtocol for XFlash {
         write_seccfg(self, &mut seccfg).await
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn get_seccfg_lock_state(&mut self) -> Result<LockFlag> {
+        Ok(self.read_seccfg().await?.get_lock_state())
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn read_seccfg(&mut self) -> Result<SecCfg> {
+        parse_seccfg(self).await.ok_or_else(|| Error::penumbra("Failed to parse seccfg"))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn write_seccfg(&mut self, seccfg: &mut SecCfg) -> Result<Vec<u8>> {
+        write_seccfg(self, seccfg).await.ok_or_else(|| Error::penumbra("Failed to write seccfg"))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn peek(
         &mut self,
         _addr: u32,
         _length: usize,
         _writer: &mut (dyn AsyncWrite + Unpin + Send),
-        _progress: &mut (dyn FnMut(usize, usize) + Send),
+        _progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
         // TODO: Rewrite V5 extensions, this is currently broken with current extensions
-        todo!()
+        Err(Error::Unsupported("Peek is not supported on XFlash DAs yet".into()))
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn poke(
+        &mut self,
+        _addr: u32,
+        _data: &[u8],
+        _progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        // Same as peek, V5 extensions need a rewrite first
+        Err(Error::Unsupported("Poke is not supported on XFlash DAs yet".into()))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da(&mut self) -> Option<DA> {
         patch::patch_da(self).ok()
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da1(&mut self) -> Option<DAEntryRegion> {
         patch::patch_da1(self).ok()
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da2(&mut self) -> Option<DAEntryRegion> {
         patch::patch_da2(self).ok()
     }
@@ -420,4 +563,8 @@ impl DAProtocol for XFlash {
     fn get_da(&self) -> &DA {
         &self.da
     }
+
+    fn extensions_state(&self) -> ExtState {
+        self.ext_state.clone()
+    }
 }
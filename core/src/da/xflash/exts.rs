@@ -13,8 +13,10 @@
     as for term 13 of the GPL-3.0-or-later license.
 */
 use log::{debug, info};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-use crate::da::DAProtocol;
+use crate::core::storage::{EmmcPartition, PartitionKind};
+use crate::da::{DAProtocol, ExtState};
 use crate::da::xflash::{Cmd, XFlash};
 use crate::error::{Error, Result};
 use crate::extract_ptr;
@@ -22,14 +24,21 @@ use crate::utilities::patching::{HEX_NOT_FOUND, find_pattern, patch_ptr};
 
 const DA_EXT: &[u8] = include_bytes!("../../../payloads/da_x.bin");
 
-pub async fn boot_extensions(xflash: &mut XFlash) -> Result<bool> {
+/// Data carried by a single RPMB frame
+pub const RPMB_BLOCK_SIZE: usize = 256;
+/// A full RPMB frame: stuff bytes, MAC, data, nonce, counter, address, count, result and type
+const RPMB_FRAME_SIZE: usize = 512;
+/// Offset of the data in a full frame, after the 196 stuff bytes and the 32 byte MAC
+const RPMB_DATA_OFFSET: usize = 228;
+
+pub async fn boot_extensions(xflash: &mut XFlash) -> Result<ExtState> {
     debug!("Trying booting XFlash extensions...");
 
     let ext_data = match prepare_extensions(xflash) {
         Some(data) => data,
         None => {
             debug!("Failed to prepare DA extensions");
-            return Ok(false);
+            return Ok(ExtState::FailedToBoot("DA2 not supported by the extensions".into()));
         }
     };
 
@@ -42,12 +51,12 @@ pub async fn boot_extensions(xflash: &mut XFlash) -> Result<bool> {
         // If DA extensions fail to upload, we just return false, not a fatal error
         Err(_) => {
             info!("Failed to upload DA extensions, continuing without extensions");
-            return Ok(false);
+            return Ok(ExtState::FailedToBoot("upload failed".into()));
         }
     }
     info!("DA extensions uploaded");
 
-    let ack = xflash.devctrl(Cmd::ExtAck, None).await?;
+    let ack = ext_devctrl(xflash, Cmd::ExtAck, &[]).await?;
 
     // Ack must be 0xA1A2A3A4
     if ack.len() < 4 || ack[0..4] != [0xA4, 0xA3, 0xA2, 0xA1] {
@@ -56,7 +65,7 @@ pub async fn boot_extensions(xflash: &mut XFlash) -> Result<bool> {
         info!("Received ack: {:02X?}", &ack[0..4]);
     }
 
-    Ok(true)
+    Ok(ExtState::Active)
 }
 
 fn prepare_extensions(xflash: &XFlash) -> Option<Vec<u8>> {
@@ -111,19 +120,7 @@ fn prepare_extensions(xflash: &XFlash) -> Option<Vec<u8>> {
         mmc_rpmb_send_command = find_pattern(da2, "2DE9F0414FF6FD74", 0);
     }
 
-    let ufs_patterns =
-        [("20460BB0BDE8F08300BF", 10), ("20460DB0BDE8F083", 8), ("214602F002FB1BE600BF", 18)];
-
-    let mut g_ufs_hba = 0;
-
-    for (pattern, offset) in ufs_patterns {
-        let pos = find_pattern(da2, pattern, 0);
-        if pos != HEX_NOT_FOUND && pos + offset + 4 <= da2.len() {
-            g_ufs_hba = extract_ptr!(u32, da2, pos + offset);
-            break;
-        }
-    }
-
+    let g_ufs_hba = find_ufs_hba(da2).unwrap_or(0);
     let has_ufs = g_ufs_hba != 0;
 
     let ufs_tag_pos = if has_ufs { find_pattern(da2, "B52EB190F8", 0) } else { HEX_NOT_FOUND };
@@ -159,20 +156,63 @@ fn prepare_extensions(xflash: &XFlash) -> Option<Vec<u8>> {
     Some(da_ext_data)
 }
 
-pub async fn read32_ext(xflash: &mut XFlash, addr: u32) -> Result<u32> {
-    xflash.devctrl(Cmd::ExtReadRegister, Some(&[&addr.to_le_bytes()])).await?;
+/// Address of `g_ufs_hba` in DA2, the UFS host controller the DA drives.
+/// Only DAs built for UFS storage have it, eMMC ones don't.
+fn find_ufs_hba(da2: &[u8]) -> Option<u32> {
+    let ufs_patterns =
+        [("20460BB0BDE8F08300BF", 10), ("20460DB0BDE8F083", 8), ("214602F002FB1BE600BF", 18)];
 
-    let payload = xflash.read_data().await?;
-    status_ok!(xflash);
+    for (pattern, offset) in ufs_patterns {
+        let pos = find_pattern(da2, pattern, 0);
+        if pos != HEX_NOT_FOUND && pos + offset + 4 <= da2.len() {
+            return Some(extract_ptr!(u32, da2, pos + offset)).filter(|&addr| addr != 0);
+        }
+    }
 
-    Ok(u32::from_le_bytes(payload[0..4].try_into().unwrap()))
+    None
 }
 
-pub async fn write32_ext(xflash: &mut XFlash, addr: u32, value: u32) -> Result<()> {
-    let addr_bytes = addr.to_le_bytes();
-    let value_bytes = value.to_le_bytes();
+/// Sends an extensions command, each of `payloads` as its own data packet, and returns
+/// its response. Commands without parameters always answer with a data packet, while
+/// the others only do for [`ext_has_response`] commands and return an empty buffer.
+///
+/// The status following the response is checked here, so callers only deal with data.
+pub async fn ext_devctrl(xflash: &mut XFlash, cmd: Cmd, payloads: &[&[u8]]) -> Result<Vec<u8>> {
+    debug!("[EXT] Sending {:?} with {} payloads", cmd, payloads.len());
+
+    if payloads.is_empty() {
+        return xflash.devctrl(cmd, None).await;
+    }
 
-    xflash.devctrl(Cmd::ExtWriteRegister, Some(&[&addr_bytes, &value_bytes])).await?;
+    xflash.devctrl(cmd, Some(payloads)).await?;
+    if !ext_has_response(cmd) {
+        return Ok(Vec::new());
+    }
+
+    let response = xflash.read_data().await?;
+    status_ok!(xflash, format!("Extensions command {:?} failed", cmd));
+
+    Ok(response)
+}
+
+/// Whether the extensions answer a command sent with parameters with a data packet.
+/// RPMB reads answer with more than one, and are read by hand.
+fn ext_has_response(cmd: Cmd) -> bool {
+    matches!(cmd, Cmd::ExtReadRegister | Cmd::ExtReadMem | Cmd::ExtSej)
+}
+
+pub async fn read32_ext(xflash: &mut XFlash, addr: u32) -> Result<u32> {
+    let payload = ext_devctrl(xflash, Cmd::ExtReadRegister, &[&addr.to_le_bytes()]).await?;
+
+    match payload.get(0..4) {
+        Some(value) => Ok(u32::from_le_bytes(value.try_into().unwrap())),
+        None => Err(Error::proto(format!("Short register read at 0x{:08X}", addr))),
+    }
+}
+
+pub async fn write32_ext(xflash: &mut XFlash, addr: u32, value: u32) -> Result<()> {
+    ext_devctrl(xflash, Cmd::ExtWriteRegister, &[&addr.to_le_bytes(), &value.to_le_bytes()])
+        .await?;
 
     Ok(())
 }
@@ -193,10 +233,79 @@ pub async fn sej(
     params[3] = if xor { 1 } else { 0 };
     params[4..8].copy_from_slice(&(data.len() as u32).to_le_bytes());
 
-    xflash.devctrl(Cmd::ExtSej, Some(&[&params, data])).await?;
+    ext_devctrl(xflash, Cmd::ExtSej, &[&params, data]).await
+}
 
-    let payload = xflash.read_data().await?;
+/// Reads the whole RPMB partition through the extensions, writing its data to `writer`.
+/// Only the 256 bytes of data of each frame are kept, without the MAC, nonce or counter.
+///
+/// The extensions only drive RPMB on eMMC, UFS devices get an [`Error::Unsupported`].
+/// Returns the number of bytes read.
+pub async fn read_rpmb<F>(
+    xflash: &mut XFlash,
+    writer: &mut (dyn AsyncWrite + Unpin + Send),
+    mut progress: F,
+) -> Result<u64>
+where
+    F: FnMut(u64, u64) + Send,
+{
+    if !xflash.ext_state.is_active() {
+        return Err(Error::ExtensionsUnavailable(xflash.ext_state.to_string()));
+    }
+
+    if xflash.da.get_da2().is_some_and(|da2| find_ufs_hba(&da2.data).is_some()) {
+        return Err(Error::Unsupported("RPMB reads are only supported on eMMC devices".into()));
+    }
+
+    let rpmb_size = match xflash.get_storage().await {
+        Some(storage) => storage
+            .regions()
+            .iter()
+            .find(|r| r.kind == PartitionKind::Emmc(EmmcPartition::Rpmb))
+            .map_or(0, |r| r.size),
+        None => return Err(Error::penumbra("Storage not detected")),
+    };
+    if rpmb_size == 0 {
+        return Err(Error::Unsupported("The device has no RPMB partition".into()));
+    }
+
+    let blocks = rpmb_size / RPMB_BLOCK_SIZE as u64;
+    info!("Reading {} RPMB blocks", blocks);
+
+    ext_devctrl(xflash, Cmd::ExtInitRpmb, &[]).await?;
+
+    for block in 0..blocks {
+        let data = read_rpmb_block(xflash, block as u32).await?;
+        writer.write_all(&data).await?;
+        progress((block + 1) * RPMB_BLOCK_SIZE as u64, rpmb_size);
+    }
+    writer.flush().await?;
+
+    Ok(blocks * RPMB_BLOCK_SIZE as u64)
+}
+
+/// Reads a single RPMB data frame, returning its data
+async fn read_rpmb_block(xflash: &mut XFlash, address: u32) -> Result<Vec<u8>> {
+    ext_devctrl(xflash, Cmd::ExtReadRpmb, &[&address.to_le_bytes()]).await?;
+
+    // The result field of the response frame, 0 when the read went through
+    let result = xflash.read_data().await?;
+    let result = result.get(0..2).map_or(u16::MAX, |r| u16::from_le_bytes([r[0], r[1]]));
+    if result != 0 {
+        status_ok!(xflash);
+        return Err(Error::Status {
+            ctx: format!("RPMB read of block {} failed", address),
+            status: result as u32,
+        });
+    }
+
+    let frame = xflash.read_data().await?;
     status_ok!(xflash);
 
-    Ok(payload)
+    // Depending on the extensions version, either the data or the whole frame is sent back
+    match frame.len() {
+        RPMB_BLOCK_SIZE => Ok(frame),
+        RPMB_FRAME_SIZE => Ok(frame[RPMB_DATA_OFFSET..RPMB_DATA_OFFSET + RPMB_BLOCK_SIZE].to_vec()),
+        len => Err(Error::proto(format!("Unexpected RPMB frame length {}", len))),
+    }
 }
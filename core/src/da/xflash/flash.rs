@@ -5,22 +5,34 @@
 use log::{debug, info};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+use crate::connection::phases::FlashPhase;
 use crate::core::storage::PartitionKind;
 use crate::da::DAProtocol;
 use crate::da::xflash::XFlash;
 use crate::da::xflash::cmds::*;
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, XFlashErrorKind};
+
+/// Sends the abort status in place of the next chunk status, which makes the DA drop the
+/// transfer and go back to its command loop.
+async fn abort_transfer(xflash: &mut XFlash) -> Error {
+    debug!("Aborting transfer");
+    let status: u32 = XFlashErrorKind::Abort.into();
+    // The DA answers with its own error status, which is expected
+    xflash.send(&status.to_le_bytes()).await.ok();
+    xflash.conn.phases.finish();
+    Error::cancelled()
+}
 
 pub async fn read_flash<F, W>(
     xflash: &mut XFlash,
     addr: u64,
-    size: usize,
+    size: u64,
     section: PartitionKind,
     mut progress: F,
     mut writer: W,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
     W: AsyncWrite + Unpin,
 {
     info!("Reading flash at address {:#X} with size {:#X}", addr, size);
@@ -47,7 +59,7 @@ where
     param.extend_from_slice(&storage_type.to_le_bytes());
     param.extend_from_slice(&partition_type.to_le_bytes());
     param.extend_from_slice(&addr.to_le_bytes());
-    param.extend_from_slice(&(size as u64).to_le_bytes());
+    param.extend_from_slice(&size.to_le_bytes());
     // Which basically means: append it! Improvements are welcome.
     param.extend_from_slice(&nand_ext.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>());
 
@@ -55,7 +67,7 @@ where
     xflash.send(&param).await?;
     status_ok!(xflash);
 
-    let mut bytes_read = 0;
+    let mut bytes_read = 0u64;
 
     // Read chunk, send acknowledgment, status, repeat until profit
     progress(0, size);
@@ -67,14 +79,18 @@ where
         }
 
         writer.write_all(&chunk).await?;
-        bytes_read += chunk.len();
+        bytes_read += chunk.len() as u64;
 
+        if xflash.conn.cancel.is_cancelled() {
+            return Err(abort_transfer(xflash).await);
+        }
         let ack_payload = [0u8; 4];
 
         xflash.send(&ack_payload).await?;
 
         debug!("Chunk of {} bytes read.", chunk.len());
         progress(bytes_read, size);
+        xflash.conn.throttle(chunk.len()).await;
 
         if bytes_read >= size {
             debug!("Requested size read. Breaking.");
@@ -92,14 +108,14 @@ where
 pub async fn write_flash<F, R>(
     xflash: &mut XFlash,
     addr: u64,
-    size: usize,
+    size: u64,
     mut reader: R,
     section: PartitionKind,
     mut progress: F,
 ) -> Result<()>
 where
     R: AsyncRead + Unpin,
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     info!("Writing flash at address {:#X} with size {:#X}", addr, size);
 
@@ -117,16 +133,17 @@ where
     param.extend_from_slice(&storage_type.to_le_bytes());
     param.extend_from_slice(&partition_type.to_le_bytes());
     param.extend_from_slice(&addr.to_le_bytes());
-    param.extend_from_slice(&(size as u64).to_le_bytes());
+    param.extend_from_slice(&size.to_le_bytes());
     param.extend_from_slice(&nand_ext.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>());
 
     xflash.send_cmd(Cmd::WriteData).await?;
     xflash.send(&param).await?;
 
     let mut buffer = vec![0u8; chunk_size];
-    let mut bytes_written = 0;
+    let mut bytes_written = 0u64;
 
     debug!("Starting to write data in chunks of {} bytes...", chunk_size);
+    xflash.conn.phases.enter(FlashPhase::Write);
     progress(0, size);
     loop {
         if bytes_written >= size {
@@ -139,8 +156,11 @@ where
         // but for other partitions, this might make the partition unusable.
         // This issue only arises when flashing stuff that is not coming from a dump made
         // with read_flash() or any other tool like mtkclient.
+        if xflash.conn.cancel.is_cancelled() {
+            return Err(abort_transfer(xflash).await);
+        }
         let remaining = size - bytes_written;
-        let to_read = remaining.min(chunk_size);
+        let to_read = remaining.min(chunk_size as u64) as usize;
 
         let bytes_read = reader.read(&mut buffer[..to_read]).await?;
         let chunk = if bytes_read == 0 {
@@ -159,12 +179,14 @@ where
         let checksum = chunk.iter().fold(0u32, |total, &byte| total + byte as u32) & 0xFFFF;
         xflash.send_data(&[&0u32.to_le_bytes(), &checksum.to_le_bytes(), chunk]).await?;
 
-        bytes_written += chunk.len();
+        bytes_written += chunk.len() as u64;
         progress(bytes_written, size);
         debug!("Written {}/{} bytes...", bytes_written, size);
+        xflash.conn.throttle(to_read).await;
     }
 
-    status_ok!(xflash);
+    status_done!(xflash);
+    xflash.conn.phases.finish();
     info!("Flash write completed, 0x{:X} bytes written.", bytes_written);
 
     Ok(())
@@ -173,12 +195,12 @@ where
 pub async fn erase_flash<F>(
     xflash: &mut XFlash,
     addr: u64,
-    size: usize,
+    size: u64,
     section: PartitionKind,
     mut progress: F,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     info!("Erasing flash at address {:#X} with size {:#X}", addr, size);
     let storage_type = xflash.get_storage_type().await as u32;
@@ -189,12 +211,13 @@ where
     param.extend_from_slice(&storage_type.to_le_bytes());
     param.extend_from_slice(&partition_type.to_le_bytes());
     param.extend_from_slice(&addr.to_le_bytes());
-    param.extend_from_slice(&(size as u64).to_le_bytes());
+    param.extend_from_slice(&size.to_le_bytes());
     param.extend_from_slice(&nand_ext.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>());
 
     xflash.send_cmd(Cmd::Format).await?;
     xflash.send(&param).await?;
 
+    xflash.conn.phases.enter(FlashPhase::Erase);
     progress(0, size);
     let mut status = vec![0u8; 4];
     loop {
@@ -213,10 +236,11 @@ where
         xflash.conn.port.write_all(&hdr).await?;
         xflash.conn.port.write_all(&ack).await?;
 
-        let progress_bytes = (progress_percent as usize * size) / 100;
+        let progress_bytes = (progress_percent as u64 * size) / 100;
         progress(progress_bytes, size);
     }
 
+    xflash.conn.phases.finish();
     info!("Flash erase completed.");
     Ok(())
 }
@@ -224,13 +248,13 @@ where
 pub async fn download<F, R>(
     xflash: &mut XFlash,
     part_name: String,
-    size: usize,
+    size: u64,
     mut reader: R,
     mut progress: F,
 ) -> Result<()>
 where
     R: AsyncRead + Unpin,
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     // Works like write_flash, but instead of address and size, it takes a partition name
     // and writes the whole data to it.
@@ -248,31 +272,37 @@ where
     xflash.send_data(&[part_name.as_bytes(), &size.to_le_bytes()]).await?;
 
     let mut buffer = vec![0u8; chunk_size];
-    let mut bytes_written = 0;
+    let mut bytes_written = 0u64;
 
     info!("Starting download to partition '{}' with size 0x{:X}", part_name, size);
 
+    xflash.conn.phases.enter(FlashPhase::Write);
     progress(0, size);
     loop {
         let remaining = size - bytes_written;
-        let to_read = remaining.min(chunk_size);
+        let to_read = remaining.min(chunk_size as u64) as usize;
 
         let bytes_read = reader.read(&mut buffer[..to_read]).await?;
         if bytes_read == 0 {
             break;
         }
+        if xflash.conn.cancel.is_cancelled() {
+            return Err(abort_transfer(xflash).await);
+        }
 
         let chunk = &buffer[..bytes_read];
 
         let checksum = chunk.iter().fold(0u32, |total, &byte| total + byte as u32) & 0xFFFF;
         xflash.send_data(&[&0u32.to_le_bytes(), &checksum.to_le_bytes(), chunk]).await?;
 
-        bytes_written += bytes_read;
+        bytes_written += bytes_read as u64;
 
         progress(bytes_written, size);
+        xflash.conn.throttle(bytes_read).await;
     }
 
-    status_ok!(xflash);
+    status_done!(xflash);
+    xflash.conn.phases.finish();
 
     xflash.send_cmd(Cmd::DeviceCtrl).await?;
     xflash.send_cmd(Cmd::EndDlInfo).await?;
@@ -291,7 +321,7 @@ pub async fn upload<F, W>(
 ) -> Result<()>
 where
     W: AsyncWrite + Unpin,
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     xflash.send_cmd(Cmd::Upload).await?;
     xflash.send(part_name.as_bytes()).await?;
@@ -304,12 +334,12 @@ where
         }
         let mut size_buf = [0u8; 8];
         size_buf.copy_from_slice(&size_data[0..8]);
-        u64::from_le_bytes(size_buf) as usize
+        u64::from_le_bytes(size_buf)
     };
 
     info!("Starting readback of partition '{}' with size 0x{:X}", part_name, size);
 
-    let mut bytes_read = 0;
+    let mut bytes_read = 0u64;
     progress(0, size);
     loop {
         let chunk = xflash.read_data().await?;
@@ -319,11 +349,15 @@ where
         }
 
         writer.write_all(&chunk).await?;
-        bytes_read += chunk.len();
+        bytes_read += chunk.len() as u64;
 
+        if xflash.conn.cancel.is_cancelled() {
+            return Err(abort_transfer(xflash).await);
+        }
         xflash.send(&[0u8; 4]).await?;
 
         progress(bytes_read, size);
+        xflash.conn.throttle(chunk.len()).await;
 
         if bytes_read >= size {
             debug!("Requested size read. Breaking.");
@@ -337,7 +371,7 @@ where
 
 pub async fn format<F>(xflash: &mut XFlash, part_name: String, mut progress: F) -> Result<()>
 where
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     let part = match xflash.dev_info.get_partition(&part_name).await {
         Some(p) => p,
@@ -359,6 +393,7 @@ where
 
     info!("Formatting partition '{}'", part_name);
 
+    xflash.conn.phases.enter(FlashPhase::Erase);
     progress(0, part.size);
     let mut status = vec![0u8; 4];
     loop {
@@ -377,10 +412,11 @@ where
         xflash.conn.port.write_all(&hdr).await?;
         xflash.conn.port.write_all(&ack).await?;
 
-        let progress_bytes = (progress_percent as usize * part.size) / 100;
+        let progress_bytes = (progress_percent as u64 * part.size) / 100;
         progress(progress_bytes, part.size);
     }
 
+    xflash.conn.phases.finish();
     info!("Partition '{}' formatted.", part_name);
     Ok(())
 }
@@ -388,13 +424,13 @@ where
 pub async fn set_rsc_info<F, R>(
     xflash: &mut XFlash,
     part_name: &str,
-    size: usize,
+    size: u64,
     mut reader: R,
     mut progress: F,
 ) -> Result<()>
 where
     R: AsyncRead + Unpin,
-    F: FnMut(usize, usize),
+    F: FnMut(u64, u64),
 {
     // Split in chunks of 256 bytes
     // The payload structure is like this:
@@ -437,7 +473,7 @@ where
 
         xflash.devctrl(Cmd::SetRscInfo, Some(&[&payload])).await?;
 
-        progress(offset as usize * 256 + bytes_read, size);
+        progress(offset * 256 + bytes_read as u64, size);
         offset += 1;
     }
 
@@ -460,6 +496,7 @@ pub async fn get_packet_length(xflash: &mut XFlash) -> Result<(usize, usize)> {
 
     let write_len = u32::from_le_bytes(write_buf) as usize;
     let read_len = u32::from_le_bytes(read_buf) as usize;
+    debug!("DA packet length: write 0x{:X}, read 0x{:X}", write_len, read_len);
 
     xflash.write_packet_length = Some(write_len);
     xflash.read_packet_length = Some(read_len);
@@ -467,18 +504,19 @@ pub async fn get_packet_length(xflash: &mut XFlash) -> Result<(usize, usize)> {
     Ok((write_len, read_len))
 }
 
+/// Returns the size of the data packets to send, the one the DA reported unless
+/// lowered through the connection [`crate::connection::transfer::TransferConfig`].
 pub async fn get_write_packet_length(xflash: &mut XFlash) -> Result<usize> {
-    if xflash.write_packet_length.is_some() {
-        return Ok(xflash.write_packet_length.unwrap());
-    }
-
-    let (write_len, _) = get_packet_length(xflash).await?;
-    Ok(write_len)
+    let reported = match xflash.write_packet_length {
+        Some(len) => len,
+        None => get_packet_length(xflash).await?.0,
+    };
+    Ok(xflash.conn.transfer.write_chunk(Some(reported)))
 }
 
 pub async fn _get_read_packet_length(xflash: &mut XFlash) -> Result<usize> {
-    if xflash.read_packet_length.is_some() {
-        return Ok(xflash.read_packet_length.unwrap());
+    if let Some(len) = xflash.read_packet_length {
+        return Ok(len);
     }
 
     let (_, read_len) = get_packet_length(xflash).await?;
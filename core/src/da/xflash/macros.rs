@@ -32,6 +32,22 @@ macro_rules! status_ok {
     }};
 }
 
+/// Like `status_ok!`, but only fails on Error severity statuses. Info and Warning ones,
+/// which the DA sends when the operation still went through, are logged.
+macro_rules! status_done {
+    ($self:ident) => {{
+        let status = $self.get_status().await?;
+        let xflash_err = crate::error::XFlashError::from_code(status);
+        if xflash_err.severity().is_failure() {
+            log::error!("Status is not expected: 0x{:08X} ({})", status, xflash_err);
+            return Err(Error::XFlash(xflash_err));
+        } else if status != 0 {
+            let severity = xflash_err.severity();
+            log::warn!("DA reported 0x{:08X} ({:?}) after the transfer", status, severity);
+        }
+    }};
+}
+
 macro_rules! status_any {
     ($self:ident, $($valid:expr),+ $(,)?) => {{
         let status = $self.get_status().await?;
@@ -6,10 +6,9 @@
 const EXT_LOADER: &[u8] = include_bytes!("../../../payloads/extloader_v5.bin");
 
 use log::info;
-use sha2::{Digest, Sha256};
 
 use crate::da::xflash::XFlash;
-use crate::da::{DA, DAEntryRegion};
+use crate::da::{DA, DAEntryRegion, hash_fixup};
 use crate::error::Result;
 use crate::utilities::arm::*;
 use crate::utilities::patching::*;
@@ -18,30 +17,26 @@ use crate::utilities::patching::*;
 pub fn patch_da(xflash: &mut XFlash) -> Result<DA> {
     let da2 = patch_da2(xflash)?;
     let mut da1 = patch_da1(xflash)?;
+    let old_da2 = xflash.da.get_da2().cloned().unwrap();
 
-    let hash_pos = xflash.da.find_da_hash_offset();
-    match hash_pos {
-        Some(pos) => {
-            let mut hasher = Sha256::new();
-            hasher.update(&da2.data[..da2.data.len().saturating_sub(da2.sig_len as usize)]);
-            let hash_result = hasher.finalize();
-            patch(&mut da1.data, pos, &bytes_to_hex(&hash_result))?;
-
-            let original_da = &xflash.da;
-            let da = DA {
-                da_type: xflash.da.da_type.clone(),
-                regions: vec![original_da.regions[0].clone(), da1.clone(), da2.clone()],
-                magic: original_da.magic,
-                hw_code: original_da.hw_code,
-                hw_sub_code: original_da.hw_sub_code,
-            };
-            Ok(da)
-        }
-        None => {
+    // Looking the digest up by value is more reliable, the known offset is a fallback
+    if hash_fixup(&mut da1, &old_da2, &da2).is_none() {
+        let Some(pos) = xflash.da.find_da_hash_offset() else {
             info!("[Penumbra] Could not find DA1 hash position, skipping patching");
-            Ok(xflash.da.clone())
-        }
+            return Ok(xflash.da.clone());
+        };
+        patch(&mut da1.data, pos, &bytes_to_hex(&da2.digest()))?;
     }
+
+    let original_da = &xflash.da;
+    Ok(DA {
+        da_type: original_da.da_type.clone(),
+        regions: vec![original_da.regions[0].clone(), da1, da2],
+        magic: original_da.magic,
+        hw_code: original_da.hw_code,
+        hw_sub_code: original_da.hw_sub_code,
+        sw_version: original_da.sw_version,
+    })
 }
 
 /// Patches only DA1, specific for V5 DA
@@ -4,33 +4,40 @@
 */
 use std::io::Cursor;
 
-use crate::core::seccfg::{SecCfgV4, SecCfgV4Algo};
+use crate::core::progress::Progress;
+use crate::core::seccfg::{SECCFG_MAX_SIZE, SecCfg, SecCfgAlgo};
 use crate::da::xflash::exts::sej;
 use crate::da::{DAProtocol, XFlash};
+use crate::error::Result;
 
-pub async fn parse_seccfg(xflash: &mut XFlash) -> Option<SecCfgV4> {
+async fn sej_algo(
+    xflash: &mut XFlash,
+    data: &[u8],
+    encrypt: bool,
+    algo: SecCfgAlgo,
+) -> Result<Vec<u8>> {
+    let (legacy, anti_clone, xor) = algo.sej_flags();
+    sej(xflash, data, encrypt, legacy, anti_clone, xor).await
+}
+
+pub async fn parse_seccfg(xflash: &mut XFlash) -> Option<SecCfg> {
     let seccfg = xflash.dev_info.get_partition("seccfg").await?;
     let section = xflash.get_storage().await?.get_user_part();
 
-    let mut progress = |_, _| {};
+    let mut progress = |_: &Progress| {};
 
-    // We only need the header and padding, which is 200 bytes
-    let mut seccfg_header = Vec::with_capacity(200);
+    // V4 only needs the first 200 bytes, but V3 encrypts a few KiB after its header
+    let length = seccfg.size.min(SECCFG_MAX_SIZE as u64);
+    let mut seccfg_header = Vec::with_capacity(length as usize);
     let mut cursor = Cursor::new(&mut seccfg_header);
 
-    xflash.read_flash(seccfg.address, 200, section, &mut progress, &mut cursor).await.ok()?;
+    xflash.read_flash(seccfg.address, length, section, &mut progress, &mut cursor).await.ok()?;
 
-    let mut parsed_seccfg = SecCfgV4::parse_header(&seccfg_header).ok()?;
-    let hash = parsed_seccfg.get_encrypted_hash();
-    for algo in [SecCfgV4Algo::SW, SecCfgV4Algo::HW, SecCfgV4Algo::HWv3, SecCfgV4Algo::HWv4] {
-        let dec_hash = match algo {
-            SecCfgV4Algo::SW => sej(xflash, &hash, false, false, false, false).await.ok()?,
-            SecCfgV4Algo::HW => sej(xflash, &hash, false, false, true, true).await.ok()?,
-            SecCfgV4Algo::HWv3 => sej(xflash, &hash, false, true, true, false).await.ok()?,
-            SecCfgV4Algo::HWv4 => sej(xflash, &hash, false, false, true, false).await.ok()?,
-        };
-        if dec_hash == parsed_seccfg.get_hash() {
-            parsed_seccfg.set_algo(algo);
+    let mut parsed_seccfg = SecCfg::parse_header(&seccfg_header).ok()?;
+    let encrypted = parsed_seccfg.get_encrypted();
+    for algo in SecCfgAlgo::ALL {
+        let decrypted = sej_algo(xflash, &encrypted, false, algo).await.ok()?;
+        if parsed_seccfg.try_decrypted(&decrypted, algo) {
             return Some(parsed_seccfg);
         }
     }
@@ -38,34 +45,21 @@ pub async fn parse_seccfg(xflash: &mut XFlash) -> Option<SecCfgV4> {
     None
 }
 
-pub async fn write_seccfg(xflash: &mut XFlash, seccfg: &mut SecCfgV4) -> Option<Vec<u8>> {
+pub async fn write_seccfg(xflash: &mut XFlash, seccfg: &mut SecCfg) -> Option<Vec<u8>> {
     let seccfg_part = xflash.dev_info.get_partition("seccfg").await?;
     let section = xflash.get_storage().await?.get_user_part();
 
-    let enc_hash = match seccfg.get_algo() {
-        Some(SecCfgV4Algo::SW) => {
-            sej(xflash, &seccfg.get_hash(), true, false, false, false).await.ok()?
-        }
-        Some(SecCfgV4Algo::HW) => {
-            sej(xflash, &seccfg.get_hash(), true, false, true, true).await.ok()?
-        }
-        Some(SecCfgV4Algo::HWv3) => {
-            sej(xflash, &seccfg.get_hash(), true, true, true, false).await.ok()?
-        }
-        Some(SecCfgV4Algo::HWv4) => {
-            sej(xflash, &seccfg.get_hash(), true, false, true, false).await.ok()?
-        }
-        _ => return None,
-    };
+    let algo = seccfg.get_algo()?;
+    let encrypted = sej_algo(xflash, &seccfg.get_plain(), true, algo).await.ok()?;
 
-    seccfg.set_encrypted_hash(enc_hash);
+    seccfg.set_encrypted(encrypted);
     let seccfg_data = seccfg.create();
 
-    let mut progress = |_, _| {};
+    let mut progress = |_: &Progress| {};
     let mut cursor = Cursor::new(&seccfg_data);
 
     xflash
-        .write_flash(seccfg_part.address, seccfg_data.len(), &mut cursor, section, &mut progress)
+        .write_flash(seccfg_part.address, seccfg_data.len() as u64, &mut cursor, section, &mut progress)
         .await
         .ok()?;
 
@@ -8,11 +8,18 @@ use log::debug;
 
 use crate::core::storage::Storage;
 use crate::core::storage::emmc::EmmcStorage;
+use crate::core::storage::nand::NandStorage;
 use crate::core::storage::ufs::UfsStorage;
 use crate::da::xflash::{Cmd, XFlash};
 
-// TODO: Avoid repeated logic
 pub async fn detect_storage(xflash: &mut XFlash) -> Option<Arc<dyn Storage>> {
+    let storage = probe_storage(xflash).await?;
+    debug!("Storage hardware info: {:?}", storage.hw_info());
+    Some(storage)
+}
+
+// TODO: Avoid repeated logic
+async fn probe_storage(xflash: &mut XFlash) -> Option<Arc<dyn Storage>> {
     let emmc_response = xflash.devctrl(Cmd::GetEmmcInfo, None).await;
     let ufs_response = xflash.devctrl(Cmd::GetUfsInfo, None).await;
 
@@ -36,5 +43,17 @@ pub async fn detect_storage(xflash: &mut XFlash) -> Option<Arc<dyn Storage>> {
         }
     }
 
+    // Only legacy devices use NAND, so it is checked last
+    let nand_response = xflash.devctrl(Cmd::GetNandInfo, None).await;
+    debug!("NAND response: {:?}", nand_response);
+    if let Ok(resp) = nand_response
+        && !resp.iter().all(|&b| b == 0)
+    {
+        debug!("NAND storage detected.");
+        if let Ok(storage) = NandStorage::from_response(&resp) {
+            return Some(Arc::new(storage));
+        }
+    }
+
     None
 }
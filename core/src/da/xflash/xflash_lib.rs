@@ -4,30 +4,40 @@
 */
 use std::sync::Arc;
 
-use log::{debug, error, info, warn};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use log::warn;
+use log::{debug, error, info};
 
 use crate::connection::Connection;
 use crate::core::auth::{AuthManager, SignData, SignPurpose, SignRequest};
 use crate::core::devinfo::DeviceInfo;
-use crate::core::emi::extract_emi_settings;
-use crate::core::storage::Storage;
+use crate::core::emi::extract_emi;
+use crate::core::progress::{ProgressFn, ProgressTracker};
+use crate::core::storage::{Partition, Pmt, Storage};
 use crate::da::xflash::cmds::*;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xflash::exts::boot_extensions;
+use crate::da::xflash::flash;
 use crate::da::xflash::storage::detect_storage;
-use crate::da::{DA, DAProtocol};
+use crate::da::{DA, DAProtocol, ExtState};
 use crate::error::{Error, Result, XFlashError};
 
 pub struct XFlash {
     pub conn: Connection,
     pub da: DA,
     pub pl: Option<Vec<u8>>,
+    /// EMI settings sent instead of the ones extracted from `pl`
+    pub emi: Option<Vec<u8>>,
     pub dev_info: DeviceInfo,
-    pub(super) using_exts: bool,
+    pub(super) ext_state: ExtState,
     pub(super) read_packet_length: Option<usize>,
     pub(super) write_packet_length: Option<usize>,
+    #[cfg_attr(not(all(feature = "payloads", not(feature = "no_exploits"))), allow(dead_code))]
     pub(super) patch: bool,
     pub(super) verbose: bool,
+    /// Exploits turned off by the user, by name
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub(crate) disabled_exploits: Vec<String>,
 }
 
 impl XFlash {
@@ -48,12 +58,15 @@ impl XFlash {
             conn,
             da,
             pl,
+            emi: None,
             dev_info,
-            using_exts: false,
+            ext_state: ExtState::Disabled,
             read_packet_length: None,
             write_packet_length: None,
             patch: true,
             verbose,
+            #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+            disabled_exploits: Vec::new(),
         }
     }
 
@@ -81,14 +94,12 @@ impl XFlash {
     // or functions like read_flash will fail.
     pub async fn read_data(&mut self) -> Result<Vec<u8>> {
         let mut hdr = [0u8; 12];
-        self.conn.port.read_exact(&mut hdr).await?;
+        self.conn.read_response(&mut hdr).await?;
 
         let len = self.parse_header(&hdr)?;
 
-        let mut data = vec![0u8; len as usize];
-        self.conn.port.read_exact(&mut data).await?;
-
-        Ok(data)
+        let chunk = self.conn.transfer.read_chunk(self.read_packet_length);
+        self.conn.read_packet(len as usize, chunk).await
     }
 
     pub(super) async fn upload_stage1(
@@ -153,17 +164,37 @@ impl XFlash {
         Ok(true)
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    /// Flags the next boot as META mode, over the UART (COM) port with `uart` or USB otherwise.
+    /// The flag is a `boot_mode` (1 = META), `com_type` (1 = UART, 2 = USB) and `com_id`
+    /// (0 = single interface device) triplet of u32.
+    pub(super) async fn set_meta_boot_mode(&mut self, uart: bool) -> Result<()> {
+        let com_type: u32 = if uart { 1 } else { 2 };
+
+        let mut flag = Vec::with_capacity(12);
+        for v in [1u32, com_type, 0] {
+            flag.extend_from_slice(&v.to_le_bytes());
+        }
+
+        self.devctrl(Cmd::SetMetaBootMode, Some(&[&flag])).await?;
+        Ok(())
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     pub(super) async fn boot_extensions(&mut self) -> Result<bool> {
-        if self.using_exts {
+        if self.ext_state.is_active() {
             warn!("DA extensions already in use, skipping re-upload");
             return Ok(true);
         }
         info!("Booting DA extensions...");
-        self.using_exts = boot_extensions(self).await?;
+        self.ext_state = boot_extensions(self).await?;
         Ok(true)
     }
 
+    /// Internal helper timing a transfer, and reporting the flash phase running with it.
+    pub(super) fn track<'a>(&self, progress: &'a mut ProgressFn<'_>) -> ProgressTracker<'a> {
+        ProgressTracker::new(progress).with_phases(Some(self.conn.phases.clone()))
+    }
+
     // This is an internal helper, do not use it directly
     pub(super) async fn get_or_detect_storage(&mut self) -> Option<Arc<dyn Storage>> {
         if let Some(storage) = self.dev_info.storage().await {
@@ -178,6 +209,27 @@ impl XFlash {
         None
     }
 
+    /// Reads the PMT, the partition table of legacy devices without a GPT.
+    /// Returns no partitions if it can't be read or parsed.
+    pub(super) async fn read_pmt(&mut self, storage: &dyn Storage) -> Vec<Partition> {
+        let (addr, size) = Pmt::location(storage);
+        let mut data = Vec::new();
+
+        let section = storage.get_user_part();
+        if let Err(e) = flash::read_flash(self, addr, size, section, |_, _| {}, &mut data).await {
+            debug!("Failed to read PMT: {}", e);
+            return Vec::new();
+        }
+
+        match Pmt::parse(&data, storage.kind()) {
+            Ok(pmt) => pmt.partitions(),
+            Err(e) => {
+                debug!("Failed to parse PMT: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
     pub(super) fn generate_header(&self, data: &[u8]) -> [u8; 12] {
         let mut hdr = [0u8; 12];
 
@@ -212,13 +264,15 @@ impl XFlash {
             return Ok(());
         }
 
-        let pl = self
-            .pl
-            .as_ref()
-            .ok_or_else(|| Error::penumbra("Device is in BROM but no preloader was provided!"))?;
-
-        let emi = extract_emi_settings(pl)
-            .ok_or_else(|| Error::penumbra("Failed to extract EMI settings from preloader!"))?;
+        let emi = match (&self.emi, &self.pl) {
+            (Some(emi), _) => emi.clone(),
+            (None, Some(pl)) => extract_emi(pl)?,
+            (None, None) => {
+                return Err(Error::penumbra(
+                    "Device is in BROM but neither a preloader nor EMI settings were provided!",
+                ));
+            }
+        };
 
         info!("[Penumbra] Uploading EMI settings to device...");
         self.send_cmd(Cmd::InitExtRam).await?;
@@ -245,7 +299,7 @@ impl XFlash {
 
         info!("DA SLA is enabled");
 
-        #[cfg(not(feature = "no_exploits"))]
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
         {
             let dummy_sig = vec![0u8; 256];
             if self.devctrl(Cmd::SetRemoteSecPolicy, Some(&[&dummy_sig])).await.is_ok() {
@@ -13,16 +13,81 @@ pub const MAGIC: u32 = 0xFEEEEEEF;
 pub const DT_PROTOCOL_FLOW: u32 = 0x1;
 pub const CMD_START: &[u8] = b"<command>CMD:START</command>";
 pub const CMD_END: &[u8] = b"<command>CMD:END</command>";
+/// Commands the DA sends on its own between host commands.
+/// They are not part of any command flow, and must be skipped when reading responses.
+///
+/// Unconfirmed: these names weren't taken from a DA binary or a capture, they follow the
+/// naming of the DA initiated commands above (`CMD:PROGRESS-REPORT`...). Frames with other
+/// commands are returned as responses like before, so a wrong name can't swallow one.
+/// Replace them with the ones of a capture once a DA is seen sending reports.
+pub const DA_REPORT_CMDS: &[&str] = &["CMD:NOTIFY", "CMD:REPORT"];
 pub const HOST_CMDS: &str =
     "CMD:DOWNLOAD-FILE^1@CMD:FILE-SYS-OPERATION^1@CMD:PROGRESS-REPORT^1@CMD:UPLOAD-FILE^1@";
 
+/// Host commands the DA agreed to use, out of the ones offered in [`HOST_CMDS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupportedCmds(u8);
+
+impl SupportedCmds {
+    pub const DOWNLOAD_FILE: Self = Self(1 << 0);
+    pub const FILE_SYS_OPERATION: Self = Self(1 << 1);
+    pub const PROGRESS_REPORT: Self = Self(1 << 2);
+    pub const UPLOAD_FILE: Self = Self(1 << 3);
+    pub const ALL: Self = Self(0xF);
+
+    const NAMES: [(Self, &'static str); 4] = [
+        (Self::DOWNLOAD_FILE, "CMD:DOWNLOAD-FILE"),
+        (Self::FILE_SYS_OPERATION, "CMD:FILE-SYS-OPERATION"),
+        (Self::PROGRESS_REPORT, "CMD:PROGRESS-REPORT"),
+        (Self::UPLOAD_FILE, "CMD:UPLOAD-FILE"),
+    ];
+
+    /// Parses a capability list in the [`HOST_CMDS`] format (`CMD:NAME^1@...`).
+    /// Returns `None` if `s` doesn't hold one.
+    pub fn parse(s: &str) -> Option<Self> {
+        // Skip anything before the list, like the <command>CMD:END</command> around it
+        let caret = s.find('^')?;
+        let list = &s[s[..caret].rfind("CMD:")?..];
+        let list = &list[..list.find('<').unwrap_or(list.len())];
+
+        let mut cmds = Self(0);
+        for entry in list.split('@').filter(|e| !e.is_empty()) {
+            let (name, version) = entry.split_once('^').unwrap_or((entry, "1"));
+            if version.trim() == "0" {
+                continue;
+            }
+            if let Some((cmd, _)) = Self::NAMES.iter().find(|(_, n)| *n == name.trim()) {
+                cmds.0 |= cmd.0;
+            }
+        }
+
+        Some(cmds)
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Name of the first command of `cmds` missing from `self`, if any
+    pub fn missing(&self, cmds: Self) -> Option<&'static str> {
+        Self::NAMES.iter().find(|(c, _)| cmds.contains(*c) && !self.contains(*c)).map(|(_, n)| *n)
+    }
+}
+
+impl Default for SupportedCmds {
+    /// DAs that don't negotiate are assumed to take every command
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
 /// Perform a (fake) file system operation
 #[allow(dead_code)]
 #[derive(Clone, Copy)]
 pub enum FileSystemOp {
     MkDir,
     Exists,
-    FileSize(usize),
+    FileSize(u64),
     RemoveAll,
     Remove,
 }
@@ -39,6 +104,28 @@ impl FileSystemOp {
     }
 }
 
+/// Sections of a command the DA reads arguments from.
+/// Fields are put in `Arg` unless told otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    /// `<arg>`, regular arguments
+    Arg,
+    /// `<adv>`, advanced arguments (e.g. DRAM initialization)
+    Adv,
+    /// Any other section, only kept for compatibility with `custom_arg = "..."`
+    Custom(&'static str),
+}
+
+impl Section {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::Arg => "arg",
+            Section::Adv => "adv",
+            Section::Custom(name) => name,
+        }
+    }
+}
+
 /// Lifetime of an XML command
 #[derive(Clone, Copy)]
 pub enum XmlCmdLifetime {
@@ -50,7 +137,7 @@ pub enum XmlCmdLifetime {
 /// using the `XmlCommand` derive macro.
 pub trait XmlCommand {
     fn cmd_name(&self) -> &'static str;
-    fn args(&self) -> Vec<(Option<&'static str>, &'static str, String)>;
+    fn args(&self) -> Vec<(Section, &'static str, String)>;
     fn version(&self) -> &'static str;
 }
 
@@ -78,7 +165,7 @@ pub struct SetRuntimeParameter {
     log_channel: String,
     #[xml(tag = "system_os")]
     system_os: String,
-    #[xml(custom_arg = "adv", tag = "initialize_dram")]
+    #[xml(section = adv, tag = "initialize_dram")]
     init_dram: String,
 }
 
@@ -91,6 +178,17 @@ pub struct HostSupportedCommands {
 #[derive(XmlCommand)]
 pub struct NotifyInitHw;
 
+#[derive(XmlCommand)]
+pub struct CanHigherUsbSpeed {
+    #[allow(dead_code)]
+    #[xml(tag = "target_file", fmt = "MEM://0x0:0x40")]
+    target_file: String,
+}
+
+/// Makes the DA re-enumerate at the higher speed found by [`CanHigherUsbSpeed`]
+#[derive(XmlCommand)]
+pub struct NotifySwitchUsbSpeed;
+
 #[derive(XmlCommand)]
 pub struct SetHostInfo {
     #[xml(tag = "info")]
@@ -143,7 +241,7 @@ pub struct ReadFlash {
     #[xml(tag = "target_file", fmt = "{partition}")]
     target_file: String,
     #[xml(tag = "length", fmt = "0x{length:X}")]
-    length: usize,
+    length: u64,
     #[xml(tag = "offset", fmt = "0x{offset:X}")]
     offset: u64,
 }
@@ -162,7 +260,7 @@ pub struct WriteFlash {
     #[xml(tag = "partition")]
     partition: String,
     #[xml(tag = "source_file", fmt = "MEM:\\0x0:0x{length:X}")]
-    length: usize,
+    length: u64,
     #[xml(tag = "offset", fmt = "0x{offset:X}")]
     offset: u64,
 }
@@ -178,7 +276,7 @@ pub struct EraseFlash {
     #[xml(tag = "partition")]
     section: String,
     #[xml(tag = "length", fmt = "0x{length:X}")]
-    length: usize,
+    length: u64,
     #[xml(tag = "offset", fmt = "0x{offset:X}")]
     offset: u64,
 }
@@ -202,20 +300,35 @@ pub struct SetBootMode {
 }
 
 pub fn create_cmd<C: XmlCommand>(cmd: &C) -> String {
+    build_cmd(cmd.version(), cmd.cmd_name(), cmd.args())
+}
+
+/// Builds a command from its name (without `CMD:`) and `(tag, content)` arguments,
+/// all put in `<arg>`. Meant for commands not known at compile time, like the ones typed
+/// in a development console, [`XmlCommand`] types should be preferred otherwise.
+pub fn create_raw_cmd(name: &str, args: &[(&str, &str)]) -> String {
+    let args = args.iter().map(|(tag, content)| (Section::Arg, *tag, content.to_string()));
+    build_cmd("1.0", name, args)
+}
+
+fn build_cmd<'a>(
+    version: &str,
+    name: &str,
+    args: impl IntoIterator<Item = (Section, &'a str, String)>,
+) -> String {
     let mut xml = format!(
         r#"<?xml version="1.0" encoding="utf-8"?><da><version>{}</version><command>CMD:{}</command>"#,
-        cmd.version(),
-        cmd.cmd_name()
+        version, name
     );
 
-    let mut sections: BTreeMap<Option<&str>, Vec<(&str, String)>> = BTreeMap::new();
+    let mut sections: BTreeMap<Section, Vec<(&str, String)>> = BTreeMap::new();
 
-    for (section, tag, content) in cmd.args() {
+    for (section, tag, content) in args {
         sections.entry(section).or_default().push((tag, content));
     }
 
     for (section, entries) in sections {
-        let tag = section.unwrap_or("arg");
+        let tag = section.as_str();
         xml.push_str(&format!("<{}>", tag));
         for (tag_path, content) in entries {
             let parts: Vec<&str> = tag_path.split('/').collect();
@@ -6,33 +6,47 @@ use std::io::Cursor;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use log::{debug, error, info};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use log::error;
+use log::{debug, info, warn};
 use tokio::io::{AsyncRead, AsyncWrite, BufReader};
 
 use crate::connection::Connection;
-use crate::connection::port::ConnectionType;
+use crate::connection::port::{ConnectionType, UsbSpeed};
 use crate::core::devinfo::DeviceInfo;
-use crate::core::seccfg::LockFlag;
-use crate::core::storage::{Gpt, Partition, PartitionKind, Storage, StorageType};
-use crate::da::protocol::{BootMode, DAProtocol};
+use crate::core::progress::{Progress, ProgressFn};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::seccfg::{LockFlag, SecCfg};
+use crate::core::storage::{
+    Gpt,
+    GptReport,
+    GptSource,
+    Partition,
+    PartitionKind,
+    Storage,
+    StorageType,
+};
+use crate::da::protocol::{BootMode, DAProtocol, ExtState};
 use crate::da::xml::cmds::{
     BootTo,
-    HOST_CMDS,
-    HostSupportedCommands,
+    CanHigherUsbSpeed,
     NotifyInitHw,
+    NotifySwitchUsbSpeed,
     Reboot,
     SetBootMode,
     XmlCmdLifetime,
 };
 use crate::da::xml::flash;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xml::sec::{parse_seccfg, write_seccfg};
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xml::{exts, patch};
-use crate::da::{DA, DAEntryRegion, Xml};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::da::DAEntryRegion;
+use crate::da::{DA, Xml};
 use crate::error::{Error, Result};
 use crate::exploit;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::exploit::{Carbonara, Exploit, HeapBait};
 
 #[async_trait]
@@ -56,7 +70,16 @@ impl DAProtocol for Xml {
         };
 
         info!("Uploading and booting to XML DA2...");
-        if let Err(e) = self.boot_to(da2_addr, &da2_data).await {
+        let mut booted = self.boot_to(da2_addr, &da2_data).await;
+        // Some devices re-enumerate as a new USB device once DA2 runs
+        if let Err(e) = &booted
+            && e.is_disconnect()
+        {
+            info!("Device re-enumerated, reconnecting to the DA port");
+            booted = self.conn.reconnect_da().await.map(|_| true);
+        }
+
+        if let Err(e) = booted {
             self.reboot(BootMode::Normal).await.ok();
             return Err(Error::proto(format!("Failed to upload XML DA2: {}", e)));
         }
@@ -65,8 +88,10 @@ impl DAProtocol for Xml {
 
         exploit!(HeapBait, self);
 
-        // These may fail on some devices — safe to ignore
-        xmlcmd_e!(self, HostSupportedCommands, HOST_CMDS).ok();
+        // This may fail on some devices — safe to ignore
+        if let Ok(cmds) = self.negotiate_host_cmds().await {
+            self.supported_cmds = cmds;
+        }
 
         xmlcmd!(self, NotifyInitHw)?;
         let mut mock_progress = |_, _| {};
@@ -75,7 +100,7 @@ impl DAProtocol for Xml {
 
         self.handle_sla().await?;
 
-        #[cfg(not(feature = "no_exploits"))]
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
         self.boot_extensions().await?;
 
         Ok(true)
@@ -86,7 +111,7 @@ impl DAProtocol for Xml {
 
         let reader = BufReader::new(Cursor::new(data));
         let mut progress = |_, _| {};
-        self.download_file(data.len(), reader, &mut progress).await?;
+        self.download_file(data.len() as u64, reader, &mut progress).await?;
 
         self.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
         Ok(true)
@@ -105,13 +130,13 @@ impl DAProtocol for Xml {
             self.conn.port.write_all(&hdr).await?;
 
             let mut pos = 0;
-            let max_chunk_size = self.write_packet_length.unwrap_or(0x8000);
+            let max_chunk_size = self.conn.transfer.write_chunk(self.write_packet_length);
 
             while pos < param.len() {
                 let end = param.len().min(pos + max_chunk_size);
                 let chunk = &param[pos..end];
                 debug!("[TX] Sending chunk (0x{:X} bytes)", chunk.len());
-                self.conn.port.write_all(chunk).await?;
+                self.conn.write_chunk(chunk).await?;
                 pos = end;
             }
 
@@ -136,9 +161,13 @@ impl DAProtocol for Xml {
     async fn reboot(&mut self, bootmode: BootMode) -> Result<()> {
         match bootmode {
             BootMode::Normal | BootMode::HomeScreen => self.shutdown().await?,
+            BootMode::Recovery => {
+                return Err(Error::Unsupported("XML DAs have no recovery boot mode".into()));
+            }
             mode => {
                 let xml_mode = mode.to_text().unwrap();
-                xmlcmd_e!(self, SetBootMode, xml_mode.to_string(), "USB", "ON", "ON")?;
+                let connection = if mode == BootMode::MetaUart { "UART" } else { "USB" };
+                xmlcmd_e!(self, SetBootMode, xml_mode.to_string(), connection, "ON", "ON")?;
             }
         }
 
@@ -148,42 +177,50 @@ impl DAProtocol for Xml {
     async fn read_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::read_flash(self, addr, size, section, writer, progress).await
     }
 
     async fn write_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::write_flash(self, addr, size, section, reader, progress).await
     }
 
     async fn erase_flash(
         &mut self,
         addr: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::erase_flash(self, addr, size, section, progress).await
     }
 
     async fn download(
         &mut self,
         part_name: String,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::download(self, part_name, size, reader, progress).await
     }
 
@@ -191,29 +228,82 @@ impl DAProtocol for Xml {
         &mut self,
         part_name: String,
         reader: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::upload(self, part_name, reader, progress).await
     }
 
-    async fn format(
-        &mut self,
-        part_name: String,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-    ) -> Result<()> {
+    async fn format(&mut self, part_name: String, progress: &mut ProgressFn<'_>) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         flash::format(self, part_name, progress).await
     }
 
-    async fn read32(&mut self, _addr: u32) -> Result<u32> {
-        todo!()
+    async fn read32(&mut self, addr: u32) -> Result<u32> {
+        // XML DAs have no register commands, so this can only go through the extensions
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+        if self.ext_state.is_active() {
+            return exts::read32_ext(self, addr).await;
+        }
+        Err(Error::proto(format!(
+            "Can't read register 0x{:08X}: XML DAs need the extensions for register access",
+            addr
+        )))
+    }
+
+    async fn write32(&mut self, addr: u32, value: u32) -> Result<()> {
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+        if self.ext_state.is_active() {
+            return exts::write32_ext(self, addr, value).await;
+        }
+        Err(Error::proto(format!(
+            "Can't write 0x{:08X} to 0x{:08X}: XML DAs need the extensions for register access",
+            value, addr
+        )))
+    }
+
+    /// XML DAs don't report their speed, so this is the one the USB backend sees.
+    async fn get_usb_speed(&mut self) -> Result<UsbSpeed> {
+        self.conn
+            .port
+            .get_usb_link()
+            .and_then(|link| link.speed)
+            .ok_or_else(|| Error::Unsupported("USB speed is unknown on this port".into()))
     }
 
-    async fn write32(&mut self, _addr: u32, _value: u32) -> Result<()> {
-        todo!()
+    async fn switch_usb_speed(&mut self) -> Result<bool> {
+        // A port not reporting its speed might still be slow, so the DA is asked anyway
+        if self.get_usb_speed().await.is_ok_and(|s| !s.is_slow()) {
+            return Ok(false);
+        }
+
+        if !xmlcmd!(self, CanHigherUsbSpeed, "0")? {
+            return Ok(false);
+        }
+        let resp = self.get_upload_file_resp().await?;
+        self.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
+
+        debug!("Higher USB speed support: {}", resp);
+        if !resp.contains("SUPPORTED") || resp.contains("UNSUPPORTED") {
+            info!("DA can't switch to a higher USB speed");
+            return Ok(false);
+        }
+
+        info!("Switching the DA to a higher USB speed");
+        if !xmlcmd!(self, NotifySwitchUsbSpeed)? {
+            return Ok(false);
+        }
+        // The DA may drop off the bus before CMD:END makes it through
+        self.lifetime_ack(XmlCmdLifetime::CmdEnd).await.ok();
+
+        self.conn.reconnect_after_speed_switch().await?;
+        Ok(true)
     }
 
-    async fn get_usb_speed(&mut self) -> Result<u32> {
-        todo!()
+    fn write_packet_length(&self) -> Option<usize> {
+        self.write_packet_length
     }
 
     fn get_connection(&mut self) -> &mut Connection {
@@ -233,23 +323,36 @@ impl DAProtocol for Xml {
         self.get_or_detect_storage().await.map_or(StorageType::Unknown, |s| s.kind())
     }
 
-    async fn get_partitions(&mut self) -> Vec<Partition> {
-        let storage = match self.get_storage().await {
-            Some(s) => s,
-            None => {
-                error!("[Penumbra] Failed to get storage for partition parsing");
-                return Vec::new();
-            }
-        };
+    async fn get_partitions(&mut self) -> Result<Vec<Partition>> {
+        let storage = self
+            .get_storage()
+            .await
+            .ok_or_else(|| Error::penumbra("Failed to detect the storage to read partitions"))?;
 
         let storage_type = storage.kind();
+
+        // NAND has no GPT at all, its only partition table is the PMT
+        if storage_type == StorageType::Nand {
+            let partitions = self.read_pmt(storage.as_ref()).await;
+            let source = if partitions.is_empty() {
+                warn!("[Penumbra] Failed to read the PMT, no partitions available");
+                GptSource::Synthetic
+            } else {
+                GptSource::Pmt
+            };
+
+            self.dev_info.set_gpt_report(None).await;
+            self.dev_info.set_gpt_source(source).await;
+            return Ok(partitions);
+        }
+
         let pl_part1 = storage.get_pl_part1();
         let pl_part2 = storage.get_pl_part2();
         let user_part = storage.get_user_part();
-        let pl1_size = storage.get_pl1_size() as usize;
-        let pl2_size = storage.get_pl2_size() as usize;
-        let user_size = storage.get_user_size() as usize;
-        let gpt_size = 32 * 1024; // TODO: Change this when adding NAND support and PMT
+        let pl1_size = storage.get_pl1_size();
+        let pl2_size = storage.get_pl2_size();
+        let user_size = storage.get_user_size();
+        let gpt_size = 32 * 1024;
 
         let mut partitions = vec![
             Partition::new("preloader", pl1_size, 0, pl_part1),
@@ -257,32 +360,74 @@ impl DAProtocol for Xml {
             Partition::new("PGPT", gpt_size, 0, user_part),
         ];
 
-        let sgpt = Partition::new("SGPT", gpt_size, user_size as u64 - gpt_size as u64, user_part);
+        let sgpt = Partition::new("SGPT", gpt_size, user_size - gpt_size, user_part);
 
-        let mut progress = |_, _| {};
+        let mut progress = |_: &Progress| {};
 
         let mut pgpt_data = Vec::new();
         let mut pgpt_cursor = Cursor::new(&mut pgpt_data);
-        self.upload("PGPT".into(), &mut pgpt_cursor, &mut progress).await.ok();
-        let parsed_gpt_parts =
-            Gpt::parse(&pgpt_data, storage_type).map(|g| g.partitions()).unwrap_or_default();
+        // A table that can't be read is looked for elsewhere, unless the device is gone
+        if let Err(e) = self.upload("PGPT".into(), &mut pgpt_cursor, &mut progress).await
+            && e.is_disconnect()
+        {
+            return Err(e);
+        }
+        let pgpt = Gpt::parse(&pgpt_data, storage_type).ok();
+
+        // The SGPT is always read, so a disagreement with the PGPT can be reported
+        let mut sgpt_data = Vec::new();
+        let mut sgpt_cursor = Cursor::new(&mut sgpt_data);
+        if let Err(e) = self.upload("SGPT".into(), &mut sgpt_cursor, &mut progress).await
+            && e.is_disconnect()
+        {
+            return Err(e);
+        }
+        let sgpt_gpt = Gpt::parse(&sgpt_data, storage_type).ok();
+
+        let report = GptReport::new(pgpt.as_ref(), sgpt_gpt.as_ref());
+        if !report.diff.is_empty() {
+            warn!(
+                "[Penumbra] Primary and secondary GPT differ ({} partitions), using the primary",
+                report.diff.entries.len()
+            );
+        } else if report.primary_ok && !report.secondary_ok {
+            warn!("[Penumbra] Secondary GPT is corrupted");
+        }
+        // Without any valid GPT, the device might simply not have one
+        let has_gpt = report.primary_ok || report.secondary_ok;
+        self.dev_info.set_gpt_report(Some(report).filter(|_| has_gpt)).await;
 
-        let mut gpt_parts = if !parsed_gpt_parts.is_empty() {
-            parsed_gpt_parts
+        let parsed_gpt_parts = pgpt.map(|g| g.partitions()).unwrap_or_default();
+        let (mut gpt_parts, source) = if !parsed_gpt_parts.is_empty() {
+            (parsed_gpt_parts, GptSource::Primary)
         } else {
-            let mut sgpt_data = Vec::new();
-            let mut sgpt_cursor = Cursor::new(&mut sgpt_data);
-            self.upload("SGPT".into(), &mut sgpt_cursor, &mut progress).await.ok();
-            Gpt::parse(&sgpt_data, storage_type).map(|g| g.partitions()).unwrap_or_default()
+            let mut parts = sgpt_gpt.map(|g| g.partitions()).unwrap_or_default();
+            let mut source = GptSource::Secondary;
+
+            // Legacy eMMC devices might have a PMT instead of a GPT
+            if parts.is_empty() {
+                parts = self.read_pmt(storage.as_ref()).await;
+                source = if parts.is_empty() { GptSource::Synthetic } else { GptSource::Pmt };
+            }
+
+            if source != GptSource::Pmt {
+                warn!(
+                    "[Penumbra] Primary GPT is unreadable, partition table source: {}",
+                    source.as_str()
+                );
+            }
+            (parts, source)
         };
 
+        self.dev_info.set_gpt_source(source).await;
+
         partitions.append(&mut gpt_parts);
         partitions.push(sgpt);
 
-        partitions
+        Ok(partitions)
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn set_seccfg_lock_state(&mut self, locked: LockFlag) -> Option<Vec<u8>> {
         let seccfg = parse_seccfg(self).await;
         if seccfg.is_none() {
@@ -295,28 +440,57 @@ impl DAProtocol for Xml {
         write_seccfg(self, &mut seccfg).await
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn get_seccfg_lock_state(&mut self) -> Result<LockFlag> {
+        Ok(self.read_seccfg().await?.get_lock_state())
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn read_seccfg(&mut self) -> Result<SecCfg> {
+        parse_seccfg(self).await.ok_or_else(|| Error::penumbra("Failed to parse seccfg"))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn write_seccfg(&mut self, seccfg: &mut SecCfg) -> Result<Vec<u8>> {
+        write_seccfg(self, seccfg).await.ok_or_else(|| Error::penumbra("Failed to write seccfg"))
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     async fn peek(
         &mut self,
         addr: u32,
         length: usize,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut ProgressFn<'_>,
     ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
         exts::peek(self, addr, length, writer, progress).await
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    async fn poke(
+        &mut self,
+        addr: u32,
+        data: &[u8],
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<()> {
+        let mut tracker = self.track(progress);
+        let progress = |done, total| tracker.update(done, total);
+        exts::poke(self, addr, data, progress).await
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da(&mut self) -> Option<DA> {
         patch::patch_da(self).ok()
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da1(&mut self) -> Option<DAEntryRegion> {
         patch::patch_da1(self).ok()
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     fn patch_da2(&mut self) -> Option<DAEntryRegion> {
         patch::patch_da2(self).ok()
     }
@@ -328,4 +502,8 @@ impl DAProtocol for Xml {
     fn get_da(&self) -> &DA {
         &self.da
     }
+
+    fn extensions_state(&self) -> ExtState {
+        self.ext_state.clone()
+    }
 }
@@ -8,11 +8,11 @@ use log::{debug, info};
 use tokio::io::AsyncWrite;
 use xmlcmd_derive::XmlCommand;
 
-use crate::da::DAProtocol;
 use crate::da::xml::Xml;
 use crate::da::xml::cmds::{XmlCmdLifetime, XmlCommand};
 use crate::da::xml::patch::{find_sej_base, is_arm64};
-use crate::error::Result;
+use crate::da::{DAProtocol, ExtState};
+use crate::error::{Error, Result};
 use crate::exploit::get_v6_payload;
 use crate::utilities::analysis::{Aarch64Analyzer, ArchAnalyzer, ArmAnalyzer};
 use crate::utilities::patching::{bytes_to_hex, patch_pattern_str};
@@ -37,15 +37,17 @@ pub struct ExtReadMem {
     length: usize,
 }
 
-/*
 #[derive(XmlCommand)]
 pub struct ExtWriteMem {
     #[xml(tag = "address", fmt = "0x{address:X}")]
     address: u32,
     #[xml(tag = "length", fmt = "0x{length:X}")]
-    length: u32,
+    length: usize,
 }
-*/
+
+/// Largest payload accepted by [`poke`]. Poking is meant for small live patches,
+/// bigger payloads should be booted with `boot_to` instead.
+pub const MAX_POKE_LEN: usize = 0x10000;
 
 #[derive(XmlCommand)]
 pub struct ExtSej {
@@ -59,12 +61,12 @@ pub struct ExtSej {
     length: u32,
 }
 
-pub async fn boot_extensions(xml: &mut Xml) -> Result<bool> {
+pub async fn boot_extensions(xml: &mut Xml) -> Result<ExtState> {
     let ext_data = match prepare_extensions(xml) {
         Some(data) => data,
         None => {
             debug!("Failed to prepare XML extensions. Continuing without.");
-            return Ok(false);
+            return Ok(ExtState::FailedToBoot("DA2 not supported by the extensions".into()));
         }
     };
 
@@ -78,14 +80,14 @@ pub async fn boot_extensions(xml: &mut Xml) -> Result<bool> {
     let boot_to_resp = xml.boot_to(ext_addr, &ext_data).await.unwrap_or(false);
     if !boot_to_resp {
         info!("Failed to upload XML extensions, continuing without extensions");
-        return Ok(false);
+        return Ok(ExtState::FailedToBoot("upload failed".into()));
     }
 
     match xmlcmd!(xml, ExtAck) {
         Ok(_) => {}
         Err(_) => {
             info!("Extensions did not reply, continuing without extensions");
-            return Ok(false);
+            return Ok(ExtState::FailedToBoot("no reply".into()));
         }
     }
 
@@ -94,7 +96,7 @@ pub async fn boot_extensions(xml: &mut Xml) -> Result<bool> {
         Err(_) => {
             xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
             info!("Failed to get extension ack response, continuing without extensions");
-            return Ok(false);
+            return Ok(ExtState::FailedToBoot("no ack response".into()));
         }
     };
 
@@ -103,7 +105,7 @@ pub async fn boot_extensions(xml: &mut Xml) -> Result<bool> {
     let ack: String = get_tag(&response, "status")?;
     if ack != "OK" {
         info!("DA extensions failed to start: {}", ack);
-        return Ok(false);
+        return Ok(ExtState::FailedToBoot(format!("status {}", ack)));
     }
 
     // Some V6 devices have a different SEJ base, we need to set it here so that SEJ commands work
@@ -112,7 +114,7 @@ pub async fn boot_extensions(xml: &mut Xml) -> Result<bool> {
 
     info!("Successfully booted XML extensions");
 
-    Ok(true)
+    Ok(ExtState::Active)
 }
 
 fn prepare_extensions(xml: &Xml) -> Option<Vec<u8>> {
@@ -196,9 +198,9 @@ pub async fn sej(
 
     let mut buf = data.to_vec();
     let mut cursor = Cursor::new(&mut buf);
-    let mut progress = |_: usize, _: usize| {};
+    let mut progress = |_: u64, _: u64| {};
 
-    xml.download_file(length as usize, &mut cursor, &mut progress).await?;
+    xml.download_file(length as u64, &mut cursor, &mut progress).await?;
     cursor.set_position(0);
     xml.upload_file(&mut cursor, &mut progress).await?;
 
@@ -215,7 +217,7 @@ pub async fn peek<F>(
     mut progress: F,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
     xmlcmd!(xml, ExtReadMem, addr, length)?;
 
@@ -225,3 +227,47 @@ where
 
     Ok(())
 }
+
+pub async fn poke<F>(xml: &mut Xml, addr: u32, data: &[u8], mut progress: F) -> Result<()>
+where
+    F: FnMut(u64, u64) + Send,
+{
+    let length = data.len();
+    if length == 0 || length > MAX_POKE_LEN {
+        return Err(Error::penumbra(format!(
+            "Invalid poke length 0x{:X}, must be between 1 and 0x{:X} bytes",
+            length, MAX_POKE_LEN
+        )));
+    }
+    if (addr as u64) + (length as u64) > u32::MAX as u64 + 1 {
+        return Err(Error::penumbra(format!(
+            "Poke range 0x{:08X}+0x{:X} overflows the address space",
+            addr, length
+        )));
+    }
+
+    xmlcmd!(xml, ExtWriteMem, addr, length)?;
+
+    let mut cursor = Cursor::new(data);
+    xml.download_file(length as u64, &mut cursor, &mut progress).await?;
+
+    xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
+
+    Ok(())
+}
+
+pub async fn read32_ext(xml: &mut Xml, addr: u32) -> Result<u32> {
+    let mut buf = Vec::with_capacity(4);
+    peek(xml, addr, 4, &mut buf, |_, _| {}).await?;
+
+    let bytes: [u8; 4] = buf
+        .get(0..4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| Error::io("Short register read"))?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub async fn write32_ext(xml: &mut Xml, addr: u32, value: u32) -> Result<()> {
+    poke(xml, addr, &value.to_le_bytes(), |_, _| {}).await
+}
@@ -4,12 +4,14 @@
 */
 use tokio::io::{AsyncRead, AsyncWrite};
 
+use crate::connection::phases::FlashPhase;
 use crate::core::storage::{PartitionKind, is_pl_part};
 use crate::da::Xml;
 use crate::da::xml::cmds::{
     ErasePartition,
     FileSystemOp,
     ReadPartition,
+    SupportedCmds,
     WritePartition,
     XmlCmdLifetime,
 };
@@ -24,8 +26,9 @@ pub async fn upload<F, W>(
 ) -> Result<()>
 where
     W: AsyncWrite + Unpin,
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
+    xml.require_cmds(SupportedCmds::UPLOAD_FILE)?;
     xmlcmd!(xml, ReadPartition, &part_name, &part_name)?;
 
     xml.upload_file(&mut writer, &mut progress).await?;
@@ -37,14 +40,14 @@ where
 pub async fn read_flash<F, W>(
     xml: &mut Xml,
     addr: u64,
-    size: usize,
+    size: u64,
     section: PartitionKind,
     mut writer: W,
     mut progress: F,
 ) -> Result<()>
 where
     W: AsyncWrite + Unpin,
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
     xmlcmd!(xml, ReadFlash, section.as_str(), section.as_str(), size, addr)?;
     xml.upload_file(&mut writer, &mut progress).await?;
@@ -56,26 +59,30 @@ where
 pub async fn download<F, R>(
     xml: &mut Xml,
     part_name: String,
-    size: usize,
+    size: u64,
     mut reader: R,
     mut progress: F,
 ) -> Result<()>
 where
     R: AsyncRead + Unpin,
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
+    xml.require_cmds(SupportedCmds::DOWNLOAD_FILE)?;
     xmlcmd!(xml, WritePartition, &part_name, &part_name)?;
     // Progress report is not needed for PL partitions,
     // because the DA skips the erase process for them.
     if !is_pl_part(&part_name) {
-        let mut mock_progress = |_: usize, _: usize| {};
+        xml.conn.phases.enter(FlashPhase::Erase);
+        let mut mock_progress = |_: u64, _: u64| {};
         xml.progress_report(&mut mock_progress).await?;
     }
 
     xml.file_system_op(FileSystemOp::Exists).await?;
     xml.file_system_op(FileSystemOp::Exists).await?;
 
+    xml.conn.phases.enter(FlashPhase::Write);
     xml.download_file(size, &mut reader, &mut progress).await?;
+    xml.conn.phases.finish();
     xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
 
     Ok(())
@@ -84,20 +91,23 @@ where
 pub async fn write_flash<F, R>(
     xml: &mut Xml,
     addr: u64,
-    size: usize,
+    size: u64,
     section: PartitionKind,
     mut reader: R,
     mut progress: F,
 ) -> Result<()>
 where
     R: AsyncRead + Unpin,
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
     xmlcmd!(xml, WriteFlash, section.as_str(), size, addr)?;
 
     xml.file_system_op(FileSystemOp::FileSize(size)).await?;
+    xml.conn.phases.enter(FlashPhase::Erase);
     xml.progress_report(&mut |_, _| {}).await?; // Pre-erase
+    xml.conn.phases.enter(FlashPhase::Write);
     xml.download_file(size, &mut reader, &mut progress).await?;
+    xml.conn.phases.finish();
     xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
 
     Ok(())
@@ -105,10 +115,13 @@ where
 
 pub async fn format<F>(xml: &mut Xml, part_name: String, mut progress: F) -> Result<()>
 where
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
+    xml.require_cmds(SupportedCmds::PROGRESS_REPORT)?;
     xmlcmd!(xml, ErasePartition, &part_name)?;
+    xml.conn.phases.enter(FlashPhase::Erase);
     xml.progress_report(&mut progress).await?;
+    xml.conn.phases.finish();
 
     xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
 
@@ -118,15 +131,17 @@ where
 pub async fn erase_flash<F>(
     xml: &mut Xml,
     addr: u64,
-    size: usize,
+    size: u64,
     section: PartitionKind,
     mut progress: F,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(u64, u64) + Send,
 {
     xmlcmd!(xml, EraseFlash, section.as_str(), size, addr)?;
+    xml.conn.phases.enter(FlashPhase::Erase);
     xml.progress_report(&mut progress).await?;
+    xml.conn.phases.finish();
     xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
 
     Ok(())
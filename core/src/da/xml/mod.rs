@@ -6,12 +6,12 @@
 mod macros;
 mod cmds;
 mod da_protocol;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 mod exts;
 mod flash;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 mod patch;
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 mod sec;
 mod storage;
 mod xml_lib;
@@ -5,8 +5,8 @@
 
 use log::{info, warn};
 
-use crate::da::{DA, DAEntryRegion, Xml};
-use crate::error::Result;
+use crate::da::{DA, DAEntryRegion, Xml, hash_fixup};
+use crate::error::{Error, Result};
 use crate::exploit::get_v6_payload;
 use crate::utilities::analysis::{Aarch64Analyzer, ArchAnalyzer, ArmAnalyzer};
 use crate::utilities::arm::{encode_bl_arm, force_return as arm_force_return};
@@ -63,12 +63,29 @@ pub fn find_sej_base(data: &[u8]) -> u32 {
     sej_base
 }
 
-pub fn patch_da(_xml: &mut Xml) -> Result<DA> {
-    todo!()
+/// Patches DA2 and fixes its digest up in DA1, so it goes through DA1's verification
+pub fn patch_da(xml: &mut Xml) -> Result<DA> {
+    let da2 = patch_da2(xml)?;
+    let mut da1 = patch_da1(xml)?;
+    let old_da2 = xml.da.get_da2().cloned().unwrap();
+
+    if hash_fixup(&mut da1, &old_da2, &da2).is_none() {
+        return Err(Error::penumbra("DA2 digest not found in DA1"));
+    }
+
+    let original_da = &xml.da;
+    Ok(DA {
+        da_type: original_da.da_type.clone(),
+        regions: vec![original_da.regions[0].clone(), da1, da2],
+        magic: original_da.magic,
+        hw_code: original_da.hw_code,
+        hw_sub_code: original_da.hw_sub_code,
+        sw_version: original_da.sw_version,
+    })
 }
 
-pub fn patch_da1(_xml: &mut Xml) -> Result<DAEntryRegion> {
-    todo!()
+pub fn patch_da1(xml: &mut Xml) -> Result<DAEntryRegion> {
+    Ok(xml.da.get_da1().cloned().unwrap())
 }
 
 pub fn patch_da2(xml: &mut Xml) -> Result<DAEntryRegion> {
@@ -4,33 +4,34 @@
 */
 use std::io::Cursor;
 
-use crate::core::seccfg::{SecCfgV4, SecCfgV4Algo};
+use crate::core::progress::Progress;
+use crate::core::seccfg::{SECCFG_MAX_SIZE, SecCfg, SecCfgAlgo};
 use crate::da::xml::exts::sej;
 use crate::da::{DAProtocol, Xml};
+use crate::error::Result;
 
-pub async fn parse_seccfg(xml: &mut Xml) -> Option<SecCfgV4> {
+async fn sej_algo(xml: &mut Xml, data: &[u8], encrypt: bool, algo: SecCfgAlgo) -> Result<Vec<u8>> {
+    let (legacy, anti_clone, xor) = algo.sej_flags();
+    sej(xml, data, encrypt, legacy, anti_clone, xor).await
+}
+
+pub async fn parse_seccfg(xml: &mut Xml) -> Option<SecCfg> {
     let seccfg = xml.dev_info.get_partition("seccfg").await?;
-    let mut progress = |_, _| {};
+    let mut progress = |_: &Progress| {};
 
-    let mut seccfg_header = Vec::with_capacity(seccfg.size);
+    let mut seccfg_header = Vec::with_capacity(seccfg.size as usize);
     let mut cursor = Cursor::new(&mut seccfg_header);
 
     xml.upload("seccfg".to_string(), &mut cursor, &mut progress).await.ok()?;
 
-    // Cut to 200 bytes
-    seccfg_header.truncate(200);
+    // Only the start is used, however large the partition is
+    seccfg_header.truncate(SECCFG_MAX_SIZE);
 
-    let mut parsed_seccfg = SecCfgV4::parse_header(&seccfg_header).ok()?;
-    let hash = parsed_seccfg.get_encrypted_hash();
-    for algo in [SecCfgV4Algo::SW, SecCfgV4Algo::HW, SecCfgV4Algo::HWv3, SecCfgV4Algo::HWv4] {
-        let dec_hash = match algo {
-            SecCfgV4Algo::SW => sej(xml, &hash, false, false, false, false).await.ok()?,
-            SecCfgV4Algo::HW => sej(xml, &hash, false, false, true, true).await.ok()?,
-            SecCfgV4Algo::HWv3 => sej(xml, &hash, false, true, true, false).await.ok()?,
-            SecCfgV4Algo::HWv4 => sej(xml, &hash, false, false, true, false).await.ok()?,
-        };
-        if dec_hash == parsed_seccfg.get_hash() {
-            parsed_seccfg.set_algo(algo);
+    let mut parsed_seccfg = SecCfg::parse_header(&seccfg_header).ok()?;
+    let encrypted = parsed_seccfg.get_encrypted();
+    for algo in SecCfgAlgo::ALL {
+        let decrypted = sej_algo(xml, &encrypted, false, algo).await.ok()?;
+        if parsed_seccfg.try_decrypted(&decrypted, algo) {
             return Some(parsed_seccfg);
         }
     }
@@ -38,30 +39,19 @@ pub async fn parse_seccfg(xml: &mut Xml) -> Option<SecCfgV4> {
     None
 }
 
-pub async fn write_seccfg(xml: &mut Xml, seccfg: &mut SecCfgV4) -> Option<Vec<u8>> {
-    let enc_hash = match seccfg.get_algo() {
-        Some(SecCfgV4Algo::SW) => {
-            sej(xml, &seccfg.get_hash(), true, false, false, false).await.ok()?
-        }
-        Some(SecCfgV4Algo::HW) => {
-            sej(xml, &seccfg.get_hash(), true, false, true, true).await.ok()?
-        }
-        Some(SecCfgV4Algo::HWv3) => {
-            sej(xml, &seccfg.get_hash(), true, true, true, false).await.ok()?
-        }
-        Some(SecCfgV4Algo::HWv4) => {
-            sej(xml, &seccfg.get_hash(), true, false, true, false).await.ok()?
-        }
-        _ => return None,
-    };
+pub async fn write_seccfg(xml: &mut Xml, seccfg: &mut SecCfg) -> Option<Vec<u8>> {
+    let algo = seccfg.get_algo()?;
+    let encrypted = sej_algo(xml, &seccfg.get_plain(), true, algo).await.ok()?;
 
-    seccfg.set_encrypted_hash(enc_hash);
+    seccfg.set_encrypted(encrypted);
     let seccfg_data = seccfg.create();
 
-    let mut progress = |_, _| {};
+    let mut progress = |_: &Progress| {};
     let mut cursor = Cursor::new(&seccfg_data);
 
-    xml.download("seccfg".to_string(), 200, &mut cursor, &mut progress).await.ok()?;
+    xml.download("seccfg".to_string(), seccfg_data.len() as u64, &mut cursor, &mut progress)
+        .await
+        .ok()?;
 
     Some(seccfg_data)
 }
@@ -8,12 +8,19 @@ use log::debug;
 
 use crate::core::storage::Storage;
 use crate::core::storage::emmc::EmmcStorage;
+use crate::core::storage::nand::NandStorage;
 use crate::core::storage::ufs::UfsStorage;
 use crate::da::xml::Xml;
 use crate::da::xml::cmds::{GetHwInfo, XmlCmdLifetime};
 use crate::utilities::xml::get_tag;
 
 pub async fn detect_storage(xml: &mut Xml) -> Option<Arc<dyn Storage>> {
+    let storage = probe_storage(xml).await?;
+    debug!("Storage hardware info: {:?}", storage.hw_info());
+    Some(storage)
+}
+
+async fn probe_storage(xml: &mut Xml) -> Option<Arc<dyn Storage>> {
     xmlcmd!(xml, GetHwInfo, "0").ok();
 
     let reponse = xml.get_upload_file_resp().await.ok()?;
@@ -34,6 +41,12 @@ pub async fn detect_storage(xml: &mut Xml) -> Option<Arc<dyn Storage>> {
                 return Some(Arc::new(storage));
             }
         }
+        "NAND" => {
+            debug!("NAND storage detected.");
+            if let Ok(storage) = NandStorage::from_xml_response(&reponse) {
+                return Some(Arc::new(storage));
+            }
+        }
         _ => {}
     }
 
@@ -12,10 +12,13 @@ use crate::VERSION;
 use crate::connection::Connection;
 use crate::core::auth::{AuthManager, SignData, SignPurpose, SignRequest};
 use crate::core::devinfo::DeviceInfo;
-use crate::core::storage::Storage;
+use crate::core::events::{CoreEvent, EventSender};
+use crate::core::progress::{ProgressFn, ProgressTracker};
+use crate::core::storage::{Partition, Pmt, Storage};
 use crate::da::xml::cmds::{
     CMD_END,
     CMD_START,
+    DA_REPORT_CMDS,
     DT_PROTOCOL_FLOW,
     FileSystemOp,
     GetSysProperty,
@@ -27,14 +30,17 @@ use crate::da::xml::cmds::{
     SecuritySetFlashPolicy,
     SetHostInfo,
     SetRuntimeParameter,
+    SupportedCmds,
     XmlCmdLifetime,
     XmlCommand,
     create_cmd,
+    create_raw_cmd,
 };
-#[cfg(not(feature = "no_exploits"))]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 use crate::da::xml::exts::boot_extensions;
+use crate::da::xml::flash;
 use crate::da::xml::storage::detect_storage;
-use crate::da::{DA, DAProtocol};
+use crate::da::{DA, DAProtocol, ExtState};
 use crate::error::{Error, Result, XmlError, XmlErrorKind};
 use crate::utilities::xml::{get_tag, get_tag_usize};
 
@@ -43,12 +49,19 @@ pub struct Xml {
     pub da: DA,
     pub dev_info: DeviceInfo,
     #[allow(dead_code)]
-    pub(super) using_exts: bool,
-    #[allow(dead_code)]
+    pub(super) ext_state: ExtState,
     pub(super) read_packet_length: Option<usize>,
     pub(super) write_packet_length: Option<usize>,
+    #[cfg_attr(not(all(feature = "payloads", not(feature = "no_exploits"))), allow(dead_code))]
     pub(super) patch: bool,
     pub(super) verbose: bool,
+    /// Host commands the DA agreed to use, see [`Xml::negotiate_host_cmds`]
+    pub(crate) supported_cmds: SupportedCmds,
+    /// Exploits turned off by the user, by name
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub(crate) disabled_exploits: Vec<String>,
+    /// Where unsolicited DA reports are forwarded, if anyone listens
+    pub events: Option<EventSender>,
 }
 
 impl Xml {
@@ -57,25 +70,75 @@ impl Xml {
             conn,
             da,
             dev_info,
-            using_exts: false,
+            ext_state: ExtState::Disabled,
             read_packet_length: None,
             write_packet_length: None,
             patch: true,
             verbose,
+            supported_cmds: SupportedCmds::default(),
+            #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+            disabled_exploits: Vec::new(),
+            events: None,
         }
     }
 
     /// Reads data of arbitrary length taken from the header sent by the device.
+    /// Unsolicited report frames sent by the DA are handled here and never returned,
+    /// so that they can't be mistaken for the response of the pending command.
     pub async fn read_data(&mut self) -> Result<Vec<u8>> {
+        loop {
+            let data = self.read_frame().await?;
+
+            match Self::parse_report(&data) {
+                Some(notice) => self.handle_report(notice).await?,
+                None => return Ok(data),
+            }
+        }
+    }
+
+    /// Reads a single frame, without any classification.
+    async fn read_frame(&mut self) -> Result<Vec<u8>> {
         let mut hdr = [0u8; 12];
-        self.conn.port.read_exact(&mut hdr).await?;
+        self.conn.read_response(&mut hdr).await?;
 
         let len = self.parse_header(&hdr)?;
 
-        let mut data = vec![0u8; len as usize];
-        self.conn.port.read_exact(&mut data).await?;
+        let chunk = self.conn.transfer.read_chunk(self.read_packet_length);
+        self.conn.read_packet(len as usize, chunk).await
+    }
+
+    /// Returns the message of a DA report frame, or `None` if the frame is anything else.
+    fn parse_report(data: &[u8]) -> Option<String> {
+        // Cheap check first, most frames are acks or raw data
+        if !data.starts_with(b"<?xml") {
+            return None;
+        }
+
+        let xml = String::from_utf8_lossy(data);
+        let cmd: String = get_tag(&xml, "command").ok()?;
+        if !DA_REPORT_CMDS.contains(&cmd.as_str()) {
+            return None;
+        }
+
+        let message: String = get_tag(&xml, "arg/message")
+            .unwrap_or_else(|_| xml.trim_end_matches('\0').trim().to_string());
+        Some(message)
+    }
+
+    /// Forwards a DA report to the event sink, or logs it without one, and acknowledges it.
+    async fn handle_report(&mut self, notice: String) -> Result<()> {
+        match &self.events {
+            Some(events) => {
+                debug!("[DA] {}", notice);
+                events.send(CoreEvent::DaNotice(notice)).ok();
+            }
+            None => warn!("[DA] {}", notice),
+        }
 
-        Ok(data)
+        // Like any other DA initiated command, the report needs an ack
+        // before the DA resumes the pending command
+        self.ack(None).await?;
+        Ok(())
     }
 
     pub(super) fn generate_header(&self, data: &[u8]) -> [u8; 12] {
@@ -154,7 +217,7 @@ impl Xml {
             return Ok(true);
         }
 
-        if s.contains("ERR!UNSUPPORTED") {
+        if s.contains("ERR!") {
             return Err(Error::Xml(XmlError::from_message(&resp)));
         }
 
@@ -172,13 +235,20 @@ impl Xml {
 
     /// Sends an XML command to the device.
     pub async fn send_cmd<C: XmlCommand>(&mut self, cmd: &C) -> Result<bool> {
-        let xml_str = create_cmd(cmd);
-        let xml_bytes = xml_str.as_bytes();
+        self.send_xml_cmd(cmd.cmd_name(), &create_cmd(cmd)).await
+    }
 
+    /// Sends a command built at runtime, see [`create_raw_cmd`].
+    /// Returns `false` if the DA doesn't support the command.
+    pub async fn send_raw_cmd(&mut self, name: &str, args: &[(&str, &str)]) -> Result<bool> {
+        self.send_xml_cmd(name, &create_raw_cmd(name, args)).await
+    }
+
+    async fn send_xml_cmd(&mut self, name: &str, xml_str: &str) -> Result<bool> {
         self.lifetime_ack(XmlCmdLifetime::CmdStart).await?;
-        self.send(xml_bytes).await?;
+        self.send(xml_str.as_bytes()).await?;
 
-        debug!("Sent XML Command: CMD:{}", cmd.cmd_name());
+        debug!("Sent XML Command: CMD:{}", name);
 
         // Read the ack back.
         // We don't wait for CMD:END here, because each CMD might
@@ -193,12 +263,44 @@ impl Xml {
         }
     }
 
+    /// Offers [`HOST_CMDS`] to the DA and returns the ones it agreed to use.
+    /// DAs that don't answer with a list are assumed to take all of them.
+    pub async fn negotiate_host_cmds(&mut self) -> Result<SupportedCmds> {
+        if !xmlcmd!(self, HostSupportedCommands, HOST_CMDS)? {
+            return Ok(SupportedCmds::default());
+        }
+
+        // The answer comes with CMD:END, which must be acked like any other
+        let end = match timeout(Duration::from_millis(700), self.read_data()).await {
+            Ok(end) => end?,
+            Err(_) => return Ok(SupportedCmds::default()),
+        };
+        self.ack(None).await?;
+
+        let end = String::from_utf8_lossy(&end);
+        let cmds = SupportedCmds::parse(&end).unwrap_or_default();
+        debug!("DA supported host commands: {:?}", cmds);
+        Ok(cmds)
+    }
+
+    /// Fails with [`XmlErrorKind::UnsupportedCmd`] if the DA didn't agree to use all of
+    /// `cmds`, instead of letting the operation time out halfway.
+    pub(super) fn require_cmds(&self, cmds: SupportedCmds) -> Result<()> {
+        match self.supported_cmds.missing(cmds) {
+            Some(name) => Err(Error::Xml(XmlError::new(
+                format!("DA doesn't support {}", name),
+                XmlErrorKind::UnsupportedCmd,
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Sends a file to the device.
     pub async fn download_file<R>(
         &mut self,
-        size: usize,
+        size: u64,
         mut reader: R,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(u64, u64) + Send),
     ) -> Result<()>
     where
         R: AsyncRead + Unpin,
@@ -211,9 +313,11 @@ impl Xml {
          * Host: OK!
          * Device: OK@0x<size in hex>
          * Host: OK!
-         * Device: OK@0x0 (status 0)
-         * Host: <data packets>
-         * Device: OK! (each packet)
+         * For each packet:
+         * Host: OK@0x<checksum> (0 when checksums are off)
+         * Device: OK!
+         * Host: <data packet>
+         * Device: OK!, or ERR!CHECKSUM if the packet is corrupt
          */
         let resp = self.read_data().await?;
         let resp_string = String::from_utf8_lossy(&resp);
@@ -232,24 +336,36 @@ impl Xml {
         // Read the response
         self.read_ack().await?;
 
-        let packet_length: usize = get_tag_usize(&resp_string, "arg/packet_length")?;
+        // The DA advertises the largest packet it takes, smaller ones are fine
+        let reported: usize = get_tag_usize(&resp_string, "arg/packet_length")?;
+        self.write_packet_length = Some(reported);
+        let packet_length = self.conn.transfer.write_chunk(Some(reported));
 
         let mut chunk = vec![0u8; packet_length];
-        let mut bytes_sent = 0;
+        let mut bytes_sent = 0u64;
 
         while bytes_sent < size {
-            let to_read = packet_length.min(size - bytes_sent);
+            let to_read = (packet_length as u64).min(size - bytes_sent) as usize;
+            if self.conn.cancel.is_cancelled() {
+                return Err(self.cancel_transfer().await);
+            }
             reader.read_exact(&mut chunk[..to_read]).await?;
 
-            // Status
-            self.ack("0".to_string().into()).await?;
+            let data = &chunk[..to_read];
+            let checksum = if self.conn.transfer.checksum { packet_checksum(data) } else { 0 };
+            self.ack(format!("{:x}", checksum).into()).await?;
             self.read_ack().await?;
 
-            self.send(&chunk[..to_read]).await?;
-            self.read_ack().await?;
+            self.send(data).await?;
+            self.read_ack().await.inspect_err(|e| {
+                if let Error::Xml(XmlError { kind: XmlErrorKind::ChecksumMismatch, .. }) = e {
+                    error!("Packet at 0x{:X} got corrupted on the way to the DA", bytes_sent);
+                }
+            })?;
 
-            bytes_sent += to_read;
+            bytes_sent += to_read as u64;
             progress(bytes_sent, size);
+            self.conn.throttle(to_read).await;
         }
 
         debug!("File download completed, 0x{:X} bytes sent.", size);
@@ -260,7 +376,7 @@ impl Xml {
     pub async fn upload_file<W>(
         &mut self,
         mut writer: W,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(u64, u64) + Send),
     ) -> Result<bool>
     where
         W: AsyncWrite + Unpin,
@@ -295,25 +411,31 @@ impl Xml {
                 .strip_prefix("OK@0x")
                 .ok_or_else(|| Error::proto("Invalid response format, expected OK@0x<hex>\\0"))?;
 
-            usize::from_str_radix(hex, 16)
+            u64::from_str_radix(hex, 16)
                 .map_err(|_| Error::proto("Invalid hex number in OK@0x<...>\\0"))?
         };
 
         self.ack(None).await?;
 
         let packet_length: usize = get_tag_usize(&resp_string, "arg/packet_length")?;
-        let mut bytes_received = 0;
+        self.read_packet_length = Some(packet_length);
+        let mut bytes_received = 0u64;
 
         while bytes_received < size {
-            let to_read = packet_length.min(size - bytes_received);
+            let to_read = (packet_length as u64).min(size - bytes_received);
             self.read_ack().await?;
+            if self.conn.cancel.is_cancelled() {
+                return Err(self.cancel_transfer().await);
+            }
             self.ack(None).await?;
-            let data = self.read_data().await?;
+            // Raw partition data, never classify it as a report
+            let data = self.read_frame().await?;
             writer.write_all(&data).await?;
             self.ack(None).await?;
 
             bytes_received += to_read;
             progress(bytes_received, size);
+            self.conn.throttle(data.len()).await;
         }
 
         debug!("File upload completed, 0x{:X} bytes received.", size);
@@ -321,10 +443,21 @@ impl Xml {
         Ok(true)
     }
 
+    /// Answers the next chunk handshake with an error, which makes the DA drop the file
+    /// transfer and end the command, then consumes the CMD:END so it's ready for the next one.
+    async fn cancel_transfer(&mut self) -> Error {
+        debug!("Cancelling file transfer");
+        if self.send(b"ERR!CANCEL\0").await.is_ok() {
+            self.lifetime_ack(XmlCmdLifetime::CmdEnd).await.ok();
+        }
+        self.conn.phases.finish();
+        Error::cancelled()
+    }
+
     /// Waits for the device to finish a certain operation, reporting progress.
     pub async fn progress_report(
         &mut self,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(u64, u64) + Send),
     ) -> Result<bool> {
         let resp = self.read_data().await?;
         let resp_string = String::from_utf8_lossy(&resp);
@@ -353,7 +486,7 @@ impl Xml {
                 .nth(1)
                 .ok_or_else(|| Error::proto("Invalid progress format"))?;
 
-            let progress_value: usize =
+            let progress_value: u64 =
                 prog.parse().map_err(|_| Error::proto("Invalid progress value"))?;
 
             progress(progress_value, 100);
@@ -400,18 +533,21 @@ impl Xml {
         self.conn.jump_da(addr).await?;
 
         let log_level = if self.verbose { "DEBUG" } else { "INFO" };
+        // With USB, the DA checks each packet of a download against the checksum sent
+        // in its status slot
+        let checksum_level = if self.conn.transfer.checksum { "USB" } else { "NONE" };
 
         xmlcmd_e!(
             self,
             SetRuntimeParameter,
-            "NONE",
+            checksum_level,
             "AUTO-DETECT",
             log_level,
             "UART",
             "LINUX",
             "YES"
         )?;
-        xmlcmd_e!(self, HostSupportedCommands, HOST_CMDS)?;
+        self.supported_cmds = self.negotiate_host_cmds().await?;
         // Wait for the device to initialize DRAM
         xmlcmd!(self, NotifyInitHw)?;
         let mut mock_progress = |_, _| {};
@@ -423,6 +559,11 @@ impl Xml {
         Ok(true)
     }
 
+    /// Internal helper timing a transfer, and reporting the flash phase running with it.
+    pub(super) fn track<'a>(&self, progress: &'a mut ProgressFn<'_>) -> ProgressTracker<'a> {
+        ProgressTracker::new(progress).with_phases(Some(self.conn.phases.clone()))
+    }
+
     pub(super) async fn get_or_detect_storage(&mut self) -> Option<Arc<dyn Storage>> {
         if let Some(storage) = self.dev_info.storage().await {
             return Some(storage);
@@ -436,6 +577,27 @@ impl Xml {
         None
     }
 
+    /// Reads the PMT, the partition table of legacy devices without a GPT.
+    /// Returns no partitions if it can't be read or parsed.
+    pub(super) async fn read_pmt(&mut self, storage: &dyn Storage) -> Vec<Partition> {
+        let (addr, size) = Pmt::location(storage);
+        let mut data = Vec::new();
+
+        let section = storage.get_user_part();
+        if let Err(e) = flash::read_flash(self, addr, size, section, &mut data, |_, _| {}).await {
+            debug!("Failed to read PMT: {}", e);
+            return Vec::new();
+        }
+
+        match Pmt::parse(&data, storage.kind()) {
+            Ok(pmt) => pmt.partitions(),
+            Err(e) => {
+                debug!("Failed to parse PMT: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
     pub async fn get_upload_file_resp(&mut self) -> Result<String> {
         let mut buffer = Vec::new();
         let mut writer = BufWriter::new(&mut buffer);
@@ -447,7 +609,11 @@ impl Xml {
         Ok(String::from_utf8_lossy(&buffer).into_owned())
     }
 
-    pub(super) async fn handle_sla(&mut self) -> Result<bool> {
+    /// Answers the DA SLA challenge, if the DA has SLA enabled. A registered [`Signer`]
+    /// able to sign it is preferred over the dummy signature of exploit builds.
+    ///
+    /// [`Signer`]: crate::core::auth::Signer
+    pub async fn handle_sla(&mut self) -> Result<bool> {
         xmlcmd!(self, GetSysProperty, "DA.SLA", "0")?;
 
         let response = self.get_upload_file_resp().await?;
@@ -460,19 +626,38 @@ impl Xml {
 
         info!("DA SLA is enabled");
 
-        let mut progress = |_, _| {};
+        // A registered signer answers the real challenge, the dummy signature only
+        // works on DAs with SLA patched out, so it's the fallback
+        let sign_req = self.sla_sign_request().await?;
+        let auth = AuthManager::get();
+        if auth.can_sign(&sign_req) {
+            info!("Found signer for DA SLA!");
+            let signed_rnd = auth.sign(&sign_req).await?;
+            info!("Signed DA SLA challenge. Uploading to device...");
 
-        #[cfg(not(feature = "no_exploits"))]
+            self.send_sla_signature(&signed_rnd, "Penumbra SLA challenge").await?;
+            info!("DA SLA signature accepted!");
+            return Ok(true);
+        }
+
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
         {
             let dummy_sig = vec![0u8; 256];
-            xmlcmd!(self, SecuritySetFlashPolicy, "Penumbra Dummy SLA challenge")?;
-            self.download_file(dummy_sig.len(), dummy_sig.as_slice(), &mut progress).await?;
-            if self.lifetime_ack(XmlCmdLifetime::CmdEnd).await.is_ok() {
+            if self.send_sla_signature(&dummy_sig, "Penumbra Dummy SLA challenge").await.is_ok() {
                 info!("DA SLA signature accepted (dummy)!");
                 return Ok(true);
             }
         }
 
+        error!("No signer available for DA SLA! Can't proceed.");
+        Err(Error::penumbra(
+            "DA SLA is enabled, but no signer is available. \
+             Register a signer for the DA SLA key to use this DA.",
+        ))
+    }
+
+    /// Reads the DA SLA challenge and builds the request to sign it
+    async fn sla_sign_request(&mut self) -> Result<SignRequest> {
         xmlcmd!(self, SecurityGetDevFwInfo, "0")?;
         let fw_info = self.get_upload_file_resp().await?;
         self.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
@@ -490,35 +675,32 @@ impl Xml {
             None => Vec::new(),
         };
 
-        let auth = AuthManager::get();
         let sign_data = SignData { rnd, hrid, soc_id, raw: fw_info.into() };
-        let sign_req =
-            SignRequest { data: sign_data, purpose: SignPurpose::DaSla, pubk_mod: da2_data };
-
-        if auth.can_sign(&sign_req) {
-            info!("Found signer for DA SLA!");
-            let signed_rnd = auth.sign(&sign_req).await?;
-            info!("Signed DA SLA challenge. Uploading to device...");
-
-            xmlcmd!(self, SecuritySetFlashPolicy, "Penumbra SLA challenge")?;
-            self.download_file(signed_rnd.len(), signed_rnd.as_slice(), &mut progress).await?;
-            self.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
-            info!("DA SLA signature accepted!");
-            return Ok(true);
-        }
+        Ok(SignRequest { data: sign_data, purpose: SignPurpose::DaSla, pubk_mod: da2_data })
+    }
 
-        error!("No signer available for DA SLA! Can't proceed.");
-        Err(Error::penumbra("DA SLA is enabled, but no signer is available. Can't continue."))
+    /// Answers the DA SLA challenge with `signature`
+    async fn send_sla_signature(&mut self, signature: &[u8], label: &str) -> Result<()> {
+        let mut progress = |_, _| {};
+        xmlcmd!(self, SecuritySetFlashPolicy, label)?;
+        self.download_file(signature.len() as u64, signature, &mut progress).await?;
+        self.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
+        Ok(())
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     pub(super) async fn boot_extensions(&mut self) -> Result<bool> {
-        if self.using_exts {
+        if self.ext_state.is_active() {
             warn!("DA extensions already in use, skipping re-upload");
             return Ok(true);
         }
         info!("Booting DA extensions...");
-        self.using_exts = boot_extensions(self).await?;
+        self.ext_state = boot_extensions(self).await?;
         Ok(true)
     }
 }
+
+/// Additive checksum of a download packet, like the XFlash one but kept to 32 bits.
+fn packet_checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |total, &byte| total.wrapping_add(byte as u32))
+}
@@ -2,21 +2,88 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
-use log::{error, info};
-use tokio::io::{AsyncRead, AsyncWrite};
+use log::{error, info, warn};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::time::timeout;
 
-use crate::connection::Connection;
-use crate::connection::port::{ConnectionType, MTKPort};
+use crate::connection::{Connection, env_baudrate};
+use crate::connection::cancel::CancelToken;
+use crate::connection::phases::{FlashPhase, PhaseTracker};
+use crate::connection::port::{
+    ConnectionType,
+    MTKPort,
+    PortInfo,
+    UsbLink,
+    list_mtk_ports,
+    open_mtk_port,
+};
+use crate::connection::retry::RetryPolicy;
+use crate::connection::stats::ConnStats;
+use crate::connection::trace::{DEFAULT_TRACE_LIMIT, TracePort};
+use crate::connection::transfer::TransferConfig;
+use crate::core::auth::{AuthManager, SignData, SignPurpose, SignRequest};
+use crate::core::chipdb;
 use crate::core::crypto::config::CryptoIO;
-use crate::core::devinfo::{DevInfoData, DeviceInfo};
-use crate::core::seccfg::LockFlag;
-use crate::core::storage::{Partition, PartitionKind};
+use crate::core::devinfo::{DevInfoData, DeviceInfo, TargetConfig};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::efuse::{EFUSE_BANK_COUNT, EfuseBank, efuse_base};
+use crate::core::emi::validate_emi;
+use crate::core::events::EventSender;
+use crate::core::journal::{HashingReader, Journal, JournalState};
+use crate::core::progress::{Progress, ProgressTracker};
+use crate::core::scatter::ScatterFile;
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::core::seccfg::{LockFlag, SecCfg};
+use crate::core::storage::image::{IMAGE_HEADER_LEN, extract_preloader, has_gfh_header};
+use crate::core::storage::sparse::{SparseHeader, SparseReader};
+use crate::core::storage::{
+    BlockWindow,
+    BootRegion,
+    EraseMethod,
+    EraseReport,
+    EraseSpan,
+    Gpt,
+    GptDiff,
+    GptReport,
+    GptSource,
+    ImageKind,
+    Partition,
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageType,
+    is_gpt_part,
+    is_pl_part,
+    is_protected_part,
+    matches_pattern,
+};
 use crate::da::protocol::BootMode;
-use crate::da::{DAFile, DAProtocol, DAType, XFlash, Xml};
-use crate::error::{Error, Result};
+use crate::da::{DAFile, DAProtocol, DAType, ExtState, Legacy, XFlash, Xml};
+use crate::error::{Error, Result, XFlashError, XFlashErrorKind};
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+use crate::exploit::find_exploit;
+use crate::utilities::hash::{HashingWriter, sha256};
+
+/// Size of the reads a user area dump is split into.
+/// Chunks are only written out once fully read, so a partial image always ends on a chunk
+/// boundary and can be resumed.
+const DUMP_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Transfers from this size on get a warning when the USB link is slow.
+const SLOW_LINK_WARN_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Command of the Android bootloader message, at the start of `misc`, booting into recovery
+const BCB_RECOVERY: &[u8] = b"boot-recovery";
+
+/// Picks one of the listed ports by index, see [`DeviceBuilder::with_port_selector`].
+pub type PortSelector = Box<dyn Fn(&[PortInfo]) -> Option<usize> + Send + Sync>;
 
 /// A builder for creating a new [`Device`].
 ///
@@ -26,7 +93,7 @@ use crate::error::{Error, Result};
 /// A MTKPort must be provided to build the device.
 ///
 /// # Example
-/// ```rust
+/// ```rust,ignore
 /// use penumbra::{Device, DeviceBuilder, find_mtk_port};
 ///
 /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
@@ -47,8 +114,48 @@ pub struct DeviceBuilder {
     /// If provided, it can be used to extract EMI settings or other information.
     /// Only needed if told to do so, like when the device is in BROM mode.
     preloader_data: Option<Vec<u8>>,
+    /// EMI settings, to initialize DRAM from BROM without a whole preloader.
+    emi_data: Option<Vec<u8>>,
+    /// Download Agent Auth file, for devices with DAA enabled.
+    auth_file: Option<Vec<u8>>,
     /// Whether to enable verbose logging.
     verbose: bool,
+    /// Whether to allow writes when the partition table doesn't come from the primary GPT.
+    allow_secondary_gpt: bool,
+    /// Optional limit on flash transfer rate, in bytes per second.
+    rate_limit: Option<u64>,
+    /// Overrides of the transfer chunk sizes and packet checksums.
+    transfer: TransferConfig,
+    /// How data chunks are retried after transient I/O errors.
+    retry: RetryPolicy,
+    /// How long to wait for the DA to answer, `None` keeps the connection default.
+    response_timeout: Option<Option<Duration>>,
+    /// Optional sink for events emitted outside of the command flow.
+    events: Option<EventSender>,
+    /// Optional journal recording write and erase operations.
+    journal: Option<Journal>,
+    /// Whether to write images whose header doesn't match the target region.
+    ignore_region_mismatch: bool,
+    /// Whether to read partitions back after writing them, comparing hashes.
+    verify_writes: bool,
+    /// Whether to leave the parts of an erase that don't cover a whole erase group untouched.
+    skip_partial_erase: bool,
+    /// Whether to access partitions extending past the end of the storage.
+    allow_out_of_range: bool,
+    /// Exploits that must not run, by name.
+    disabled_exploits: Vec<String>,
+    /// Whether to leave the watchdog running after the handshake.
+    keep_watchdog: bool,
+    /// Whether to leave the DA at the USB speed it starts with.
+    keep_usb_speed: bool,
+    /// Baudrate UART connections are switched to after the handshake.
+    baudrate: Option<u32>,
+    /// Picks the port used by [`Self::find_port`] when several are plugged in.
+    port_selector: Option<PortSelector>,
+    /// File recording the traffic with the device.
+    trace: Option<PathBuf>,
+    /// Bytes of each transfer kept in the trace, `None` keeps the default.
+    trace_limit: Option<Option<usize>>,
 }
 
 impl DeviceBuilder {
@@ -58,6 +165,51 @@ impl DeviceBuilder {
         self
     }
 
+    /// Assigns how [`Self::find_port`] picks a port among the connected ones, by returning
+    /// its index, or `None` to give up. Without a selector the first port is used.
+    pub fn with_port_selector<F>(mut self, selector: F) -> Self
+    where
+        F: Fn(&[PortInfo]) -> Option<usize> + Send + Sync + 'static,
+    {
+        self.port_selector = Some(Box::new(selector));
+        self
+    }
+
+    /// Lists the connected ports and opens the one picked by the port selector, unless
+    /// a port was already assigned with [`Self::with_mtk_port`].
+    ///
+    /// ```rust,ignore
+    /// let device = DeviceBuilder::default()
+    ///     .with_port_selector(|ports| ports.iter().position(|p| p.matches("0123456789")))
+    ///     .find_port()
+    ///     .await?
+    ///     .build()?;
+    /// ```
+    pub async fn find_port(mut self) -> Result<Self> {
+        if self.mtk_port.is_some() {
+            return Ok(self);
+        }
+
+        let ports = list_mtk_ports().await;
+        if ports.is_empty() {
+            return Err(Error::conn("No MTK port found"));
+        }
+
+        let index = match &self.port_selector {
+            Some(select) => select(&ports).ok_or_else(|| Error::penumbra("No port selected"))?,
+            None => 0,
+        };
+        let info = ports
+            .get(index)
+            .ok_or_else(|| Error::penumbra(format!("Invalid port index {}", index)))?;
+
+        let port = open_mtk_port(info)
+            .await
+            .ok_or_else(|| Error::conn(format!("Can't open port {}", info)))?;
+        self.mtk_port = Some(port);
+        Ok(self)
+    }
+
     /// Assigns the DA data to be used for the device.
     pub fn with_da_data(mut self, data: Vec<u8>) -> Self {
         self.da_data = Some(data);
@@ -70,20 +222,191 @@ impl DeviceBuilder {
         self
     }
 
+    /// Assigns the EMI settings sent to initialize DRAM when booting from BROM, taking
+    /// precedence over the ones in the preloader. They can be taken out of a preloader
+    /// with [`extract_emi`](crate::core::emi::extract_emi), and are checked by [`Self::build`].
+    pub fn with_emi(mut self, data: Vec<u8>) -> Self {
+        self.emi_data = Some(data);
+        self
+    }
+
+    /// Assigns the auth file sent before the DA on devices with Download Agent Auth
+    /// (`target_config & 0x4`). It must start with a GFH header, checked by [`Self::build`].
+    pub fn with_auth_file(mut self, data: Vec<u8>) -> Self {
+        self.auth_file = Some(data);
+        self
+    }
+
+    /// Keeps an exploit from running, like `"carbonara"`. Names are matched ignoring case
+    /// and checked by [`Self::build`]. Has no effect in builds without exploits.
+    pub fn disable_exploit(mut self, name: impl Into<String>) -> Self {
+        self.disabled_exploits.push(name.into());
+        self
+    }
+
+    /// Leaves the SoC watchdog running instead of turning it off after the handshake,
+    /// for chips where its registers are not where [`chipdb`](crate::core::chipdb) expects.
+    pub fn with_keep_watchdog(mut self, keep: bool) -> Self {
+        self.keep_watchdog = keep;
+        self
+    }
+
+    /// Leaves the DA at the USB speed it starts with, instead of having it re-enumerate
+    /// at a higher one when it runs at USB 1.1 speeds.
+    pub fn with_keep_usb_speed(mut self, keep: bool) -> Self {
+        self.keep_usb_speed = keep;
+        self
+    }
+
+    /// Switches UART connections to `baudrate` after the handshake. `None` falls back to
+    /// [`BAUDRATE_ENV`](crate::connection::BAUDRATE_ENV), and then to the rate the port
+    /// opened at.
+    pub fn with_baudrate(mut self, baudrate: Option<u32>) -> Self {
+        self.baudrate = baudrate;
+        self
+    }
+
     /// Enables verbose logging mode.
     pub fn with_verbose(mut self, verbose: bool) -> Self {
         self.verbose = verbose;
         self
     }
 
+    /// Allows writing and erasing partitions even when the partition table
+    /// was read from the secondary GPT. `pgpt` and `sgpt` are writable either way.
+    pub fn with_allow_secondary_gpt(mut self, allow: bool) -> Self {
+        self.allow_secondary_gpt = allow;
+        self
+    }
+
+    /// Allows writing boot region images (preloader) to user area partitions, and vice versa.
+    pub fn with_ignore_region_mismatch(mut self, ignore: bool) -> Self {
+        self.ignore_region_mismatch = ignore;
+        self
+    }
+
+    /// Allows reading and writing partitions that extend past the end of the storage,
+    /// usually because the partition table was made for a bigger storage.
+    pub fn with_allow_out_of_range(mut self, allow: bool) -> Self {
+        self.allow_out_of_range = allow;
+        self
+    }
+
+    /// Reads partitions back after writing them, failing with [`Error::VerifyMismatch`]
+    /// when the data doesn't match. Progress then covers both passes.
+    pub fn with_verify_writes(mut self, verify: bool) -> Self {
+        self.verify_writes = verify;
+        self
+    }
+
+    /// Leaves the head and tail of erases that don't line up with the storage erase groups
+    /// untouched, instead of zero-filling them. Only the whole groups get erased, which is
+    /// faster, but the partition isn't fully wiped.
+    pub fn with_skip_partial_erase(mut self, skip: bool) -> Self {
+        self.skip_partial_erase = skip;
+        self
+    }
+
+    /// Limits the flash transfer rate to the given bytes per second.
+    /// Useful to avoid starving other devices on a shared USB hub.
+    pub fn with_rate_limit(mut self, rate: Option<u64>) -> Self {
+        self.rate_limit = rate;
+        self
+    }
+
+    /// Overrides the transfer chunk sizes. They never exceed what the DA reports, so this
+    /// can only lower them, e.g. for flaky USB hubs.
+    ///
+    /// Setting [`TransferConfig::checksum`] makes [`Device::download`] have the DA verify
+    /// each packet, failing with [`crate::error::XmlErrorKind::ChecksumMismatch`] on
+    /// corrupt data.
+    pub fn with_transfer_config(mut self, transfer: TransferConfig) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
+    /// Sets how many times, and after how long, a data chunk is sent or read again after
+    /// a transient I/O error, like a USB timeout. [`RetryPolicy::none`] disables retrying.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets how long to wait for the DA to start answering before failing, instead of
+    /// hanging if it crashed. `None` waits forever. Defaults to
+    /// [`crate::connection::DEFAULT_RESPONSE_TIMEOUT`].
+    pub fn with_response_timeout(mut self, limit: Option<Duration>) -> Self {
+        self.response_timeout = Some(limit);
+        self
+    }
+
+    /// Assigns a sink receiving [`crate::core::events::CoreEvent`]s, like DA notices.
+    pub fn with_event_sink(mut self, events: EventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Assigns a journal recording write and erase operations, to find out after a crash
+    /// which partition was being written.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Records every transfer with the device to `path`, to be shared when reporting DA
+    /// issues and read with [`crate::connection::trace::dump`]. Only the first
+    /// [`DEFAULT_TRACE_LIMIT`] bytes of each transfer are kept, see
+    /// [`Self::with_trace_limit`]. Nothing changes on the connection when not set.
+    pub fn with_trace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trace = Some(path.into());
+        self
+    }
+
+    /// Sets how many bytes of each transfer [`Self::with_trace`] keeps, `None` keeps them
+    /// whole, partition data included.
+    pub fn with_trace_limit(mut self, limit: Option<usize>) -> Self {
+        self.trace_limit = Some(limit);
+        self
+    }
+
     /// Builds and returns a new `Device` instance.
     pub fn build(self) -> Result<Device> {
-        let connection = self.mtk_port.map(Connection::new);
+        let mtk_port = match (self.mtk_port, &self.trace) {
+            (Some(port), Some(path)) => {
+                let limit = self.trace_limit.unwrap_or(Some(DEFAULT_TRACE_LIMIT));
+                Some(Box::new(TracePort::create(port, path, limit)?) as Box<dyn MTKPort>)
+            }
+            (port, _) => port,
+        };
+
+        let connection = mtk_port.map(|port| {
+            let mut conn = Connection::new(port);
+            conn.set_rate_limit(self.rate_limit);
+            conn.transfer = self.transfer;
+            conn.retry = self.retry;
+            if let Some(limit) = self.response_timeout {
+                conn.response_timeout = limit;
+            }
+            conn
+        });
 
         if connection.is_none() {
             return Err(Error::penumbra("MTK port must be provided to build a Device."));
         }
 
+        if self.auth_file.as_deref().is_some_and(|auth| !has_gfh_header(auth)) {
+            return Err(Error::penumbra("Invalid auth file, it doesn't start with a GFH header."));
+        }
+
+        if let Some(emi) = &self.emi_data {
+            validate_emi(emi)?;
+        }
+
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+        if let Some(name) = self.disabled_exploits.iter().find(|n| find_exploit(n).is_none()) {
+            return Err(Error::penumbra(format!("Unknown exploit '{}'", name)));
+        }
+
         Ok(Device {
             dev_info: DeviceInfo::default(),
             connection,
@@ -91,7 +414,22 @@ impl DeviceBuilder {
             connected: false,
             da_data: self.da_data,
             preloader_data: self.preloader_data,
+            emi_data: self.emi_data,
+            auth_file: self.auth_file,
             verbose: self.verbose,
+            allow_secondary_gpt: self.allow_secondary_gpt,
+            events: self.events,
+            journal: self.journal,
+            ignore_region_mismatch: self.ignore_region_mismatch,
+            verify_writes: self.verify_writes,
+            skip_partial_erase: self.skip_partial_erase,
+            allow_out_of_range: self.allow_out_of_range,
+            disabled_exploits: self.disabled_exploits,
+            keep_watchdog: self.keep_watchdog,
+            keep_usb_speed: self.keep_usb_speed,
+            baudrate: self.baudrate.or_else(env_baudrate),
+            da_reattached: false,
+            partitions_loaded: false,
         })
     }
 }
@@ -120,8 +458,38 @@ pub struct Device {
     da_data: Option<Vec<u8>>,
     /// Preloader data, if provided.
     preloader_data: Option<Vec<u8>>,
+    /// EMI settings, if provided.
+    emi_data: Option<Vec<u8>>,
+    /// Download Agent Auth file, if provided.
+    auth_file: Option<Vec<u8>>,
     /// Whether verbose logging is enabled.
     verbose: bool,
+    /// Whether partition writes are allowed when the GPT is not the primary one.
+    allow_secondary_gpt: bool,
+    /// Sink for events emitted outside of the command flow.
+    events: Option<EventSender>,
+    /// Journal recording write and erase operations.
+    journal: Option<Journal>,
+    /// Whether images whose header doesn't match the target region can be written.
+    ignore_region_mismatch: bool,
+    /// Whether partitions are read back and compared after being written.
+    verify_writes: bool,
+    /// Whether partial erase groups are left untouched when erasing.
+    skip_partial_erase: bool,
+    /// Whether partitions extending past the end of the storage can be accessed.
+    allow_out_of_range: bool,
+    /// Exploits that must not run, by name.
+    disabled_exploits: Vec<String>,
+    /// Whether the watchdog is left running after the handshake.
+    keep_watchdog: bool,
+    /// Whether the DA is left at the USB speed it starts with.
+    keep_usb_speed: bool,
+    /// Baudrate UART connections are switched to after the handshake.
+    baudrate: Option<u32>,
+    /// Whether the DA was already running when connecting, instead of being uploaded by us.
+    da_reattached: bool,
+    /// Whether the partition table was read, so an empty one isn't read again.
+    partitions_loaded: bool,
 }
 
 impl Device {
@@ -129,7 +497,7 @@ impl Device {
     /// This must be called before any other operations.
     ///
     /// # Examples
-    /// ```rust
+    /// ```rust,ignore
     /// use penumbra::{DeviceBuilder, find_mtk_port};
     ///
     /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
@@ -146,11 +514,30 @@ impl Device {
 
         conn.handshake().await?;
 
+        if let Some(baudrate) = self.baudrate
+            && conn.connection_type != ConnectionType::Da
+            && baudrate != conn.baudrate
+        {
+            match conn.switch_baudrate(baudrate).await {
+                Err(Error::Unsupported(msg)) => warn!("Keeping the baudrate: {}", msg),
+                result => result?,
+            }
+        }
+
         let soc_id = conn.get_soc_id().await?;
         let meid = conn.get_meid().await?;
         let hw_code = conn.get_hw_code().await?;
+
+        if !self.keep_watchdog && conn.connection_type != ConnectionType::Da {
+            Self::disable_watchdog(&mut conn, hw_code).await?;
+        }
+
         let target_config = conn.get_target_config().await?;
 
+        if conn.connection_type == ConnectionType::Brom && TargetConfig::from(target_config).sla {
+            Self::brom_sla(&mut conn, &soc_id, &meid).await?;
+        }
+
         let device_info = DevInfoData {
             soc_id,
             meid,
@@ -158,7 +545,10 @@ impl Device {
             chipset: String::from("Unknown"),
             storage: None,
             partitions: vec![],
+            gpt_source: GptSource::default(),
+            gpt_report: None,
             target_config,
+            usb_speed: None,
         };
 
         self.dev_info.set_data(device_info).await;
@@ -174,6 +564,67 @@ impl Device {
         Ok(())
     }
 
+    /// Internal helper turning the watchdog off, so the SoC doesn't reset during a slow
+    /// DA upload. The BootROM refusing the write is only logged, the device may still make
+    /// it in time. Any other failure leaves the echo stream out of sync, so it's returned.
+    async fn disable_watchdog(conn: &mut Connection, hw_code: u16) -> Result<()> {
+        let Some((address, value)) = chipdb::watchdog(hw_code) else {
+            warn!("Unknown watchdog address for HW code 0x{:04X}, leaving it on", hw_code);
+            return Ok(());
+        };
+
+        info!("Disabling watchdog at 0x{:08X}", address);
+        match conn.write32(address, &[value]).await {
+            Err(Error::Status { status, .. }) => {
+                warn!("Failed to disable watchdog, status 0x{:04X}", status);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Internal helper answering the BROM SLA challenge with a registered signer.
+    /// Without one, builds with exploits go on so they can bypass SLA, others stop here.
+    async fn brom_sla(conn: &mut Connection, soc_id: &[u8], hrid: &[u8]) -> Result<()> {
+        info!("BROM SLA is enabled");
+
+        // Signers pick requests by purpose and key, so the challenge is only fetched once one
+        // can answer it: the BootROM would wait for the signature otherwise.
+        let sign_data = SignData {
+            rnd: Vec::new(),
+            soc_id: soc_id.to_vec(),
+            hrid: hrid.to_vec(),
+            raw: Vec::new(),
+        };
+        let mut sign_req =
+            SignRequest { data: sign_data, purpose: SignPurpose::BromSla, pubk_mod: Vec::new() };
+
+        let auth = AuthManager::get();
+        if !auth.can_sign(&sign_req) {
+            #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+            {
+                warn!("No signer available for BROM SLA, relying on an exploit to bypass it");
+                return Ok(());
+            }
+            #[cfg(not(all(feature = "payloads", not(feature = "no_exploits"))))]
+            return Err(Error::penumbra(
+                "BROM SLA is enabled, but no signer is available. \
+                 Register a signer for this device's SLA key with AuthManager::register_signer.",
+            ));
+        }
+
+        let Some(rnd) = conn.get_sla_challenge().await? else {
+            return Ok(());
+        };
+        sign_req.data.rnd = rnd;
+
+        let signature = auth.sign(&sign_req).await?;
+        conn.send_sla_signature(&signature).await?;
+        info!("BROM SLA signature accepted!");
+
+        Ok(())
+    }
+
     /// Reinits the device connection based on the current connection type and optional DA info.
     /// This is useful for CLIs or scenarios where the Device instance needs to be reset.
     pub async fn reinit(&mut self, dev_info: DevInfoData) -> Result<()> {
@@ -199,6 +650,7 @@ impl Device {
             }
             ConnectionType::Da => {
                 self.protocol = Some(self.init_da_protocol(conn).await?);
+                self.da_reattached = true;
             }
         };
 
@@ -212,7 +664,7 @@ impl Device {
     /// After entering DA mode, the device's partition information is read and stored in `dev_info`.
     ///
     /// # Examples
-    /// ```rust
+    /// ```rust,ignore
     /// use penumbra::{DeviceBuilder, find_mtk_port};
     ///
     /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
@@ -238,14 +690,64 @@ impl Device {
             self.protocol = Some(protocol);
         }
 
+        if conn_type != ConnectionType::Da && self.dev_info.daa_enabled().await {
+            self.send_auth_file().await?;
+        }
+
         let protocol = self.protocol.as_mut().unwrap();
         if conn_type != ConnectionType::Da {
             protocol.upload_da().await?;
             self.set_connection_type(ConnectionType::Da)?;
+            self.da_reattached = false;
+
+            if !self.keep_usb_speed {
+                self.switch_usb_speed().await?;
+            }
         }
 
         // Fallback to ensure we always have the partitions available.
-        self.get_partitions().await;
+        if let Err(e) = self.get_partitions().await {
+            warn!("Failed to read the partition table: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Internal helper moving a DA running at USB 1.1 speeds to a higher one, recording the
+    /// speed it ends up at in `dev_info`. DAs that can't switch, or come back just as slow,
+    /// keep going at their current speed.
+    async fn switch_usb_speed(&mut self) -> Result<()> {
+        let protocol = self.protocol.as_mut().unwrap();
+        if protocol.switch_usb_speed().await? {
+            info!("Reconnected to the DA after switching USB speed");
+        }
+
+        let speed = protocol.get_usb_speed().await.ok();
+        if let Some(speed) = speed.filter(|s| s.is_slow()) {
+            warn!("DA is running at {}, transfers will be slow", speed.as_str());
+        }
+        self.dev_info.set_usb_speed(speed).await;
+        Ok(())
+    }
+
+    /// Internal helper sending the auth file before the DA on devices with DAA.
+    /// Without one, builds with exploits go on so they can bypass DAA, others stop here.
+    async fn send_auth_file(&mut self) -> Result<()> {
+        info!("Download Agent Auth is enabled");
+        let Some(auth) = self.auth_file.clone() else {
+            #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+            {
+                warn!("No auth file provided, relying on an exploit to bypass DAA");
+                return Ok(());
+            }
+            #[cfg(not(all(feature = "payloads", not(feature = "no_exploits"))))]
+            return Err(Error::penumbra(
+                "This device needs an auth file to load the DA (SEC auth file needed). \
+                 Provide one with DeviceBuilder::with_auth_file.",
+            ));
+        };
+
+        self.get_connection()?.send_auth(&auth).await?;
+        info!("Auth file accepted!");
         Ok(())
     }
 
@@ -267,6 +769,116 @@ impl Device {
         Ok(self.get_protocol().unwrap())
     }
 
+    /// Internal helper refusing partition writes when the partition table doesn't come from
+    /// the primary GPT (or the PMT), since addresses from a stale backup table might be wrong.
+    /// The GPTs themselves are always writable, restoring them is how the table gets fixed.
+    async fn ensure_gpt_writable(&mut self, partition: &str) -> Result<()> {
+        let source = self.dev_info.gpt_source().await;
+        if source.is_trusted() || self.allow_secondary_gpt || is_gpt_part(partition) {
+            return Ok(());
+        }
+
+        Err(Error::penumbra(format!(
+            "Partition table was not read from the primary GPT (source: {}), refusing to write \
+             '{}'. Repair the primary GPT from the secondary one, or explicitly allow writing.",
+            source.as_str(),
+            partition
+        )))
+    }
+
+    /// Internal helper refusing partitions that extend past the end of the storage, where
+    /// some DAs fail late and others wrap around to the start.
+    async fn ensure_in_range(&mut self, part: &Partition) -> Result<()> {
+        if self.allow_out_of_range {
+            return Ok(());
+        }
+
+        let Some(storage) = self.dev_info.storage().await else {
+            return Ok(());
+        };
+        if part.fits(storage.as_ref()) {
+            return Ok(());
+        }
+
+        Err(Error::penumbra(format!(
+            "Partition '{}' (0x{:X}+0x{:X}) extends past the end of {}. \
+             The partition table was likely made for a bigger storage. \
+             Pass --force to access it anyway.",
+            part.name,
+            part.address,
+            part.size,
+            part.kind.as_str()
+        )))
+    }
+
+    /// Internal helper returning the storage, detecting it through the DA if it isn't
+    /// cached yet. Must be called in DA mode.
+    async fn detected_storage(&mut self) -> Result<Arc<dyn Storage>> {
+        match self.dev_info.storage().await {
+            Some(storage) => Ok(storage as Arc<dyn Storage>),
+            None => self
+                .get_protocol()
+                .unwrap()
+                .get_storage()
+                .await
+                .ok_or_else(|| Error::penumbra("Failed to detect storage")),
+        }
+    }
+
+    /// Internal helper refusing operations that need the DA extensions when they're not active.
+    /// When reattaching to a running DA the state is unknown, and the operation is attempted.
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    fn ensure_extensions(&self) -> Result<()> {
+        match self.extensions_state() {
+            ExtState::Active | ExtState::Unknown => Ok(()),
+            state => Err(Error::ExtensionsUnavailable(state.to_string())),
+        }
+    }
+
+    /// Internal helper refusing to write a boot region image (preloader) to a user area
+    /// partition, or the reverse, based on the image header.
+    fn ensure_region_matches(&self, name: &str, kind: PartitionKind, header: &[u8]) -> Result<()> {
+        let image = ImageKind::detect(header);
+        if self.ignore_region_mismatch || image.fits(kind) {
+            return Ok(());
+        }
+
+        let target = if kind.is_boot_region() { "boot region" } else { "user area" };
+        Err(Error::penumbra(format!(
+            "Image looks like a {} image, but partition '{}' is in the {} ({}). \
+             Writing it would likely leave the device unbootable. \
+             Pass --ignore-region-mismatch to write it anyway.",
+            image.as_str(),
+            name,
+            target,
+            kind.as_str()
+        )))
+    }
+
+    /// Internal helper recording the start of a write or erase in the journal, if any.
+    /// Journal failures are logged, but never block the operation itself.
+    async fn journal_begin(&mut self, partition: &str, size: u64) -> Option<u64> {
+        let device = hex::encode_upper(self.dev_info.meid().await);
+        let journal = self.journal.as_ref()?;
+
+        journal
+            .begin(&device, partition, size)
+            .inspect_err(|e| warn!("Failed to write journal entry: {}", e))
+            .ok()
+    }
+
+    /// Internal helper recording the outcome of an operation started with `journal_begin`.
+    fn journal_finish<T>(&self, id: Option<u64>, result: &Result<T>, hash: Option<&[u8]>) {
+        let (Some(journal), Some(id)) = (&self.journal, id) else {
+            return;
+        };
+
+        let state = if result.is_ok() { JournalState::Completed } else { JournalState::Failed };
+        if let Err(e) = journal.finish(id, state, hash) {
+            warn!("Failed to write journal entry: {}", e);
+        }
+    }
+
     async fn init_da_protocol(&mut self, conn: Connection) -> Result<Box<dyn DAProtocol + Send>> {
         let da_bytes = self.da_data.clone().ok_or_else(|| {
             Error::conn("DA protocol is not initialized and no DA file was provided.")
@@ -274,23 +886,44 @@ impl Device {
 
         let da_file = DAFile::parse_da(&da_bytes)?;
         let hw_code = self.dev_info.hw_code().await;
-        let da = da_file.get_da_from_hw_code(hw_code).ok_or_else(|| {
-            Error::penumbra(format!("No compatible DA for hardware code 0x{:04X}", hw_code))
-        })?;
+        let da = da_file.select_for(hw_code)?;
 
         let protocol: Box<dyn DAProtocol + Send> = match da.da_type {
-            DAType::V5 => Box::new(XFlash::new(
-                conn,
-                da,
-                self.dev_info.clone(),
-                self.preloader_data.clone(),
-                self.verbose,
-            )),
-            DAType::V6 => Box::new(Xml::new(conn, da, self.dev_info.clone(), self.verbose)),
-            _ => return Err(Error::penumbra("Unsupported DA type")),
+            DAType::V5 => {
+                let mut xflash = XFlash::new(
+                    conn,
+                    da,
+                    self.dev_info.clone(),
+                    self.preloader_data.clone(),
+                    self.verbose,
+                );
+                xflash.emi = self.emi_data.clone();
+                #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+                {
+                    xflash.disabled_exploits = self.disabled_exploits.clone();
+                }
+                Box::new(xflash)
+            }
+            DAType::V6 => {
+                if self.emi_data.is_some() {
+                    warn!("EMI settings are only sent with V5 DAs, ignoring them");
+                }
+                let mut xml = Xml::new(conn, da, self.dev_info.clone(), self.verbose);
+                xml.events = self.events.clone();
+                #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+                {
+                    xml.disabled_exploits = self.disabled_exploits.clone();
+                }
+                Box::new(xml)
+            }
+            DAType::Legacy => {
+                if self.emi_data.is_some() {
+                    warn!("EMI settings are only sent with V5 DAs, ignoring them");
+                }
+                Box::new(Legacy::new(conn, da, self.dev_info.clone()))
+            }
         };
 
-        self.get_partitions().await;
         Ok(protocol)
     }
 
@@ -319,139 +952,922 @@ impl Device {
         self.protocol.as_deref_mut()
     }
 
-    pub async fn get_partitions(&mut self) -> Vec<Partition> {
-        let cached = self.dev_info.partitions().await;
-        if !cached.is_empty() {
-            return cached;
-        }
+    /// Limits the flash transfer rate to the given bytes per second, or removes the limit.
+    pub fn set_rate_limit(&mut self, rate: Option<u64>) -> Result<()> {
+        self.get_connection()?.set_rate_limit(rate);
+        Ok(())
+    }
 
-        let protocol = match self.get_protocol() {
-            Some(p) => p,
-            None => return Vec::new(),
-        };
+    /// Returns the current flash transfer rate limit, in bytes per second.
+    pub fn rate_limit(&mut self) -> Option<u64> {
+        self.get_connection().ok()?.rate_limiter.as_ref().map(|l| l.rate())
+    }
 
-        info!("Retrieving partition information...");
-        let partitions = protocol.get_partitions().await;
+    /// Overrides the transfer chunk sizes, see [`DeviceBuilder::with_transfer_config`].
+    pub fn set_transfer_config(&mut self, transfer: TransferConfig) -> Result<()> {
+        self.get_connection()?.transfer = transfer;
+        Ok(())
+    }
 
-        self.dev_info.set_partitions(partitions.clone()).await;
+    /// Returns the current overrides of the transfer chunk sizes and checksums.
+    pub fn transfer_config(&mut self) -> Option<TransferConfig> {
+        Some(self.get_connection().ok()?.transfer)
+    }
 
-        partitions
+    /// Sets how data chunks are retried after transient I/O errors, see
+    /// [`DeviceBuilder::with_retry`].
+    pub fn set_retry(&mut self, retry: RetryPolicy) -> Result<()> {
+        self.get_connection()?.retry = retry;
+        Ok(())
     }
 
-    /// Reads data from a specified partition on the device.
-    /// This function assumes the partition to be part of the user section.
-    /// To read from other sections, use `read_offset` with appropriate address.
-    pub async fn read_partition(
-        &mut self,
-        name: &str,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-        writer: &mut (dyn AsyncWrite + Unpin + Send),
-    ) -> Result<()> {
-        self.ensure_da_mode().await?;
+    /// Sets how long to wait for the DA to answer, see [`DeviceBuilder::with_response_timeout`].
+    pub fn set_response_timeout(&mut self, limit: Option<Duration>) -> Result<()> {
+        self.get_connection()?.response_timeout = limit;
+        Ok(())
+    }
 
-        let part = self
-            .dev_info
-            .get_partition(name)
-            .await
-            .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found", name)))?;
+    /// Returns the tracker timing the erase, write and verify phases of flash operations.
+    /// It's shared with the connection, so it can be kept to follow operations in progress.
+    pub fn phase_tracker(&mut self) -> Option<PhaseTracker> {
+        Some(self.get_connection().ok()?.phases.clone())
+    }
 
-        let protocol = self.protocol.as_mut().unwrap();
-        protocol.read_flash(part.address, part.size, part.kind, progress, writer).await
+    /// Returns the counters of the data transfers, see [`ConnStats`].
+    /// It's shared with the connection, so it can be kept to follow operations in progress.
+    pub fn conn_stats(&mut self) -> Option<ConnStats> {
+        Some(self.get_connection().ok()?.stats().clone())
     }
 
-    /// Writes data to a specified partition on the device.
-    /// This function assumes the partition to be part of the user section.
-    /// To write to other sections, use `write_offset` with appropriate address.
-    pub async fn write_partition(
-        &mut self,
-        name: &str,
-        reader: &mut (dyn AsyncRead + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-    ) -> Result<()> {
-        self.ensure_da_mode().await?;
+    /// Returns the token cancelling the running flash operation.
+    /// It's shared with the connection, so it can be kept to cancel operations in progress.
+    pub fn cancel_handle(&mut self) -> Option<CancelToken> {
+        Some(self.get_connection().ok()?.cancel.clone())
+    }
 
-        let part = self
-            .dev_info
-            .get_partition(name)
-            .await
-            .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found", name)))?;
+    /// Stops the running flash operation at the next chunk, which then fails with
+    /// [`Error::cancelled`]. Operations made of several transfers stop as a whole.
+    /// The token stays set until reset, see [`CancelToken`].
+    pub fn cancel_current(&mut self) {
+        if let Some(cancel) = self.cancel_handle() {
+            cancel.cancel();
+        }
+    }
 
-        let protocol = self.protocol.as_mut().unwrap();
-        protocol.write_flash(part.address, part.size, reader, part.kind, progress).await
+    /// Returns where the current partition table was read from.
+    pub async fn gpt_source(&mut self) -> GptSource {
+        self.dev_info.gpt_source().await
     }
 
-    pub async fn erase_partition(
-        &mut self,
-        partition: &str,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-    ) -> Result<()> {
-        self.ensure_da_mode().await?;
+    /// Returns whether the DA extensions are active, which some operations (peek, seccfg)
+    /// depend on. Always `Disabled` before entering DA mode.
+    pub fn extensions_state(&self) -> ExtState {
+        match self.protocol.as_ref().map(|p| p.extensions_state()) {
+            Some(ExtState::Disabled) if self.da_reattached => ExtState::Unknown,
+            Some(state) => state,
+            None => ExtState::Disabled,
+        }
+    }
 
-        let part = self
-            .dev_info
-            .get_partition(partition)
-            .await
-            .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found", partition)))?;
+    /// Speed and hubs of the USB link to the device, when the backend knows them.
+    pub fn usb_link(&self) -> Option<UsbLink> {
+        self.connection.as_ref().and_then(|c| c.port.get_usb_link())
+    }
 
-        let protocol = self.protocol.as_mut().unwrap();
-        protocol.erase_flash(part.address, part.size, part.kind, progress).await
+    /// Where the port of the device is plugged in, like `bus 3-2`, when the backend knows it.
+    pub fn port_location(&self) -> Option<String> {
+        self.connection.as_ref().and_then(|c| c.port.get_location())
     }
 
-    /// Reads data from a specified offset and size on the device.
-    /// This allows reading from arbitrary locations, not limited to named partitions.
-    /// To specify the section (e.g., user, pl_part1, pl_part2), provide the appropriate
-    /// `PartitionKind`.
-    ///
-    /// # Examples
-    /// ```rust
-    /// // Let's assume we want to read preloader
-    /// use penumbra::{DeviceBuilder, PartitionKind, find_mtk_port};
-    ///
-    /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
-    /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
-    ///
-    /// device.init().await?;
-    ///
-    /// let mut progress = |_read: usize, _total: usize| {};
-    /// let preloader_data = device
-    ///     .read_offset(0x0, 0x40000, PartitionKind::Emmc(EmmcPartition::Boot1), &mut progress)
-    ///     .await?;
-    /// ```
-    pub async fn read_offset(
-        &mut self,
-        address: u64,
-        size: usize,
-        section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
-        writer: &mut (dyn AsyncWrite + Unpin + Send),
-    ) -> Result<()> {
-        self.ensure_da_mode().await?;
+    /// Internal helper warning before moving `bytes` over a USB 1.1 link, where it
+    /// takes ages, with an estimate of how long.
+    fn warn_if_slow_link(&self, bytes: u64) {
+        let Some(link) = self.usb_link().filter(|l| l.is_slow()) else {
+            return;
+        };
+        let Some(speed) = link.speed.filter(|_| bytes >= SLOW_LINK_WARN_SIZE) else {
+            return;
+        };
 
-        let protocol = self.protocol.as_mut().unwrap();
-        protocol.read_flash(address, size, section, progress, writer).await
+        let minutes = bytes / speed.throughput() / 60;
+        warn!(
+            "Connected over {}, moving 0x{:X} bytes will take about {} minutes. \
+             Plug the device into a USB 2.0 port of the computer for faster transfers.",
+            link, bytes, minutes
+        );
     }
 
-    /// Writes data to a specified offset and size on the device.
-    /// This allows writing to arbitrary locations, not limited to named partitions.
-    /// To specify the section (e.g., user, pl_part1, pl_part2), provide the appropriate
-    /// `PartitionKind`.
-    ///
-    /// # Examples
-    /// ```rust
-    /// // Let's assume we want to write to preloader
-    /// use penumbra::{DeviceBuilder, PartitionKind, find_mtk_port};
-    ///
+    /// Exploits turned off with [`DeviceBuilder::disable_exploit`].
+    pub fn disabled_exploits(&self) -> &[String] {
+        &self.disabled_exploits
+    }
+
+    /// Allows or forbids writing images to a storage region they weren't made for,
+    /// see [`DeviceBuilder::with_ignore_region_mismatch`].
+    pub fn set_ignore_region_mismatch(&mut self, ignore: bool) {
+        self.ignore_region_mismatch = ignore;
+    }
+
+    /// Turns reading partitions back after writing them on or off,
+    /// see [`DeviceBuilder::with_verify_writes`].
+    pub fn set_verify_writes(&mut self, verify: bool) {
+        self.verify_writes = verify;
+    }
+
+    /// Whether writes are read back and verified, doubling the bytes reported as progress.
+    pub fn verify_writes(&self) -> bool {
+        self.verify_writes
+    }
+
+    /// Turns skipping partial erase groups on or off,
+    /// see [`DeviceBuilder::with_skip_partial_erase`].
+    pub fn set_skip_partial_erase(&mut self, skip: bool) {
+        self.skip_partial_erase = skip;
+    }
+
+    /// Buffer size for readers feeding write operations. Matches the DA write chunks when
+    /// they are known, so large images don't go through a read syscall per few KBs.
+    pub fn write_buffer_size(&mut self) -> usize {
+        self.get_protocol().and_then(|p| p.write_packet_length()).unwrap_or(1024 * 1024)
+    }
+
+    /// Allows or forbids partition writes when the partition table
+    /// doesn't come from the primary GPT.
+    pub fn set_allow_secondary_gpt(&mut self, allow: bool) {
+        self.allow_secondary_gpt = allow;
+    }
+
+    /// Allows or forbids accessing partitions that extend past the end of the storage,
+    /// see [`DeviceBuilder::with_allow_out_of_range`].
+    pub fn set_allow_out_of_range(&mut self, allow: bool) {
+        self.allow_out_of_range = allow;
+    }
+
+    /// Returns the partitions extending past the end of the storage, which are refused
+    /// unless explicitly allowed.
+    pub async fn out_of_range_partitions(&mut self) -> Vec<Partition> {
+        self.dev_info.out_of_range_partitions().await
+    }
+
+    /// Returns the partition table, read from the device on first use and cached after that.
+    /// Fails when it can't be read, e.g. because the storage wasn't detected.
+    pub async fn get_partitions(&mut self) -> Result<Vec<Partition>> {
+        let cached = self.dev_info.partitions().await;
+        if self.partitions_loaded || !cached.is_empty() {
+            return Ok(cached);
+        }
+
+        let protocol = self.get_protocol().ok_or_else(|| {
+            Error::conn("DA protocol is not initialized, partitions can't be read.")
+        })?;
+
+        info!("Retrieving partition information...");
+        let partitions = protocol.get_partitions().await?;
+
+        self.dev_info.set_partitions(partitions.clone()).await;
+        self.partitions_loaded = true;
+
+        Ok(partitions)
+    }
+
+    /// Drops the cached partition table and reads it again from the device, e.g. after
+    /// writing the GPT or formatting the storage.
+    pub async fn refresh_partitions(&mut self) -> Result<Vec<Partition>> {
+        self.partitions_loaded = false;
+        self.dev_info.set_partitions(Vec::new()).await;
+        self.get_partitions().await
+    }
+
+    /// Reads the partition table back after changing it. The change itself went through,
+    /// so a failure only leaves the table unknown until the next refresh.
+    async fn reload_partitions(&mut self) {
+        if let Err(e) = self.refresh_partitions().await {
+            warn!("Failed to read the partition table back: {}", e);
+        }
+    }
+
+    /// Drops the cached storage and partition table, and reads them again from the device.
+    /// Useful after the layout was changed by another tool, or to pick up a GPT written
+    /// outside of [`Device::write_gpt`].
+    pub async fn refresh_info(&mut self) -> Result<()> {
+        self.ensure_da_mode().await?;
+
+        info!("Refreshing device information...");
+        self.dev_info.clear_storage().await;
+
+        self.detected_storage().await?;
+        self.refresh_partitions().await?;
+
+        Ok(())
+    }
+
+    /// Returns how the primary and secondary GPT disagreed when the partition table was read,
+    /// if both are valid but differ.
+    pub async fn gpt_mismatch(&mut self) -> Option<GptDiff> {
+        self.dev_info.gpt_diff().await
+    }
+
+    /// Returns whether the primary and secondary GPT were valid and agreed when the partition
+    /// table was read. `None` if the device has no GPT.
+    pub async fn gpt_report(&mut self) -> Option<GptReport> {
+        self.dev_info.gpt_report().await
+    }
+
+    /// Reads both GPTs from the device and compares them.
+    /// Fails if either of them can't be parsed.
+    pub async fn gpt_diff(&mut self) -> Result<GptDiff> {
+        let primary = self.read_gpt(GptSource::Primary).await?;
+        let secondary = self.read_gpt(GptSource::Secondary).await?;
+        Ok(Gpt::compare(&primary, &secondary))
+    }
+
+    /// Reads both GPTs from the device, and reports whether each of them is valid and how
+    /// they differ. Unlike [`Device::gpt_diff`], a table that can't be parsed is reported
+    /// instead of failing. The cached report (see [`Device::gpt_report`]) is updated.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use penumbra::{DeviceBuilder, find_mtk_port};
+    ///
+    /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
+    /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
+    ///
+    /// device.init().await?;
+    /// let report = device.check_gpt().await?;
+    /// if let Some(from) = report.repair_source() {
+    ///     device.repair_gpt(from).await?;
+    /// }
+    /// ```
+    pub async fn check_gpt(&mut self) -> Result<GptReport> {
+        let storage_type = self.ensure_da_mode().await?.get_storage_type().await;
+
+        let mut tables = Vec::new();
+        for source in [GptSource::Primary, GptSource::Secondary] {
+            let data = self.read_gpt_data(source).await?;
+            let gpt = Gpt::parse(&data, storage_type)
+                .inspect_err(|e| warn!("{} is invalid: {}", source.as_str(), e))
+                .ok();
+            tables.push(gpt);
+        }
+
+        let report = GptReport::new(tables[0].as_ref(), tables[1].as_ref());
+        self.dev_info.set_gpt_report(Some(report.clone())).await;
+        Ok(report)
+    }
+
+    /// Overwrites one GPT with the other, e.g. `GptSource::Primary` copies the primary GPT
+    /// over the secondary one. The header is adjusted for its new location, the partition
+    /// entries are copied as is. The partition table is re-read afterwards.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use penumbra::core::storage::GptSource;
+    /// use penumbra::{DeviceBuilder, find_mtk_port};
+    ///
+    /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
+    /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
+    ///
+    /// device.init().await?;
+    /// if device.gpt_mismatch().await.is_some() {
+    ///     device.repair_gpt(GptSource::Primary).await?;
+    /// }
+    /// ```
+    pub async fn repair_gpt(&mut self, from: GptSource) -> Result<()> {
+        let target = match from {
+            GptSource::Primary => GptSource::Secondary,
+            GptSource::Secondary => GptSource::Primary,
+            GptSource::Pmt | GptSource::Synthetic => {
+                return Err(Error::penumbra("Can only repair from the primary or secondary GPT"));
+            }
+        };
+
+        let gpt = self.read_gpt(from).await?;
+        let (offset, data) = gpt.relocate(target)?;
+        let section = self.partition(target.as_str()).await?.kind;
+
+        info!("Copying {} over {}...", from.as_str(), target.as_str());
+        let id = self.journal_begin(target.as_str(), data.len() as u64).await;
+        let mut progress = |_: &Progress| {};
+        let result = self
+            .write_offset(offset, data.len() as u64, &mut data.as_slice(), section, &mut progress)
+            .await;
+        self.journal_finish(id, &result, None);
+        result?;
+
+        self.reload_partitions().await;
+
+        Ok(())
+    }
+
+    /// Writes a GPT to the device, both as the primary and the secondary table, e.g. after
+    /// resizing a partition or to restore a wiped table from a dump.
+    /// The table must match the storage sector size and user area size.
+    /// The partition table is re-read afterwards.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use penumbra::core::storage::{Gpt, StorageType};
+    /// use penumbra::{DeviceBuilder, find_mtk_port};
+    ///
+    /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
+    /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
+    ///
+    /// device.init().await?;
+    /// let mut gpt = Gpt::parse(&std::fs::read("pgpt.bin")?, StorageType::Emmc)?;
+    /// gpt.resize_partition("userdata", 0x2000_0000)?;
+    /// device.write_gpt(&gpt).await?;
+    /// ```
+    pub async fn write_gpt(&mut self, gpt: &Gpt) -> Result<()> {
+        self.ensure_da_mode().await?;
+
+        let storage = self.detected_storage().await?;
+
+        let sector = storage.block_size() as u64;
+        if gpt.sector_size() as u64 != sector {
+            return Err(Error::penumbra(format!(
+                "GPT sector size ({}) doesn't match the storage block size ({})",
+                gpt.sector_size(),
+                sector
+            )));
+        }
+
+        let last_lba = (storage.get_user_size() / sector).saturating_sub(1);
+        if gpt.last_lba() != last_lba {
+            return Err(Error::penumbra(format!(
+                "GPT was made for a user area ending at LBA {}, but the device ends at LBA {}",
+                gpt.last_lba(),
+                last_lba
+            )));
+        }
+
+        let images = gpt.serialize()?;
+        let section = storage.get_user_part();
+
+        for (name, offset, data) in [
+            ("PGPT", 0, &images.primary),
+            ("SGPT", images.secondary_offset, &images.secondary),
+        ] {
+            info!("Writing {}...", name);
+            let id = self.journal_begin(name, data.len() as u64).await;
+            let size = data.len() as u64;
+            let mut progress = |_: &Progress| {};
+            let result =
+                self.write_offset(offset, size, &mut data.as_slice(), section, &mut progress).await;
+            self.journal_finish(id, &result, None);
+            result?;
+        }
+
+        self.reload_partitions().await;
+
+        Ok(())
+    }
+
+    /// Internal helper reading and parsing the primary or secondary GPT.
+    async fn read_gpt(&mut self, source: GptSource) -> Result<Gpt> {
+        let data = self.read_gpt_data(source).await?;
+
+        let storage_type = self.protocol.as_mut().unwrap().get_storage_type().await;
+        Gpt::parse(&data, storage_type)
+    }
+
+    /// Internal helper reading the raw primary or secondary GPT.
+    async fn read_gpt_data(&mut self, source: GptSource) -> Result<Vec<u8>> {
+        self.ensure_da_mode().await?;
+
+        let part = self.partition(source.as_str()).await?;
+        let mut data = Vec::new();
+        let mut progress = |_: &Progress| {};
+        self.read_offset(part.address, part.size, part.kind, &mut progress, &mut data).await?;
+
+        Ok(data)
+    }
+
+    /// Returns the parsed DA file the device was built with, if any.
+    pub fn da_file(&self) -> Option<DAFile> {
+        self.da_data.as_deref().and_then(|data| DAFile::parse_da(data).ok())
+    }
+
+    /// Returns the physical regions of the detected storage, along with their size and
+    /// whether they can be written. See [`PartitionKind`] for the meaning of each region
+    /// on eMMC and UFS.
+    ///
+    /// Returns an empty list if the storage could not be detected (e.g. not in DA mode yet).
+    pub async fn regions(&mut self) -> Vec<RegionInfo> {
+        if let Some(storage) = self.dev_info.storage().await {
+            return storage.regions();
+        }
+
+        match self.get_protocol() {
+            Some(protocol) => protocol.get_storage().await.map_or(Vec::new(), |s| s.regions()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Resolves a partition by name through the cached partition table.
+    pub async fn partition(&mut self, name: &str) -> Result<Partition> {
+        self.dev_info
+            .get_partition(name)
+            .await
+            .ok_or_else(|| Error::penumbra(format!("Partition '{}' not found", name)))
+    }
+
+    /// Internal helper refusing a partition that no longer matches the device table,
+    /// e.g. one resolved before the table was re-read or a GPT change.
+    async fn ensure_partition_current(&mut self, part: &Partition) -> Result<()> {
+        match self.dev_info.get_partition(&part.name).await {
+            Some(current) if current == *part => Ok(()),
+            Some(_) => Err(Error::penumbra(format!(
+                "Partition '{}' doesn't match the device partition table anymore",
+                part.name
+            ))),
+            None => Err(Error::penumbra(format!("Partition '{}' not found", part.name))),
+        }
+    }
+
+    /// Reads data from a specified partition on the device.
+    /// The name is resolved once through the cached partition table, see
+    /// [`Device::read_resolved_partition`].
+    /// To read arbitrary ranges, use `read_offset` with appropriate address.
+    pub async fn read_partition(
+        &mut self,
+        name: &str,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        let part = self.partition(name).await?;
+        self.read_resolved_partition(&part, writer, progress).await
+    }
+
+    /// Reads a whole partition, using the address and size it was resolved with.
+    /// Use [`Device::partition`] to resolve a partition by name. Partitions that no longer
+    /// match the device table, e.g. after it was read again, are refused.
+    pub async fn read_resolved_partition(
+        &mut self,
+        part: &Partition,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        self.ensure_partition_current(part).await?;
+        self.ensure_in_range(part).await?;
+        self.warn_if_slow_link(part.size);
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol.read_flash(part.address, part.size, part.kind, progress, writer).await
+    }
+
+    /// Writes data to a specified partition on the device, filling the whole partition.
+    /// The name is resolved once through the cached partition table, see
+    /// [`Device::write_resolved_partition`] to write fewer bytes.
+    /// To write arbitrary ranges, use `write_offset` with appropriate address.
+    pub async fn write_partition(
+        &mut self,
+        name: &str,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        let part = self.partition(name).await?;
+        self.write_resolved_partition(&part, reader, part.size, progress).await
+    }
+
+    /// Writes `len` bytes from `reader` to the start of a partition.
+    /// Android sparse images are expanded on the fly, ignoring `len`.
+    /// Use [`Device::partition`] to resolve a partition by name. Partitions that no longer
+    /// match the device table, e.g. after it was read again, are refused.
+    pub async fn write_resolved_partition(
+        &mut self,
+        part: &Partition,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        len: u64,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        self.ensure_gpt_writable(&part.name).await?;
+        self.ensure_partition_current(part).await?;
+        self.ensure_in_range(part).await?;
+
+        let header = read_header(reader).await?;
+        let sparse = SparseHeader::parse(&header);
+        let len = sparse.map_or(len, |s| s.expanded_size());
+
+        if len > part.size {
+            return Err(Error::penumbra(format!(
+                "Image is larger than partition '{}' (0x{:X} > 0x{:X} bytes)",
+                part.name, len, part.size
+            )));
+        }
+
+        self.ensure_region_matches(&part.name, part.kind, &header)?;
+        let mut reader = (&header[..]).chain(reader);
+        let mut sparse_reader;
+        let reader: &mut (dyn AsyncRead + Unpin + Send) = match sparse {
+            Some(sparse) => {
+                info!("Expanding sparse image to 0x{:X} bytes", len);
+                sparse_reader = SparseReader::new(&mut reader, sparse);
+                &mut sparse_reader
+            }
+            None => &mut reader,
+        };
+
+        self.warn_if_slow_link(len);
+        let id = self.journal_begin(&part.name, len).await;
+        let mut reader = HashingReader::new(reader);
+
+        let passes = if self.verify_writes { 2 } else { 1 };
+        let mut progress = self.track(progress);
+        let mut write_progress = |p: &Progress| progress.update(p.written, p.total * passes);
+
+        let protocol = self.protocol.as_mut().unwrap();
+        let result = protocol
+            .write_flash(part.address, len, &mut reader, part.kind, &mut write_progress)
+            .await;
+
+        let digest = reader.digest();
+        self.journal_finish(id, &result, Some(&digest));
+        result?;
+
+        if self.verify_writes {
+            self.verify_written(part, len, digest, &mut progress).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Erases a partition, see [`Device::erase_aligned`] for how partial erase groups
+    /// are handled.
+    pub async fn erase_partition(
+        &mut self,
+        partition: &str,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<EraseReport> {
+        self.ensure_da_mode().await?;
+        self.ensure_gpt_writable(partition).await?;
+
+        let part = self.partition(partition).await?;
+        self.ensure_in_range(&part).await?;
+        let id = self.journal_begin(partition, part.size).await;
+
+        let result = self.erase_aligned(part.address, part.size, part.kind, progress).await;
+
+        self.journal_finish(id, &result, None);
+        result
+    }
+
+    /// Patches a few bytes of a partition in place, without rewriting the whole partition.
+    ///
+    /// Only the storage blocks containing `[offset, offset + bytes.len())` are read back,
+    /// patched in memory and written again. If `verify` is set, the blocks are read once
+    /// more to make sure the change landed.
+    ///
+    /// Returns the bytes that were replaced.
+    pub async fn patch_partition(
+        &mut self,
+        name: &str,
+        offset: u64,
+        bytes: &[u8],
+        verify: bool,
+    ) -> Result<Vec<u8>> {
+        self.ensure_da_mode().await?;
+        self.ensure_gpt_writable(name).await?;
+
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (part, window) = self.block_window(name, offset, bytes.len() as u64).await?;
+        let address = window.address();
+        let len = window.size() as usize;
+        let patch_start = window.skip() as usize;
+        let patch_range = patch_start..patch_start + bytes.len();
+
+        let mut data = self.read_blocks(address, len, part.kind).await?;
+        let original = data[patch_range.clone()].to_vec();
+        data[patch_range].copy_from_slice(bytes);
+
+        let id = self.journal_begin(name, len as u64).await;
+        let result =
+            self.write_offset(address, len as u64, &mut &data[..], part.kind, &mut |_| {}).await;
+        self.journal_finish(id, &result, Some(&sha256(&data)));
+        result?;
+
+        if verify && self.read_blocks(address, len, part.kind).await? != data {
+            return Err(Error::penumbra(format!(
+                "Verification of patch on partition '{}' failed",
+                name
+            )));
+        }
+
+        Ok(original)
+    }
+
+    /// Reads `size` bytes at `offset` inside a partition into memory, taking care of block
+    /// alignment. Use [`Device::upload_range`] to stream larger ranges.
+    pub async fn read_partition_range(
+        &mut self,
+        name: &str,
+        offset: u64,
+        size: u64,
+    ) -> Result<Vec<u8>> {
+        self.ensure_da_mode().await?;
+
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let (part, window) = self.block_window(name, offset, size).await?;
+        let len = usize::try_from(window.size()).map_err(|_| {
+            Error::penumbra(format!(
+                "Range 0x{:X}+0x{:X} of partition '{}' doesn't fit in memory",
+                offset, size, name
+            ))
+        })?;
+        let data = self.read_blocks(window.address(), len, part.kind).await?;
+
+        let start = window.skip() as usize;
+        Ok(data[start..start + size as usize].to_vec())
+    }
+
+    /// Internal helper returning the partition and the block aligned window covering
+    /// `[offset, offset + size)` inside it.
+    /// Ranges crossing the end of the partition are rejected.
+    async fn block_window(
+        &mut self,
+        name: &str,
+        offset: u64,
+        size: u64,
+    ) -> Result<(Partition, BlockWindow)> {
+        let part = self.partition(name).await?;
+        self.ensure_in_range(&part).await?;
+
+        let block_size = self.dev_info.storage().await.map_or(512, |s| s.block_size() as u64);
+        let window = BlockWindow::new(&part, offset, size, block_size).ok_or_else(|| {
+            Error::penumbra(format!(
+                "Range 0x{:X}+0x{:X} crosses the end of partition '{}'",
+                offset, size, name
+            ))
+        })?;
+
+        Ok((part, window))
+    }
+
+    /// Internal helper reading `size` bytes at `address` into memory.
+    async fn read_blocks(
+        &mut self,
+        address: u64,
+        size: usize,
+        section: PartitionKind,
+    ) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(size);
+        self.read_offset(address, size as u64, section, &mut |_| {}, &mut data).await?;
+
+        if data.len() < size {
+            return Err(Error::penumbra(format!(
+                "Short read at 0x{:X}: expected 0x{:X} bytes, got 0x{:X}",
+                address,
+                size,
+                data.len()
+            )));
+        }
+
+        data.truncate(size);
+        Ok(data)
+    }
+
+    /// Reads data from a specified offset and size on the device.
+    /// This allows reading from arbitrary locations, not limited to named partitions.
+    /// To specify the section (e.g., user, pl_part1, pl_part2), provide the appropriate
+    /// `PartitionKind`.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// // Let's assume we want to read preloader
+    /// use penumbra::{DeviceBuilder, PartitionKind, find_mtk_port};
+    ///
+    /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
+    /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
+    ///
+    /// device.init().await?;
+    ///
+    /// let mut progress = |_: &Progress| {};
+    /// let preloader_data = device
+    ///     .read_offset(0x0, 0x40000, PartitionKind::Emmc(EmmcPartition::Boot1), &mut progress)
+    ///     .await?;
+    /// ```
+    pub async fn read_offset(
+        &mut self,
+        address: u64,
+        size: u64,
+        section: PartitionKind,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol.read_flash(address, size, section, progress, writer).await
+    }
+
+    /// Reads a whole boot region (see [`BootRegion`]), e.g. to back the preloader up on
+    /// devices whose partition table doesn't list it.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let mut file = File::create("boot1.bin").await?;
+    /// let mut progress = |_: &Progress| {};
+    /// device.read_boot_region(BootRegion::Boot1, &mut file, &mut progress).await?;
+    /// ```
+    pub async fn read_boot_region(
+        &mut self,
+        region: BootRegion,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        let (section, size) = self.locate_boot_region(region).await?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol.read_flash(0, size, section, progress, writer).await
+    }
+
+    /// Writes `len` bytes from `reader` to the start of a boot region (see [`BootRegion`]),
+    /// e.g. to restore a preloader backup. Images larger than the region are refused, and so
+    /// are user area images (like `boot.img`) unless region mismatches are ignored.
+    pub async fn write_boot_region(
+        &mut self,
+        region: BootRegion,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        len: u64,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        let (section, size) = self.locate_boot_region(region).await?;
+
+        if len > size {
+            return Err(Error::penumbra(format!(
+                "Image is larger than {} (0x{:X} > 0x{:X} bytes)",
+                region.as_str(),
+                len,
+                size
+            )));
+        }
+
+        let header = read_header(reader).await?;
+        self.ensure_region_matches(region.as_str(), section, &header)?;
+        let mut reader = (&header[..]).chain(reader);
+
+        let id = self.journal_begin(region.as_str(), len).await;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        let result = protocol.write_flash(0, len, &mut reader, section, progress).await;
+
+        self.journal_finish(id, &result, None);
+        result
+    }
+
+    /// Dumps the preloader into `writer`, ready to be used with
+    /// [`DeviceBuilder::with_preloader`], and returns its size.
+    ///
+    /// The preloader is read from Boot1, or Boot2 when Boot1 doesn't hold a valid one. The
+    /// storage boot header and the padding of the region are stripped.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let mut file = File::create("preloader_mt6768.bin").await?;
+    /// let mut progress = |_: &Progress| {};
+    /// device.dump_preloader(&mut file, &mut progress).await?;
+    /// ```
+    pub async fn dump_preloader(
+        &mut self,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<usize> {
+        self.ensure_da_mode().await?;
+
+        for region in [BootRegion::Boot1, BootRegion::Boot2] {
+            let Ok((section, size)) = self.locate_boot_region(region).await else {
+                continue;
+            };
+
+            let mut data = Vec::with_capacity(size as usize);
+            let protocol = self.protocol.as_mut().unwrap();
+            protocol.read_flash(0, size, section, progress, &mut data).await?;
+
+            match extract_preloader(&data) {
+                Some(preloader) => {
+                    writer.write_all(preloader).await?;
+                    writer.flush().await?;
+                    return Ok(preloader.len());
+                }
+                None => warn!("No valid preloader found in {}", region.as_str()),
+            }
+        }
+
+        Err(Error::penumbra("No valid preloader found in the boot regions"))
+    }
+
+    /// Internal helper returning the section and size of a boot region.
+    async fn locate_boot_region(&mut self, region: BootRegion) -> Result<(PartitionKind, u64)> {
+        let storage = self.detected_storage().await?;
+        region.locate(storage.as_ref()).ok_or_else(|| {
+            Error::penumbra(format!("The {:?} storage has no {}", storage.kind(), region.as_str()))
+        })
+    }
+
+    /// Dumps the whole user area into `writer` as a single raw image, starting at `offset`.
+    ///
+    /// The image is read in chunks, so an interrupted dump can be resumed by appending to the
+    /// partial image, with `offset` set to its length. `progress` reports against the size of
+    /// the user area, including the part dumped before resuming.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let mut file = OpenOptions::new().create(true).append(true).open("userarea.bin").await?;
+    /// let offset = file.metadata().await?.len();
+    ///
+    /// let mut progress = |_: &Progress| {};
+    /// device.dump_userarea(&mut file, offset, &mut progress).await?;
+    /// ```
+    pub async fn dump_userarea(
+        &mut self,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        offset: u64,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+
+        let storage = self.detected_storage().await?;
+
+        let total = storage.get_user_size();
+        let section = storage.get_user_part();
+        let block_size = (storage.block_size() as u64).max(1);
+
+        if offset > total || !offset.is_multiple_of(block_size) {
+            return Err(Error::penumbra(format!(
+                "Can't resume the dump at 0x{:X}: must be a multiple of 0x{:X} below 0x{:X}",
+                offset, block_size, total
+            )));
+        }
+
+        info!("Dumping user area (0x{:X} bytes) from 0x{:X}", total, offset);
+        self.warn_if_slow_link(total - offset);
+
+        let mut done = offset;
+        let mut progress = self.track(progress);
+        progress.update(done, total);
+
+        let protocol = self.protocol.as_mut().unwrap();
+        while done < total {
+            let size = DUMP_CHUNK_SIZE.min(total - done);
+            let mut chunk_progress =
+                |p: &Progress| progress.update(done + p.written.min(size), total);
+
+            let mut chunk = Vec::with_capacity(size as usize);
+            let read = protocol.read_flash(done, size, section, &mut chunk_progress, &mut chunk);
+            if let Err(e) = read.await {
+                warn!("User area dump interrupted, resume it from 0x{:X}", done);
+                return Err(e);
+            }
+            if (chunk.len() as u64) < size {
+                return Err(Error::penumbra(format!(
+                    "Short read at 0x{:X}: expected 0x{:X} bytes, got 0x{:X}",
+                    done,
+                    size,
+                    chunk.len()
+                )));
+            }
+
+            writer.write_all(&chunk[..size as usize]).await?;
+            writer.flush().await?;
+            done += size;
+        }
+
+        progress.update(total, total);
+        Ok(())
+    }
+
+    /// Writes data to a specified offset and size on the device.
+    /// This allows writing to arbitrary locations, not limited to named partitions.
+    /// To specify the section (e.g., user, pl_part1, pl_part2), provide the appropriate
+    /// `PartitionKind`.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// // Let's assume we want to write to preloader
+    /// use penumbra::{DeviceBuilder, PartitionKind, find_mtk_port};
+    ///
     /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
     /// let mut device = DeviceBuilder::default().with_mtk_port(mtk_port).build()?;
     ///
     /// device.init().await?;
     ///
     /// let preloader_data = std::fs::read("path/to/preloader_penangf.bin").expect("Failed to read preloader");
-    /// let mut progress = |_written: usize, _total: usize| {};
+    /// let mut progress = |_: &Progress| {};
     /// device
     ///     .write_offset(
     ///         0x1000, // Actual preloader offset is 0x0, but we skip the header to ensure correct writing
-    ///         preloader_data.len(),
+    ///         preloader_data.len() as u64,
     ///         &preloader_data,
     ///         PartitionKind::Emmc(EmmcPartition::Boot1),
     ///         &mut progress,
@@ -461,10 +1877,10 @@ impl Device {
     pub async fn write_offset(
         &mut self,
         address: u64,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
 
@@ -475,9 +1891,9 @@ impl Device {
     pub async fn erase_offset(
         &mut self,
         address: u64,
-        size: usize,
+        size: u64,
         section: PartitionKind,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
 
@@ -485,6 +1901,140 @@ impl Device {
         protocol.erase_flash(address, size, section, progress).await
     }
 
+    /// Erases `size` bytes at `address` inside `section`, e.g. to wipe a GPT or a corrupted
+    /// area regardless of the partition table.
+    ///
+    /// Unlike `erase_offset`, the range is checked first: it must be non empty, block aligned
+    /// and fit inside the region as reported by the storage. Partial erase groups are then
+    /// handled like in [`Device::erase_partition`].
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use penumbra::core::storage::{EmmcPartition, PartitionKind};
+    ///
+    /// // Wipe the primary GPT (protective MBR included)
+    /// let mut progress = |_: &Progress| {};
+    /// device
+    ///     .erase_range(0x0, 0x8000, PartitionKind::Emmc(EmmcPartition::User), &mut progress)
+    ///     .await?;
+    /// ```
+    pub async fn erase_range(
+        &mut self,
+        address: u64,
+        size: u64,
+        section: PartitionKind,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<EraseReport> {
+        self.ensure_da_mode().await?;
+
+        if size == 0 {
+            return Err(Error::penumbra("Can't erase an empty range"));
+        }
+
+        let region = self.regions().await.into_iter().find(|r| r.kind == section);
+        let region = region.ok_or_else(|| {
+            Error::penumbra(format!("Region {} is not available", section.as_str()))
+        })?;
+        if !region.writable {
+            return Err(Error::penumbra(format!("Region {} is read-only", section.as_str())));
+        }
+
+        let block_size =
+            self.dev_info.storage().await.map_or(512, |s| s.block_size() as u64).max(1);
+        if !address.is_multiple_of(block_size) || !size.is_multiple_of(block_size) {
+            return Err(Error::penumbra(format!(
+                "Range 0x{:X}+0x{:X} is not aligned to the block size (0x{:X})",
+                address, size, block_size
+            )));
+        }
+
+        if !region.contains(address, size) {
+            return Err(Error::penumbra(format!(
+                "Range 0x{:X}+0x{:X} crosses the end of region {} (0x{:X} bytes)",
+                address,
+                size,
+                section.as_str(),
+                region.size
+            )));
+        }
+
+        let id = self.journal_begin(section.as_str(), size).await;
+        let result = self.erase_aligned(address, size, section, progress).await;
+
+        self.journal_finish(id, &result, None);
+        result
+    }
+
+    /// Erases `[address, address + size)`, handing only whole erase groups to the DA.
+    ///
+    /// The DA erases in erase group units, and either rounds an unaligned range down or
+    /// rejects it. Only the groups fully inside the range are handed to it, and the partial
+    /// groups at the head and tail are zero-filled, or left untouched when skipping them
+    /// was asked for. The group size is the one the DA reported with the storage info,
+    /// or the block size when it didn't report any. The report tells which bytes got
+    /// which treatment, and whether the storage erased or only discarded them.
+    async fn erase_aligned(
+        &mut self,
+        address: u64,
+        size: u64,
+        section: PartitionKind,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<EraseReport> {
+        let storage = self.dev_info.storage().await;
+        let group_size = storage.as_ref().map_or(512, |s| s.erase_group_size());
+        let method = storage.as_ref().map_or(EraseMethod::Erase, |s| s.erase_method());
+
+        let span = EraseSpan::new(address, size, group_size);
+        let mut report = EraseReport { method, erased: 0, zero_filled: 0, skipped: 0 };
+        let mut done = 0;
+        let mut progress = self.track(progress);
+
+        if span.head != 0 && !self.skip_partial_erase {
+            self.zero_fill(address, span.head, section, done, size, &mut progress).await?;
+            report.zero_filled += span.head;
+        }
+        done += span.head;
+
+        if span.size != 0 {
+            let mut erase_progress = |p: &Progress| progress.update(done + p.written, size);
+            self.erase_offset(span.start, span.size, section, &mut erase_progress).await?;
+            report.erased = span.size;
+            done += span.size;
+        }
+
+        if span.tail != 0 && !self.skip_partial_erase {
+            let tail_start = span.start + span.size;
+            self.zero_fill(tail_start, span.tail, section, done, size, &mut progress).await?;
+            report.zero_filled += span.tail;
+        }
+
+        report.skipped = span.head + span.tail - report.zero_filled;
+        if report.skipped != 0 {
+            warn!(
+                "Left 0x{:X} bytes outside of whole erase groups (0x{:X}) untouched",
+                report.skipped, group_size
+            );
+        }
+
+        progress.update(size, size);
+        Ok(report)
+    }
+
+    /// Overwrites `size` bytes at `address` with zeros, reporting progress past `done`.
+    async fn zero_fill(
+        &mut self,
+        address: u64,
+        size: u64,
+        section: PartitionKind,
+        done: u64,
+        total: u64,
+        progress: &mut ProgressTracker<'_>,
+    ) -> Result<()> {
+        let zeros = vec![0u8; size as usize];
+        let mut fill_progress = |p: &Progress| progress.update(done + p.written, total);
+        self.write_offset(address, size, &mut zeros.as_slice(), section, &mut fill_progress).await
+    }
+
     /// Like `write_partition`, but instead of writing using offsets and sizes from GPT,
     /// it uses the partition name directly.
     ///
@@ -493,8 +2043,11 @@ impl Device {
     /// without hitting security checks, since the data is first uploaded and then verified as a
     /// whole.
     ///
+    /// Android sparse images are expanded on the fly, in which case `size` is ignored
+    /// in favor of the expanded size.
+    ///
     /// # Examples
-    /// ```rust
+    /// ```rust,ignore
     /// use penumbra::{DeviceBuilder, find_mtk_port};
     ///
     /// let mtk_port = find_mtk_port().await.ok_or("No MTK port found")?;
@@ -507,14 +2060,113 @@ impl Device {
     pub async fn download(
         &mut self,
         partition: &str,
-        size: usize,
+        size: u64,
         reader: &mut (dyn AsyncRead + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
+        self.ensure_gpt_writable(partition).await?;
+
+        // The DA resolves the name on its own, so names unknown to the cached table
+        // (e.g. the preloader on some storages) are passed through as is
+        let cached = self.dev_info.get_partition(partition).await;
+        if self.verify_writes && cached.is_none() {
+            return Err(Error::penumbra(format!(
+                "Can't verify '{}', it isn't in the partition table",
+                partition
+            )));
+        }
+
+        let header = read_header(reader).await?;
+        let sparse = SparseHeader::parse(&header);
+        let size = sparse.map_or(size, |s| s.expanded_size());
+
+        if let Some(part) = &cached {
+            self.ensure_in_range(part).await?;
+            if size > part.size {
+                return Err(Error::penumbra(format!(
+                    "Image is larger than partition '{}' (0x{:X} > 0x{:X} bytes)",
+                    partition, size, part.size
+                )));
+            }
+            self.ensure_region_matches(partition, part.kind, &header)?;
+        }
+        let mut reader = (&header[..]).chain(reader);
+        let mut sparse_reader;
+        let reader: &mut (dyn AsyncRead + Unpin + Send) = match sparse {
+            Some(sparse) => {
+                info!("Expanding sparse image to 0x{:X} bytes", size);
+                sparse_reader = SparseReader::new(&mut reader, sparse);
+                &mut sparse_reader
+            }
+            None => &mut reader,
+        };
+
+        self.warn_if_slow_link(size);
+        let id = self.journal_begin(partition, size).await;
+        let mut reader = HashingReader::new(reader);
+
+        let passes = if self.verify_writes { 2 } else { 1 };
+        let mut progress = self.track(progress);
+        let mut write_progress = |p: &Progress| progress.update(p.written, p.total * passes);
+
+        let protocol = self.protocol.as_mut().unwrap();
+        let result =
+            protocol.download(partition.to_string(), size, &mut reader, &mut write_progress).await;
+
+        let digest = reader.digest();
+        self.journal_finish(id, &result, Some(&digest));
+        result?;
+
+        if let Some(part) = cached.filter(|_| self.verify_writes) {
+            self.verify_written(&part, size, digest, &mut progress).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal helper timing an operation made of several transfers as a whole, so the
+    /// rate and ETA reported don't restart with each of them.
+    fn track<'a>(
+        &mut self,
+        progress: &'a mut (dyn FnMut(&Progress) + Send),
+    ) -> ProgressTracker<'a> {
+        ProgressTracker::new(progress).with_phases(self.phase_tracker())
+    }
+
+    /// Internal helper reading back the first `len` bytes of a partition after a write,
+    /// and comparing their SHA-256 with the one of the written data.
+    /// Progress continues from where the write pass left it.
+    async fn verify_written(
+        &mut self,
+        part: &Partition,
+        len: u64,
+        expected: [u8; 32],
+        progress: &mut ProgressTracker<'_>,
+    ) -> Result<()> {
+        info!("Verifying '{}'...", part.name);
+
+        let mut sink = HashingWriter::sink();
+        let mut read_progress = |p: &Progress| progress.update(len + p.written, len * 2);
 
         let protocol = self.protocol.as_mut().unwrap();
-        protocol.download(partition.to_string(), size, reader, progress).await
+        let phases = protocol.get_connection().phases.clone();
+        phases.enter(FlashPhase::Verify);
+        let result =
+            protocol.read_flash(part.address, len, part.kind, &mut read_progress, &mut sink).await;
+        phases.finish();
+        result?;
+
+        let actual = sink.digest();
+        if actual != expected {
+            return Err(Error::VerifyMismatch {
+                partition: part.name.clone(),
+                expected: hex::encode(expected),
+                actual: hex::encode(actual),
+            });
+        }
+
+        Ok(())
     }
 
     /// Like `read_partition`, but instead of reading using offsets and sizes from GPT,
@@ -523,7 +2175,7 @@ impl Device {
     /// This is the same method uses by SP Flash Tool when reading back without scatter.
     ///
     /// # Examples
-    /// ```rust
+    /// ```rust,ignore
     /// use penumbra::{DeviceBuilder, find_mtk_port};
     /// use tokio::fs::File;
     /// use tokio::io::BufWriter;
@@ -537,30 +2189,314 @@ impl Device {
     /// // Readsback "logo" partition to "logo.bin"
     /// let file = File::create("logo.bin").await?;
     /// let mut writer = BufWriter::new(file);
-    /// let mut progress = |_written: usize, _total: usize| {};
+    /// let mut progress = |_: &Progress| {};
     /// device.upload("logo", &mut writer, &mut progress).await?;
     /// ```
     pub async fn upload(
         &mut self,
         partition: &str,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
 
+        if let Some(part) = self.dev_info.get_partition(partition).await {
+            self.ensure_in_range(&part).await?;
+            self.warn_if_slow_link(part.size);
+        }
+
         let protocol = self.protocol.as_mut().unwrap();
         protocol.upload(partition.to_string(), writer, progress).await
     }
 
-    pub async fn format(
+    /// Like [`Device::upload`], also returning the SHA-256 of the data, computed while
+    /// it streams to `writer`.
+    pub async fn upload_hashed(
+        &mut self,
+        partition: &str,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<[u8; 32]> {
+        let mut writer = HashingWriter::new(writer);
+        self.upload(partition, &mut writer, progress).await?;
+        writer.flush().await?;
+
+        Ok(writer.digest())
+    }
+
+    /// Reads `length` bytes at `offset` inside a partition, streaming them to `writer`.
+    ///
+    /// Unlike [`Device::upload`], only the storage blocks covering the range are read,
+    /// which is handy to grab a few MBs out of large partitions like `super`.
+    /// Ranges crossing the end of the partition are rejected.
+    pub async fn upload_range(
+        &mut self,
+        partition: &str,
+        offset: u64,
+        length: u64,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+
+        let (part, window) = self.block_window(partition, offset, length).await?;
+        if length == 0 {
+            return Ok(());
+        }
+
+        let skip = window.skip();
+        let mut writer = RangeWriter::new(writer, skip, length);
+        let mut read_progress = |p: &Progress| {
+            let written = p.written.saturating_sub(skip).min(length);
+            progress(&Progress { written, total: length, ..*p })
+        };
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol
+            .read_flash(window.address(), window.size(), part.kind, &mut read_progress, &mut writer)
+            .await?;
+
+        if writer.left > 0 {
+            return Err(Error::penumbra(format!(
+                "Short read of partition '{}': 0x{:X} bytes missing",
+                partition, writer.left
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Writes `length` bytes from `reader` at `offset` inside a partition, leaving the rest
+    /// of the partition untouched.
+    ///
+    /// Counterpart of [`Device::upload_range`], handy to patch a flag in `misc` or `para`
+    /// without flashing the whole partition. When the range isn't block aligned, the blocks
+    /// at its edges are read first so the bytes around it are written back as they were.
+    /// Ranges crossing the end of the partition are rejected.
+    pub async fn download_range(
         &mut self,
         partition: &str,
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        offset: u64,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        length: u64,
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
+        self.ensure_gpt_writable(partition).await?;
+
+        let (part, window) = self.block_window(partition, offset, length).await?;
+        if length == 0 {
+            return Ok(());
+        }
+
+        // The blocks at the edges are written back as they were
+        let mut head = Vec::new();
+        if let Some(blocks) = window.head() {
+            let len = (blocks.end - blocks.start) as usize;
+            head = self.read_blocks(blocks.start, len, part.kind).await?;
+            head.truncate(window.skip() as usize);
+        }
+
+        let mut tail = Vec::new();
+        if let Some(blocks) = window.tail() {
+            let len = (blocks.end - blocks.start) as usize;
+            tail = self.read_blocks(blocks.start, len, part.kind).await?;
+            tail.drain(..(window.base + window.range.end - blocks.start) as usize);
+        }
+
+        let head_len = head.len() as u64;
+        let mut reader = (&head[..]).chain(reader.take(length)).chain(&tail[..]);
+        let mut write_progress = |p: &Progress| {
+            let written = p.written.saturating_sub(head_len).min(length);
+            progress(&Progress { written, total: length, ..*p })
+        };
 
+        let id = self.journal_begin(&part.name, length).await;
         let protocol = self.protocol.as_mut().unwrap();
-        protocol.format(partition.to_string(), progress).await
+        let result = protocol
+            .write_flash(
+                window.address(),
+                window.size(),
+                &mut reader,
+                part.kind,
+                &mut write_progress,
+            )
+            .await;
+
+        self.journal_finish(id, &result, None);
+        result
+    }
+
+    /// Formats a partition. Like [`Device::erase_partition`], the whole partition is
+    /// erased, with its partial erase groups handled by [`Device::erase_aligned`], instead
+    /// of leaving the DA to round them.
+    pub async fn format(
+        &mut self,
+        partition: &str,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<EraseReport> {
+        self.erase_partition(partition, progress).await
+    }
+
+    /// Formats every partition matching one of `patterns` (e.g. `userdata`, `md*`),
+    /// in partition table order.
+    ///
+    /// Protected partitions (preloader, GPT, seccfg...) are skipped unless `allow_critical`
+    /// is set. A failure doesn't stop the next partitions from being formatted, the result
+    /// of each one is returned instead. `progress` covers all the partitions at once.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let mut progress = |_: &Progress| {};
+    /// let results = device.format_many(&["userdata", "md*"], false, &mut progress).await?;
+    /// for (name, result) in results {
+    ///     if let Err(e) = result {
+    ///         eprintln!("Failed to format {}: {}", name, e);
+    ///     }
+    /// }
+    /// ```
+    pub async fn format_many(
+        &mut self,
+        patterns: &[&str],
+        allow_critical: bool,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<Vec<(String, Result<EraseReport>)>> {
+        self.ensure_da_mode().await?;
+
+        let mut targets = Vec::new();
+        for part in self.get_partitions().await? {
+            if !patterns.iter().any(|pattern| matches_pattern(&part.name, pattern)) {
+                continue;
+            }
+            if is_protected_part(&part.name) && !allow_critical {
+                warn!("Skipping protected partition '{}'", part.name);
+                continue;
+            }
+            self.ensure_gpt_writable(&part.name).await?;
+            targets.push(part);
+        }
+
+        if targets.is_empty() {
+            return Err(Error::penumbra(format!(
+                "No partition to format matches {}",
+                patterns.join(", ")
+            )));
+        }
+
+        let total: u64 = targets.iter().map(|p| p.size).sum();
+        let mut done = 0;
+        let mut results = Vec::with_capacity(targets.len());
+        let mut progress = self.track(progress);
+
+        for part in targets {
+            if self.cancel_handle().is_some_and(|c| c.is_cancelled()) {
+                return Err(Error::cancelled());
+            }
+            let mut part_progress = |p: &Progress| progress.update(done + p.written, total);
+            let result = self.format(&part.name, &mut part_progress).await;
+            if let Err(e) = &result {
+                warn!("Failed to format '{}': {}", part.name, e);
+            }
+
+            done += part.size;
+            progress.update(done, total);
+            results.push((part.name, result));
+        }
+
+        Ok(results)
+    }
+
+    /// Flashes the images listed in a scatter file, in file order, from `image_dir`.
+    ///
+    /// Entries without an image or marked `is_download: false` are skipped. Before flashing
+    /// anything, the scatter storage must match the device one and every image must exist
+    /// and target a partition the device has, which the partition table source allows
+    /// writing. A failure doesn't stop the next images from being flashed, the result of
+    /// each one is returned instead. `progress` covers all the images at once.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use penumbra::core::scatter::ScatterFile;
+    ///
+    /// let scatter = ScatterFile::from_file(Path::new("fw/MT6765_Android_scatter.txt"))?;
+    /// let mut progress = |_: &Progress| {};
+    /// let results = device.flash_scatter(&scatter, Path::new("fw"), &mut progress).await?;
+    /// ```
+    pub async fn flash_scatter(
+        &mut self,
+        scatter: &ScatterFile,
+        image_dir: &Path,
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<Vec<(String, Result<()>)>> {
+        self.ensure_da_mode().await?;
+
+        let storage = self.dev_info.storage().await.map_or(StorageType::Unknown, |s| s.kind());
+        let expected = scatter.storage_type();
+        let known = storage != StorageType::Unknown && expected != StorageType::Unknown;
+        if known && storage != expected {
+            error!("Scatter file is for {} storage, the device has {:?}", scatter.storage, storage);
+            return Err(Error::XFlash(XFlashError::from_code(
+                XFlashErrorKind::StorageMismatch.into(),
+            )));
+        }
+
+        // Several chips share a hardware code, so a different name is only suspicious
+        if let Some(chip) = self.dev_info.chip_name().await
+            && !chip.eq_ignore_ascii_case(&scatter.platform)
+        {
+            warn!("Scatter file is for {}, the device reports {}", scatter.platform, chip);
+        }
+
+        let mut targets = Vec::new();
+        for entry in scatter.flashable() {
+            // The DA resolves the preloader by name, even when the table doesn't list it
+            if !is_pl_part(&entry.name) && self.dev_info.get_partition(&entry.name).await.is_none()
+            {
+                error!("Scatter partition '{}' is not on the device", entry.name);
+                return Err(Error::XFlash(XFlashError::from_code(
+                    XFlashErrorKind::ScatterFileInvalid.into(),
+                )));
+            }
+            self.ensure_gpt_writable(&entry.name).await?;
+
+            let path = image_dir.join(entry.file_name.as_deref().unwrap_or_default());
+            let len = tokio::fs::metadata(&path).await.map(|m| m.len()).map_err(|e| {
+                Error::penumbra(format!("Image {} for '{}': {}", path.display(), entry.name, e))
+            })?;
+            targets.push((entry.name.as_str(), path, len));
+        }
+
+        let total: u64 = targets.iter().map(|(_, _, len)| len).sum();
+        let mut done = 0;
+        let mut results = Vec::with_capacity(targets.len());
+        let mut progress = self.track(progress);
+
+        for (name, path, len) in targets {
+            if self.cancel_handle().is_some_and(|c| c.is_cancelled()) {
+                return Err(Error::cancelled());
+            }
+            info!("Flashing '{}' from {}", name, path.display());
+
+            // Sparse expansion and verification change the inner total, so scale it to the
+            // image size to keep the overall progress monotonic
+            let mut part_progress = |p: &Progress| {
+                let scaled = p.written as u128 * len as u128 / p.total.max(1) as u128;
+                progress.update(done + (scaled as u64).min(len), total)
+            };
+            let result = match File::open(&path).await {
+                Ok(mut file) => self.download(name, len, &mut file, &mut part_progress).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = &result {
+                warn!("Failed to flash '{}': {}", name, e);
+            }
+
+            done += len;
+            progress.update(done, total);
+            results.push((name.to_string(), result));
+        }
+
+        Ok(results)
     }
 
     pub async fn shutdown(&mut self) -> Result<()> {
@@ -573,31 +2509,140 @@ impl Device {
     pub async fn reboot(&mut self, bootmode: BootMode) -> Result<()> {
         self.ensure_da_mode().await?;
 
+        // DAs have no recovery mode, but the Android bootloader boots into it when the
+        // bootloader message in `misc` asks for it, like `adb reboot recovery` does
+        if bootmode == BootMode::Recovery {
+            if self.partition("misc").await.is_err() {
+                return Err(Error::Unsupported(
+                    "Rebooting to recovery needs a 'misc' partition".into(),
+                ));
+            }
+
+            let mut command = [0u8; 32];
+            command[..BCB_RECOVERY.len()].copy_from_slice(BCB_RECOVERY);
+            self.patch_partition("misc", 0, &command, true).await?;
+
+            info!("Requested recovery mode through the 'misc' partition");
+            let protocol = self.protocol.as_mut().unwrap();
+            return protocol.reboot(BootMode::Normal).await;
+        }
+
         let protocol = self.protocol.as_mut().unwrap();
         protocol.reboot(bootmode).await
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     pub async fn set_seccfg_lock_state(&mut self, lock_state: LockFlag) -> Option<Vec<u8>> {
         // Ensure DA mode first; this will populate partitions and storage
         self.ensure_da_mode().await.ok()?;
+        if let Err(e) = self.ensure_extensions() {
+            error!("Can't change the lock state: {}", e);
+            return None;
+        }
+
         let protocol = self.protocol.as_mut().unwrap();
         protocol.set_seccfg_lock_state(lock_state).await
     }
 
-    #[cfg(not(feature = "no_exploits"))]
+    /// Reads whether the bootloader is locked from seccfg, without writing anything back.
+    /// Like changing it, this needs the DA extensions to verify the seccfg hash.
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub async fn get_seccfg_lock_state(&mut self) -> Result<LockFlag> {
+        self.ensure_da_mode().await?;
+        self.ensure_extensions()?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol.get_seccfg_lock_state().await
+    }
+
+    /// Reads seccfg, lets `edit` change any of its flags, and writes it back encrypted
+    /// with the same SEJ algorithm, returning the written data.
+    /// Inconsistent flags (see [`SecCfg::validate`]) are refused unless `force` is set.
+    ///
+    /// ```rust,ignore
+    /// device.edit_seccfg(|cfg| if let SecCfg::V4(v4) = cfg {
+    ///     v4.set_critical_locked(false);
+    /// }, false).await?;
+    /// ```
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub async fn edit_seccfg<F>(&mut self, edit: F, force: bool) -> Result<Vec<u8>>
+    where
+        F: FnOnce(&mut SecCfg),
+    {
+        self.ensure_da_mode().await?;
+        self.ensure_extensions()?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        let mut seccfg = protocol.read_seccfg().await?;
+        edit(&mut seccfg);
+
+        if let Err(e) = seccfg.validate() {
+            if !force {
+                return Err(e);
+            }
+            warn!("Writing seccfg anyway: {}", e);
+        }
+
+        protocol.write_seccfg(&mut seccfg).await
+    }
+
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
     pub async fn peek(
         &mut self,
         addr: u32,
         size: usize,
         writer: &mut (dyn AsyncWrite + Unpin + Send),
-        progress: &mut (dyn FnMut(usize, usize) + Send),
+        progress: &mut (dyn FnMut(&Progress) + Send),
     ) -> Result<()> {
         self.ensure_da_mode().await?;
+        self.ensure_extensions()?;
 
         let protocol = self.protocol.as_mut().unwrap();
         protocol.peek(addr, size, writer, progress).await
     }
+
+    /// Writes `data` to memory at `addr` through the DA extensions, e.g. to toggle
+    /// a DA variable without repatching DA2.
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub async fn poke(
+        &mut self,
+        addr: u32,
+        data: &[u8],
+        progress: &mut (dyn FnMut(&Progress) + Send),
+    ) -> Result<()> {
+        self.ensure_da_mode().await?;
+        self.ensure_extensions()?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        protocol.poke(addr, data, progress).await
+    }
+
+    /// Reads the eFuse words through the DA extensions, e.g. to check the SBC and DAA fuses
+    /// without a separate tool. Only chips with a known eFuse controller address are supported.
+    /// On XFlash, failed reads are reported with their eFuse error kind (0xC00402xx).
+    #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+    pub async fn read_efuses(&mut self) -> Result<Vec<EfuseBank>> {
+        self.ensure_da_mode().await?;
+        self.ensure_extensions()?;
+
+        let hw_code = self.dev_info.hw_code().await;
+        let base = efuse_base(hw_code).ok_or_else(|| {
+            Error::Unsupported(format!(
+                "eFuse controller address unknown for hardware code 0x{:04X}",
+                hw_code
+            ))
+        })?;
+
+        let protocol = self.protocol.as_mut().unwrap();
+        let mut banks = Vec::with_capacity(EFUSE_BANK_COUNT);
+        for index in 0..EFUSE_BANK_COUNT {
+            let address = base + index as u32 * 4;
+            let value = protocol.read32(address).await?;
+            banks.push(EfuseBank { index, address, value });
+        }
+
+        Ok(banks)
+    }
 }
 
 #[async_trait::async_trait]
@@ -628,3 +2673,68 @@ impl CryptoIO for Device {
         }
     }
 }
+
+/// Reads the first bytes of an image, to classify it before writing.
+/// Returns less than [`IMAGE_HEADER_LEN`] bytes only if the image is shorter.
+async fn read_header(reader: &mut (dyn AsyncRead + Unpin + Send)) -> Result<Vec<u8>> {
+    let mut header = vec![0u8; IMAGE_HEADER_LEN];
+    let mut len = 0;
+
+    while len < header.len() {
+        let n = reader.read(&mut header[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+
+    header.truncate(len);
+    Ok(header)
+}
+
+/// Writer forwarding only `[skip, skip + len)` of the data written to it, used to trim
+/// the block aligned window read from the storage to the requested range.
+struct RangeWriter<'a> {
+    inner: &'a mut (dyn AsyncWrite + Unpin + Send),
+    skip: u64,
+    left: u64,
+}
+
+impl<'a> RangeWriter<'a> {
+    fn new(inner: &'a mut (dyn AsyncWrite + Unpin + Send), skip: u64, len: u64) -> Self {
+        Self { inner, skip, left: len }
+    }
+}
+
+impl AsyncWrite for RangeWriter<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+
+        if this.skip > 0 {
+            let n = this.skip.min(buf.len() as u64);
+            this.skip -= n;
+            return Poll::Ready(Ok(n as usize));
+        }
+
+        if this.left == 0 {
+            return Poll::Ready(Ok(buf.len()));
+        }
+
+        let len = this.left.min(buf.len() as u64) as usize;
+        let written = std::task::ready!(Pin::new(&mut *this.inner).poll_write(cx, &buf[..len]))?;
+        this.left -= written as u64;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
@@ -2,6 +2,7 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::io::ErrorKind;
 use std::sync::PoisonError;
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
@@ -24,12 +25,26 @@ pub enum Error {
     Connection(String),
     /// Error related to I/O operations
     /// In particular with the connection backends
-    #[error("I/O Error: {0}")]
-    Io(String),
+    /// Keeps the kind of the original error, to tell e.g. a timeout from a device
+    /// that went away.
+    #[error("I/O Error: {msg}")]
+    Io { kind: ErrorKind, msg: String },
     /// Generic error that happens in Penumbra, can
     /// be used for anything
     #[error("Penumbra Error: {0}")]
     Penumbra(String),
+    /// The operation needs the DA extensions, which are not active.
+    /// Holds the reason (see [`crate::da::ExtState`]).
+    #[error("DA extensions unavailable: {0}")]
+    ExtensionsUnavailable(String),
+    /// The operation is not supported by the device or its storage,
+    /// e.g. reading RPMB through the extensions on UFS.
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+    /// The data read back after a write doesn't match the written one.
+    /// Holds the SHA-256 of both sides, hex encoded.
+    #[error("Verification of '{partition}' failed: wrote {expected}, read back {actual}")]
+    VerifyMismatch { partition: String, expected: String, actual: String },
     /// Error that takes a status code and formats it as hex.
     /// When dealing with statuses in general, use
     /// this, unless a more specific implementation
@@ -40,7 +55,7 @@ pub enum Error {
 
 impl Error {
     pub fn io<S: Into<String>>(msg: S) -> Self {
-        Error::Io(msg.into())
+        Error::Io { kind: ErrorKind::Other, msg: msg.into() }
     }
 
     pub fn conn<S: Into<String>>(msg: S) -> Self {
@@ -54,17 +69,40 @@ impl Error {
     pub fn penumbra<S: Into<String>>(msg: S) -> Self {
         Error::Penumbra(msg.into())
     }
+
+    /// Error of the operations stopped through a `CancelToken`
+    pub fn cancelled() -> Self {
+        Error::penumbra("cancelled")
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Error::Penumbra(msg) if msg == "cancelled")
+    }
+
+    /// Kind of the underlying I/O error, if this is one
+    pub fn io_kind(&self) -> Option<ErrorKind> {
+        match self {
+            Error::Io { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Whether the device went away, e.g. it was unplugged or rebooted
+    pub fn is_disconnect(&self) -> bool {
+        matches!(self.io_kind(), Some(ErrorKind::NotConnected | ErrorKind::ConnectionAborted))
+    }
 }
 
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
-        Error::penumbra(value.to_string())
+        Error::Io { kind: value.kind(), msg: value.to_string() }
     }
 }
 
+#[cfg(feature = "nusb")]
 impl From<nusb::Error> for Error {
     fn from(err: nusb::Error) -> Self {
-        Error::io(err.to_string())
+        std::io::Error::from(err).into()
     }
 }
 
@@ -77,7 +115,17 @@ impl<T> From<PoisonError<T>> for Error {
 #[cfg(feature = "libusb")]
 impl From<rusb::Error> for Error {
     fn from(err: rusb::Error) -> Self {
-        Error::io(err.to_string())
+        let kind = match err {
+            rusb::Error::Timeout => ErrorKind::TimedOut,
+            rusb::Error::Pipe => ErrorKind::BrokenPipe,
+            rusb::Error::Interrupted => ErrorKind::Interrupted,
+            rusb::Error::NoDevice => ErrorKind::NotConnected,
+            rusb::Error::Access => ErrorKind::PermissionDenied,
+            rusb::Error::NotFound => ErrorKind::NotFound,
+            rusb::Error::Busy => ErrorKind::ResourceBusy,
+            _ => ErrorKind::Other,
+        };
+        Error::Io { kind, msg: err.to_string() }
     }
 }
 
@@ -520,18 +568,178 @@ impl XFlashError {
         let kind = XFlashErrorKind::try_from(code).unwrap_or(XFlashErrorKind::Unknown);
         Self { kind, code }
     }
+
+    pub fn severity(&self) -> Severity {
+        Severity::from_code(self.code)
+    }
+
+    pub fn domain(&self) -> Domain {
+        Domain::from_code(self.code)
+    }
+
+    pub fn is_security(&self) -> bool {
+        self.domain() == Domain::Security
+    }
+
+    /// Whether the storage (eMMC, NAND, UFS) or its partitions caused the error
+    pub fn is_storage(&self) -> bool {
+        use XFlashErrorKind::*;
+
+        let family = self.code & 0xFFFF;
+        matches!(
+            self.kind,
+            StorageMismatch
+                | UnknownStorageSectionType
+                | PartitionNotFound
+                | ReadParttblFailed
+                | ExceededMaxPartitionNumber
+                | UnknownStorageType
+                | ExceedAvailableRange
+                | WriteSparseImageFailed
+        ) || (self.domain() == Domain::Device
+            && matches!(family, 0x30..=0x4F | 0x60..=0x6F))
+    }
+}
+
+/// Severity of an XFlash status, held in its top two bits
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    Success,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn from_code(code: u32) -> Self {
+        match code >> 30 {
+            0 => Severity::Success,
+            1 => Severity::Info,
+            2 => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Info and Warning statuses report something, but the operation went through
+    pub fn is_failure(&self) -> bool {
+        *self == Severity::Error
+    }
 }
 
+/// What an XFlash status relates to, held in bits 16 to 23
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Domain {
+    Common,
+    Security,
+    Library,
+    /// Storage, DRAM and eFuses
+    Device,
+    Host,
+    Brom,
+    Da,
+    Preloader,
+    Unknown(u8),
+}
+
+impl Domain {
+    pub fn from_code(code: u32) -> Self {
+        match (code >> 16) as u8 {
+            1 => Domain::Common,
+            2 => Domain::Security,
+            3 => Domain::Library,
+            4 => Domain::Device,
+            5 => Domain::Host,
+            6 => Domain::Brom,
+            7 => Domain::Da,
+            8 => Domain::Preloader,
+            other => Domain::Unknown(other),
+        }
+    }
+}
+
+/// Everything known about a raw XFlash status code, see [`describe_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusDescription {
+    pub code: u32,
+    /// `None` for codes not in [`XFlashErrorKind`]
+    pub kind: Option<XFlashErrorKind>,
+    pub severity: Severity,
+    pub domain: Domain,
+    pub message: String,
+}
+
+impl std::fmt::Display for StatusDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#010x}: {} ({:?}, {:?})", self.code, self.message, self.severity, self.domain)
+    }
+}
+
+/// Decodes a status code, e.g. one pasted from another tool's log.
+/// Codes that aren't known still get their severity and domain decoded.
+pub fn describe_status(code: u32) -> StatusDescription {
+    let kind = XFlashErrorKind::try_from(code).ok().filter(|k| *k != XFlashErrorKind::Unknown);
+    let severity = Severity::from_code(code);
+    let domain = Domain::from_code(code);
+
+    let message = match kind {
+        Some(kind) => kind.to_string(),
+        None if code == 0 => "Success".to_string(),
+        None => format!("Unknown status 0x{:04X}", code & 0xFFFF),
+    };
+
+    StatusDescription { code, kind, severity, domain, message }
+}
+
+/// Errors reported by XML DAs as `ERR!<REASON>` strings
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
 pub enum XmlErrorKind {
+    #[error("Unknown error")]
     Unknown,
+    #[error("Unsupported command")]
     UnsupportedCmd,
+    #[error("Cancelled")]
     Cancel,
+    /// A packet didn't match the checksum sent with it
+    #[error("Checksum mismatch")]
+    ChecksumMismatch,
+    #[error("Storage not ready")]
+    StorageNotReady,
+    /// The partition or file named in the command doesn't exist
+    #[error("File not found")]
+    FileNotFound,
+    /// The data doesn't fit the buffer or partition it's meant for
+    #[error("Insufficient buffer")]
+    InsufficientBuffer,
+    /// The DA read back something else than what was written
+    #[error("Verification failed")]
+    VerifyFailed,
 }
 
+/// Substrings of the DA error strings, and the kind they map to.
+/// Checked in order, so the more specific ones come first.
+const XML_ERROR_STRINGS: &[(&str, XmlErrorKind)] = &[
+    ("UNSUPPORTED", XmlErrorKind::UnsupportedCmd),
+    ("CANCEL", XmlErrorKind::Cancel),
+    ("CHECKSUM", XmlErrorKind::ChecksumMismatch),
+    ("CHKSUM", XmlErrorKind::ChecksumMismatch),
+    ("NOT-READY", XmlErrorKind::StorageNotReady),
+    ("NOT_READY", XmlErrorKind::StorageNotReady),
+    ("NOT-FOUND", XmlErrorKind::FileNotFound),
+    ("NOT_FOUND", XmlErrorKind::FileNotFound),
+    ("NOT-EXIST", XmlErrorKind::FileNotFound),
+    ("NOT_EXIST", XmlErrorKind::FileNotFound),
+    ("INSUFFICIENT", XmlErrorKind::InsufficientBuffer),
+    ("BUFFER", XmlErrorKind::InsufficientBuffer),
+    ("TOO-LARGE", XmlErrorKind::InsufficientBuffer),
+    ("TOO_LARGE", XmlErrorKind::InsufficientBuffer),
+    ("VERIFY", XmlErrorKind::VerifyFailed),
+    ("VERIFICATION", XmlErrorKind::VerifyFailed),
+];
+
 #[derive(Debug, Error)]
-#[error("XML Error: {message}")]
+#[error("{kind} ({message})")]
 pub struct XmlError {
+    /// The message as sent by the DA, or our own description
     pub message: String,
     pub kind: XmlErrorKind,
 }
@@ -542,14 +750,15 @@ impl XmlError {
     }
 
     pub fn from_message(resp: &[u8]) -> Self {
-        let msg = std::str::from_utf8(resp).unwrap_or("Invalid UTF-8");
-
+        let msg = String::from_utf8_lossy(resp);
         let msg = msg.trim_end_matches('\0');
 
-        match msg {
-            "ERR!UNSUPPORTED" => XmlError::new("Unsupported command", XmlErrorKind::UnsupportedCmd),
-            "ERR!CANCEL" => XmlError::new("Cancelled", XmlErrorKind::Cancel),
-            _ => XmlError::new(msg, XmlErrorKind::UnsupportedCmd),
-        }
+        let upper = msg.to_ascii_uppercase();
+        let kind = XML_ERROR_STRINGS
+            .iter()
+            .find(|(s, _)| upper.contains(s))
+            .map_or(XmlErrorKind::Unknown, |(_, kind)| *kind);
+
+        XmlError::new(msg, kind)
     }
 }
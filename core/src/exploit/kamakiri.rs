@@ -15,6 +15,7 @@ use log::{debug, info};
 
 use crate::connection::Connection;
 use crate::connection::port::{ConnectionType, MTKPort};
+use crate::core::devinfo::{DevInfoData, TargetConfig};
 use crate::da::{DA, DAProtocol};
 use crate::error::{Error, Result};
 use crate::exploit::{BootStage, Exploit, ExploitMeta};
@@ -331,9 +332,7 @@ impl Exploit for Kamakiri2 {
         // We print the target config bits both for debugging, as well to ensure
         // the payload returned to the cmd_handler loop properly.
         let target_config = protocol.get_connection().get_target_config().await?;
-        debug!("SBC: {}", target_config & 0x1 != 0);
-        debug!("SLA: {}", target_config & 0x2 != 0);
-        debug!("DAA: {}", target_config & 0x4 != 0);
+        debug!("Target config: {}", TargetConfig::from(target_config));
 
         protocol.get_devinfo().set_target_config(target_config).await;
 
@@ -343,6 +342,10 @@ impl Exploit for Kamakiri2 {
         Ok(true)
     }
 
+    fn applies_to(&self, dev: &DevInfoData) -> bool {
+        self.get_payload(dev.hw_code).is_some()
+    }
+
     fn get_meta(&self) -> &ExploitMeta {
         &self.meta
     }
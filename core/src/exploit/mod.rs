@@ -9,9 +9,12 @@ pub use heapbait::HeapBait;
 pub use carbonara::Carbonara;
 pub use kamakiri::Kamakiri2 as Kamakiri;
 
+use log::debug;
+
 use crate::connection::port::ConnectionType;
+use crate::core::devinfo::DevInfoData;
 use crate::da::protocol::DAProtocol;
-use crate::da::{DA, DAEntryRegion};
+use crate::da::{DA, DAEntryRegion, DAType};
 use crate::error::Result;
 const V6_PAYLOAD_MAGIC: &[u8] = b"PENUMBRAV6P";
 
@@ -35,6 +38,60 @@ pub trait Exploit {
 
     fn get_meta(&self) -> &ExploitMeta;
     fn get_patched_da(&self) -> Option<DA>;
+
+    /// Whether the exploit can work on this device, checked before [`Exploit::run`].
+    /// Exploits that can only tell from the DA itself keep the default.
+    fn applies_to(&self, _dev: &DevInfoData) -> bool {
+        true
+    }
+}
+
+/// An entry of the exploit registry
+pub struct ExploitEntry {
+    pub name: &'static str,
+    /// DA protocols the exploit targets
+    pub da_types: &'static [DAType],
+}
+
+/// Every exploit penumbra knows about, by name.
+/// Per device applicability is left to [`Exploit::applies_to`].
+pub const EXPLOITS: &[ExploitEntry] = &[
+    ExploitEntry { name: "Kamakiri2", da_types: &[DAType::V5] },
+    ExploitEntry { name: "Carbonara", da_types: &[DAType::V5, DAType::V6] },
+    ExploitEntry { name: "HeapBait", da_types: &[DAType::V6] },
+];
+
+/// Looks up a registry entry, ignoring case
+pub fn find_exploit(name: &str) -> Option<&'static ExploitEntry> {
+    EXPLOITS.iter().find(|entry| entry.name.eq_ignore_ascii_case(name))
+}
+
+/// Decides whether `exploit` should run against the DA in use on this device.
+/// `disabled` holds the names turned off with `DeviceBuilder::disable_exploit`.
+pub fn should_run(
+    exploit: &dyn Exploit,
+    da_type: &DAType,
+    dev: &DevInfoData,
+    disabled: &[String],
+) -> bool {
+    let name = &exploit.get_meta().name;
+
+    if disabled.iter().any(|d| d.eq_ignore_ascii_case(name)) {
+        debug!("[Exploit] {} is disabled, skipping", name);
+        return false;
+    }
+
+    if find_exploit(name).is_none_or(|entry| !entry.da_types.contains(da_type)) {
+        debug!("[Exploit] {} doesn't target {:?} DAs, skipping", name, da_type);
+        return false;
+    }
+
+    if !exploit.applies_to(dev) {
+        debug!("[Exploit] {} doesn't apply to HW code 0x{:04X}, skipping", name, dev.hw_code);
+        return false;
+    }
+
+    true
 }
 
 pub fn get_v6_payload(data: &[u8], is_arm64: bool) -> &[u8] {
@@ -91,5 +148,6 @@ pub fn rebuild_patched_da(
         magic: original_da.magic,
         hw_code: original_da.hw_code,
         hw_sub_code: original_da.hw_sub_code,
+        sw_version: original_da.sw_version,
     }
 }
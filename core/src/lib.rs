@@ -2,17 +2,50 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+
+//! Penumbra talks to MediaTek devices in BootROM, preloader and DA mode.
+//!
+//! Everything frontends need is in [`api`]. The other modules are where the items are
+//! defined, and are only public for the crate's own tests and examples: they get
+//! reorganized without notice, so they're hidden from the docs.
+pub mod api;
+#[doc(hidden)]
+#[cfg(feature = "usb")]
 pub mod connection;
+#[doc(hidden)]
 pub mod core;
+#[doc(hidden)]
 pub mod da;
+#[doc(hidden)]
+#[cfg(feature = "da-protocols")]
 pub mod device;
+#[doc(hidden)]
 pub mod error;
-#[cfg(not(feature = "no_exploits"))]
+#[doc(hidden)]
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
 pub mod exploit;
+#[doc(hidden)]
 pub mod macros;
+#[doc(hidden)]
 pub mod utilities;
 
-pub use connection::port::{MTKPort, find_mtk_port};
+#[cfg(feature = "usb")]
+pub use connection::port::{
+    MTKPort,
+    PortInfo,
+    UsbLink,
+    UsbSpeed,
+    find_mtk_port,
+    list_mtk_ports,
+    open_mtk_port,
+    probe_mtk_port,
+    register_port,
+};
+#[cfg(feature = "usb")]
+pub use connection::watch::{PortEvent, PortWatch, watch_mtk_ports};
+#[cfg(feature = "da-protocols")]
 pub use device::{Device, DeviceBuilder};
+pub use error::{Error, Result};
 
+#[cfg(feature = "da-protocols")]
 const VERSION: &str = env!("CARGO_PKG_VERSION");
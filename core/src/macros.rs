@@ -1,12 +1,16 @@
 #[macro_export]
 macro_rules! exploit {
     ($exploit:ty, $proto:expr) => {{
-        #[cfg(not(feature = "no_exploits"))]
+        #[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
         {
             if $proto.patch {
                 let mut exploit = <$exploit>::new();
+                let dev = $proto.dev_info.get_data().await;
+                let da_type = $proto.da.da_type.clone();
 
-                if let Ok(result) = exploit.run($proto).await {
+                if $crate::exploit::should_run(&exploit, &da_type, &dev, &$proto.disabled_exploits)
+                    && let Ok(result) = exploit.run($proto).await
+                {
                     $proto.patch = !result;
 
                     if let Some(patched_da) = exploit.get_patched_da() {
@@ -0,0 +1,88 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+#[cfg(feature = "usb")]
+use std::pin::Pin;
+#[cfg(feature = "usb")]
+use std::task::{Context, Poll};
+
+use sha2::{Digest, Sha256};
+#[cfg(feature = "usb")]
+use tokio::io::{AsyncWrite, Sink, sink};
+
+/// Computes the SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Writer computing the SHA-256 of everything written through it to `inner`.
+///
+/// Each buffer is hashed right before being passed on, so a dump can be checksummed
+/// while it streams to disk, without reading the file back.
+///
+/// # Examples
+/// ```rust,ignore
+/// use penumbra::utilities::hash::HashingWriter;
+///
+/// let mut writer = HashingWriter::new(BufWriter::new(File::create("boot.bin").await?));
+/// device.upload("boot", &mut writer, &mut progress).await?;
+/// writer.flush().await?;
+/// println!("{}", hex::encode(writer.digest()));
+/// ```
+#[cfg(feature = "usb")]
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+#[cfg(feature = "usb")]
+impl<W: AsyncWrite + Unpin> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, hasher: Sha256::new() }
+    }
+
+    /// SHA-256 of the bytes written so far.
+    pub fn digest(&self) -> [u8; 32] {
+        self.hasher.clone().finalize().into()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(feature = "usb")]
+impl HashingWriter<Sink> {
+    /// A writer discarding everything written to it, only computing its SHA-256.
+    pub fn sink() -> Self {
+        Self::new(sink())
+    }
+}
+
+#[cfg(feature = "usb")]
+impl<W: AsyncWrite + Unpin> AsyncWrite for HashingWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+
+        // Only what the inner writer accepted, the rest comes again in the next call
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = result {
+            this.hasher.update(&buf[..written]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -7,6 +7,8 @@ use rand::TryRngCore;
 use rand::rngs::OsRng;
 use sha2::{Digest, Sha256};
 
+use crate::error::{Error, Result};
+
 pub struct RsaPrivateKey {
     pub n: BigUint,
     pub d: BigUint,
@@ -24,6 +26,104 @@ impl RsaPrivateKey {
     pub fn d(&self) -> &BigUint {
         &self.d
     }
+
+    /// Parses a DER encoded RSA private key, either PKCS#1 (`RSAPrivateKey`) or an
+    /// unencrypted PKCS#8 (`PrivateKeyInfo`) wrapping one.
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let (tag, body, _) = der_read(der)?;
+        if tag != DER_SEQUENCE {
+            return Err(Error::penumbra("RSA key is not a DER sequence"));
+        }
+
+        let (_, _, rest) = der_read(body)?; // version
+        let (tag, _, _) = der_read(rest)?;
+        if tag == DER_SEQUENCE {
+            // PKCS#8: algorithm identifier, then the PKCS#1 key as an octet string
+            let (_, _, rest) = der_read(rest)?;
+            let (tag, key, _) = der_read(rest)?;
+            if tag != DER_OCTET_STRING {
+                return Err(Error::penumbra("Invalid PKCS#8 private key"));
+            }
+            return Self::from_der(key);
+        }
+
+        let (n, rest) = der_integer(rest)?;
+        let (_, rest) = der_integer(rest)?; // public exponent
+        let (d, _) = der_integer(rest)?;
+        Ok(RsaPrivateKey::new(n, d))
+    }
+
+    /// Parses a PEM encoded RSA private key, `RSA PRIVATE KEY` or `PRIVATE KEY`
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let mut lines = pem.lines().map(str::trim).skip_while(|l| !l.starts_with("-----BEGIN"));
+        let label = lines.next().ok_or_else(|| Error::penumbra("No PEM block found"))?;
+        if label.contains("ENCRYPTED") {
+            return Err(Error::penumbra("Encrypted private keys are not supported"));
+        }
+
+        let body: String = lines.take_while(|l| !l.starts_with("-----END")).collect();
+        Self::from_der(&base64_decode(&body)?)
+    }
+}
+
+const DER_INTEGER: u8 = 0x02;
+const DER_OCTET_STRING: u8 = 0x04;
+const DER_SEQUENCE: u8 = 0x30;
+
+/// Reads one DER element, returning its tag, its content and what follows it
+fn der_read(data: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let invalid = || Error::penumbra("Invalid DER encoding");
+    let (&tag, rest) = data.split_first().ok_or_else(invalid)?;
+    let (&first, rest) = rest.split_first().ok_or_else(invalid)?;
+
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7F) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return Err(invalid());
+        }
+        let len = rest[..count].iter().fold(0usize, |len, &b| (len << 8) | b as usize);
+        (len, &rest[count..])
+    };
+
+    if rest.len() < len {
+        return Err(invalid());
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
+
+fn der_integer(data: &[u8]) -> Result<(BigUint, &[u8])> {
+    let (tag, value, rest) = der_read(data)?;
+    if tag != DER_INTEGER {
+        return Err(Error::penumbra("Expected a DER integer"));
+    }
+    Ok((BigUint::from_bytes_be(value), rest))
+}
+
+fn base64_decode(input: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+
+    for c in input.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(Error::penumbra("Invalid base64 in PEM")),
+        };
+        acc = ((acc << 6) | value as u32) & 0xFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+
+    Ok(out)
 }
 
 /// Mask Generation Function 1 (MGF1) using SHA-256
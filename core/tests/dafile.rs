@@ -0,0 +1,107 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::api::sha256;
+use penumbra::da::{DA, DAEntryRegion, DAType, hash_fixup};
+
+const SIG_LEN: usize = 0x100;
+
+fn region(data: Vec<u8>, sig_len: usize) -> DAEntryRegion {
+    DAEntryRegion {
+        length: data.len() as u32,
+        region_length: (data.len() - sig_len) as u32,
+        data,
+        offset: 0,
+        addr: 0x4000_0000,
+        sig_len: sig_len as u32,
+    }
+}
+
+/// A signed DA2, its code filled with `fill`
+fn da2(fill: u8) -> DAEntryRegion {
+    let mut data = vec![fill; 0x1000];
+    data.extend([0x51; SIG_LEN]);
+    region(data, SIG_LEN)
+}
+
+/// A V6 style DA1, with the digest of `da2` in the 0x30 bytes before its signature
+fn da1_v6(da2: &DAEntryRegion) -> DAEntryRegion {
+    let mut data = vec![0xD1; 0x800];
+    data.extend(da2.digest());
+    data.extend([0xEE; 0xC]);
+    data.extend([0; 4]);
+    data.extend([0x51; SIG_LEN]);
+    region(data, SIG_LEN)
+}
+
+/// A V5 style DA1, with the digest of `da2` 0x30 bytes before the MMU map string
+fn da1_v5(da2: &DAEntryRegion) -> DAEntryRegion {
+    let mut data = vec![0xD1; 0x800];
+    data.extend(da2.digest());
+    data.extend([0xEE; 0x10]);
+    data.extend(b"MMU MAP: VA 0x%x");
+    data.extend([0xD1; 0x200]);
+    region(data, 0)
+}
+
+fn da(da_type: DAType, da1: DAEntryRegion, da2: DAEntryRegion) -> DA {
+    DA {
+        da_type,
+        regions: vec![region(vec![0; 0x100], 0), da1, da2],
+        magic: 0xDADA,
+        hw_code: 0x6768,
+        hw_sub_code: 0xCA00,
+        sw_version: 0,
+    }
+}
+
+#[test]
+fn digest_leaves_the_signature_out() {
+    let signed = da2(0xA2);
+    assert_eq!(signed.digest(), sha256(&[0xA2; 0x1000]));
+
+    let mut resigned = signed.clone();
+    resigned.data[0x1000..].fill(0x52);
+    assert_eq!(resigned.digest(), signed.digest());
+}
+
+#[test]
+fn hash_fixup_patches_the_digest() {
+    let old_da2 = da2(0xA2);
+    let mut new_da2 = old_da2.clone();
+    new_da2.data[0x10..0x14].copy_from_slice(&[0x1F, 0x20, 0x03, 0xD5]);
+
+    let cases = [("v5", DAType::V5, da1_v5(&old_da2)), ("v6", DAType::V6, da1_v6(&old_da2))];
+    for (name, da_type, da1) in cases {
+        let mut patched = da1.clone();
+        let offset = hash_fixup(&mut patched, &old_da2, &new_da2);
+
+        // Where the DA looks for it
+        let expected = da(da_type, da1.clone(), old_da2.clone()).find_da_hash_offset();
+        assert_eq!(offset, expected, "{}", name);
+        assert_eq!(offset, Some(0x800), "{}", name);
+        assert_eq!(patched.data[0x800..0x820], new_da2.digest(), "{}", name);
+
+        // Nothing else moved
+        assert_eq!(patched.data.len(), da1.data.len());
+        assert_eq!(patched.data[..0x800], da1.data[..0x800], "{}", name);
+        assert_eq!(patched.data[0x820..], da1.data[0x820..], "{}", name);
+    }
+}
+
+#[test]
+fn hash_fixup_needs_the_digest() {
+    let old_da2 = da2(0xA2);
+    let new_da2 = da2(0xB2);
+
+    // A DA1 made for another DA2, and one hashing DA2 with SHA-1
+    let mut other = da1_v6(&da2(0xC2));
+    let mut sha1 = region([vec![0xD1; 0x800], vec![0x5A; 20], vec![0; SIG_LEN]].concat(), SIG_LEN);
+
+    for da1 in [&mut other, &mut sha1] {
+        let before = da1.data.clone();
+        assert_eq!(hash_fixup(da1, &old_da2, &new_da2), None);
+        assert_eq!(da1.data, before);
+    }
+}
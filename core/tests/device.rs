@@ -0,0 +1,408 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+
+use penumbra::DeviceBuilder;
+use penumbra::api::sha256;
+use penumbra::connection::mock::{MockMTKPort, Transcript};
+use penumbra::core::devinfo::DevInfoData;
+use penumbra::core::journal::{Journal, JournalState};
+use penumbra::core::progress::Progress;
+use penumbra::core::storage::emmc::{EmmcInfo, EmmcStorage};
+use penumbra::core::storage::{
+    BootRegion,
+    EmmcPartition,
+    EraseMethod,
+    EraseReport,
+    GptSource,
+    Partition,
+    PartitionKind,
+};
+use penumbra::da::xflash::Cmd;
+use penumbra::device::Device;
+
+const HW_CODE: u16 = 0x707;
+
+/// A V5 DA file with a single entry for [`HW_CODE`] and no regions, enough to reattach to a
+/// DA that is already running
+fn da_file() -> Vec<u8> {
+    let mut data = vec![0u8; 0x6C + 0xDC];
+    data[..0x12].copy_from_slice(b"MTK_DOWNLOAD_AGENT");
+    data[0x68..0x6C].copy_from_slice(&1u32.to_le_bytes());
+    data[0x6C..0x6E].copy_from_slice(&0xDADAu16.to_le_bytes());
+    data[0x6E..0x70].copy_from_slice(&0x6768u16.to_le_bytes());
+    data
+}
+
+fn emmc() -> EmmcStorage {
+    EmmcStorage {
+        info: EmmcInfo {
+            kind: 1,
+            block_size: 0x200,
+            boot1_size: 0x40_0000,
+            boot2_size: 0x40_0000,
+            rpmb_size: 0x100_0000,
+            gp1_size: 0,
+            gp2_size: 0,
+            gp3_size: 0,
+            gp4_size: 0,
+            user_size: 0x7_4000_0000,
+            cid: vec![0; 16],
+            fwver: 0,
+            erase_group_size: 0x8_0000,
+        },
+    }
+}
+
+fn user(name: &str, size: u64, address: u64) -> Partition {
+    Partition::new(name, size, address, PartitionKind::Emmc(EmmcPartition::User))
+}
+
+fn builder(port: MockMTKPort) -> DeviceBuilder {
+    DeviceBuilder::default().with_mtk_port(Box::new(port)).with_da_data(da_file())
+}
+
+/// A device reattached to a running XFlash DA, with a partition table read from `source`
+async fn da_device(port: MockMTKPort, source: GptSource) -> Device {
+    reattach(builder(port), source).await
+}
+
+async fn reattach(builder: DeviceBuilder, source: GptSource) -> Device {
+    let mut device = builder.build().unwrap();
+
+    let data = DevInfoData {
+        chipset: String::from("MT6768"),
+        soc_id: Vec::new(),
+        meid: vec![0; 16],
+        hw_code: HW_CODE,
+        partitions: vec![user("pgpt", 0x8800, 0), user("boot", 0x200_0000, 0x800_0000)],
+        gpt_source: source,
+        gpt_report: None,
+        storage: Some(Arc::new(emmc())),
+        target_config: 0,
+        usb_speed: None,
+    };
+    device.reinit(data).await.unwrap();
+    device
+}
+
+/// An XFlash packet answered with `status`
+fn xflash_send(transcript: Transcript, data: &[u8], status: u32) -> Transcript {
+    transcript.write_packet(data.to_vec()).read_packet(status.to_le_bytes())
+}
+
+/// Parameters of an XFlash read, write or erase of `size` bytes at `address` of the user area
+fn xflash_param(address: u64, size: u64) -> Vec<u8> {
+    let mut param = Vec::new();
+    param.extend_from_slice(&1u32.to_le_bytes());
+    param.extend_from_slice(&(EmmcPartition::User as u32).to_le_bytes());
+    param.extend_from_slice(&address.to_le_bytes());
+    param.extend_from_slice(&size.to_le_bytes());
+    param.extend_from_slice(&[0u8; 32]);
+    param
+}
+
+/// `data` written at `address` of the user area, in a single chunk
+fn xflash_write(transcript: Transcript, address: u64, data: &[u8]) -> Transcript {
+    let transcript = xflash_send(transcript, &(Cmd::DeviceCtrl as u32).to_le_bytes(), 0);
+    let transcript = xflash_send(transcript, &(Cmd::GetPacketLength as u32).to_le_bytes(), 0);
+    let lengths = [0x1000u32.to_le_bytes(), 0x1000u32.to_le_bytes()].concat();
+    let transcript = transcript.read_packet(lengths).read_packet(0u32.to_le_bytes());
+    xflash_write_data(transcript, address, data)
+}
+
+/// Like [`xflash_write`], once the packet length is known
+fn xflash_write_data(transcript: Transcript, address: u64, data: &[u8]) -> Transcript {
+    let param = xflash_param(address, data.len() as u64);
+    let transcript = xflash_send(transcript, &(Cmd::WriteData as u32).to_le_bytes(), 0);
+    let transcript = xflash_send(transcript, &param, 0);
+
+    let checksum = data.iter().map(|&b| b as u32).sum::<u32>() & 0xFFFF;
+    transcript
+        .write_packet(0u32.to_le_bytes())
+        .write_packet(checksum.to_le_bytes())
+        .write_packet(data.to_vec())
+        .read_packet(0u32.to_le_bytes())
+        .read_packet(0u32.to_le_bytes())
+}
+
+/// `data` read at `address` of the user area, in a single chunk
+fn xflash_read(transcript: Transcript, address: u64, data: &[u8]) -> Transcript {
+    let param = xflash_param(address, data.len() as u64);
+    let transcript = xflash_send(transcript, &(Cmd::ReadData as u32).to_le_bytes(), 0);
+    let transcript = xflash_send(transcript, &param, 0);
+
+    let transcript = transcript.read_packet(0u32.to_le_bytes()).read_packet(data.to_vec());
+    xflash_send(transcript, &[0u8; 4], 0)
+}
+
+/// `size` bytes erased at `address` of the user area, the DA answering as done right away
+fn xflash_erase(transcript: Transcript, address: u64, size: u64) -> Transcript {
+    let transcript = xflash_send(transcript, &(Cmd::Format as u32).to_le_bytes(), 0);
+    let transcript = xflash_send(transcript, &xflash_param(address, size), 0);
+    transcript.read_packet(0x40040005u32.to_le_bytes())
+}
+
+#[tokio::test]
+async fn fallback_table_refuses_partition_writes() {
+    for source in [GptSource::Secondary, GptSource::Synthetic] {
+        // Nothing may reach the port
+        let mut device = da_device(MockMTKPort::new(Transcript::new()), source).await;
+        let part = device.partition("boot").await.unwrap();
+
+        let mut image = &[0u8; 0x20][..];
+        let mut progress = |_: &Progress| {};
+        let result = device.write_resolved_partition(&part, &mut image, 0x20, &mut progress).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("not read from the primary GPT"), "{:?}: {}", source, err);
+    }
+}
+
+#[tokio::test]
+async fn fallback_table_allows_gpt_restore() {
+    let image = [0x5Au8; 0x20];
+    for source in [GptSource::Secondary, GptSource::Synthetic] {
+        let port = MockMTKPort::new(xflash_write(Transcript::new(), 0, &image));
+        let mut device = da_device(port.clone(), source).await;
+        let part = device.partition("pgpt").await.unwrap();
+
+        let mut reader = &image[..];
+        let mut progress = |_: &Progress| {};
+        device.write_resolved_partition(&part, &mut reader, 0x20, &mut progress).await.unwrap();
+        assert!(port.is_done(), "{:?}", source);
+    }
+}
+
+#[tokio::test]
+async fn allowed_fallback_table_writes_partitions() {
+    let image = [0x5Au8; 0x20];
+    let port = MockMTKPort::new(xflash_write(Transcript::new(), 0x800_0000, &image));
+    let mut device = da_device(port.clone(), GptSource::Synthetic).await;
+    device.set_allow_secondary_gpt(true);
+    let part = device.partition("boot").await.unwrap();
+
+    let mut reader = &image[..];
+    let mut progress = |_: &Progress| {};
+    device.write_resolved_partition(&part, &mut reader, 0x20, &mut progress).await.unwrap();
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn patches_are_journaled() {
+    let block = [0u8; 0x200];
+    let mut patched = block;
+    patched[0x10..0x14].copy_from_slice(b"ABCD");
+    let transcript = xflash_read(Transcript::new(), 0x800_0000, &block);
+    let transcript = xflash_write(transcript, 0x800_0000, &patched);
+
+    let path = std::env::temp_dir().join(format!("penumbra-patch-journal-{}", std::process::id()));
+    std::fs::remove_file(&path).ok();
+    let port = MockMTKPort::new(transcript);
+    let builder = builder(port.clone()).with_journal(Journal::new(&path));
+    let mut device = reattach(builder, GptSource::Primary).await;
+
+    let original = device.patch_partition("boot", 0x10, b"ABCD", false).await.unwrap();
+    assert_eq!(original, [0u8; 4]);
+    assert!(port.is_done());
+
+    let entries = Journal::new(&path).entries().unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(entries.len(), 1);
+    assert_eq!((entries[0].partition.as_str(), entries[0].size), ("boot", 0x200));
+    assert_eq!(entries[0].state, JournalState::Completed);
+    assert_eq!(entries[0].hash, Some(hex::encode(sha256(&patched))));
+}
+
+#[tokio::test]
+async fn stale_partitions_are_refused() {
+    // Nothing may reach the port
+    let mut device = da_device(MockMTKPort::new(Transcript::new()), GptSource::Primary).await;
+    let boot = device.partition("boot").await.unwrap();
+
+    let mut renamed = boot.clone();
+    renamed.name = "boot_a".into();
+    let mut resized = boot.clone();
+    resized.size /= 2;
+
+    // The table was read again, and `boot` moved since it was resolved
+    let moved = user("boot", 0x200_0000, 0x1000_0000);
+    let table = vec![user("pgpt", 0x8800, 0), moved.clone()];
+    device.dev_info.set_partitions(table).await;
+
+    for (part, expected) in [
+        (&boot, "doesn't match the device partition table"),
+        (&resized, "doesn't match the device partition table"),
+        (&renamed, "not found"),
+    ] {
+        let mut progress = |_: &Progress| {};
+        let mut data = Vec::new();
+        let result = device.read_resolved_partition(part, &mut data, &mut progress).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(expected), "read {:?}: {}", part, err);
+
+        let mut image = &[0u8; 0x20][..];
+        let result = device.write_resolved_partition(part, &mut image, 0x20, &mut progress).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(expected), "write {:?}: {}", part, err);
+    }
+
+    assert_eq!(device.partition("boot").await.unwrap(), moved);
+}
+
+#[tokio::test]
+async fn partitions_above_4gib() {
+    const ADDRESS: u64 = 0x1_2340_0000;
+    let image = [0xA5u8; 0x200];
+    let transcript = xflash_read(Transcript::new(), ADDRESS, &image);
+    let transcript = xflash_write(transcript, ADDRESS, &image);
+    let port = MockMTKPort::new(transcript);
+    let mut device = da_device(port.clone(), GptSource::Primary).await;
+    let table = vec![user("pgpt", 0x8800, 0), user("userdata", 0x200, ADDRESS)];
+    device.dev_info.set_partitions(table).await;
+
+    let mut totals = Vec::new();
+    let mut data = Vec::new();
+    let mut progress = |p: &Progress| totals.push((p.written, p.total));
+    device.read_partition("userdata", &mut progress, &mut data).await.unwrap();
+    assert_eq!(data, image);
+
+    let mut reader = &image[..];
+    device.write_partition("userdata", &mut reader, &mut progress).await.unwrap();
+    assert!(port.is_done());
+    assert_eq!(totals.last(), Some(&(0x200, 0x200)));
+}
+
+#[tokio::test]
+async fn range_reads_above_4gib() {
+    const ADDRESS: u64 = 0x1_0000_0000;
+    let mut block = [0u8; 0x200];
+    block[0x10..0x30].fill(0x5A);
+    let transcript = xflash_read(Transcript::new(), ADDRESS + 0x1_0000_0000, &block);
+    let port = MockMTKPort::new(transcript);
+    let mut device = da_device(port.clone(), GptSource::Primary).await;
+    let table = vec![user("pgpt", 0x8800, 0), user("userdata", 0x1_8000_0000, ADDRESS)];
+    device.dev_info.set_partitions(table).await;
+
+    let data = device.read_partition_range("userdata", 0x1_0000_0010, 0x20).await.unwrap();
+    assert_eq!(data, [0x5A; 0x20]);
+    assert!(port.is_done());
+
+    // 0x100 bytes once truncated to a 32 bit usize
+    let result = device.read_partition_range("userdata", 0x1_0000_0000, 0x1_0000_0100).await;
+    assert!(result.unwrap_err().to_string().contains("crosses the end"));
+}
+
+#[tokio::test]
+async fn out_of_range_partitions_are_refused() {
+    // Nothing may reach the port
+    let mut device = da_device(MockMTKPort::new(Transcript::new()), GptSource::Primary).await;
+    // A table made for a storage bigger than the 29 GiB user area
+    let userdata = user("userdata", 0x2_0000, 0x7_3FFF_0000);
+    let table = vec![user("pgpt", 0x8800, 0), userdata.clone()];
+    device.dev_info.set_partitions(table).await;
+    assert_eq!(device.out_of_range_partitions().await, [userdata]);
+
+    let mut progress = |_: &Progress| {};
+    let mut data = Vec::new();
+    let result = device.read_partition("userdata", &mut progress, &mut data).await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("extends past the end"), "read: {}", err);
+
+    let mut image = &[0u8; 0x20][..];
+    let result = device.write_partition("userdata", &mut image, &mut progress).await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("extends past the end"), "write: {}", err);
+
+    let result = device.upload_range("userdata", 0, 0x200, &mut data, &mut progress).await;
+    assert!(result.unwrap_err().to_string().contains("extends past the end"));
+}
+
+#[tokio::test]
+async fn boot_region_writes_are_checked() {
+    // Nothing may reach the port
+    let mut device = da_device(MockMTKPort::new(Transcript::new()), GptSource::Primary).await;
+    let mut progress = |_: &Progress| {};
+
+    // Boot1 holds 4 MiB
+    let mut image = &[0u8; 0x20][..];
+    let result = device.write_boot_region(BootRegion::Boot1, &mut image, 0x40_0200, &mut progress);
+    let err = result.await.unwrap_err().to_string();
+    assert!(err.contains("larger than boot1 (0x400200 > 0x400000"), "{}", err);
+
+    let mut boot_img = [0u8; 0x1000];
+    boot_img[..8].copy_from_slice(b"ANDROID!");
+    let mut image = &boot_img[..];
+    let result = device.write_boot_region(BootRegion::Boot2, &mut image, 0x1000, &mut progress);
+    let err = result.await.unwrap_err().to_string();
+    assert!(err.contains("partition 'boot2' is in the boot region"), "{}", err);
+}
+
+#[tokio::test]
+async fn erase_ranges_are_checked() {
+    // Nothing may reach the port
+    let mut device = da_device(MockMTKPort::new(Transcript::new()), GptSource::Primary).await;
+    let user = PartitionKind::Emmc(EmmcPartition::User);
+    let rpmb = PartitionKind::Emmc(EmmcPartition::Rpmb);
+
+    let cases = [
+        (0x0, 0x0, user, "empty range"),
+        (0x100, 0x8_0000, user, "not aligned to the block size (0x200)"),
+        (0x0, 0x8_0100, user, "not aligned to the block size (0x200)"),
+        (0x7_3FF8_0000, 0x8_0200, user, "crosses the end of region"),
+        (0x0, 0x200, rpmb, "read-only"),
+        (0x0, 0x200, PartitionKind::Emmc(EmmcPartition::Gp1), "not available"),
+    ];
+    for (address, size, section, expected) in cases {
+        let mut progress = |_: &Progress| {};
+        let result = device.erase_range(address, size, section, &mut progress).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(expected), "0x{:X}+0x{:X}: {}", address, size, err);
+    }
+}
+
+#[tokio::test]
+async fn erases_zero_fill_partial_groups() {
+    // The DA reported 512 KiB erase groups, the partition starts and ends 4 KiB off them
+    const GROUP: u64 = 0x8_0000;
+    let zeros = [0u8; 0x1000];
+    let transcript = xflash_write(Transcript::new(), 0x7FF_F000, &zeros);
+    let transcript = xflash_erase(transcript, 0x800_0000, GROUP);
+    let transcript = xflash_write_data(transcript, 0x808_0000, &zeros);
+    let port = MockMTKPort::new(transcript);
+    let mut device = da_device(port.clone(), GptSource::Primary).await;
+    let table = vec![user("pgpt", 0x8800, 0), user("cache", GROUP + 0x2000, 0x7FF_F000)];
+    device.dev_info.set_partitions(table).await;
+
+    let mut totals = Vec::new();
+    let mut progress = |p: &Progress| totals.push((p.written, p.total));
+    let report = device.erase_partition("cache", &mut progress).await.unwrap();
+    let expected =
+        EraseReport { method: EraseMethod::Erase, erased: GROUP, zero_filled: 0x2000, skipped: 0 };
+    assert_eq!(report, expected);
+    assert!(port.is_done());
+    assert_eq!(totals.last(), Some(&(GROUP + 0x2000, GROUP + 0x2000)));
+}
+
+#[tokio::test]
+async fn small_partitions_are_formatted_whole() {
+    // Smaller than an erase group, so the DA gets nothing to erase
+    let transcript = xflash_write(Transcript::new(), 0x800_0000, &[0u8; 0x1000]);
+    let port = MockMTKPort::new(transcript);
+    let mut device = da_device(port.clone(), GptSource::Primary).await;
+    let table = vec![user("pgpt", 0x8800, 0), user("misc", 0x1000, 0x800_0000)];
+    device.dev_info.set_partitions(table).await;
+
+    let mut progress = |_: &Progress| {};
+    let report = device.format("misc", &mut progress).await.unwrap();
+    let expected =
+        EraseReport { method: EraseMethod::Erase, erased: 0, zero_filled: 0x1000, skipped: 0 };
+    assert_eq!(report, expected);
+    assert!(port.is_done());
+
+    // Skipping the partial groups leaves it untouched, without reaching the port
+    device.set_skip_partial_erase(true);
+    let report = device.format("misc", &mut progress).await.unwrap();
+    assert_eq!(report, EraseReport { zero_filled: 0, skipped: 0x1000, ..expected });
+}
@@ -0,0 +1,104 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::error::{
+    Domain,
+    Severity,
+    XFlashError,
+    XFlashErrorKind,
+    XmlError,
+    XmlErrorKind,
+};
+
+#[test]
+fn xml_error_strings() {
+    let cases = [
+        ("ERR!UNSUPPORTED", XmlErrorKind::UnsupportedCmd),
+        ("ERR!CANCEL", XmlErrorKind::Cancel),
+        ("ERR!CHECKSUM", XmlErrorKind::ChecksumMismatch),
+        ("ERR!CHKSUM-MISMATCH", XmlErrorKind::ChecksumMismatch),
+        ("ERR!STORAGE-NOT-READY", XmlErrorKind::StorageNotReady),
+        ("ERR!NOT_READY", XmlErrorKind::StorageNotReady),
+        ("ERR!PARTITION-NOT-FOUND", XmlErrorKind::FileNotFound),
+        ("ERR!NOT_FOUND", XmlErrorKind::FileNotFound),
+        ("ERR!FILE-NOT-EXIST", XmlErrorKind::FileNotFound),
+        ("ERR!NOT_EXIST", XmlErrorKind::FileNotFound),
+        ("ERR!INSUFFICIENT", XmlErrorKind::InsufficientBuffer),
+        ("ERR!BUFFER-OVERFLOW", XmlErrorKind::InsufficientBuffer),
+        ("ERR!IMAGE-TOO-LARGE", XmlErrorKind::InsufficientBuffer),
+        ("ERR!TOO_LARGE", XmlErrorKind::InsufficientBuffer),
+        ("ERR!VERIFY", XmlErrorKind::VerifyFailed),
+        ("ERR!VERIFICATION-FAILED", XmlErrorKind::VerifyFailed),
+        ("ERR!GENERIC", XmlErrorKind::Unknown),
+        ("", XmlErrorKind::Unknown),
+    ];
+
+    for (msg, kind) in cases {
+        let err = XmlError::from_message(msg.as_bytes());
+        assert_eq!(err.kind, kind, "{}", msg);
+        assert_eq!(err.message, msg);
+
+        let lower = XmlError::from_message(msg.to_ascii_lowercase().as_bytes());
+        assert_eq!(lower.kind, kind, "{}", msg);
+    }
+}
+
+#[test]
+fn xml_error_message() {
+    let err = XmlError::from_message(b"ERR!CHECKSUM\0\0");
+    assert_eq!(err.kind, XmlErrorKind::ChecksumMismatch);
+    assert_eq!(err.message, "ERR!CHECKSUM");
+    assert_eq!(err.to_string(), "Checksum mismatch (ERR!CHECKSUM)");
+
+    let err = XmlError::from_message(b"ERR!\xFFNOT-READY");
+    assert_eq!(err.kind, XmlErrorKind::StorageNotReady);
+    assert_eq!(err.message, "ERR!\u{FFFD}NOT-READY");
+}
+
+#[test]
+fn xflash_status_bits() {
+    let hash = XFlashError::from_code(0xC0070004);
+    assert_eq!(hash.kind, XFlashErrorKind::DaHashMismatch);
+    assert_eq!((hash.severity(), hash.domain()), (Severity::Error, Domain::Da));
+    assert!(hash.severity().is_failure());
+
+    // Not a known status, but the bits still say what it is
+    let info = XFlashError::from_code(0x40010001);
+    assert_eq!(info.kind, XFlashErrorKind::Unknown);
+    assert_eq!((info.severity(), info.domain()), (Severity::Info, Domain::Common));
+    assert!(!info.severity().is_failure());
+
+    let cases = [
+        (0x00000000, Severity::Success, Domain::Unknown(0)),
+        (0x80040001, Severity::Warning, Domain::Device),
+        (0xC0010003, Severity::Error, Domain::Common),
+        (0xC0020004, Severity::Error, Domain::Security),
+        (0xC0030003, Severity::Error, Domain::Library),
+        (0xC0090001, Severity::Error, Domain::Unknown(9)),
+    ];
+    for (code, severity, domain) in cases {
+        let err = XFlashError::from_code(code);
+        assert_eq!((err.severity(), err.domain()), (severity, domain), "{:#010x}", code);
+    }
+}
+
+#[test]
+fn xflash_status_families() {
+    let security = [0xC0020004, 0xC0020011];
+    let storage = [0xC0030006, 0xC0040006, 0xC004000B, 0xC0040030, 0xC0040042, 0xC0040060];
+    let other = [0xC0010003, 0xC004000A, 0xC0070004, 0x40010001];
+
+    for code in security {
+        let err = XFlashError::from_code(code);
+        assert!(err.is_security() && !err.is_storage(), "{:#010x}", code);
+    }
+    for code in storage {
+        let err = XFlashError::from_code(code);
+        assert!(err.is_storage() && !err.is_security(), "{:#010x}", code);
+    }
+    for code in other {
+        let err = XFlashError::from_code(code);
+        assert!(!err.is_storage() && !err.is_security(), "{:#010x}", code);
+    }
+}
@@ -0,0 +1,81 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::core::journal::{Journal, JournalState};
+
+#[test]
+fn unfinished_operations_dangle() {
+    let journal = concat!(
+        "begin\t1\t100\tMEID\tboot\t4096\n",
+        "begin\t2\t101\tMEID\tsystem\t8192\n",
+        "end\t1\tcompleted\tabcd\n",
+    );
+
+    let entries = Journal::parse(journal.as_bytes());
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].state, JournalState::Completed);
+    assert_eq!(entries[0].hash.as_deref(), Some("abcd"));
+    assert_eq!(entries[1].partition, "system");
+    assert_eq!(entries[1].state, JournalState::Started);
+    assert_eq!(entries[1].hash, None);
+}
+
+#[test]
+fn failed_and_interrupted_operations_dont_dangle() {
+    let journal = concat!(
+        "begin\t1\t100\tMEID\tboot\t4096\n",
+        "end\t1\tfailed\t\n",
+        "begin\t2\t101\tMEID\tvbmeta\t4096\n",
+        "end\t2\tinterrupted\t\n",
+    );
+
+    let states: Vec<_> = Journal::parse(journal.as_bytes()).iter().map(|e| e.state).collect();
+    assert_eq!(states, [JournalState::Failed, JournalState::Interrupted]);
+}
+
+#[test]
+fn torn_lines_are_skipped() {
+    // A crash can leave the last line half written
+    let journal = concat!(
+        "begin\t1\t100\tMEID\tboot\t4096\n",
+        "end\t1\tcomple",
+    );
+
+    let entries = Journal::parse(journal.as_bytes());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].state, JournalState::Started);
+
+    let journal = concat!(
+        "begin\t1\t100\tMEID\tbo",
+        "begin\tnot-a-number\t100\tMEID\tboot\t4096\n",
+        "garbage\n",
+        "begin\t2\t101\tMEID\tboot\t4096\n",
+    );
+    let ids: Vec<u64> = Journal::parse(journal.as_bytes()).iter().map(|e| e.id).collect();
+    assert_eq!(ids, [2]);
+}
+
+#[test]
+fn end_without_begin_is_ignored() {
+    let journal = concat!("end\t7\tcompleted\t\n", "begin\t8\t100\tMEID\tboot\t4096\n");
+
+    let entries = Journal::parse(journal.as_bytes());
+    assert_eq!(entries.len(), 1);
+    assert_eq!((entries[0].id, entries[0].state), (8, JournalState::Started));
+}
+
+#[test]
+fn acknowledged_operations_are_reported_once() {
+    let path = std::env::temp_dir().join(format!("penumbra-journal-{}", std::process::id()));
+    let journal = Journal::new(&path);
+    let done = journal.begin("MEID", "boot", 0x1000).unwrap();
+    journal.finish(done, JournalState::Completed, Some(&[0xAB])).unwrap();
+    let dangling = journal.begin("MEID", "system", 0x2000).unwrap();
+
+    let acknowledged = journal.acknowledge().unwrap();
+    assert_eq!(acknowledged.iter().map(|e| e.id).collect::<Vec<_>>(), [dangling]);
+    assert!(journal.dangling().unwrap().is_empty());
+
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,34 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::connection::port::{ConnectionType, port_connection_type};
+use penumbra::connection::{known_ports, register_port};
+
+#[test]
+fn registered_port_is_known() {
+    assert_eq!(port_connection_type(0x1234, 0x0001), None);
+    register_port(0x1234, 0x0001, ConnectionType::Preloader).unwrap();
+
+    assert_eq!(port_connection_type(0x1234, 0x0001), Some(ConnectionType::Preloader));
+    assert!(known_ports().contains(&(0x1234, 0x0001, ConnectionType::Preloader)));
+}
+
+#[test]
+fn registered_port_overrides_builtin() {
+    assert_eq!(port_connection_type(0x0FCE, 0xF200), Some(ConnectionType::Brom));
+    register_port(0x0FCE, 0xF200, ConnectionType::Preloader).unwrap();
+    register_port(0x0FCE, 0xF200, ConnectionType::Preloader).unwrap();
+
+    assert_eq!(port_connection_type(0x0FCE, 0xF200), Some(ConnectionType::Preloader));
+    let entries = known_ports().into_iter().filter(|p| p.0 == 0x0FCE && p.1 == 0xF200);
+    assert_eq!(entries.collect::<Vec<_>>(), [(0x0FCE, 0xF200, ConnectionType::Preloader)]);
+}
+
+#[test]
+fn invalid_ports_are_rejected() {
+    assert!(register_port(0, 0x0001, ConnectionType::Brom).is_err());
+    assert!(register_port(0x1234, 0, ConnectionType::Brom).is_err());
+    assert!("bootrom".parse::<ConnectionType>().is_err());
+    assert_eq!("Preloader".parse::<ConnectionType>().unwrap(), ConnectionType::Preloader);
+}
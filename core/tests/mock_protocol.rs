@@ -0,0 +1,592 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use penumbra::UsbSpeed;
+use penumbra::error::Error;
+use penumbra::connection::Connection;
+use penumbra::connection::mock::{MockMTKPort, Step, Transcript, record};
+use penumbra::connection::retry::RetryPolicy;
+use penumbra::core::auth::{AuthManager, SignData, SignPurpose, SignRequest, Signer};
+use penumbra::core::devinfo::DeviceInfo;
+use penumbra::core::events::{CoreEvent, event_channel};
+use penumbra::core::storage::{EmmcPartition, PartitionKind};
+use penumbra::da::legacy::LegacyFlavor;
+use penumbra::da::protocol::BootMode;
+use penumbra::da::xflash::{Cmd, DA_USB_ID};
+use penumbra::da::xml::{
+    CMD_END,
+    CMD_START,
+    GetSysProperty,
+    HOST_CMDS,
+    HostSupportedCommands,
+    SecurityGetDevFwInfo,
+    SecuritySetFlashPolicy,
+    SupportedCmds,
+    create_cmd,
+};
+use penumbra::da::{DA, DAEntryRegion, DAProtocol, DAType, Legacy, XFlash, Xml};
+
+fn da(da_type: DAType) -> DA {
+    DA {
+        da_type,
+        regions: Vec::new(),
+        magic: 0xDADA,
+        hw_code: 0x6768,
+        hw_sub_code: 0xCA00,
+        sw_version: 0,
+    }
+}
+
+fn xflash(port: MockMTKPort) -> XFlash {
+    let conn = Connection::new(Box::new(port));
+    XFlash::new(conn, da(DAType::V5), DeviceInfo::new(), None, false)
+}
+
+fn xml(port: MockMTKPort) -> Xml {
+    let conn = Connection::new(Box::new(port));
+    Xml::new(conn, da(DAType::V6), DeviceInfo::new(), false)
+}
+
+fn legacy(port: MockMTKPort) -> Legacy {
+    let conn = Connection::new(Box::new(port));
+    Legacy::new(conn, da(DAType::Legacy), DeviceInfo::new())
+}
+
+/// An XFlash command the DA accepts with `status`
+fn xflash_cmd(transcript: Transcript, cmd: Cmd, status: u32) -> Transcript {
+    transcript.write_packet((cmd as u32).to_le_bytes()).read_packet(status.to_le_bytes())
+}
+
+/// `GetUsbSpeed` answered with `speed`
+fn xflash_usb_speed(transcript: Transcript, speed: &str) -> Transcript {
+    let transcript = xflash_cmd(transcript, Cmd::DeviceCtrl, 0);
+    let transcript = xflash_cmd(transcript, Cmd::GetUsbSpeed, 0);
+    transcript.read_packet(speed.as_bytes()).read_packet(0u32.to_le_bytes())
+}
+
+/// Lifetime of an XML command sent by the host, up to the DA acknowledging it
+fn xml_cmd(transcript: Transcript, cmd: String) -> Transcript {
+    transcript
+        .read_packet(CMD_START)
+        .write_packet(*b"OK\0")
+        .write_packet(cmd.into_bytes())
+        .read_packet(*b"OK\0")
+}
+
+#[tokio::test]
+async fn xflash_reads_usb_speed() {
+    let port = MockMTKPort::new(xflash_usb_speed(Transcript::new(), "high-speed\0"));
+    let mut xflash = xflash(port.clone());
+
+    assert_eq!(xflash.get_usb_speed().await.unwrap(), UsbSpeed::High);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xflash_keeps_speed_when_switch_is_refused() {
+    let transcript = xflash_usb_speed(Transcript::new(), "full-speed\0");
+    let transcript = xflash_cmd(transcript, Cmd::SwitchUsbSpeed, 0xC0010004);
+    let port = MockMTKPort::new(transcript);
+    let mut xflash = xflash(port.clone());
+
+    assert!(!xflash.switch_usb_speed().await.unwrap());
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xflash_switches_usb_speed() {
+    let transcript = xflash_usb_speed(Transcript::new(), "full-speed\0");
+    let transcript = xflash_cmd(transcript, Cmd::SwitchUsbSpeed, 0);
+    let transcript =
+        transcript.write_packet(DA_USB_ID.to_le_bytes()).read_packet(0u32.to_le_bytes());
+    let transcript = xflash_usb_speed(transcript, "high-speed\0");
+    let port = MockMTKPort::new(transcript);
+    let mut xflash = xflash(port.clone());
+
+    assert!(xflash.switch_usb_speed().await.unwrap());
+    assert_eq!(xflash.get_usb_speed().await.unwrap(), UsbSpeed::High);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xflash_fails_on_unexpected_write() {
+    let transcript = xflash_cmd(Transcript::new(), Cmd::GetPacketLength, 0);
+    let mut xflash = xflash(MockMTKPort::new(transcript));
+
+    assert!(xflash.get_usb_speed().await.is_err());
+}
+
+#[tokio::test]
+async fn xml_negotiates_host_cmds() {
+    let cmd = create_cmd(&HostSupportedCommands::new(HOST_CMDS));
+    let end = [CMD_END, b"CMD:DOWNLOAD-FILE^1@CMD:UPLOAD-FILE^1@"].concat();
+    let transcript = xml_cmd(Transcript::new(), cmd).read_packet(end).write_packet(*b"OK\0");
+    let port = MockMTKPort::new(transcript);
+    let mut xml = xml(port.clone());
+
+    let cmds = xml.negotiate_host_cmds().await.unwrap();
+    assert_eq!(cmds.missing(SupportedCmds::ALL), Some("CMD:FILE-SYS-OPERATION"));
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xml_skips_da_reports() {
+    let report = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:NOTIFY</command><arg><message>low battery</message></arg></da>"
+    );
+    let transcript = Transcript::new()
+        .read_packet(report.as_bytes())
+        .write_packet(*b"OK\0")
+        .read_packet(*b"OK\0");
+    let port = MockMTKPort::new(transcript);
+    let mut xml = xml(port.clone());
+    let (events, mut notices) = event_channel();
+    xml.events = Some(events);
+
+    assert!(xml.read_ack().await.unwrap());
+    assert!(port.is_done());
+    assert!(matches!(notices.try_recv(), Ok(CoreEvent::DaNotice(n)) if n == "low battery"));
+}
+
+#[tokio::test]
+async fn recorded_transcript_plays_back() {
+    let path = std::env::temp_dir().join(format!("penumbra-mock-{}.trace", std::process::id()));
+
+    let port = MockMTKPort::new(xflash_usb_speed(Transcript::new(), "high-speed\0"));
+    {
+        let conn = Connection::new(record(Box::new(port), &path).unwrap());
+        let mut xflash = XFlash::new(conn, da(DAType::V5), DeviceInfo::new(), None, false);
+        assert_eq!(xflash.get_usb_speed().await.unwrap(), UsbSpeed::High);
+    }
+
+    let transcript = Transcript::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    let port = MockMTKPort::new(transcript);
+    let mut xflash = xflash(port.clone());
+    assert_eq!(xflash.get_usb_speed().await.unwrap(), UsbSpeed::High);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn baudrate_switch_needs_a_uart() {
+    let port = MockMTKPort::new(Transcript::new());
+    let mut conn = Connection::new(Box::new(port.clone()));
+
+    assert!(matches!(conn.switch_baudrate(921_600).await, Err(Error::Unsupported(_))));
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn chunks_are_counted() {
+    let transcript = Transcript::new().write(*b"data").read(*b"back").read(*b"more");
+    let port = MockMTKPort::new(transcript);
+    let mut conn = Connection::new(Box::new(port));
+
+    conn.write_chunk(b"data").await.unwrap();
+    conn.read_packet(8, Some(4)).await.unwrap();
+
+    let stats = conn.reset_stats();
+    assert_eq!((stats.bytes_tx, stats.bytes_rx, stats.chunks, stats.retries), (4, 8, 3, 0));
+    assert!(conn.stats().snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn chunks_failing_before_any_byte_are_retried() {
+    let transcript = Transcript::new().error("stall").write(*b"data").error("timeout");
+    let port = MockMTKPort::new(transcript.read(*b"back"));
+    let mut conn = Connection::new(Box::new(port.clone()));
+    conn.retry = RetryPolicy { attempts: 1, backoff: Duration::ZERO };
+
+    conn.write_chunk(b"data").await.unwrap();
+    assert_eq!(conn.read_packet(4, None).await.unwrap(), b"back");
+    assert_eq!(conn.stats().snapshot().retries, 2);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn chunks_cut_short_are_not_retried() {
+    let transcript = Transcript::new().read(*b"ba").error("timeout").read(*b"ck");
+    let port = MockMTKPort::new(transcript.write(*b"da").error("stall").write(*b"ta"));
+    let mut conn = Connection::new(Box::new(port.clone()));
+    conn.retry = RetryPolicy { attempts: 3, backoff: Duration::ZERO };
+
+    // Re-reading the whole chunk would drop "ba", and the rest of the read is left alone
+    let mut buf = [0u8; 4];
+    assert!(matches!(conn.read_chunk(&mut buf).await, Err(Error::Connection(_))));
+    assert_eq!(port.remaining()[0], Step::Read(b"ck".to_vec()));
+    let mut rest = [0u8; 2];
+    conn.read_chunk(&mut rest).await.unwrap();
+    assert_eq!(&rest, b"ck");
+
+    // Re-writing it would send "da" twice
+    assert!(matches!(conn.write_chunk(b"data").await, Err(Error::Connection(_))));
+    assert_eq!(port.remaining(), [Step::Write(b"ta".to_vec())]);
+    assert_eq!(conn.stats().snapshot().retries, 0);
+}
+
+#[tokio::test]
+async fn xflash_peek_is_unsupported() {
+    let port = MockMTKPort::new(Transcript::new());
+    let mut xflash = xflash(port.clone());
+
+    let mut out = Vec::new();
+    let result = xflash.peek(0x1000_0000, 4, &mut out, &mut |_| {}).await;
+    assert!(matches!(result, Err(Error::Unsupported(_))));
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xml_poke_sends_address_and_length() {
+    let cmd = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:EXT-WRITE-MEM</command>",
+        "<arg><address>0x1000ABC0</address><length>0x6</length></arg></da>\0"
+    );
+    let download = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:DOWNLOAD-FILE</command><arg><checksum>CHK_NO</checksum>",
+        "<info>MEM</info><target_file>MEM</target_file>",
+        "<packet_length>0x1000</packet_length></arg></da>"
+    );
+    let transcript = xml_cmd(Transcript::new(), cmd.to_string())
+        .read_packet(download.as_bytes())
+        .write_packet(*b"OK\0")
+        .write_packet(*b"OK@0x6\0")
+        .read_packet(*b"OK\0")
+        .write_packet(*b"OK@0x0\0")
+        .read_packet(*b"OK\0")
+        .write_packet(*b"poked!")
+        .read_packet(*b"OK\0")
+        .read_packet(CMD_END)
+        .write_packet(*b"OK\0");
+    let port = MockMTKPort::new(transcript);
+    let mut xml = xml(port.clone());
+
+    xml.poke(0x1000_ABC0, b"poked!", &mut |_| {}).await.unwrap();
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xml_poke_refuses_bad_ranges() {
+    // Nothing may reach the port
+    let port = MockMTKPort::new(Transcript::new());
+    let mut xml = xml(port.clone());
+
+    let too_long = vec![0u8; 0x10001];
+    for (addr, data) in [(0x1000_0000, &[][..]), (0x1000_0000, &too_long), (0xFFFF_FFFE, &[0; 4])] {
+        let result = xml.poke(addr, data, &mut |_| {}).await;
+        assert!(result.is_err(), "0x{:08X}+0x{:X}", addr, data.len());
+    }
+    assert!(port.is_done());
+}
+
+/// A BootROM echo of `data`
+fn echo(transcript: Transcript, data: &[u8]) -> Transcript {
+    transcript.write(data.to_vec()).read(data.to_vec())
+}
+
+#[tokio::test]
+async fn brom_write32_refusal_keeps_stream_in_sync() {
+    let transcript = echo(Transcript::new(), &[0xD4]);
+    let transcript = echo(transcript, &0x1000_7000u32.to_be_bytes());
+    let transcript = echo(transcript, &1u32.to_be_bytes()).read(0x1D0Cu16.to_be_bytes());
+    let port = MockMTKPort::new(transcript);
+    let mut conn = Connection::new(Box::new(port.clone()));
+
+    let result = conn.write32(0x1000_7000, &[0x2200_0064]).await;
+    assert!(matches!(result, Err(Error::Status { status: 0x1D0C, .. })));
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn brom_write32_echo_mismatch_is_not_a_status() {
+    let transcript = Transcript::new().write([0xD4]).read([0x00]);
+    let mut conn = Connection::new(Box::new(MockMTKPort::new(transcript)));
+
+    let result = conn.write32(0x1000_7000, &[0x2200_0064]).await;
+    assert!(result.is_err());
+    assert!(!matches!(result, Err(Error::Status { .. })));
+}
+
+const FAKE_DA2: &[u8] = b"fake DA2 with its SLA key modulus";
+
+/// A signer for the DA SLA key of [`FAKE_DA2`], keeping the challenges it signed
+struct FakeSigner {
+    signed: Mutex<Vec<SignData>>,
+}
+
+#[async_trait]
+impl Signer for FakeSigner {
+    fn name(&self) -> &str {
+        "fake-da-sla"
+    }
+
+    fn can_sign(&self, req: &SignRequest) -> bool {
+        req.purpose == SignPurpose::DaSla && req.pubk_mod == FAKE_DA2
+    }
+
+    async fn sign(&self, req: &SignRequest) -> Result<Vec<u8>, Error> {
+        let data = &req.data;
+        self.signed.lock().unwrap().push(SignData {
+            rnd: data.rnd.clone(),
+            soc_id: data.soc_id.clone(),
+            hrid: data.hrid.clone(),
+            raw: data.raw.clone(),
+        });
+        Ok(vec![0x5A; 0x100])
+    }
+}
+
+/// A file the DA sends to the host with CMD:UPLOAD-FILE, up to its CMD:END
+fn xml_upload(transcript: Transcript, data: &[u8]) -> Transcript {
+    let upload = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:UPLOAD-FILE</command><arg><checksum>CHK_NO</checksum>",
+        "<info>MEM</info><source_file>MEM</source_file>",
+        "<packet_length>0x1000</packet_length></arg></da>"
+    );
+    transcript
+        .read_packet(upload.as_bytes())
+        .write_packet(*b"OK\0")
+        .read_packet(format!("OK@0x{:X}\0", data.len()).into_bytes())
+        .write_packet(*b"OK\0")
+        .read_packet(*b"OK\0")
+        .write_packet(*b"OK\0")
+        .read_packet(data.to_vec())
+        .write_packet(*b"OK\0")
+        .read_packet(CMD_END)
+        .write_packet(*b"OK\0")
+}
+
+/// The DA.SLA property, answered with `state`
+fn xml_sla_state(state: &str) -> Transcript {
+    let cmd = create_cmd(&GetSysProperty::new("DA.SLA", "0"));
+    xml_upload(xml_cmd(Transcript::new(), cmd), state.as_bytes())
+}
+
+/// `signature` sent as the answer to the SLA challenge, with CMD:SECURITY-SET-FLASH-POLICY
+fn xml_sla_signature(transcript: Transcript, label: &str, signature: &[u8]) -> Transcript {
+    let download = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:DOWNLOAD-FILE</command><arg><checksum>CHK_NO</checksum>",
+        "<info>MEM</info><target_file>MEM</target_file>",
+        "<packet_length>0x1000</packet_length></arg></da>"
+    );
+    xml_cmd(transcript, create_cmd(&SecuritySetFlashPolicy::new(label)))
+        .read_packet(download.as_bytes())
+        .write_packet(*b"OK\0")
+        .write_packet(format!("OK@0x{:X}\0", signature.len()).into_bytes())
+        .read_packet(*b"OK\0")
+        .write_packet(*b"OK@0x0\0")
+        .read_packet(*b"OK\0")
+        .write_packet(signature.to_vec())
+        .read_packet(*b"OK\0")
+        .read_packet(CMD_END)
+        .write_packet(*b"OK\0")
+}
+
+#[tokio::test]
+async fn xml_sla_disabled() {
+    let port = MockMTKPort::new(xml_sla_state("DISABLED"));
+    let mut xml = xml(port.clone());
+
+    assert!(xml.handle_sla().await.unwrap());
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn xml_sla_challenge_is_signed() {
+    let fw_info = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<rnd>00112233445566778899AABBCCDDEEFF</rnd><hrid>0102030405060708</hrid>",
+        "<socid>A0A1A2A3</socid></da>"
+    );
+    let transcript = xml_sla_state("ENABLED");
+    let transcript = xml_cmd(transcript, create_cmd(&SecurityGetDevFwInfo::new("0")));
+    let transcript = xml_upload(transcript, fw_info.as_bytes());
+    let transcript = xml_sla_signature(transcript, "Penumbra SLA challenge", &[0x5A; 0x100]);
+    let port = MockMTKPort::new(transcript);
+
+    let mut da = da(DAType::V6);
+    let region = |data: &[u8]| DAEntryRegion {
+        data: data.to_vec(),
+        offset: 0,
+        length: data.len() as u32,
+        addr: 0,
+        region_length: data.len() as u32,
+        sig_len: 0,
+    };
+    da.regions = vec![region(b""), region(b"DA1"), region(FAKE_DA2)];
+    let conn = Connection::new(Box::new(port.clone()));
+    let mut xml = Xml::new(conn, da, DeviceInfo::new(), false);
+
+    // Before the built-in keyring, which could claim the request too
+    let signer = Arc::new(FakeSigner { signed: Mutex::new(Vec::new()) });
+    let auth = AuthManager::get();
+    auth.register_signer_with_priority(signer.clone(), 100).unwrap();
+    let result = xml.handle_sla().await;
+    auth.unregister_signer("fake-da-sla").unwrap();
+
+    assert!(result.unwrap());
+    assert!(port.is_done());
+    let signed = signer.signed.lock().unwrap();
+    assert_eq!(signed.len(), 1);
+    assert_eq!(signed[0].rnd, hex::decode("00112233445566778899AABBCCDDEEFF").unwrap());
+    assert_eq!(signed[0].hrid, hex::decode("0102030405060708").unwrap());
+    assert_eq!(signed[0].soc_id, hex::decode("A0A1A2A3").unwrap());
+    assert_eq!(signed[0].raw, fw_info.as_bytes());
+}
+
+#[cfg(all(feature = "payloads", not(feature = "no_exploits")))]
+#[tokio::test]
+async fn xml_sla_falls_back_to_the_dummy_signature() {
+    let fw_info = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<rnd>FFEEDDCC</rnd><hrid>01</hrid><socid>02</socid></da>"
+    );
+    let transcript = xml_sla_state("ENABLED");
+    let transcript = xml_cmd(transcript, create_cmd(&SecurityGetDevFwInfo::new("0")));
+    let transcript = xml_upload(transcript, fw_info.as_bytes());
+    let transcript = xml_sla_signature(transcript, "Penumbra Dummy SLA challenge", &[0; 0x100]);
+    let port = MockMTKPort::new(transcript);
+    // No DA2, so no signer can claim the challenge
+    let mut xml = xml(port.clone());
+
+    assert!(xml.handle_sla().await.unwrap());
+    assert!(port.is_done());
+}
+
+/// Legacy DA transfer header: address, size and packet length, all big endian
+fn legacy_transfer(cmd: &[u8], addr: u64, size: u64) -> Vec<u8> {
+    [cmd, &addr.to_be_bytes(), &size.to_be_bytes(), &0x100000u32.to_be_bytes()].concat()
+}
+
+fn legacy_checksum(data: &[u8]) -> [u8; 2] {
+    (data.iter().map(|&b| b as u32).sum::<u32>() as u16).to_be_bytes()
+}
+
+#[tokio::test]
+async fn legacy_uploads_da() {
+    let da1 = b"DA1\0";
+    let da2 = [0xAAu8; 0x1800];
+
+    // DA1 through the BootROM
+    let transcript = echo(Transcript::new(), &[0xD7]);
+    let transcript = echo(transcript, &0x0020_0000u32.to_be_bytes());
+    let transcript = echo(transcript, &(da1.len() as u32).to_be_bytes());
+    let transcript = echo(transcript, &0u32.to_be_bytes()).read([0, 0]);
+    let transcript = transcript.write(da1.to_vec()).read([0x12, 0x34]).read([0, 0]);
+    let transcript = echo(transcript, &[0xD5]);
+    let transcript = echo(transcript, &0x0020_0000u32.to_be_bytes()).read([0, 0]);
+
+    // Sync, storage IDs, then the DA version: v4.2 on baseband 0x8A
+    let ids = [&[0u8; 4][..], &1u16.to_be_bytes(), &[0; 2], &[0; 4], &[0x15; 16]].concat();
+    let transcript = transcript.read([0xC0]).read(ids).write([0x5A]).read([4, 2, 0x8A]);
+
+    // Stage 2 config, then a working 512 MiB external RAM
+    let config = [
+        &0x08u16.to_be_bytes()[..],
+        &[0],
+        &0x7007FFFFu32.to_be_bytes(),
+        &[0],
+        &0u32.to_be_bytes(),
+        &[0x02, 1, 0x02, 0],
+    ]
+    .concat();
+    let ext_ram = [&[0u8, 0][..], &0x2000_0000u64.to_be_bytes()].concat();
+    let transcript = transcript.write(config).read([0x5A]).read(0u32.to_be_bytes()).read(ext_ram);
+
+    // DA2 in 0x1000 byte packets
+    let hdr = [0x4000_0000u32, da2.len() as u32, 0x1000].map(u32::to_be_bytes).concat();
+    let transcript = transcript.write(hdr).read([0x5A]);
+    let transcript = transcript.write(da2[..0x1000].to_vec()).read([0x5A]);
+    let transcript = transcript.write(da2[0x1000..].to_vec()).read([0x5A]);
+    let transcript = transcript.write([0x5A]).read([0x5A]);
+
+    // Storage reports, only eMMC (with a 4 GiB user area) being there
+    let mut emmc = vec![0u8; 0x5C];
+    emmc[60..68].copy_from_slice(&0x1_0000_0000u64.to_be_bytes());
+    let transcript = transcript.read([0xFF; 0x1C]).read(vec![0; 0x11]).read(vec![0; 9]);
+    let transcript = transcript.read(emmc).read([0; 0x1C]).read(vec![0; 0x26]);
+    let transcript = transcript.read([0x5A, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let port = MockMTKPort::new(transcript);
+
+    let mut da = da(DAType::Legacy);
+    let region = |data: &[u8], addr: u32| DAEntryRegion {
+        data: data.to_vec(),
+        offset: 0,
+        length: data.len() as u32,
+        addr,
+        region_length: data.len() as u32,
+        sig_len: 0,
+    };
+    da.regions = vec![region(b"", 0), region(da1, 0x0020_0000), region(&da2, 0x4000_0000)];
+    let conn = Connection::new(Box::new(port.clone()));
+    let mut legacy = Legacy::new(conn, da, DeviceInfo::new());
+
+    assert!(legacy.upload_da().await.unwrap());
+    assert_eq!(legacy.flavor, Some(LegacyFlavor::V4));
+    assert_eq!(legacy.get_storage().await.unwrap().get_user_size(), 0x1_0000_0000);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn legacy_reads_flash() {
+    let data = b"PMT!".to_vec();
+    let transcript = Transcript::new().write([0x60]).read([0x5A]).write([0x08]).read([0x5A]);
+    let transcript = transcript.write(legacy_transfer(&[0xD6, 0x0C, 0x02], 0x1000, 4));
+    let transcript = transcript.read([0x5A]).read(data.clone()).read(legacy_checksum(&data));
+    let port = MockMTKPort::new(transcript.write([0x5A]));
+    let mut legacy = legacy(port.clone());
+
+    let mut out = Vec::new();
+    let section = PartitionKind::Emmc(EmmcPartition::User);
+    legacy.read_flash(0x1000, 4, section, &mut |_| {}, &mut out).await.unwrap();
+    assert_eq!(out, data);
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn legacy_read_checks_the_checksum() {
+    let transcript = Transcript::new().write([0x60]).read([0x5A]).write([0x08]).read([0x5A]);
+    let transcript = transcript.write(legacy_transfer(&[0xD6, 0x0C, 0x02], 0, 4));
+    let transcript = transcript.read([0x5A]).read(*b"PMT!").read([0, 0]);
+    let mut legacy = legacy(MockMTKPort::new(transcript));
+
+    let section = PartitionKind::Emmc(EmmcPartition::User);
+    let result = legacy.read_flash(0, 4, section, &mut |_| {}, &mut Vec::new()).await;
+    assert!(matches!(result, Err(Error::Protocol(msg)) if msg.contains("Checksum")));
+}
+
+#[tokio::test]
+async fn legacy_writes_flash() {
+    // Data short of the size is padded with zeroes
+    let chunk = [0x11, 0x22, 0, 0];
+    let transcript = Transcript::new().write(legacy_transfer(&[0x62, 0x02, 0x01], 0x200, 4));
+    let transcript = transcript.read([0x5A]).write(chunk).write(legacy_checksum(&chunk));
+    let port = MockMTKPort::new(transcript.read([0x69]));
+    let mut legacy = legacy(port.clone());
+
+    let section = PartitionKind::Emmc(EmmcPartition::Boot1);
+    let mut reader: &[u8] = &[0x11, 0x22];
+    legacy.write_flash(0x200, 4, &mut reader, section, &mut |_| {}).await.unwrap();
+    assert!(port.is_done());
+}
+
+#[tokio::test]
+async fn legacy_reboots() {
+    let param = [&[0xDB][..], &1000u32.to_be_bytes(), &[0, 1, 0, 0, 0]].concat();
+    let port = MockMTKPort::new(Transcript::new().write(param).read([0x5A]));
+    let mut legacy = legacy(port.clone());
+
+    assert!(matches!(legacy.reboot(BootMode::Fastboot).await, Err(Error::Unsupported(_))));
+    legacy.reboot(BootMode::Normal).await.unwrap();
+    assert!(port.is_done());
+}
@@ -0,0 +1,103 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Snapshot of what `penumbra::api` exports, so that removing or renaming an item, or
+//! changing the features it needs, is a deliberate change of `tests/public_api.txt`.
+//! Items only change with a new major version, see the `api` module docs.
+//!
+//! Run with `PENUMBRA_BLESS=1` to write the snapshot after an intended change.
+use std::path::Path;
+
+/// Names of the `pub use` items of an `api.rs` source, one per line, followed by the `cfg`
+/// they're behind, if any. Where they're defined is left out, the modules can move.
+fn exports(source: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut module = String::new();
+    let mut module_cfg: Option<String> = None;
+    let mut cfg: Option<String> = None;
+    let mut lines = source.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        if line.starts_with("#[cfg(") {
+            cfg = Some(line.to_string());
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("pub mod ").and_then(|l| l.strip_suffix(" {")) {
+            module = format!("{}::", name);
+            module_cfg = cfg.take();
+            continue;
+        }
+        if line == "}" {
+            module.clear();
+            module_cfg = None;
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("pub use ") else {
+            continue;
+        };
+
+        let mut stmt = rest.to_string();
+        while !stmt.ends_with(';') {
+            stmt.push_str(lines.next().expect("unterminated pub use"));
+        }
+        let stmt = stmt.trim_end_matches(';');
+        let items: Vec<&str> = match stmt.split_once('{') {
+            Some((_, items)) => items.trim_end_matches('}').split(',').collect(),
+            None => vec![stmt.rsplit_once("::").unwrap().1],
+        };
+
+        let cfgs: Vec<String> = module_cfg.iter().chain(cfg.take().iter()).cloned().collect();
+        for item in items.iter().map(|i| i.trim()).filter(|i| !i.is_empty()) {
+            let name = item.split_once(" as ").map_or(item, |(_, alias)| alias);
+            let mut entry = format!("{}{}", module, name);
+            for cfg in &cfgs {
+                entry.push(' ');
+                entry.push_str(cfg);
+            }
+            out.push(entry);
+        }
+    }
+
+    out.sort();
+    out
+}
+
+#[test]
+fn api_matches_snapshot() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = std::fs::read_to_string(root.join("src/api.rs")).unwrap();
+    let snapshot_path = root.join("tests/public_api.txt");
+
+    let current = exports(&source).join("\n") + "\n";
+    if std::env::var_os("PENUMBRA_BLESS").is_some() {
+        std::fs::write(&snapshot_path, &current).unwrap();
+        return;
+    }
+
+    let snapshot = std::fs::read_to_string(&snapshot_path).unwrap_or_default();
+    let missing = |from: &str, other: &str| -> Vec<String> {
+        from.lines().filter(|l| !other.lines().any(|o| o == *l)).map(String::from).collect()
+    };
+    let removed = missing(&snapshot, &current);
+    let added = missing(&current, &snapshot);
+    assert!(
+        removed.is_empty() && added.is_empty(),
+        "penumbra::api changed, run with PENUMBRA_BLESS=1 if intended.\n\
+         Removed:\n  {}\nAdded:\n  {}",
+        removed.join("\n  "),
+        added.join("\n  ")
+    );
+}
+
+/// The items frontends use the most, checked through the facade with their signatures
+#[cfg(feature = "da-protocols")]
+#[allow(dead_code)]
+fn facade_signatures() {
+    use penumbra::api::{DeviceBuilder, Error, Progress};
+
+    let _: fn() -> DeviceBuilder = DeviceBuilder::default;
+    let _: fn(&Progress) -> bool = Progress::is_done;
+    let _: fn(&Error) -> bool = Error::is_disconnect;
+}
@@ -0,0 +1,280 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Replays the captures in `tests/fixtures/`, see the README there.
+//!
+//! Each capture is played by [`MockMTKPort`] against the operation its [`Scenario`] runs:
+//! the host must write the captured bytes in the captured order, and the scenario checks
+//! what the operation returned once the device side of the capture is through.
+//!
+//! The `synthetic_` captures are not recorded from devices: they're built from exchanges
+//! written after penumbra's own flows, so they catch changes to what penumbra sends, but not
+//! differences between penumbra and real DAs.
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use penumbra::connection::Connection;
+use penumbra::connection::mock::{MockMTKPort, Transcript, record};
+use penumbra::connection::port::MTKPort;
+use penumbra::core::devinfo::DeviceInfo;
+use penumbra::core::progress::Progress;
+use penumbra::core::storage::emmc::{EmmcInfo, EmmcStorage};
+use penumbra::core::storage::{EmmcPartition, PartitionKind};
+use penumbra::da::xflash::Cmd;
+use penumbra::da::xml::{
+    CMD_END,
+    CMD_START,
+    HOST_CMDS,
+    HostSupportedCommands,
+    ReadFlash,
+    SupportedCmds,
+    create_cmd,
+};
+use penumbra::da::{DA, DAProtocol, DAType, XFlash, Xml};
+use penumbra::error::{Error, XmlErrorKind};
+
+type Run = fn(Box<dyn MTKPort>) -> Pin<Box<dyn Future<Output = ()>>>;
+
+/// An operation played against a capture
+struct Scenario {
+    /// Capture file in `tests/fixtures/`, without the `.trace` extension
+    name: &'static str,
+    /// The hand-written exchange a synthetic capture is recorded from, see
+    /// [`record_seed_captures`]. `None` for captures of real devices.
+    seed: Option<fn() -> Transcript>,
+    run: Run,
+}
+
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "synthetic_xml_connect_read",
+        seed: Some(xml_connect_read_seed),
+        run: xml_connect_read,
+    },
+    Scenario {
+        name: "synthetic_xflash_write",
+        seed: Some(xflash_write_seed),
+        run: xflash_write,
+    },
+    Scenario {
+        name: "synthetic_xml_read_not_ready",
+        seed: Some(xml_read_not_ready_seed),
+        run: xml_read_not_ready,
+    },
+];
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+fn da(da_type: DAType) -> DA {
+    DA {
+        da_type,
+        regions: Vec::new(),
+        magic: 0xDADA,
+        hw_code: 0x6768,
+        hw_sub_code: 0xCA00,
+        sw_version: 0,
+    }
+}
+
+fn emmc() -> EmmcStorage {
+    EmmcStorage {
+        info: EmmcInfo {
+            kind: 1,
+            block_size: 0x200,
+            boot1_size: 0x40_0000,
+            boot2_size: 0x40_0000,
+            rpmb_size: 0x100_0000,
+            gp1_size: 0,
+            gp2_size: 0,
+            gp3_size: 0,
+            gp4_size: 0,
+            user_size: 0x7_4000_0000,
+            cid: vec![0; 16],
+            fwver: 0,
+            erase_group_size: 0,
+        },
+    }
+}
+
+/// Lifetime of an XML command sent by the host, up to the DA acknowledging it with `ack`
+fn xml_cmd(transcript: Transcript, cmd: String, ack: &[u8]) -> Transcript {
+    transcript
+        .read_packet(CMD_START)
+        .write_packet(*b"OK\0")
+        .write_packet(cmd.into_bytes())
+        .read_packet(ack.to_vec())
+}
+
+/// An XFlash packet answered with `status`
+fn xflash_send(transcript: Transcript, data: &[u8], status: u32) -> Transcript {
+    transcript.write_packet(data.to_vec()).read_packet(status.to_le_bytes())
+}
+
+const READ_DATA: &[u8; 0x20] = b"penumbra replay corpus, 32 bytes";
+
+fn xml_connect_read_seed() -> Transcript {
+    let cmd = create_cmd(&HostSupportedCommands::new(HOST_CMDS));
+    let end = [CMD_END, b"CMD:DOWNLOAD-FILE^1@CMD:UPLOAD-FILE^1@"].concat();
+    let transcript = xml_cmd(Transcript::new(), cmd, b"OK\0").read_packet(end);
+    let transcript = transcript.write_packet(*b"OK\0");
+
+    let cmd = create_cmd(&ReadFlash::new("EMMC-USER", "EMMC-USER", 0x20u64, 0x8800u64));
+    let transcript = xml_cmd(transcript, cmd, b"OK\0");
+
+    // The DA can report at any point, the host acks and goes on
+    let notice = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:NOTIFY</command><arg><message>battery low</message></arg></da>"
+    );
+    let upload = concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?><da><version>1.0</version>"#,
+        "<command>CMD:UPLOAD-FILE</command><arg><checksum>CHK_NO</checksum>",
+        "<info>EMMC-USER</info><source_file>EMMC-USER</source_file>",
+        "<packet_length>0x10</packet_length></arg></da>"
+    );
+    let mut transcript = transcript
+        .read_packet(notice.as_bytes())
+        .write_packet(*b"OK\0")
+        .read_packet(upload.as_bytes())
+        .write_packet(*b"OK\0")
+        .read_packet(*b"OK@0x20\0")
+        .write_packet(*b"OK\0");
+    for chunk in READ_DATA.chunks(0x10) {
+        transcript = transcript
+            .read_packet(*b"OK\0")
+            .write_packet(*b"OK\0")
+            .read_packet(chunk.to_vec())
+            .write_packet(*b"OK\0");
+    }
+    transcript.read_packet(CMD_END).write_packet(*b"OK\0")
+}
+
+fn xml_connect_read(port: Box<dyn MTKPort>) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(async move {
+        let conn = Connection::new(port);
+        let mut xml = Xml::new(conn, da(DAType::V6), DeviceInfo::new(), false);
+
+        let cmds = xml.negotiate_host_cmds().await.unwrap();
+        assert_eq!(cmds.missing(SupportedCmds::ALL), Some("CMD:FILE-SYS-OPERATION"));
+
+        let mut data = Vec::new();
+        let section = PartitionKind::Emmc(EmmcPartition::User);
+        xml.read_flash(0x8800, 0x20, section, &mut |_| {}, &mut data).await.unwrap();
+        assert_eq!(data, READ_DATA);
+    })
+}
+
+const WRITE_DATA: &[u8; 0x20] = b"written through the replay suite";
+
+fn xflash_write_seed() -> Transcript {
+    let transcript = xflash_send(Transcript::new(), &(Cmd::DeviceCtrl as u32).to_le_bytes(), 0);
+    let transcript = xflash_send(transcript, &(Cmd::GetPacketLength as u32).to_le_bytes(), 0);
+    let lengths = [0x10u32.to_le_bytes(), 0x10u32.to_le_bytes()].concat();
+    let transcript = transcript.read_packet(lengths).read_packet(0u32.to_le_bytes());
+
+    let mut param = Vec::new();
+    param.extend_from_slice(&1u32.to_le_bytes());
+    param.extend_from_slice(&(EmmcPartition::User as u32).to_le_bytes());
+    param.extend_from_slice(&0x8800u64.to_le_bytes());
+    param.extend_from_slice(&0x20u64.to_le_bytes());
+    param.extend_from_slice(&[0u8; 32]);
+    let transcript = xflash_send(transcript, &(Cmd::WriteData as u32).to_le_bytes(), 0);
+    let mut transcript = xflash_send(transcript, &param, 0);
+
+    for chunk in WRITE_DATA.chunks(0x10) {
+        let checksum = chunk.iter().map(|&b| b as u32).sum::<u32>() & 0xFFFF;
+        transcript = transcript
+            .write_packet(0u32.to_le_bytes())
+            .write_packet(checksum.to_le_bytes())
+            .write_packet(chunk.to_vec())
+            .read_packet(0u32.to_le_bytes());
+    }
+    transcript.read_packet(0u32.to_le_bytes())
+}
+
+fn xflash_write(port: Box<dyn MTKPort>) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(async move {
+        let dev_info = DeviceInfo::new();
+        dev_info.set_storage(Arc::new(emmc())).await;
+        let conn = Connection::new(port);
+        let mut xflash = XFlash::new(conn, da(DAType::V5), dev_info, None, false);
+
+        let mut written = 0;
+        let mut reader = &WRITE_DATA[..];
+        let section = PartitionKind::Emmc(EmmcPartition::User);
+        let mut progress = |p: &Progress| written = p.written;
+        xflash.write_flash(0x8800, 0x20, &mut reader, section, &mut progress).await.unwrap();
+        assert_eq!(written, 0x20);
+    })
+}
+
+fn xml_read_not_ready_seed() -> Transcript {
+    let cmd = create_cmd(&ReadFlash::new("EMMC-USER", "EMMC-USER", 0x20u64, 0x8800u64));
+    xml_cmd(Transcript::new(), cmd, b"ERR!STORAGE-NOT-READY\0")
+}
+
+fn xml_read_not_ready(port: Box<dyn MTKPort>) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(async move {
+        let conn = Connection::new(port);
+        let mut xml = Xml::new(conn, da(DAType::V6), DeviceInfo::new(), false);
+
+        let mut data = Vec::new();
+        let section = PartitionKind::Emmc(EmmcPartition::User);
+        let result = xml.read_flash(0x8800, 0x20, section, &mut |_| {}, &mut data).await;
+        assert!(matches!(result, Err(Error::Xml(e)) if e.kind == XmlErrorKind::StorageNotReady));
+        assert!(data.is_empty());
+    })
+}
+
+#[tokio::test]
+async fn captures_replay() {
+    for scenario in SCENARIOS {
+        let path = fixtures().join(format!("{}.trace", scenario.name));
+        let transcript = Transcript::load(&path).unwrap_or_else(|e| {
+            panic!("{}: failed to load {}: {}", scenario.name, path.display(), e)
+        });
+
+        let port = MockMTKPort::new(transcript);
+        (scenario.run)(Box::new(port.clone())).await;
+        assert!(port.is_done(), "{}: capture not played to the end", scenario.name);
+    }
+}
+
+#[test]
+fn every_capture_has_a_scenario() {
+    for entry in std::fs::read_dir(fixtures()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "trace") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_string_lossy();
+        assert!(
+            SCENARIOS.iter().any(|s| s.name == name),
+            "{} has no scenario in tests/replay.rs",
+            path.display()
+        );
+    }
+}
+
+/// Records the synthetic captures again, from exchanges written after the documented flows.
+/// Captures of real devices are recorded through `DeviceBuilder::with_trace` instead.
+#[tokio::test]
+#[ignore = "rewrites tests/fixtures, run with --ignored when a seed changes"]
+async fn record_seed_captures() {
+    for scenario in SCENARIOS {
+        let Some(seed) = scenario.seed else {
+            continue;
+        };
+        let path = fixtures().join(format!("{}.trace", scenario.name));
+        let port = MockMTKPort::new(seed());
+        let traced = record(Box::new(port.clone()), &path).unwrap();
+        (scenario.run)(traced).await;
+        assert!(port.is_done(), "{}: seed not played to the end", scenario.name);
+    }
+}
@@ -0,0 +1,175 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::api::sha256;
+use penumbra::core::seccfg::{LockFlag, SecCfg, SecCfgAlgo};
+
+const MAGIC_BEGIN: u32 = 0x4D4D4D4D;
+const MAGIC_END: u32 = 0x45454545;
+
+const V3_IMGINFO_SIZE: usize = 0x68 * 20;
+const V3_ENC_OFFSET: usize = 0x2C;
+const V3_STATUS_COMPLETE: u32 = 0x43434343;
+const V3_ATTR_DEFAULT: u32 = 0x33333333;
+const V3_ATTR_UNLOCK: u32 = 0x44444444;
+
+/// The V4 header fields, from the magic to the end flag
+fn v4_fields(lock_state: u32, critical_lock_state: u32) -> Vec<u8> {
+    [MAGIC_BEGIN, 4, 0x3C, lock_state, critical_lock_state, 0, MAGIC_END]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+/// A V4 seccfg, with `enc_hash` as the encrypted hash
+fn v4(lock_state: u32, critical_lock_state: u32, enc_hash: &[u8; 32]) -> Vec<u8> {
+    let mut data = v4_fields(lock_state, critical_lock_state);
+    data.extend_from_slice(enc_hash);
+    data.resize(0x200, 0);
+    data
+}
+
+/// A V3 body: image info, SIU status, seccfg status and attribute, and 4 bytes of extension
+fn v3_body(attr: u32) -> Vec<u8> {
+    let mut body = vec![0xA5u8; V3_IMGINFO_SIZE];
+    body.extend(0u32.to_le_bytes());
+    body.extend(V3_STATUS_COMPLETE.to_le_bytes());
+    body.extend(attr.to_le_bytes());
+    body.extend(*b"EXT!");
+    body
+}
+
+/// A V3 seccfg with `enc_body` as its encrypted body
+fn v3(enc_body: &[u8]) -> Vec<u8> {
+    let mut data = b"AND_SECCFG_v\0\0\0\0".to_vec();
+    data.extend(MAGIC_BEGIN.to_le_bytes());
+    data.extend(3u32.to_le_bytes());
+    data.extend(0x1860u32.to_le_bytes());
+    data.extend((V3_ENC_OFFSET as u32).to_le_bytes());
+    data.extend((enc_body.len() as u32).to_le_bytes());
+    data.extend([1, 0]);
+    data.extend(0x200u16.to_le_bytes());
+    data.extend(0x10u32.to_le_bytes());
+    assert_eq!(data.len(), V3_ENC_OFFSET);
+    data.extend_from_slice(enc_body);
+    data.extend(MAGIC_END.to_le_bytes());
+    data.resize(0x1A00, 0);
+    data
+}
+
+#[test]
+fn v4_header() {
+    let SecCfg::V4(seccfg) = SecCfg::parse_header(&v4(3, 0, &[0xEE; 32])).unwrap() else {
+        panic!("not parsed as V4");
+    };
+    assert_eq!((seccfg.seccfg_ver, seccfg.seccfg_size), (4, 0x3C));
+    assert!(!seccfg.is_locked());
+    assert!(!seccfg.is_critical_locked());
+    assert_eq!(seccfg.get_encrypted_hash(), [0xEE; 32]);
+    assert_eq!(seccfg.get_hash(), sha256(&v4_fields(3, 0)));
+}
+
+#[test]
+fn v4_lock_round_trip() {
+    let mut seccfg = SecCfg::parse_header(&v4(3, 0, &[0xEE; 32])).unwrap();
+    assert_eq!(seccfg.get_lock_state(), LockFlag::Unlock);
+    assert!(seccfg.validate().is_ok());
+
+    // The encrypted hash is checked against the hash of the header
+    assert_eq!(seccfg.get_encrypted(), [0xEE; 32]);
+    assert!(!seccfg.try_decrypted(&[0u8; 32], SecCfgAlgo::SW));
+    assert!(seccfg.get_algo().is_none());
+    assert!(seccfg.try_decrypted(&sha256(&v4_fields(3, 0)), SecCfgAlgo::HWv4));
+    assert!(matches!(seccfg.get_algo(), Some(SecCfgAlgo::HWv4)));
+
+    seccfg.set_lock_state(LockFlag::Lock);
+    assert!(seccfg.validate().is_ok());
+    assert_eq!(seccfg.get_plain(), sha256(&v4_fields(4, 1)));
+    seccfg.set_encrypted(vec![0x11; 32]);
+    assert_eq!(seccfg.create(), v4(4, 1, &[0x11; 32]));
+}
+
+#[test]
+fn v4_validate() {
+    let cases = [
+        (1, 1, true),
+        (3, 0, true),
+        (4, 1, true),
+        // Locked with the critical partitions unlocked
+        (4, 0, false),
+        (1, 0, false),
+        (2, 1, false),
+    ];
+    for (lock_state, critical, valid) in cases {
+        let seccfg = SecCfg::parse_header(&v4(lock_state, critical, &[0; 32])).unwrap();
+        assert_eq!(seccfg.validate().is_ok(), valid, "{} {}", lock_state, critical);
+    }
+}
+
+#[test]
+fn v3_header() {
+    let enc_body = vec![0x5Au8; v3_body(V3_ATTR_DEFAULT).len()];
+    let SecCfg::V3(seccfg) = SecCfg::parse_header(&v3(&enc_body)).unwrap() else {
+        panic!("not parsed as V3");
+    };
+    assert_eq!((seccfg.seccfg_ver, seccfg.seccfg_size), (3, 0x1860));
+    assert_eq!(seccfg.enc_offset, V3_ENC_OFFSET as u32);
+    assert_eq!((seccfg.sw_sec_lock_try, seccfg.sw_sec_lock_done), (1, 0));
+    assert_eq!((seccfg.page_size, seccfg.page_count), (0x200, 0x10));
+    assert_eq!(SecCfg::V3(seccfg).get_encrypted(), enc_body);
+}
+
+#[test]
+fn v3_lock_round_trip() {
+    // The "encryption" is left out, the body is what the right algorithm would decrypt
+    let body = v3_body(V3_ATTR_UNLOCK);
+    let mut seccfg = SecCfg::parse_header(&v3(&body)).unwrap();
+    assert!(seccfg.get_algo().is_none());
+
+    // The wrong algorithm gives a body with an unknown status and attribute
+    assert!(!seccfg.try_decrypted(&vec![0x5A; body.len()], SecCfgAlgo::SW));
+    assert!(!seccfg.try_decrypted(&body[..V3_IMGINFO_SIZE], SecCfgAlgo::SW));
+    assert!(seccfg.try_decrypted(&body, SecCfgAlgo::HWv3));
+    assert!(matches!(seccfg.get_algo(), Some(SecCfgAlgo::HWv3)));
+    assert_eq!(seccfg.get_lock_state(), LockFlag::Unlock);
+    assert!(seccfg.validate().is_ok());
+    assert_eq!(seccfg.get_plain(), body);
+
+    seccfg.set_lock_state(LockFlag::Lock);
+    let locked = v3_body(V3_ATTR_DEFAULT);
+    assert_eq!(seccfg.get_plain(), locked);
+    seccfg.set_encrypted(locked.clone());
+    let data = seccfg.create();
+    assert_eq!(data, v3(&locked));
+
+    let mut reparsed = SecCfg::parse_header(&data).unwrap();
+    assert!(reparsed.try_decrypted(&locked, SecCfgAlgo::HWv3));
+    assert_eq!(reparsed.get_lock_state(), LockFlag::Lock);
+}
+
+#[test]
+fn invalid_headers() {
+    let body = v3_body(V3_ATTR_DEFAULT);
+    let mut bad_v3_end = v3(&body);
+    let end = V3_ENC_OFFSET + body.len();
+    bad_v3_end[end] ^= 1;
+    let mut bad_v3_len = v3(&body);
+    bad_v3_len[0x20..0x24].copy_from_slice(&0x10_0000u32.to_le_bytes());
+    let mut bad_v4_end = v4(3, 0, &[0; 32]);
+    bad_v4_end[24] ^= 1;
+
+    let cases: &[(&str, &[u8])] = &[
+        ("empty", &[]),
+        ("short v4", &v4(3, 0, &[0; 32])[..0x1C]),
+        ("v4 without its hash", &v4(3, 0, &[0; 32])[..0x30]),
+        ("v4 end flag", &bad_v4_end),
+        ("short v3", &v3(&body)[..0x20]),
+        ("v3 end flag", &bad_v3_end),
+        ("v3 body past the data", &bad_v3_len),
+        ("no magic", &[0u8; 0x200]),
+    ];
+    for (name, data) in cases {
+        assert!(SecCfg::parse_header(data).is_err(), "{}", name);
+    }
+}
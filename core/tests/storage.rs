@@ -0,0 +1,621 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+
+use penumbra::core::devinfo::DevInfoData;
+use penumbra::core::storage::emmc::{EmmcInfo, EmmcStorage};
+use penumbra::core::storage::nand::NandInfo;
+use penumbra::core::storage::ufs::{UfsInfo, UfsStorage};
+use penumbra::core::storage::{
+    BlockWindow,
+    BootRegion,
+    EmmcPartition,
+    EraseMethod,
+    EraseSpan,
+    Gpt,
+    GptEntryDiff,
+    GptReport,
+    GptSource,
+    ImageKind,
+    NandStorage,
+    Partition,
+    PartitionKind,
+    RegionInfo,
+    Storage,
+    StorageType,
+    UfsPartition,
+};
+
+const SECTOR: u64 = 0x200;
+/// Last LBA of the user area the GPT fixtures are made for, 64 MiB
+const LAST_LBA: u64 = 0x2_0000 - 1;
+const ENTRIES: usize = 128;
+const ENTRY_SIZE: usize = 128;
+
+/// `len` bytes with `bytes` written at `offset`
+fn image(len: usize, offset: usize, bytes: &[u8]) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    data
+}
+
+#[test]
+fn image_kind_detect() {
+    let cases: &[(&str, Vec<u8>, ImageKind)] = &[
+        ("emmc boot header", image(0x1000, 0, b"EMMC_BOOT"), ImageKind::BootRegion),
+        ("ufs boot header", image(0x1000, 0, b"UFS_BOOT"), ImageKind::BootRegion),
+        ("combo boot header", image(0x1000, 0, b"COMBO_BOOT"), ImageKind::BootRegion),
+        ("raw preloader", image(0x1000, 0, b"MMM\x01"), ImageKind::BootRegion),
+        ("sparse image", image(0x1000, 0, &[0x3A, 0xFF, 0x26, 0xED]), ImageKind::UserArea),
+        ("android boot", image(0x1000, 0, b"ANDROID!"), ImageKind::UserArea),
+        ("vendor boot", image(0x1000, 0, b"VNDRBOOT"), ImageKind::UserArea),
+        ("mtk partition header", image(0x1000, 0, &[0x88, 0x16, 0x88, 0x58]), ImageKind::UserArea),
+        ("gpt", image(0x1000, 0x200, b"EFI PART"), ImageKind::UserArea),
+        ("ext4", image(0x1000, 0x438, &[0x53, 0xEF]), ImageKind::UserArea),
+        ("zeroes", vec![0u8; 0x1000], ImageKind::Unknown),
+        ("empty", Vec::new(), ImageKind::Unknown),
+        // Too short to hold the ext4 magic, which must not be read out of bounds
+        ("short", image(0x439, 0x438, &[0x53]), ImageKind::Unknown),
+        // Magics only count at the start
+        ("late magic", image(0x1000, 0x10, b"ANDROID!"), ImageKind::Unknown),
+    ];
+
+    for (name, header, expected) in cases {
+        assert_eq!(ImageKind::detect(header), *expected, "{}", name);
+    }
+}
+
+#[test]
+fn image_kind_fits() {
+    let boot1 = PartitionKind::Emmc(EmmcPartition::Boot1);
+    let boot2 = PartitionKind::Emmc(EmmcPartition::Boot2);
+    let user = PartitionKind::Emmc(EmmcPartition::User);
+    let lu0 = PartitionKind::Ufs(UfsPartition::Lu0);
+    let lu2 = PartitionKind::Ufs(UfsPartition::Lu2);
+    let unknown = PartitionKind::Unknown;
+
+    let cases = [
+        (ImageKind::BootRegion, boot1, true),
+        (ImageKind::BootRegion, boot2, true),
+        (ImageKind::BootRegion, lu0, true),
+        (ImageKind::BootRegion, user, false),
+        (ImageKind::BootRegion, lu2, false),
+        (ImageKind::BootRegion, unknown, true),
+        (ImageKind::UserArea, boot1, false),
+        (ImageKind::UserArea, lu0, false),
+        (ImageKind::UserArea, user, true),
+        (ImageKind::UserArea, lu2, true),
+        (ImageKind::UserArea, unknown, true),
+        (ImageKind::Unknown, boot1, true),
+        (ImageKind::Unknown, user, true),
+        (ImageKind::Unknown, unknown, true),
+    ];
+
+    for (image, target, expected) in cases {
+        assert_eq!(image.fits(target), expected, "{:?} into {:?}", image, target);
+    }
+}
+
+fn user(name: &str, size: u64, address: u64) -> Partition {
+    Partition::new(name, size, address, PartitionKind::Emmc(EmmcPartition::User))
+}
+
+#[test]
+fn block_window() {
+    // Above 4 GiB, so truncating to u32 anywhere shows
+    let para = user("para", 0x8_0000, 0x1_2340_0000);
+    let cases = [
+        // offset, length, block size, window, skip
+        (0x0, 0x1000, 0x200, 0x0..0x1000, 0x0),
+        (0x10, 0x4, 0x200, 0x0..0x200, 0x10),
+        (0x1FE, 0x4, 0x200, 0x0..0x400, 0x1FE),
+        (0x7_FFF0, 0x10, 0x200, 0x7_FE00..0x8_0000, 0x1F0),
+        (0x8_0000, 0x0, 0x200, 0x8_0000..0x8_0000, 0x0),
+        (0x1234, 0x10, 0x1000, 0x1000..0x2000, 0x234),
+        (0x1234, 0x1_0000, 0x1000, 0x1000..0x1_2000, 0x234),
+        // No block size reported, byte granular
+        (0x1234, 0x10, 0x0, 0x1234..0x1244, 0x0),
+    ];
+
+    for (offset, length, block_size, window, skip) in cases {
+        let name = format!("0x{:X}+0x{:X} in 0x{:X} blocks", offset, length, block_size);
+        let w = BlockWindow::new(&para, offset, length, block_size).expect(&name);
+        assert_eq!(w.range, offset..offset + length, "{}", name);
+        assert_eq!(w.window, window, "{}", name);
+        assert_eq!(w.skip(), skip, "{}", name);
+        assert_eq!(w.address(), 0x1_2340_0000 + window.start, "{}", name);
+        assert_eq!(w.size(), window.end - window.start, "{}", name);
+    }
+}
+
+#[test]
+fn block_window_clamps_to_the_partition() {
+    // The last block is cut short by the end of the partition
+    let part = user("misc", 0x300, 0x8000);
+    let w = BlockWindow::new(&part, 0x250, 0x10, 0x200).unwrap();
+    assert_eq!(w.window, 0x200..0x300);
+    assert_eq!(w.address(), 0x8200);
+}
+
+#[test]
+fn block_window_refuses_ranges_past_the_end() {
+    let para = user("para", 0x8_0000, 0x1_2340_0000);
+    let cases = [(0x7_FFF0, 0x11), (0x8_0000, 0x1), (0x0, 0x1_0000_0000), (u64::MAX, 0x2)];
+    for (offset, length) in cases {
+        let window = BlockWindow::new(&para, offset, length, 0x200);
+        assert_eq!(window, None, "0x{:X}+0x{:X}", offset, length);
+    }
+}
+
+#[test]
+fn block_window_edges() {
+    let table = [
+        user("pgpt", 0x8800, 0x0),
+        user("para", 0x8_0000, 0x1_2340_0000),
+        user("misc", 0x300, 0x1_2348_0000),
+    ];
+    let find = |name: &str| table.iter().find(|p| p.name == name).unwrap();
+
+    let cases = [
+        // partition, offset, length, head and tail relative to the partition
+        ("para", 0x0, 0x400, None, None),
+        ("para", 0x10, 0x4, Some(0x0..0x200), Some(0x0..0x200)),
+        ("para", 0x210, 0x200, Some(0x200..0x400), Some(0x400..0x600)),
+        ("para", 0x200, 0x1F0, None, Some(0x200..0x400)),
+        ("para", 0x7_FFF0, 0x10, Some(0x7_FE00..0x8_0000), None),
+        // The partition ends mid block, so does the window
+        ("misc", 0x210, 0x10, Some(0x200..0x300), Some(0x200..0x300)),
+        ("misc", 0x210, 0xF0, Some(0x200..0x300), None),
+        ("pgpt", 0x200, 0x5C, None, Some(0x200..0x400)),
+    ];
+
+    for (name, offset, length, head, tail) in cases {
+        let part = find(name);
+        let absolute = |r: Option<std::ops::Range<u64>>| {
+            r.map(|r| part.address + r.start..part.address + r.end)
+        };
+        let w = BlockWindow::new(part, offset, length, 0x200).unwrap();
+        assert_eq!(w.head(), absolute(head), "{} 0x{:X}+0x{:X}", name, offset, length);
+        assert_eq!(w.tail(), absolute(tail), "{} 0x{:X}+0x{:X}", name, offset, length);
+
+        // What is read back around the range, and the range itself, make up the window
+        let kept_tail = w.tail().map_or(0, |t| t.end - (w.base + w.range.end));
+        assert_eq!(w.skip() + length + kept_tail, w.size(), "{} 0x{:X}", name, offset);
+    }
+}
+
+/// A 128 bytes GPT entry, named `name` and covering `first..=last`
+fn gpt_entry(index: u8, name: &str, first: u64, last: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; ENTRY_SIZE];
+    // Microsoft basic data
+    entry[0..16].copy_from_slice(&[
+        0xA2, 0xA0, 0xD0, 0xEB, 0xE5, 0xB9, 0x33, 0x44, 0x87, 0xC0, 0x68, 0xB6, 0xB7, 0x26,
+        0x99, 0xC7,
+    ]);
+    entry[16] = index + 1;
+    entry[32..40].copy_from_slice(&first.to_le_bytes());
+    entry[40..48].copy_from_slice(&last.to_le_bytes());
+    for (i, c) in name.encode_utf16().enumerate() {
+        entry[56 + i * 2..58 + i * 2].copy_from_slice(&c.to_le_bytes());
+    }
+    entry
+}
+
+/// A primary GPT image, protective MBR included, with `(name, first LBA, last LBA)` entries
+fn pgpt(parts: &[(&str, u64, u64)]) -> Vec<u8> {
+    let mut entries = vec![0u8; ENTRIES * ENTRY_SIZE];
+    for (i, (name, first, last)) in parts.iter().enumerate() {
+        let entry = gpt_entry(i as u8, name, *first, *last);
+        entries[i * ENTRY_SIZE..(i + 1) * ENTRY_SIZE].copy_from_slice(&entry);
+    }
+    let array_sectors = (entries.len() as u64).div_ceil(SECTOR);
+
+    let mut header = vec![0u8; 92];
+    header[0..8].copy_from_slice(b"EFI PART");
+    header[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+    header[12..16].copy_from_slice(&92u32.to_le_bytes());
+    header[24..32].copy_from_slice(&1u64.to_le_bytes());
+    header[32..40].copy_from_slice(&LAST_LBA.to_le_bytes());
+    header[40..48].copy_from_slice(&(2 + array_sectors).to_le_bytes());
+    header[48..56].copy_from_slice(&(LAST_LBA - array_sectors - 1).to_le_bytes());
+    header[56..72].copy_from_slice(b"penumbra-testgpt");
+    header[72..80].copy_from_slice(&2u64.to_le_bytes());
+    header[80..84].copy_from_slice(&(ENTRIES as u32).to_le_bytes());
+    header[84..88].copy_from_slice(&(ENTRY_SIZE as u32).to_le_bytes());
+    header[88..92].copy_from_slice(&crc32fast::hash(&entries).to_le_bytes());
+    let crc = crc32fast::hash(&header);
+    header[16..20].copy_from_slice(&crc.to_le_bytes());
+    header.resize(SECTOR as usize, 0);
+
+    let mut mbr = vec![0u8; SECTOR as usize];
+    mbr[510..512].copy_from_slice(&[0x55, 0xAA]);
+    [mbr, header, entries].concat()
+}
+
+const LAYOUT: &[(&str, u64, u64)] = &[
+    ("boot_a", 0x800, 0x87FF),
+    ("vbmeta_a", 0x8800, 0x8FFF),
+    ("userdata", 0x1_0000, 0x1_FFDE),
+];
+
+fn partitions(layout: &[(&str, u64, u64)]) -> Vec<Partition> {
+    layout
+        .iter()
+        .map(|(name, first, last)| {
+            let kind = PartitionKind::Emmc(EmmcPartition::User);
+            Partition::new(name, (last - first + 1) * SECTOR, first * SECTOR, kind)
+        })
+        .collect()
+}
+
+#[test]
+fn gpt_round_trip() {
+    let gpt = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    assert!(gpt.is_primary());
+    assert_eq!(gpt.partitions(), partitions(LAYOUT));
+    assert_eq!(gpt.last_lba(), LAST_LBA);
+
+    let images = gpt.serialize().unwrap();
+    // The array and header end on the last LBA
+    assert_eq!(images.secondary_offset + images.secondary.len() as u64, (LAST_LBA + 1) * SECTOR);
+
+    let primary = Gpt::parse(&images.primary, StorageType::Emmc).unwrap();
+    let secondary = Gpt::parse(&images.secondary, StorageType::Emmc).unwrap();
+    assert!(primary.is_primary());
+    assert!(!secondary.is_primary());
+    for table in [&primary, &secondary] {
+        assert_eq!(table.partitions(), partitions(LAYOUT));
+        assert_eq!(table.last_lba(), LAST_LBA);
+    }
+    assert!(Gpt::compare(&primary, &secondary).is_empty());
+
+    // Serializing again from either copy gives the same images
+    for table in [&primary, &secondary] {
+        let again = table.serialize().unwrap();
+        assert_eq!(again.primary, images.primary);
+        assert_eq!(again.secondary, images.secondary);
+        assert_eq!(again.secondary_offset, images.secondary_offset);
+    }
+}
+
+#[test]
+fn gpt_resize_round_trip() {
+    let mut gpt = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    gpt.resize_partition("userdata", 0x80_0000).unwrap();
+
+    let images = gpt.serialize().unwrap();
+    let mut expected = partitions(LAYOUT);
+    expected[2].size = 0x80_0000;
+    for image in [&images.primary, &images.secondary] {
+        assert_eq!(Gpt::parse(image, StorageType::Emmc).unwrap().partitions(), expected);
+    }
+
+    // Past the last usable LBA, overlapping `vbmeta_a`, not a sector multiple, unknown
+    assert!(gpt.resize_partition("userdata", LAST_LBA * SECTOR).is_err());
+    assert!(gpt.resize_partition("boot_a", 0x100_1000).is_err());
+    assert!(gpt.resize_partition("boot_a", 0x1001).is_err());
+    assert!(gpt.resize_partition("system", 0x1000).is_err());
+}
+
+/// A secondary GPT, as the device would have it after an interrupted OTA moved `userdata`,
+/// dropped `vbmeta_a` and added `super`
+fn diverged_sgpt() -> Gpt {
+    let layout = &[
+        ("boot_a", 0x800, 0x87FF),
+        ("userdata", 0x1_8000, 0x1_FFDE),
+        ("super", 0x1_0000, 0x1_7FFF),
+    ];
+    let images = Gpt::parse(&pgpt(layout), StorageType::Emmc).unwrap().serialize().unwrap();
+    Gpt::parse(&images.secondary, StorageType::Emmc).unwrap()
+}
+
+#[test]
+fn gpt_compare_diverging() {
+    let primary = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    let secondary = diverged_sgpt();
+    let p = partitions(LAYOUT);
+    let s = secondary.partitions();
+
+    let diff = Gpt::compare(&primary, &secondary);
+    assert!(diff.arrays_differ);
+    assert_eq!(
+        diff.entries,
+        [
+            GptEntryDiff::OnlyPrimary(p[1].clone()),
+            GptEntryDiff::Changed { primary: p[2].clone(), secondary: s[1].clone() },
+            GptEntryDiff::OnlySecondary(s[2].clone()),
+        ]
+    );
+
+    // Same partitions in another order, only the raw arrays tell them apart
+    let reordered: Vec<_> = LAYOUT.iter().rev().copied().collect();
+    let reordered = Gpt::parse(&pgpt(&reordered), StorageType::Emmc).unwrap();
+    let images = reordered.serialize().unwrap();
+    let reordered = Gpt::parse(&images.secondary, StorageType::Emmc).unwrap();
+    let diff = Gpt::compare(&primary, &reordered);
+    assert!(diff.entries.is_empty());
+    assert!(diff.arrays_differ);
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn gpt_relocate() {
+    let primary = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    let secondary = diverged_sgpt();
+    let mbr = vec![0u8; SECTOR as usize];
+
+    // The secondary copy rebuilt as the primary one, starting at LBA 1
+    let (offset, data) = secondary.relocate(GptSource::Primary).unwrap();
+    assert_eq!(offset, SECTOR);
+    let rebuilt = Gpt::parse(&[mbr.clone(), data].concat(), StorageType::Emmc).unwrap();
+    assert!(rebuilt.is_primary());
+    assert_eq!(rebuilt.partitions(), secondary.partitions());
+    assert_eq!(rebuilt.last_lba(), LAST_LBA);
+    assert!(Gpt::compare(&rebuilt, &secondary).is_empty());
+
+    // The primary copy rebuilt as the secondary one, ending on the last LBA
+    let (offset, data) = primary.relocate(GptSource::Secondary).unwrap();
+    assert_eq!(offset, primary.serialize().unwrap().secondary_offset);
+    assert_eq!(offset + data.len() as u64, (LAST_LBA + 1) * SECTOR);
+    let rebuilt = Gpt::parse(&data, StorageType::Emmc).unwrap();
+    assert!(!rebuilt.is_primary());
+    assert_eq!(rebuilt.partitions(), partitions(LAYOUT));
+    assert!(Gpt::compare(&primary, &rebuilt).is_empty());
+
+    for target in [GptSource::Pmt, GptSource::Synthetic] {
+        assert!(primary.relocate(target).is_err());
+    }
+}
+
+#[test]
+fn gpt_report_repairs_corrupted_copy() {
+    let images = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap().serialize().unwrap();
+    let parse = |data: &[u8]| Gpt::parse(data, StorageType::Emmc).ok();
+    let primary = parse(&images.primary);
+    let secondary = parse(&images.secondary);
+
+    let report = GptReport::new(primary.as_ref(), secondary.as_ref());
+    assert!(report.is_healthy());
+    assert_eq!(report.repair_source(), None);
+
+    // A flipped bit in the primary partition array, and in the secondary header
+    let mut bad_primary = images.primary.clone();
+    bad_primary[2 * SECTOR as usize + 56] ^= 1;
+    let mut bad_secondary = images.secondary.clone();
+    let header = bad_secondary.len() - SECTOR as usize;
+    bad_secondary[header + 32] ^= 1;
+    assert!(parse(&bad_primary).is_none());
+    assert!(parse(&bad_secondary).is_none());
+
+    let report = GptReport::new(None, secondary.as_ref());
+    assert!(!report.is_healthy());
+    assert!(report.diff.is_empty());
+    assert_eq!(report.repair_source(), Some(GptSource::Secondary));
+    let (offset, data) = secondary.as_ref().unwrap().relocate(GptSource::Primary).unwrap();
+    bad_primary[offset as usize..offset as usize + data.len()].copy_from_slice(&data);
+    let repaired = parse(&bad_primary);
+    assert!(GptReport::new(repaired.as_ref(), secondary.as_ref()).is_healthy());
+
+    let report = GptReport::new(primary.as_ref(), None);
+    assert!(!report.is_healthy());
+    assert_eq!(report.repair_source(), Some(GptSource::Primary));
+    let (offset, data) = primary.as_ref().unwrap().relocate(GptSource::Secondary).unwrap();
+    assert_eq!(offset, images.secondary_offset);
+    let repaired = parse(&data);
+    assert!(GptReport::new(primary.as_ref(), repaired.as_ref()).is_healthy());
+
+    // Nothing to repair from
+    assert_eq!(GptReport::new(None, None).repair_source(), None);
+}
+
+#[test]
+fn gpt_report_diverging_copies() {
+    let primary = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    let secondary = diverged_sgpt();
+
+    let report = GptReport::new(Some(&primary), Some(&secondary));
+    assert!(report.primary_ok && report.secondary_ok);
+    assert!(!report.is_healthy());
+    assert_eq!(report.diff, Gpt::compare(&primary, &secondary));
+    // Both are valid, the user picks which one is right
+    assert_eq!(report.repair_source(), None);
+}
+
+fn emmc(user_size: u64) -> EmmcStorage {
+    EmmcStorage {
+        info: EmmcInfo {
+            kind: 1,
+            block_size: 0x200,
+            boot1_size: 0x40_0000,
+            boot2_size: 0x40_0000,
+            rpmb_size: 0x100_0000,
+            gp1_size: 0,
+            gp2_size: 0,
+            gp3_size: 0,
+            gp4_size: 0,
+            user_size,
+            cid: vec![0; 16],
+            fwver: 0,
+            erase_group_size: 0,
+        },
+    }
+}
+
+#[test]
+fn partition_fits() {
+    let storage = emmc(0x7_4000_0000);
+    let boot1 = PartitionKind::Emmc(EmmcPartition::Boot1);
+    let rpmb = PartitionKind::Emmc(EmmcPartition::Rpmb);
+    let gp1 = PartitionKind::Emmc(EmmcPartition::Gp1);
+
+    let cases = [
+        (user("userdata", 0x1000, 0x7_3FFF_F000), true),
+        (user("userdata", 0x1001, 0x7_3FFF_F000), false),
+        (user("userdata", 0x1000, 0x7_4000_0000), false),
+        (user("userdata", 0x2, u64::MAX), false),
+        (Partition::new("preloader", 0x40_0000, 0, boot1), true),
+        (Partition::new("preloader", 0x40_0200, 0, boot1), false),
+        (Partition::new("rpmb", 0x100_0000, 0, rpmb), true),
+        // Regions the storage doesn't report can't be checked
+        (Partition::new("gp1", 0x1000, 0, gp1), true),
+        (Partition::new("unknown", u64::MAX, 0, PartitionKind::Unknown), true),
+    ];
+
+    for (part, fits) in cases {
+        assert_eq!(part.fits(&storage), fits, "{:?}", part);
+    }
+}
+
+#[test]
+fn oversized_gpt_partitions_dont_fit() {
+    // The fixture GPT is made for 64 MiB, `userdata` runs from 32 MiB to the end of it
+    let gpt = Gpt::parse(&pgpt(LAYOUT), StorageType::Emmc).unwrap();
+    let mut data = DevInfoData { partitions: gpt.partitions(), ..Default::default() };
+
+    // Unknown until the storage is detected
+    assert!(data.out_of_range_partitions().is_empty());
+
+    data.storage = Some(Arc::new(emmc(0x400_0000)));
+    assert!(data.out_of_range_partitions().is_empty());
+
+    data.storage = Some(Arc::new(emmc(0x300_0000)));
+    let names: Vec<_> = data.out_of_range_partitions().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["userdata"]);
+
+    data.storage = Some(Arc::new(emmc(0x100_0000)));
+    let names: Vec<_> = data.out_of_range_partitions().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["boot_a", "vbmeta_a", "userdata"]);
+}
+
+fn ufs(lu0_size: u64, lu1_size: u64) -> UfsStorage {
+    UfsStorage {
+        info: UfsInfo {
+            kind: 0x30,
+            block_size: 0x1000,
+            lu0_size,
+            lu1_size,
+            lu2_size: 0x1D_C000_0000,
+            cid: Vec::new(),
+            fwver: Vec::new(),
+            serial: Vec::new(),
+        },
+    }
+}
+
+#[test]
+fn boot_region_locate() {
+    let emmc_storage: &dyn Storage = &emmc(0x7_4000_0000);
+    let mut no_boot2 = emmc(0x7_4000_0000);
+    no_boot2.info.boot2_size = 0;
+    let no_boot2: &dyn Storage = &no_boot2;
+    let ufs_storage: &dyn Storage = &ufs(0x40_0000, 0x80_0000);
+    let nand: &dyn Storage = &NandStorage {
+        info: NandInfo {
+            kind: 0x10,
+            page_size: 0x800,
+            erase_size: 0x2_0000,
+            spare_size: 0x40,
+            total_size: 0x1000_0000,
+            available_size: 0xF00_0000,
+            bmt_exist: true,
+            id: Vec::new(),
+        },
+    };
+    let boot1 = PartitionKind::Emmc(EmmcPartition::Boot1);
+    let boot2 = PartitionKind::Emmc(EmmcPartition::Boot2);
+    let lu0 = PartitionKind::Ufs(UfsPartition::Lu0);
+    let lu1 = PartitionKind::Ufs(UfsPartition::Lu1);
+
+    let cases = [
+        ("emmc", emmc_storage, BootRegion::Boot1, Some((boot1, 0x40_0000))),
+        ("emmc", emmc_storage, BootRegion::Boot2, Some((boot2, 0x40_0000))),
+        ("emmc without boot2", no_boot2, BootRegion::Boot1, Some((boot1, 0x40_0000))),
+        ("emmc without boot2", no_boot2, BootRegion::Boot2, None),
+        ("ufs", ufs_storage, BootRegion::Boot1, Some((lu0, 0x40_0000))),
+        ("ufs", ufs_storage, BootRegion::Boot2, Some((lu1, 0x80_0000))),
+        // No boot region on NAND, the preloader lives in the flash array
+        ("nand", nand, BootRegion::Boot1, None),
+        ("nand", nand, BootRegion::Boot2, None),
+    ];
+
+    for (name, storage, region, expected) in cases {
+        assert_eq!(region.locate(storage), expected, "{} {}", name, region.as_str());
+    }
+}
+
+#[test]
+fn erase_span() {
+    const KIB: u64 = 0x400;
+    const MIB: u64 = 0x10_0000;
+    let span = |start, size, head, tail| EraseSpan { start, size, head, tail };
+
+    let cases = [
+        // address, size, group size, span
+        (0, 2 * MIB, 512 * KIB, span(0, 2 * MIB, 0, 0)),
+        (0, 2 * MIB, 4 * MIB, span(0, 0, 2 * MIB, 0)),
+        (0x200, 2 * MIB, 512 * KIB, span(512 * KIB, 1536 * KIB, 512 * KIB - 0x200, 0x200)),
+        (0x200, 8 * MIB, 4 * MIB, span(4 * MIB, 4 * MIB, 4 * MIB - 0x200, 0x200)),
+        (MIB, 7 * MIB, 4 * MIB, span(4 * MIB, 4 * MIB, 3 * MIB, 0)),
+        (4 * MIB, 5 * MIB, 4 * MIB, span(4 * MIB, 4 * MIB, 0, MIB)),
+        // Inside a single group, or straddling two without covering either
+        (0x1000, 0x2000, 512 * KIB, span(0x1000, 0, 0x2000, 0)),
+        (3 * MIB, 2 * MIB, 4 * MIB, span(3 * MIB, 0, 2 * MIB, 0)),
+        // Above 4 GiB
+        (0x1_0000_0000 + KIB, 8 * MIB, 4 * MIB, span(0x1_0040_0000, 4 * MIB, 4 * MIB - KIB, KIB)),
+        // No group size reported
+        (0x200, 0x400, 0, span(0x200, 0x400, 0, 0)),
+    ];
+
+    for (address, size, group_size, expected) in cases {
+        let span = EraseSpan::new(address, size, group_size);
+        let name = format!("0x{:X}+0x{:X} in 0x{:X} groups", address, size, group_size);
+        assert_eq!(span, expected, "{}", name);
+        assert_eq!(span.head + span.size + span.tail, size, "{}", name);
+        if span.size != 0 {
+            assert!(span.start.is_multiple_of(group_size.max(1)), "{}", name);
+            assert!(span.size.is_multiple_of(group_size.max(1)), "{}", name);
+        }
+    }
+}
+
+#[test]
+fn erase_group_sizes() {
+    // The block size when the DA doesn't report it
+    assert_eq!(emmc(0x7_4000_0000).erase_group_size(), 0x200);
+    let mut reported = emmc(0x7_4000_0000);
+    reported.info.erase_group_size = 0x40_0000;
+    assert_eq!(reported.erase_group_size(), 0x40_0000);
+    assert_eq!(ufs(0x40_0000, 0x40_0000).erase_group_size(), 0x1000);
+    assert_eq!(emmc(0x7_4000_0000).erase_method(), EraseMethod::Erase);
+    assert_eq!(ufs(0x40_0000, 0x40_0000).erase_method(), EraseMethod::Discard);
+}
+
+#[test]
+fn erase_group_size_from_the_xml_storage_info() {
+    let info = |extra: &str| {
+        let sizes = ["boot1", "boot2", "rpmb", "gp1", "gp2", "gp3", "gp4", "user"]
+            .map(|name| format!("<{0}_size>0x400000</{0}_size>", name))
+            .concat();
+        let emmc = format!("<block_size>0x200</block_size>{}<id>00</id>{}", sizes, extra);
+        format!("<?xml version=\"1.0\" encoding=\"utf-8\"?><da><emmc>{}</emmc></da>", emmc)
+    };
+
+    let plain = EmmcStorage::from_xml_response(&info("")).unwrap();
+    assert_eq!(plain.erase_group_size(), 0x200);
+
+    let xml = info("<erase_group_size>0x80000</erase_group_size>");
+    let reported = EmmcStorage::from_xml_response(&xml).unwrap();
+    assert_eq!(reported.erase_group_size(), 0x8_0000);
+}
+
+#[test]
+fn region_contains() {
+    let user = RegionInfo::new(PartitionKind::Emmc(EmmcPartition::User), 0x7_4000_0000, true);
+    assert!(user.contains(0, 0x7_4000_0000));
+    assert!(user.contains(0x7_3FF8_0000, 0x8_0000));
+    assert!(user.contains(0x7_4000_0000, 0));
+    assert!(!user.contains(0x7_3FF8_0000, 0x8_0200));
+    assert!(!user.contains(u64::MAX, 0x200));
+}
@@ -0,0 +1,112 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use penumbra::da::xml::{
+    EraseFlash,
+    ReadFlash,
+    Section,
+    SetRuntimeParameter,
+    WriteFlash,
+    XmlCommand,
+    create_cmd,
+};
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="utf-8"?><da>"#;
+
+/// A command with a field in each section, `legacy` standing for a `custom_arg = "..."` one
+struct Sectioned;
+
+impl XmlCommand for Sectioned {
+    fn cmd_name(&self) -> &'static str {
+        "SECTIONED"
+    }
+
+    fn args(&self) -> Vec<(Section, &'static str, String)> {
+        vec![
+            (Section::Custom("legacy"), "mode", String::from("1")),
+            (Section::Adv, "dram", String::from("YES")),
+            (Section::Arg, "target/name", String::from("boot")),
+        ]
+    }
+
+    fn version(&self) -> &'static str {
+        "1.0"
+    }
+}
+
+#[test]
+fn section_tags() {
+    assert_eq!(Section::Arg.as_str(), "arg");
+    assert_eq!(Section::Adv.as_str(), "adv");
+    assert_eq!(Section::Custom("legacy").as_str(), "legacy");
+}
+
+#[test]
+fn sections_serialize_to_their_tag() {
+    let expected = [
+        HEADER,
+        "<version>1.0</version><command>CMD:SECTIONED</command>",
+        "<arg><target><name>boot</name></target></arg>",
+        "<adv><dram>YES</dram></adv>",
+        "<legacy><mode>1</mode></legacy>",
+        "</da>\0",
+    ];
+    assert_eq!(create_cmd(&Sectioned), expected.concat());
+}
+
+#[test]
+fn derived_fields_go_in_their_section() {
+    let cmd = SetRuntimeParameter::new("NONE", "AUTO-DETECT", "INFO", "UART", "LINUX", "YES");
+    let sections: Vec<_> = cmd.args().into_iter().map(|(s, tag, _)| (s, tag)).collect();
+    assert_eq!(
+        sections,
+        [
+            (Section::Arg, "checksum_level"),
+            (Section::Arg, "battery_exist"),
+            (Section::Arg, "da_log_level"),
+            (Section::Arg, "log_channel"),
+            (Section::Arg, "system_os"),
+            (Section::Adv, "initialize_dram"),
+        ]
+    );
+
+    let expected = [
+        HEADER,
+        "<version>1.1</version><command>CMD:SET-RUNTIME-PARAMETER</command><arg>",
+        "<checksum_level>NONE</checksum_level><battery_exist>AUTO-DETECT</battery_exist>",
+        "<da_log_level>INFO</da_log_level><log_channel>UART</log_channel>",
+        "<system_os>LINUX</system_os></arg>",
+        "<adv><initialize_dram>YES</initialize_dram></adv>",
+        "</da>\0",
+    ];
+    assert_eq!(create_cmd(&cmd), expected.concat());
+}
+
+#[test]
+fn offsets_and_lengths_above_4gib() {
+    let args = |cmd: &dyn XmlCommand| -> Vec<(&str, String)> {
+        cmd.args().into_iter().map(|(_, tag, value)| (tag, value)).collect()
+    };
+
+    let read = ReadFlash::new("EMMC-USER", "EMMC-USER", 0x1_0000_0200u64, 0x7_3FFF_0000u64);
+    assert_eq!(
+        args(&read)[2..],
+        [("length", String::from("0x100000200")), ("offset", String::from("0x73FFF0000"))]
+    );
+
+    let write = WriteFlash::new("EMMC-USER", 0x2_0000_0000u64, 0x1_2340_0000u64);
+    assert_eq!(
+        args(&write)[1..],
+        [
+            ("source_file", String::from("MEM:\\0x0:0x200000000")),
+            ("offset", String::from("0x123400000"))
+        ]
+    );
+
+    let erase = EraseFlash::new("EMMC-USER", 0x1_8000_0000u64, 0x4_0000_0000u64);
+    assert_eq!(
+        args(&erase)[1..],
+        [("length", String::from("0x180000000")), ("offset", String::from("0x400000000"))]
+    );
+}
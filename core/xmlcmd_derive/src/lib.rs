@@ -4,7 +4,7 @@
 */
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use regex::Regex;
 use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, LitStr, parse_macro_input};
 
@@ -13,8 +13,52 @@ use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, LitStr, parse_macr
 /// to args, otherwise it's either Simple or Formatted (if fmt).
 enum FieldMeta {
     Skip,
-    Simple { tag: String, section: Option<String> },
-    Formatted { tag: String, fmt: String, section: Option<String> },
+    Simple { tag: String, section: SectionMeta },
+    Formatted { tag: String, fmt: String, section: SectionMeta },
+}
+
+/// The section a field is serialized in.
+/// Known sections map to a `Section` variant, anything else given through
+/// the legacy `custom_arg = "..."` form becomes `Section::Custom`.
+enum SectionMeta {
+    Known(Ident),
+    Custom(LitStr),
+}
+
+impl SectionMeta {
+    /// Sections the DA understands, as (name, `Section` variant).
+    const KNOWN: &[(&str, &str)] = &[("arg", "Arg"), ("adv", "Adv")];
+
+    fn known(name: &str) -> Option<Self> {
+        Self::KNOWN
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, variant)| SectionMeta::Known(Ident::new(variant, Span::call_site())))
+    }
+
+    fn to_tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            SectionMeta::Known(variant) => quote! { crate::da::xml::Section::#variant },
+            SectionMeta::Custom(name) => {
+                // Emits a deprecation warning on the literal, pointing users to `section = ...`
+                let usage = quote_spanned! { name.span() =>
+                    #[allow(clippy::let_unit_value)]
+                    let _ = CUSTOM_ARG;
+                };
+                quote! {
+                    {
+                        #[deprecated(
+                            note = "free-form `custom_arg` sections are deprecated, \
+                                    use `section = ...` with a known section instead"
+                        )]
+                        const CUSTOM_ARG: () = ();
+                        #usage
+                        crate::da::xml::Section::Custom(#name)
+                    }
+                }
+            }
+        }
+    }
 }
 
 /// Struct-level metadata extracted from #[xmlcmd(... )]
@@ -36,6 +80,9 @@ struct CmdMeta {
 ///     #[xml(tag = "length"), fmt = "{length}"]
 ///     length: u32,
 /// }
+///
+/// Fields are serialized in the `arg` section, `#[xml(section = adv)]` puts them
+/// in another one (see `Section` for the known sections).
 #[proc_macro_derive(XmlCommand, attributes(xmlcmd, xml))]
 pub fn xmlcmd_derive(input: TokenStream) -> TokenStream {
     let parsed = parse_macro_input!(input as DeriveInput);
@@ -50,7 +97,10 @@ pub fn xmlcmd_derive(input: TokenStream) -> TokenStream {
     let cmd_meta = extract_command_meta(&parsed.attrs, name);
     let cmd_name = &cmd_meta.name;
     let cmd_version = &cmd_meta.version;
-    let arg_entries = extract_field_entries(&parsed.data);
+    let arg_entries = match extract_field_entries(&parsed.data) {
+        Ok(entries) => entries,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let (constructor_args, constructor_fields) = extract_constructor(&parsed.data);
 
     quote! {
@@ -73,7 +123,7 @@ pub fn xmlcmd_derive(input: TokenStream) -> TokenStream {
                 #cmd_version
             }
 
-            fn args(&self) -> Vec<(Option<&'static str>, &'static str, String)> {
+            fn args(&self) -> Vec<(crate::da::xml::Section, &'static str, String)> {
                 vec![
                     #(#arg_entries),*
                 ]
@@ -134,7 +184,7 @@ fn extract_constructor(
     (constructor_args, constructor_fields)
 }
 
-fn extract_field_entries(data: &Data) -> Vec<proc_macro2::TokenStream> {
+fn extract_field_entries(data: &Data) -> Result<Vec<proc_macro2::TokenStream>, Error> {
     let mut arg_entries = Vec::new();
 
     if let Data::Struct(data_struct) = data
@@ -142,14 +192,11 @@ fn extract_field_entries(data: &Data) -> Vec<proc_macro2::TokenStream> {
     {
         for field in &fields_named.named {
             let ident = field.ident.as_ref().unwrap();
-            match extract_field_metadata(&field.attrs, ident) {
+            match extract_field_metadata(&field.attrs, ident)? {
                 FieldMeta::Skip => continue,
 
                 FieldMeta::Simple { tag, section } => {
-                    let section_expr = match &section {
-                        Some(s) => quote! { Some(#s) },
-                        None => quote! { None },
-                    };
+                    let section_expr = section.to_tokens();
                     let tag_lit = LitStr::new(&tag, Span::call_site());
                     arg_entries.push(quote! {
                         (#section_expr, #tag_lit, self.#ident.to_string())
@@ -167,10 +214,7 @@ fn extract_field_entries(data: &Data) -> Vec<proc_macro2::TokenStream> {
                         quote! { #id = self.#id }
                     });
 
-                    let section_expr = match &section {
-                        Some(s) => quote! { Some(#s) },
-                        None => quote! { None },
-                    };
+                    let section_expr = section.to_tokens();
 
                     let tag_lit = LitStr::new(&tag, Span::call_site());
                     arg_entries.push(quote! {
@@ -181,10 +225,10 @@ fn extract_field_entries(data: &Data) -> Vec<proc_macro2::TokenStream> {
         }
     }
 
-    arg_entries
+    Ok(arg_entries)
 }
 
-fn extract_field_metadata(attrs: &[Attribute], ident: &Ident) -> FieldMeta {
+fn extract_field_metadata(attrs: &[Attribute], ident: &Ident) -> Result<FieldMeta, Error> {
     let mut tag_name = ident.to_string();
     let mut fmt_str = None;
     let mut section = None;
@@ -196,45 +240,45 @@ fn extract_field_metadata(attrs: &[Attribute], ident: &Ident) -> FieldMeta {
         }
         saw_xml = true;
 
-        let _ = attr.parse_nested_meta(|meta| {
-            let ident = meta.path.get_ident().map(|i| i.to_string());
+        attr.parse_nested_meta(|meta| {
+            let Some(name) = meta.path.get_ident().map(|i| i.to_string()) else {
+                return Ok(());
+            };
 
-            if let Some(name) = ident {
-                let value: Option<String> =
-                    meta.value().ok().and_then(|v| v.parse::<LitStr>().ok()).map(|lit| lit.value());
+            match name.as_str() {
+                "section" => {
+                    let value: Ident = meta.value()?.parse()?;
+                    section = Some(SectionMeta::known(&value.to_string()).ok_or_else(|| {
+                        Error::new_spanned(&value, format!("unknown XML section `{}`", value))
+                    })?);
+                }
+                "tag" | "fmt" | "custom_arg" => {
+                    let Some(lit) = meta.value().ok().and_then(|v| v.parse::<LitStr>().ok()) else {
+                        return Ok(());
+                    };
 
-                match name.as_str() {
-                    "tag" => {
-                        if let Some(v) = value {
-                            tag_name = v
-                        }
-                    }
-                    "fmt" => {
-                        if let Some(v) = value {
-                            fmt_str = Some(v)
-                        }
-                    }
-                    "custom_arg" => {
-                        if let Some(v) = value {
-                            section = Some(v)
-                        }
+                    match name.as_str() {
+                        "tag" => tag_name = lit.value(),
+                        "fmt" => fmt_str = Some(lit.value()),
+                        _ => section = Some(SectionMeta::Custom(lit)),
                     }
-                    _ => {}
                 }
+                _ => {}
             }
 
             Ok(())
-        });
+        })?;
     }
 
     if !saw_xml {
-        return FieldMeta::Skip;
+        return Ok(FieldMeta::Skip);
     }
 
+    let section = section.unwrap_or_else(|| SectionMeta::known("arg").unwrap());
     if let Some(fmt) = fmt_str {
-        FieldMeta::Formatted { tag: tag_name, fmt, section }
+        Ok(FieldMeta::Formatted { tag: tag_name, fmt, section })
     } else {
-        FieldMeta::Simple { tag: tag_name, section }
+        Ok(FieldMeta::Simple { tag: tag_name, section })
     }
 }
 
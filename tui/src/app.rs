@@ -7,17 +7,22 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::Result;
-use penumbra::da::DAFile;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use penumbra::api::DAFile;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::style::Style;
 use ratatui::widgets::Block;
 use ratatui::{DefaultTerminal, Frame};
 
 use crate::cli::CliArgs;
+use crate::cli::helpers::{interrupted_operations, open_journal};
 use crate::components::ThemedWidgetRef;
 use crate::components::dialog::{Dialog, DialogBuilder};
 use crate::config::AntumbraConfig;
+use crate::i18n;
+use crate::notify::NotifyMode;
 use crate::pages::{DevicePage, OptionsPage, Page, WelcomePage};
+#[cfg(unix)]
+use crate::suspend::SuspendHandler;
 use crate::themes::{Theme, load_themes};
 
 #[derive(PartialEq, Clone, Copy, Default)]
@@ -42,6 +47,8 @@ pub struct AppCtx {
 pub struct App {
     current_page: Box<dyn Page + Send>,
     pub context: AppCtx,
+    #[cfg(unix)]
+    suspend: SuspendHandler,
 }
 
 pub struct Loader {
@@ -140,12 +147,60 @@ impl AppCtx {
     pub fn set_theme(&mut self, theme_id: &str) {
         let themes = load_themes();
         if let Some(theme) = themes.get(theme_id) {
-            self.theme = theme();
+            self.theme = theme().with_overrides(self.config.theme_overrides());
             self.config.theme = self.theme.id.to_string();
             self.config.save().ok();
         }
     }
 
+    /// The flash transfer rate limit in bytes per second, if any
+    pub fn rate_limit(&self) -> Option<u64> {
+        Some(self.config.rate_limit).filter(|&r| r > 0)
+    }
+
+    pub fn set_rate_limit(&mut self, rate: u64) {
+        self.config.rate_limit = rate;
+        self.config.save().ok();
+    }
+
+    pub fn verify_writes(&self) -> bool {
+        self.config.verify_writes
+    }
+
+    pub fn set_verify_writes(&mut self, verify: bool) {
+        self.config.verify_writes = verify;
+        self.config.save().ok();
+    }
+
+    pub fn notify_mode(&self) -> NotifyMode {
+        self.config.notify
+    }
+
+    pub fn set_notify_mode(&mut self, mode: NotifyMode) {
+        self.config.notify = mode;
+        self.config.save().ok();
+    }
+
+    pub fn notify_threshold(&self) -> Duration {
+        Duration::from_secs(self.config.notify_threshold)
+    }
+
+    pub fn set_notify_threshold(&mut self, secs: u64) {
+        self.config.notify_threshold = secs;
+        self.config.save().ok();
+    }
+
+    /// The language setting, a language code or "auto"
+    pub fn language(&self) -> &str {
+        &self.config.language
+    }
+
+    pub fn set_language(&mut self, code: &str) {
+        i18n::set_language(code);
+        self.config.language = code.to_string();
+        self.config.save().ok();
+    }
+
     pub fn config(&mut self) -> &mut AntumbraConfig {
         &mut self.config
     }
@@ -154,12 +209,15 @@ impl AppCtx {
 impl Default for AppCtx {
     fn default() -> Self {
         let config = AntumbraConfig::load();
+        // The environment may override the language picked up by main
+        i18n::set_language(&config.language);
         let theme_map = load_themes();
 
         let theme = theme_map
             .get(config.theme.as_str())
             .map(|constructor| constructor())
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .with_overrides(config.theme_overrides());
 
         Self {
             loader: None,
@@ -174,6 +232,23 @@ impl Default for AppCtx {
     }
 }
 
+#[cfg(test)]
+impl AppCtx {
+    /// A context with the default config, neither read from nor written to disk
+    pub fn detached() -> Self {
+        Self {
+            loader: None,
+            preloader: None,
+            exit: false,
+            current_page_id: AppPage::default(),
+            next_page_id: None,
+            config: AntumbraConfig::default(),
+            theme: Theme::default(),
+            dialog: None,
+        }
+    }
+}
+
 impl App {
     pub fn new(args: &CliArgs) -> App {
         let mut ctx = AppCtx::default();
@@ -185,7 +260,19 @@ impl App {
             ctx.set_loader(da_path.clone(), file)
         }
 
-        App { current_page: Box::new(WelcomePage::new()), context: ctx }
+        if let Some(journal) = open_journal() {
+            let warnings = interrupted_operations(&journal);
+            if !warnings.is_empty() {
+                error_dialog!(ctx, warnings.join("\n"));
+            }
+        }
+
+        App {
+            current_page: Box::new(WelcomePage::new()),
+            context: ctx,
+            #[cfg(unix)]
+            suspend: SuspendHandler::new(),
+        }
     }
 
     pub async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
@@ -197,10 +284,25 @@ impl App {
             }
 
             self.current_page.update(&mut self.context).await;
+
+            // Pages keep processing their events while suspended in the background,
+            // so the progress is up to date once back in the foreground
+            #[cfg(unix)]
+            {
+                self.suspend.poll(terminal)?;
+                if self.suspend.is_suspended() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            }
+
             terminal.draw(|f: &mut Frame<'_>| self.draw(f))?;
 
             self.handle_events().await?;
         }
+
+        // Let the page release what it holds, e.g. reboot a connected device
+        self.current_page.on_exit(&mut self.context).await;
         Ok(())
     }
 
@@ -208,31 +310,59 @@ impl App {
         if event::poll(Duration::from_millis(100))?
             && let Event::Key(key) = event::read()?
         {
-            // Force exit: [Ctrl + Delete]
-            if key.code == KeyCode::Delete && key.modifiers.contains(KeyModifiers::CONTROL) {
-                self.context.quit();
-            }
+            self.handle_key(key).await;
+        }
 
-            if let Some(dialog) = &mut self.context.dialog {
-                match key.code {
-                    KeyCode::Left => dialog.move_left(),
-                    KeyCode::Right => dialog.move_right(),
-                    KeyCode::Enter => {
-                        dialog.press_selected();
-                        self.context.dialog = None;
-                    }
-                    KeyCode::Esc => {
-                        self.context.dialog = None;
-                    }
-                    _ => {}
+        Ok(())
+    }
+
+    /// Sends a key to the open dialog if any, or to the current page
+    async fn handle_key(&mut self, key: KeyEvent) {
+        if !Self::should_dispatch(&key) {
+            return;
+        }
+
+        // Force exit: [Ctrl + Delete]
+        if key.code == KeyCode::Delete && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.context.quit();
+        }
+
+        // Suspend: [Ctrl + Z], raw mode turns it into a regular key press
+        #[cfg(unix)]
+        if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.suspend.request();
+            return;
+        }
+
+        if let Some(dialog) = &mut self.context.dialog {
+            match key.code {
+                KeyCode::Left => dialog.move_left(),
+                KeyCode::Right => dialog.move_right(),
+                KeyCode::Enter => {
+                    dialog.press_selected();
+                    self.context.dialog = None;
                 }
-                return Ok(());
+                KeyCode::Esc => {
+                    self.context.dialog = None;
+                }
+                _ => {}
             }
-
-            self.current_page.handle_input(&mut self.context, key).await;
+            return;
         }
 
-        Ok(())
+        self.current_page.handle_input(&mut self.context, key).await;
+    }
+
+    /// Filters out key events that shouldn't reach pages or dialogs.
+    /// Windows reports both press and release events, so only presses go through.
+    /// Repeats are kept for navigation, but not for toggle keys, to avoid flickering
+    /// a checkbox while the key is held.
+    fn should_dispatch(key: &KeyEvent) -> bool {
+        match key.kind {
+            KeyEventKind::Press => true,
+            KeyEventKind::Repeat => !matches!(key.code, KeyCode::Char('x') | KeyCode::Char(' ')),
+            KeyEventKind::Release => false,
+        }
     }
 
     fn draw(&mut self, frame: &mut Frame<'_>) {
@@ -264,3 +394,126 @@ impl App {
         self.current_page.on_enter(&mut self.context).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use ratatui::crossterm::event::KeyEventState;
+
+    use super::*;
+    use crate::components::DialogButton;
+    use crate::components::selectable_list::{
+        ListItemEntryBuilder,
+        SelectableList,
+        SelectableListBuilder,
+    };
+
+    /// A page moving through a list with Down, and toggling its items with X
+    struct ListPage(Arc<Mutex<SelectableList>>);
+
+    #[async_trait::async_trait]
+    impl Page for ListPage {
+        fn render(&mut self, _frame: &mut Frame<'_>, _ctx: &mut AppCtx) {}
+
+        async fn handle_input(&mut self, _ctx: &mut AppCtx, key: KeyEvent) {
+            let mut list = self.0.lock().unwrap();
+            match key.code {
+                KeyCode::Down => list.next(),
+                KeyCode::Char('x') => list.toggle_selected(),
+                _ => {}
+            }
+        }
+    }
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent { code, modifiers: KeyModifiers::NONE, kind, state: KeyEventState::NONE }
+    }
+
+    /// An app showing a three items [`ListPage`], with no config read from or written to disk
+    fn app() -> (App, Arc<Mutex<SelectableList>>) {
+        let items = ["boot", "vbmeta", "super"]
+            .iter()
+            .map(|label| ListItemEntryBuilder::new(*label).build().unwrap())
+            .collect();
+        let list = SelectableListBuilder::default()
+            .items(items)
+            .highlight_symbol(">")
+            .checkboxes(true)
+            .build()
+            .unwrap();
+        let list = Arc::new(Mutex::new(list));
+
+        let app = App {
+            current_page: Box::new(ListPage(list.clone())),
+            context: AppCtx::detached(),
+            #[cfg(unix)]
+            suspend: SuspendHandler::new(),
+        };
+        (app, list)
+    }
+
+    #[test]
+    fn key_kinds_dispatched() {
+        let cases = [
+            (KeyCode::Down, KeyEventKind::Press, true),
+            (KeyCode::Down, KeyEventKind::Repeat, true),
+            (KeyCode::Down, KeyEventKind::Release, false),
+            (KeyCode::Enter, KeyEventKind::Release, false),
+            (KeyCode::Char('x'), KeyEventKind::Press, true),
+            (KeyCode::Char('x'), KeyEventKind::Repeat, false),
+            (KeyCode::Char(' '), KeyEventKind::Repeat, false),
+            (KeyCode::Char('x'), KeyEventKind::Release, false),
+        ];
+        for (code, kind, expected) in cases {
+            assert_eq!(App::should_dispatch(&key(code, kind)), expected, "{:?} {:?}", code, kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn release_doesnt_reach_lists() {
+        let (mut app, list) = app();
+
+        // A press and its release move down once
+        app.handle_key(key(KeyCode::Down, KeyEventKind::Press)).await;
+        app.handle_key(key(KeyCode::Down, KeyEventKind::Release)).await;
+        assert_eq!(list.lock().unwrap().selected_index(), Some(1));
+
+        // Holding X toggles once
+        app.handle_key(key(KeyCode::Char('x'), KeyEventKind::Press)).await;
+        app.handle_key(key(KeyCode::Char('x'), KeyEventKind::Repeat)).await;
+        app.handle_key(key(KeyCode::Char('x'), KeyEventKind::Release)).await;
+        let list = list.lock().unwrap();
+        let checked: Vec<_> = list.checked_items().iter().map(|i| i.label.clone()).collect();
+        assert_eq!(checked, ["vbmeta"]);
+    }
+
+    #[tokio::test]
+    async fn release_doesnt_press_dialog_buttons() {
+        let (mut app, list) = app();
+        let pressed = Arc::new(AtomicUsize::new(0));
+        let counter = pressed.clone();
+        let mut dialog = DialogBuilder::other("Erase?", &app.context.theme);
+        dialog.button(DialogButton::new("Cancel", || {}));
+        dialog.button(DialogButton::new("Erase", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        app.context.set_dialog(&mut dialog);
+
+        app.handle_key(key(KeyCode::Right, KeyEventKind::Release)).await;
+        app.handle_key(key(KeyCode::Enter, KeyEventKind::Release)).await;
+        assert!(app.context.dialog.is_some());
+        assert_eq!(app.context.dialog.as_ref().unwrap().selected, 0);
+
+        app.handle_key(key(KeyCode::Right, KeyEventKind::Press)).await;
+        app.handle_key(key(KeyCode::Enter, KeyEventKind::Press)).await;
+        assert!(app.context.dialog.is_none());
+        assert_eq!(pressed.load(Ordering::SeqCst), 1);
+
+        // The release of the Enter that closed the dialog doesn't reach the page
+        app.handle_key(key(KeyCode::Enter, KeyEventKind::Release)).await;
+        assert_eq!(pressed.load(Ordering::SeqCst), 1);
+        assert_eq!(list.lock().unwrap().selected_index(), Some(0));
+    }
+}
@@ -0,0 +1,56 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Args;
+use log::warn;
+use penumbra::api::{DEFAULT_BRIDGE_PORT, Device, serve_bridge};
+
+use crate::cli::MtkCommand;
+use crate::cli::common::CommandMetadata;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Args, Debug)]
+pub struct BridgeArgs {
+    /// Address to listen on. The bridge has no authentication, only expose it to
+    /// trusted networks.
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value_t = format!("127.0.0.1:{}", DEFAULT_BRIDGE_PORT)
+    )]
+    pub listen: String,
+}
+
+impl CommandMetadata for BridgeArgs {
+    fn about() -> &'static str {
+        t("cmd.bridge.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.bridge.long_about")
+    }
+}
+
+impl BridgeArgs {
+    /// Serves the local port until interrupted
+    pub async fn serve(&self) -> Result<()> {
+        if !self.listen.starts_with("127.") && !self.listen.starts_with("localhost") {
+            warn!("The bridge has no authentication, anyone reaching {} can use it", self.listen);
+        }
+
+        serve_bridge(&self.listen).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MtkCommand for BridgeArgs {
+    // Handled by `run_cli` before connecting, since the bridge claims the port itself
+    async fn run(&self, _dev: &mut Device, _state: &mut PersistedDeviceState) -> Result<()> {
+        self.serve().await
+    }
+}
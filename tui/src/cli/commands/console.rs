@@ -0,0 +1,435 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use clap::Args;
+use clap_num::maybe_hex;
+use log::{info, warn};
+use penumbra::api::protocol::{DAProtocol, XFlash, XFlashCmd, Xml, XmlCmdLifetime};
+use penumbra::api::{Device, Error, XFlashError};
+use tokio::time::{Duration, timeout};
+
+use crate::cli::MtkCommand;
+use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+/// How long a single exchange may take before the session is considered out of sync
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const XFLASH_HELP: &str = "\
+cmd <code>                  send a command code, then read its status
+devctrl <code> [hex...]     send a devctrl code with payloads, or read its response without
+send <hex> [hex...]         send data packets, then read the status
+read                        read a data packet
+status                      read a status";
+
+const XML_HELP: &str = "\
+xml <NAME> [tag=value...]   send CMD:<NAME> with the arguments in <arg>, tags can be paths (a/b)
+send <hex> [hex...]         send data packets
+read                        read a data frame
+ack [value]                 send OK, or OK@0x<value>
+end                         wait for CMD:END and acknowledge it";
+
+const COMMON_HELP: &str = "\
+history                     list the commands typed so far
+!<n>                        run the n-th command of the history again
+replay <file>               run the commands of a file, one per line, # for comments
+help                        show this help
+quit                        leave the console";
+
+#[derive(Args, Debug)]
+pub struct ConsoleArgs {
+    #[command(flatten)]
+    pub da: DaArgs,
+    /// Run the commands of a file instead of prompting for them
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+}
+
+impl CommandMetadata for ConsoleArgs {
+    fn about() -> &'static str {
+        t("cmd.console.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.console.long_about")
+    }
+}
+
+/// The protocol the console talks, picked from the DA in use
+enum Session<'a> {
+    XFlash(&'a mut XFlash),
+    Xml(&'a mut Xml),
+}
+
+impl Session<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Session::XFlash(_) => "xflash",
+            Session::Xml(_) => "xml",
+        }
+    }
+}
+
+/// What a console line asks for, once parsed
+enum Line<'a> {
+    Empty,
+    Quit,
+    Help,
+    History,
+    Rerun(usize),
+    Replay(&'a str),
+    Protocol(&'a str, Vec<&'a str>),
+}
+
+fn parse_line(line: &str) -> Result<Line<'_>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(Line::Empty);
+    }
+
+    if let Some(index) = line.strip_prefix('!') {
+        let index = index.parse().map_err(|_| anyhow!("Invalid history entry '{}'", index))?;
+        return Ok(Line::Rerun(index));
+    }
+
+    let mut words = line.split_whitespace();
+    let cmd = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+
+    Ok(match (cmd, args.as_slice()) {
+        ("quit" | "exit", _) => Line::Quit,
+        ("help" | "?", _) => Line::Help,
+        ("history", _) => Line::History,
+        ("replay", [file]) => Line::Replay(file),
+        ("replay", _) => bail!("Usage: replay <file>"),
+        _ => Line::Protocol(cmd, args),
+    })
+}
+
+fn parse_code(s: &str) -> Result<u32> {
+    maybe_hex::<u32>(s).map_err(|_| anyhow!("Invalid code '{}'", s))
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim_start_matches("0x").replace(':', "");
+    hex::decode(&s).map_err(|e| anyhow!("Invalid hex string '{}': {}", s, e))
+}
+
+fn parse_payloads(args: &[&str]) -> Result<Vec<Vec<u8>>> {
+    args.iter().map(|arg| parse_hex(arg)).collect()
+}
+
+/// Prints a response: as text if it's printable (like XML), as a hex dump otherwise
+fn print_data(data: &[u8]) {
+    println!("<- {} bytes", data.len());
+
+    let text = String::from_utf8_lossy(data);
+    let text = text.trim_end_matches('\0');
+    if !text.is_empty() && text.chars().all(|c| !c.is_control() || c.is_whitespace()) {
+        println!("{}", text);
+        return;
+    }
+
+    for (i, chunk) in data.chunks(16).enumerate() {
+        let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        println!("{:08X}  {:<47}  {}", i * 16, bytes.join(" "), ascii);
+    }
+}
+
+fn print_status(status: u32) {
+    if status == 0 {
+        println!("<- status 0x00000000 (OK)");
+    } else {
+        println!("<- status {}", XFlashError::from_code(status));
+    }
+}
+
+/// Runs a device exchange, giving up after [`RESPONSE_TIMEOUT`].
+/// Failures are always [`Error`]s, telling them apart from mistyped commands.
+async fn exchange<T>(future: impl Future<Output = penumbra::api::Result<T>>) -> Result<T> {
+    match timeout(RESPONSE_TIMEOUT, future).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(Error::io(format!("No response within {}s", RESPONSE_TIMEOUT.as_secs())))?,
+    }
+}
+
+async fn run_xflash(xflash: &mut XFlash, cmd: &str, args: &[&str]) -> Result<()> {
+    match (cmd, args) {
+        ("cmd", [code]) => {
+            exchange(xflash.send(&parse_code(code)?.to_le_bytes())).await?;
+            print_status(0);
+        }
+        ("devctrl", [code, payloads @ ..]) => {
+            let code = parse_code(code)?;
+            let payloads = parse_payloads(payloads)?;
+
+            exchange(xflash.send(&(XFlashCmd::DeviceCtrl as u32).to_le_bytes())).await?;
+            exchange(xflash.send(&code.to_le_bytes())).await?;
+
+            if payloads.is_empty() {
+                print_data(&exchange(xflash.read_data()).await?);
+                print_status(exchange(xflash.get_status()).await?);
+            } else {
+                let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+                exchange(xflash.send_data(&payloads)).await?;
+                print_status(0);
+            }
+        }
+        ("send", [_, ..]) => {
+            let payloads = parse_payloads(args)?;
+            let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+            exchange(xflash.send_data(&payloads)).await?;
+            print_status(0);
+        }
+        ("read", []) => print_data(&exchange(xflash.read_data()).await?),
+        ("status", []) => print_status(exchange(xflash.get_status()).await?),
+        _ => bail!("Unknown command or wrong arguments, see `help`"),
+    }
+
+    Ok(())
+}
+
+async fn run_xml(xml: &mut Xml, cmd: &str, args: &[&str]) -> Result<()> {
+    match (cmd, args) {
+        ("xml", [name, xml_args @ ..]) => {
+            let xml_args = xml_args
+                .iter()
+                .map(|arg| {
+                    arg.split_once('=').ok_or_else(|| anyhow!("Expected tag=value: {}", arg))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let name = name.trim_start_matches("CMD:").to_uppercase();
+            if exchange(xml.send_raw_cmd(&name, &xml_args)).await? {
+                println!("<- CMD:{} acknowledged", name);
+            } else {
+                println!("<- CMD:{} is not supported by this DA", name);
+            }
+        }
+        ("send", [_, ..]) => {
+            let payloads = parse_payloads(args)?;
+            let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+            exchange(xml.send_data(&payloads)).await?;
+        }
+        ("read", []) => print_data(&exchange(xml.read_data()).await?),
+        ("ack", []) => {
+            exchange(xml.ack(None)).await?;
+        }
+        ("ack", [value]) => {
+            let value = value.trim_start_matches("0x").to_string();
+            exchange(xml.ack(Some(value))).await?;
+        }
+        ("end", []) => {
+            exchange(xml.lifetime_ack(XmlCmdLifetime::CmdEnd)).await?;
+            println!("<- CMD:END acknowledged");
+        }
+        _ => bail!("Unknown command or wrong arguments, see `help`"),
+    }
+
+    Ok(())
+}
+
+/// The console state besides the protocol: history, and whether the last exchange failed
+/// midway, which likely left the DA expecting something else than the next command
+struct Console<'a> {
+    session: Session<'a>,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+    desync: bool,
+}
+
+impl Console<'_> {
+    fn prompt(&self) -> String {
+        let desync = if self.desync { " (desync?)" } else { "" };
+        format!("{}{}> ", self.session.name(), desync)
+    }
+
+    fn print_help(&self) {
+        match self.session {
+            Session::XFlash(_) => println!("{}", XFLASH_HELP),
+            Session::Xml(_) => println!("{}", XML_HELP),
+        }
+        println!("{}", COMMON_HELP);
+        println!("Codes are hex with 0x or decimal, payloads are hex strings.");
+    }
+
+    fn remember(&mut self, line: &str) {
+        self.history.push(line.to_string());
+
+        let Some(path) = &self.history_file else { return };
+        let appended = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = appended {
+            warn!("Failed to save the console history to {}: {}", path.display(), e);
+            self.history_file = None;
+        }
+    }
+
+    /// Runs a single line. Returns `false` when the console should be left.
+    async fn run_line(&mut self, line: &str, depth: usize) -> Result<bool> {
+        match parse_line(line)? {
+            Line::Empty => {}
+            Line::Quit => return Ok(false),
+            Line::Help => self.print_help(),
+            Line::History => {
+                for (i, entry) in self.history.iter().enumerate() {
+                    println!("{:>4}  {}", i, entry);
+                }
+            }
+            Line::Rerun(index) => {
+                let entry = self
+                    .history
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No history entry {}", index))?;
+                println!("{}{}", self.prompt(), entry);
+                return Box::pin(self.run_line(&entry, depth + 1)).await;
+            }
+            Line::Replay(file) => return self.replay(Path::new(file), depth + 1).await,
+            Line::Protocol(cmd, args) => {
+                let result = match &mut self.session {
+                    Session::XFlash(xflash) => run_xflash(xflash, cmd, &args).await,
+                    Session::Xml(xml) => run_xml(xml, cmd, &args).await,
+                };
+
+                match result {
+                    Ok(()) => self.desync = false,
+                    Err(e) => {
+                        // Mistyped commands never reach the device, while a failed exchange
+                        // might have stopped halfway through
+                        if e.downcast_ref::<Error>().is_some() {
+                            self.desync = true;
+                            warn!(
+                                "The DA might be waiting for more data: try `read` or `status` \
+                                 to drain it, or reconnect the device"
+                            );
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Runs the lines of a file, stopping at the first failing one
+    async fn replay(&mut self, path: &Path, depth: usize) -> Result<bool> {
+        if depth > 8 {
+            bail!("Too many nested replays");
+        }
+
+        let script = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+        for (number, line) in script.lines().enumerate() {
+            if matches!(parse_line(line)?, Line::Empty) {
+                continue;
+            }
+
+            println!("{}{}", self.prompt(), line.trim());
+            match Box::pin(self.run_line(line, depth)).await {
+                Ok(true) => {}
+                Ok(false) => return Ok(false),
+                Err(e) => bail!("{}:{}: {}", path.display(), number + 1, e),
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn interactive(&mut self) -> Result<()> {
+        println!("Protocol console ({}), type `help` for the commands.", self.session.name());
+
+        let stdin = io::stdin();
+        loop {
+            print!("{}", self.prompt());
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                println!();
+                return Ok(());
+            }
+
+            let line = line.trim().to_string();
+            if !line.is_empty() && !line.starts_with('!') {
+                self.remember(&line);
+            }
+
+            match self.run_line(&line, 0).await {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                Err(e) => println!("!! {}", e),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MtkCommand for ConsoleArgs {
+    async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        dev.enter_da_mode().await?;
+
+        state.connection_type = CONN_DA;
+        state.flash_mode = 1;
+
+        let protocol = dev.get_protocol().ok_or_else(|| anyhow!("No DA protocol in use"))?;
+        let protocol = protocol.as_any_mut();
+        let session = if protocol.is::<XFlash>() {
+            Session::XFlash(protocol.downcast_mut::<XFlash>().unwrap())
+        } else if let Some(xml) = protocol.downcast_mut::<Xml>() {
+            Session::Xml(xml)
+        } else {
+            bail!("The console supports XFlash and XML DAs only");
+        };
+
+        let history_file = dirs::config_dir().map(|p| p.join("antumbra/console_history"));
+        let history = history_file
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|data| data.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let mut console = Console { session, history, history_file, desync: false };
+
+        match &self.script {
+            Some(script) => {
+                console.replay(script, 0).await?;
+            }
+            None => console.interactive().await?,
+        }
+
+        if console.desync {
+            warn!("Leaving the console with the DA possibly out of sync");
+        } else {
+            info!("Console closed.");
+        }
+
+        Ok(())
+    }
+
+    fn da(&self) -> Option<&PathBuf> {
+        Some(&self.da.da_file)
+    }
+
+    fn pl(&self) -> Option<&PathBuf> {
+        self.da.preloader_file.as_ref()
+    }
+}
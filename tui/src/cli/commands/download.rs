@@ -8,14 +8,13 @@ use anyhow::Result;
 use async_trait::async_trait;
 use clap::Args;
 use log::info;
-use penumbra::Device;
-use tokio::fs::{File, metadata};
-use tokio::io::BufReader;
+use penumbra::api::{Device, Progress};
 
 use crate::cli::MtkCommand;
-use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs, image_size};
+use crate::cli::helpers::{AntumbraProgress, open_image};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct DownloadArgs {
@@ -33,13 +32,11 @@ impl CommandMetadata for DownloadArgs {
     }
 
     fn about() -> &'static str {
-        "Download a file to a specified partition on the device."
+        t("cmd.download.about")
     }
 
     fn long_about() -> &'static str {
-        "Download (flash) a file to a specificed partition on the device.
-        Use this command for flashing stock firmware on locked bootloader, or the device
-        will return write data not allowed error."
+        t("cmd.download.long_about")
     }
 }
 
@@ -51,13 +48,12 @@ impl MtkCommand for DownloadArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
-        let file = File::open(&self.file).await?;
-        let mut reader = BufReader::new(file);
+        let mut reader = open_image(&self.file, dev.write_buffer_size()).await?;
 
-        let file_size = metadata(&self.file).await?.len();
+        let file_size = image_size(&self.file).await?;
 
         let part_size = match dev.dev_info.get_partition(&self.partition).await {
-            Some(p) => p.size as u64,
+            Some(p) => p.size,
             None => {
                 return Err(anyhow::anyhow!("Partition '{}' not found on device.", self.partition));
             }
@@ -71,14 +67,14 @@ impl MtkCommand for DownloadArgs {
             ));
         }
 
-        let pb = AntumbraProgress::new(file_size);
+        let pb = AntumbraProgress::new(file_size).with_phases(dev.phase_tracker());
 
         let mut progress_callback = {
             let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Downloading...");
+            move |progress: &Progress| {
+                pb.update(progress, "Downloading...");
 
-                if written >= total {
+                if progress.is_done() {
                     pb.finish("Download complete!");
                 }
             }
@@ -86,10 +82,7 @@ impl MtkCommand for DownloadArgs {
 
         info!("Downloading to partition '{}'...", self.partition);
 
-        match dev
-            .download(&self.partition, file_size as usize, &mut reader, &mut progress_callback)
-            .await
-        {
+        match dev.download(&self.partition, file_size, &mut reader, &mut progress_callback).await {
             Ok(_) => {}
             Err(e) => {
                 pb.abandon("Download failed!");
@@ -109,4 +102,8 @@ impl MtkCommand for DownloadArgs {
     fn pl(&self) -> Option<&PathBuf> {
         self.da.preloader_file.as_ref()
     }
+
+    fn partitions(&self) -> Vec<String> {
+        vec![self.partition.clone()]
+    }
 }
@@ -8,17 +8,20 @@ use anyhow::Result;
 use async_trait::async_trait;
 use clap::Args;
 use log::info;
-use penumbra::Device;
+use penumbra::api::{Device, EraseMethod, Progress, is_protected_part};
 
 use crate::cli::MtkCommand;
-use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::common::{CONN_DA, CommandMetadata, ConfirmArgs, DaArgs};
+use crate::cli::helpers::{AntumbraProgress, confirm};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct EraseArgs {
     #[command(flatten)]
     pub da: DaArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
     /// The partition to erase
     pub partition: String,
 }
@@ -29,11 +32,11 @@ impl CommandMetadata for EraseArgs {
     }
 
     fn about() -> &'static str {
-        "Erase a partition on the device."
+        t("cmd.erase.about")
     }
 
     fn long_about() -> &'static str {
-        "Erase the specified partition on the device."
+        t("cmd.erase.long_about")
     }
 }
 
@@ -53,14 +56,22 @@ impl MtkCommand for EraseArgs {
             }
         };
 
-        let pb = AntumbraProgress::new(partition.size as u64);
+        // Critical partitions need the name typed back, to avoid bricking by accident
+        let expected = is_protected_part(&partition.name).then_some(partition.name.as_str());
+        confirm(
+            &format!("Erase partition '{}'? All its data will be lost.", partition.name),
+            expected,
+            self.confirm.yes,
+        )?;
+
+        let pb = AntumbraProgress::new(partition.size).with_phases(dev.phase_tracker());
 
         let mut progress_callback = {
             let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Erasing...");
+            move |progress: &Progress| {
+                pb.update(progress, "Erasing...");
 
-                if written >= total {
+                if progress.is_done() {
                     pb.finish("Erase complete!");
                 }
             }
@@ -68,15 +79,25 @@ impl MtkCommand for EraseArgs {
 
         info!("Erasing partition '{}'...", self.partition);
 
-        match dev.erase_partition(&self.partition, &mut progress_callback).await {
-            Ok(_) => {}
+        let report = match dev.erase_partition(&self.partition, &mut progress_callback).await {
+            Ok(report) => report,
             Err(e) => {
                 pb.abandon("Erase failed!");
                 return Err(e)?;
             }
-        }
+        };
 
-        info!("Partition '{}' erase completed.", self.partition);
+        info!(
+            "Partition '{}' erase completed: {} bytes {}, {} zero-filled, {} untouched.",
+            self.partition,
+            report.erased,
+            if report.method == EraseMethod::Discard { "discarded" } else { "erased" },
+            report.zero_filled,
+            report.skipped
+        );
+        if report.method == EraseMethod::Discard {
+            info!("The storage only discarded the data, it may still be recoverable.");
+        }
 
         Ok(())
     }
@@ -88,4 +109,8 @@ impl MtkCommand for EraseArgs {
     fn pl(&self) -> Option<&PathBuf> {
         self.da.preloader_file.as_ref()
     }
+
+    fn partitions(&self) -> Vec<String> {
+        vec![self.partition.clone()]
+    }
 }
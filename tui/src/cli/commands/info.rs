@@ -0,0 +1,102 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Args;
+use human_bytes::human_bytes;
+use log::info;
+use penumbra::api::Device;
+
+use crate::cli::MtkCommand;
+use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    #[command(flatten)]
+    pub da: DaArgs,
+}
+
+impl CommandMetadata for InfoArgs {
+    fn about() -> &'static str {
+        t("cmd.info.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.info.long_about")
+    }
+}
+
+#[async_trait]
+impl MtkCommand for InfoArgs {
+    async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        dev.enter_da_mode().await?;
+
+        state.connection_type = CONN_DA;
+        state.flash_mode = 1;
+
+        let hw_code = dev.dev_info.hw_code().await;
+        match dev.dev_info.chip_name().await {
+            Some(chip) => info!("Chip: {} (0x{:04X})", chip, hw_code),
+            None => info!("HW Code: 0x{:04X}", hw_code),
+        }
+        info!("SoC ID: {}", hex::encode_upper(dev.dev_info.soc_id().await));
+        info!("MEID: {}", hex::encode_upper(dev.dev_info.meid().await));
+        info!("Security: {}", dev.dev_info.security().await);
+        info!("DA Extensions: {}", dev.extensions_state());
+        if let Some(link) = dev.usb_link() {
+            info!("USB: {}", link);
+        }
+        if let Some(speed) = dev.dev_info.usb_speed().await {
+            info!("DA USB Speed: {}", speed.as_str());
+        }
+
+        let regions = dev.regions().await;
+        if regions.is_empty() {
+            info!("Storage: Unknown");
+            return Ok(());
+        }
+
+        if let Some(storage) = dev.dev_info.storage().await {
+            let hw = storage.hw_info();
+            let fields = [
+                ("Flash Vendor", hw.vendor),
+                ("Flash Product", hw.product),
+                ("Flash Firmware", hw.fw_version),
+                ("Flash Serial", hw.serial),
+                ("Flash ID", hw.raw_cid.map(hex::encode_upper)),
+            ];
+            for (label, value) in fields {
+                if let Some(value) = value {
+                    info!("{}: {}", label, value);
+                }
+            }
+        }
+
+        info!("Storage Regions:");
+        for region in regions {
+            info!(
+                "Region: {:<15} \t Size: 0x{:010X} ({}) \t {}",
+                region.kind.as_str(),
+                region.size,
+                human_bytes(region.size as f64),
+                if region.writable { "RW" } else { "RO" }
+            );
+        }
+
+        Ok(())
+    }
+
+    fn da(&self) -> Option<&PathBuf> {
+        Some(&self.da.da_file)
+    }
+
+    fn pl(&self) -> Option<&PathBuf> {
+        self.da.preloader_file.as_ref()
+    }
+}
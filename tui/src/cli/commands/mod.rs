@@ -2,9 +2,14 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+pub mod bridge;
+#[cfg(feature = "devtools")]
+pub mod console;
 pub mod download;
 pub mod erase;
 pub mod format;
+pub mod info;
+pub mod patch;
 pub mod peek;
 pub mod pgpt;
 pub mod readall;
@@ -12,13 +17,22 @@ pub mod readflash;
 pub mod reboot;
 pub mod seccfg;
 pub mod shutdown;
+pub mod state;
+pub mod support_bundle;
+pub mod trace;
 pub mod upload;
+pub mod wait;
 pub mod writeflash;
 pub mod xflash;
 
+pub use bridge::BridgeArgs;
+#[cfg(feature = "devtools")]
+pub use console::ConsoleArgs;
 pub use download::DownloadArgs;
 pub use erase::EraseArgs;
 pub use format::FormatArgs;
+pub use info::InfoArgs;
+pub use patch::PatchArgs;
 pub use peek::PeekArgs;
 pub use pgpt::PgptArgs;
 pub use readall::ReadAllArgs;
@@ -26,6 +40,10 @@ pub use readflash::ReadArgs;
 pub use reboot::RebootArgs;
 pub use seccfg::SeccfgArgs;
 pub use shutdown::ShutdownArgs;
+pub use state::StateArgs;
+pub use support_bundle::SupportBundleArgs;
+pub use trace::TraceArgs;
 pub use upload::UploadArgs;
+pub use wait::WaitArgs;
 pub use writeflash::WriteArgs;
 pub use xflash::XFlashArgs;
@@ -0,0 +1,118 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use clap::Args;
+use clap_num::maybe_hex;
+use log::info;
+use penumbra::api::{Device, is_protected_part};
+
+use crate::cli::MtkCommand;
+use crate::cli::common::{CONN_DA, CommandMetadata, ConfirmArgs, DaArgs};
+use crate::cli::helpers::confirm;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Args, Debug)]
+pub struct PatchArgs {
+    #[command(flatten)]
+    pub da: DaArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+    /// The partition to patch
+    pub partition: String,
+    /// The offset inside the partition to patch at
+    #[clap(value_parser=maybe_hex::<u64>)]
+    pub offset: u64,
+    /// The bytes to write, as a hex string (e.g. 0a0b0c0d)
+    pub bytes: String,
+    /// Only show the bytes that would be changed, without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Read the patched region back to verify the change
+    #[arg(long)]
+    pub verify: bool,
+}
+
+fn parse_hex_bytes(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim_start_matches("0x").replace([' ', ':'], "");
+    let bytes = hex::decode(&s).map_err(|e| anyhow!("Invalid hex string '{}': {}", s, e))?;
+
+    if bytes.is_empty() {
+        return Err(anyhow!("No bytes to patch"));
+    }
+
+    Ok(bytes)
+}
+
+impl CommandMetadata for PatchArgs {
+    fn about() -> &'static str {
+        t("cmd.patch.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.patch.long_about")
+    }
+}
+
+#[async_trait]
+impl MtkCommand for PatchArgs {
+    async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        dev.enter_da_mode().await?;
+
+        state.connection_type = CONN_DA;
+        state.flash_mode = 1;
+
+        let bytes = parse_hex_bytes(&self.bytes)?;
+
+        if dev.dev_info.get_partition(&self.partition).await.is_none() {
+            return Err(anyhow!("Partition '{}' not found on device.", self.partition));
+        }
+
+        if self.dry_run {
+            let len = bytes.len() as u64;
+            let current = dev.read_partition_range(&self.partition, self.offset, len).await?;
+
+            info!("Partition '{}' at offset 0x{:X}:", self.partition, self.offset);
+            info!("  before: {}", hex::encode(&current));
+            info!("  after:  {}", hex::encode(&bytes));
+            info!("Dry run, nothing was written.");
+            return Ok(());
+        }
+
+        let expected = is_protected_part(&self.partition).then_some(self.partition.as_str());
+        confirm(
+            &format!(
+                "Patch {} bytes of partition '{}' at offset 0x{:X}?",
+                bytes.len(),
+                self.partition,
+                self.offset
+            ),
+            expected,
+            self.confirm.yes,
+        )?;
+
+        info!("Patching partition '{}' at offset 0x{:X}...", self.partition, self.offset);
+
+        let original =
+            dev.patch_partition(&self.partition, self.offset, &bytes, self.verify).await?;
+
+        info!("  before: {}", hex::encode(&original));
+        info!("  after:  {}", hex::encode(&bytes));
+        info!("Partition '{}' patched.", self.partition);
+
+        Ok(())
+    }
+
+    fn da(&self) -> Option<&PathBuf> {
+        Some(&self.da.da_file)
+    }
+
+    fn pl(&self) -> Option<&PathBuf> {
+        self.da.preloader_file.as_ref()
+    }
+}
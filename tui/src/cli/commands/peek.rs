@@ -9,7 +9,7 @@ use async_trait::async_trait;
 use clap::Args;
 use clap_num::maybe_hex;
 use log::info;
-use penumbra::Device;
+use penumbra::api::{Device, Progress};
 use tokio::fs::File;
 use tokio::io::BufWriter;
 
@@ -17,6 +17,7 @@ use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
 use crate::cli::helpers::AntumbraProgress;
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct PeekArgs {
@@ -34,11 +35,11 @@ pub struct PeekArgs {
 
 impl CommandMetadata for PeekArgs {
     fn about() -> &'static str {
-        "Peek memory."
+        t("cmd.peek.about")
     }
 
     fn long_about() -> &'static str {
-        "Read memory from the specified address and length. DA Extensions must be loaded for this command to work."
+        t("cmd.peek.long_about")
     }
 }
 
@@ -57,10 +58,10 @@ impl MtkCommand for PeekArgs {
 
         let mut progress_callback = {
             let pb = &pb;
-            move |read: usize, total: usize| {
-                pb.update(read as u64, "Reading memory...");
+            move |progress: &Progress| {
+                pb.update(progress, "Reading memory...");
 
-                if read >= total {
+                if progress.is_done() {
                     pb.finish("Memory readback completed!");
                 }
             }
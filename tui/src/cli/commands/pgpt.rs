@@ -4,21 +4,59 @@
 */
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use clap::Args;
+use clap::{Args, Subcommand, ValueEnum};
 use human_bytes::human_bytes;
-use log::info;
-use penumbra::Device;
+use log::{info, warn};
+use penumbra::api::{Device, GptDiff, GptEntryDiff, GptReport, GptSource, Partition};
 
 use crate::cli::MtkCommand;
-use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
+use crate::cli::common::{CONN_DA, CommandMetadata, ConfirmArgs, DaArgs};
+use crate::cli::helpers::confirm;
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum GptTable {
+    Primary,
+    Secondary,
+}
+
+impl From<GptTable> for GptSource {
+    fn from(table: GptTable) -> Self {
+        match table {
+            GptTable::Primary => GptSource::Primary,
+            GptTable::Secondary => GptSource::Secondary,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct GptRepairArgs {
+    /// The GPT to keep, copied over the other one
+    #[arg(long, value_enum)]
+    pub from: GptTable,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GptAction {
+    /// Check that both GPTs are valid and match
+    Check,
+    /// Compare the primary and secondary GPT side by side
+    Diff,
+    /// Copy one GPT over the other
+    Repair(GptRepairArgs),
+}
 
 #[derive(Args, Debug)]
 pub struct PgptArgs {
     #[command(flatten)]
     pub da: DaArgs,
+    #[command(subcommand)]
+    pub action: Option<GptAction>,
 }
 
 impl CommandMetadata for PgptArgs {
@@ -27,11 +65,11 @@ impl CommandMetadata for PgptArgs {
     }
 
     fn about() -> &'static str {
-        "Display the partition table of the connected device."
+        t("cmd.pgpt.about")
     }
 
     fn long_about() -> &'static str {
-        Self::about()
+        t("cmd.pgpt.long_about")
     }
 }
 
@@ -43,17 +81,80 @@ impl MtkCommand for PgptArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
+        match &self.action {
+            Some(GptAction::Check) => {
+                let report = dev.check_gpt().await?;
+                print_report(&report)?;
+
+                match report.repair_source() {
+                    Some(GptSource::Primary) => info!("Run `gpt repair --from primary` to fix."),
+                    Some(GptSource::Secondary) => {
+                        info!("Run `gpt repair --from secondary` to fix.")
+                    }
+                    _ if !report.diff.is_empty() => {
+                        info!("Check which table is right, then run `gpt repair --from <table>`.")
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            Some(GptAction::Diff) => return print_diff(&dev.gpt_diff().await?),
+            Some(GptAction::Repair(args)) => {
+                let from = GptSource::from(args.from);
+                let report = dev.check_gpt().await?;
+                if report.is_healthy() {
+                    info!("Primary and secondary GPT already match, nothing to repair.");
+                    return Ok(());
+                }
+
+                let from_ok = match args.from {
+                    GptTable::Primary => report.primary_ok,
+                    GptTable::Secondary => report.secondary_ok,
+                };
+                if !from_ok {
+                    return Err(anyhow!("The {} is invalid, can't repair from it", from.as_str()));
+                }
+
+                print_report(&report)?;
+                let to = match args.from {
+                    GptTable::Primary => GptSource::Secondary,
+                    GptTable::Secondary => GptSource::Primary,
+                };
+                confirm(
+                    &format!("Overwrite the {} with the {}?", to.as_str(), from.as_str()),
+                    None,
+                    args.confirm.yes,
+                )?;
+
+                dev.repair_gpt(from).await?;
+                info!("GPT repaired.");
+                return Ok(());
+            }
+            None => {}
+        }
+
         let partitions = dev.dev_info.partitions().await;
+        let out_of_range = dev.out_of_range_partitions().await;
 
         info!("Partition Table:");
         for p in partitions {
-            info!(
+            let line = format!(
                 "Name: {:<15} \t Addr: 0x{:08X} \t Size: 0x{:08X} ({})",
                 p.name,
                 p.address,
                 p.size,
                 human_bytes(p.size as f64)
             );
+            if out_of_range.contains(&p) {
+                warn!("{} [OUT OF RANGE]", line);
+            } else {
+                info!("{}", line);
+            }
+        }
+
+        if !out_of_range.is_empty() {
+            warn!("Partitions marked OUT OF RANGE extend past the end of the storage.");
+            warn!("They are refused unless --force is passed.");
         }
 
         Ok(())
@@ -67,3 +168,47 @@ impl MtkCommand for PgptArgs {
         self.da.preloader_file.as_ref()
     }
 }
+
+fn describe(part: Option<&Partition>) -> String {
+    match part {
+        Some(p) => format!("0x{:08X} +0x{:08X}", p.address, p.size),
+        None => "-".to_string(),
+    }
+}
+
+fn print_report(report: &GptReport) -> Result<()> {
+    let status = |ok: bool| if ok { "OK" } else { "INVALID" };
+    info!("Primary GPT:   {}", status(report.primary_ok));
+    info!("Secondary GPT: {}", status(report.secondary_ok));
+
+    if report.primary_ok && report.secondary_ok {
+        print_diff(&report.diff)?;
+    }
+
+    Ok(())
+}
+
+fn print_diff(diff: &GptDiff) -> Result<()> {
+    if diff.is_empty() {
+        info!("Primary and secondary GPT match.");
+        return Ok(());
+    }
+
+    info!("{:<20} {:<24} {:<24}", "Name", "Primary", "Secondary");
+    for entry in &diff.entries {
+        let (name, primary, secondary) = match entry {
+            GptEntryDiff::OnlyPrimary(p) => (&p.name, Some(p), None),
+            GptEntryDiff::OnlySecondary(s) => (&s.name, None, Some(s)),
+            GptEntryDiff::Changed { primary, secondary } => {
+                (&primary.name, Some(primary), Some(secondary))
+            }
+        };
+        info!("{:<20} {:<24} {:<24}", name, describe(primary), describe(secondary));
+    }
+
+    if diff.entries.is_empty() {
+        warn!("Partition addresses match, but types, GUIDs or attributes differ.");
+    }
+
+    Ok(())
+}
@@ -3,19 +3,21 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use clap::Args;
 use log::info;
-use penumbra::Device;
+use penumbra::api::{Device, Progress};
 use tokio::fs::{File, create_dir_all, read_dir};
 use tokio::io::{AsyncWriteExt, BufWriter};
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::helpers::{AntumbraProgress, FileSink};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct ReadAllArgs {
@@ -26,6 +28,9 @@ pub struct ReadAllArgs {
     /// The destination file
     #[arg(long, short = 's', value_delimiter = ',')]
     pub skip: Vec<String>,
+    /// Save each partition before reading the next one, for DAs misbehaving otherwise
+    #[arg(long)]
+    pub serial: bool,
 }
 
 impl CommandMetadata for ReadAllArgs {
@@ -34,12 +39,11 @@ impl CommandMetadata for ReadAllArgs {
     }
 
     fn about() -> &'static str {
-        "Read all partitions from the device and save them to the specified output directory."
+        t("cmd.readall.about")
     }
 
     fn long_about() -> &'static str {
-        "Read all partitions from the device and save them to the specified output directory,
-        skipping any partitions listed in the skip option."
+        t("cmd.readall.long_about")
     }
 }
 
@@ -66,7 +70,7 @@ impl MtkCommand for ReadAllArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
-        let partitions = dev.get_partitions().await;
+        let partitions = dev.get_partitions().await?;
         if partitions.is_empty() {
             info!("No partitions found on device.");
             return Ok(());
@@ -74,6 +78,10 @@ impl MtkCommand for ReadAllArgs {
 
         let proto = dev.get_protocol().ok_or(anyhow!("Failed to get device protocol"))?;
 
+        let started = Instant::now();
+        let mut sink = FileSink::new(self.serial);
+        let mut count = 0;
+
         for p in partitions {
             if self.skip.contains(&p.name) {
                 info!("Skipping partition '{}'", p.name);
@@ -81,22 +89,37 @@ impl MtkCommand for ReadAllArgs {
             }
 
             let output_path = self.output_dir.join(format!("{}.bin", p.name));
-            let mut output_file = BufWriter::new(File::create(&output_path).await?);
 
-            let part_size = p.size as u64;
+            let part_size = p.size;
             let pb = AntumbraProgress::new(part_size);
 
             let mut progress_callback = {
                 let pb = &pb;
-                move |read: usize, total: usize| {
-                    pb.update(read as u64, "Reading...");
+                move |progress: &Progress| {
+                    pb.update(progress, "Reading...");
 
-                    if read >= total {
+                    if progress.is_done() {
                         pb.finish("Read complete!");
                     }
                 }
             };
 
+            // Small partitions are saved in the background while reading the next one
+            if sink.buffers(part_size) {
+                let mut data = Vec::with_capacity(part_size as usize);
+                match proto
+                    .read_flash(p.address, p.size, p.kind, &mut progress_callback, &mut data)
+                    .await
+                {
+                    Ok(_) => sink.save(output_path, data).await?,
+                    Err(_) => pb.abandon("Read failed! Skipping partition."),
+                }
+                count += 1;
+                continue;
+            }
+
+            let mut output_file = BufWriter::new(File::create(&output_path).await?);
+
             match proto
                 .read_flash(p.address, p.size, p.kind, &mut progress_callback, &mut output_file)
                 .await
@@ -109,8 +132,11 @@ impl MtkCommand for ReadAllArgs {
 
             output_file.flush().await?;
             info!("Saved partition '{}' to '{}'", p.name, output_path.display());
+            count += 1;
         }
 
+        sink.finish().await?;
+        info!("Read {} partitions in {:.1}s", count, started.elapsed().as_secs_f64());
         info!("All partitions read successfully.");
 
         Ok(())
@@ -2,29 +2,38 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use clap::Args;
-use log::info;
-use penumbra::Device;
-use tokio::fs::File;
+use log::{info, warn};
+use penumbra::api::{Device, Partition, Progress};
+use tokio::fs::{File, create_dir_all};
 use tokio::io::{AsyncWriteExt, BufWriter};
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::helpers::{AntumbraProgress, Presets, resolve_preset};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct ReadArgs {
     #[command(flatten)]
     pub da: DaArgs,
     /// The partition to read
-    pub partition: String,
+    #[arg(required_unless_present = "preset")]
+    pub partition: Option<String>,
     /// The destination file
-    pub output_file: PathBuf,
+    #[arg(required_unless_present = "preset")]
+    pub output_file: Option<PathBuf>,
+    /// Read the partitions of a preset saved from the TUI instead
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["partition", "output_file"])]
+    pub preset: Option<String>,
+    /// Where to save the partitions read with --preset, defaults to the preset name
+    #[arg(long, short = 'o', value_name = "DIR", requires = "preset")]
+    pub output_dir: Option<PathBuf>,
 }
 
 impl CommandMetadata for ReadArgs {
@@ -33,11 +42,11 @@ impl CommandMetadata for ReadArgs {
     }
 
     fn about() -> &'static str {
-        "Read a partition from the device and save it to a file."
+        t("cmd.readflash.about")
     }
 
     fn long_about() -> &'static str {
-        "Read a specified partition from the device and save it to a file with the given output filename."
+        t("cmd.readflash.long_about")
     }
 }
 
@@ -49,42 +58,24 @@ impl MtkCommand for ReadArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
-        let partition = match dev.dev_info.get_partition(&self.partition).await {
-            Some(p) => p,
-            None => {
-                info!("Partition '{}' not found on device.", self.partition);
-                return Err(anyhow::anyhow!("Partition '{}' not found on device.", self.partition));
-            }
-        };
+        if let Some(preset) = &self.preset {
+            return self.read_preset(dev, preset).await;
+        }
 
-        let total_size = partition.size as u64;
-        let pb = AntumbraProgress::new(total_size);
-
-        let mut progress_callback = {
-            let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Reading flash");
-
-                if written >= total {
-                    pb.finish("Read complete!");
-                }
-            }
+        // Both are required by clap without a preset
+        let (Some(name), Some(output_file)) = (&self.partition, &self.output_file) else {
+            return Err(anyhow!("A partition and an output file are required"));
         };
 
-        let file = File::create(&self.output_file).await?;
-        let mut writer = BufWriter::new(file);
-
-        match dev.read_partition(&self.partition, &mut progress_callback, &mut writer).await {
-            Ok(_) => {}
-            Err(e) => {
-                pb.abandon("Read failed!");
-                return Err(e)?;
+        let partition = match dev.dev_info.get_partition(name).await {
+            Some(p) => p,
+            None => {
+                info!("Partition '{}' not found on device.", name);
+                return Err(anyhow!("Partition '{}' not found on device.", name));
             }
         };
 
-        writer.flush().await?;
-
-        Ok(())
+        read_to_file(dev, &partition, output_file).await
     }
 
     fn da(&self) -> Option<&PathBuf> {
@@ -94,4 +85,69 @@ impl MtkCommand for ReadArgs {
     fn pl(&self) -> Option<&PathBuf> {
         self.da.preloader_file.as_ref()
     }
+
+    fn partitions(&self) -> Vec<String> {
+        match &self.preset {
+            Some(name) => Presets::load().get(name).map(<[String]>::to_vec).unwrap_or_default(),
+            None => self.partition.iter().cloned().collect(),
+        }
+    }
+}
+
+impl ReadArgs {
+    async fn read_preset(&self, dev: &mut Device, preset: &str) -> Result<()> {
+        let presets = Presets::load();
+        let names = presets.get(preset).ok_or_else(|| {
+            let available: Vec<&str> = presets.names().map(String::as_str).collect();
+            anyhow!("Preset '{}' not found (available: {})", preset, available.join(", "))
+        })?;
+
+        let (partitions, missing) = resolve_preset(names, &dev.get_partitions().await?);
+        for name in &missing {
+            warn!("Partition '{}' from preset '{}' not found on device, skipping", name, preset);
+        }
+
+        let output_dir = self.output_dir.clone().unwrap_or_else(|| PathBuf::from(preset));
+        create_dir_all(&output_dir).await?;
+
+        for partition in &partitions {
+            let output_file = output_dir.join(format!("{}.bin", partition.name));
+            read_to_file(dev, partition, &output_file).await?;
+            info!("Saved partition '{}' to '{}'", partition.name, output_file.display());
+        }
+
+        info!("Read {} partitions from preset '{}'.", partitions.len(), preset);
+        Ok(())
+    }
+}
+
+async fn read_to_file(dev: &mut Device, partition: &Partition, output_file: &Path) -> Result<()> {
+    let total_size = partition.size;
+    let pb = AntumbraProgress::new(total_size);
+
+    let mut progress_callback = {
+        let pb = &pb;
+        move |progress: &Progress| {
+            pb.update(progress, "Reading flash");
+
+            if progress.is_done() {
+                pb.finish("Read complete!");
+            }
+        }
+    };
+
+    let file = File::create(output_file).await?;
+    let mut writer = BufWriter::new(file);
+
+    match dev.read_resolved_partition(partition, &mut writer, &mut progress_callback).await {
+        Ok(_) => {}
+        Err(e) => {
+            pb.abandon("Read failed!");
+            return Err(e)?;
+        }
+    };
+
+    writer.flush().await?;
+
+    Ok(())
 }
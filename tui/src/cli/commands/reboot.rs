@@ -7,12 +7,12 @@ use std::path::PathBuf;
 use anyhow::Result;
 use async_trait::async_trait;
 use clap::{Args, ValueEnum};
-use penumbra::Device;
-use penumbra::da::protocol::BootMode;
+use penumbra::api::{BootMode, Device};
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Debug, ValueEnum, Clone)]
 pub enum RebootAction {
@@ -20,18 +20,20 @@ pub enum RebootAction {
     HomeScreen,
     Fastboot,
     Meta,
+    /// META mode over the UART (COM) port instead of USB
+    MetaUart,
     Test,
+    /// Android recovery, through the bootloader message in the 'misc' partition
+    Recovery,
 }
 
 impl CommandMetadata for RebootArgs {
     fn about() -> &'static str {
-        "Reboot the device into a specified mode."
+        t("cmd.reboot.about")
     }
 
     fn long_about() -> &'static str {
-        "Reboot the device into a specified mode. On XFlash and Legacy, only Normal, HomeScreen
-        and Fastboot modes are supported, the rest will default to Normal.
-        On XML, also the Meta and Test modes are available."
+        t("cmd.reboot.long_about")
     }
 }
 
@@ -43,6 +45,8 @@ impl From<RebootAction> for BootMode {
             RebootAction::Fastboot => BootMode::Fastboot,
             RebootAction::Test => BootMode::Test,
             RebootAction::Meta => BootMode::Meta,
+            RebootAction::MetaUart => BootMode::MetaUart,
+            RebootAction::Recovery => BootMode::Recovery,
         }
     }
 }
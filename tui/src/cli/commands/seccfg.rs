@@ -4,16 +4,16 @@
 */
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use clap::{Args, ValueEnum};
 use log::info;
-use penumbra::Device;
-use penumbra::core::seccfg::LockFlag;
+use penumbra::api::{Device, ExtState, LockFlag};
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Debug, ValueEnum, Clone)]
 pub enum SeccfgAction {
@@ -30,13 +30,11 @@ pub struct SeccfgArgs {
 
 impl CommandMetadata for SeccfgArgs {
     fn about() -> &'static str {
-        "Lock or unlock the seccfg partition on the device."
+        t("cmd.seccfg.about")
     }
 
     fn long_about() -> &'static str {
-        "Lock or unlock the seccfg partition on the device.
-        This command only work when the device is in DA mode and vulnerable to an exploit or unfused,
-        because it requires DA extensions to be loaded."
+        t("cmd.seccfg.long_about")
     }
 }
 
@@ -48,6 +46,18 @@ impl MtkCommand for SeccfgArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
+        // Seccfg needs the DA extensions for SEJ, fail early instead of halfway through
+        match dev.extensions_state() {
+            ExtState::Active | ExtState::Unknown => {}
+            ext_state => {
+                return Err(anyhow!(
+                    "Changing the lock state is not possible with this DA, \
+                     DA extensions are unavailable: {}",
+                    ext_state
+                ));
+            }
+        }
+
         match self.action {
             SeccfgAction::Unlock => {
                 info!("Unlocking seccfg...");
@@ -8,11 +8,12 @@ use anyhow::Result;
 use async_trait::async_trait;
 use clap::Args;
 use log::info;
-use penumbra::Device;
+use penumbra::api::Device;
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct ShutdownArgs {
@@ -22,11 +23,11 @@ pub struct ShutdownArgs {
 
 impl CommandMetadata for ShutdownArgs {
     fn about() -> &'static str {
-        "Shutdown the device."
+        t("cmd.shutdown.about")
     }
 
     fn long_about() -> &'static str {
-        "Shutdown the device through DA mode."
+        t("cmd.shutdown.long_about")
     }
 }
 
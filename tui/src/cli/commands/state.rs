@@ -0,0 +1,58 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+use log::info;
+use penumbra::api::Device;
+
+use crate::cli::MtkCommand;
+use crate::cli::common::CommandMetadata;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Debug, Subcommand)]
+pub enum StateSubcommand {
+    /// Forget the device state saved by previous commands, including the DA file
+    Clear,
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    #[command(subcommand)]
+    pub command: StateSubcommand,
+}
+
+impl CommandMetadata for StateArgs {
+    fn about() -> &'static str {
+        t("cmd.state.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.state.long_about")
+    }
+}
+
+impl StateArgs {
+    /// Runs the command on the saved state alone, without connecting to the device
+    pub async fn execute(&self, state: &mut PersistedDeviceState) -> Result<()> {
+        match self.command {
+            StateSubcommand::Clear => {
+                state.reset().await?;
+                info!("Saved device state cleared.");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MtkCommand for StateArgs {
+    // Handled by `run_cli` before connecting, the state is all this command works on
+    async fn run(&self, _dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        self.execute(state).await
+    }
+}
@@ -0,0 +1,69 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Args;
+use log::{info, warn};
+use penumbra::api::Device;
+
+use crate::cli::MtkCommand;
+use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
+use crate::cli::helpers::SupportBundle;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Args, Debug)]
+pub struct SupportBundleArgs {
+    #[command(flatten)]
+    pub da: DaArgs,
+    /// The zip archive to write the bundle to
+    pub output: PathBuf,
+    /// Include device identifiers (SoC ID, MEID) instead of hashing them
+    #[arg(long)]
+    pub include_ids: bool,
+}
+
+impl CommandMetadata for SupportBundleArgs {
+    fn about() -> &'static str {
+        t("cmd.support_bundle.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.support_bundle.long_about")
+    }
+}
+
+#[async_trait]
+impl MtkCommand for SupportBundleArgs {
+    async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        dev.enter_da_mode().await?;
+
+        state.connection_type = CONN_DA;
+        state.flash_mode = 1;
+
+        info!("Collecting device information...");
+        // The bundle is still useful without the partitions, e.g. to report why they fail
+        if let Err(e) = dev.get_partitions().await {
+            warn!("Failed to read the partition table: {}", e);
+        }
+
+        let bundle = SupportBundle::collect(dev, self.include_ids).await?;
+        bundle.write_to(&self.output).await?;
+
+        info!("Support bundle written to {:?}", self.output);
+
+        Ok(())
+    }
+
+    fn da(&self) -> Option<&PathBuf> {
+        Some(&self.da.da_file)
+    }
+
+    fn pl(&self) -> Option<&PathBuf> {
+        self.da.preloader_file.as_ref()
+    }
+}
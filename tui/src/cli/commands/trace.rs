@@ -0,0 +1,48 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Args;
+use penumbra::api::Device;
+use penumbra::api::trace::dump;
+
+use crate::cli::MtkCommand;
+use crate::cli::common::CommandMetadata;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+#[derive(Args, Debug)]
+pub struct TraceArgs {
+    /// Trace file written with --trace
+    pub file: PathBuf,
+}
+
+impl CommandMetadata for TraceArgs {
+    fn about() -> &'static str {
+        t("cmd.trace.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.trace.long_about")
+    }
+}
+
+impl TraceArgs {
+    /// Prints the trace, one transfer per line
+    pub fn print(&self) -> Result<()> {
+        print!("{}", dump(&self.file)?);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MtkCommand for TraceArgs {
+    // Handled by `run_cli` before connecting, reading a trace needs no device
+    async fn run(&self, _dev: &mut Device, _state: &mut PersistedDeviceState) -> Result<()> {
+        self.print()
+    }
+}
@@ -8,7 +8,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use clap::Args;
 use log::info;
-use penumbra::Device;
+use penumbra::api::{Device, Progress};
 use tokio::fs::File;
 use tokio::io::BufWriter;
 
@@ -16,6 +16,7 @@ use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
 use crate::cli::helpers::AntumbraProgress;
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct UploadArgs {
@@ -33,12 +34,11 @@ impl CommandMetadata for UploadArgs {
     }
 
     fn about() -> &'static str {
-        "Upload a partition from the device to the host."
+        t("cmd.upload.about")
     }
 
     fn long_about() -> &'static str {
-        "Upload (readback) a specificed partition on the device to a file on the host.
-        Use this command for reading back if the `read` command fails."
+        t("cmd.upload.long_about")
     }
 }
 
@@ -58,15 +58,15 @@ impl MtkCommand for UploadArgs {
             }
         };
 
-        let total_size = partition.size as u64;
+        let total_size = partition.size;
         let pb = AntumbraProgress::new(total_size);
 
         let mut progress_callback = {
             let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Uploading...");
+            move |progress: &Progress| {
+                pb.update(progress, "Uploading...");
 
-                if written >= total {
+                if progress.is_done() {
                     pb.finish("Upload complete!");
                 }
             }
@@ -93,4 +93,8 @@ impl MtkCommand for UploadArgs {
     fn pl(&self) -> Option<&PathBuf> {
         self.da.preloader_file.as_ref()
     }
+
+    fn partitions(&self) -> Vec<String> {
+        vec![self.partition.clone()]
+    }
 }
@@ -0,0 +1,159 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use clap::{Args, ValueEnum};
+use log::info;
+use penumbra::api::{ConnectionType, Device, PortInfo, probe_mtk_port};
+use serde_json::json;
+use tokio::time::sleep;
+
+use crate::cli::MtkCommand;
+use crate::cli::common::CommandMetadata;
+use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
+
+/// Interval between two looks at the port list
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum WaitState {
+    Brom,
+    Preloader,
+    Da,
+    /// The device was unplugged
+    Gone,
+}
+
+impl WaitState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WaitState::Brom => "brom",
+            WaitState::Preloader => "preloader",
+            WaitState::Da => "da",
+            WaitState::Gone => "gone",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct WaitArgs {
+    /// The state to wait for
+    #[arg(long = "for", value_enum)]
+    pub state: WaitState,
+    /// Seconds to wait before failing, 0 waits forever
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    pub timeout: u64,
+    /// Print the matched port as JSON on stdout
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl CommandMetadata for WaitArgs {
+    fn about() -> &'static str {
+        t("cmd.wait.about")
+    }
+
+    fn long_about() -> &'static str {
+        t("cmd.wait.long_about")
+    }
+}
+
+impl WaitArgs {
+    /// Polls the port list until the requested state is reached or the timeout expires.
+    /// The port is never opened, so the device is left free for the next command.
+    pub async fn wait(&self, state: &PersistedDeviceState) -> Result<()> {
+        let remembered = state.port_location.as_deref();
+        let started = Instant::now();
+        let timeout = Duration::from_secs(self.timeout);
+
+        info!("Waiting for the device to reach the '{}' state...", self.state.as_str());
+        loop {
+            let port = probe_mtk_port().await;
+            if self.matches(port.as_ref(), remembered) {
+                self.report(port.as_ref(), remembered, started.elapsed());
+                return Ok(());
+            }
+
+            if self.timeout != 0 && started.elapsed() >= timeout {
+                bail!(
+                    "Timed out after {}s waiting for the '{}' state",
+                    self.timeout,
+                    self.state.as_str()
+                );
+            }
+
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Whether the port found, if any, is in the requested state.
+    ///
+    /// For `gone`, a port at another location than the remembered one is a different
+    /// device, so ours is considered unplugged. Without a remembered location, any MTK
+    /// port keeps the wait going.
+    fn matches(&self, port: Option<&PortInfo>, remembered: Option<&str>) -> bool {
+        let Some(port) = port else {
+            return self.state == WaitState::Gone;
+        };
+
+        match self.state {
+            WaitState::Brom => port.connection_type == ConnectionType::Brom,
+            WaitState::Preloader => port.connection_type == ConnectionType::Preloader,
+            WaitState::Da => port.connection_type == ConnectionType::Da,
+            WaitState::Gone => match (remembered, port.location.as_deref()) {
+                (Some(remembered), Some(location)) => remembered != location,
+                _ => false,
+            },
+        }
+    }
+
+    fn report(&self, port: Option<&PortInfo>, remembered: Option<&str>, elapsed: Duration) {
+        // For `gone`, the port found (if any) belongs to another device
+        let port = port.filter(|_| self.state != WaitState::Gone);
+
+        if self.json {
+            let port = port.map(|p| {
+                json!({
+                    "connection_type": p.connection_type.as_str(),
+                    "name": p.name,
+                    "location": p.location,
+                    "usb_link": p.usb_link.map(|l| l.to_string()),
+                })
+            });
+            let output = json!({
+                "state": self.state.as_str(),
+                "elapsed_ms": elapsed.as_millis() as u64,
+                "port": port,
+                "remembered_location": remembered,
+            });
+            println!("{}", output);
+            return;
+        }
+
+        match port {
+            Some(port) => info!(
+                "Found {} port: {}{}",
+                port.connection_type.as_str(),
+                port.name,
+                port.location.as_ref().map(|l| format!(" ({})", l)).unwrap_or_default()
+            ),
+            None => match remembered {
+                Some(location) => info!("Device at {} is gone", location),
+                None => info!("Device is gone"),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl MtkCommand for WaitArgs {
+    // Handled by `run_cli` before connecting, since waiting must not claim the port
+    async fn run(&self, _dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        self.wait(state).await
+    }
+}
@@ -7,14 +7,13 @@ use std::path::PathBuf;
 use anyhow::Result;
 use async_trait::async_trait;
 use clap::Args;
-use penumbra::Device;
-use tokio::fs::{File, metadata};
-use tokio::io::BufReader;
+use penumbra::api::{Device, Progress};
 
 use crate::cli::MtkCommand;
-use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs, image_size};
+use crate::cli::helpers::{AntumbraProgress, open_image};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct WriteArgs {
@@ -32,12 +31,11 @@ impl CommandMetadata for WriteArgs {
     }
 
     fn about() -> &'static str {
-        "Write a file to a specified partition on the device."
+        t("cmd.writeflash.about")
     }
 
     fn long_about() -> &'static str {
-        "Write (flash) a file to a specificed partition on the device.
-        If this command fails, use `download` instead."
+        t("cmd.writeflash.long_about")
     }
 }
 
@@ -49,33 +47,33 @@ impl MtkCommand for WriteArgs {
         state.connection_type = CONN_DA;
         state.flash_mode = 1;
 
-        let file = File::open(&self.file).await?;
-        let mut reader = BufReader::new(file);
+        let mut reader = open_image(&self.file, dev.write_buffer_size()).await?;
 
-        let file_size = metadata(&self.file).await?.len();
+        let file_size = image_size(&self.file).await?;
 
-        let part_size = match dev.dev_info.get_partition(&self.partition).await {
-            Some(p) => p.size as u64,
+        let partition = match dev.dev_info.get_partition(&self.partition).await {
+            Some(p) => p,
             None => {
                 return Err(anyhow::anyhow!("Partition '{}' not found on device.", self.partition));
             }
         };
 
-        let total_size = file_size.min(part_size);
-        let pb = AntumbraProgress::new(total_size);
+        let total_size = file_size.min(partition.size);
+        let pb = AntumbraProgress::new(total_size).with_phases(dev.phase_tracker());
 
         let mut progress_callback = {
             let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Writing flash");
+            move |progress: &Progress| {
+                pb.update(progress, "Writing flash");
 
-                if written >= total {
+                if progress.is_done() {
                     pb.finish("Write complete!");
                 }
             }
         };
 
-        match dev.write_partition(&self.partition, &mut reader, &mut progress_callback).await {
+        let progress = &mut progress_callback;
+        match dev.write_resolved_partition(&partition, &mut reader, total_size, progress).await {
             Ok(_) => {}
             Err(e) => {
                 pb.abandon("Write failed!");
@@ -93,4 +91,8 @@ impl MtkCommand for WriteArgs {
     fn pl(&self) -> Option<&PathBuf> {
         self.da.preloader_file.as_ref()
     }
+
+    fn partitions(&self) -> Vec<String> {
+        vec![self.partition.clone()]
+    }
 }
@@ -9,16 +9,16 @@ use anyhow::Result;
 use async_trait::async_trait;
 use clap::{Args, Subcommand};
 use log::info;
-use penumbra::Device;
-use penumbra::da::XFlash;
-use penumbra::da::xflash::flash::set_rsc_info;
+use penumbra::api::protocol::{XFlash, read_rpmb, set_rsc_info};
+use penumbra::api::{Device, EmmcPartition, PartitionKind, Progress, ProgressTracker};
 use tokio::fs::{File, metadata};
-use tokio::io::BufReader;
+use tokio::io::BufWriter;
 
 use crate::cli::MtkCommand;
 use crate::cli::common::{CONN_DA, CommandMetadata, DaArgs};
-use crate::cli::helpers::AntumbraProgress;
+use crate::cli::helpers::{AntumbraProgress, open_image};
 use crate::cli::state::PersistedDeviceState;
+use crate::i18n::t;
 
 #[derive(Args, Debug)]
 pub struct RscFlashArgs {
@@ -39,13 +39,12 @@ impl MtkCommand for RscFlashArgs {
 
         info!("Flashing file {:?} to partition {} with RSC", self.file, self.partition);
 
-        let file = File::open(&self.file).await?;
-        let mut reader = BufReader::new(file);
+        let mut reader = open_image(&self.file, dev.write_buffer_size()).await?;
 
         let file_size = metadata(&self.file).await?.len();
 
         let part_size = match dev.dev_info.get_partition(&self.partition).await {
-            Some(p) => p.size as u64,
+            Some(p) => p.size,
             None => {
                 return Err(anyhow::anyhow!("Partition '{}' not found on device.", self.partition));
             }
@@ -69,23 +68,20 @@ impl MtkCommand for RscFlashArgs {
 
         let mut progress_callback = {
             let pb = &pb;
-            move |written: usize, total: usize| {
-                pb.update(written as u64, "Flashing...");
+            move |progress: &Progress| {
+                pb.update(progress, "Flashing...");
 
-                if written >= total {
+                if progress.is_done() {
                     pb.finish("Flash complete!");
                 }
             }
         };
 
-        set_rsc_info(
-            xflash,
-            &self.partition,
-            file_size as usize,
-            &mut reader,
-            &mut progress_callback,
-        )
-        .await?;
+        // The XFlash helpers report raw byte counts
+        let mut tracker = ProgressTracker::new(&mut progress_callback);
+        let mut progress = |done, total| tracker.update(done, total);
+
+        set_rsc_info(xflash, &self.partition, file_size, &mut reader, &mut progress).await?;
 
         info!("Flashing to partition '{}' completed.", self.partition);
 
@@ -101,9 +97,76 @@ impl MtkCommand for RscFlashArgs {
     }
 }
 
+#[derive(Args, Debug)]
+pub struct RpmbReadArgs {
+    #[command(flatten)]
+    pub da: DaArgs,
+    /// File to save the RPMB data to
+    pub output_file: PathBuf,
+}
+
+#[async_trait]
+impl MtkCommand for RpmbReadArgs {
+    async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
+        dev.enter_da_mode().await?;
+        state.connection_type = CONN_DA;
+        state.flash_mode = 1;
+
+        let rpmb = PartitionKind::Emmc(EmmcPartition::Rpmb);
+        let rpmb_size = dev.regions().await.iter().find(|r| r.kind == rpmb).map_or(0, |r| r.size);
+
+        let proto = dev.get_protocol().unwrap();
+        let xflash = proto
+            .as_any_mut()
+            .downcast_mut::<XFlash>()
+            .ok_or_else(|| anyhow::anyhow!("Current protocol is not XFlash"))?;
+
+        let file = File::create(&self.output_file).await?;
+        let mut writer = BufWriter::new(file);
+
+        let pb = AntumbraProgress::new(rpmb_size);
+
+        let mut progress_callback = {
+            let pb = &pb;
+            move |progress: &Progress| {
+                pb.update(progress, "Reading RPMB...");
+
+                if progress.is_done() {
+                    pb.finish("RPMB read complete!");
+                }
+            }
+        };
+
+        let mut tracker = ProgressTracker::new(&mut progress_callback);
+        let mut progress = |done, total| tracker.update(done, total);
+
+        let read = match read_rpmb(xflash, &mut writer, &mut progress).await {
+            Ok(read) => read,
+            Err(e) => {
+                pb.abandon("RPMB read failed!");
+                return Err(e)?;
+            }
+        };
+
+        info!("Saved {} bytes of RPMB data to {:?}", read, self.output_file);
+
+        Ok(())
+    }
+
+    fn da(&self) -> Option<&PathBuf> {
+        Some(&self.da.da_file)
+    }
+
+    fn pl(&self) -> Option<&PathBuf> {
+        self.da.preloader_file.as_ref()
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum XFlashSubcommand {
     RscFlash(RscFlashArgs),
+    /// Read the RPMB data through the DA extensions (eMMC only)
+    RpmbRead(RpmbReadArgs),
 }
 
 #[derive(Args, Debug)]
@@ -118,11 +181,11 @@ impl CommandMetadata for XFlashArgs {
     }
 
     fn about() -> &'static str {
-        "XFlash-specific commands."
+        t("cmd.xflash.about")
     }
 
     fn long_about() -> &'static str {
-        "Commands specific to XFlash / V5 devices."
+        t("cmd.xflash.long_about")
     }
 }
 
@@ -131,18 +194,21 @@ impl MtkCommand for XFlashArgs {
     async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()> {
         match &self.command {
             XFlashSubcommand::RscFlash(cmd) => cmd.run(dev, state).await,
+            XFlashSubcommand::RpmbRead(cmd) => cmd.run(dev, state).await,
         }
     }
 
     fn da(&self) -> Option<&PathBuf> {
         match &self.command {
             XFlashSubcommand::RscFlash(cmd) => cmd.da(),
+            XFlashSubcommand::RpmbRead(cmd) => cmd.da(),
         }
     }
 
     fn pl(&self) -> Option<&PathBuf> {
         match &self.command {
             XFlashSubcommand::RscFlash(cmd) => cmd.pl(),
+            XFlashSubcommand::RpmbRead(cmd) => cmd.pl(),
         }
     }
 }
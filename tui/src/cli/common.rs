@@ -2,7 +2,7 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[allow(dead_code)]
 pub const CONN_BR: u8 = 0;
@@ -11,6 +11,9 @@ pub const CONN_PL: u8 = 1;
 pub const CONN_DA: u8 = 2;
 
 use clap::Args;
+use penumbra::api::SparseHeader;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
 
 #[derive(Args, Debug)]
 pub struct DaArgs {
@@ -24,6 +27,55 @@ pub struct DaArgs {
     pub preloader_file: Option<PathBuf>,
 }
 
+#[derive(Args, Debug)]
+pub struct ConfirmArgs {
+    /// Skip confirmation prompts, required when stdin is not a terminal
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+}
+
+/// Parses a transfer rate like `512K`, `10M` or `1G` (bytes per second, 1024 based).
+pub fn parse_rate(value: &str) -> Result<u64, String> {
+    parse_suffixed(value, "Rate")
+}
+
+/// Parses a size like `64K` or `1M` (1024 based).
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let size = parse_suffixed(value, "Size")?;
+    usize::try_from(size).map_err(|_| format!("Size '{}' is too large", value.trim()))
+}
+
+fn parse_suffixed(value: &str, what: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1024),
+        Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        Some('G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+
+    let number: u64 = digits
+        .parse()
+        .map_err(|_| format!("Invalid {} '{}'", what.to_lowercase(), value))?;
+    if number == 0 {
+        return Err(format!("{} must be greater than 0", what));
+    }
+
+    number.checked_mul(multiplier).ok_or_else(|| format!("{} '{}' is too large", what, value))
+}
+
+/// Returns the size an image will take once flashed: the expanded size for
+/// sparse images, the file size otherwise.
+pub async fn image_size(path: &Path) -> anyhow::Result<u64> {
+    let mut file = File::open(path).await?;
+    let file_size = file.metadata().await?.len();
+
+    let mut header = Vec::with_capacity(32);
+    (&mut file).take(32).read_to_end(&mut header).await?;
+
+    Ok(SparseHeader::parse(&header).map_or(file_size, |s| s.expanded_size()))
+}
+
 /// A trait for providing metadata for CLI commands.
 /// This trait can be implemented by command structs to give additional info
 pub trait CommandMetadata {
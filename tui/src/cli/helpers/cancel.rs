@@ -0,0 +1,34 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+#[cfg(feature = "tui")]
+use log::warn;
+use penumbra::api::CancelToken;
+use tokio::task::JoinHandle;
+
+/// Cancels the running operation on the first Ctrl-C, so that the DA is told to stop
+/// at the next chunk instead of being left mid-transfer. A second Ctrl-C quits right away.
+///
+/// Signals need tokio's `signal` feature, which comes with the `tui` one. Without it,
+/// Ctrl-C keeps killing the process.
+#[cfg(feature = "tui")]
+pub fn cancel_on_ctrl_c(cancel: Option<CancelToken>) -> Option<JoinHandle<()>> {
+    let cancel = cancel?;
+    Some(tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        warn!("Cancelling at the next chunk, press Ctrl-C again to quit now");
+        cancel.cancel();
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    }))
+}
+
+#[cfg(not(feature = "tui"))]
+pub fn cancel_on_ctrl_c(_cancel: Option<CancelToken>) -> Option<JoinHandle<()>> {
+    None
+}
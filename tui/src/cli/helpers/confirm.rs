@@ -0,0 +1,98 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Result, anyhow};
+use colored::Colorize;
+
+use crate::logger::{LOGGER_PREIX, WARN_SYMBOL};
+
+/// Asks the user to confirm a destructive operation.
+///
+/// * If `assume_yes` is set, the prompt is skipped.
+/// * If stdin is a TTY, the user is asked to answer y/N, or to type `expected` back if provided.
+/// * Otherwise (CI, pipes) the operation is refused, since nobody can answer the prompt.
+pub fn confirm(prompt: &str, expected: Option<&str>, assume_yes: bool) -> Result<()> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    ask(prompt, expected, assume_yes, interactive, stdin.lock())
+}
+
+/// [`confirm`], reading the answer from `input`
+fn ask(
+    prompt: &str,
+    expected: Option<&str>,
+    assume_yes: bool,
+    interactive: bool,
+    mut input: impl BufRead,
+) -> Result<()> {
+    if assume_yes {
+        return Ok(());
+    }
+
+    if !interactive {
+        return Err(anyhow!(
+            "Refusing to continue without confirmation: stdin is not a terminal. \
+             Pass --yes to confirm non-interactively."
+        ));
+    }
+
+    let prefix = format!("{} {}", LOGGER_PREIX.bold().purple(), WARN_SYMBOL.yellow());
+    match expected {
+        Some(expected) => eprint!("{} {} Type '{}' to continue: ", prefix, prompt, expected),
+        None => eprint!("{} {} [y/N]: ", prefix, prompt),
+    }
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+
+    let confirmed = match expected {
+        Some(expected) => answer == expected,
+        None => answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"),
+    };
+
+    if confirmed { Ok(()) } else { Err(anyhow!("Operation cancelled by user.")) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_tty_is_refused() {
+        for expected in [None, Some("erase")] {
+            // Whatever is piped in, nobody could have read the prompt
+            let err = ask("Erase?", expected, false, false, &b"y\nerase\n"[..]).unwrap_err();
+            assert!(err.to_string().contains("--yes"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn assume_yes_skips_the_prompt() {
+        assert!(ask("Erase?", None, true, false, &b""[..]).is_ok());
+        assert!(ask("Erase?", Some("erase"), true, true, &b""[..]).is_ok());
+    }
+
+    #[test]
+    fn tty_answers() {
+        let cases: &[(Option<&str>, &[u8], bool)] = &[
+            (None, b"y\n", true),
+            (None, b"YES\n", true),
+            (None, b" y \n", true),
+            (None, b"n\n", false),
+            (None, b"\n", false),
+            (None, b"", false),
+            (Some("erase"), b"erase\n", true),
+            (Some("erase"), b"y\n", false),
+            (Some("erase"), b"ERASE\n", false),
+        ];
+        for (expected, input, confirmed) in cases {
+            let result = ask("Erase?", *expected, false, true, *input);
+            assert_eq!(result.is_ok(), *confirmed, "{:?} {:?}", expected, input);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Hooks that don't exit in time are killed
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 30;
+
+/// Shell commands run once an operation completes, shared by the CLI and TUI.
+/// Stored in the `[hooks]` table of `config.toml`.
+///
+/// Commands are templates, where `{device}`, `{operation}`, `{partitions}`, `{duration}`,
+/// `{status}`, `{error}` and `{report}` are replaced by their shell-quoted value.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HookConfig {
+    /// Run after an operation succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_operation_success: Option<String>,
+    /// Run after an operation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_operation_failure: Option<String>,
+    /// Seconds a hook is allowed to run for
+    #[serde(default = "default_hook_timeout")]
+    pub timeout: u64,
+}
+
+fn default_hook_timeout() -> u64 {
+    DEFAULT_HOOK_TIMEOUT
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        Self {
+            on_operation_success: None,
+            on_operation_failure: None,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+}
+
+impl HookConfig {
+    /// Loads the hooks from the config file, for the CLI. The TUI gets them from its config.
+    /// A missing or invalid file results in no hooks.
+    pub fn load() -> Self {
+        #[derive(Deserialize)]
+        struct ConfigFile {
+            #[serde(default)]
+            hooks: HookConfig,
+        }
+
+        let Some(path) =
+            dirs::config_dir().map(|p| p.join("antumbra/config.toml")).filter(|p| p.exists())
+        else {
+            return Self::default();
+        };
+
+        let config = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(toml::from_str::<ConfigFile>(&data)?));
+
+        match config {
+            Ok(config) => config.hooks,
+            Err(e) => {
+                warn!("Failed to load hooks from {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Runs the hook matching the outcome of the operation, if any is configured.
+    /// Hook output and failures are only logged, they never change the operation result.
+    pub fn run(&self, ctx: &HookContext) {
+        let template = match ctx.error {
+            None => self.on_operation_success.as_deref(),
+            Some(_) => self.on_operation_failure.as_deref(),
+        };
+        let Some(template) = template.filter(|t| !t.trim().is_empty()) else {
+            return;
+        };
+
+        let command = ctx.expand(template);
+        info!("Running hook: {}", command);
+
+        match execute(&command, Duration::from_secs(self.timeout)) {
+            Ok(output) => {
+                for line in output.stdout.lines().chain(output.stderr.lines()) {
+                    info!("[hook] {}", line);
+                }
+                if !output.success {
+                    warn!("Hook exited with {}", output.status);
+                }
+            }
+            Err(e) => warn!("Hook failed: {}", e),
+        }
+    }
+}
+
+/// Describes a completed operation, for filling in hook templates.
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    /// The device MEID, as hex
+    pub device: String,
+    pub operation: String,
+    pub partitions: Vec<String>,
+    pub duration: Duration,
+    /// The operation journal
+    pub report: Option<PathBuf>,
+    /// Why the operation failed, `None` on success
+    pub error: Option<String>,
+}
+
+impl HookContext {
+    /// Replaces the placeholders in `template`. Unknown placeholders are kept as is.
+    pub fn expand(&self, template: &str) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest.find('}').and_then(|end| Some((self.value(&rest[1..end])?, end)));
+            match value {
+                Some((value, end)) => {
+                    out.push_str(&quote(&value));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        out.push_str(rest);
+        out
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "device" => self.device.clone(),
+            "operation" => self.operation.clone(),
+            "partitions" => self.partitions.join(","),
+            "duration" => self.duration.as_secs().to_string(),
+            "status" => if self.error.is_none() { "success" } else { "failure" }.to_string(),
+            "error" => self.error.clone().unwrap_or_default(),
+            "report" => self.report.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            _ => return None,
+        })
+    }
+}
+
+/// Quotes a value so the shell passes it as a single argument
+#[cfg(unix)]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quotes a value so the shell passes it as a single argument
+#[cfg(not(unix))]
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+struct HookOutput {
+    status: String,
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn execute(command: &str, timeout: Duration) -> Result<HookOutput> {
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    #[cfg(not(unix))]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+
+    let mut child =
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Drain the pipes while waiting, a chatty hook would block on a full pipe otherwise
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("timed out after {}s", timeout.as_secs()));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    // Background processes started by the hook may keep the pipes open, don't wait on them
+    let grace = Duration::from_secs(1);
+    Ok(HookOutput {
+        status: status.to_string(),
+        success: status.success(),
+        stdout: stdout.recv_timeout(grace).unwrap_or_default(),
+        stderr: stderr.recv_timeout(grace).unwrap_or_default(),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            let _ = tx.send(String::from_utf8_lossy(&buf).into_owned());
+        });
+    }
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext {
+        HookContext {
+            device: String::from("DEADBEEF"),
+            operation: String::from("write"),
+            partitions: vec![String::from("boot_a"), String::from("vbmeta_a")],
+            duration: Duration::from_millis(42_900),
+            report: Some(PathBuf::from("/tmp/journal.log")),
+            error: None,
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn placeholders_are_expanded() {
+        let ctx = context();
+        let cases = [
+            ("notify {device} {operation}", "notify 'DEADBEEF' 'write'"),
+            ("{partitions}:{duration}s", "'boot_a,vbmeta_a':'42's"),
+            ("{status} {error}", "'success' ''"),
+            ("cat {report}", "cat '/tmp/journal.log'"),
+            // Unknown and unterminated placeholders are kept
+            ("echo {unknown} {device", "echo {unknown} {device"),
+            ("awk '{print $1}' {{device}}", "awk '{print $1}' {'DEADBEEF'}"),
+            ("", ""),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(ctx.expand(template), expected, "{}", template);
+        }
+
+        let failed = HookContext { error: Some(String::from("it's gone")), report: None, ..ctx };
+        assert_eq!(failed.expand("{status} {error} {report}"), r"'failure' 'it'\''s gone' ''");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn values_stay_single_arguments() {
+        let ctx = HookContext { error: Some(String::from("x'; echo injected; '")), ..context() };
+        let output = execute(&ctx.expand("printf '%s\\n' {error}"), Duration::from_secs(5));
+        let output = output.unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "x'; echo injected; '\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn output_and_status_are_kept() {
+        let output = execute("echo out; echo err >&2; exit 3", Duration::from_secs(5)).unwrap();
+        assert!(!output.success);
+        assert_eq!(output.status, "exit status: 3");
+        assert_eq!((output.stdout.as_str(), output.stderr.as_str()), ("out\n", "err\n"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn hooks_time_out() {
+        let start = Instant::now();
+        let err = execute("sleep 10", Duration::from_millis(200)).err().unwrap();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn timeout_defaults() {
+        assert_eq!(HookConfig::default().timeout, DEFAULT_HOOK_TIMEOUT);
+        let config: HookConfig = toml::from_str("on_operation_success = \"true\"").unwrap();
+        assert_eq!(config.timeout, DEFAULT_HOOK_TIMEOUT);
+        let config: HookConfig = toml::from_str("timeout = 5").unwrap();
+        assert_eq!((config.timeout, config.on_operation_success), (5, None));
+    }
+}
@@ -0,0 +1,84 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::path::Path;
+
+use anyhow::Result;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, BufReader};
+
+/// Reader of an image to write to the device
+pub type ImageReader = Box<dyn AsyncRead + Unpin + Send>;
+
+/// Regular files at least this big are memory mapped instead of read, with the `mmap`
+/// feature. Below that, mapping costs more than the reads it saves.
+#[cfg(feature = "mmap")]
+pub const MMAP_MIN_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Opens the image at `path` to feed it to a write operation.
+///
+/// Large regular files are memory mapped with the `mmap` feature, which spares a read
+/// syscall and a copy through the runtime's blocking pool per chunk. Anything else, like
+/// pipes, small files or builds without the feature, goes through a buffer of
+/// `buffer_size` bytes, which should match the DA write chunks.
+pub async fn open_image(path: &Path, buffer_size: usize) -> Result<ImageReader> {
+    let file = File::open(path).await?;
+
+    #[cfg(feature = "mmap")]
+    {
+        let metadata = file.metadata().await?;
+        if metadata.is_file() && metadata.len() >= MMAP_MIN_SIZE {
+            match mmap::MmapReader::new(&file).await {
+                Ok(reader) => return Ok(Box::new(reader)),
+                Err(e) => log::debug!("Failed to map '{}', reading it: {}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(Box::new(BufReader::with_capacity(buffer_size, file)))
+}
+
+#[cfg(feature = "mmap")]
+mod mmap {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use memmap2::Mmap;
+    use tokio::fs::File;
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    /// Reads a file through a read-only mapping of it
+    pub struct MmapReader {
+        map: Mmap,
+        pos: usize,
+    }
+
+    impl MmapReader {
+        pub async fn new(file: &File) -> io::Result<Self> {
+            let file = file.try_clone().await?.into_std().await;
+            // SAFETY: the mapping is only read, and the image isn't expected to change
+            // while it's being flashed. If it does, the device gets whatever the file
+            // holds at the time, like it would with reads.
+            let map = unsafe { Mmap::map(&file)? };
+            #[cfg(unix)]
+            map.advise(memmap2::Advice::Sequential).ok();
+            Ok(Self { map, pos: 0 })
+        }
+    }
+
+    impl AsyncRead for MmapReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let rest = &self.map[self.pos..];
+            let len = rest.len().min(buf.remaining());
+            buf.put_slice(&rest[..len]);
+            self.pos += len;
+            Poll::Ready(Ok(()))
+        }
+    }
+}
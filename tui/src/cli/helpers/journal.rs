@@ -0,0 +1,33 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use log::warn;
+use penumbra::api::Journal;
+
+/// Returns the journal shared by the CLI and TUI, stored in the user data directory.
+pub fn open_journal() -> Option<Journal> {
+    dirs::data_dir().map(|dir| Journal::new(dir.join("antumbra").join("journal")))
+}
+
+/// Returns a warning for each operation a previous session left unfinished,
+/// and marks them so they are only reported once.
+pub fn interrupted_operations(journal: &Journal) -> Vec<String> {
+    let dangling = match journal.acknowledge() {
+        Ok(dangling) => dangling,
+        Err(e) => {
+            warn!("Failed to read the operation journal: {}", e);
+            return Vec::new();
+        }
+    };
+
+    dangling
+        .iter()
+        .map(|entry| {
+            format!(
+                "Previous session was interrupted while writing '{}' — consider re-flashing it",
+                entry.partition
+            )
+        })
+        .collect()
+}
@@ -1,3 +1,23 @@
+mod cancel;
+mod confirm;
+mod hooks;
+mod image_reader;
+mod journal;
+mod pipeline;
+mod ports;
+mod presets;
 mod progress_bar;
+mod sla_keys;
+mod support_bundle;
 
+pub use cancel::cancel_on_ctrl_c;
+pub use confirm::confirm;
+pub use hooks::{HookConfig, HookContext};
+pub use image_reader::open_image;
+pub use journal::{interrupted_operations, open_journal};
+pub use pipeline::FileSink;
+pub use ports::load_user_ports;
+pub use presets::{Presets, resolve_preset};
 pub use progress_bar::AntumbraProgress;
+pub use sla_keys::{load_user_sla_keys, register_sla_signer};
+pub use support_bundle::SupportBundle;
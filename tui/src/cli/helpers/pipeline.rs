@@ -0,0 +1,74 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::info;
+use tokio::task::JoinSet;
+
+/// Partitions up to this size are read into memory, so that saving them overlaps with
+/// reading the next one. Larger ones are streamed to disk as they are read.
+pub const PIPELINE_MAX_BUFFERED: u64 = 64 * 1024 * 1024;
+/// Files being saved at once, bounding the memory held by buffered partitions
+pub const PIPELINE_DEPTH: usize = 4;
+
+/// Saves read partitions in the background while the next ones are read.
+///
+/// The DA session itself is serial, but dumping many small partitions is dominated by
+/// per-partition overhead, so hiding the disk writes behind the next read adds up.
+///
+/// Only the disk side can overlap. The next read can't be set up early: an XML DA only
+/// takes a command after sending its CMD:START, which comes once the CMD:END of the
+/// previous one was acked, and an XFlash DA answers every command and parameter packet
+/// with a status before reading anything else. Acks can't be batched either, the DA
+/// waits for each one before sending the next packet.
+pub struct FileSink {
+    tasks: JoinSet<Result<()>>,
+    serial: bool,
+}
+
+impl FileSink {
+    /// With `serial`, nothing is buffered and every partition is streamed to disk
+    /// before reading the next one.
+    pub fn new(serial: bool) -> Self {
+        Self { tasks: JoinSet::new(), serial }
+    }
+
+    /// Whether a partition of `size` bytes should be read into memory and queued
+    pub fn buffers(&self, size: u64) -> bool {
+        !self.serial && size <= PIPELINE_MAX_BUFFERED
+    }
+
+    /// Queues `data` to be saved to `path`, waiting first if too many saves are in flight.
+    /// Errors of the saves already completed are returned here.
+    pub async fn save(&mut self, path: PathBuf, data: Vec<u8>) -> Result<()> {
+        while self.tasks.len() >= PIPELINE_DEPTH {
+            self.join_next().await?;
+        }
+
+        self.tasks.spawn(async move {
+            tokio::fs::write(&path, data).await?;
+            info!("Saved '{}'", path.display());
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    /// Waits for all the queued saves to complete
+    pub async fn finish(mut self) -> Result<()> {
+        while !self.tasks.is_empty() {
+            self.join_next().await?;
+        }
+        Ok(())
+    }
+
+    async fn join_next(&mut self) -> Result<()> {
+        match self.tasks.join_next().await {
+            Some(result) => result?,
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use log::{info, warn};
+use penumbra::api::register_port;
+use serde::Deserialize;
+
+/// Extra ports, stored in `ports.toml` next to the config as:
+///
+/// ```toml
+/// [[port]]
+/// vid = 0x0E8D
+/// pid = 0x2000
+/// mode = "preloader"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct PortsFile {
+    #[serde(default)]
+    port: Vec<PortEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PortEntry {
+    vid: u16,
+    pid: u16,
+    /// `brom`, `preloader` or `da`
+    mode: String,
+}
+
+/// Registers the ports of the user's `ports.toml` (e.g. `~/.config/antumbra/ports.toml`),
+/// on top of the built-in ones. Invalid entries are skipped.
+pub fn load_user_ports() {
+    let Some(path) = dirs::config_dir().map(|p| p.join("antumbra/ports.toml")) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+
+    match load_ports(&path) {
+        Ok(0) => {}
+        Ok(count) => info!("Loaded {} port(s) from {}", count, path.display()),
+        Err(e) => warn!("Failed to load ports from {}: {}", path.display(), e),
+    }
+}
+
+fn load_ports(path: &Path) -> Result<usize> {
+    let file: PortsFile = toml::from_str(&fs::read_to_string(path)?)?;
+
+    let mut added = 0;
+    for entry in file.port {
+        let result = entry.mode.parse().and_then(|ct| register_port(entry.vid, entry.pid, ct));
+        match result {
+            Ok(()) => added += 1,
+            Err(e) => warn!("Skipping port {:04x}:{:04x}: {}", entry.vid, entry.pid, e),
+        }
+    }
+    Ok(added)
+}
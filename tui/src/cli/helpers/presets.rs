@@ -0,0 +1,87 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(feature = "tui")]
+use anyhow::Result;
+use log::warn;
+use penumbra::api::Partition;
+
+/// Named partition selections, shared by the CLI and TUI.
+/// Stored next to the config in `presets.toml`, as `name = ["boot", "vbmeta", ...]`.
+#[derive(Debug, Default)]
+pub struct Presets {
+    presets: BTreeMap<String, Vec<String>>,
+}
+
+impl Presets {
+    /// Loads the saved presets. A missing or invalid file results in no presets.
+    pub fn load() -> Self {
+        let Some(path) = Self::get_path().filter(|p| p.exists()) else {
+            return Self::default();
+        };
+
+        let presets = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(toml::from_str(&data)?));
+
+        match presets {
+            Ok(presets) => Self { presets },
+            Err(e) => {
+                warn!("Failed to load presets from {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.presets.get(name).map(Vec::as_slice)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.presets.keys()
+    }
+
+    fn get_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("antumbra/presets.toml"))
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Presets {
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = Self::get_path() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(path, toml::to_string_pretty(&self.presets)?)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a preset, replacing any existing one with the same name.
+    pub fn set(&mut self, name: &str, partitions: Vec<String>) {
+        self.presets.insert(name.to_string(), partitions);
+    }
+}
+
+/// Resolves the partition names of a preset against the device partition table.
+/// Returns the partitions found, in preset order, and the names missing on the device.
+pub fn resolve_preset(names: &[String], partitions: &[Partition]) -> (Vec<Partition>, Vec<String>) {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+
+    for name in names {
+        match partitions.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+            Some(part) => found.push(part.clone()),
+            None => missing.push(name.clone()),
+        }
+    }
+
+    (found, missing)
+}
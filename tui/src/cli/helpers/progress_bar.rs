@@ -1,5 +1,9 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanBytes, HumanDuration, ProgressBar, ProgressState, ProgressStyle};
+use penumbra::api::{FlashPhase, PhaseTracker, Progress};
 
 use crate::logger::{INFO_SYMBOL, LOGGER_PREIX};
 
@@ -7,31 +11,71 @@ use crate::logger::{INFO_SYMBOL, LOGGER_PREIX};
 /// With custom styling from the logger
 pub struct AntumbraProgress {
     pb: ProgressBar,
-    #[allow(dead_code)]
     prefix: String,
+    /// Latest progress reported, the rate and ETA shown are the ones computed by the core
+    latest: Arc<Mutex<Option<Progress>>>,
 }
 
 impl AntumbraProgress {
     pub fn new(total_size: u64) -> Self {
         let prefix = format!("{} {}", LOGGER_PREIX.bold().purple(), INFO_SYMBOL.purple());
+        let latest = Arc::new(Mutex::new(None));
 
         let pb = ProgressBar::new(total_size);
-        pb.set_style(
-            ProgressStyle::with_template(
-                &format!(
-                     "{}  [{{bar:40.white/red}}] {{bytes}}/{{total_bytes}} ({{elapsed}} / ETA: {{eta}}, {{bytes_per_sec}}) {{msg}}",
-                     prefix
-                 )
-            )
-            .unwrap()
-            .progress_chars("##-"),
-        );
-
-        Self { pb, prefix }
+        pb.set_style(Self::style(&prefix, &latest, "{rate}"));
+
+        Self { pb, prefix, latest }
+    }
+
+    /// Shows the speed of the running flash phase instead of the overall average,
+    /// so the time the DA spends erasing doesn't count against the transfer speed.
+    pub fn with_phases(self, phases: Option<PhaseTracker>) -> Self {
+        if let Some(phases) = phases {
+            let speed = move |state: &ProgressState, w: &mut dyn Write| {
+                let _ = match (phases.current(), phases.rate()) {
+                    (Some(FlashPhase::Erase), _) => write!(w, "erasing"),
+                    (_, Some(rate)) => write!(w, "{}/s", HumanBytes(rate as u64)),
+                    _ => write!(w, "{}/s", HumanBytes(state.per_sec() as u64)),
+                };
+            };
+            let style = Self::style(&self.prefix, &self.latest, "{phase_speed}")
+                .with_key("phase_speed", speed);
+            self.pb.set_style(style);
+        }
+        self
+    }
+
+    fn style(prefix: &str, latest: &Arc<Mutex<Option<Progress>>>, speed: &str) -> ProgressStyle {
+        let (rate_latest, eta_latest) = (latest.clone(), latest.clone());
+        let rate = move |_: &ProgressState, w: &mut dyn Write| {
+            let rate = rate_latest.lock().ok().and_then(|p| p.and_then(|p| p.rate()));
+            let _ = write!(w, "{}/s", HumanBytes(rate.unwrap_or(0.0) as u64));
+        };
+        let eta = move |_: &ProgressState, w: &mut dyn Write| {
+            let _ = match eta_latest.lock().ok().and_then(|p| p.and_then(|p| p.eta())) {
+                Some(eta) => write!(w, "{}", HumanDuration(eta)),
+                None => write!(w, "-"),
+            };
+        };
+
+        ProgressStyle::with_template(&format!(
+            "{}  [{{bar:40.white/red}}] {{bytes}}/{{total_bytes}} ({{elapsed}} / ETA: {{core_eta}}, {}) {{msg}}",
+            prefix, speed
+        ))
+        .unwrap()
+        .with_key("rate", rate)
+        .with_key("core_eta", eta)
+        .progress_chars("##-")
     }
 
-    pub fn update(&self, written: u64, msg: &str) {
-        self.pb.set_position(written);
+    /// Moves the bar to the reported progress. The length follows the reported total,
+    /// which grows e.g. when written data is read back for verification.
+    pub fn update(&self, progress: &Progress, msg: &str) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some(*progress);
+        }
+        self.pb.set_length(progress.total);
+        self.pb.set_position(progress.written);
         self.pb.set_message(msg.to_string());
     }
 
@@ -0,0 +1,30 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::sync::Arc;
+
+use log::warn;
+use penumbra::api::{AuthManager, BUILTIN_SIGNER_PRIORITY, ExecSigner};
+
+/// Adds the user's SLA keys, the `.pem` and `.der` files in the config directory
+/// (e.g. `~/.config/antumbra/sla_keys`), to the built-in keyring.
+pub fn load_user_sla_keys() {
+    if let Some(dir) = dirs::config_dir().map(|p| p.join("antumbra/sla_keys")) {
+        AuthManager::get().local_keyring().add_keys_from_dir(dir);
+    }
+}
+
+/// Registers the `--sla-signer` command, if any, ahead of the built-in keys.
+pub fn register_sla_signer(command: Option<&str>) {
+    let Some(command) = command else { return };
+
+    let result = ExecSigner::from_command_line(command)
+        .and_then(|signer| {
+            let priority = BUILTIN_SIGNER_PRIORITY + 1;
+            AuthManager::get().register_signer_with_priority(Arc::new(signer), priority)
+        });
+    if let Err(e) = result {
+        warn!("Failed to register the SLA signer: {}", e);
+    }
+}
@@ -0,0 +1,193 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2025 Shomy
+*/
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use penumbra::api::{Device, TargetConfig, sha256};
+use serde_json::{Value, json};
+use tokio::fs;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::logger::recent_log;
+
+/// Facts about a device, gathered to be shared when asking for support.
+pub struct SupportBundle {
+    files: Vec<(&'static str, Value)>,
+    /// The last log lines of this run, see [`recent_log`]
+    log: Vec<String>,
+}
+
+/// Hides an identifier behind a short hash, so that bundles from the same device can still be
+/// correlated without leaking the identifier itself.
+fn sanitize_id(id: &[u8], include_ids: bool) -> String {
+    if include_ids {
+        return hex::encode_upper(id);
+    }
+
+    if id.is_empty() {
+        return String::new();
+    }
+
+    format!("sha256:{}", hex::encode(&sha256(id)[..8]))
+}
+
+impl SupportBundle {
+    /// Collects the device facts. Nothing is written to the device.
+    pub async fn collect(dev: &mut Device, include_ids: bool) -> Result<Self> {
+        let info = &dev.dev_info;
+        let target_config = info.target_config().await;
+        let security = TargetConfig::from(target_config);
+
+        let device = json!({
+            "chipset": info.chipset().await,
+            "hw_code": format!("0x{:04X}", info.hw_code().await),
+            "chip": info.chip_name().await,
+            "soc_id": sanitize_id(&info.soc_id().await, include_ids),
+            "meid": sanitize_id(&info.meid().await, include_ids),
+            "target_config": {
+                "raw": format!("0x{:08X}", target_config),
+                "sbc": security.sbc,
+                "sla": security.sla,
+                "daa": security.daa,
+                "swjtag": security.swjtag,
+                "epp": security.epp,
+                "root_cert_required": security.root_cert_required,
+                "mem_preserve": security.mem_preserve,
+            },
+            "gpt_source": info.gpt_source().await.as_str(),
+        });
+
+        let storage: Vec<Value> = dev
+            .regions()
+            .await
+            .iter()
+            .map(|r| json!({ "kind": r.kind.as_str(), "size": r.size, "writable": r.writable }))
+            .collect();
+
+        let partitions: Vec<Value> = dev
+            .dev_info
+            .partitions()
+            .await
+            .iter()
+            .map(|p| {
+                json!({
+                    "name": p.name,
+                    "address": format!("0x{:X}", p.address),
+                    "size": p.size,
+                    "kind": p.kind.as_str(),
+                })
+            })
+            .collect();
+
+        let da = match dev.da_file() {
+            Some(da_file) => json!({
+                "type": format!("{:?}", da_file.da_type),
+                "size": da_file.da_raw_data.len(),
+                "sha256": hex::encode(sha256(&da_file.da_raw_data)),
+                "hw_codes": da_file
+                    .das
+                    .iter()
+                    .map(|da| format!("0x{:04X}", da.hw_code))
+                    .collect::<Vec<_>>(),
+            }),
+            None => Value::Null,
+        };
+
+        let versions = json!({
+            "antumbra": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        });
+
+        Ok(Self {
+            files: vec![
+                ("device.json", device),
+                ("storage.json", Value::Array(storage)),
+                ("partitions.json", Value::Array(partitions)),
+                ("da.json", da),
+                ("versions.json", versions),
+            ],
+            log: recent_log(),
+        })
+    }
+
+    /// Writes the bundle to `path` as a zip archive of JSON files, plus the log excerpt.
+    pub async fn write_to(&self, path: &Path) -> Result<()> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        for (name, value) in &self.files {
+            zip.start_file(*name, options)?;
+            zip.write_all(&serde_json::to_vec_pretty(value)?)?;
+        }
+
+        if !self.log.is_empty() {
+            zip.start_file("antumbra.log", options)?;
+            zip.write_all(self.log.join("\n").as_bytes())?;
+        }
+
+        fs::write(path, zip.finish()?.into_inner()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    const MEID: &[u8] = &[0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x23, 0x45, 0x67];
+
+    #[test]
+    fn ids_are_hashed_by_default() {
+        let hashed = sanitize_id(MEID, false);
+        assert_eq!(hashed, format!("sha256:{}", hex::encode(&sha256(MEID)[..8])));
+        assert!(!hashed.to_uppercase().contains("DEADBEEF"));
+    }
+
+    #[test]
+    fn hashed_ids_correlate() {
+        assert_eq!(sanitize_id(MEID, false), sanitize_id(MEID, false));
+        assert_ne!(sanitize_id(MEID, false), sanitize_id(&MEID[1..], false));
+    }
+
+    #[test]
+    fn ids_are_kept_when_asked() {
+        assert_eq!(sanitize_id(MEID, true), "DEADBEEF01234567");
+    }
+
+    #[test]
+    fn missing_ids_stay_empty() {
+        assert_eq!(sanitize_id(&[], false), "");
+        assert_eq!(sanitize_id(&[], true), "");
+    }
+
+    #[tokio::test]
+    async fn bundle_is_a_zip() {
+        let bundle = SupportBundle {
+            files: vec![("device.json", json!({ "meid": sanitize_id(MEID, false) }))],
+            log: vec!["[INFO] first".into(), "[WARN] second".into()],
+        };
+        let path = std::env::temp_dir().join(format!("antumbra-bundle-{}.zip", std::process::id()));
+        bundle.write_to(&path).await.unwrap();
+
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut text = String::new();
+            zip.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+            text
+        };
+        let device: Value = serde_json::from_str(&read("device.json")).unwrap();
+        assert_eq!(device["meid"], sanitize_id(MEID, false));
+        assert_eq!(read("antumbra.log"), "[INFO] first\n[WARN] second");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
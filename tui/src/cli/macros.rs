@@ -1,9 +1,10 @@
 #[macro_export]
 macro_rules! mtk_commands {
-    ( $( $variant:ident ($ty:ty) ),+ $(,)? ) => {
+    ( $( $(#[$attr:meta])* $variant:ident ($ty:ty) ),+ $(,)? ) => {
         #[derive(clap::Subcommand, Debug)]
         pub enum Commands {
             $(
+                $(#[$attr])*
                 #[command(
                     aliases = <$ty as $crate::cli::common::CommandMetadata>::aliases(),
                     visible_aliases = <$ty as $crate::cli::common::CommandMetadata>::visible_aliases(),
@@ -15,11 +16,31 @@ macro_rules! mtk_commands {
             )+
         }
 
+        impl Commands {
+            /// The subcommand name, as shown in `--help`
+            pub fn name(&self) -> String {
+                use clap::CommandFactory;
+
+                let name = match self {
+                    $(
+                        $(#[$attr])*
+                        Commands::$variant(_) => stringify!($variant),
+                    )+
+                };
+                $crate::cli::CliArgs::command()
+                    .get_subcommands()
+                    .map(|c| c.get_name().to_string())
+                    .find(|c| c.replace('-', "").eq_ignore_ascii_case(name))
+                    .unwrap_or_else(|| name.to_string())
+            }
+        }
+
         #[async_trait::async_trait]
         impl $crate::cli::MtkCommand for Commands {
             fn da(&self) -> Option<&std::path::PathBuf> {
                 match self {
                     $(
+                        $(#[$attr])*
                         Commands::$variant(inner) => inner.da(),
                     )+
                 }
@@ -28,18 +49,29 @@ macro_rules! mtk_commands {
             fn pl(&self) -> Option<&std::path::PathBuf> {
                 match self {
                     $(
+                        $(#[$attr])*
                         Commands::$variant(inner) => inner.pl(),
                     )+
                 }
             }
 
+            fn partitions(&self) -> Vec<String> {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Commands::$variant(inner) => inner.partitions(),
+                    )+
+                }
+            }
+
             async fn run(
                 &self,
-                dev: &mut penumbra::Device,
+                dev: &mut penumbra::api::Device,
                 state: &mut $crate::cli::state::PersistedDeviceState,
             ) -> anyhow::Result<()> {
                 match self {
                     $(
+                        $(#[$attr])*
                         Commands::$variant(inner) => inner.run(dev, state).await,
                     )+
                 }
@@ -4,7 +4,7 @@
 */
 mod commands;
 mod common;
-mod helpers;
+pub mod helpers;
 mod macros;
 mod state;
 
@@ -14,15 +14,36 @@ use std::time::{Duration, Instant};
 use anyhow::Result;
 use async_trait::async_trait;
 use clap::{CommandFactory, Parser};
-use log::info;
-use penumbra::connection::port::ConnectionType;
-use penumbra::core::devinfo::DevInfoData;
-use penumbra::{Device, DeviceBuilder, find_mtk_port};
+use human_bytes::human_bytes;
+use log::{info, warn};
+use penumbra::api::{
+    ConnectionType,
+    CoreEvent,
+    DEFAULT_RESPONSE_TIMEOUT,
+    DevInfoData,
+    Device,
+    DeviceBuilder,
+    GptSource,
+    MTKPort,
+    RetryPolicy,
+    TargetConfig,
+    TcpMTKPort,
+    TransferConfig,
+    event_channel,
+    find_mtk_port,
+    list_mtk_ports,
+    open_mtk_port,
+};
 use tokio::fs::read;
 
 use crate::cli::commands::*;
+use crate::cli::common::{parse_rate, parse_size};
+use crate::cli::helpers::{
+    HookConfig, HookContext, cancel_on_ctrl_c, interrupted_operations, open_journal,
+};
 use crate::cli::macros::mtk_commands;
-use crate::cli::state::PersistedDeviceState;
+use crate::cli::state::{DEFAULT_STATE_TTL, PersistedDeviceState};
+use crate::notify::{DEFAULT_NOTIFY_THRESHOLD, NotifyMode, notify_completion};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -33,6 +54,100 @@ pub struct CliArgs {
     /// Enable verbose logging, including debug information
     #[arg(short, long)]
     pub verbose: bool,
+    /// Allow writing and erasing partitions when the partition table was read from the
+    /// secondary GPT, because the primary one is unreadable. Restoring pgpt or sgpt is
+    /// always allowed
+    #[arg(long, global = true)]
+    pub allow_secondary_gpt: bool,
+    /// Allow writing preloader images to user area partitions, and other images to the
+    /// boot region
+    #[arg(long, global = true)]
+    pub ignore_region_mismatch: bool,
+    /// Allow reading and writing partitions that extend past the end of the storage,
+    /// e.g. when the partition table was made for a bigger storage
+    #[arg(long, global = true)]
+    pub force: bool,
+    /// Read partitions back after writing them, and fail if the data doesn't match
+    #[arg(long, global = true)]
+    pub verify_writes: bool,
+    /// Leave the parts of an erase or format that don't cover a whole erase group untouched,
+    /// instead of zero-filling them
+    #[arg(long, global = true)]
+    pub skip_partial_erase: bool,
+    /// Limit the flash transfer rate, in bytes per second (e.g. 512K, 10M)
+    #[arg(long, global = true, value_name = "RATE", value_parser = parse_rate)]
+    pub limit_rate: Option<u64>,
+    /// Largest packet sent to the DA (e.g. 64K), can't exceed what the DA reports
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    pub write_chunk: Option<usize>,
+    /// Largest single USB read while receiving from the DA (e.g. 64K)
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    pub read_chunk: Option<usize>,
+    /// Use the device attached to another machine through `antumbra bridge` (HOST[:PORT]),
+    /// instead of a local one. Also read from the PENUMBRA_BRIDGE variable
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    pub bridge: Option<String>,
+    /// Use the device with this USB serial number or port location (e.g. `bus 3-2` or
+    /// `/dev/ttyACM0`), when several are connected
+    #[arg(long, global = true, value_name = "SERIAL|PATH")]
+    pub device: Option<String>,
+    /// Record the traffic with the device to FILE, to attach to bug reports. Read it with
+    /// `antumbra trace FILE`
+    #[arg(long, global = true, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+    /// Have the DA verify a checksum of every packet written (XML DAs, slightly slower)
+    #[arg(long, global = true)]
+    pub checksum: bool,
+    /// Times a data chunk is sent or read again after a USB timeout, 0 to never retry
+    #[arg(long, global = true, value_name = "N", default_value_t = RetryPolicy::default().attempts)]
+    pub retries: u32,
+    /// Seconds to wait for the DA to answer before giving up, 0 to wait forever
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = DEFAULT_RESPONSE_TIMEOUT.as_secs()
+    )]
+    pub response_timeout: u64,
+    /// Command signing SLA challenges, e.g. through an HSM. Gets the challenge as hex
+    /// on stdin and prints the signature as hex
+    #[arg(long, global = true, value_name = "COMMAND")]
+    pub sla_signer: Option<String>,
+    /// Keep an exploit from running (e.g. carbonara), can be given more than once
+    #[arg(long, global = true, value_name = "NAME")]
+    pub disable_exploit: Vec<String>,
+    /// Leave the SoC watchdog running after the handshake, for chips where turning it off
+    /// fails or resets them
+    #[arg(long, global = true)]
+    pub keep_watchdog: bool,
+    /// Leave the DA at the USB speed it starts with, instead of switching a USB 1.1 link
+    /// to a higher speed
+    #[arg(long, global = true)]
+    pub keep_usb_speed: bool,
+    /// Baudrate to switch serial connections to after the handshake
+    /// [env: PENUMBRA_BAUDRATE]
+    #[arg(long, global = true, value_name = "BAUD")]
+    pub baudrate: Option<u32>,
+    /// Notify when the command finishes, if it ran longer than --notify-after
+    #[arg(long, global = true, value_name = "MODE", default_value = "off")]
+    pub notify: NotifyMode,
+    /// Minimum command duration, in seconds, before notifying
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = DEFAULT_NOTIFY_THRESHOLD.as_secs()
+    )]
+    pub notify_after: u64,
+    /// Seconds after which the state saved by the previous command is discarded,
+    /// 0 keeps it regardless of its age
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = DEFAULT_STATE_TTL.as_secs()
+    )]
+    pub state_ttl: u64,
     /// The DA file to use
     #[arg(short, long = "da", value_name = "DA_FILE")]
     pub da_file: Option<PathBuf>,
@@ -54,10 +169,19 @@ mtk_commands! {
     ReadAll(ReadAllArgs),
     Seccfg(SeccfgArgs),
     Pgpt(PgptArgs),
+    Info(InfoArgs),
+    SupportBundle(SupportBundleArgs),
     Peek(PeekArgs),
+    Patch(PatchArgs),
     Shutdown(ShutdownArgs),
     Reboot(RebootArgs),
     XFlash(XFlashArgs),
+    Wait(WaitArgs),
+    State(StateArgs),
+    Bridge(BridgeArgs),
+    Trace(TraceArgs),
+    #[cfg(feature = "devtools")]
+    Console(ConsoleArgs),
 }
 
 #[async_trait]
@@ -68,15 +192,61 @@ pub trait MtkCommand {
     fn pl(&self) -> Option<&PathBuf> {
         None
     }
+    /// The partitions the command works on, passed to hooks
+    fn partitions(&self) -> Vec<String> {
+        Vec::new()
+    }
     async fn run(&self, dev: &mut Device, state: &mut PersistedDeviceState) -> Result<()>;
 }
 
+/// Finds the port to use, behind the bridge at `bridge` if given, else the local one
+/// matching `device`, or the first one
+async fn find_port(bridge: Option<&str>, device: Option<&str>) -> Option<Box<dyn MTKPort>> {
+    if let Some(addr) = bridge {
+        let mut port = TcpMTKPort::new(addr);
+        port.open().await.ok()?;
+        return Some(Box::new(port));
+    }
+
+    let Some(device) = device else {
+        return find_mtk_port().await;
+    };
+
+    let ports = list_mtk_ports().await;
+    open_mtk_port(ports.iter().find(|port| port.matches(device))?).await
+}
+
 pub async fn run_cli(args: &CliArgs) -> Result<()> {
     if args.command.is_none() {
         CliArgs::command().print_help()?;
         return Ok(());
     }
 
+    // Waiting only looks at the port list, the device must not be connected to
+    if let Some(Commands::Wait(wait)) = &args.command {
+        return wait.wait(&PersistedDeviceState::load().await).await;
+    }
+
+    if let Some(Commands::State(cmd)) = &args.command {
+        return cmd.execute(&mut PersistedDeviceState::load().await).await;
+    }
+
+    if let Some(Commands::Bridge(cmd)) = &args.command {
+        return cmd.serve().await;
+    }
+
+    if let Some(Commands::Trace(cmd)) = &args.command {
+        return cmd.print();
+    }
+
+    let journal = open_journal();
+    let report = journal.as_ref().map(|j| j.path().to_path_buf());
+    if let Some(journal) = &journal {
+        for warning in interrupted_operations(journal) {
+            warn!("{}", warning);
+        }
+    }
+
     let mut state = PersistedDeviceState::load().await;
 
     let da_data = if let Some(cmd) = &args.command {
@@ -105,9 +275,20 @@ pub async fn run_cli(args: &CliArgs) -> Result<()> {
     let mut last_seen = Instant::now();
     let timeout = Duration::from_millis(500);
 
+    if args.bridge.is_none() && args.device.is_none() {
+        let ports = list_mtk_ports().await;
+        if ports.len() > 1 {
+            warn!("{} MTK devices are connected, using the first one:", ports.len());
+            for port in &ports {
+                warn!("  {}", port);
+            }
+            warn!("Pick one with --device <SERIAL|PATH>");
+        }
+    }
+
     info!("Waiting for MTK device...");
     let mtk_port = loop {
-        if let Some(port) = find_mtk_port().await {
+        if let Some(port) = find_port(args.bridge.as_deref(), args.device.as_deref()).await {
             info!("Found MTK port: {}", port.get_port_name());
             break port;
         } else if last_seen.elapsed() > timeout {
@@ -116,21 +297,56 @@ pub async fn run_cli(args: &CliArgs) -> Result<()> {
         }
     };
 
+    let da_data = match (da_data, &state.da_file_path) {
+        (Some(da), _) => Some(da),
+        (None, Some(da_path)) => Some(read(Path::new(da_path)).await?),
+        (None, None) => None,
+    };
+
+    let state_ttl = Duration::from_secs(args.state_ttl);
+    state.invalidate_if_stale(mtk_port.get_location().as_deref(), da_data.as_deref(), state_ttl);
+
     let mut builder = DeviceBuilder::default()
         .with_mtk_port(mtk_port)
-        .with_verbose(args.verbose);
-
-    builder = if let Some(da) = da_data {
-        builder.with_da_data(da)
-    } else if let Some(da_path_str) = &state.da_file_path {
-        let da_path = Path::new(da_path_str);
-        let data = read(da_path).await?;
-        builder.with_da_data(data)
-    } else {
-        builder
-    };
+        .with_verbose(args.verbose)
+        .with_allow_secondary_gpt(args.allow_secondary_gpt)
+        .with_ignore_region_mismatch(args.ignore_region_mismatch)
+        .with_allow_out_of_range(args.force)
+        .with_verify_writes(args.verify_writes)
+        .with_skip_partial_erase(args.skip_partial_erase)
+        .with_keep_watchdog(args.keep_watchdog)
+        .with_keep_usb_speed(args.keep_usb_speed)
+        .with_baudrate(args.baudrate)
+        .with_rate_limit(args.limit_rate)
+        .with_transfer_config(TransferConfig {
+            read_chunk: args.read_chunk,
+            write_chunk: args.write_chunk,
+            checksum: args.checksum,
+        })
+        .with_retry(RetryPolicy { attempts: args.retries, ..Default::default() })
+        .with_response_timeout(
+            Some(Duration::from_secs(args.response_timeout)).filter(|t| !t.is_zero()),
+        );
+
+    builder = if let Some(da) = da_data { builder.with_da_data(da) } else { builder };
 
     builder = if let Some(pl) = pl_data { builder.with_preloader(pl) } else { builder };
+    builder = if let Some(journal) = journal { builder.with_journal(journal) } else { builder };
+    builder = if let Some(trace) = &args.trace { builder.with_trace(trace) } else { builder };
+    for name in &args.disable_exploit {
+        builder = builder.disable_exploit(name.clone());
+    }
+
+    // The DA can report at any point of a command, print it as it comes
+    let (events, mut notices) = event_channel();
+    builder = builder.with_event_sink(events);
+    tokio::spawn(async move {
+        while let Some(event) = notices.recv().await {
+            match event {
+                CoreEvent::DaNotice(notice) => warn!("DA reports: {}", notice),
+            }
+        }
+    });
 
     let mut dev = builder.build()?;
 
@@ -142,7 +358,10 @@ pub async fn run_cli(args: &CliArgs) -> Result<()> {
             chipset: String::from("Unknown"),
             storage: None,
             partitions: vec![],
+            gpt_source: GptSource::default(),
+            gpt_report: None,
             target_config: state.target_config,
+            usb_speed: None,
         };
 
         if state.flash_mode != 0 {
@@ -162,14 +381,85 @@ pub async fn run_cli(args: &CliArgs) -> Result<()> {
         state.save().await?;
     }
 
+    if let Some(rate) = args.limit_rate {
+        info!("Transfer rate limited to {}/s", human_bytes(rate as f64));
+    }
+
     info!("=====================================");
-    info!("SBC: {}", (state.target_config & 0x1) != 0);
-    info!("SLA: {}", (state.target_config & 0x2) != 0);
-    info!("DAA: {}", (state.target_config & 0x4) != 0);
+    let security = TargetConfig::from(state.target_config);
+    info!("SBC: {}", security.sbc);
+    info!("SLA: {}", security.sla);
+    info!("DAA: {}", security.daa);
     info!("=====================================");
 
     if let Some(cmd) = &args.command {
-        cmd.run(&mut dev, &mut state).await?;
+        let started = Instant::now();
+        let ctrl_c = cancel_on_ctrl_c(dev.cancel_handle());
+        let conn_stats = dev.conn_stats();
+        if let Some(conn_stats) = &conn_stats {
+            conn_stats.reset();
+        }
+        let result = cmd.run(&mut dev, &mut state).await;
+        if let Some(ctrl_c) = ctrl_c {
+            ctrl_c.abort();
+        }
+
+        let stats = dev.phase_tracker().map(|p| p.take()).filter(|s| !s.is_empty());
+        if let Some(stats) = &stats {
+            info!("Time spent: {}", stats);
+        }
+        if let Some(traffic) = conn_stats.map(|s| s.take()).filter(|s| !s.is_empty()) {
+            info!("Traffic: {}", traffic);
+            if let Some(error) = &traffic.last_error {
+                info!("Last transfer error: {}", error);
+            }
+        }
+
+        let summary = match (&result, &stats) {
+            (Ok(_), Some(stats)) => format!("Antumbra: operation completed ({})", stats),
+            (Ok(_), None) => "Antumbra: operation completed".to_string(),
+            (Err(e), _) => format!("Antumbra: operation failed: {}", e),
+        };
+        let threshold = Duration::from_secs(args.notify_after);
+        notify_completion(args.notify, started.elapsed(), threshold, &summary);
+
+        let hook = HookContext {
+            device: hex::encode_upper(&state.meid),
+            operation: cmd.name(),
+            partitions: cmd.partitions(),
+            duration: started.elapsed(),
+            report,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        HookConfig::load().run(&hook);
+
+        let gpt_source = dev.gpt_source().await;
+        if !dev.dev_info.partitions().await.is_empty() && !gpt_source.is_trusted() {
+            warn!("=====================================");
+            warn!("Primary GPT is unreadable! Partition table source: {}", gpt_source.as_str());
+            warn!("Partition addresses might be stale, write and erase are refused unless");
+            warn!("--allow-secondary-gpt is passed. Consider repairing the primary GPT.");
+            warn!("=====================================");
+        }
+
+        if let Some(diff) = dev.gpt_mismatch().await {
+            warn!("=====================================");
+            warn!("Primary and secondary GPT differ ({} partitions)!", diff.entries.len());
+            warn!("Run `gpt diff` to compare them and `gpt repair --from <table>` to fix.");
+            warn!("=====================================");
+        }
+
+        if let Some(report) = dev.gpt_report().await
+            && report.primary_ok
+            && !report.secondary_ok
+        {
+            warn!("=====================================");
+            warn!("Secondary GPT is corrupted!");
+            warn!("Run `gpt repair --from primary` to restore it from the primary GPT.");
+            warn!("=====================================");
+        }
+
+        result?;
         state.target_config = dev.dev_info.target_config().await; // Update just in case after Kamakiri
         state.save().await?;
     }
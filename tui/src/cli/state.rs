@@ -2,10 +2,18 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use anyhow::Result;
+use log::debug;
+use penumbra::api::sha256;
 use serde::{Deserialize, Serialize};
 use tokio::fs::{metadata, read, remove_file, write};
 
+/// Age after which the saved state isn't trusted anymore, the device has likely been
+/// unplugged or rebooted in the meantime
+pub const DEFAULT_STATE_TTL: Duration = Duration::from_secs(30 * 60);
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct PersistedDeviceState {
     pub da_file_path: Option<String>,
@@ -15,6 +23,15 @@ pub struct PersistedDeviceState {
     pub target_config: u32,
     pub connection_type: u8,
     pub flash_mode: u8,
+    /// Where the device was last connected, telling it apart from other devices
+    #[serde(default)]
+    pub port_location: Option<String>,
+    /// SHA-256 of the DA the device was connected with, in hex
+    #[serde(default)]
+    pub da_hash: Option<String>,
+    /// When the state was last saved, in seconds since the Unix epoch
+    #[serde(default)]
+    pub saved_at: u64,
 }
 
 impl PersistedDeviceState {
@@ -30,7 +47,8 @@ impl PersistedDeviceState {
     }
 
     /// Saves the current state to the `.antumbra_state` file.
-    pub async fn save(&self) -> Result<()> {
+    pub async fn save(&mut self) -> Result<()> {
+        self.saved_at = now();
         let json = serde_json::to_vec_pretty(self)?;
         write(Self::STATE_FILE, json)
             .await
@@ -46,4 +64,121 @@ impl PersistedDeviceState {
         *self = PersistedDeviceState::default();
         Ok(())
     }
+
+    /// Drops the state of a previous session if it doesn't belong to the device found at
+    /// `location` and the `da` about to be used, or if it's older than `ttl`.
+    /// The DA file path is kept, as it's a user choice rather than device state.
+    /// Afterwards, the state describes the current port and DA.
+    pub fn invalidate_if_stale(
+        &mut self,
+        location: Option<&str>,
+        da: Option<&[u8]>,
+        ttl: Duration,
+    ) {
+        let da_hash = da.map(|da| hex::encode(sha256(da)));
+
+        if self.hw_code != 0
+            && let Some(reason) = self.stale_reason(location, da_hash.as_deref(), ttl)
+        {
+            debug!("Discarding persisted device state: {}", reason);
+            *self = Self { da_file_path: self.da_file_path.take(), ..Default::default() };
+        }
+
+        self.port_location = location.map(str::to_string);
+        self.da_hash = da_hash;
+    }
+
+    fn stale_reason(
+        &self,
+        location: Option<&str>,
+        da_hash: Option<&str>,
+        ttl: Duration,
+    ) -> Option<&'static str> {
+        let age = Duration::from_secs(now().saturating_sub(self.saved_at));
+        if !ttl.is_zero() && age > ttl {
+            return Some("older than the state TTL");
+        }
+
+        if let (Some(saved), Some(current)) = (self.port_location.as_deref(), location)
+            && saved != current
+        {
+            return Some("the device is connected to another port");
+        }
+
+        if self.da_hash.as_deref() != da_hash {
+            return Some("a different DA file is used");
+        }
+
+        None
+    }
+}
+
+/// Current time, in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DA: &[u8] = b"MTK_DOWNLOAD_AGENT";
+    const TTL: Duration = Duration::from_secs(60);
+
+    /// A state saved `age` seconds ago, for the device at `1-2` connected with [`DA`]
+    fn saved(age: u64) -> PersistedDeviceState {
+        PersistedDeviceState {
+            da_file_path: Some(String::from("MT6768_DA.bin")),
+            meid: vec![0xAA; 16],
+            hw_code: 0x707,
+            target_config: 0xE7,
+            port_location: Some(String::from("1-2")),
+            da_hash: Some(hex::encode(sha256(DA))),
+            saved_at: now() - age,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stale_state_is_discarded() {
+        let cases = [
+            ("same device", 0, Some("1-2"), Some(DA), TTL, true),
+            ("older than the TTL", 61, Some("1-2"), Some(DA), TTL, false),
+            ("no TTL", 86_400, Some("1-2"), Some(DA), Duration::ZERO, true),
+            ("another port", 0, Some("1-3"), Some(DA), TTL, false),
+            // Some backends can't tell where the port is
+            ("unknown port", 0, None, Some(DA), TTL, true),
+            ("another DA", 0, Some("1-2"), Some(&b"MTK_DA_V6"[..]), TTL, false),
+            ("no DA", 0, Some("1-2"), None, TTL, false),
+        ];
+
+        for (name, age, location, da, ttl, kept) in cases {
+            let mut state = saved(age);
+            state.invalidate_if_stale(location, da, ttl);
+
+            assert_eq!(state.hw_code == 0x707, kept, "{}", name);
+            assert_eq!(state.meid.is_empty(), !kept, "{}", name);
+            assert_eq!(state.target_config == 0xE7, kept, "{}", name);
+            // A user choice, not device state
+            assert_eq!(state.da_file_path.as_deref(), Some("MT6768_DA.bin"), "{}", name);
+            assert_eq!(state.port_location.as_deref(), location, "{}", name);
+            assert_eq!(state.da_hash, da.map(|da| hex::encode(sha256(da))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn empty_state_takes_the_current_device() {
+        let mut state = PersistedDeviceState::default();
+        state.invalidate_if_stale(Some("1-2"), Some(DA), TTL);
+
+        assert_eq!(state.hw_code, 0);
+        assert_eq!(state.port_location.as_deref(), Some("1-2"));
+        assert_eq!(state.da_hash, Some(hex::encode(sha256(DA))));
+
+        // And is kept the next time around
+        state.hw_code = 0x707;
+        state.saved_at = now();
+        state.invalidate_if_stale(Some("1-2"), Some(DA), TTL);
+        assert_eq!(state.hw_code, 0x707);
+    }
 }
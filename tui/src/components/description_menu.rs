@@ -10,6 +10,16 @@ use ratatui::text::{Line, Span};
 use crate::components::ThemedWidgetMut;
 use crate::themes::Theme;
 
+/// Width reserved for the menu entries.
+const MENU_WIDTH: u16 = 24;
+/// Space between the menu and the description box.
+const DESC_PAD: u16 = 4;
+/// Below this width, the description box is dropped in favor of a line under the menu.
+const MIN_DESC_WIDTH: u16 = 18;
+const MAX_DESC_WIDTH: u16 = 36;
+/// Lines used by the description when shown under the menu.
+const COMPACT_DESC_LINES: u16 = 2;
+
 #[derive(Clone)]
 pub struct DescriptionMenuItem {
     pub icon: char,
@@ -95,21 +105,42 @@ impl DescriptionMenu {
 
 impl ThemedWidgetMut for DescriptionMenu {
     fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
-        // TODO: Stop using magic values here
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+
         let y_spacing = 1u16;
-        let avail = (area.height.saturating_sub(2)) as usize;
-        self.set_max_visible(avail);
+        let menu_width = MENU_WIDTH.min(area.width);
+
+        // Side box when it fits, otherwise the description goes under the menu
+        let side_box = area.width >= menu_width + DESC_PAD + MIN_DESC_WIDTH;
+        let desc_width = if side_box {
+            (area.width - menu_width - DESC_PAD).min(MAX_DESC_WIDTH)
+        } else {
+            area.width
+        };
 
-        let menu_width = 24u16;
-        let desc_pad = 4u16;
-        let desc_width = (area.width.saturating_sub(menu_width + desc_pad)).clamp(18, 36);
+        let reserved = if side_box { 0 } else { COMPACT_DESC_LINES + 1 };
+        let avail = area.height.saturating_sub(2 + reserved) as usize;
+        self.set_max_visible(avail);
 
         let n_shown = self.max_visible.min(self.items.len());
         let menu_height = n_shown as u16 * y_spacing;
-        let start_y = area.y + (area.height.saturating_sub(menu_height)).saturating_div(2);
+        let start_y =
+            area.y + (area.height.saturating_sub(menu_height + reserved)).saturating_div(2);
+        let bottom = area.y + area.height;
+        let right = area.x + area.width;
+
+        let total_width = if side_box { menu_width + DESC_PAD + desc_width } else { menu_width };
+        let base_x = area.x + (area.width - total_width) / 2;
+        let desc_x = base_x + menu_width + DESC_PAD;
 
-        let base_x = area.x + area.width / 2 - ((menu_width + desc_pad + desc_width) / 2);
-        let desc_x = base_x + menu_width + desc_pad;
+        // Clips every string to the area, to never draw outside of it
+        let put = |buf: &mut Buffer, x: u16, y: u16, text: &str, style: Style| {
+            if y >= area.y && y < bottom && x < right {
+                buf.set_stringn(x, y, text, (right - x) as usize, style);
+            }
+        };
 
         let items = &self.items;
         let win_start = self.scroll_offset;
@@ -132,72 +163,149 @@ impl ThemedWidgetMut for DescriptionMenu {
             };
 
             let text = format!("{}  {}", item.icon, item.label);
-            buf.set_string(base_x, y, &text, style);
+            put(buf, base_x, y, &text, style);
         }
 
         // Only appear when there's more to scroll btw
-        if self.scroll_offset > 0 {
-            buf.set_string(
-                base_x,
-                start_y.saturating_sub(1),
-                "↑",
-                Style::default().fg(theme.muted),
-            );
+        if self.scroll_offset > 0 && start_y > area.y {
+            put(buf, base_x, start_y - 1, "↑", Style::default().fg(theme.muted));
         }
         if win_end < items.len() {
             let y = start_y + self.max_visible as u16 * y_spacing;
-            if y < area.y + area.height {
-                buf.set_string(base_x, y, "↓", Style::default().fg(theme.muted));
-            }
+            put(buf, base_x, y, "↓", Style::default().fg(theme.muted));
         }
 
-        if let Some(desc) = selected_desc {
-            let sel_y = start_y;
-            let max_box_height = area.height.saturating_sub(2).min(6);
-            let lines = Self::wrap_text(&desc, (desc_width - 4) as usize);
-            let content_lines = lines.iter().take((max_box_height as usize).saturating_sub(2));
-            let box_height = 2 + content_lines.len() as u16;
+        let Some(desc) = selected_desc else {
+            return;
+        };
 
-            let mut box_y = sel_y;
-            if box_y + box_height > area.y + area.height {
-                box_y = area.y + area.height - box_height;
-            }
-            if box_y < area.y {
-                box_y = area.y;
+        let desc_style =
+            Style::default().fg(theme.info).add_modifier(Modifier::ITALIC | Modifier::BOLD);
+
+        if !side_box {
+            let desc_y = start_y + menu_height + 1;
+            let lines = Self::wrap_text(&desc, area.width as usize);
+            for (j, line) in lines.iter().take(COMPACT_DESC_LINES as usize).enumerate() {
+                let x = area.x + (area.width.saturating_sub(line.chars().count() as u16)) / 2;
+                put(buf, x, desc_y + j as u16, line, desc_style);
             }
+            return;
+        }
 
-            let accent_top = format!("╭{}╮", "─".repeat(desc_width.saturating_sub(2) as usize));
-            buf.set_string(desc_x, box_y, &accent_top, Style::default().fg(theme.foreground));
-            for (j, line) in lines.iter().enumerate() {
-                let y = box_y + 1 + j as u16;
-                if (j as u16) + 1 >= max_box_height - 1 || y >= area.y + area.height {
-                    continue;
-                }
+        let max_box_height = area.height.saturating_sub(2).min(6);
+        if max_box_height < 3 {
+            return;
+        }
+
+        let lines = Self::wrap_text(&desc, (desc_width - 4) as usize);
+        let content_lines = lines.len().min(max_box_height as usize - 2);
+        let box_height = 2 + content_lines as u16;
+        let box_y = start_y.min(bottom - box_height).max(area.y);
+
+        let border_style = Style::default().fg(theme.foreground);
+        let accent_top = format!("╭{}╮", "─".repeat(desc_width.saturating_sub(2) as usize));
+        put(buf, desc_x, box_y, &accent_top, border_style);
 
-                let line_width = desc_width.saturating_sub(4) as usize;
-                let padded_text = format!("{:<width$}", line, width = line_width);
-
-                let line_spans = Line::from(vec![
-                    Span::styled("│ ", Style::default().fg(theme.foreground)),
-                    Span::styled(
-                        padded_text,
-                        Style::default()
-                            .fg(theme.info)
-                            .add_modifier(Modifier::ITALIC | Modifier::BOLD),
-                    ),
-                    Span::styled(" │", Style::default().fg(theme.foreground)),
-                ]);
-
-                buf.set_line(desc_x, y, &line_spans, desc_width);
+        let line_width = desc_width.saturating_sub(4) as usize;
+        for (j, line) in lines.iter().take(content_lines).enumerate() {
+            let padded_text = format!("{:<width$}", line, width = line_width);
+            let line_spans = Line::from(vec![
+                Span::styled("│ ", border_style),
+                Span::styled(padded_text, desc_style),
+                Span::styled(" │", border_style),
+            ]);
+
+            buf.set_line(desc_x, box_y + 1 + j as u16, &line_spans, right - desc_x);
+        }
+
+        let accent_bottom = format!("╰{}╯", "─".repeat(desc_width.saturating_sub(2) as usize));
+        put(buf, desc_x, box_y + box_height - 1, &accent_bottom, border_style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Column and row of the area inside the buffer, to catch drawing left of or above it
+    const OFFSET: u16 = 3;
+
+    fn menu() -> DescriptionMenu {
+        let item = |icon, label: &str, description: &str| DescriptionMenuItem {
+            icon,
+            label: label.to_string(),
+            description: description.to_string(),
+        };
+        DescriptionMenu::new(vec![
+            item('R', "Read partitions", "Dump partitions from the device to a folder"),
+            item('W', "Write partitions", "Flash images from a folder onto the device partitions"),
+            item('E', "Erase partitions", "Erase the selected partitions, this can't be undone"),
+            item('U', "Unlock bootloader", "Patch seccfg so the bootloader reports as unlocked"),
+        ])
+    }
+
+    /// Renders `menu` in a `width` x `height` area of a bigger buffer filled with `#`
+    fn render(menu: &mut DescriptionMenu, width: u16, height: u16) -> (Buffer, Rect) {
+        let area = Rect::new(OFFSET, OFFSET, width, height);
+        let mut buf = Buffer::filled(
+            Rect::new(0, 0, width + 2 * OFFSET, height + 2 * OFFSET),
+            ratatui::buffer::Cell::new("#"),
+        );
+        menu.render(area, &mut buf, &Theme::default());
+        (buf, area)
+    }
+
+    fn text(buf: &Buffer) -> String {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| (area.left()..area.right()).map(|x| buf[(x, y)].symbol()).collect())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn assert_inside(buf: &Buffer, area: Rect) {
+        for y in buf.area.top()..buf.area.bottom() {
+            for x in buf.area.left()..buf.area.right() {
+                if !area.contains((x, y).into()) {
+                    assert_eq!(buf[(x, y)].symbol(), "#", "drawn at {},{}\n{}", x, y, text(buf));
+                }
             }
+        }
+    }
+
+    #[test]
+    fn side_box_at_common_widths() {
+        for width in [60, 80, 120] {
+            let mut menu = menu();
+            menu.next();
+            let (buf, area) = render(&mut menu, width, 20);
+            assert_inside(&buf, area);
+
+            let text = text(&buf);
+            assert!(text.contains("W  Write partitions"), "{}:\n{}", width, text);
+            assert!(text.contains('╭') && text.contains('╯'), "{}:\n{}", width, text);
+            assert!(text.contains("Flash images"), "{}:\n{}", width, text);
+        }
+    }
+
+    #[test]
+    fn narrow_areas_put_the_description_under_the_menu() {
+        let mut menu = menu();
+        let (buf, area) = render(&mut menu, 40, 12);
+        assert_inside(&buf, area);
+
+        let text = text(&buf);
+        assert!(!text.contains('╭'), "{}", text);
+        assert!(text.contains("Dump partitions"), "{}", text);
+    }
 
-            let accent_bottom = format!("╰{}╯", "─".repeat(desc_width.saturating_sub(2) as usize));
-            buf.set_string(
-                desc_x,
-                box_y + box_height - 1,
-                &accent_bottom,
-                Style::default().fg(theme.foreground),
-            );
+    #[test]
+    fn small_areas_stay_inside() {
+        for (width, height) in [(1, 1), (10, 3), (24, 4), (46, 5), (60, 2), (120, 3), (0, 10)] {
+            let mut menu = menu();
+            menu.previous();
+            let (buf, area) = render(&mut menu, width, height);
+            assert_inside(&buf, area);
         }
     }
 }
@@ -3,29 +3,41 @@
     SPDX-FileCopyrightText: 2025 DiabloSat
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::sync::{Arc, Mutex};
+
 use derive_builder::Builder;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph, Widget, Wrap};
-use strum_macros::AsRefStr;
 
 use crate::components::ThemedWidgetRef;
-use crate::themes::Theme;
+use crate::i18n::t;
+use crate::themes::{StyleSlot, Theme};
 
-#[derive(Clone, Copy, AsRefStr, Default)]
+#[derive(Clone, Copy, Default)]
 #[allow(unused)]
 pub enum DialogType {
-    #[strum(serialize = "[!] ERROR")]
     Error,
-    #[strum(serialize = "[i] INFO")]
     Info,
-    #[strum(serialize = "[o] DIALOG")]
     #[default]
     Other,
 }
 
+impl DialogType {
+    /// The dialog title, in the current language
+    pub fn title(&self) -> &'static str {
+        match self {
+            DialogType::Error => t("dialog.error"),
+            DialogType::Info => t("dialog.info"),
+            DialogType::Other => t("dialog.other"),
+        }
+    }
+}
+
+/// A dialog button. Clones share the action, since building a dialog clones its buttons.
+#[derive(Clone)]
 pub struct DialogButton {
     pub title: String,
-    pub action: Box<dyn FnMut() + Send>,
+    pub action: Arc<Mutex<dyn FnMut() + Send>>,
 }
 
 impl DialogButton {
@@ -33,13 +45,7 @@ impl DialogButton {
     where
         F: FnMut() + Send + 'static,
     {
-        Self { title: title.to_string(), action: Box::new(action) }
-    }
-}
-
-impl Clone for DialogButton {
-    fn clone(&self) -> Self {
-        Self { title: self.title.clone(), action: Box::new(|| {}) }
+        Self { title: title.to_string(), action: Arc::new(Mutex::new(action)) }
     }
 }
 
@@ -113,7 +119,7 @@ impl ThemedWidgetRef for Dialog {
 
         let block = Block::default()
             .title(Span::styled(
-                self.dialog_type.as_ref(),
+                self.dialog_type.title(),
                 Style::default().fg(self.colors.title_color),
             ))
             .borders(Borders::ALL)
@@ -138,7 +144,7 @@ impl ThemedWidgetRef for Dialog {
 impl Dialog {
     pub fn press_selected(&mut self) {
         if let Some(button) = self.buttons.get_mut(self.selected) {
-            (button.action)();
+            (button.action.lock().unwrap())();
         }
     }
 
@@ -160,7 +166,7 @@ impl DialogBuilder {
     pub fn error(message: impl Into<String>, theme: &Theme) -> Self {
         let mut builder = DialogBuilder::default();
         builder.dialog_type(DialogType::Error);
-        builder.colors(DialogColors::new(theme.error, theme.highlight));
+        builder.colors(DialogColors::new(theme.error, theme.color(StyleSlot::DialogBackground)));
         builder.message(message);
         builder
     }
@@ -168,7 +174,7 @@ impl DialogBuilder {
     pub fn info(message: impl Into<String>, theme: &Theme) -> Self {
         let mut builder = DialogBuilder::default();
         builder.dialog_type(DialogType::Info);
-        builder.colors(DialogColors::new(theme.info, theme.highlight));
+        builder.colors(DialogColors::new(theme.info, theme.color(StyleSlot::DialogBackground)));
         builder.message(message);
         builder
     }
@@ -176,8 +182,27 @@ impl DialogBuilder {
     pub fn other(message: impl Into<String>, theme: &Theme) -> Self {
         let mut builder = DialogBuilder::default();
         builder.dialog_type(DialogType::Other);
-        builder.colors(DialogColors::new(theme.muted, theme.highlight));
+        builder.colors(DialogColors::new(theme.muted, theme.color(StyleSlot::DialogBackground)));
         builder.message(message);
         builder
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn built_dialog_keeps_button_actions() {
+        let pressed = Arc::new(AtomicBool::new(false));
+        let flag = pressed.clone();
+        let mut builder = DialogBuilder::info("Pick a port", &Theme::default());
+        builder.button(DialogButton::new("1", move || flag.store(true, Ordering::SeqCst)));
+
+        let mut dialog = builder.build().unwrap();
+        dialog.press_selected();
+        assert!(pressed.load(Ordering::SeqCst));
+    }
+}
@@ -10,7 +10,7 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
 
 use crate::components::ThemedWidgetMut;
-use crate::themes::Theme;
+use crate::themes::{StyleSlot, Theme};
 
 #[derive(Clone)]
 pub struct DropdownOption {
@@ -145,7 +145,9 @@ impl ThemedWidgetMut for Dropdown {
             .map(|(i, opt)| {
                 let mut style = Style::default().fg(theme.text).bg(theme.background);
                 if i == self.selected {
-                    style = style.bg(theme.highlight).add_modifier(Modifier::BOLD);
+                    style = style
+                        .bg(theme.color(StyleSlot::DropdownSelection))
+                        .add_modifier(Modifier::BOLD);
                 }
                 Line::from(Span::styled(format!("  {}", opt.label), style))
             })
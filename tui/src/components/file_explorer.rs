@@ -7,7 +7,7 @@ use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use ratatui::Frame;
-use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::prelude::Buffer;
 use ratatui::style::{Color, Modifier, Style};
@@ -19,7 +19,10 @@ use crate::themes::Theme;
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExplorerResult {
     Selected(PathBuf),
+    /// Only the current prompt was dismissed (Esc)
     Cancelled,
+    /// The whole operation requesting the prompt should be unwound (Ctrl+Q)
+    Aborted,
     Pending,
 }
 
@@ -71,6 +74,9 @@ impl FileExplorer {
     pub fn handle_key(&mut self, key: KeyEvent) -> ExplorerResult {
         match key.code {
             KeyCode::Esc => return ExplorerResult::Cancelled,
+            KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return ExplorerResult::Aborted;
+            }
 
             KeyCode::Char(c) if self.is_searchable_char(c) => {
                 self.handle_search_input(c);
@@ -262,9 +268,9 @@ impl FileExplorer {
         self.inner.widget().render_ref(chunks[1], buf);
 
         let help_text = if self.directories_only {
-            " [↑/↓] Nav • [Space] Select Dir • [Esc] Cancel "
+            " [↑/↓] Nav • [Space] Select Dir • [Esc] Cancel • [Ctrl+Q] Abort "
         } else {
-            " [↑/↓] Nav • [Enter] Select • [Esc] Cancel "
+            " [↑/↓] Nav • [Enter] Select • [Esc] Cancel • [Ctrl+Q] Abort "
         };
 
         let help = Paragraph::new(help_text)
@@ -2,16 +2,17 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use human_bytes::human_bytes;
+use penumbra::api::{ConnStats, FlashPhase, PhaseTracker, Progress};
 use ratatui::prelude::{Buffer, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Paragraph, WidgetRef};
 
 use crate::components::ThemedWidgetRef;
-use crate::themes::Theme;
+use crate::themes::{StyleSlot, Theme};
 
 #[derive(Debug, Clone)]
 pub enum ProgressMode {
@@ -25,6 +26,12 @@ pub struct ProgressBar {
     written_bytes: u64,
     message: String,
     start_time: Option<Instant>,
+    rate_limit: Option<u64>,
+    /// Phase timing of the device flash operations, the speed shown is the one of
+    /// the running phase rather than the overall average
+    phases: Option<PhaseTracker>,
+    /// Transfer counters of the connection, the retries are shown once there are some
+    stats: Option<ConnStats>,
 }
 
 impl ProgressBar {
@@ -35,10 +42,33 @@ impl ProgressBar {
             written_bytes: 0,
             message: String::from("No active operation"),
             start_time: None,
+            rate_limit: None,
+            phases: None,
+            stats: None,
         }
     }
 
+    /// Shows the configured transfer rate limit next to the speed
+    pub fn set_rate_limit(&mut self, rate: Option<u64>) {
+        self.rate_limit = rate;
+    }
+
+    pub fn set_phases(&mut self, phases: Option<PhaseTracker>) {
+        self.phases = phases;
+    }
+
+    pub fn set_stats(&mut self, stats: Option<ConnStats>) {
+        self.stats = stats;
+    }
+
     pub fn start(&mut self, total_bytes: u64, message: impl Into<String>) {
+        // Drop the phases of previous operations
+        if let Some(phases) = &self.phases {
+            phases.take();
+        }
+        if let Some(stats) = &self.stats {
+            stats.reset();
+        }
         self.mode = ProgressMode::Active;
         self.total_bytes = total_bytes;
         self.written_bytes = 0;
@@ -60,30 +90,45 @@ impl ProgressBar {
         }
     }
 
-    pub fn finish(&mut self) {
+    /// Time spent in each phase of the operation, e.g. "erase 12s, write 40s @ 21.0 MB/s".
+    /// Meant to be called before [`ProgressBar::finish`].
+    pub fn phase_summary(&self) -> Option<String> {
+        let stats = self.phases.as_ref()?.take();
+        (!stats.is_empty()).then(|| stats.to_string())
+    }
+
+    /// Resets the bar, returning how long the operation took
+    pub fn finish(&mut self) -> Option<Duration> {
         self.mode = ProgressMode::Idle;
         self.total_bytes = 0;
         self.written_bytes = 0;
         self.message = String::from("No active operation");
-        self.start_time = None;
+        self.start_time.take().map(|start| start.elapsed())
     }
 
-    fn ratio(&self) -> f64 {
-        if self.total_bytes == 0 {
-            0.0
-        } else {
-            self.written_bytes as f64 / self.total_bytes as f64
-        }
+    /// The running operation as seen by the core, which computes its rate and ETA
+    fn snapshot(&self) -> Option<Progress> {
+        let started = self.start_time?;
+        let mut progress = Progress::new(self.written_bytes, self.total_bytes, started);
+        progress.phase = self.phases.as_ref().and_then(|p| p.current());
+        Some(progress)
     }
 
-    fn speed(&self) -> f64 {
-        match self.start_time {
-            Some(start) => {
-                let elapsed = start.elapsed().as_secs_f64();
-                if elapsed > 0.0 { self.written_bytes as f64 / elapsed } else { 0.0 }
-            }
-            None => 0.0,
+    fn speed(&self, progress: Option<&Progress>) -> f64 {
+        if let Some(rate) = self.phases.as_ref().and_then(|p| p.rate()) {
+            return rate;
         }
+
+        progress.and_then(|p| p.rate()).unwrap_or(0.0)
+    }
+}
+
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
     }
 }
 
@@ -110,25 +155,58 @@ impl ThemedWidgetRef for ProgressBar {
             }
 
             ProgressMode::Active => {
+                let progress = self.snapshot();
+                let ratio = progress.as_ref().map_or(0.0, |p| p.fraction());
                 let bar_width = area.width.saturating_sub(6) as usize;
-                let filled = (self.ratio() * bar_width as f64).round() as usize;
+                let filled = (ratio * bar_width as f64).round() as usize;
                 let empty = bar_width.saturating_sub(filled);
-                let percent = (self.ratio() * 100.0).round() as u8;
-
-                let bar = format!("{}{} {:>3}%", "█".repeat(filled), "░".repeat(empty), percent);
+                let percent = (ratio * 100.0).round() as u8;
 
                 let written = human_bytes(self.written_bytes as f64);
                 let total = human_bytes(self.total_bytes as f64);
-                let speed = human_bytes(self.speed());
+                let phase = progress.as_ref().and_then(|p| p.phase);
+                let speed = human_bytes(self.speed(progress.as_ref()));
+                let speed = match (phase, self.rate_limit) {
+                    (Some(FlashPhase::Erase), _) => "Erasing...".to_string(),
+                    (_, Some(limit)) => {
+                        format!("{speed}/s (limit {}/s)", human_bytes(limit as f64))
+                    }
+                    (_, None) => format!("{speed}/s"),
+                };
+
+                let retries = self.stats.as_ref().map_or(0, |s| s.snapshot().retries);
+                let mut stats_line = vec![
+                    Span::raw(format!("{written} / {total}")),
+                    Span::raw("  •  "),
+                    Span::raw(speed),
+                    Span::raw("  •  "),
+                    Span::raw(match progress.and_then(|p| p.eta()) {
+                        Some(eta) => format!("ETA {}", format_eta(eta)),
+                        None => "ETA -".to_string(),
+                    }),
+                ];
+                if retries > 0 {
+                    stats_line.push(Span::raw("  •  "));
+                    stats_line.push(Span::styled(
+                        format!("retries: {retries}"),
+                        Style::default().fg(theme.warning),
+                    ));
+                }
 
                 let lines = vec![
                     Line::from(Span::styled(&self.message, style)),
-                    Line::from(Span::styled(bar, style)),
                     Line::from(vec![
-                        Span::raw(format!("{written} / {total}")),
-                        Span::raw("  •  "),
-                        Span::raw(format!("{speed}/s")),
+                        Span::styled(
+                            "█".repeat(filled),
+                            Style::default().fg(theme.color(StyleSlot::ProgressFilled)),
+                        ),
+                        Span::styled(
+                            "░".repeat(empty),
+                            Style::default().fg(theme.color(StyleSlot::ProgressEmpty)),
+                        ),
+                        Span::styled(format!(" {:>3}%", percent), style),
                     ]),
+                    Line::from(stats_line),
                 ];
 
                 Paragraph::new(lines).render_ref(area, buf);
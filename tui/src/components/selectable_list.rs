@@ -11,7 +11,7 @@ use ratatui::style::{Modifier, Style};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, StatefulWidgetRef};
 
 use crate::components::ThemedWidgetMut;
-use crate::themes::Theme;
+use crate::themes::{StyleSlot, Theme};
 
 #[derive(PartialEq, Builder, Clone, Default)]
 pub struct ListItemEntry {
@@ -45,8 +45,10 @@ pub struct SelectableList {
     pub state: ListState,
     #[builder(setter(custom))]
     pub highlight_symbol: String,
+    /// Whether items have a checkbox and can be toggled. The checkboxes are always
+    /// drawn, so checked items stay visible whether or not the list has focus.
     #[builder(default)]
-    pub toggled: bool,
+    pub checkboxes: bool,
     #[builder(default)]
     pub borders: Borders,
     #[builder(default)]
@@ -63,13 +65,14 @@ impl ThemedWidgetMut for SelectableList {
                 let mut style = item.style.unwrap_or_else(|| Style::default().fg(theme.text));
 
                 if Some(i) == self.selected_index() {
-                    style = style.fg(theme.accent).add_modifier(Modifier::BOLD)
+                    style =
+                        style.fg(theme.color(StyleSlot::ListSelection)).add_modifier(Modifier::BOLD)
                 }
 
                 let label = {
                     let mut parts = Vec::new();
 
-                    if self.toggled {
+                    if self.checkboxes {
                         parts.push(if item.toggle { "[x]" } else { "[ ]" }.to_string());
                     }
 
@@ -122,7 +125,7 @@ impl SelectableList {
 impl SelectableList {
     /// Select the currently highlighted item
     pub fn toggle_selected(&mut self) {
-        if self.toggled
+        if self.checkboxes
             && let Some(i) = self.selected_index()
             && let Some(item) = self.items.get_mut(i)
         {
@@ -136,6 +139,14 @@ impl SelectableList {
         }
     }
 
+    /// Sets the toggle state of the item identified by `value`
+    pub fn set_toggled(&mut self, value: &str, toggled: bool) {
+        if let Some(item) = self.items.iter_mut().find(|item| item.value.as_deref() == Some(value))
+        {
+            item.toggle = toggled;
+        }
+    }
+
     pub fn checked_items(&self) -> Vec<&ListItemEntry> {
         self.items.iter().filter(|item| item.toggle).collect()
     }
@@ -3,21 +3,68 @@
     SPDX-FileCopyrightText: 2026 Shomy
 */
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use anyhow::Result;
 use config::{Config, Environment, File};
+use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
+use crate::cli::helpers::HookConfig;
+use crate::i18n::AUTO_LANGUAGE;
+use crate::notify::{DEFAULT_NOTIFY_THRESHOLD, NotifyMode};
+use crate::themes::StyleSlot;
+
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct AntumbraConfig {
     pub theme: String,
+    /// Per-component color overrides applied on top of the selected theme.
+    /// Colors can be named ("red") or hex ("#ff0000").
+    #[serde(default)]
+    pub theme_overrides: HashMap<StyleSlot, String>,
+    /// Flash transfer rate limit in bytes per second, 0 means unlimited
+    #[serde(default)]
+    pub rate_limit: u64,
+    /// Whether written partitions are read back and verified
+    #[serde(default)]
+    pub verify_writes: bool,
+    /// How to notify when a long operation finishes
+    #[serde(default)]
+    pub notify: NotifyMode,
+    /// Operations shorter than this many seconds don't notify
+    #[serde(default = "default_notify_threshold")]
+    pub notify_threshold: u64,
+    /// Language code of the interface, "auto" follows the system locale
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Commands run once an operation completes
+    #[serde(default)]
+    pub hooks: HookConfig,
+}
+
+fn default_notify_threshold() -> u64 {
+    DEFAULT_NOTIFY_THRESHOLD.as_secs()
+}
+
+fn default_language() -> String {
+    AUTO_LANGUAGE.to_string()
 }
 
 impl Default for AntumbraConfig {
     fn default() -> Self {
-        Self { theme: "system".to_string() }
+        Self {
+            theme: "system".to_string(),
+            theme_overrides: HashMap::new(),
+            rate_limit: 0,
+            verify_writes: false,
+            notify: NotifyMode::default(),
+            notify_threshold: default_notify_threshold(),
+            language: default_language(),
+            hooks: HookConfig::default(),
+        }
     }
 }
 
@@ -54,6 +101,20 @@ impl AntumbraConfig {
         Ok(())
     }
 
+    /// Parsed theme overrides, invalid colors are skipped
+    pub fn theme_overrides(&self) -> HashMap<StyleSlot, Color> {
+        self.theme_overrides
+            .iter()
+            .filter_map(|(slot, value)| match Color::from_str(value) {
+                Ok(color) => Some((*slot, color)),
+                Err(_) => {
+                    log::warn!("Invalid color '{}' for theme slot {:?}", value, slot);
+                    None
+                }
+            })
+            .collect()
+    }
+
     fn get_path() -> Option<PathBuf> {
         dirs::config_dir().map(|p| p.join("antumbra/config.toml"))
     }
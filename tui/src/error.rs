@@ -3,7 +3,7 @@
     SPDX-FileCopyrightText: 2025 Shomy
 */
 use anyhow::Error as AnyError;
-use penumbra::error::Error as PenumbraError;
+use penumbra::api::Error as PenumbraError;
 
 // Ugly hack to convert Penumbra Error into anyhow::Error.
 // This is needed because PenumbraError does not implement std::error::Error,
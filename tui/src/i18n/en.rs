@@ -0,0 +1,251 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! English strings, the baseline every other language falls back to.
+//! New user-facing strings get their key here first.
+
+pub const STRINGS: &[(&str, &str)] = &[
+    // Common
+    ("common.yes", "Yes"),
+    ("common.no", "No"),
+    ("common.unknown", "Unknown"),
+    // Dialogs
+    ("dialog.error", "[!] ERROR"),
+    ("dialog.info", "[i] INFO"),
+    ("dialog.other", "[o] DIALOG"),
+    ("dialog.ok", "OK"),
+    ("dialog.cancel", "Cancel"),
+    // Footer
+    ("footer.busy", "[Esc] Cancel"),
+    ("footer.menu", "[↑↓] Navigate   [Enter] Select   [R] Refresh   [Esc] Back"),
+    (
+        "footer.partitions",
+        "[↑↓] Navigate   [X] Toggle   [C] Clear   [P] Presets   [S] Save preset   \
+         [Enter] Select   [Esc] Back",
+    ),
+    // Device page
+    ("action.unlock_bootloader", "Unlock Bootloader"),
+    ("action.lock_bootloader", "Lock Bootloader"),
+    ("action.read_partition", "Read Partition"),
+    ("action.dump_preloader", "Dump Preloader"),
+    ("action.write_partition", "Write Partition"),
+    ("action.restore_primary_gpt", "Restore Primary GPT"),
+    ("action.restore_secondary_gpt", "Restore Secondary GPT"),
+    ("action.refresh_info", "Refresh Device Info"),
+    ("action.support_bundle", "Create Support Bundle"),
+    ("action.reboot_recovery", "Reboot to Recovery"),
+    ("action.back_to_menu", "Back to Menu"),
+    ("device.disconnected", "Disconnected"),
+    ("device.connecting", "Connecting…"),
+    ("device.connected", "Connected"),
+    ("device.gpt_mismatch", "GPT MISMATCH"),
+    ("device.sgpt_corrupted", "SGPT CORRUPTED"),
+    ("device.actions", "ACTIONS"),
+    ("device.info", "DEVICE INFO"),
+    ("device.progress", "PROGRESS"),
+    ("device.storage_title", "STORAGE"),
+    ("device.waiting", "Waiting for device connection…"),
+    ("device.waiting_hint", "(Plug device in BOOTROM or Preloader mode)"),
+    ("device.hw_code", "HW Code"),
+    ("device.sbc", "Secure Boot (SBC)"),
+    ("device.sla", "Serial Link Auth (SLA)"),
+    ("device.daa", "Download Agent Auth (DAA)"),
+    ("device.da_extensions", "DA Extensions"),
+    ("device.bootloader", "Bootloader"),
+    ("device.locked", "Locked"),
+    ("device.unlocked", "Unlocked"),
+    ("device.storage", "Storage"),
+    ("device.flash", "Flash"),
+    ("device.flash_fw", "Flash Firmware"),
+    ("device.flash_serial", "Flash Serial"),
+    ("device.read_only", "{size} (read-only)"),
+    ("device.partitioned", "Partitioned"),
+    ("device.choose_port", "Several MTK devices are connected, pick the one to use:"),
+    ("device.da_notice", "DA: {message}"),
+    ("device.partitioned_of", "{used} of {total} ({percent}%)"),
+    ("device.gpt_attributes_only", "(types, GUIDs or attributes)"),
+    ("gpt.partition", "Partition"),
+    ("gpt.primary", "Primary"),
+    ("gpt.secondary", "Secondary"),
+    ("error.not_connected", "Device not connected"),
+    ("error.no_callback", "No callback registered"),
+    ("error.init_failed", "Init failed: {error}"),
+    ("error.da_mode_failed", "DA Mode failed: {error}"),
+    ("error.build_failed", "Build failed: {error}"),
+    ("error.device_unplugged", "The device was disconnected, waiting for it again"),
+    ("error.port_open_failed", "Can't open {port}, was it unplugged?"),
+    ("error.partitions_failed", "Failed to read the partition table: {error}"),
+    ("preset.save_as", "Save preset as"),
+    ("preset.load", "Load preset"),
+    ("preset.select_first", "Select partitions with [X] first"),
+    ("preset.none_saved", "No presets saved"),
+    ("preset.saved", "Saved preset '{name}'"),
+    ("preset.save_failed", "Failed to save preset '{name}': {error}"),
+    ("preset.loaded", "Loaded preset '{name}' ({count} partitions)"),
+    ("preset.loaded_missing", "Loaded preset '{name}', not on this device: {missing}"),
+    ("op.failed", "Operation failed."),
+    ("op.cancelling", "Cancelling at the next chunk..."),
+    ("op.cancelled", "Operation cancelled."),
+    ("op.aborted", "Operation aborted."),
+    ("op.unlocking", "Unlocking bootloader..."),
+    ("op.unlocked", "Bootloader unlocked."),
+    ("op.already_unlocked", "Bootloader is already unlocked."),
+    ("op.locking", "Locking bootloader..."),
+    ("op.locked", "Bootloader locked."),
+    ("op.already_locked", "Bootloader is already locked."),
+    ("op.reading", "Reading partitions..."),
+    ("op.reading_partition", "Reading partition '{name}'..."),
+    ("op.read_complete", "Partition read complete."),
+    ("op.writing", "Writing partitions..."),
+    ("op.flashing_partition", "Flashing partition '{name}'..."),
+    ("op.write_complete", "Partition write complete."),
+    ("op.restoring_gpt", "Restoring GPT from {source}..."),
+    ("op.gpt_restored", "GPT restored"),
+    ("op.refreshing", "Refreshing device information..."),
+    ("op.refreshed", "Device information refreshed."),
+    ("op.collecting_info", "Collecting device information..."),
+    ("op.bundle_written", "Support bundle written to {path}"),
+    ("op.dumping_preloader", "Dumping preloader..."),
+    ("op.preloader_dumped", "Preloader written to {path}"),
+    ("op.rebooting", "Rebooting the device..."),
+    ("op.rebooted", "Device rebooted, it can be disconnected."),
+    ("explorer.dump_dir", "Output dump directory"),
+    ("explorer.partition_file", "Select file for partition '{name}'"),
+    ("explorer.bundle_dir", "Support bundle directory"),
+    ("explorer.preloader_dir", "Preloader output directory"),
+    // Options
+    ("options.language", "Language"),
+    (
+        "options.language.description",
+        "Language of the interface and CLI help. Auto follows the system locale.",
+    ),
+    ("options.language.auto", "Auto"),
+    // CLI commands
+    ("cmd.console.about", "Interactive protocol console, for development."),
+    (
+        "cmd.console.long_about",
+        "Open an interactive console sending raw XFlash commands and devctrl codes, or XML
+        commands by name, and showing the decoded responses and status codes.
+        Commands can be replayed from a file with --script or `replay`. Meant for adding
+        support for new commands: a wrong command can leave the DA waiting for data.",
+    ),
+    ("cmd.bridge.about", "Expose the device attached to this machine to antumbra on another one."),
+    (
+        "cmd.bridge.long_about",
+        "Serve the device plugged into this machine over TCP, so antumbra running elsewhere can use
+        it with --bridge HOST:PORT or the PENUMBRA_BRIDGE variable.
+        The bridge has no authentication: only listen on trusted networks.",
+    ),
+    ("cmd.download.about", "Download a file to a specified partition on the device."),
+    (
+        "cmd.download.long_about",
+        "Download (flash) a file to a specificed partition on the device.
+        Use this command for flashing stock firmware on locked bootloader, or the device
+        will return write data not allowed error.",
+    ),
+    ("cmd.erase.about", "Erase a partition on the device."),
+    ("cmd.erase.long_about", "Erase the specified partition on the device."),
+    ("cmd.format.about", "Format a partition on the device."),
+    ("cmd.format.long_about", "Format (erase) the specified partition on the device."),
+    ("cmd.info.about", "Display information about the connected device and its storage."),
+    (
+        "cmd.info.long_about",
+        "Display information about the connected device, along with the storage regions \
+         (boot areas, RPMB, user area...) reported by the DA.",
+    ),
+    ("cmd.patch.about", "Patch bytes of a partition in place."),
+    (
+        "cmd.patch.long_about",
+        "Overwrite a few bytes at the given offset of a partition, without rewriting the whole \
+         partition. Use --dry-run to only show the bytes that would change.",
+    ),
+    ("cmd.peek.about", "Peek memory."),
+    (
+        "cmd.peek.long_about",
+        "Read memory from the specified address and length. DA Extensions must be loaded for this \
+         command to work.",
+    ),
+    ("cmd.pgpt.about", "Display the partition table of the connected device."),
+    (
+        "cmd.pgpt.long_about",
+        "Display the partition table of the connected device.
+        `check` verifies both the primary and secondary GPT, `diff` compares them,
+        and `repair --from <primary|secondary>` copies one over the other, adjusting
+        the header for its new location.",
+    ),
+    (
+        "cmd.readall.about",
+        "Read all partitions from the device and save them to the specified output directory.",
+    ),
+    (
+        "cmd.readall.long_about",
+        "Read all partitions from the device and save them to the specified output directory,
+        skipping any partitions listed in the skip option.",
+    ),
+    ("cmd.readflash.about", "Read a partition from the device and save it to a file."),
+    (
+        "cmd.readflash.long_about",
+        "Read a specified partition from the device and save it to a file with the given output filename.
+        With --preset, read all the partitions of a saved preset into a directory instead,
+        skipping the ones missing on the device.",
+    ),
+    ("cmd.reboot.about", "Reboot the device into a specified mode."),
+    (
+        "cmd.reboot.long_about",
+        "Reboot the device into a specified mode. Recovery works on any DA, through the 'misc'
+        partition. On XFlash, Normal, HomeScreen, Fastboot and Meta modes are supported.
+        On XML, also the Test mode is available.",
+    ),
+    ("cmd.seccfg.about", "Lock or unlock the seccfg partition on the device."),
+    (
+        "cmd.seccfg.long_about",
+        "Lock or unlock the seccfg partition on the device.
+        This command only work when the device is in DA mode and vulnerable to an exploit or unfused,
+        because it requires DA extensions to be loaded.",
+    ),
+    ("cmd.shutdown.about", "Shutdown the device."),
+    ("cmd.shutdown.long_about", "Shutdown the device through DA mode."),
+    ("cmd.state.about", "Manage the device state saved between commands."),
+    (
+        "cmd.state.long_about",
+        "Manage the device state saved between commands, which lets a command reuse the DA mode
+        entered by the previous one. The state is discarded by itself when the device shows up
+        on another port, another DA file is used or it's older than --state-ttl.",
+    ),
+    ("cmd.support_bundle.about", "Collect device information for support requests."),
+    (
+        "cmd.support_bundle.long_about",
+        "Collect device information, storage layout, partition table, DA file metadata and the \
+         last log lines into a zip archive, to be attached to support requests. Nothing is \
+         written to the device. Identifiers are hashed unless --include-ids is given.",
+    ),
+    ("cmd.trace.about", "Print a trace recorded with --trace."),
+    (
+        "cmd.trace.long_about",
+        "Print the transfers recorded with --trace, one per line, with the start of their data
+        in hex and the DA commands and headers they carry.",
+    ),
+    ("cmd.upload.about", "Upload a partition from the device to the host."),
+    (
+        "cmd.upload.long_about",
+        "Upload (readback) a specificed partition on the device to a file on the host.
+        Use this command for reading back if the `read` command fails.",
+    ),
+    ("cmd.writeflash.about", "Write a file to a specified partition on the device."),
+    (
+        "cmd.writeflash.long_about",
+        "Write (flash) a file to a specificed partition on the device.
+        If this command fails, use `download` instead.",
+    ),
+    ("cmd.wait.about", "Wait for the device to reach a given state."),
+    (
+        "cmd.wait.long_about",
+        "Wait until a device shows up in BROM, Preloader or DA mode, or until it is unplugged,
+        without connecting to it. Exits with an error if the timeout expires.
+        For `gone`, the device is told apart from others by the port it was last connected to.",
+    ),
+    ("cmd.xflash.about", "XFlash-specific commands."),
+    ("cmd.xflash.long_about", "Commands specific to XFlash / V5 devices."),
+];
@@ -0,0 +1,132 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+
+//! Spanish strings. Missing keys fall back to English.
+
+pub const STRINGS: &[(&str, &str)] = &[
+    // Common
+    ("common.yes", "Sí"),
+    ("common.no", "No"),
+    ("common.unknown", "Desconocido"),
+    // Dialogs
+    ("dialog.error", "[!] ERROR"),
+    ("dialog.info", "[i] INFO"),
+    ("dialog.other", "[o] DIÁLOGO"),
+    ("dialog.ok", "Aceptar"),
+    ("dialog.cancel", "Cancelar"),
+    // Footer
+    ("footer.busy", "[Esc] Cancelar"),
+    ("footer.menu", "[↑↓] Navegar   [Enter] Seleccionar   [R] Actualizar   [Esc] Volver"),
+    (
+        "footer.partitions",
+        "[↑↓] Navegar   [X] Marcar   [C] Limpiar   [P] Preajustes   [S] Guardar preajuste   \
+         [Enter] Seleccionar   [Esc] Volver",
+    ),
+    // Device page
+    ("action.unlock_bootloader", "Desbloquear bootloader"),
+    ("action.lock_bootloader", "Bloquear bootloader"),
+    ("action.read_partition", "Leer partición"),
+    ("action.dump_preloader", "Extraer preloader"),
+    ("action.write_partition", "Escribir partición"),
+    ("action.restore_primary_gpt", "Restaurar GPT primaria"),
+    ("action.restore_secondary_gpt", "Restaurar GPT secundaria"),
+    ("action.refresh_info", "Actualizar información"),
+    ("action.support_bundle", "Crear paquete de soporte"),
+    ("action.reboot_recovery", "Reiniciar en recovery"),
+    ("action.back_to_menu", "Volver al menú"),
+    ("device.disconnected", "Desconectado"),
+    ("device.connecting", "Conectando…"),
+    ("device.connected", "Conectado"),
+    ("device.gpt_mismatch", "GPT NO COINCIDEN"),
+    ("device.sgpt_corrupted", "SGPT DAÑADA"),
+    ("device.actions", "ACCIONES"),
+    ("device.info", "INFORMACIÓN DEL DISPOSITIVO"),
+    ("device.progress", "PROGRESO"),
+    ("device.storage_title", "ALMACENAMIENTO"),
+    ("device.waiting", "Esperando la conexión del dispositivo…"),
+    ("device.waiting_hint", "(Conecta el dispositivo en modo BOOTROM o Preloader)"),
+    ("device.bootloader", "Bootloader"),
+    ("device.locked", "Bloqueado"),
+    ("device.unlocked", "Desbloqueado"),
+    ("device.storage", "Almacenamiento"),
+    ("device.flash", "Memoria flash"),
+    ("device.flash_fw", "Firmware de la flash"),
+    ("device.flash_serial", "Serie de la flash"),
+    ("device.read_only", "{size} (solo lectura)"),
+    ("device.partitioned", "Particionado"),
+    ("device.choose_port", "Hay varios dispositivos MTK conectados, elige cuál usar:"),
+    ("device.da_notice", "DA: {message}"),
+    ("device.partitioned_of", "{used} de {total} ({percent}%)"),
+    ("gpt.partition", "Partición"),
+    ("gpt.primary", "Primaria"),
+    ("gpt.secondary", "Secundaria"),
+    ("error.not_connected", "Dispositivo no conectado"),
+    ("error.init_failed", "Fallo al inicializar: {error}"),
+    ("error.build_failed", "Fallo al crear el dispositivo: {error}"),
+    ("error.device_unplugged", "El dispositivo se ha desconectado, esperando a que vuelva"),
+    ("error.port_open_failed", "No se puede abrir {port}, ¿se ha desconectado?"),
+    ("error.partitions_failed", "No se pudo leer la tabla de particiones: {error}"),
+    ("preset.save_as", "Guardar preajuste como"),
+    ("preset.load", "Cargar preajuste"),
+    ("preset.select_first", "Marca primero las particiones con [X]"),
+    ("preset.none_saved", "No hay preajustes guardados"),
+    ("preset.saved", "Preajuste '{name}' guardado"),
+    ("preset.save_failed", "No se pudo guardar el preajuste '{name}': {error}"),
+    ("preset.loaded", "Preajuste '{name}' cargado ({count} particiones)"),
+    (
+        "preset.loaded_missing",
+        "Preajuste '{name}' cargado, no están en este dispositivo: {missing}",
+    ),
+    ("op.failed", "La operación falló."),
+    ("op.cancelling", "Cancelando en el siguiente bloque..."),
+    ("op.cancelled", "Operación cancelada."),
+    ("op.aborted", "Operación cancelada."),
+    ("op.unlocking", "Desbloqueando el bootloader..."),
+    ("op.unlocked", "Bootloader desbloqueado."),
+    ("op.already_unlocked", "El bootloader ya está desbloqueado."),
+    ("op.locking", "Bloqueando el bootloader..."),
+    ("op.locked", "Bootloader bloqueado."),
+    ("op.already_locked", "El bootloader ya está bloqueado."),
+    ("op.reading", "Leyendo particiones..."),
+    ("op.reading_partition", "Leyendo la partición '{name}'..."),
+    ("op.read_complete", "Lectura de particiones completada."),
+    ("op.writing", "Escribiendo particiones..."),
+    ("op.flashing_partition", "Flasheando la partición '{name}'..."),
+    ("op.write_complete", "Escritura de particiones completada."),
+    ("op.restoring_gpt", "Restaurando la GPT desde {source}..."),
+    ("op.gpt_restored", "GPT restaurada"),
+    ("op.refreshing", "Actualizando la información del dispositivo..."),
+    ("op.refreshed", "Información del dispositivo actualizada."),
+    ("op.collecting_info", "Recopilando información del dispositivo..."),
+    ("op.bundle_written", "Paquete de soporte guardado en {path}"),
+    ("op.dumping_preloader", "Extrayendo preloader..."),
+    ("op.preloader_dumped", "Preloader guardado en {path}"),
+    ("op.rebooting", "Reiniciando el dispositivo..."),
+    ("op.rebooted", "Dispositivo reiniciado, ya se puede desconectar."),
+    ("explorer.dump_dir", "Directorio de salida"),
+    ("explorer.partition_file", "Selecciona el archivo para la partición '{name}'"),
+    ("explorer.bundle_dir", "Directorio del paquete de soporte"),
+    ("explorer.preloader_dir", "Directorio de salida del preloader"),
+    // Options
+    ("options.language", "Idioma"),
+    (
+        "options.language.description",
+        "Idioma de la interfaz y de la ayuda de la CLI. Automático sigue el idioma del sistema.",
+    ),
+    ("options.language.auto", "Automático"),
+    // CLI commands
+    ("cmd.bridge.about", "Expone el dispositivo conectado a este equipo a antumbra en otro."),
+    ("cmd.download.about", "Escribe un archivo en la partición indicada del dispositivo."),
+    ("cmd.upload.about", "Copia una partición del dispositivo al equipo."),
+    ("cmd.erase.about", "Borra una partición del dispositivo."),
+    ("cmd.format.about", "Formatea una partición del dispositivo."),
+    ("cmd.info.about", "Muestra información sobre el dispositivo conectado y su almacenamiento."),
+    ("cmd.pgpt.about", "Muestra la tabla de particiones del dispositivo conectado."),
+    ("cmd.reboot.about", "Reinicia el dispositivo en el modo indicado."),
+    ("cmd.shutdown.about", "Apaga el dispositivo."),
+    ("cmd.state.about", "Gestiona el estado del dispositivo guardado entre comandos."),
+    ("cmd.trace.about", "Muestra una traza grabada con --trace."),
+    ("cmd.wait.about", "Espera a que el dispositivo llegue al estado indicado."),
+];
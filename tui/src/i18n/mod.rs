@@ -0,0 +1,112 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+mod en;
+mod es;
+
+#[cfg(feature = "tui")]
+use std::fmt::Display;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Deserialize;
+
+/// Setting value picking the language from the system locale
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// A translation of the user-facing strings, as `(key, text)` pairs.
+/// Only what users read goes through the catalog: logs and protocol messages stay in English.
+pub struct Language {
+    /// ISO 639-1 code, as found in locales (`es` in `es_ES.UTF-8`)
+    pub code: &'static str,
+    /// Name of the language, in that language
+    #[cfg(feature = "tui")]
+    pub name: &'static str,
+    strings: &'static [(&'static str, &'static str)],
+}
+
+impl Language {
+    fn get(&self, key: &str) -> Option<&'static str> {
+        self.strings.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+    }
+}
+
+/// Available languages, English first as it's the fallback for missing keys
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        #[cfg(feature = "tui")]
+        name: "English",
+        strings: en::STRINGS,
+    },
+    Language {
+        code: "es",
+        #[cfg(feature = "tui")]
+        name: "Español",
+        strings: es::STRINGS,
+    },
+];
+
+/// Index of the current language in [`LANGUAGES`]
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Switches to the language with the given code, or detects it from the system locale
+/// for [`AUTO_LANGUAGE`]. Unknown languages fall back to English.
+pub fn set_language(code: &str) {
+    let code = match code {
+        "" | AUTO_LANGUAGE => system_language(),
+        code => Some(code.to_string()),
+    };
+
+    let index = code
+        .and_then(|code| LANGUAGES.iter().position(|l| l.code.eq_ignore_ascii_case(&code)))
+        .unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+/// Sets the language from the `language` setting of the config file, so the CLI
+/// help is translated as well. Called before parsing the arguments.
+pub fn init() {
+    #[derive(Deserialize, Default)]
+    struct ConfigFile {
+        #[serde(default)]
+        language: String,
+    }
+
+    let config = dirs::config_dir()
+        .map(|p| p.join("antumbra/config.toml"))
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| toml::from_str::<ConfigFile>(&data).ok())
+        .unwrap_or_default();
+
+    set_language(&config.language);
+}
+
+/// Returns the translation of `key` in the current language.
+/// Missing translations fall back to English, and unknown keys to the key itself.
+pub fn t(key: &'static str) -> &'static str {
+    LANGUAGES[CURRENT.load(Ordering::Relaxed)]
+        .get(key)
+        .or_else(|| LANGUAGES[0].get(key))
+        .unwrap_or(key)
+}
+
+/// Like [`t`], replacing the `{name}` placeholders of the translation with `args`.
+#[cfg(feature = "tui")]
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(key).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// The language of the system locale, from the usual POSIX variables.
+/// `C` and `POSIX` locales have no language.
+fn system_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .map(|locale| locale.split(['_', '.', '@', '-']).next().unwrap_or_default().to_lowercase())
+        .filter(|lang| !lang.is_empty() && lang != "c" && lang != "posix")
+}
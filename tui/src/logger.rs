@@ -2,6 +2,7 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Write;
 use std::sync::{Arc, Mutex};
@@ -15,6 +16,24 @@ pub const LOGGER_PREIX: &str = "Antumbra";
 pub const INFO_SYMBOL: &str = "✦";
 pub const WARN_SYMBOL: &str = "✧";
 pub const ERROR_SYMBOL: &str = "❂";
+/// How many of the last log lines are kept in memory, for support bundles
+pub const RECENT_LOG_LINES: usize = 500;
+
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps `line` among the last [`RECENT_LOG_LINES`], dropping the oldest one.
+fn remember(line: String) {
+    let mut recent = RECENT_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == RECENT_LOG_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(line);
+}
+
+/// The last log lines of this run, whatever was printed or written to the log file.
+pub fn recent_log() -> Vec<String> {
+    RECENT_LOG.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+}
 
 pub fn init_logger(tui_mode: bool, verbose: bool) {
     let mut builder = env_logger::Builder::new();
@@ -32,6 +51,8 @@ pub fn init_logger(tui_mode: bool, verbose: bool) {
     };
 
     builder.format(move |buf: &mut Formatter, record: &Record| {
+        remember(format!("[{}] {}", record.level(), record.args()));
+
         if tui_mode {
             if verbose
                 && record.level() == Level::Debug
@@ -22,7 +22,7 @@ macro_rules! error_dialog {
     ($ctx:expr, $message:expr) => {
         $ctx.dialog = Some({
             let mut builder = crate::components::DialogBuilder::error($message, &$ctx.theme);
-            let button = crate::components::DialogButton::new("OK", || {});
+            let button = crate::components::DialogButton::new(crate::i18n::t("dialog.ok"), || {});
             builder.button(button);
             builder.build().unwrap()
         })
@@ -53,7 +53,11 @@ macro_rules! dialog {
 // Quick OK-only dialog
 macro_rules! ok_dialog {
     ($ctx:expr, $dialog_type:ident, $message:expr) => {
-        $ctx.dialog = Some($dialog_type!($ctx, $message, DialogButton::new("OK", || {})))
+        $ctx.dialog = Some($dialog_type!(
+            $ctx,
+            $message,
+            DialogButton::new(crate::i18n::t("dialog.ok"), || {})
+        ))
     };
 }
 
@@ -65,8 +69,8 @@ macro_rules! confirm_dialog {
     ($ctx:expr, $message:expr, $on_confirm:expr, $on_cancel:expr) => {
         $ctx.dialog = Some({
             let mut builder = DialogBuilder::info($message);
-            builder.button(DialogButton::new("OK", $on_confirm));
-            builder.button(DialogButton::new("Cancel", $on_cancel));
+            builder.button(DialogButton::new(crate::i18n::t("dialog.ok"), $on_confirm));
+            builder.button(DialogButton::new(crate::i18n::t("dialog.cancel"), $on_cancel));
             builder.build().unwrap()
         })
     };
@@ -2,6 +2,7 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+#[cfg(feature = "tui")]
 #[macro_use]
 mod macros;
 
@@ -15,23 +16,34 @@ mod pages;
 mod themes;
 
 mod cli;
+#[cfg(feature = "tui")]
 mod config;
 mod error;
+mod i18n;
 mod logger;
+mod notify;
+#[cfg(all(feature = "tui", unix))]
+mod suspend;
 
 use anyhow::Result;
 use clap::Parser;
+use cli::helpers::{load_user_ports, load_user_sla_keys, register_sla_signer};
 use cli::{CliArgs, run_cli};
 use logger::init_logger;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Before parsing, the help texts are looked up in the catalog
+    i18n::init();
     let args = CliArgs::parse();
 
     let cli_mode = args.cli || args.command.is_some() || !cfg!(feature = "tui");
     let tui_mode = !cli_mode;
 
     init_logger(tui_mode, args.verbose);
+    load_user_sla_keys();
+    load_user_ports();
+    register_sla_signer(args.sla_signer.as_deref());
 
     if cli_mode {
         return run_cli(&args).await;
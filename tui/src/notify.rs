@@ -0,0 +1,66 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Operations shorter than this don't notify, unless configured otherwise
+pub const DEFAULT_NOTIFY_THRESHOLD: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMode {
+    #[default]
+    Off,
+    /// Ring the terminal bell
+    Bell,
+    /// Ring the bell and send a desktop notification (OSC 9), for terminals supporting it
+    Desktop,
+}
+
+#[cfg(feature = "tui")]
+impl NotifyMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyMode::Off => "off",
+            NotifyMode::Bell => "bell",
+            NotifyMode::Desktop => "desktop",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Self {
+        match s {
+            "bell" => NotifyMode::Bell,
+            "desktop" => NotifyMode::Desktop,
+            _ => NotifyMode::Off,
+        }
+    }
+}
+
+/// Notifies the user that an operation finished, if it took longer than `threshold`.
+pub fn notify_completion(mode: NotifyMode, elapsed: Duration, threshold: Duration, summary: &str) {
+    if mode == NotifyMode::Off || elapsed < threshold {
+        return;
+    }
+
+    #[cfg(feature = "notify")]
+    {
+        use std::io::Write;
+
+        let mut out = std::io::stdout();
+        let _ = match mode {
+            NotifyMode::Off => Ok(()),
+            NotifyMode::Bell => write!(out, "\x07"),
+            // BEL and ESC would terminate the sequence early
+            NotifyMode::Desktop => {
+                write!(out, "\x07\x1b]9;{}\x07", summary.replace(['\x07', '\x1b'], ""))
+            }
+        };
+        let _ = out.flush();
+    }
+
+    #[cfg(not(feature = "notify"))]
+    let _ = summary;
+}
@@ -10,28 +10,58 @@ use std::time::Instant;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use human_bytes::human_bytes;
-use penumbra::core::devinfo::DevInfoData;
-use penumbra::core::seccfg::LockFlag;
-use penumbra::core::storage::{Partition, Storage};
-use penumbra::{Device, DeviceBuilder, find_mtk_port};
-#[cfg(target_os = "windows")]
-use ratatui::crossterm::event::KeyEventKind;
+use log::warn;
+use penumbra::api::{
+    BootMode,
+    CancelToken,
+    CoreEvent,
+    DevInfoData,
+    Device,
+    DeviceBuilder,
+    ExtState,
+    GptDiff,
+    GptEntryDiff,
+    GptReport,
+    GptSource,
+    HashingWriter,
+    LockFlag,
+    Partition,
+    PortEvent,
+    PortInfo,
+    Progress,
+    RegionInfo,
+    Storage,
+    UsbLink,
+    event_channel,
+    list_mtk_ports,
+    open_mtk_port,
+    watch_mtk_ports,
+};
 use ratatui::crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::prelude::{Alignment, Frame};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Row, Table};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, Row, Table};
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter};
-use tokio::fs::File;
-use tokio::io::{BufReader, BufWriter};
+use tokio::fs::{File, metadata};
+use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::spawn;
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{Mutex, mpsc, watch};
 use tokio::task::JoinHandle;
-use tokio::time::{Duration, sleep};
+use tokio::time::{Duration, sleep, timeout};
 
 use crate::app::{AppCtx, AppPage};
+use crate::cli::helpers::{
+    HookConfig,
+    HookContext,
+    Presets,
+    SupportBundle,
+    open_image,
+    open_journal,
+    resolve_preset,
+};
 use crate::components::selectable_list::{
     ListItemEntry,
     ListItemEntryBuilder,
@@ -39,6 +69,8 @@ use crate::components::selectable_list::{
     SelectableListBuilder,
 };
 use crate::components::{
+    DialogBuilder,
+    DialogButton,
     ExplorerResult,
     FileExplorer,
     ProgressBar,
@@ -46,7 +78,13 @@ use crate::components::{
     ThemedWidgetMut,
     ThemedWidgetRef,
 };
+use crate::i18n::{t, tf};
+use crate::notify::notify_completion;
 use crate::pages::Page;
+use crate::themes::{StyleSlot, Theme};
+
+/// Frames of the header spinner shown while the device info is refreshed
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
 /// Which panel is currently focused
 pub enum FocusedPanel {
@@ -62,6 +100,9 @@ pub enum DeviceStatus {
     Connected,
 }
 
+/// How long rebooting the device can take when leaving the page, e.g. if it was unplugged
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// A list of event to which the DevicePage can respond
 pub enum DeviceEvent {
     // Progress Bar Events
@@ -71,12 +112,6 @@ pub enum DeviceEvent {
         total_bytes: u64,
         message: String,
     },
-    /// Update progress with bytes written
-    /// If message is Some, update the message as well
-    ProgressUpdate {
-        written: u64,
-        message: Option<String>,
-    },
     /// Finish progress with a final message
     ProgressFinish {
         message: String,
@@ -85,6 +120,31 @@ pub enum DeviceEvent {
     StatusChanged(DeviceStatus),
     /// Notify that device is connected (To be sent once)
     Connected(Device),
+    /// Several ports are plugged in, ask which one to connect to
+    ChoosePort(Vec<PortInfo>),
+    /// Connect to the port picked among the ones of `ChoosePort`
+    PortChosen(PortInfo),
+    /// The port of the connected device went away, e.g. the cable was pulled
+    Disconnected,
+    /// Notify that the partition table was re-read, e.g. after repairing a GPT
+    GptChanged {
+        partitions: Vec<Partition>,
+        source: GptSource,
+        report: Option<GptReport>,
+    },
+    /// Notify that the bootloader lock state was read or changed, `None` if it's unknown
+    LockState(Option<LockFlag>),
+    /// Notify that the device information is being read again
+    RefreshStarted,
+    /// Notify that the device information was read again, replacing what is shown
+    InfoRefreshed {
+        devinfo: DevInfoData,
+        storage: Option<Arc<dyn Storage + Send + Sync>>,
+        regions: Vec<RegionInfo>,
+        partitions: Vec<Partition>,
+        source: GptSource,
+        report: Option<GptReport>,
+    },
 
     /// Change focused panel
     FocusPanel(FocusedPanel),
@@ -101,6 +161,65 @@ pub enum DeviceEvent {
     /// Whether to enable or disable input.
     /// Used to block input during operations
     Input(bool),
+    /// Notify that the device left DA mode, and the page to go to, if any
+    Released(Option<AppPage>),
+    /// A message the DA sent on its own, e.g. a thermal warning
+    DaNotice(String),
+}
+
+/// Latest progress of the running operation.
+/// Sent through [`DeviceEventSender::progress`], which only keeps the latest value.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressUpdate {
+    pub written: u64,
+    pub message: Option<String>,
+}
+
+/// Sending half of the page events, shared by the page and the callbacks.
+///
+/// Events go through an unbounded channel, so state changes like `Input(true)` or
+/// `ProgressFinish` are never dropped when the UI lags behind. High frequency progress
+/// updates are coalesced instead, the page only picks the latest one each frame.
+#[derive(Clone)]
+pub struct DeviceEventSender {
+    events: mpsc::UnboundedSender<DeviceEvent>,
+    progress: watch::Sender<ProgressUpdate>,
+}
+
+impl DeviceEventSender {
+    pub fn send(&self, event: DeviceEvent) {
+        // Progress of the previous operation must not show up in the next one, e.g. its
+        // message staying when the new one reports the same. Cleared while the bar is
+        // idle: before it starts, and after it finishes.
+        let (before, after) = match event {
+            DeviceEvent::ProgressStart { .. } => (true, false),
+            DeviceEvent::ProgressFinish { .. } => (false, true),
+            _ => (false, false),
+        };
+
+        if before {
+            self.progress.send_replace(ProgressUpdate::default());
+        }
+        if self.events.send(event).is_err() {
+            warn!("Device page is gone, dropping event");
+        }
+        if after {
+            self.progress.send_replace(ProgressUpdate::default());
+        }
+    }
+
+    /// Update progress with bytes written
+    /// If message is Some, update the message as well
+    pub fn progress(&self, written: u64, message: Option<&str>) {
+        self.progress.send_modify(|progress| {
+            progress.written = written;
+            if let Some(msg) = message
+                && progress.message.as_deref() != Some(msg)
+            {
+                progress.message = Some(msg.to_string());
+            }
+        });
+    }
 }
 
 /// A list of event used by the page and callbacks to communicate to
@@ -127,10 +246,42 @@ pub enum DeviceAction {
     ReadPartition,
     #[strum(serialize = "Write Partition")]
     WritePartition,
+    #[strum(serialize = "Dump Preloader")]
+    DumpPreloader,
+    #[strum(serialize = "Restore Primary GPT")]
+    RestorePrimaryGpt,
+    #[strum(serialize = "Restore Secondary GPT")]
+    RestoreSecondaryGpt,
+    #[strum(serialize = "Refresh Device Info")]
+    RefreshInfo,
+    #[strum(serialize = "Create Support Bundle")]
+    SupportBundle,
+    #[strum(serialize = "Reboot to Recovery")]
+    RebootRecovery,
     #[strum(serialize = "Back to Menu")]
     BackToMenu,
 }
 
+impl DeviceAction {
+    /// The menu label, in the current language.
+    /// `as_ref()` stays in English, for the logs and hooks.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeviceAction::UnlockBootloader => t("action.unlock_bootloader"),
+            DeviceAction::LockBootloader => t("action.lock_bootloader"),
+            DeviceAction::ReadPartition => t("action.read_partition"),
+            DeviceAction::DumpPreloader => t("action.dump_preloader"),
+            DeviceAction::WritePartition => t("action.write_partition"),
+            DeviceAction::RestorePrimaryGpt => t("action.restore_primary_gpt"),
+            DeviceAction::RestoreSecondaryGpt => t("action.restore_secondary_gpt"),
+            DeviceAction::RefreshInfo => t("action.refresh_info"),
+            DeviceAction::SupportBundle => t("action.support_bundle"),
+            DeviceAction::RebootRecovery => t("action.reboot_recovery"),
+            DeviceAction::BackToMenu => t("action.back_to_menu"),
+        }
+    }
+}
+
 /// Represent a callback for a device action
 /// The callback is executed in an async task, allowing for background operations.
 /// The callback can communicate with the page via the provided channels.
@@ -141,7 +292,7 @@ pub trait DeviceActionCallback: Send + Sync {
     async fn execute(
         &self,
         device: Arc<Mutex<Device>>,
-        event_tx: mpsc::Sender<DeviceEvent>,
+        event_tx: DeviceEventSender,
         cb_tx: mpsc::Sender<CallbackEvent>,
         cb_rx: mpsc::Receiver<CallbackEvent>,
     ) -> Result<()>;
@@ -167,6 +318,14 @@ impl DeviceState {
     }
 }
 
+/// Modal for saving or loading a partition selection preset
+enum PresetPrompt {
+    /// Typing the name of the preset to save
+    Save(String),
+    /// Picking a saved preset to apply
+    Pick(SelectableList),
+}
+
 pub struct DevicePage {
     pub device: Option<Arc<Mutex<Device>>>,
     pub device_state: DeviceState,
@@ -174,8 +333,9 @@ pub struct DevicePage {
 
     // Event channel for async communication
     /// UI Events
-    pub event_tx: mpsc::Sender<DeviceEvent>,
-    pub event_rx: mpsc::Receiver<DeviceEvent>,
+    pub event_tx: DeviceEventSender,
+    pub event_rx: mpsc::UnboundedReceiver<DeviceEvent>,
+    pub progress_rx: watch::Receiver<ProgressUpdate>,
     /// Callbacks Events, for transmitting Callback results
     pub callback_tx: Option<mpsc::Sender<CallbackEvent>>,
     pub callback_rx: Option<mpsc::Receiver<CallbackEvent>>,
@@ -183,6 +343,10 @@ pub struct DevicePage {
     // Action callbacks and active operations
     pub action_callbacks: HashMap<DeviceAction, Arc<dyn DeviceActionCallback>>,
     pub active_operations: HashMap<DeviceAction, JoinHandle<()>>,
+    /// Commands run once an action completes
+    hooks: HookConfig,
+    /// Partitions picked for the running action, reported to hooks
+    selected_partitions: Arc<std::sync::Mutex<Vec<String>>>,
 
     // UI components (foundation only, not rendered yet)
     stars: Stars,
@@ -190,6 +354,7 @@ pub struct DevicePage {
     menu: SelectableList,
     partition_list: SelectableList,
     explorer: Option<FileExplorer>,
+    preset_prompt: Option<PresetPrompt>,
 
     // UI State
     pub focused_panel: FocusedPanel,
@@ -199,31 +364,30 @@ pub struct DevicePage {
     pub partitions: Vec<Partition>,
     pub devinfo: Option<DevInfoData>,
     pub storage: Option<Arc<dyn Storage + Send + Sync>>,
+    pub regions: Vec<RegionInfo>,
+    pub gpt_source: GptSource,
+    /// Whether the primary and secondary GPT are valid and agree
+    pub gpt_report: Option<GptReport>,
+    pub ext_state: ExtState,
+    pub usb_link: Option<UsbLink>,
+    pub lock_state: Option<LockFlag>,
+    /// When the running info refresh started, drives the header spinner
+    refreshing: Option<Instant>,
+    /// Stops the running flash operation at the next chunk, Esc while an action runs
+    cancel: Option<CancelToken>,
+    /// Watches the port of the connected device, to notice it being unplugged
+    port_watch: Option<JoinHandle<()>>,
 }
 
 impl DevicePage {
     pub fn new() -> Self {
-        let (event_tx, event_rx) = mpsc::channel(32);
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let (progress, progress_rx) = watch::channel(ProgressUpdate::default());
+        let event_tx = DeviceEventSender { events, progress };
         let progress_bar = ProgressBar::new();
 
-        // Build menu from actions
-        let actions: Vec<DeviceAction> = DeviceAction::iter().collect();
-        let menu_items: Vec<ListItemEntry> = actions
-            .iter()
-            .map(|action| {
-                let icon = match action {
-                    DeviceAction::UnlockBootloader => '🔓',
-                    DeviceAction::LockBootloader => '🔒',
-                    DeviceAction::ReadPartition => '📁',
-                    DeviceAction::WritePartition => '📝',
-                    DeviceAction::BackToMenu => '↩',
-                };
-                ListItemEntryBuilder::new(action.as_ref().to_string()).icon(icon).build().unwrap()
-            })
-            .collect();
-
         let menu = SelectableListBuilder::default()
-            .items(menu_items)
+            .items(Self::menu_items())
             .highlight_symbol(">> ".to_string())
             .build()
             .unwrap();
@@ -231,6 +395,7 @@ impl DevicePage {
         let partition_list = SelectableListBuilder::default()
             .items(Vec::new())
             .highlight_symbol(">> ".to_string())
+            .checkboxes(true)
             .build()
             .unwrap();
 
@@ -240,30 +405,79 @@ impl DevicePage {
             status_message: None,
             event_tx,
             event_rx,
+            progress_rx,
             callback_tx: None,
             callback_rx: None,
             action_callbacks: HashMap::new(),
             active_operations: HashMap::new(),
+            hooks: HookConfig::default(),
+            selected_partitions: Arc::default(),
             stars: Stars::default(),
             progress_bar,
             menu,
             explorer: None,
+            preset_prompt: None,
             focused_panel: FocusedPanel::Menu,
             input_enabled: true,
             partition_list,
             partitions: Vec::new(),
             devinfo: None,
             storage: None,
+            regions: Vec::new(),
+            gpt_source: GptSource::default(),
+            gpt_report: None,
+            ext_state: ExtState::default(),
+            usb_link: None,
+            lock_state: None,
+            refreshing: None,
+            cancel: None,
+            port_watch: None,
         };
 
         page.register_action(DeviceAction::UnlockBootloader, Arc::new(UnlockBootloaderCallback));
         page.register_action(DeviceAction::LockBootloader, Arc::new(LockBootloaderCallback));
         page.register_action(DeviceAction::ReadPartition, Arc::new(ReadPartitionCallback));
         page.register_action(DeviceAction::WritePartition, Arc::new(WritePartitionCallback));
+        page.register_action(DeviceAction::DumpPreloader, Arc::new(DumpPreloaderCallback));
+        page.register_action(
+            DeviceAction::RestorePrimaryGpt,
+            Arc::new(RestoreGptCallback { from: GptSource::Secondary }),
+        );
+        page.register_action(
+            DeviceAction::RestoreSecondaryGpt,
+            Arc::new(RestoreGptCallback { from: GptSource::Primary }),
+        );
+        page.register_action(DeviceAction::RefreshInfo, Arc::new(RefreshInfoCallback));
+        page.register_action(DeviceAction::SupportBundle, Arc::new(SupportBundleCallback));
+        page.register_action(
+            DeviceAction::RebootRecovery,
+            Arc::new(RebootCallback { mode: BootMode::Recovery }),
+        );
 
         page
     }
 
+    /// Builds the menu from the actions, labelled in the current language
+    fn menu_items() -> Vec<ListItemEntry> {
+        DeviceAction::iter()
+            .map(|action| {
+                let icon = match action {
+                    DeviceAction::UnlockBootloader => '🔓',
+                    DeviceAction::LockBootloader => '🔒',
+                    DeviceAction::ReadPartition => '📁',
+                    DeviceAction::WritePartition => '📝',
+                    DeviceAction::DumpPreloader => '💾',
+                    DeviceAction::RestorePrimaryGpt | DeviceAction::RestoreSecondaryGpt => '🩹',
+                    DeviceAction::RefreshInfo => '🔄',
+                    DeviceAction::SupportBundle => '📦',
+                    DeviceAction::RebootRecovery => '🔁',
+                    DeviceAction::BackToMenu => '↩',
+                };
+                ListItemEntryBuilder::new(action.label().to_string()).icon(icon).build().unwrap()
+            })
+            .collect()
+    }
+
     pub fn register_action(
         &mut self,
         action: DeviceAction,
@@ -279,12 +493,12 @@ impl DevicePage {
         }
 
         let Some(device) = self.device.clone() else {
-            self.event_tx.send(DeviceEvent::Error("Device not connected".to_string())).await.ok();
+            self.event_tx.send(DeviceEvent::Error(t("error.not_connected").to_string()));
             return;
         };
 
         let Some(callback) = self.action_callbacks.get(&action).cloned() else {
-            self.event_tx.send(DeviceEvent::Error("No callback registered".to_string())).await.ok();
+            self.event_tx.send(DeviceEvent::Error(t("error.no_callback").to_string()));
             return;
         };
 
@@ -298,13 +512,43 @@ impl DevicePage {
 
         let event_tx = self.event_tx.clone();
 
+        let hooks = self.hooks.clone();
+        let device_id =
+            self.devinfo.as_ref().map(|d| hex::encode_upper(&d.meid)).unwrap_or_default();
+        self.selected_partitions = Arc::default();
+        let selected_partitions = self.selected_partitions.clone();
+        if let Some(cancel) = &self.cancel {
+            cancel.reset();
+        }
+
         let handle = tokio::spawn(async move {
+            let started = Instant::now();
             let result = callback
                 .execute(device, event_tx.clone(), cb_tx_from_callback, cb_rx_from_callback)
                 .await;
-            if let Err(e) = result {
-                event_tx.send(DeviceEvent::Error(e.to_string())).await.ok();
+            if let Err(e) = &result {
+                let cancelled =
+                    e.downcast_ref::<penumbra::api::Error>().is_some_and(|e| e.is_cancelled());
+                let message = if cancelled {
+                    t("op.cancelled")
+                } else {
+                    event_tx.send(DeviceEvent::Error(e.to_string()));
+                    t("op.failed")
+                };
+                // Failed callbacks bail out early, don't leave the page locked behind them
+                event_tx.send(DeviceEvent::ProgressFinish { message: message.into() });
+                event_tx.send(DeviceEvent::Input(true));
             }
+
+            let hook = HookContext {
+                device: device_id,
+                operation: action.as_ref().to_string(),
+                partitions: selected_partitions.lock().map(|p| p.clone()).unwrap_or_default(),
+                duration: started.elapsed(),
+                report: open_journal().map(|j| j.path().to_path_buf()),
+                error: result.err().map(|e| e.to_string()),
+            };
+            let _ = tokio::task::spawn_blocking(move || hooks.run(&hook)).await;
         });
 
         self.active_operations.insert(action, handle);
@@ -317,14 +561,15 @@ impl DevicePage {
                 DeviceEvent::ProgressStart { total_bytes, message } => {
                     self.progress_bar.start(total_bytes, message);
                 }
-                DeviceEvent::ProgressUpdate { written, message } => {
-                    self.progress_bar.set_written(written);
-                    if let Some(msg) = message {
-                        self.progress_bar.set_message(msg);
-                    }
-                }
                 DeviceEvent::ProgressFinish { message } => {
-                    self.progress_bar.finish();
+                    let message = match self.progress_bar.phase_summary() {
+                        Some(summary) => format!("{message} ({summary})"),
+                        None => message,
+                    };
+                    if let Some(elapsed) = self.progress_bar.finish() {
+                        let (mode, threshold) = (ctx.notify_mode(), ctx.notify_threshold());
+                        notify_completion(mode, elapsed, threshold, &message);
+                    }
                     self.status_message = Some(message);
                 }
 
@@ -334,29 +579,93 @@ impl DevicePage {
                 DeviceEvent::Connected(mut device) => {
                     self.devinfo = Some(device.dev_info.get_data().await);
 
-                    let partitions = device.get_partitions().await;
-                    let partition_list_items: Vec<ListItemEntry> = partitions
-                        .iter()
-                        .map(|p| {
-                            ListItemEntryBuilder::new(format!(
-                                "{} ({})",
-                                p.name,
-                                human_bytes(p.size as f64)
-                            ))
-                            .value(p.name.clone())
-                            .build()
-                            .unwrap()
-                        })
-                        .collect();
-
-                    self.partition_list.items = partition_list_items;
-
-                    self.partitions = partitions;
+                    // The device stays usable without its partition table, e.g. to flash a GPT
+                    let partitions = match device.get_partitions().await {
+                        Ok(partitions) => partitions,
+                        Err(e) => {
+                            error_dialog!(ctx, tf("error.partitions_failed", &[("error", &e)]));
+                            Vec::new()
+                        }
+                    };
                     self.storage = device.dev_info.storage().await.clone();
+                    self.set_partitions(partitions, &ctx.theme);
+
+                    self.regions = device.regions().await;
+                    self.gpt_source = device.gpt_source().await;
+                    self.gpt_report = device.gpt_report().await;
+                    self.ext_state = device.extensions_state();
+                    self.usb_link = device.usb_link();
+                    self.progress_bar.set_phases(device.phase_tracker());
+                    self.progress_bar.set_stats(device.conn_stats());
+                    self.cancel = device.cancel_handle();
+                    self.watch_port(device.port_location());
                     self.device = Some(Arc::new(Mutex::new(device)));
                     self.device_state.set_status(DeviceStatus::Connected);
                 }
 
+                DeviceEvent::ChoosePort(ports) => {
+                    let mut message = t("device.choose_port").to_string();
+                    for (i, port) in ports.iter().enumerate() {
+                        message.push_str(&format!("\n{}. {}", i + 1, port));
+                    }
+
+                    let mut builder = DialogBuilder::info(message, &ctx.theme);
+                    for (i, port) in ports.into_iter().enumerate() {
+                        let tx = self.event_tx.clone();
+                        builder.button(DialogButton::new(&(i + 1).to_string(), move || {
+                            tx.send(DeviceEvent::PortChosen(port.clone()))
+                        }));
+                    }
+                    ctx.set_dialog(&mut builder);
+                }
+                DeviceEvent::PortChosen(port) => {
+                    self.connect_port(ctx, Some(port));
+                }
+                DeviceEvent::Disconnected => {
+                    // Already released, e.g. rebooted on purpose
+                    if self.device.take().is_none() {
+                        continue;
+                    }
+
+                    self.cancel_all_operations();
+                    self.stop_port_watch();
+                    self.cancel = None;
+                    self.lock_state = None;
+                    self.refreshing = None;
+                    self.input_enabled = true;
+                    self.device_state.set_status(DeviceStatus::Disconnected);
+                    error_dialog!(ctx, t("error.device_unplugged"));
+                    self.connect_device(ctx);
+                }
+
+                DeviceEvent::GptChanged { partitions, source, report } => {
+                    self.set_partitions(partitions, &ctx.theme);
+                    self.gpt_source = source;
+                    self.gpt_report = report;
+                }
+                DeviceEvent::LockState(state) => {
+                    self.lock_state = state;
+                }
+                DeviceEvent::RefreshStarted => {
+                    self.refreshing = Some(Instant::now());
+                }
+                DeviceEvent::InfoRefreshed {
+                    devinfo,
+                    storage,
+                    regions,
+                    partitions,
+                    source,
+                    report,
+                } => {
+                    self.devinfo = Some(devinfo);
+                    self.storage = storage;
+                    self.regions = regions;
+                    self.set_partitions(partitions, &ctx.theme);
+                    self.gpt_source = source;
+                    self.gpt_report = report;
+                    self.refreshing = None;
+                }
+
                 DeviceEvent::FocusPanel(panel) => {
                     self.focused_panel = panel;
                 }
@@ -372,6 +681,11 @@ impl DevicePage {
                             log::debug!("Closing file explorer");
                             self.explorer = None;
                         }
+                        ExplorerResult::Aborted => {
+                            log::debug!("Operation aborted from file explorer");
+                            self.explorer = None;
+                            self.partition_list.clear_selections();
+                        }
                         _ => {
                             log::debug!("Explorer result received: {:?}", result);
                         }
@@ -382,13 +696,58 @@ impl DevicePage {
                     }
                 }
                 DeviceEvent::Error(msg) => {
+                    self.refreshing = None;
                     error_dialog!(ctx, msg);
                 }
                 DeviceEvent::HeaderStatus(msg) => {
                     self.status_message = Some(msg);
                 }
+                DeviceEvent::DaNotice(notice) => {
+                    self.status_message = Some(tf("device.da_notice", &[("message", &notice)]));
+                }
+                DeviceEvent::Released(page) => {
+                    self.stop_port_watch();
+                    self.device = None;
+                    self.cancel = None;
+                    self.lock_state = None;
+                    self.device_state.set_status(DeviceStatus::Disconnected);
+                    if let Some(page) = page {
+                        ctx.change_page(page);
+                    }
+                }
             }
         }
+
+        // After the events, so a ProgressStart queued this frame doesn't reset
+        // progress sent after it
+        if self.progress_rx.has_changed().unwrap_or(false) {
+            let progress = self.progress_rx.borrow_and_update().clone();
+            self.progress_bar.set_written(progress.written);
+            if let Some(msg) = progress.message {
+                self.progress_bar.set_message(msg);
+            }
+        }
+
+        // Callbacks may push back UI state they changed on their own (like an item
+        // being untoggled after its file prompt got cancelled)
+        while let Some(cb_rx) = &mut self.callback_rx
+            && let Ok(event) = cb_rx.try_recv()
+        {
+            if let CallbackEvent::PartitionToggled(part, toggled) = event {
+                self.partition_list.set_toggled(&part.name, toggled);
+            }
+        }
+    }
+
+    /// Asks the running action to stop at the next chunk, so the DA is left usable
+    fn cancel_operation(&mut self) {
+        let Some(cancel) = &self.cancel else {
+            return;
+        };
+        if self.active_operations.values().any(|h| !h.is_finished()) && !cancel.is_cancelled() {
+            cancel.cancel();
+            self.status_message = Some(t("op.cancelling").into());
+        }
     }
 
     pub fn cancel_all_operations(&mut self) {
@@ -397,7 +756,31 @@ impl DevicePage {
         }
     }
 
+    /// Goes to `page`, rebooting the device first so it isn't left waiting in DA mode
+    fn leave(&mut self, ctx: &mut AppCtx, page: AppPage) {
+        let Some(device) = self.device.clone() else {
+            ctx.change_page(page);
+            return;
+        };
+
+        self.cancel_all_operations();
+        let event_tx = self.event_tx.clone();
+        event_tx.send(DeviceEvent::Input(false));
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.rebooting").into()));
+        spawn(async move {
+            release_device(device).await;
+            event_tx.send(DeviceEvent::Released(Some(page)));
+            event_tx.send(DeviceEvent::Input(true));
+        });
+    }
+
     pub fn connect_device(&mut self, ctx: &mut AppCtx) {
+        self.connect_port(ctx, None);
+    }
+
+    /// Connects to `chosen`, or waits for a port to show up. When several are plugged in,
+    /// asks which one to use through [`DeviceEvent::ChoosePort`] instead.
+    fn connect_port(&mut self, ctx: &mut AppCtx, chosen: Option<PortInfo>) {
         if self.device.is_some() || self.device_state.status == DeviceStatus::Connecting {
             return;
         }
@@ -406,17 +789,52 @@ impl DevicePage {
 
         let da_data = ctx.loader().map(|da| da.file().da_raw_data.clone());
         let pl_data = ctx.preloader().map(|pl| pl.data());
+        let rate_limit = ctx.rate_limit();
+        let verify_writes = ctx.verify_writes();
+        self.progress_bar.set_rate_limit(rate_limit);
 
         spawn(async move {
-            let port = loop {
-                match find_mtk_port().await {
-                    Some(p) => break p,
-                    None => sleep(Duration::from_millis(700)).await,
+            let port = match chosen {
+                Some(info) => match open_mtk_port(&info).await {
+                    Some(p) => p,
+                    None => {
+                        let message = tf("error.port_open_failed", &[("port", &info)]);
+                        tx.send(DeviceEvent::Error(message));
+                        return;
+                    }
+                },
+                None => {
+                    let mut watch = watch_mtk_ports();
+                    'wait: loop {
+                        let Some(event) = watch.next().await else {
+                            return;
+                        };
+                        let PortEvent::Connected(info) = event else {
+                            continue;
+                        };
+
+                        let ports = list_mtk_ports().await;
+                        if ports.len() > 1 {
+                            tx.send(DeviceEvent::ChoosePort(ports));
+                            return;
+                        }
+
+                        // The OS may report the port a bit before it can be opened
+                        for _ in 0..5 {
+                            if let Some(p) = open_mtk_port(&info).await {
+                                break 'wait p;
+                            }
+                            sleep(Duration::from_millis(200)).await;
+                        }
+                    }
                 }
             };
-            let _ = tx.send(DeviceEvent::StatusChanged(DeviceStatus::Connecting)).await;
+            tx.send(DeviceEvent::StatusChanged(DeviceStatus::Connecting));
 
-            let mut devbuilder = DeviceBuilder::default().with_mtk_port(port);
+            let mut devbuilder = DeviceBuilder::default()
+                .with_mtk_port(port)
+                .with_rate_limit(rate_limit)
+                .with_verify_writes(verify_writes);
 
             if let Some(da) = da_data {
                 devbuilder = devbuilder.with_da_data(da);
@@ -424,33 +842,122 @@ impl DevicePage {
             if let Some(pl) = pl_data {
                 devbuilder = devbuilder.with_preloader(pl);
             }
+            if let Some(journal) = open_journal() {
+                devbuilder = devbuilder.with_journal(journal);
+            }
+
+            let (events, mut notices) = event_channel();
+            devbuilder = devbuilder.with_event_sink(events);
+            let notice_tx = tx.clone();
+            spawn(async move {
+                while let Some(event) = notices.recv().await {
+                    match event {
+                        CoreEvent::DaNotice(notice) => {
+                            notice_tx.send(DeviceEvent::DaNotice(notice));
+                        }
+                    }
+                }
+            });
 
             match devbuilder.build() {
                 Ok(mut dev) => {
                     if let Err(e) = dev.init().await {
-                        let _ = tx.send(DeviceEvent::Error(format!("Init failed: {}", e))).await;
-                        let _ =
-                            tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected)).await;
+                        tx.send(DeviceEvent::Error(tf("error.init_failed", &[("error", &e)])));
+                        tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected));
                         return;
                     }
 
                     if let Err(e) = dev.enter_da_mode().await {
-                        let _ = tx.send(DeviceEvent::Error(format!("DA Mode failed: {}", e))).await;
-                        let _ =
-                            tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected)).await;
+                        let message = tf("error.da_mode_failed", &[("error", &e)]);
+                        tx.send(DeviceEvent::Error(message));
+                        tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected));
                         return;
                     }
 
-                    let _ = tx.send(DeviceEvent::Connected(dev)).await;
+                    // Reading seccfg needs the extensions, don't bother without them
+                    let lock_state = match dev.extensions_state() {
+                        ExtState::Active => dev.get_seccfg_lock_state().await.ok(),
+                        _ => None,
+                    };
+
+                    tx.send(DeviceEvent::Connected(dev));
+                    tx.send(DeviceEvent::LockState(lock_state));
                 }
                 Err(e) => {
-                    let _ = tx.send(DeviceEvent::Error(format!("Build failed: {}", e))).await;
-                    let _ = tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected)).await;
+                    tx.send(DeviceEvent::Error(tf("error.build_failed", &[("error", &e)])));
+                    tx.send(DeviceEvent::StatusChanged(DeviceStatus::Disconnected));
                 }
             }
         });
     }
 
+    /// Sends [`DeviceEvent::Disconnected`] once the port at `location` goes away. Without
+    /// a location, the first port seen is assumed to be the device.
+    fn watch_port(&mut self, location: Option<String>) {
+        self.stop_port_watch();
+
+        let tx = self.event_tx.clone();
+        self.port_watch = Some(spawn(async move {
+            let mut watch = watch_mtk_ports();
+            let mut ours = None;
+            while let Some(event) = watch.next().await {
+                match event {
+                    PortEvent::Connected(info)
+                        if ours.is_none() && (location.is_none() || info.location == location) =>
+                    {
+                        ours = Some(info);
+                    }
+                    PortEvent::Disconnected(info) if ours.as_ref() == Some(&info) => {
+                        tx.send(DeviceEvent::Disconnected);
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }));
+    }
+
+    fn stop_port_watch(&mut self) {
+        if let Some(handle) = self.port_watch.take() {
+            handle.abort();
+        }
+    }
+
+    /// Updates the partition table and the partition list built from it
+    fn set_partitions(&mut self, partitions: Vec<Partition>, theme: &Theme) {
+        // Partitions still there after a table change stay checked
+        let checked: HashSet<String> = self
+            .partition_list
+            .checked_items()
+            .iter()
+            .filter_map(|item| item.value.clone())
+            .collect();
+
+        self.partition_list.items = partitions
+            .iter()
+            .map(|p| {
+                let label = format!("{} ({})", p.name, human_bytes(p.size as f64));
+                let mut entry = ListItemEntryBuilder::new(label.clone());
+                entry.value(p.name.clone());
+
+                // Partitions past the end of the storage are refused by the device
+                if let Some(storage) = &self.storage
+                    && !p.fits(storage.as_ref())
+                {
+                    entry.label(format!("{label} [out of range]"));
+                    entry.style(Style::default().fg(theme.warning));
+                }
+
+                entry.build().unwrap()
+            })
+            .collect();
+
+        for name in &checked {
+            self.partition_list.set_toggled(name, true);
+        }
+        self.partitions = partitions;
+    }
+
     /// Handles the action menu input
     async fn handle_menu_input(&mut self, ctx: &mut AppCtx, key: KeyEvent) {
         match key.code {
@@ -459,10 +966,7 @@ impl DevicePage {
 
             KeyCode::Right => {
                 if self.device_state.is_connected() {
-                    let _ = self
-                        .event_tx
-                        .send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu))
-                        .await;
+                    self.event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu));
                 }
             }
 
@@ -471,12 +975,15 @@ impl DevicePage {
                     && let Some(action) = DeviceAction::iter().nth(idx)
                 {
                     if action == DeviceAction::BackToMenu {
-                        ctx.change_page(AppPage::Welcome);
+                        self.leave(ctx, AppPage::Welcome);
                         return;
                     }
                     self.execute_action(action).await;
                 }
             }
+            KeyCode::Char('r') | KeyCode::Char('R') if self.device_state.is_connected() => {
+                self.execute_action(DeviceAction::RefreshInfo).await
+            }
 
             _ => {}
         }
@@ -488,10 +995,12 @@ impl DevicePage {
             KeyCode::Up => self.partition_list.previous(),
             KeyCode::Down => self.partition_list.next(),
 
+            // Leaving the panel keeps the checked partitions, only clearing drops them
             KeyCode::Esc => {
-                self.partition_list.toggled = false;
+                self.event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu));
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => {
                 self.partition_list.clear_selections();
-                let _ = self.event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu)).await;
             }
 
             KeyCode::Enter => {
@@ -511,12 +1020,14 @@ impl DevicePage {
                         .collect();
 
                     if !partitions.is_empty() {
+                        if let Ok(mut selected) = self.selected_partitions.lock() {
+                            *selected = partitions.iter().map(|p| p.name.clone()).collect();
+                        }
                         let _ = cb_tx.send(CallbackEvent::PartitionsSelected(partitions)).await;
                     }
                 }
             }
             KeyCode::Char('x') => {
-                self.partition_list.toggled = true;
                 self.partition_list.toggle_selected();
 
                 let cb_tx = match &self.callback_tx {
@@ -542,10 +1053,111 @@ impl DevicePage {
                 let is_checked = part_item.is_toggled();
                 cb_tx.send(CallbackEvent::PartitionToggled(part.clone(), is_checked)).await.ok();
             }
+            KeyCode::Char('p') | KeyCode::Char('P') => self.open_preset_picker(),
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                if self.partition_list.checked_items().is_empty() {
+                    self.status_message = Some(t("preset.select_first").into());
+                } else {
+                    self.preset_prompt = Some(PresetPrompt::Save(String::new()));
+                }
+            }
             _ => {}
         }
     }
 
+    fn open_preset_picker(&mut self) {
+        let items: Vec<ListItemEntry> = Presets::load()
+            .names()
+            .map(|name| {
+                ListItemEntryBuilder::new(name.clone()).value(name.clone()).build().unwrap()
+            })
+            .collect();
+
+        if items.is_empty() {
+            self.status_message = Some(t("preset.none_saved").into());
+            return;
+        }
+
+        let mut list = SelectableListBuilder::default()
+            .items(items)
+            .highlight_symbol(">> ".to_string())
+            .build()
+            .unwrap();
+        list.next();
+
+        self.preset_prompt = Some(PresetPrompt::Pick(list));
+    }
+
+    /// Handles the input of the preset modal
+    fn handle_preset_input(&mut self, key: KeyEvent) {
+        let Some(prompt) = &mut self.preset_prompt else { return };
+
+        match (prompt, key.code) {
+            (_, KeyCode::Esc) => self.preset_prompt = None,
+
+            (PresetPrompt::Save(name), KeyCode::Char(c))
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' =>
+            {
+                name.push(c);
+            }
+            (PresetPrompt::Save(name), KeyCode::Backspace) => {
+                name.pop();
+            }
+            (PresetPrompt::Save(name), KeyCode::Enter) if !name.is_empty() => {
+                let name = name.clone();
+                self.preset_prompt = None;
+                self.save_preset(&name);
+            }
+
+            (PresetPrompt::Pick(list), KeyCode::Up) => list.previous(),
+            (PresetPrompt::Pick(list), KeyCode::Down) => list.next(),
+            (PresetPrompt::Pick(list), KeyCode::Enter) => {
+                let name = list.selected_item().and_then(|item| item.value.clone());
+                self.preset_prompt = None;
+                if let Some(name) = name {
+                    self.apply_preset(&name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn save_preset(&mut self, name: &str) {
+        let partitions: Vec<String> = self
+            .partition_list
+            .checked_items()
+            .iter()
+            .filter_map(|item| item.value.clone())
+            .collect();
+
+        let mut presets = Presets::load();
+        presets.set(name, partitions);
+
+        self.status_message = Some(match presets.save() {
+            Ok(()) => tf("preset.saved", &[("name", &name)]),
+            Err(e) => tf("preset.save_failed", &[("name", &name), ("error", &e)]),
+        });
+    }
+
+    /// Checks the partitions of a preset, replacing the current selection
+    fn apply_preset(&mut self, name: &str) {
+        let presets = Presets::load();
+        let Some(names) = presets.get(name) else { return };
+
+        let (found, missing) = resolve_preset(names, &self.partitions);
+
+        self.partition_list.clear_selections();
+        for part in &found {
+            self.partition_list.set_toggled(&part.name, true);
+        }
+
+        self.status_message = Some(if missing.is_empty() {
+            tf("preset.loaded", &[("name", &name), ("count", &found.len())])
+        } else {
+            tf("preset.loaded_missing", &[("name", &name), ("missing", &missing.join(", "))])
+        });
+    }
+
     /// Renders the background (stars :D)
     fn render_background(&mut self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
         self.stars.render(area, frame.buffer_mut(), &ctx.theme);
@@ -580,26 +1192,82 @@ impl DevicePage {
     fn render_header(&self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
         let status = match &self.device_state.status {
             DeviceStatus::Disconnected => {
-                Span::styled("  Disconnected ", Style::default().fg(ctx.theme.muted))
+                let label = format!("  {} ", t("device.disconnected"));
+                Span::styled(label, Style::default().fg(ctx.theme.muted))
             }
             DeviceStatus::Connecting => {
-                Span::styled("  Connecting… ", Style::default().fg(ctx.theme.warning))
+                let label = format!("  {} ", t("device.connecting"));
+                Span::styled(label, Style::default().fg(ctx.theme.warning))
             }
             DeviceStatus::Connected => {
-                Span::styled("  Connected ", Style::default().fg(ctx.theme.success))
+                let label = format!("  {} ", t("device.connected"));
+                Span::styled(label, Style::default().fg(ctx.theme.success))
             }
         };
 
-        let header = Paragraph::new(Line::from(vec![
+        let mut spans = vec![
             Span::styled(" Antumbra ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(" | "),
             status,
             Span::raw(" | "),
-            Span::styled(
-                self.status_message.as_deref().unwrap_or(" "),
+        ];
+
+        // Writes are refused when the partition table isn't from the primary GPT,
+        // make it obvious why
+        if self.device_state.is_connected() && !self.gpt_source.is_trusted() {
+            spans.push(Span::styled(
+                format!(" GPT: {} ", self.gpt_source.as_str()),
+                Style::default().fg(ctx.theme.background).bg(ctx.theme.error),
+            ));
+            spans.push(Span::raw(" | "));
+        }
+
+        if self.device_state.is_connected() && self.gpt_diff().is_some() {
+            spans.push(Span::styled(
+                format!(" {} ", t("device.gpt_mismatch")),
+                Style::default().fg(ctx.theme.background).bg(ctx.theme.warning),
+            ));
+            spans.push(Span::raw(" | "));
+        }
+
+        // The primary GPT is in use, but the backup can't save the day anymore
+        if self.device_state.is_connected()
+            && self.gpt_report.as_ref().is_some_and(|r| r.primary_ok && !r.secondary_ok)
+        {
+            spans.push(Span::styled(
+                format!(" {} ", t("device.sgpt_corrupted")),
+                Style::default().fg(ctx.theme.background).bg(ctx.theme.warning),
+            ));
+            spans.push(Span::raw(" | "));
+        }
+
+        // Hubs and USB 1.1 fallbacks are behind most slow or flaky connections
+        if self.device_state.is_connected()
+            && let Some(link) = &self.usb_link
+        {
+            let style = if link.is_slow() || link.hub_depth > 0 {
+                Style::default().fg(ctx.theme.background).bg(ctx.theme.warning)
+            } else {
+                Style::default().fg(ctx.theme.muted)
+            };
+            spans.push(Span::styled(format!(" {} ", link), style));
+            spans.push(Span::raw(" | "));
+        }
+
+        if let Some(started) = self.refreshing {
+            let index = (started.elapsed().as_millis() / 100) as usize % SPINNER.len();
+            spans.push(Span::styled(
+                format!("{} ", SPINNER[index]),
                 Style::default().fg(ctx.theme.info),
-            ),
-        ]))
+            ));
+        }
+
+        spans.push(Span::styled(
+            self.status_message.as_deref().unwrap_or(" "),
+            Style::default().fg(ctx.theme.info),
+        ));
+
+        let header = Paragraph::new(Line::from(spans))
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -628,11 +1296,16 @@ impl DevicePage {
 
     /// Action menu
     fn render_menu(&mut self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
+        let border = match self.focused_panel {
+            FocusedPanel::Menu => StyleSlot::BorderFocused,
+            FocusedPanel::PartitionMenu => StyleSlot::BorderUnfocused,
+        };
+
         let block = Block::default()
-            .title(" ACTIONS ")
+            .title(format!(" {} ", t("device.actions")))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(ctx.theme.text));
+            .border_style(Style::default().fg(ctx.theme.color(border)));
 
         frame.render_widget(block.clone(), area);
         self.menu.render(block.inner(area), frame.buffer_mut(), &ctx.theme);
@@ -640,11 +1313,16 @@ impl DevicePage {
 
     /// Device info card
     fn render_device_info(&mut self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
+        let border = match self.focused_panel {
+            FocusedPanel::Menu => StyleSlot::BorderUnfocused,
+            FocusedPanel::PartitionMenu => StyleSlot::BorderFocused,
+        };
+
         let block = Block::default()
-            .title(" DEVICE INFO ")
+            .title(format!(" {} ", t("device.info")))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(ctx.theme.text));
+            .border_style(Style::default().fg(ctx.theme.color(border)));
 
         frame.render_widget(block.clone(), area);
         let inner = block.inner(area);
@@ -656,11 +1334,82 @@ impl DevicePage {
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(8), Constraint::Length(1), Constraint::Min(0)])
+            .constraints([Constraint::Length(10), Constraint::Length(1), Constraint::Min(0)])
             .split(inner);
 
-        self.render_device_table(frame, chunks[0], ctx);
-        self.partition_list.render(chunks[2], frame.buffer_mut(), &ctx.theme);
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(chunks[0]);
+
+        self.render_device_table(frame, top[0], ctx);
+        self.render_storage_card(frame, top[1], ctx);
+
+        let list_area = match self.gpt_diff() {
+            Some(diff) => {
+                // Header row and borders, capped so the partition list stays usable
+                let height = (diff.entries.len() as u16 + 3).min(10);
+                let bottom = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(height), Constraint::Min(0)])
+                    .split(chunks[2]);
+
+                self.render_gpt_diff(frame, bottom[0], diff, ctx);
+                bottom[1]
+            }
+            None => chunks[2],
+        };
+
+        self.partition_list.render(list_area, frame.buffer_mut(), &ctx.theme);
+    }
+
+    /// How the primary and secondary GPT disagree, if both are valid but differ
+    fn gpt_diff(&self) -> Option<&GptDiff> {
+        self.gpt_report.as_ref().map(|r| &r.diff).filter(|d| !d.is_empty())
+    }
+
+    /// Side by side view of the primary and secondary GPT entries that differ
+    fn render_gpt_diff(&self, frame: &mut Frame<'_>, area: Rect, diff: &GptDiff, ctx: &AppCtx) {
+        let describe = |part: Option<&Partition>| match part {
+            Some(p) => format!("0x{:08X} ({})", p.address, human_bytes(p.size as f64)),
+            None => "-".to_string(),
+        };
+
+        let mut rows: Vec<Row> = diff
+            .entries
+            .iter()
+            .map(|entry| {
+                let (name, primary, secondary) = match entry {
+                    GptEntryDiff::OnlyPrimary(p) => (&p.name, Some(p), None),
+                    GptEntryDiff::OnlySecondary(s) => (&s.name, None, Some(s)),
+                    GptEntryDiff::Changed { primary, secondary } => {
+                        (&primary.name, Some(primary), Some(secondary))
+                    }
+                };
+                Row::new(vec![name.clone(), describe(primary), describe(secondary)])
+            })
+            .collect();
+
+        if rows.is_empty() {
+            rows.push(Row::new(vec![t("device.gpt_attributes_only"), "-", "-"]));
+        }
+
+        let header = Row::new(vec![t("gpt.partition"), t("gpt.primary"), t("gpt.secondary")])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let widths =
+            [Constraint::Percentage(30), Constraint::Percentage(35), Constraint::Percentage(35)];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .title(format!(" {} ", t("device.gpt_mismatch")))
+                    .borders(Borders::BOTTOM | Borders::TOP)
+                    .border_style(Style::default().fg(ctx.theme.warning)),
+            )
+            .column_spacing(1)
+            .style(Style::default().fg(ctx.theme.text));
+
+        frame.render_widget(table, area);
     }
 
     /// Disconnected message
@@ -668,11 +1417,11 @@ impl DevicePage {
         let message = Paragraph::new(vec![
             Line::from(""),
             Line::from(Span::styled(
-                " Waiting for device connection…",
+                format!(" {}", t("device.waiting")),
                 Style::default().fg(ctx.theme.warning).add_modifier(Modifier::BOLD),
             )),
             Line::from(Span::styled(
-                " (Plug device in BOOTROM or Preloader mode)",
+                format!(" {}", t("device.waiting_hint")),
                 Style::default().fg(ctx.theme.muted),
             )),
         ])
@@ -685,19 +1434,48 @@ impl DevicePage {
     fn render_device_table(&self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
         let Some(devinfo) = &self.devinfo else { return };
 
-        let hw_code = format!("0x{:X}", devinfo.hw_code);
+        let hw_code = match devinfo.chip_name() {
+            Some(chip) => format!("{} (0x{:04X})", chip, devinfo.hw_code),
+            None => format!("0x{:X}", devinfo.hw_code),
+        };
 
-        let sbc = if devinfo.target_config & 0x1 != 0 { "Yes" } else { "No" };
-        let sla = if devinfo.target_config & 0x2 != 0 { "Yes" } else { "No" };
-        let daa = if devinfo.target_config & 0x4 != 0 { "Yes" } else { "No" };
+        let yes_no = |set: bool| if set { t("common.yes") } else { t("common.no") };
+        let security = devinfo.security();
+        let sbc = yes_no(security.sbc);
+        let sla = yes_no(security.sla);
+        let daa = yes_no(security.daa);
+        let exts = self.ext_state.to_string();
+        let bootloader = match self.lock_state {
+            Some(LockFlag::Lock) => t("device.locked"),
+            Some(LockFlag::Unlock) => t("device.unlocked"),
+            None => t("common.unknown"),
+        };
 
-        let rows = vec![
-            Row::new(vec!["HW Code", hw_code.as_str()]),
-            Row::new(vec!["Secure Boot (SBC)", sbc]),
-            Row::new(vec!["Serial Link Auth (SLA)", sla]),
-            Row::new(vec!["Download Agent Auth (DAA)", daa]),
+        let mut rows = vec![
+            Row::new(vec![t("device.hw_code").to_string(), hw_code]),
+            Row::new(vec![t("device.sbc").to_string(), sbc.to_string()]),
+            Row::new(vec![t("device.sla").to_string(), sla.to_string()]),
+            Row::new(vec![t("device.daa").to_string(), daa.to_string()]),
+            Row::new(vec![t("device.da_extensions").to_string(), exts]),
+            Row::new(vec![t("device.bootloader").to_string(), bootloader.to_string()]),
         ];
 
+        // Unknown fields are left out rather than shown empty
+        if let Some(storage) = &self.storage {
+            let hw = storage.hw_info();
+            let flash = [hw.vendor, hw.product].into_iter().flatten().collect::<Vec<_>>();
+            let fields = [
+                ("device.flash", (!flash.is_empty()).then(|| flash.join(" "))),
+                ("device.flash_fw", hw.fw_version),
+                ("device.flash_serial", hw.serial),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    rows.push(Row::new(vec![t(key).to_string(), value]));
+                }
+            }
+        }
+
         let table = Table::new(rows, [Constraint::Percentage(45), Constraint::Percentage(55)])
             .block(Block::default().borders(Borders::BOTTOM))
             .column_spacing(1)
@@ -706,10 +1484,74 @@ impl DevicePage {
         frame.render_widget(table, area);
     }
 
+    /// Storage regions card
+    fn render_storage_card(&self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
+        let mut rows: Vec<Row> = if self.regions.is_empty() {
+            vec![Row::new(vec![t("device.storage"), t("common.unknown")])]
+        } else {
+            self.regions
+                .iter()
+                .map(|region| {
+                    let size = human_bytes(region.size as f64);
+                    let size = if region.writable {
+                        size
+                    } else {
+                        tf("device.read_only", &[("size", &size)])
+                    };
+                    Row::new(vec![region.kind.as_str().to_string(), size])
+                })
+                .collect()
+        };
+
+        if let Some(row) = self.usage_row(ctx) {
+            rows.push(row);
+        }
+
+        let table = Table::new(rows, [Constraint::Percentage(45), Constraint::Percentage(55)])
+            .block(
+                Block::default()
+                    .title(format!(" {} ", t("device.storage_title")))
+                    .borders(Borders::BOTTOM | Borders::LEFT),
+            )
+            .column_spacing(1)
+            .style(Style::default().fg(ctx.theme.text));
+
+        frame.render_widget(table, area);
+    }
+
+    /// How much of the user area the partition table covers. A table reaching past the
+    /// end of the storage is highlighted, as some partitions can't be fully accessed.
+    fn usage_row(&self, ctx: &AppCtx) -> Option<Row<'static>> {
+        let storage = self.storage.as_ref()?;
+        let total = storage.get_user_size();
+        if total == 0 || self.partitions.is_empty() {
+            return None;
+        }
+
+        let user = storage.get_user_part();
+        let used: u64 = self.partitions.iter().filter(|p| p.kind == user).map(|p| p.size).sum();
+
+        let value = tf(
+            "device.partitioned_of",
+            &[
+                ("used", &human_bytes(used as f64)),
+                ("total", &human_bytes(total as f64)),
+                ("percent", &(used * 100 / total).to_string()),
+            ],
+        );
+        let style = if used > total {
+            Style::default().fg(ctx.theme.warning)
+        } else {
+            Style::default()
+        };
+
+        Some(Row::new(vec![t("device.partitioned").to_string(), value]).style(style))
+    }
+
     /// Progress bar
     fn render_progress(&self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
         let block = Block::default()
-            .title(" PROGRESS ")
+            .title(format!(" {} ", t("device.progress")))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .style(Style::default().fg(ctx.theme.accent));
@@ -729,12 +1571,59 @@ impl DevicePage {
 
     /// Footer help text
     fn render_footer(&self, frame: &mut Frame<'_>, area: Rect, ctx: &mut AppCtx) {
-        let footer = Paragraph::new("[↑↓] Navigate   [Enter] Select   [Esc] Back")
+        let help = match self.focused_panel {
+            _ if !self.input_enabled => t("footer.busy"),
+            FocusedPanel::PartitionMenu => t("footer.partitions"),
+            FocusedPanel::Menu => t("footer.menu"),
+        };
+
+        let footer = Paragraph::new(help)
             .alignment(Alignment::Center)
             .style(Style::default().fg(ctx.theme.foreground));
 
         frame.render_widget(footer, area);
     }
+
+    /// Renders the preset name prompt or picker as a small centered modal
+    fn render_preset_prompt(&mut self, area: Rect, frame: &mut Frame<'_>, ctx: &mut AppCtx) {
+        let Some(prompt) = &mut self.preset_prompt else { return };
+
+        let height = match prompt {
+            PresetPrompt::Save(_) => 3,
+            PresetPrompt::Pick(list) => (list.items.len() as u16 + 2).min(area.height / 2),
+        };
+        let width = 40.min(area.width);
+        let modal_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        frame.render_widget(Clear, modal_area);
+
+        let title = match prompt {
+            PresetPrompt::Save(_) => t("preset.save_as"),
+            PresetPrompt::Pick(_) => t("preset.load"),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(ctx.theme.accent))
+            .title(format!(" {title} "))
+            .style(Style::default().bg(ctx.theme.highlight));
+        let inner = block.inner(modal_area);
+        frame.render_widget(block, modal_area);
+
+        match prompt {
+            PresetPrompt::Save(name) => {
+                let input = Paragraph::new(format!("{name}_"))
+                    .style(Style::default().fg(ctx.theme.foreground));
+                frame.render_widget(input, inner);
+            }
+            PresetPrompt::Pick(list) => list.render(inner, frame.buffer_mut(), &ctx.theme),
+        }
+    }
 }
 
 #[async_trait]
@@ -748,22 +1637,27 @@ impl Page for DevicePage {
         if let Some(explorer) = &mut self.explorer {
             explorer.render_modal(area, frame.buffer_mut(), &ctx.theme);
         }
+
+        self.render_preset_prompt(area, frame, ctx);
     }
 
     async fn handle_input(&mut self, ctx: &mut AppCtx, key: KeyEvent) {
-        #[cfg(target_os = "windows")]
-        if key.kind != KeyEventKind::Press {
-            return;
-        }
-
         if !self.input_enabled {
+            if key.code == KeyCode::Esc {
+                self.cancel_operation();
+            }
             return;
         }
 
         // The explorer takes priority if active
         if let Some(explorer) = &mut self.explorer {
             let result = explorer.handle_key(key);
-            let _ = self.event_tx.send(DeviceEvent::ExplorerResult(result)).await;
+            self.event_tx.send(DeviceEvent::ExplorerResult(result));
+            return;
+        }
+
+        if self.preset_prompt.is_some() {
+            self.handle_preset_input(key);
             return;
         }
 
@@ -775,13 +1669,19 @@ impl Page for DevicePage {
 
     async fn on_enter(&mut self, ctx: &mut AppCtx) {
         self.device_state.set_status(DeviceStatus::Disconnected);
+        self.hooks = ctx.config().hooks.clone();
+        // The language may have changed in the options
+        self.menu.items = Self::menu_items();
 
         self.connect_device(ctx);
     }
 
     async fn on_exit(&mut self, _ctx: &mut AppCtx) {
         self.cancel_all_operations();
-        // TOOD: Add device shutdown if connected
+        self.stop_port_watch();
+        if let Some(device) = self.device.take() {
+            release_device(device).await;
+        }
     }
 
     async fn update(&mut self, ctx: &mut AppCtx) {
@@ -795,17 +1695,23 @@ impl DeviceActionCallback for UnlockBootloaderCallback {
     async fn execute(
         &self,
         device: Arc<Mutex<Device>>,
-        event_tx: mpsc::Sender<DeviceEvent>,
+        event_tx: DeviceEventSender,
         _cb_tx: mpsc::Sender<CallbackEvent>,
         _cb_rx: mpsc::Receiver<CallbackEvent>,
     ) -> Result<()> {
-        let _ = event_tx.send(DeviceEvent::HeaderStatus("Unlocking bootloader...".into())).await;
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.unlocking").into()));
 
         let mut dev = device.lock().await;
+        ensure_extensions(&dev)?;
+        if already_in_state(&mut dev, &event_tx, LockFlag::Unlock).await {
+            event_tx.send(DeviceEvent::HeaderStatus(t("op.already_unlocked").into()));
+            return Ok(());
+        }
+
         match dev.set_seccfg_lock_state(LockFlag::Unlock).await {
             Some(_) => {
-                let _ =
-                    event_tx.send(DeviceEvent::HeaderStatus("Bootloader unlocked.".into())).await;
+                event_tx.send(DeviceEvent::LockState(Some(LockFlag::Unlock)));
+                event_tx.send(DeviceEvent::HeaderStatus(t("op.unlocked").into()));
                 Ok(())
             }
             None => Err(anyhow!("Failed to unlock bootloader")),
@@ -819,16 +1725,23 @@ impl DeviceActionCallback for LockBootloaderCallback {
     async fn execute(
         &self,
         device: Arc<Mutex<Device>>,
-        event_tx: mpsc::Sender<DeviceEvent>,
+        event_tx: DeviceEventSender,
         _cb_tx: mpsc::Sender<CallbackEvent>,
         _cb_rx: mpsc::Receiver<CallbackEvent>,
     ) -> Result<()> {
-        event_tx.send(DeviceEvent::HeaderStatus("Locking bootloader...".into())).await.ok();
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.locking").into()));
 
         let mut dev = device.lock().await;
-        match dev.set_seccfg_lock_state(LockFlag::Unlock).await {
+        ensure_extensions(&dev)?;
+        if already_in_state(&mut dev, &event_tx, LockFlag::Lock).await {
+            event_tx.send(DeviceEvent::HeaderStatus(t("op.already_locked").into()));
+            return Ok(());
+        }
+
+        match dev.set_seccfg_lock_state(LockFlag::Lock).await {
             Some(_) => {
-                event_tx.send(DeviceEvent::HeaderStatus("Bootloader locked.".into())).await.ok();
+                event_tx.send(DeviceEvent::LockState(Some(LockFlag::Lock)));
+                event_tx.send(DeviceEvent::HeaderStatus(t("op.locked").into()));
                 Ok(())
             }
             None => Err(anyhow!("Failed to lock bootloader")),
@@ -836,31 +1749,64 @@ impl DeviceActionCallback for LockBootloaderCallback {
     }
 }
 
+/// Lock state changes rely on the DA extensions, refuse early instead of failing
+/// halfway through seccfg parsing.
+fn ensure_extensions(dev: &Device) -> Result<()> {
+    match dev.extensions_state() {
+        ExtState::Active | ExtState::Unknown => Ok(()),
+        state => Err(anyhow!(
+            "Can't change the lock state with this DA, extensions are unavailable: {}",
+            state
+        )),
+    }
+}
+
+/// Whether seccfg already has the `wanted` lock state, so there is nothing to write.
+/// If it can't be read, the change is attempted anyway.
+async fn already_in_state(
+    dev: &mut Device,
+    event_tx: &DeviceEventSender,
+    wanted: LockFlag,
+) -> bool {
+    match dev.get_seccfg_lock_state().await {
+        Ok(state) => {
+            event_tx.send(DeviceEvent::LockState(Some(state)));
+            state == wanted
+        }
+        Err(e) => {
+            warn!("Couldn't read the current lock state: {}", e);
+            false
+        }
+    }
+}
+
 pub struct ReadPartitionCallback;
 #[async_trait]
 impl DeviceActionCallback for ReadPartitionCallback {
     async fn execute(
         &self,
         device: Arc<Mutex<Device>>,
-        event_tx: mpsc::Sender<DeviceEvent>,
+        event_tx: DeviceEventSender,
         _cb_tx: mpsc::Sender<CallbackEvent>,
         mut cb_rx: mpsc::Receiver<CallbackEvent>,
     ) -> Result<()> {
-        let _ = event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu)).await;
+        event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu));
 
-        let explorer = FileExplorer::new("Output dump directory")?.directories_only();
+        let explorer = FileExplorer::new(t("explorer.dump_dir"))?.directories_only();
 
         let partitions = loop {
             match cb_rx.recv().await {
                 Some(CallbackEvent::PartitionsSelected(parts)) => break parts,
-                Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
+                Some(CallbackEvent::ExplorerResult(
+                    ExplorerResult::Cancelled | ExplorerResult::Aborted,
+                )) => {
                     return Ok(());
                 }
                 _ => {}
             }
         };
 
-        let _ = event_tx.send(DeviceEvent::ShowExplorer(explorer)).await;
+        event_tx.send(DeviceEvent::ShowExplorer(explorer));
 
         let output_dir = loop {
             match cb_rx.recv().await {
@@ -870,57 +1816,51 @@ impl DeviceActionCallback for ReadPartitionCallback {
                 Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
                     return Ok(());
                 }
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)) => {
+                    return abort_operation(&event_tx).await;
+                }
                 _ => {}
             }
         };
 
-        let total_size = partitions.iter().map(|p| p.size as u64).sum::<u64>();
+        let total_size = partitions.iter().map(|p| p.size).sum::<u64>();
 
         let mut bytes_read: u64 = 0;
 
         let mut dev = device.lock().await;
         // Block page input to avoid interruptions
-        event_tx.send(DeviceEvent::Input(false)).await.ok();
+        event_tx.send(DeviceEvent::Input(false));
 
-        event_tx
-            .send(DeviceEvent::ProgressStart {
-                total_bytes: total_size,
-                message: "Reading partitions...".into(),
-            })
-            .await
-            .ok();
+        event_tx.send(DeviceEvent::ProgressStart {
+            total_bytes: total_size,
+            message: t("op.reading").into(),
+        });
         for partition in partitions {
             let output_path = output_dir.join(format!("{}.bin", partition.name));
             let file = File::create(&output_path).await?;
-            let mut writer = BufWriter::new(file);
-
-            let mut progress_cb = |written: usize, _total_partition_bytes: usize| {
-                let total_bytes = bytes_read + written as u64;
-
-                let event_tx = event_tx.clone();
-                let part_name = partition.name.clone();
-                spawn(async move {
-                    let _ = event_tx
-                        .send(DeviceEvent::ProgressUpdate {
-                            written: total_bytes,
-                            message: Some(format!("Reading partition '{}'...", part_name,)),
-                        })
-                        .await;
-                });
+            let mut writer = HashingWriter::new(BufWriter::new(file));
+
+            let message = tf("op.reading_partition", &[("name", &partition.name)]);
+            let mut progress_cb = |progress: &Progress| {
+                event_tx.progress(bytes_read + progress.written, Some(message.as_str()));
             };
 
-            dev.upload(&partition.name, &mut writer, &mut progress_cb).await?;
+            dev.read_resolved_partition(&partition, &mut writer, &mut progress_cb).await?;
+            writer.flush().await?;
 
-            bytes_read += partition.size as u64;
+            // sha256sum compatible sidecar, hashed while streaming instead of a second pass
+            let sidecar = format!("{}  {}.bin\n", hex::encode(writer.digest()), partition.name);
+            tokio::fs::write(output_dir.join(format!("{}.bin.sha256", partition.name)), sidecar)
+                .await?;
+
+            bytes_read += partition.size;
         }
 
-        let _ = event_tx
-            .send(DeviceEvent::ProgressFinish { message: "Partition read complete.".into() })
-            .await;
+        event_tx.send(DeviceEvent::ProgressFinish { message: t("op.read_complete").into() });
 
         // Focus back the menu panel to avoid confusion
-        let _ = event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu)).await;
-        event_tx.send(DeviceEvent::Input(true)).await.ok();
+        event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu));
+        event_tx.send(DeviceEvent::Input(true));
 
         Ok(())
     }
@@ -932,11 +1872,11 @@ impl DeviceActionCallback for WritePartitionCallback {
     async fn execute(
         &self,
         device: Arc<Mutex<Device>>,
-        event_tx: mpsc::Sender<DeviceEvent>,
-        _cb_tx: mpsc::Sender<CallbackEvent>,
+        event_tx: DeviceEventSender,
+        cb_tx: mpsc::Sender<CallbackEvent>,
         mut cb_rx: mpsc::Receiver<CallbackEvent>,
     ) -> Result<()> {
-        event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu)).await.ok();
+        event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::PartitionMenu));
 
         let mut partition_map: HashMap<String, PathBuf> = HashMap::new();
 
@@ -947,26 +1887,41 @@ impl DeviceActionCallback for WritePartitionCallback {
                 Some(CallbackEvent::PartitionToggled(partition, selected)) => {
                     if selected {
                         // Show file explorer to select partition file
-                        let explorer = FileExplorer::new(format!(
-                            "Select file for partition '{}'",
-                            partition.name
+                        let explorer = FileExplorer::new(tf(
+                            "explorer.partition_file",
+                            &[("name", &partition.name)],
                         ))?;
 
-                        event_tx.send(DeviceEvent::ShowExplorer(explorer)).await.ok();
+                        event_tx.send(DeviceEvent::ShowExplorer(explorer));
 
                         let path = loop {
                             match cb_rx.recv().await {
                                 Some(CallbackEvent::ExplorerResult(ExplorerResult::Selected(
                                     path,
-                                ))) => break path,
+                                ))) => break Some(path),
                                 Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
-                                    continue;
+                                    break None;
+                                }
+                                Some(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)) => {
+                                    return abort_operation(&event_tx).await;
                                 }
+                                None => return Ok(()),
                                 _ => {}
                             }
                         };
 
-                        partition_map.insert(partition.name.clone(), path);
+                        match path {
+                            Some(path) => {
+                                partition_map.insert(partition.name.clone(), path);
+                            }
+                            None => {
+                                // No file for this partition, so untoggle it in the list too
+                                cb_tx
+                                    .send(CallbackEvent::PartitionToggled(partition, false))
+                                    .await
+                                    .ok();
+                            }
+                        }
                     } else {
                         partition_map.remove(&partition.name);
                     }
@@ -974,6 +1929,10 @@ impl DeviceActionCallback for WritePartitionCallback {
                 Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
                     continue;
                 }
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)) => {
+                    return abort_operation(&event_tx).await;
+                }
+                None => return Ok(()),
                 Some(CallbackEvent::PartitionsSelected(parts)) => {
                     partitions = parts;
                     break;
@@ -987,54 +1946,498 @@ impl DeviceActionCallback for WritePartitionCallback {
             .filter_map(|p| partition_map.get(&p.name).cloned().map(|path| (p, path)))
             .collect();
 
-        let total_size = part_to_write.iter().map(|(p, _)| p.size as u64).sum::<u64>();
+        let mut dev = device.lock().await;
+
+        // Verified writes report the read back pass as progress too
+        let passes = if dev.verify_writes() { 2 } else { 1 };
+        let total_size = part_to_write.iter().map(|(p, _)| p.size * passes).sum::<u64>();
 
         let mut bytes_written: u64 = 0;
 
-        let mut dev = device.lock().await;
         // Block page input to avoid interruptions
-        event_tx.send(DeviceEvent::Input(false)).await.ok();
+        event_tx.send(DeviceEvent::Input(false));
 
-        event_tx
-            .send(DeviceEvent::ProgressStart {
-                total_bytes: total_size,
-                message: "Writing partitions...".into(),
-            })
-            .await
-            .ok();
+        event_tx.send(DeviceEvent::ProgressStart {
+            total_bytes: total_size,
+            message: t("op.writing").into(),
+        });
 
         for (partition, path) in part_to_write {
-            let file = File::open(path).await?;
-            let mut reader = BufReader::new(file);
-
-            let mut progress_cb = |written: usize, _total_partition_bytes: usize| {
-                let total_bytes = bytes_written + written as u64;
-
-                let event_tx = event_tx.clone();
-                let part_name = partition.name.clone();
-                spawn(async move {
-                    let _ = event_tx
-                        .send(DeviceEvent::ProgressUpdate {
-                            written: total_bytes,
-                            message: Some(format!("Flashing partition '{}'...", part_name,)),
-                        })
-                        .await;
-                });
+            let len = metadata(&path).await?.len();
+            let mut reader = open_image(&path, dev.write_buffer_size()).await?;
+
+            let message = tf("op.flashing_partition", &[("name", &partition.name)]);
+            let mut progress_cb = |progress: &Progress| {
+                event_tx.progress(bytes_written + progress.written, Some(message.as_str()));
             };
 
-            dev.download(&partition.name, partition.size, &mut reader, &mut progress_cb).await?;
+            dev.write_resolved_partition(&partition, &mut reader, len, &mut progress_cb).await?;
 
-            bytes_written += partition.size as u64;
+            bytes_written += partition.size * passes;
         }
 
-        let _ = event_tx
-            .send(DeviceEvent::ProgressFinish { message: "Partition write complete.".into() })
-            .await;
+        event_tx.send(DeviceEvent::ProgressFinish { message: t("op.write_complete").into() });
 
         // Focus back the menu panel to avoid confusion
-        let _ = event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu)).await;
-        event_tx.send(DeviceEvent::Input(true)).await.ok();
+        event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu));
+        event_tx.send(DeviceEvent::Input(true));
 
         Ok(())
     }
 }
+
+/// Unwinds an operation aborted from the file explorer, bringing the page back
+/// to the action menu. Partition selections are cleared by the page itself
+/// when it receives the `Aborted` result.
+async fn abort_operation(event_tx: &DeviceEventSender) -> Result<()> {
+    event_tx.send(DeviceEvent::HeaderStatus(t("op.aborted").into()));
+    event_tx.send(DeviceEvent::FocusPanel(FocusedPanel::Menu));
+    event_tx.send(DeviceEvent::Input(true));
+    Ok(())
+}
+
+/// Copies one GPT over the other, then refreshes the partition table shown
+pub struct RestoreGptCallback {
+    /// The GPT to keep
+    from: GptSource,
+}
+
+#[async_trait]
+impl DeviceActionCallback for RestoreGptCallback {
+    async fn execute(
+        &self,
+        device: Arc<Mutex<Device>>,
+        event_tx: DeviceEventSender,
+        _cb_tx: mpsc::Sender<CallbackEvent>,
+        _cb_rx: mpsc::Receiver<CallbackEvent>,
+    ) -> Result<()> {
+        event_tx.send(DeviceEvent::Input(false));
+        event_tx.send(DeviceEvent::HeaderStatus(tf(
+            "op.restoring_gpt",
+            &[("source", &self.from.as_str())],
+        )));
+
+        let mut dev = device.lock().await;
+        dev.repair_gpt(self.from).await?;
+
+        event_tx.send(DeviceEvent::GptChanged {
+            partitions: dev.get_partitions().await?,
+            source: dev.gpt_source().await,
+            report: dev.gpt_report().await,
+        });
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.gpt_restored").into()));
+        event_tx.send(DeviceEvent::Input(true));
+
+        Ok(())
+    }
+}
+
+/// Extracts the preloader from the boot region, for later runs from BROM
+pub struct DumpPreloaderCallback;
+
+#[async_trait]
+impl DeviceActionCallback for DumpPreloaderCallback {
+    async fn execute(
+        &self,
+        device: Arc<Mutex<Device>>,
+        event_tx: DeviceEventSender,
+        _cb_tx: mpsc::Sender<CallbackEvent>,
+        mut cb_rx: mpsc::Receiver<CallbackEvent>,
+    ) -> Result<()> {
+        let explorer = FileExplorer::new(t("explorer.preloader_dir"))?.directories_only();
+        event_tx.send(DeviceEvent::ShowExplorer(explorer));
+
+        let output_dir = loop {
+            match cb_rx.recv().await {
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Selected(path))) => break path,
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
+                    return Ok(());
+                }
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)) => {
+                    return abort_operation(&event_tx).await;
+                }
+                _ => {}
+            }
+        };
+
+        let mut dev = device.lock().await;
+        event_tx.send(DeviceEvent::Input(false));
+
+        let devinfo = dev.dev_info.get_data().await;
+        let name = match devinfo.chip_name() {
+            Some(chip) => format!("preloader_{}.bin", chip.to_lowercase()),
+            None => format!("preloader_{:04x}.bin", devinfo.hw_code),
+        };
+        let output_path = output_dir.join(name);
+
+        let region_size = dev
+            .get_partitions()
+            .await?
+            .iter()
+            .find(|p| p.name == "preloader")
+            .map_or(0, |p| p.size);
+        event_tx.send(DeviceEvent::ProgressStart {
+            total_bytes: region_size,
+            message: t("op.dumping_preloader").into(),
+        });
+
+        let mut data = Vec::new();
+        let mut progress_cb = |progress: &Progress| event_tx.progress(progress.written, None);
+        dev.dump_preloader(&mut data, &mut progress_cb).await?;
+        tokio::fs::write(&output_path, &data).await?;
+
+        event_tx.send(DeviceEvent::ProgressFinish {
+            message: tf("op.preloader_dumped", &[("path", &output_path.display())]),
+        });
+        event_tx.send(DeviceEvent::Input(true));
+
+        Ok(())
+    }
+}
+
+/// Reads the storage and partition table again, and updates the page in place
+pub struct RefreshInfoCallback;
+
+#[async_trait]
+impl DeviceActionCallback for RefreshInfoCallback {
+    async fn execute(
+        &self,
+        device: Arc<Mutex<Device>>,
+        event_tx: DeviceEventSender,
+        _cb_tx: mpsc::Sender<CallbackEvent>,
+        _cb_rx: mpsc::Receiver<CallbackEvent>,
+    ) -> Result<()> {
+        event_tx.send(DeviceEvent::Input(false));
+        event_tx.send(DeviceEvent::RefreshStarted);
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.refreshing").into()));
+
+        let mut dev = device.lock().await;
+        dev.refresh_info().await?;
+
+        event_tx.send(DeviceEvent::InfoRefreshed {
+            devinfo: dev.dev_info.get_data().await,
+            storage: dev.dev_info.storage().await,
+            regions: dev.regions().await,
+            partitions: dev.get_partitions().await?,
+            source: dev.gpt_source().await,
+            report: dev.gpt_report().await,
+        });
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.refreshed").into()));
+        event_tx.send(DeviceEvent::Input(true));
+
+        Ok(())
+    }
+}
+
+pub struct SupportBundleCallback;
+#[async_trait]
+impl DeviceActionCallback for SupportBundleCallback {
+    async fn execute(
+        &self,
+        device: Arc<Mutex<Device>>,
+        event_tx: DeviceEventSender,
+        _cb_tx: mpsc::Sender<CallbackEvent>,
+        mut cb_rx: mpsc::Receiver<CallbackEvent>,
+    ) -> Result<()> {
+        let explorer = FileExplorer::new(t("explorer.bundle_dir"))?.directories_only();
+        event_tx.send(DeviceEvent::ShowExplorer(explorer));
+
+        let output = loop {
+            match cb_rx.recv().await {
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Selected(path))) => {
+                    break path.join("antumbra-support.zip");
+                }
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)) => {
+                    return Ok(());
+                }
+                Some(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)) => {
+                    return abort_operation(&event_tx).await;
+                }
+                _ => {}
+            }
+        };
+
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.collecting_info").into()));
+
+        let mut dev = device.lock().await;
+        let bundle = SupportBundle::collect(&mut dev, false).await?;
+        bundle.write_to(&output).await?;
+
+        event_tx.send(DeviceEvent::HeaderStatus(tf(
+            "op.bundle_written",
+            &[("path", &output.display())],
+        )));
+
+        Ok(())
+    }
+}
+
+/// Reboots the device back to normal mode, if the DA is running, so it doesn't need a
+/// battery pull once the page is left. Gives up after [`RELEASE_TIMEOUT`].
+async fn release_device(device: Arc<Mutex<Device>>) {
+    let reboot = async {
+        let mut dev = device.lock().await;
+        if dev.get_protocol().is_none() {
+            return Ok(());
+        }
+        dev.reboot(BootMode::Normal).await
+    };
+
+    match timeout(RELEASE_TIMEOUT, reboot).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to reboot the device: {}", e),
+        Err(_) => warn!("Timed out rebooting the device"),
+    }
+}
+
+/// Reboots the device into another mode, which ends the session
+pub struct RebootCallback {
+    mode: BootMode,
+}
+
+#[async_trait]
+impl DeviceActionCallback for RebootCallback {
+    async fn execute(
+        &self,
+        device: Arc<Mutex<Device>>,
+        event_tx: DeviceEventSender,
+        _cb_tx: mpsc::Sender<CallbackEvent>,
+        _cb_rx: mpsc::Receiver<CallbackEvent>,
+    ) -> Result<()> {
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.rebooting").into()));
+
+        let mut dev = device.lock().await;
+        dev.reboot(self.mode).await?;
+
+        event_tx.send(DeviceEvent::Released(None));
+        event_tx.send(DeviceEvent::HeaderStatus(t("op.rebooted").into()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra::api::{EmmcPartition, PartitionKind};
+    use penumbra::connection::mock::{MockMTKPort, Transcript};
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn channels()
+    -> (DeviceEventSender, mpsc::UnboundedReceiver<DeviceEvent>, watch::Receiver<ProgressUpdate>)
+    {
+        let (events, event_rx) = mpsc::unbounded_channel();
+        let (progress, progress_rx) = watch::channel(ProgressUpdate::default());
+        (DeviceEventSender { events, progress }, event_rx, progress_rx)
+    }
+
+    fn label(event: &DeviceEvent) -> &'static str {
+        match event {
+            DeviceEvent::Input(false) => "input off",
+            DeviceEvent::Input(true) => "input on",
+            DeviceEvent::ProgressStart { .. } => "start",
+            DeviceEvent::ProgressFinish { .. } => "finish",
+            _ => "other",
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn progress_flood_keeps_state_events() {
+        const FLOOD: u64 = 200_000;
+        let (event_tx, mut event_rx, progress_rx) = channels();
+
+        let flood = spawn(async move {
+            event_tx.send(DeviceEvent::Input(false));
+            let message = "Flooding".to_string();
+            event_tx.send(DeviceEvent::ProgressStart { total_bytes: FLOOD, message });
+            for written in 1..=FLOOD {
+                event_tx.progress(written, Some("Flooding"));
+            }
+            event_tx.send(DeviceEvent::ProgressFinish { message: "Done".into() });
+            event_tx.send(DeviceEvent::Input(true));
+        });
+
+        // A UI lagging behind, only looking every few milliseconds
+        let mut seen = Vec::new();
+        while seen.last() != Some(&"input on") {
+            sleep(Duration::from_millis(5)).await;
+            while let Ok(event) = event_rx.try_recv() {
+                seen.push(label(&event));
+            }
+        }
+        flood.await.unwrap();
+
+        assert_eq!(seen, ["input off", "start", "finish", "input on"]);
+        let progress = progress_rx.borrow().clone();
+        assert_eq!((progress.written, progress.message), (0, None));
+    }
+
+    #[test]
+    fn next_operation_gets_its_own_message() {
+        let (event_tx, _event_rx, progress_rx) = channels();
+
+        event_tx.send(DeviceEvent::ProgressStart { total_bytes: 10, message: "Read".into() });
+        event_tx.progress(10, Some("Reading 'boot'"));
+        event_tx.send(DeviceEvent::ProgressFinish { message: "Done".into() });
+
+        event_tx.send(DeviceEvent::ProgressStart { total_bytes: 10, message: "Read".into() });
+        assert_eq!(progress_rx.borrow().message, None);
+        event_tx.progress(2, Some("Reading 'boot'"));
+        assert_eq!(progress_rx.borrow().message.as_deref(), Some("Reading 'boot'"));
+    }
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn checked(page: &DevicePage) -> Vec<String> {
+        page.partition_list.checked_items().iter().filter_map(|i| i.value.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn checked_partitions_survive_leaving_the_panel() {
+        let mut ctx = AppCtx::detached();
+        let mut page = DevicePage::new();
+        page.device_state.set_status(DeviceStatus::Connected);
+        let kind = PartitionKind::Emmc(EmmcPartition::User);
+        let table = |names: &[&str]| -> Vec<Partition> {
+            let part = |(i, name): (usize, &&str)| {
+                Partition::new(name, 0x1000, 0x8000 + i as u64 * 0x1000, kind)
+            };
+            names.iter().enumerate().map(part).collect()
+        };
+        page.set_partitions(table(&["boot", "dtbo", "vbmeta", "super"]), &ctx.theme);
+
+        page.handle_input(&mut ctx, press(KeyCode::Right)).await;
+        page.update(&mut ctx).await;
+        assert!(matches!(page.focused_panel, FocusedPanel::PartitionMenu));
+
+        for code in [KeyCode::Char('x'), KeyCode::Down, KeyCode::Down, KeyCode::Char('x')] {
+            page.handle_input(&mut ctx, press(code)).await;
+        }
+        assert_eq!(checked(&page), ["boot", "vbmeta"]);
+
+        // Away to the action menu, moving through it, and back
+        page.handle_input(&mut ctx, press(KeyCode::Esc)).await;
+        page.update(&mut ctx).await;
+        assert!(matches!(page.focused_panel, FocusedPanel::Menu));
+        page.handle_input(&mut ctx, press(KeyCode::Down)).await;
+        page.handle_input(&mut ctx, press(KeyCode::Right)).await;
+        page.update(&mut ctx).await;
+        assert!(matches!(page.focused_panel, FocusedPanel::PartitionMenu));
+        assert_eq!(checked(&page), ["boot", "vbmeta"]);
+
+        // A new table keeps the partitions that are still there
+        page.set_partitions(table(&["boot", "dtbo", "super"]), &ctx.theme);
+        assert_eq!(checked(&page), ["boot"]);
+
+        page.handle_input(&mut ctx, press(KeyCode::Char('c'))).await;
+        assert!(checked(&page).is_empty());
+    }
+
+    /// A callback running on a device that isn't expected to be talked to
+    struct Running {
+        task: JoinHandle<Result<()>>,
+        to_callback: mpsc::Sender<CallbackEvent>,
+        from_callback: mpsc::Receiver<CallbackEvent>,
+        event_rx: mpsc::UnboundedReceiver<DeviceEvent>,
+    }
+
+    impl Running {
+        fn start(callback: impl DeviceActionCallback + 'static) -> Self {
+            let port = MockMTKPort::new(Transcript::new());
+            let device = DeviceBuilder::default().with_mtk_port(Box::new(port)).build().unwrap();
+            let (event_tx, event_rx, _) = channels();
+            let (to_callback, cb_rx) = mpsc::channel(1);
+            let (cb_tx, from_callback) = mpsc::channel(1);
+
+            let device = Arc::new(Mutex::new(device));
+            let task = spawn(async move { callback.execute(device, event_tx, cb_tx, cb_rx).await });
+            Self { task, to_callback, from_callback, event_rx }
+        }
+
+        async fn send(&self, event: CallbackEvent) {
+            self.to_callback.send(event).await.unwrap();
+        }
+
+        /// Waits for the callback to open its file prompt
+        async fn explorer_shown(&mut self) {
+            loop {
+                let event = timeout(Duration::from_secs(5), self.event_rx.recv()).await.unwrap();
+                if let Some(DeviceEvent::ShowExplorer(_)) = event {
+                    return;
+                }
+            }
+        }
+
+        /// Whether the callback unwound through `abort_operation`
+        fn unwound(&mut self) -> bool {
+            let mut unwound = false;
+            while let Ok(event) = self.event_rx.try_recv() {
+                if let DeviceEvent::HeaderStatus(msg) = event {
+                    unwound |= msg == t("op.aborted");
+                }
+            }
+            unwound
+        }
+
+        async fn finished(&mut self) {
+            let task = &mut self.task;
+            timeout(Duration::from_secs(5), task).await.unwrap().unwrap().unwrap();
+        }
+    }
+
+    fn boot() -> Partition {
+        Partition::new("boot", 0x1000, 0x8000, PartitionKind::Emmc(EmmcPartition::User))
+    }
+
+    #[tokio::test]
+    async fn cancelled_file_prompt_untoggles_the_partition() {
+        let mut op = Running::start(WritePartitionCallback);
+        op.send(CallbackEvent::PartitionToggled(boot(), true)).await;
+        op.explorer_shown().await;
+        op.send(CallbackEvent::ExplorerResult(ExplorerResult::Cancelled)).await;
+
+        let toggled = timeout(Duration::from_secs(5), op.from_callback.recv()).await.unwrap();
+        let Some(CallbackEvent::PartitionToggled(part, false)) = toggled else {
+            panic!("expected boot to be untoggled, got {:?}", toggled);
+        };
+        assert_eq!(part.name, "boot");
+        assert!(!op.unwound());
+        assert!(!op.task.is_finished());
+
+        // Still waiting on the partition list, which can abort the whole write as well
+        op.send(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)).await;
+        op.finished().await;
+        assert!(op.unwound());
+        assert!(op.from_callback.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn aborted_file_prompt_unwinds_the_write() {
+        let mut op = Running::start(WritePartitionCallback);
+        op.send(CallbackEvent::PartitionToggled(boot(), true)).await;
+        op.explorer_shown().await;
+        op.send(CallbackEvent::ExplorerResult(ExplorerResult::Aborted)).await;
+
+        op.finished().await;
+        assert!(op.unwound());
+        // The page clears the whole list by itself, nothing to untoggle
+        assert!(op.from_callback.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn read_prompt_cancel_and_abort() {
+        let cases = [(ExplorerResult::Cancelled, false), (ExplorerResult::Aborted, true)];
+        for (result, unwound) in cases {
+            let mut op = Running::start(ReadPartitionCallback);
+            op.send(CallbackEvent::PartitionsSelected(vec![boot()])).await;
+            op.explorer_shown().await;
+            op.send(CallbackEvent::ExplorerResult(result.clone())).await;
+
+            op.finished().await;
+            assert_eq!(op.unwound(), unwound, "{:?}", result);
+        }
+    }
+}
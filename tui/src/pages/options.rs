@@ -9,6 +9,8 @@ use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::app::{AppCtx, AppPage};
 use crate::components::{Dropdown, DropdownOption, Stars, ThemedWidgetMut};
+use crate::i18n::{AUTO_LANGUAGE, LANGUAGES, t};
+use crate::notify::NotifyMode;
 use crate::pages::Page;
 use crate::themes::{Theme, load_themes};
 
@@ -71,21 +73,151 @@ impl OptionsPage {
 
         theme_options.sort_by(|a, b| a.label.cmp(&b.label));
 
+        let language_options: Vec<DropdownOption> = std::iter::once(DropdownOption {
+            label: t("options.language.auto").to_string(),
+            value: AUTO_LANGUAGE.to_string(),
+        })
+        .chain(LANGUAGES.iter().map(|lang| DropdownOption {
+            label: lang.name.to_string(),
+            value: lang.code.to_string(),
+        }))
+        .collect();
+
         let ui_section = OptionSection {
             title: "INTERFACE",
-            items: vec![OptionItem {
-                label: "Antumbra Theme",
-                description: "Visual style for Antumbra",
-                widget: OptionWidget::Dropdown(Dropdown::new("Theme", theme_options, 0)),
-                on_change: Box::new(|ctx, val| ctx.set_theme(val)),
-                sync: Box::new(|w, ctx| {
-                    let OptionWidget::Dropdown(d) = w;
-                    d.set_by_value(ctx.theme.id);
-                }),
-            }],
+            items: vec![
+                OptionItem {
+                    label: "Antumbra Theme",
+                    description: "Visual style for Antumbra",
+                    widget: OptionWidget::Dropdown(Dropdown::new("Theme", theme_options, 0)),
+                    on_change: Box::new(|ctx, val| ctx.set_theme(val)),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(ctx.theme.id);
+                    }),
+                },
+                OptionItem {
+                    label: t("options.language"),
+                    description: t("options.language.description"),
+                    widget: OptionWidget::Dropdown(Dropdown::new(
+                        t("options.language"),
+                        language_options,
+                        0,
+                    )),
+                    on_change: Box::new(|ctx, val| ctx.set_language(val)),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(ctx.language());
+                    }),
+                },
+            ],
+        };
+
+        let rate_options: Vec<DropdownOption> = [0u64, 1, 5, 10, 20, 40]
+            .iter()
+            .map(|&mib| DropdownOption {
+                label: if mib == 0 { "Unlimited".to_string() } else { format!("{} MiB/s", mib) },
+                value: (mib * 1024 * 1024).to_string(),
+            })
+            .collect();
+
+        let transfer_section = OptionSection {
+            title: "TRANSFER",
+            items: vec![
+                OptionItem {
+                    label: "Rate Limit",
+                    description: "Avoid starving other devices on the same USB hub",
+                    widget: OptionWidget::Dropdown(Dropdown::new("Rate Limit", rate_options, 0)),
+                    on_change: Box::new(|ctx, val| ctx.set_rate_limit(val.parse().unwrap_or(0))),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(&ctx.rate_limit().unwrap_or(0).to_string());
+                    }),
+                },
+                OptionItem {
+                    label: "Verify Writes",
+                    description: "Read partitions back after writing them, doubling flash time",
+                    widget: OptionWidget::Dropdown(Dropdown::new(
+                        "Verify Writes",
+                        vec![
+                            DropdownOption { label: "Off".to_string(), value: "off".to_string() },
+                            DropdownOption { label: "On".to_string(), value: "on".to_string() },
+                        ],
+                        0,
+                    )),
+                    on_change: Box::new(|ctx, val| ctx.set_verify_writes(val == "on")),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(if ctx.verify_writes() { "on" } else { "off" });
+                    }),
+                },
+            ],
         };
 
-        Self { sections: vec![ui_section], selected_idx: 0, stars: Stars::new(2.0) }
+        let notify_options: Vec<DropdownOption> =
+            [NotifyMode::Off, NotifyMode::Bell, NotifyMode::Desktop]
+                .iter()
+                .map(|mode| DropdownOption {
+                    label: match mode {
+                        NotifyMode::Off => "Off".to_string(),
+                        NotifyMode::Bell => "Terminal Bell".to_string(),
+                        NotifyMode::Desktop => "Desktop Notification".to_string(),
+                    },
+                    value: mode.as_str().to_string(),
+                })
+                .collect();
+
+        let threshold_options: Vec<DropdownOption> = [0u64, 30, 60, 300, 600]
+            .iter()
+            .map(|&secs| DropdownOption {
+                label: match secs {
+                    0 => "Always".to_string(),
+                    s if s < 60 => format!("{} seconds", s),
+                    s => format!("{} minutes", s / 60),
+                },
+                value: secs.to_string(),
+            })
+            .collect();
+
+        let notify_section = OptionSection {
+            title: "NOTIFICATIONS",
+            items: vec![
+                OptionItem {
+                    label: "On Completion",
+                    description: "Notify when a long operation finishes",
+                    widget: OptionWidget::Dropdown(Dropdown::new("Notify", notify_options, 0)),
+                    on_change: Box::new(|ctx, val| {
+                        ctx.set_notify_mode(NotifyMode::from_name(val))
+                    }),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(ctx.notify_mode().as_str());
+                    }),
+                },
+                OptionItem {
+                    label: "Notify After",
+                    description: "Skip notifying for quicker operations",
+                    widget: OptionWidget::Dropdown(Dropdown::new(
+                        "Notify After",
+                        threshold_options,
+                        2,
+                    )),
+                    on_change: Box::new(|ctx, val| {
+                        ctx.set_notify_threshold(val.parse().unwrap_or(60))
+                    }),
+                    sync: Box::new(|w, ctx| {
+                        let OptionWidget::Dropdown(d) = w;
+                        d.set_by_value(&ctx.notify_threshold().as_secs().to_string());
+                    }),
+                },
+            ],
+        };
+
+        Self {
+            sections: vec![ui_section, transfer_section, notify_section],
+            selected_idx: 0,
+            stars: Stars::new(2.0),
+        }
     }
 
     fn total_items(&self) -> usize {
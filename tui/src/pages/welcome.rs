@@ -6,15 +6,16 @@ use std::fs;
 use std::path::Path;
 
 use anyhow::Result;
-use penumbra::da::DAFile;
+use penumbra::api::{DAFile, PortInfo, probe_mtk_port};
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
-#[cfg(target_os = "windows")]
-use ratatui::crossterm::event::KeyEventKind;
 use ratatui::crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style};
 use ratatui::widgets::Paragraph;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, sleep};
 
 use super::LOGO;
 use crate::app::{AppCtx, AppPage};
@@ -56,6 +57,11 @@ pub struct WelcomePage {
     actions: Vec<MenuAction>,
     menu: DescriptionMenu,
     stars: Stars,
+
+    /// Background task looking for an already plugged in device
+    probe: Option<JoinHandle<()>>,
+    probe_rx: Option<watch::Receiver<Option<PortInfo>>>,
+    detected: Option<PortInfo>,
 }
 
 impl Default for WelcomePage {
@@ -107,7 +113,53 @@ impl WelcomePage {
             actions,
             menu: DescriptionMenu::new(items),
             stars: Stars::new(3.0),
+            probe: None,
+            probe_rx: None,
+            detected: None,
+        }
+    }
+
+    /// Polls for a MTK port without opening it, so the device page can still connect to it.
+    fn start_probe(&mut self) {
+        let (tx, rx) = watch::channel(None);
+
+        self.probe = Some(tokio::spawn(async move {
+            while !tx.is_closed() {
+                let port = probe_mtk_port().await;
+                tx.send_if_modified(|current| {
+                    let changed = *current != port;
+                    *current = port;
+                    changed
+                });
+                sleep(Duration::from_millis(1000)).await;
+            }
+        }));
+        self.probe_rx = Some(rx);
+    }
+
+    fn stop_probe(&mut self) {
+        if let Some(probe) = self.probe.take() {
+            probe.abort();
         }
+        self.probe_rx = None;
+        self.detected = None;
+    }
+
+    fn render_detection_banner(&self, f: &mut Frame, area: Rect, ctx: &AppCtx) {
+        let Some(port) = &self.detected else { return };
+
+        let location = port.location.as_deref().unwrap_or(port.name.as_str());
+        let link = port.usb_link.map(|l| format!(" ({})", l)).unwrap_or_default();
+        let banner = Paragraph::new(format!(
+            "⚡ Device detected in {} mode on {}{}    [C] Connect",
+            port.connection_type.as_str(),
+            location,
+            link
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(ctx.theme.success).add_modifier(Modifier::BOLD));
+
+        f.render_widget(banner, area);
     }
 
     fn open_da_loader(&mut self) {
@@ -174,6 +226,29 @@ impl WelcomePage {
 
         CardRow::new(cards, 2).render(buf, area.x, area.width, area.y);
     }
+
+    /// Lists the chips the selected DA has entries for, as many as fit on one line
+    fn render_da_chips(&self, f: &mut Frame, area: Rect, ctx: &AppCtx) {
+        let Some(loader) = ctx.loader() else { return };
+        let chips = loader.file().supported_chips();
+
+        let mut line = String::from("Supports:");
+        for (i, chip) in chips.iter().enumerate() {
+            let label = chip.label();
+            let more = format!(" (+{} more)", chips.len() - i);
+            if line.len() + label.len() + 2 + more.len() > area.width as usize {
+                line.push_str(&more);
+                break;
+            }
+            line.push_str(if i == 0 { " " } else { ", " });
+            line.push_str(&label);
+        }
+
+        let chips_line = Paragraph::new(line)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(ctx.theme.muted));
+        f.render_widget(chips_line, area);
+    }
 }
 
 #[async_trait::async_trait]
@@ -192,10 +267,13 @@ impl Page for WelcomePage {
                 Constraint::Length(12), // Logo
                 Constraint::Min(12),    // Menu
                 Constraint::Length(3),  // Status cards
+                Constraint::Length(1),  // Chips supported by the DA
                 Constraint::Length(1),  // Footer
             ])
             .split(area);
 
+        self.render_detection_banner(f, chunks[0], ctx);
+
         // Logo
         let logo = Paragraph::new(LOGO)
             .alignment(Alignment::Center)
@@ -210,11 +288,12 @@ impl Page for WelcomePage {
         self.menu.render(menu_layout[1], f.buffer_mut(), &ctx.theme);
 
         self.render_status_cards(chunks[3], f.buffer_mut(), ctx);
+        self.render_da_chips(f, chunks[4], ctx);
 
         let footer = Paragraph::new("[↑↓] Navigate    [Enter] Select    [Esc] Back")
             .alignment(Alignment::Center)
             .style(Style::default().fg(ctx.theme.muted));
-        f.render_widget(footer, chunks[4]);
+        f.render_widget(footer, chunks[5]);
 
         if let WelcomeState::Browsing { explorer, callback: _ } = &mut self.state {
             explorer.render_modal(area, f.buffer_mut(), &ctx.theme);
@@ -222,11 +301,6 @@ impl Page for WelcomePage {
     }
 
     async fn handle_input(&mut self, ctx: &mut AppCtx, key: KeyEvent) {
-        #[cfg(target_os = "windows")]
-        if key.kind != KeyEventKind::Press {
-            return;
-        }
-
         match &mut self.state {
             WelcomeState::Browsing { explorer, callback } => match explorer.handle_key(key) {
                 ExplorerResult::Selected(path) => match fs::read(&path) {
@@ -245,13 +319,18 @@ impl Page for WelcomePage {
                     }
                     Err(e) => error_dialog!(ctx, e.to_string()),
                 },
-                ExplorerResult::Cancelled => self.state = WelcomeState::Idle,
+                ExplorerResult::Cancelled | ExplorerResult::Aborted => {
+                    self.state = WelcomeState::Idle
+                }
                 ExplorerResult::Pending => {}
             },
 
             WelcomeState::Idle => match key.code {
                 KeyCode::Up => self.menu.previous(),
                 KeyCode::Down => self.menu.next(),
+                KeyCode::Char('c') | KeyCode::Char('C') if self.detected.is_some() => {
+                    ctx.change_page(AppPage::DevicePage)
+                }
                 KeyCode::Enter => match self.current_action() {
                     Some(MenuAction::SelectDa) => self.open_da_loader(),
                     Some(MenuAction::SelectPreloader) => self.open_preloader(),
@@ -264,4 +343,20 @@ impl Page for WelcomePage {
             },
         }
     }
+
+    async fn on_enter(&mut self, _ctx: &mut AppCtx) {
+        self.start_probe();
+    }
+
+    async fn on_exit(&mut self, _ctx: &mut AppCtx) {
+        self.stop_probe();
+    }
+
+    async fn update(&mut self, _ctx: &mut AppCtx) {
+        if let Some(rx) = &mut self.probe_rx
+            && rx.has_changed().unwrap_or(false)
+        {
+            self.detected = rx.borrow_and_update().clone();
+        }
+    }
 }
@@ -0,0 +1,122 @@
+/*
+    SPDX-License-Identifier: AGPL-3.0-or-later
+    SPDX-FileCopyrightText: 2026 Shomy
+*/
+use std::io::stdout;
+
+use anyhow::Result;
+use futures::FutureExt;
+use log::warn;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+    disable_raw_mode,
+    enable_raw_mode,
+};
+use tokio::signal::unix::{Signal, SignalKind, signal};
+
+/// Handles job control (SIGTSTP/SIGCONT) for the TUI.
+///
+/// Raw mode disables the terminal's own Ctrl+Z handling, so the key is forwarded here as a
+/// suspend request. Before stopping, the terminal is restored so the shell is usable.
+/// Device operations run in their own tasks and are left untouched: they are stopped along
+/// with the process, and carry on after `fg` or `bg`.
+///
+/// When resumed in the background (`bg`), the terminal belongs to the shell, and touching it
+/// would stop the process again (SIGTTOU/SIGTTIN). The TUI then stays hidden, without
+/// reading input, until it's brought back to the foreground.
+pub struct SuspendHandler {
+    tstp: Option<Signal>,
+    cont: Option<Signal>,
+    requested: bool,
+    suspended: bool,
+}
+
+impl SuspendHandler {
+    pub fn new() -> Self {
+        let listen = |kind| {
+            signal(SignalKind::from_raw(kind))
+                .map_err(|e| warn!("Failed to listen for signal {}: {}", kind, e))
+                .ok()
+        };
+
+        Self {
+            tstp: listen(libc::SIGTSTP),
+            cont: listen(libc::SIGCONT),
+            requested: false,
+            suspended: false,
+        }
+    }
+
+    /// Asks for the TUI to be suspended on the next [`SuspendHandler::poll`], e.g. on Ctrl+Z.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Whether the TUI is running in the background and must not draw or read input.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Handles pending signals and suspend requests, without blocking.
+    /// Meant to be called on every iteration of the main loop.
+    pub fn poll(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let tstp = Self::pending(&mut self.tstp);
+        let cont = Self::pending(&mut self.cont);
+
+        if std::mem::take(&mut self.requested) || tstp {
+            self.suspend(terminal)?;
+        } else if cont || self.suspended {
+            // Also covers SIGSTOP sent by something else, and `fg` after `bg`
+            self.resume(terminal)?;
+        }
+
+        Ok(())
+    }
+
+    fn pending(sig: &mut Option<Signal>) -> bool {
+        sig.as_mut().is_some_and(|s| s.recv().now_or_never().flatten().is_some())
+    }
+
+    fn suspend(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        if !self.suspended {
+            terminal.show_cursor()?;
+            disable_raw_mode()?;
+            execute!(stdout(), LeaveAlternateScreen)?;
+        }
+
+        // SIGTSTP is handled here, so stop for real with the one that can't be caught.
+        // This returns once the process gets continued.
+        unsafe {
+            libc::raise(libc::SIGSTOP);
+        }
+
+        self.suspended = true;
+        self.resume(terminal)
+    }
+
+    fn resume(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        if !Self::is_foreground() {
+            self.suspended = true;
+            return Ok(());
+        }
+
+        if self.suspended {
+            enable_raw_mode()?;
+            execute!(stdout(), EnterAlternateScreen)?;
+            terminal.hide_cursor()?;
+            self.suspended = false;
+        }
+
+        // The previous frame is gone from the screen, force a full redraw
+        terminal.clear()?;
+        Ok(())
+    }
+
+    /// Whether the process group owns the terminal, see `tcgetpgrp(3)`.
+    fn is_foreground() -> bool {
+        unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp() }
+    }
+}
@@ -2,6 +2,8 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::collections::HashMap;
+
 use ratatui::style::Color;
 
 use crate::themes::Theme;
@@ -31,6 +33,7 @@ pub fn gruvbox_light() -> Theme {
         success: Color::Rgb(152, 151, 26),
         // #a89984
         muted: Color::Rgb(168, 153, 132),
+        overrides: HashMap::new(),
     }
 }
 
@@ -59,5 +62,6 @@ pub fn gruvbox_dark() -> Theme {
         success: Color::Rgb(152, 151, 26),
         // #a89984
         muted: Color::Rgb(168, 153, 132),
+        overrides: HashMap::new(),
     }
 }
@@ -5,6 +5,7 @@
 use std::collections::HashMap;
 
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 
 // Themes
 mod gruvbox;
@@ -13,6 +14,27 @@ mod rose_pine;
 pub type ThemeConstructor = fn() -> Theme;
 pub type ThemeRegistry = HashMap<&'static str, ThemeConstructor>;
 
+/// Well known component styles that a theme can refine.
+/// Each slot falls back to one of the base theme colors when not overridden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleSlot {
+    /// Border of the focused panel (default: accent)
+    BorderFocused,
+    /// Border of unfocused panels (default: text)
+    BorderUnfocused,
+    /// Selected item in lists (default: accent)
+    ListSelection,
+    /// Selected item background in dropdowns (default: highlight)
+    DropdownSelection,
+    /// Filled part of the progress bar (default: accent)
+    ProgressFilled,
+    /// Empty part of the progress bar (default: accent)
+    ProgressEmpty,
+    /// Dialog background (default: highlight)
+    DialogBackground,
+}
+
 pub struct Theme {
     pub name: &'static str,
     pub id: &'static str,
@@ -27,6 +49,8 @@ pub struct Theme {
     pub info: Color,
     pub success: Color,
     pub muted: Color,
+    /// Per-component refinements, see [`Theme::color`]
+    pub overrides: HashMap<StyleSlot, Color>,
 }
 
 impl Default for Theme {
@@ -45,8 +69,34 @@ impl Default for Theme {
             info: Color::LightBlue,
             success: Color::LightGreen,
             muted: Color::DarkGray,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the color for a component slot, falling back to the base colors
+    pub fn color(&self, slot: StyleSlot) -> Color {
+        if let Some(color) = self.overrides.get(&slot) {
+            return *color;
+        }
+
+        match slot {
+            StyleSlot::BorderFocused => self.accent,
+            StyleSlot::BorderUnfocused => self.text,
+            StyleSlot::ListSelection => self.accent,
+            StyleSlot::DropdownSelection => self.highlight,
+            StyleSlot::ProgressFilled => self.accent,
+            StyleSlot::ProgressEmpty => self.accent,
+            StyleSlot::DialogBackground => self.highlight,
         }
     }
+
+    /// Applies overrides on top of the current ones
+    pub fn with_overrides(mut self, overrides: HashMap<StyleSlot, Color>) -> Self {
+        self.overrides.extend(overrides);
+        self
+    }
 }
 
 pub fn load_themes() -> ThemeRegistry {
@@ -2,6 +2,8 @@
     SPDX-License-Identifier: AGPL-3.0-or-later
     SPDX-FileCopyrightText: 2025 Shomy
 */
+use std::collections::HashMap;
+
 use ratatui::style::Color;
 
 use crate::themes::Theme;
@@ -31,5 +33,6 @@ pub fn rose_pine_moon() -> Theme {
         success: Color::Rgb(196, 167, 231),
         // #6e6a86
         muted: Color::Rgb(110, 106, 134),
+        overrides: HashMap::new(),
     }
 }